@@ -0,0 +1,773 @@
+//! Archives for `compress_to_zip` and `extract_archive`. Zip (with Zip64 for
+//! large files and archives), tar and .7z go through the `zip`, `tar` and
+//! `sevenz-rust` crates; naming, path safety, size limits, cancellation and
+//! progress are handled here.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use walkdir::WalkDir;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+use crate::long_path;
+use crate::search_cancel::{is_cancelled, SearchCancelToken};
+use crate::AppResult;
+
+pub(crate) const ARCHIVE_CANCELLED: &str = "Archive cancelled.";
+
+const COPY_CHUNK: usize = 256 * 1024;
+/// Largest GNU long-name or pax header read into memory. Real ones are a few
+/// hundred bytes; a bigger one is a crafted archive.
+const MAX_TAR_EXTENDED_HEADER: u64 = 1 << 20;
+/// Deflate members above this size must compress less than
+/// `MAX_INFLATE_RATIO` to one, which stops zip bombs filling the disk.
+const INFLATE_RATIO_FLOOR: u64 = 16 << 20;
+const MAX_INFLATE_RATIO: u64 = 250;
+
+fn io_err(e: io::Error) -> String {
+    e.to_string()
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's
+/// days-from-civil).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// MS-DOS date/time of a Unix timestamp, in UTC. DOS dates start in 1980.
+fn dos_datetime(epoch_secs: i64) -> (u16, u16) {
+    let days = epoch_secs.div_euclid(86_400);
+    let secs = epoch_secs.rem_euclid(86_400);
+    // Civil-from-days (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    let year = year.min(2107);
+    let time = ((secs / 3600) << 11) | (((secs % 3600) / 60) << 5) | ((secs % 60) / 2);
+    let date = ((year - 1980) << 9) | (month << 5) | day;
+    (time as u16, date as u16)
+}
+
+fn metadata_mtime(meta: &fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|m| m.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(unix)]
+fn unix_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode()
+}
+
+#[cfg(not(unix))]
+fn unix_mode(meta: &fs::Metadata) -> u32 {
+    if meta.is_dir() {
+        0o40755
+    } else if meta.permissions().readonly() {
+        0o100444
+    } else {
+        0o100644
+    }
+}
+
+/// `dir/<stem><suffix>`, or `<stem> 2<suffix>`, `<stem> 3<suffix>`, …
+/// when taken.
+fn unique_child(dir: &Path, stem: &str, suffix: &str) -> PathBuf {
+    let mut candidate = dir.join(format!("{stem}{suffix}"));
+    let mut n = 2;
+    while long_path::symlink_metadata(&candidate).is_ok() {
+        candidate = dir.join(format!("{stem} {n}{suffix}"));
+        n += 1;
+    }
+    candidate
+}
+
+/// `dir/name.zip`, or `dir/name 2.zip`, `dir/name 3.zip`, … when taken.
+pub(crate) fn unique_archive_path(dir: &Path, stem: &str) -> PathBuf {
+    unique_child(dir, stem, ".zip")
+}
+
+/// Number of files and folders `zip_paths` will add, for progress totals.
+pub(crate) fn count_entries(sources: &[PathBuf]) -> u64 {
+    sources
+        .iter()
+        .map(|s| {
+            WalkDir::new(s)
+                .follow_links(false)
+                .into_iter()
+                .flatten()
+                .filter(|e| !e.file_type().is_symlink())
+                .count() as u64
+        })
+        .sum()
+}
+
+/// Deflate `reader` into the member `zip` has just started.
+fn copy_member(
+    zip: &mut impl Write,
+    mut reader: impl Read,
+    cancel: Option<&SearchCancelToken>,
+) -> AppResult<()> {
+    let mut buf = vec![0u8; COPY_CHUNK];
+    loop {
+        if is_cancelled(cancel) {
+            return Err(ARCHIVE_CANCELLED.to_string());
+        }
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        zip.write_all(&buf[..n]).map_err(io_err)?;
+    }
+}
+
+/// Write every file and folder under `sources` into a new zip at `dest`,
+/// named relative to each source's parent. Symlinks are skipped. `progress`
+/// gets (entries written, path) after each entry. A failed or cancelled
+/// archive is deleted again.
+pub(crate) fn zip_paths(
+    sources: &[PathBuf],
+    dest: &Path,
+    cancel: Option<&SearchCancelToken>,
+    mut progress: impl FnMut(u64, &Path),
+) -> AppResult<u64> {
+    let file = File::options()
+        .write(true)
+        .create_new(true)
+        .open(dest)
+        .map_err(io_err)?;
+    let result = (|| {
+        let mut zip = zip::ZipWriter::new(BufWriter::new(file));
+        let mut written = 0u64;
+        for source in sources {
+            let base = source.parent().unwrap_or(Path::new(""));
+            for entry in WalkDir::new(source).follow_links(false) {
+                if is_cancelled(cancel) {
+                    return Err(ARCHIVE_CANCELLED.to_string());
+                }
+                let entry = entry.map_err(|e| e.to_string())?;
+                if entry.file_type().is_symlink() || entry.path() == dest {
+                    continue;
+                }
+                let relative = entry.path().strip_prefix(base).map_err(|e| e.to_string())?;
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let meta = entry.metadata().map_err(|e| e.to_string())?;
+                let (time, date) = dos_datetime(metadata_mtime(&meta));
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Deflated)
+                    .last_modified_time(zip::DateTime::try_from_msdos(date, time).unwrap_or_default())
+                    .unix_permissions(unix_mode(&meta))
+                    // Zip64 sizes have to be reserved before the data is written.
+                    .large_file(meta.len() >= u64::from(u32::MAX));
+                if meta.is_dir() {
+                    zip.add_directory(name, options).map_err(|e| e.to_string())?;
+                } else {
+                    let reader = File::open(entry.path()).map_err(io_err)?;
+                    zip.start_file(name, options).map_err(|e| e.to_string())?;
+                    copy_member(&mut zip, reader, cancel)?;
+                }
+                written += 1;
+                progress(written, entry.path());
+            }
+        }
+        zip.finish()
+            .map_err(|e| e.to_string())?
+            .into_inner()
+            .map_err(|e| e.to_string())?;
+        Ok(written)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(dest);
+    }
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+}
+
+impl ArchiveFormat {
+    /// Format and name stem from the file name: "Photos.tar.gz" is
+    /// (TarGz, "Photos").
+    pub(crate) fn detect(path: &Path) -> Option<(ArchiveFormat, String)> {
+        let name = path.file_name()?.to_string_lossy().to_string();
+        let lower = name.to_ascii_lowercase();
+        let (format, suffix_len) = [
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar", ArchiveFormat::Tar),
+            (".zip", ArchiveFormat::Zip),
+            (".7z", ArchiveFormat::SevenZip),
+        ]
+        .into_iter()
+        .find(|(suffix, _)| lower.ends_with(suffix))
+        .map(|(suffix, format)| (format, suffix.len()))?;
+        let stem = &name[..name.len() - suffix_len];
+        let stem = if stem.is_empty() { "Archive" } else { stem };
+        Some((format, stem.to_string()))
+    }
+}
+
+/// Turn an archive member name into a relative path, refusing anything that
+/// would land outside the destination ("../x", "/etc/x", "C:\x").
+fn entry_relative_path(name: &str) -> AppResult<PathBuf> {
+    let mut relative = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        if part.is_empty() || part == "." {
+            continue;
+        }
+        let mut components = Path::new(part).components();
+        match (components.next(), components.next()) {
+            (Some(std::path::Component::Normal(c)), None) => relative.push(c),
+            _ => return Err(format!("Unsafe path in archive: {name}")),
+        }
+    }
+    Ok(relative)
+}
+
+/// Stream `reader` into `target` (replacing a duplicate entry written
+/// earlier), then apply mtime and Unix mode.
+fn write_entry_file(
+    target: &Path,
+    mut reader: impl Read,
+    mtime: Option<i64>,
+    mode: Option<u32>,
+    cancel: Option<&SearchCancelToken>,
+) -> AppResult<()> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(io_err)?;
+    }
+    let mut file = File::create(target).map_err(io_err)?;
+    let mut buf = vec![0u8; COPY_CHUNK];
+    loop {
+        if is_cancelled(cancel) {
+            return Err(ARCHIVE_CANCELLED.to_string());
+        }
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        file.write_all(&buf[..n]).map_err(io_err)?;
+    }
+    if let Some(mtime) = mtime.and_then(|t| u64::try_from(t).ok()) {
+        let _ = file.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime));
+    }
+    set_mode(target, mode);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(mode) = mode.map(|m| m & 0o777).filter(|&m| m != 0) {
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+    }
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: Option<u32>) {}
+
+/// Seconds since the epoch of a zip member's MS-DOS timestamp, read as UTC.
+fn zip_mtime(dt: zip::DateTime) -> i64 {
+    let days = days_from_civil(
+        i64::from(dt.year()),
+        i64::from(dt.month()),
+        i64::from(dt.day()),
+    );
+    days * 86_400
+        + i64::from(dt.hour()) * 3600
+        + i64::from(dt.minute()) * 60
+        + i64::from(dt.second())
+}
+
+/// After `write_entry_file` stopped at the declared size, one more read
+/// either finds the end (where the decoder checks the CRC) or finds data the
+/// header didn't declare.
+fn check_member_end(name: &str, mut member: impl Read) -> AppResult<()> {
+    let mut probe = [0u8; 1];
+    match member.read(&mut probe) {
+        Ok(0) => Ok(()),
+        Ok(_) => Err(format!("{name} is corrupt (longer than its declared size).")),
+        Err(e) => Err(format!("{name} is corrupt ({e}).")),
+    }
+}
+
+/// Zip and Zip64 through the `zip` crate, which reads the central directory
+/// entry by entry rather than trusting its declared size.
+fn extract_zip(
+    archive: &Path,
+    dest: &Path,
+    cancel: Option<&SearchCancelToken>,
+    progress: &mut dyn FnMut(u64, Option<u64>, &Path),
+) -> AppResult<u64> {
+    let file = File::open(archive).map_err(io_err)?;
+    let mut zip = zip::ZipArchive::new(BufReader::new(file)).map_err(|e| match e {
+        ZipError::InvalidArchive(_) => "Not a zip archive.".to_string(),
+        e => e.to_string(),
+    })?;
+    let count = zip.len() as u64;
+    for index in 0..zip.len() {
+        if is_cancelled(cancel) {
+            return Err(ARCHIVE_CANCELLED.to_string());
+        }
+        let name = zip.name_for_index(index).unwrap_or_default().to_string();
+        let mut member = match zip.by_index(index) {
+            Ok(member) => member,
+            Err(ZipError::UnsupportedArchive(msg)) if msg == ZipError::PASSWORD_REQUIRED => {
+                return Err(format!("{name} is encrypted, which is not supported."));
+            }
+            Err(e) => return Err(e.to_string()),
+        };
+        let relative = entry_relative_path(&name)?;
+        let done = index as u64 + 1;
+        if member.is_dir() {
+            fs::create_dir_all(dest.join(&relative)).map_err(io_err)?;
+            progress(done, Some(count), &relative);
+            continue;
+        }
+        if member.is_symlink() {
+            progress(done, Some(count), &relative);
+            continue;
+        }
+
+        // Never write more than the central directory declares: data past it
+        // is reported as corruption instead of filling the disk.
+        let declared = member.size();
+        check_inflate_ratio(&name, member.compressed_size(), declared)?;
+        let mtime = member.last_modified().map(zip_mtime);
+        let mode = member.unix_mode();
+        write_entry_file(
+            &dest.join(&relative),
+            (&mut member).take(declared),
+            mtime,
+            mode,
+            cancel,
+        )?;
+        check_member_end(&name, &mut member)?;
+        progress(done, Some(count), &relative);
+    }
+    Ok(count)
+}
+
+/// Reject a member whose declared size is implausibly large for its
+/// compressed size.
+fn check_inflate_ratio(name: &str, compressed: u64, uncompressed: u64) -> AppResult<()> {
+    if uncompressed > INFLATE_RATIO_FLOOR
+        && uncompressed > compressed.saturating_mul(MAX_INFLATE_RATIO)
+    {
+        return Err(format!(
+            "{name} expands more than {MAX_INFLATE_RATIO}x, which looks like a zip bomb."
+        ));
+    }
+    Ok(())
+}
+
+/// ustar/GNU/pax tar stream through the `tar` crate. Entries are read raw so
+/// GNU long-name and pax headers are bounded here: the crate reads them into
+/// memory whole, whatever size the header claims. Links and device files are
+/// skipped.
+fn extract_tar(
+    reader: impl Read,
+    dest: &Path,
+    cancel: Option<&SearchCancelToken>,
+    progress: &mut dyn FnMut(u64, Option<u64>, &Path),
+) -> AppResult<u64> {
+    let mut archive = tar::Archive::new(reader);
+    let mut done = 0u64;
+    let mut long_name: Option<String> = None;
+    for entry in archive.entries().map_err(io_err)?.raw(true) {
+        if is_cancelled(cancel) {
+            return Err(ARCHIVE_CANCELLED.to_string());
+        }
+        let mut entry = entry.map_err(io_err)?;
+        let entry_type = entry.header().entry_type();
+
+        if entry_type.is_gnu_longname() || entry_type.is_pax_local_extensions() {
+            if entry.size() > MAX_TAR_EXTENDED_HEADER {
+                return Err("Corrupt tar: oversized long-name or pax header.".to_string());
+            }
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut data).map_err(io_err)?;
+            long_name = if entry_type.is_gnu_longname() {
+                let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+                Some(String::from_utf8_lossy(&data[..end]).to_string())
+            } else {
+                tar::PaxExtensions::new(&data)
+                    .flatten()
+                    .find(|ext| ext.key() == Ok("path"))
+                    .and_then(|ext| ext.value().ok().map(str::to_string))
+                    .or(long_name)
+            };
+            continue;
+        }
+
+        let name = long_name
+            .take()
+            .unwrap_or_else(|| String::from_utf8_lossy(&entry.path_bytes()).to_string());
+        let relative = entry_relative_path(&name)?;
+        let mode = entry.header().mode().ok();
+        let mtime = entry.header().mtime().ok().map(|t| t as i64);
+        if entry_type.is_dir() {
+            fs::create_dir_all(dest.join(&relative)).map_err(io_err)?;
+        } else if matches!(entry_type, tar::EntryType::Regular | tar::EntryType::Continuous)
+            && !relative.as_os_str().is_empty()
+        {
+            let size = entry.size();
+            write_entry_file(&dest.join(&relative), (&mut entry).take(size), mtime, mode, cancel)?;
+        } else {
+            continue;
+        }
+        done += 1;
+        progress(done, None, &relative);
+    }
+    Ok(done)
+}
+
+/// 100 ns intervals between 1601-01-01 (FILETIME) and 1970-01-01.
+const FILETIME_UNIX_DIFF: u64 = 116_444_736_000_000_000;
+
+/// 7z through `sevenz-rust`. Solid blocks decode front to back, so members
+/// arrive in archive order and cancellation stops at the next member.
+fn extract_7z(
+    archive: &Path,
+    dest: &Path,
+    cancel: Option<&SearchCancelToken>,
+    progress: &mut dyn FnMut(u64, Option<u64>, &Path),
+) -> AppResult<u64> {
+    let mut reader = sevenz_rust::SevenZReader::open(archive, sevenz_rust::Password::empty())
+        .map_err(|e| match e {
+            sevenz_rust::Error::PasswordRequired => {
+                "Encrypted .7z archives are not supported.".to_string()
+            }
+            e => format!("Not a readable .7z archive ({e})."),
+        })?;
+    let total = reader.archive().files.len() as u64;
+    let mut done = 0u64;
+    // The callback can only return the crate's error type; keep ours here.
+    let mut failure: Option<String> = None;
+    let result = reader.for_each_entries(|entry, data| {
+        let outcome = (|| {
+            if is_cancelled(cancel) {
+                return Err(ARCHIVE_CANCELLED.to_string());
+            }
+            let relative = entry_relative_path(entry.name())?;
+            if entry.is_directory() {
+                fs::create_dir_all(dest.join(&relative)).map_err(io_err)?;
+            } else if !entry.is_anti_item() && !relative.as_os_str().is_empty() {
+                let mtime = entry.has_last_modified_date.then(|| {
+                    (entry.last_modified_date().to_raw().saturating_sub(FILETIME_UNIX_DIFF)
+                        / 10_000_000) as i64
+                });
+                write_entry_file(&dest.join(&relative), data.take(entry.size()), mtime, None, cancel)?;
+                check_member_end(entry.name(), data)?;
+            }
+            done += 1;
+            progress(done, Some(total), &relative);
+            Ok(())
+        })();
+        match outcome {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                failure = Some(e);
+                Err(sevenz_rust::Error::other("stopped"))
+            }
+        }
+    });
+    match (failure, result) {
+        (Some(e), _) => Err(e),
+        (None, Err(e)) => Err(format!("{} is corrupt ({e}).", archive.display())),
+        (None, Ok(())) => Ok(done),
+    }
+}
+
+/// Extract `archive` next to itself. Members go into a scratch folder first;
+/// then a lone top-level item is moved up beside the archive, and anything
+/// else becomes a folder named after the archive, numbered when the name is
+/// taken ("photos.zip" → "photos 2"). `progress` gets (entries done, total
+/// when known, member path). Returns the new path and the entry count; a
+/// failed or cancelled extraction leaves nothing behind.
+pub(crate) fn extract_archive(
+    archive: &Path,
+    cancel: Option<&SearchCancelToken>,
+    mut progress: impl FnMut(u64, Option<u64>, &Path),
+) -> AppResult<(PathBuf, u64)> {
+    let (format, stem) = ArchiveFormat::detect(archive)
+        .ok_or_else(|| format!("Not a supported archive: {}", archive.display()))?;
+    let dir = archive
+        .parent()
+        .ok_or_else(|| format!("Cannot extract {}", archive.display()))?;
+    let scratch = unique_child(dir, &format!(".{stem}"), ".extracting");
+    fs::create_dir(&scratch).map_err(io_err)?;
+
+    let result = (|| {
+        let entries = match format {
+            ArchiveFormat::Zip => extract_zip(archive, &scratch, cancel, &mut progress)?,
+            ArchiveFormat::Tar => {
+                let file = File::open(archive).map_err(io_err)?;
+                extract_tar(BufReader::new(file), &scratch, cancel, &mut progress)?
+            }
+            ArchiveFormat::TarGz => {
+                let file = File::open(archive).map_err(io_err)?;
+                extract_tar(
+                    GzDecoder::new(BufReader::new(file)),
+                    &scratch,
+                    cancel,
+                    &mut progress,
+                )?
+            }
+            ArchiveFormat::SevenZip => extract_7z(archive, &scratch, cancel, &mut progress)?,
+        };
+
+        let top: Vec<PathBuf> = fs::read_dir(&scratch)
+            .map_err(io_err)?
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        let target = match top.as_slice() {
+            [single] => {
+                let name = single
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                let (stem, suffix) = match name.rfind('.').filter(|&i| i > 0 && single.is_file()) {
+                    Some(i) => name.split_at(i),
+                    None => (name.as_str(), ""),
+                };
+                let target = unique_child(dir, stem, suffix);
+                fs::rename(single, &target).map_err(io_err)?;
+                fs::remove_dir(&scratch).map_err(io_err)?;
+                target
+            }
+            _ => {
+                let target = unique_child(dir, &stem, "");
+                fs::rename(&scratch, &target).map_err(io_err)?;
+                target
+            }
+        };
+        Ok((target, entries))
+    })();
+    if result.is_err() {
+        let _ = fs::remove_dir_all(&scratch);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dos_datetime_encodes_utc_fields() {
+        // 2024-02-29 13:45:30 UTC
+        let (time, date) = dos_datetime(1_709_214_330);
+        assert_eq!((date >> 9) + 1980, 2024);
+        assert_eq!((date >> 5) & 0xF, 2);
+        assert_eq!(date & 0x1F, 29);
+        assert_eq!(
+            (time >> 11, (time >> 5) & 0x3F, (time & 0x1F) * 2),
+            (13, 45, 30)
+        );
+        assert_eq!(dos_datetime(0), (0, (1 << 5) | 1));
+    }
+
+    #[test]
+    fn zips_tree_relative_to_each_source_parent() {
+        let root = crate::temp_case_dir("archive_zip");
+        let src = root.join("photos");
+        fs::create_dir_all(src.join("raw")).unwrap();
+        fs::write(src.join("a.txt"), "hello hello hello hello").unwrap();
+        fs::write(src.join("raw").join("b.bin"), [1u8, 2, 3]).unwrap();
+
+        let dest = unique_archive_path(&root, "photos");
+        assert_eq!(dest, root.join("photos.zip"));
+        let mut seen = Vec::new();
+        let written =
+            zip_paths(std::slice::from_ref(&src), &dest, None, |n, _| seen.push(n)).unwrap();
+        assert_eq!(written, 4);
+        assert_eq!(seen, vec![1, 2, 3, 4]);
+        assert_eq!(count_entries(std::slice::from_ref(&src)), 4);
+        assert_eq!(
+            unique_archive_path(&root, "photos"),
+            root.join("photos 2.zip")
+        );
+
+        // Read it back and re-inflate each file.
+        let mut zip = zip::ZipArchive::new(File::open(&dest).unwrap()).unwrap();
+        let mut names = Vec::new();
+        for index in 0..zip.len() {
+            let mut member = zip.by_index(index).unwrap();
+            assert_eq!(member.last_modified().map(|t| t.year() >= 1980), Some(true));
+            if !member.is_dir() {
+                assert_eq!(member.compression(), CompressionMethod::Deflated);
+                let mut inflated = Vec::new();
+                member.read_to_end(&mut inflated).unwrap();
+                assert!(!inflated.is_empty(), "{}", member.name());
+            }
+            names.push(member.name().to_string());
+        }
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["photos/", "photos/a.txt", "photos/raw/", "photos/raw/b.bin"]
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn extracts_zips_and_rejects_a_lying_central_directory() {
+        let root = crate::temp_case_dir("archive_unzip");
+        let src = root.join("album");
+        fs::create_dir_all(src.join("raw")).unwrap();
+        fs::write(src.join("a.txt"), "hello hello hello hello").unwrap();
+        fs::write(src.join("raw").join("b.bin"), [1u8, 2, 3]).unwrap();
+        let archive = root.join("album.zip");
+        zip_paths(std::slice::from_ref(&src), &archive, None, |_, _| {}).unwrap();
+        fs::remove_dir_all(&src).unwrap();
+
+        let mut seen = Vec::new();
+        let (target, entries) =
+            extract_archive(&archive, None, |done, total, _| seen.push((done, total))).unwrap();
+        assert_eq!(target, root.join("album"));
+        assert_eq!(entries, 4);
+        assert_eq!(seen.last(), Some(&(4, Some(4))));
+        assert_eq!(
+            fs::read(target.join("a.txt")).unwrap(),
+            b"hello hello hello hello"
+        );
+        assert_eq!(fs::read(target.join("raw").join("b.bin")).unwrap(), [1, 2, 3]);
+
+        // End record claiming one entry in a ~4 GiB directory.
+        let mut eocd = 0x0605_4b50u32.to_le_bytes().to_vec();
+        eocd.extend_from_slice(&[0, 0, 0, 0, 1, 0, 1, 0]);
+        eocd.extend_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        eocd.extend_from_slice(&0u32.to_le_bytes());
+        eocd.extend_from_slice(&0u16.to_le_bytes());
+        let lying = root.join("lying.zip");
+        fs::write(&lying, eocd).unwrap();
+        assert!(extract_archive(&lying, None, |_, _, _| {}).is_err());
+        assert!(!root.join("lying").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn inflate_ratio_caps_only_large_members() {
+        assert!(check_inflate_ratio("small", 1, INFLATE_RATIO_FLOOR).is_ok());
+        assert!(check_inflate_ratio("text", 1 << 20, 100 << 20).is_ok());
+        assert!(check_inflate_ratio("bomb", 64 << 10, 4 << 30).is_err());
+    }
+
+    fn tar_member(out: &mut Vec<u8>, name: &str, type_flag: u8, data: &[u8]) {
+        let mut header = [0u8; 512];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", data.len()).as_bytes());
+        header[136..147].copy_from_slice(b"14707072352");
+        header[156] = type_flag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].fill(b' ');
+        let checksum: u32 = header.iter().map(|&b| u32::from(b)).sum();
+        header[148..155].copy_from_slice(format!("{checksum:06o}\0").as_bytes());
+        out.extend_from_slice(&header);
+        out.extend_from_slice(data);
+        out.resize(out.len().div_ceil(512) * 512, 0);
+    }
+
+    #[test]
+    fn extracts_tarballs_beside_the_archive_and_rejects_escaping_paths() {
+        let root = crate::temp_case_dir("archive_tar");
+        fs::create_dir_all(&root).unwrap();
+        let long_name = format!("docs/{}.txt", "n".repeat(120));
+        let mut tar = Vec::new();
+        tar_member(&mut tar, "docs/", b'5', b"");
+        tar_member(&mut tar, "docs/readme.md", b'0', b"# hi");
+        tar_member(&mut tar, "././@LongLink", b'L', long_name.as_bytes());
+        tar_member(&mut tar, "docs/truncated", b'0', b"long");
+        tar_member(&mut tar, "docs/link", b'2', b"");
+        tar_member(&mut tar, "notes.txt", b'0', b"notes");
+        tar.extend_from_slice(&[0u8; 1024]);
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&tar).unwrap();
+        let archive = root.join("bundle.tar.gz");
+        fs::write(&archive, gz.finish().unwrap()).unwrap();
+
+        let mut last = 0;
+        let (target, entries) = extract_archive(&archive, None, |done, total, _| {
+            assert_eq!(total, None);
+            last = done;
+        })
+        .unwrap();
+        assert_eq!(target, root.join("bundle"));
+        assert_eq!((entries, last), (4, 4));
+        assert_eq!(
+            fs::read(target.join("docs").join("readme.md")).unwrap(),
+            b"# hi"
+        );
+        assert_eq!(fs::read(target.join(&long_name)).unwrap(), b"long");
+        assert!(!target.join("docs").join("link").exists());
+        assert_eq!(
+            fs::read_dir(&root).unwrap().count(),
+            2,
+            "no scratch folder left"
+        );
+
+        let mut evil = Vec::new();
+        tar_member(&mut evil, "../escaped.txt", b'0', b"x");
+        let evil_path = root.join("evil.tar");
+        fs::write(&evil_path, evil).unwrap();
+        assert!(extract_archive(&evil_path, None, |_, _, _| {}).is_err());
+        assert!(!root.join("escaped.txt").exists());
+
+        let mut bomb = Vec::new();
+        let huge_name = vec![b'n'; MAX_TAR_EXTENDED_HEADER as usize + 1];
+        tar_member(&mut bomb, "././@LongLink", b'L', &huge_name);
+        let bomb_path = root.join("bomb.tar");
+        fs::write(&bomb_path, bomb).unwrap();
+        assert!(extract_archive(&bomb_path, None, |_, _, _| {})
+            .unwrap_err()
+            .contains("oversized"));
+        assert_eq!(
+            ArchiveFormat::detect(Path::new("a/Photos.TGZ")),
+            Some((ArchiveFormat::TarGz, "Photos".to_string()))
+        );
+        assert_eq!(ArchiveFormat::detect(Path::new("a/notes.txt")), None);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,205 @@
+//! Launch at login, so the watcher keeps the index warm from boot: a
+//! LaunchAgent on macOS, an `HKCU\…\Run` value on Windows, an XDG autostart
+//! entry on Linux. Every entry starts this executable with
+//! [`AUTOSTART_FLAG`], which opens straight to the tray instead of the window.
+
+use std::path::{Path, PathBuf};
+
+use crate::AppResult;
+
+pub(crate) const AUTOSTART_FLAG: &str = "--autostart";
+
+pub(crate) fn launched_at_login() -> bool {
+    std::env::args().skip(1).any(|a| a == AUTOSTART_FLAG)
+}
+
+fn current_exe() -> AppResult<PathBuf> {
+    std::env::current_exe().map_err(|e| format!("Cannot locate the app executable: {e}"))
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// LaunchAgent plist for `label` running `exe` at login.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent_plist(label: &str, exe: &Path) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{AUTOSTART_FLAG}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Interactive</string>
+</dict>
+</plist>
+"#,
+        xml_escape(label),
+        xml_escape(&exe.to_string_lossy())
+    )
+}
+
+/// `exe` quoted for a `.desktop` file's `Exec` key, per the Desktop Entry spec.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+pub(crate) fn desktop_exec(exe: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for ch in exe.to_string_lossy().chars() {
+        if matches!(ch, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// XDG autostart `.desktop` entry.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn desktop_entry(name: &str, exe: &Path) -> String {
+    format!(
+        "[Desktop Entry]\nType=Application\nName={name}\nExec={} {AUTOSTART_FLAG}\n\
+         X-GNOME-Autostart-enabled=true\nNoDisplay=true\n",
+        desktop_exec(exe)
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn entry_path(identifier: &str) -> PathBuf {
+    crate::resolve_home_dir()
+        .join("Library/LaunchAgents")
+        .join(format!("{identifier}.plist"))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn entry_path(identifier: &str) -> PathBuf {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| crate::resolve_home_dir().join(".config"));
+    config
+        .join("autostart")
+        .join(format!("{identifier}.desktop"))
+}
+
+#[cfg(target_os = "macos")]
+fn entry_contents(identifier: &str, _name: &str, exe: &Path) -> String {
+    launch_agent_plist(identifier, exe)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn entry_contents(_identifier: &str, name: &str, exe: &Path) -> String {
+    desktop_entry(name, exe)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn is_enabled(identifier: &str) -> bool {
+    entry_path(identifier).is_file()
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn set_enabled(identifier: &str, name: &str, enabled: bool) -> AppResult<()> {
+    let path = entry_path(identifier);
+    if !enabled {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+            _ => Ok(()),
+        };
+    }
+    let contents = entry_contents(identifier, name, &current_exe()?);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(&path, contents).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "windows")]
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// The Run value is named after the bundle identifier, not "Everything",
+/// so it can't collide with voidtools Everything's own entry.
+#[cfg(target_os = "windows")]
+pub(crate) fn is_enabled(identifier: &str) -> bool {
+    use windows::core::HSTRING;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &HSTRING::from(RUN_KEY),
+            &HSTRING::from(identifier),
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            None,
+        )
+    }
+    .is_ok()
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn set_enabled(identifier: &str, _name: &str, enabled: bool) -> AppResult<()> {
+    use windows::core::HSTRING;
+    use windows::Win32::Foundation::ERROR_FILE_NOT_FOUND;
+    use windows::Win32::System::Registry::{
+        RegDeleteKeyValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ,
+    };
+
+    let key = HSTRING::from(RUN_KEY);
+    let value_name = HSTRING::from(identifier);
+    if !enabled {
+        let err = unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, &key, &value_name) };
+        if err == ERROR_FILE_NOT_FOUND {
+            return Ok(());
+        }
+        return err.ok().map_err(|e| e.to_string());
+    }
+    let command = format!("\"{}\" {AUTOSTART_FLAG}", current_exe()?.display());
+    let data: Vec<u16> = command.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        RegSetKeyValueW(
+            HKEY_CURRENT_USER,
+            &key,
+            &value_name,
+            REG_SZ.0,
+            Some(data.as_ptr().cast()),
+            (data.len() * std::mem::size_of::<u16>()) as u32,
+        )
+    }
+    .ok()
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_quote_the_executable() {
+        let plist = launch_agent_plist(
+            "com.everything.app",
+            Path::new("/Applications/A & B.app/Contents/MacOS/everything"),
+        );
+        assert!(plist.contains("<string>com.everything.app</string>"));
+        assert!(plist
+            .contains("<string>/Applications/A &amp; B.app/Contents/MacOS/everything</string>"));
+        assert!(plist.contains("<string>--autostart</string>"));
+
+        let desktop = desktop_entry("Everything", Path::new("/opt/my $apps/everything"));
+        assert!(desktop.contains("Exec=\"/opt/my \\$apps/everything\" --autostart\n"));
+        assert!(desktop.starts_with("[Desktop Entry]\nType=Application\nName=Everything\n"));
+    }
+}
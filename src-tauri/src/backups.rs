@@ -0,0 +1,199 @@
+//! Backup and copy naming patterns — "report (1).pdf", "report copy.pdf",
+//! "report - Copy.pdf", "~$report.docx" — recognized from the name alone, no
+//! hashing. Copies rank below their originals in name relevance, and the
+//! probable-duplicates report groups them by the original they came from.
+
+use std::collections::HashMap;
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::{row_to_entry, AppResult, EntryDto};
+
+/// Copies that share a directory and an original name.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroupDto {
+    pub dir: String,
+    pub base_name: String,
+    /// Path of the original when it still sits next to its copies.
+    pub original: Option<String>,
+    pub copies: Vec<EntryDto>,
+}
+
+/// Strip a trailing " (N)" (browser downloads, Windows copies).
+fn strip_counter(stem: &str) -> Option<&str> {
+    let rest = stem.strip_suffix(')')?;
+    let open = rest.rfind(" (")?;
+    let digits = &rest[open + 2..];
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(&rest[..open])
+}
+
+/// Strip a trailing " copy", " copy N" (Finder) or " - Copy" (Explorer).
+fn strip_copy_word(stem: &str) -> Option<&str> {
+    let lower = stem.to_ascii_lowercase();
+    let without_number = match lower.rfind(' ') {
+        Some(pos) if pos + 1 < lower.len() && lower[pos + 1..].bytes().all(|b| b.is_ascii_digit()) => pos,
+        _ => lower.len(),
+    };
+    let head = &lower[..without_number];
+    for suffix in [" - copy", " copy"] {
+        if let Some(kept) = head.strip_suffix(suffix) {
+            return Some(&stem[..kept.len()]);
+        }
+    }
+    None
+}
+
+/// The original name a backup copy was derived from, or None when `name`
+/// doesn't look like a copy.
+pub(crate) fn backup_base_name(name: &str) -> Option<String> {
+    // Office owner files: "~$report.docx" next to "report.docx".
+    if let Some(rest) = name.strip_prefix("~$") {
+        return (!rest.is_empty()).then(|| rest.to_string());
+    }
+
+    let (stem, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 => (&name[..pos], Some(&name[pos + 1..])),
+        _ => (name, None),
+    };
+    // "report - Copy (2)" and "report copy (1)" carry both markers.
+    let mut base = stem;
+    let mut matched = false;
+    if let Some(stripped) = strip_counter(base) {
+        base = stripped;
+        matched = true;
+    }
+    if let Some(stripped) = strip_copy_word(base) {
+        base = stripped;
+        matched = true;
+    }
+    if !matched || base.trim().is_empty() {
+        return None;
+    }
+    Some(match ext {
+        Some(ext) => format!("{base}.{ext}"),
+        None => base.to_string(),
+    })
+}
+
+pub(crate) fn is_backup_name(name: &str) -> bool {
+    backup_base_name(name).is_some()
+}
+
+/// Group backup-named files by (directory, original name), largest groups
+/// first. Candidates come from a cheap LIKE prefilter and are confirmed with
+/// `backup_base_name`.
+pub(crate) fn find_probable_duplicates(
+    conn: &Connection,
+    limit: usize,
+) -> AppResult<Vec<DuplicateGroupDto>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, name, dir, is_dir, ext, size, mtime, cloud_only FROM entries \
+             WHERE is_dir = 0 AND (name LIKE '% (%)%' OR name LIKE '% copy%' OR name LIKE '~$%')",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], row_to_entry).map_err(|e| e.to_string())?;
+
+    let mut groups: HashMap<(String, String), Vec<EntryDto>> = HashMap::new();
+    for row in rows {
+        let entry = row.map_err(|e| e.to_string())?;
+        if let Some(base) = backup_base_name(&entry.name) {
+            groups.entry((entry.dir.clone(), base)).or_default().push(entry);
+        }
+    }
+
+    let mut exists = conn
+        .prepare("SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)")
+        .map_err(|e| e.to_string())?;
+    let sep = std::path::MAIN_SEPARATOR;
+    let mut result: Vec<DuplicateGroupDto> = Vec::with_capacity(groups.len());
+    for ((dir, base_name), mut copies) in groups {
+        let original_path = if dir.ends_with(sep) {
+            format!("{dir}{base_name}")
+        } else {
+            format!("{dir}{sep}{base_name}")
+        };
+        let original = exists
+            .query_row(params![original_path], |r| r.get::<_, bool>(0))
+            .unwrap_or(false)
+            .then_some(original_path);
+        copies.sort_by(|a, b| a.name.cmp(&b.name));
+        result.push(DuplicateGroupDto {
+            dir,
+            base_name,
+            original,
+            copies,
+        });
+    }
+
+    let copies_size = |g: &DuplicateGroupDto| -> i64 { g.copies.iter().filter_map(|e| e.size).sum() };
+    result.sort_by(|a, b| {
+        b.copies
+            .len()
+            .cmp(&a.copies.len())
+            .then_with(|| copies_size(b).cmp(&copies_size(a)))
+            .then_with(|| a.dir.cmp(&b.dir))
+            .then_with(|| a.base_name.cmp(&b.base_name))
+    });
+    result.truncate(limit);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_common_copy_names() {
+        assert_eq!(backup_base_name("report (1).pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(backup_base_name("report copy.pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(backup_base_name("report copy 3.pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(backup_base_name("Report - Copy.PDF").as_deref(), Some("Report.PDF"));
+        assert_eq!(backup_base_name("report - Copy (2).pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(backup_base_name("~$report.docx").as_deref(), Some("report.docx"));
+        assert_eq!(backup_base_name("Makefile (2)").as_deref(), Some("Makefile"));
+
+        assert_eq!(backup_base_name("report.pdf"), None);
+        assert_eq!(backup_base_name("copy.txt"), None);
+        assert_eq!(backup_base_name("photocopy.png"), None);
+        assert_eq!(backup_base_name("notes (draft).md"), None);
+        assert_eq!(backup_base_name("(1).txt"), None);
+    }
+
+    #[test]
+    fn groups_copies_by_original() {
+        let root = crate::temp_case_dir("probable_duplicates");
+        std::fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+        let sep = std::path::MAIN_SEPARATOR;
+        for (dir, name) in [
+            ("docs", "report.pdf"),
+            ("docs", "report (1).pdf"),
+            ("docs", "report copy.pdf"),
+            ("docs", "notes (draft).md"),
+            ("dl", "setup (2).exe"),
+        ] {
+            let path = std::path::PathBuf::from(format!("{dir}{sep}{name}"));
+            crate::insert_test_entry(&conn, &path, false, 1);
+            crate::set_test_entry_stats(&conn, &path, None, None, Some(10));
+        }
+
+        let groups = find_probable_duplicates(&conn, 10).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].base_name, "report.pdf");
+        assert_eq!(groups[0].original, Some(format!("docs{sep}report.pdf")));
+        let copies: Vec<&str> = groups[0].copies.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(copies, vec!["report (1).pdf", "report copy.pdf"]);
+        assert_eq!(groups[1].base_name, "setup.exe");
+        assert_eq!(groups[1].original, None);
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
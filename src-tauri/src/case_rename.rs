@@ -0,0 +1,94 @@
+//! Case-only renames (`Readme.md` → `README.md`). On case-insensitive
+//! filesystems (macOS, Windows) both spellings stat as present, so the
+//! watchers' stat-and-upsert kept the old row next to a new one, and the
+//! rename preflight took the file for a collision with itself. What the
+//! folder lists tells the spellings apart.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::name_norm;
+
+/// Whether `a` and `b` are the same name in the same folder, spelled
+/// differently only in case (or Unicode normalization).
+pub(crate) fn is_case_only_change(a: &Path, b: &Path) -> bool {
+    if a == b || a.parent() != b.parent() {
+        return false;
+    }
+    match (a.file_name(), b.file_name()) {
+        (Some(a), Some(b)) => {
+            name_norm::fold(&a.to_string_lossy()) == name_norm::fold(&b.to_string_lossy())
+        }
+        _ => false,
+    }
+}
+
+/// Whether `path`'s folder lists it under exactly this spelling. Unreadable
+/// folders count as a yes, leaving the path to the usual handling.
+pub(crate) fn listed_as_is(path: &Path) -> bool {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return true;
+    };
+    match fs::read_dir(parent) {
+        Ok(entries) => entries.flatten().any(|entry| entry.file_name() == name),
+        Err(_) => true,
+    }
+}
+
+/// Case-only renames among a watcher batch, as (stale, current) pairs: paths
+/// in the same folder whose names differ only in case, of which the folder
+/// lists just one. Only folders with such a pair are read.
+pub(crate) fn find_in_batch(paths: &[PathBuf]) -> Vec<(PathBuf, PathBuf)> {
+    let mut groups: HashMap<(&Path, String), Vec<&PathBuf>> = HashMap::new();
+    for path in paths {
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            let key = (parent, name_norm::fold(&name.to_string_lossy()));
+            let group = groups.entry(key).or_default();
+            if !group.contains(&path) {
+                group.push(path);
+            }
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for group in groups.into_values().filter(|group| group.len() > 1) {
+        let (current, stale): (Vec<&PathBuf>, Vec<&PathBuf>) =
+            group.into_iter().partition(|path| listed_as_is(path));
+        if let [current] = current[..] {
+            pairs.extend(stale.into_iter().map(|old| (old.clone(), current.clone())));
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_pairs_the_unlisted_spelling_with_the_listed_one() {
+        let dir = crate::temp_case_dir("case_rename");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("README.md"), b"x").unwrap();
+
+        let old = dir.join("Readme.md");
+        let new = dir.join("README.md");
+        assert!(is_case_only_change(&old, &new));
+        assert!(!is_case_only_change(&new, &new));
+        assert!(!is_case_only_change(&old, &dir.join("sub").join("README.md")));
+        assert!(listed_as_is(&new));
+        assert!(!listed_as_is(&old));
+
+        let other = dir.join("notes.txt");
+        let pairs = find_in_batch(&[new.clone(), other, old.clone(), new.clone()]);
+        assert_eq!(pairs, vec![(old, new)]);
+
+        // Both spellings listed: two distinct files on a case-sensitive volume.
+        if fs::write(dir.join("Readme.md"), b"y").is_ok() && listed_as_is(&dir.join("Readme.md")) {
+            assert!(find_in_batch(&[dir.join("Readme.md"), dir.join("README.md")]).is_empty());
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
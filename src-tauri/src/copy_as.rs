@@ -0,0 +1,156 @@
+//! `copy_as`: clipboard text for result paths in the shape the destination
+//! wants — a POSIX or Windows path, a `file://` URL, a shell argument, a
+//! `~`-relative path or a markdown link. `copy_paths` stays the plain form.
+
+use std::path::Path;
+
+use crate::AppResult;
+
+/// Formats accepted by `copy_as`.
+pub(crate) const COPY_AS_FORMATS: &[&str] = &[
+    "posix",
+    "windows",
+    "file_url",
+    "shell",
+    "home_relative",
+    "markdown",
+];
+
+fn is_windows_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with("\\\\")
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+fn to_posix(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+fn to_windows(path: &str) -> String {
+    path.replace('/', "\\")
+}
+
+fn percent_encode_path(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// `file:///C:/dir/a%20b.txt`, `file://server/share/x` for UNC paths,
+/// `file:///Users/me/a%20b.txt` otherwise.
+pub(crate) fn file_url(path: &str) -> String {
+    let posix = to_posix(path);
+    if let Some(unc) = posix.strip_prefix("//") {
+        return format!("file://{}", percent_encode_path(unc));
+    }
+    if is_windows_path(path) {
+        return format!("file:///{}", percent_encode_path(&posix));
+    }
+    format!("file://{}", percent_encode_path(&posix))
+}
+
+/// Single quotes for POSIX shells; double quotes for Windows paths, which
+/// cannot themselves contain `"`.
+fn shell_quote(path: &str) -> String {
+    if is_windows_path(path) {
+        return format!("\"{path}\"");
+    }
+    format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+pub(crate) fn home_relative(path: &str, home: &Path) -> String {
+    match Path::new(path).strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => {
+            let sep = if is_windows_path(path) { '\\' } else { '/' };
+            format!("~{sep}{}", rest.to_string_lossy())
+        }
+        Err(_) => path.to_string(),
+    }
+}
+
+fn markdown_link(path: &str) -> String {
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    let label = name
+        .replace('\\', "\\\\")
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    format!("[{label}]({})", file_url(path))
+}
+
+/// Clipboard text for `paths` in `format`. Shell arguments are joined with
+/// spaces so they paste into a command line; everything else one per line.
+pub(crate) fn format_paths(paths: &[String], format: &str, home: &Path) -> AppResult<String> {
+    let convert: Box<dyn Fn(&str) -> String> = match format {
+        "posix" => Box::new(to_posix),
+        "windows" => Box::new(to_windows),
+        "file_url" => Box::new(file_url),
+        "shell" => Box::new(shell_quote),
+        "home_relative" => Box::new(|p: &str| home_relative(p, home)),
+        "markdown" => Box::new(markdown_link),
+        other => {
+            return Err(format!(
+                "Unknown copy format: {other} (expected one of {})",
+                COPY_AS_FORMATS.join(", ")
+            ))
+        }
+    };
+    let separator = if format == "shell" { " " } else { "\n" };
+    Ok(paths
+        .iter()
+        .map(|p| convert(p))
+        .collect::<Vec<_>>()
+        .join(separator))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_each_copy_style() {
+        let home = Path::new("/Users/me");
+        let one =
+            |path: &str, format: &str| format_paths(&[path.to_string()], format, home).unwrap();
+
+        assert_eq!(one("C:\\Users\\me\\a.txt", "posix"), "C:/Users/me/a.txt");
+        assert_eq!(one("/Users/me/a.txt", "windows"), "\\Users\\me\\a.txt");
+        assert_eq!(
+            one("/Users/me/my file#1.txt", "file_url"),
+            "file:///Users/me/my%20file%231.txt"
+        );
+        assert_eq!(
+            one("C:\\dir\\a b.txt", "file_url"),
+            "file:///C:/dir/a%20b.txt"
+        );
+        assert_eq!(
+            one("\\\\server\\share\\x", "file_url"),
+            "file://server/share/x"
+        );
+        assert_eq!(one("/tmp/it's here", "shell"), "'/tmp/it'\\''s here'");
+        assert_eq!(one("C:\\a b\\c", "shell"), "\"C:\\a b\\c\"");
+        assert_eq!(one("/Users/me/docs/a.md", "home_relative"), "~/docs/a.md");
+        assert_eq!(one("/Users/me", "home_relative"), "~");
+        assert_eq!(one("/opt/x", "home_relative"), "/opt/x");
+        assert_eq!(
+            one("/Users/me/[draft] notes.md", "markdown"),
+            "[\\[draft\\] notes.md](file:///Users/me/%5Bdraft%5D%20notes.md)"
+        );
+
+        let both = format_paths(&["/a b".to_string(), "/c".to_string()], "shell", home).unwrap();
+        assert_eq!(both, "'/a b' '/c'");
+        let lines = format_paths(&["/a".to_string(), "/c".to_string()], "posix", home).unwrap();
+        assert_eq!(lines, "/a\n/c");
+        assert!(format_paths(&["/a".to_string()], "yaml", home).is_err());
+    }
+}
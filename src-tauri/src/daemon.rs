@@ -28,9 +28,9 @@ use fs2::FileExt;
 
 use crate::mcp_server::default_db_path;
 use crate::{
-    build_app_state, cleanup_entries_gc_tables, db_connection, ensure_db_indexes,
-    finalize_fresh_index, get_meta, init_db_tables, resolve_home_dir, run_db_maintenance,
-    run_incremental_index, AppState,
+    build_app_state, cleanup_entries_gc_tables, db_connection, db_location, ensure_db_indexes,
+    finalize_fresh_index, get_meta, init_db_tables, profiles, resolve_home_dir,
+    run_db_maintenance, run_incremental_index, AppState,
 };
 
 /// How often the resident daemon polls the GUI beacon so it yields writes
@@ -286,10 +286,7 @@ pub fn run_daemon() {
         return;
     };
 
-    let app_data_dir = db_path
-        .parent()
-        .map(Path::to_path_buf)
-        .unwrap_or_else(|| PathBuf::from("."));
+    let app_data_dir = db_location::data_dir_for(&db_path);
     if let Err(e) = fs::create_dir_all(&app_data_dir) {
         eprintln!("[daemon] create data dir failed: {e}");
         return;
@@ -299,7 +296,8 @@ pub fn run_daemon() {
         return;
     }
 
-    let state = build_app_state(db_path.clone(), home_dir, &app_data_dir);
+    let mut state = build_app_state(db_path.clone(), home_dir, &app_data_dir);
+    profiles::apply_to_state(&mut state);
 
     // Startup reconcile: a fresh build if the DB is empty, otherwise a catchup
     // covering everything changed while no daemon was running.
@@ -0,0 +1,214 @@
+//! Where a profile's `index.db` lives. By default it sits with the rest of
+//! the profile's data (the app data dir, or its directory under `profiles/`);
+//! `set_db_location` can move it elsewhere — a faster disk, or out of a
+//! synced folder — recorded in `db_location.json` in the data dir. The new
+//! directory gets a `data_dir.json` pointing back, so processes that only
+//! know the DB path (the MCP server's daemon) still find the profile's
+//! `.pathignore` and settings.
+//!
+//! Commands that write the DB outside an index run enter a [`WriteGate`]
+//! first, so a move never copies a file someone is still writing.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use parking_lot::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::{AppResult, DB_FILE_NAME};
+
+const LOCATION_FILE_NAME: &str = "db_location.json";
+const DATA_DIR_POINTER_NAME: &str = "data_dir.json";
+/// SQLite's companions of the DB file. `-shm` is rebuilt on open, so only
+/// the WAL is carried over.
+const WAL_SUFFIX: &str = "-wal";
+const SHM_SUFFIX: &str = "-shm";
+
+/// Held shared by each DB write outside an index run (pins, saved searches,
+/// file operations, the idle checkpoint) and exclusively by a move. The
+/// watcher and index runs are stopped by the move itself.
+#[derive(Debug, Default)]
+pub(crate) struct WriteGate(RwLock<()>);
+
+impl WriteGate {
+    /// Refuses instead of waiting once a move has started: the move ends in
+    /// a restart, and the write would land in the old file.
+    pub(crate) fn enter(&self) -> AppResult<RwLockReadGuard<'_, ()>> {
+        self.0
+            .try_read_recursive()
+            .ok_or_else(|| "The index is being moved; try again once the app restarts.".to_string())
+    }
+
+    /// Wait for the writes in flight and keep new ones out until dropped.
+    pub(crate) fn close(&self) -> RwLockWriteGuard<'_, ()> {
+        self.0.write()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DirPointer {
+    dir: PathBuf,
+}
+
+fn read_pointer(path: &Path) -> Option<PathBuf> {
+    let text = fs::read_to_string(path).ok()?;
+    let pointer: DirPointer = serde_json::from_str(&text).ok()?;
+    pointer.dir.is_absolute().then_some(pointer.dir)
+}
+
+fn write_pointer(path: &Path, dir: &Path) -> AppResult<()> {
+    let pointer = DirPointer {
+        dir: dir.to_path_buf(),
+    };
+    let text = serde_json::to_string_pretty(&pointer).map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| e.to_string())
+}
+
+/// The DB of the profile whose data lives in `data_dir`.
+pub(crate) fn db_path(data_dir: &Path) -> PathBuf {
+    read_pointer(&data_dir.join(LOCATION_FILE_NAME))
+        .unwrap_or_else(|| data_dir.to_path_buf())
+        .join(DB_FILE_NAME)
+}
+
+/// The data dir of the profile `db_path` belongs to.
+pub(crate) fn data_dir_for(db_path: &Path) -> PathBuf {
+    let db_dir = db_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+    read_pointer(&db_dir.join(DATA_DIR_POINTER_NAME)).unwrap_or(db_dir)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Move the DB and its WAL into `new_dir` and record the new location;
+/// returns the new DB path. The caller has closed the `WriteGate`, stopped
+/// the watcher and index runs, and dropped its pooled connections. The WAL is checkpointed first, and the old files
+/// are only removed once the copy passes `quick_check`.
+pub(crate) fn relocate(data_dir: &Path, db_path: &Path, new_dir: &Path) -> AppResult<PathBuf> {
+    if !new_dir.is_absolute() {
+        return Err(format!("Not an absolute path: {}", new_dir.display()));
+    }
+    fs::create_dir_all(new_dir).map_err(|e| e.to_string())?;
+    let new_dir = fs::canonicalize(new_dir).map_err(|e| e.to_string())?;
+    let old_dir = db_path.parent().and_then(|dir| fs::canonicalize(dir).ok());
+    if old_dir.as_deref() == Some(new_dir.as_path()) {
+        return Ok(db_path.to_path_buf());
+    }
+    let target = new_dir.join(DB_FILE_NAME);
+    if target.exists() {
+        return Err(format!("{} already contains an index", new_dir.display()));
+    }
+
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+        .map_err(|e| e.to_string())?;
+    drop(conn);
+
+    let copied = [("", true), (WAL_SUFFIX, false)]
+        .into_iter()
+        .filter(|(suffix, required)| *required || with_suffix(db_path, suffix).exists())
+        .try_for_each(|(suffix, _)| {
+            fs::copy(with_suffix(db_path, suffix), with_suffix(&target, suffix))
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .and_then(|()| {
+            let conn = Connection::open(&target).map_err(|e| e.to_string())?;
+            let check: String = conn
+                .query_row("PRAGMA quick_check", [], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            if check == "ok" {
+                Ok(())
+            } else {
+                Err(format!("Copied index failed its check: {check}"))
+            }
+        });
+    if let Err(e) = copied {
+        for suffix in ["", WAL_SUFFIX, SHM_SUFFIX] {
+            let _ = fs::remove_file(with_suffix(&target, suffix));
+        }
+        return Err(e);
+    }
+
+    let data_dir_canonical = fs::canonicalize(data_dir).map_err(|e| e.to_string())?;
+    if new_dir == data_dir_canonical {
+        let _ = fs::remove_file(data_dir.join(LOCATION_FILE_NAME));
+    } else {
+        write_pointer(&new_dir.join(DATA_DIR_POINTER_NAME), &data_dir_canonical)?;
+        write_pointer(&data_dir.join(LOCATION_FILE_NAME), &new_dir)?;
+    }
+    for suffix in ["", WAL_SUFFIX, SHM_SUFFIX] {
+        let _ = fs::remove_file(with_suffix(db_path, suffix));
+    }
+    if old_dir.as_deref() != Some(data_dir_canonical.as_path()) {
+        if let Some(old_dir) = db_path.parent() {
+            let _ = fs::remove_file(old_dir.join(DATA_DIR_POINTER_NAME));
+        }
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relocated_db_keeps_rows_and_points_back_to_its_data_dir() {
+        let root = crate::temp_case_dir("db_location");
+        let data_dir = root.join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        let data_dir = fs::canonicalize(&data_dir).unwrap();
+        let db = db_path(&data_dir);
+        assert_eq!(db, data_dir.join(DB_FILE_NAME));
+        let conn = Connection::open(&db).unwrap();
+        conn.execute_batch(
+            "PRAGMA journal_mode=WAL; CREATE TABLE t(x); INSERT INTO t VALUES (1), (2);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let fast = root.join("fast");
+        let moved = relocate(&data_dir, &db, &fast).unwrap();
+        assert_eq!(moved, db_path(&data_dir));
+        assert!(!db.exists());
+        assert_eq!(data_dir_for(&moved), data_dir);
+        let count: i64 = Connection::open(&moved)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+
+        assert!(relocate(&data_dir, &moved, Path::new("relative")).is_err());
+        fs::create_dir_all(root.join("taken")).unwrap();
+        fs::write(root.join("taken").join(DB_FILE_NAME), b"").unwrap();
+        assert!(relocate(&data_dir, &moved, &root.join("taken")).is_err());
+
+        let back = relocate(&data_dir, &moved, &data_dir).unwrap();
+        assert_eq!(back, data_dir.join(DB_FILE_NAME));
+        assert_eq!(db_path(&data_dir), back);
+        assert!(!data_dir.join(LOCATION_FILE_NAME).exists());
+        assert!(!fast.join(DATA_DIR_POINTER_NAME).exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn write_gate_refuses_writers_while_closed() {
+        let gate = WriteGate::default();
+        let writing = gate.enter().unwrap();
+        assert!(gate.enter().is_ok(), "writers share the gate");
+        drop(writing);
+        let closed = gate.close();
+        assert!(gate.enter().is_err());
+        drop(closed);
+        assert!(gate.enter().is_ok());
+    }
+}
@@ -0,0 +1,300 @@
+//! `everything://` links for notes apps and browsers:
+//!
+//! * `everything://search?q=*.pdf` brings the window up with the query run
+//! * `everything://reveal?path=/Users/me/report.pdf` reveals the file, if it
+//!   (or its folder) is in the index
+//!
+//! macOS declares the scheme in Info.plist and delivers links to the running
+//! app (`RunEvent::Opened`). Windows and Linux register it per user at
+//! startup and pass the link as the first argument of a new launch, like the
+//! jump list's `--search`. When the app is already running, the
+//! single-instance plugin forwards either to it and the new launch exits
+//! (`handle_forwarded_args`).
+
+use std::path::Path;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{search_history, AppState};
+
+pub(crate) const SCHEME: &str = "everything";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DeepLink {
+    Search(String),
+    Reveal(String),
+}
+
+pub(crate) fn parse(url: &str) -> Option<DeepLink> {
+    let (scheme, rest) = url.split_once(':')?;
+    if !scheme.eq_ignore_ascii_case(SCHEME) {
+        return None;
+    }
+    let rest = rest.trim_start_matches('/');
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let params = crate::http_api::parse_params(query);
+    let param = |key: &str| {
+        params
+            .get(key)
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    match action.trim_end_matches('/').to_ascii_lowercase().as_str() {
+        "search" => param("q").map(DeepLink::Search),
+        // Relative paths would resolve against wherever the app started.
+        "reveal" => param("path")
+            .filter(|p| Path::new(p).is_absolute() && !is_unc_or_device(p))
+            .map(DeepLink::Reveal),
+        _ => None,
+    }
+}
+
+/// UNC shares (`\\host\share`, `//host/share`) and device or verbatim
+/// paths (`\\.\`, `\\?\`). Revealing one makes Windows contact the host,
+/// which hands NTLM credentials to whatever server a web page names.
+fn is_unc_or_device(path: &str) -> bool {
+    let mut chars = path.chars();
+    matches!(
+        (chars.next(), chars.next()),
+        (Some('\\' | '/'), Some('\\' | '/'))
+    )
+}
+
+/// `path` or its folder is in the index: a link can only reveal what a
+/// search could have shown.
+fn is_indexed(state: &AppState, path: &str) -> bool {
+    let Ok(conn) = crate::db_connection(&state.db_path) else {
+        return false;
+    };
+    let parent = Path::new(path)
+        .parent()
+        .map(|p| p.to_string_lossy().into_owned());
+    [Some(path.to_string()), parent]
+        .into_iter()
+        .flatten()
+        .any(|p| {
+            conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)",
+                [&p],
+                |row| row.get::<_, bool>(0),
+            )
+            .unwrap_or(false)
+        })
+}
+
+/// The first `everything://` link among the launch arguments.
+pub(crate) fn from_args(args: &[String]) -> Option<DeepLink> {
+    args.iter().find_map(|arg| parse(arg))
+}
+
+/// Arguments of a later launch, forwarded by the single-instance plugin
+/// (`args[0]` is its executable): run its link or `--search` query here, or
+/// just bring the window up.
+pub(crate) fn handle_forwarded_args(app: &AppHandle, args: &[String]) {
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let args = args.get(1..).unwrap_or_default();
+    let link = from_args(args)
+        .or_else(|| search_history::launch_query_from_args(args).map(DeepLink::Search));
+    match link {
+        Some(link) => handle(app, &state, link),
+        None => crate::tray::show_main_window(app),
+    }
+}
+
+/// Act on `link`. A search arriving before the frontend is listening is
+/// parked as the launch query, which the frontend takes once it's up.
+pub(crate) fn handle(app: &AppHandle, state: &AppState, link: DeepLink) {
+    crate::tray::show_main_window(app);
+    match link {
+        DeepLink::Search(query) => {
+            if state
+                .frontend_ready
+                .load(std::sync::atomic::Ordering::Acquire)
+            {
+                let _ = app.emit("service_search", query);
+            } else {
+                *state.launch_query.lock() = Some(query);
+            }
+        }
+        DeepLink::Reveal(path) => {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                if !is_indexed(&state, &path) {
+                    eprintln!("[deep_link] not revealing {path}: not in the index");
+                    return;
+                }
+                if let Err(e) = crate::reveal_in_finder_impl(vec![path]) {
+                    eprintln!("[deep_link] reveal failed: {e}");
+                }
+            });
+        }
+    }
+}
+
+/// Make this executable the per-user handler for `everything://`, leaving
+/// the registry alone when it already is.
+#[cfg(target_os = "windows")]
+pub(crate) fn register(_identifier: &str) -> crate::AppResult<()> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let key = format!(r"Software\Classes\{SCHEME}");
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    let values = [
+        (key.clone(), None, "URL:Everything"),
+        (key.clone(), Some("URL Protocol"), ""),
+        (format!(r"{key}\shell\open\command"), None, command.as_str()),
+    ];
+    if values
+        .iter()
+        .all(|(subkey, name, value)| registry_string(subkey, *name).as_deref() == Some(*value))
+    {
+        return Ok(());
+    }
+    for (subkey, name, value) in values {
+        let data: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
+        let name = name.map(HSTRING::from);
+        unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                &HSTRING::from(subkey),
+                name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr())),
+                REG_SZ.0,
+                Some(data.as_ptr().cast()),
+                (data.len() * std::mem::size_of::<u16>()) as u32,
+            )
+        }
+        .ok()
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// A `REG_SZ` value under HKCU, `None` when it's missing or another type.
+#[cfg(target_os = "windows")]
+fn registry_string(subkey: &str, name: Option<&str>) -> Option<String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_SZ};
+
+    let subkey = HSTRING::from(subkey);
+    let name = name.map(HSTRING::from);
+    let name = name.as_ref().map_or(PCWSTR::null(), |n| PCWSTR(n.as_ptr()));
+    let mut len = 0u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &subkey,
+            name,
+            RRF_RT_REG_SZ,
+            None,
+            None,
+            Some(&mut len),
+        )
+    }
+    .ok()
+    .ok()?;
+    let mut buf = vec![0u16; (len as usize).div_ceil(2)];
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            &subkey,
+            name,
+            RRF_RT_REG_SZ,
+            None,
+            Some(buf.as_mut_ptr().cast()),
+            Some(&mut len),
+        )
+    }
+    .ok()
+    .ok()?;
+    buf.truncate(len as usize / 2);
+    while buf.last() == Some(&0) {
+        buf.pop();
+    }
+    Some(String::from_utf16_lossy(&buf))
+}
+
+/// Make this executable the per-user handler for `everything://`, leaving
+/// the desktop file and the mime default alone when they already say so.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn register(identifier: &str) -> crate::AppResult<()> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(std::path::PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| crate::resolve_home_dir().join(".local/share"));
+    let file_name = format!("{identifier}.url-handler.desktop");
+    let mime = format!("x-scheme-handler/{SCHEME}");
+    let applications = data_home.join("applications");
+    let desktop_file = applications.join(&file_name);
+    let contents = format!(
+        "[Desktop Entry]\nType=Application\nName=Everything\nExec={} %u\n\
+         NoDisplay=true\nMimeType={mime};\n",
+        crate::autostart::desktop_exec(&exe)
+    );
+    let current_default = || {
+        std::process::Command::new("xdg-mime")
+            .args(["query", "default", &mime])
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+    if std::fs::read_to_string(&desktop_file).is_ok_and(|text| text == contents)
+        && current_default().as_deref() == Some(file_name.as_str())
+    {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&applications).map_err(|e| e.to_string())?;
+    std::fs::write(&desktop_file, contents).map_err(|e| e.to_string())?;
+    let status = std::process::Command::new("xdg-mime")
+        .args(["default", &file_name, &mime])
+        .status()
+        .map_err(|e| format!("xdg-mime: {e}"))?;
+    if !status.success() {
+        return Err(format!("xdg-mime exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_search_and_reveal_links() {
+        assert_eq!(
+            parse("everything://search?q=*.pdf%20report"),
+            Some(DeepLink::Search("*.pdf report".to_string()))
+        );
+        assert_eq!(
+            parse("Everything:///search/?q=notes"),
+            Some(DeepLink::Search("notes".to_string()))
+        );
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(
+            parse("everything://reveal?path=%2FUsers%2Fme%2Fa%20b.txt"),
+            Some(DeepLink::Reveal("/Users/me/a b.txt".to_string()))
+        );
+        assert_eq!(parse("everything://reveal?path=relative.txt"), None);
+        assert_eq!(
+            parse("everything://reveal?path=%5C%5Cevil.example%5Cshare%5Cx"),
+            None
+        );
+        assert_eq!(
+            parse("everything://reveal?path=//evil.example/share/x"),
+            None
+        );
+        assert_eq!(parse("everything://reveal?path=%5C%5C%3F%5CC%3A%5Cx"), None);
+        assert_eq!(parse("everything://search?q="), None);
+        assert_eq!(parse("everything://delete?path=/x"), None);
+        assert_eq!(parse("https://search?q=x"), None);
+        assert_eq!(
+            from_args(&["--flag".to_string(), "everything://search?q=x".to_string()]),
+            Some(DeepLink::Search("x".to_string()))
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Recursive directory size for `get_dir_size`. Indexed directories are
+//! summed straight from `entries` with one range scan over the `path` index,
+//! plus a live walk of whatever the index skipped inside them (ignored or
+//! unreadable-at-scan-time subtrees), found by listing each indexed directory
+//! against its indexed children. Directories the index doesn't cover at all
+//! (outside the scan roots, ignored by `.pathignore`, or before the first scan
+//! finishes) fall back to a live walk.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use walkdir::WalkDir;
+
+use crate::long_path;
+use crate::{subtree_range_bounds, AppResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirSizeDto {
+    pub path: String,
+    pub size: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+    /// "index", "walk", or "partial" (from the index while a scan is still
+    /// filling it in).
+    pub source: String,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DirTotals {
+    pub(crate) size: u64,
+    pub(crate) file_count: u64,
+    pub(crate) dir_count: u64,
+}
+
+/// Strip trailing separators the way `delete_paths` does, keeping a bare root.
+pub(crate) fn normalize_dir(path: &str) -> String {
+    if path == "/" || path == "\\" {
+        return path.to_string();
+    }
+    path.trim_end_matches(&['/', '\\'][..]).to_string()
+}
+
+/// Totals for every row under `dir` plus whatever the index skipped inside
+/// it, or `None` when `dir` itself is not an indexed directory.
+pub(crate) fn indexed_dir_totals(conn: &Connection, dir: &str) -> AppResult<Option<DirTotals>> {
+    let is_dir: Option<bool> = conn
+        .query_row(
+            "SELECT is_dir FROM entries WHERE path = ?1",
+            params![dir],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    match is_dir {
+        None => return Ok(None),
+        Some(false) => return Err(format!("Not a directory: {dir}")),
+        Some(true) => {}
+    }
+
+    let (sql, bounds) = if dir == "/" || dir == "\\" {
+        (
+            "SELECT path, is_dir, size FROM entries WHERE path <> ?1",
+            None,
+        )
+    } else {
+        (
+            "SELECT path, is_dir, size FROM entries WHERE path >= ?1 AND path < ?2",
+            Some(subtree_range_bounds(dir)),
+        )
+    };
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    let mut rows = match &bounds {
+        Some((lo, hi)) => stmt.query(params![lo, hi]),
+        None => stmt.query(params![dir]),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut totals = DirTotals::default();
+    let mut indexed_dirs = vec![dir.to_string()];
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let is_dir: bool = row.get(1).map_err(|e| e.to_string())?;
+        if is_dir {
+            totals.dir_count += 1;
+            indexed_dirs.push(row.get(0).map_err(|e| e.to_string())?);
+        } else {
+            let size: Option<i64> = row.get(2).map_err(|e| e.to_string())?;
+            totals.file_count += 1;
+            totals.size += size.unwrap_or(0).max(0) as u64;
+        }
+    }
+
+    for indexed_dir in &indexed_dirs {
+        add_unindexed_children(conn, indexed_dir, &mut totals)?;
+    }
+    Ok(Some(totals))
+}
+
+/// List `dir` on disk and add every child the index has no row for: files
+/// by size, directories by a live walk. A directory that can't be listed
+/// adds nothing.
+fn add_unindexed_children(conn: &Connection, dir: &str, totals: &mut DirTotals) -> AppResult<()> {
+    let Ok(children) = fs::read_dir(long_path::extended(Path::new(dir))) else {
+        return Ok(());
+    };
+    let mut stmt = conn
+        .prepare_cached("SELECT name FROM entries WHERE dir = ?1")
+        .map_err(|e| e.to_string())?;
+    let indexed: HashSet<String> = stmt
+        .query_map(params![dir], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>()
+        .map_err(|e| e.to_string())?;
+
+    for child in children.flatten() {
+        if indexed.contains(child.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        let Ok(file_type) = child.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            let walked = walk_dir_totals(&child.path()).unwrap_or_default();
+            totals.dir_count += 1 + walked.dir_count;
+            totals.file_count += walked.file_count;
+            totals.size += walked.size;
+        } else {
+            totals.file_count += 1;
+            totals.size += child.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    Ok(())
+}
+
+/// Walk `dir` on disk without following symlinks. Unreadable entries are
+/// skipped, so the result is a lower bound on permission errors.
+pub(crate) fn walk_dir_totals(dir: &Path) -> AppResult<DirTotals> {
+    let meta = long_path::symlink_metadata(dir).map_err(|e| e.to_string())?;
+    if !meta.is_dir() {
+        return Err(format!("Not a directory: {}", dir.display()));
+    }
+    let mut totals = DirTotals::default();
+    for entry in WalkDir::new(dir)
+        .min_depth(1)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+    {
+        if entry.file_type().is_dir() {
+            totals.dir_count += 1;
+        } else {
+            totals.file_count += 1;
+            totals.size += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+    Ok(totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn sums_indexed_subtree_and_walks_unindexed_dirs() {
+        let root = crate::temp_case_dir("dir_size");
+        let proj = root.join("proj");
+        let sibling = root.join("proj0");
+        fs::create_dir_all(proj.join("src")).unwrap();
+        fs::create_dir_all(proj.join("node_modules").join("dep")).unwrap();
+        fs::create_dir_all(&sibling).unwrap();
+        fs::write(proj.join("a.txt"), [0u8; 10]).unwrap();
+        fs::write(proj.join("src").join("main.rs"), [0u8; 32]).unwrap();
+        fs::write(proj.join("empty.txt"), b"").unwrap();
+        fs::write(
+            proj.join("node_modules").join("dep").join("index.js"),
+            [0u8; 5],
+        )
+        .unwrap();
+        fs::write(proj.join("skipped.log"), [0u8; 3]).unwrap();
+        fs::write(sibling.join("b.txt"), [0u8; 100]).unwrap();
+
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+        // node_modules/ and skipped.log stay out of the index, as if ignored.
+        for (path, is_dir, size) in [
+            (proj.clone(), true, None),
+            (proj.join("a.txt"), false, Some(10)),
+            (proj.join("src"), true, None),
+            (proj.join("src").join("main.rs"), false, Some(32)),
+            (proj.join("empty.txt"), false, None),
+            // Prefix sibling: must not be counted under "proj".
+            (sibling.clone(), true, None),
+            (sibling.join("b.txt"), false, Some(100)),
+        ] {
+            crate::insert_test_entry(&conn, &path, is_dir, 1);
+            crate::set_test_entry_stats(&conn, &path, None, None, size);
+        }
+
+        let proj_str = proj.to_string_lossy().to_string();
+        let sep = std::path::MAIN_SEPARATOR;
+        let totals = indexed_dir_totals(&conn, &normalize_dir(&format!("{proj_str}{sep}")))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            totals,
+            DirTotals {
+                size: 50,
+                file_count: 5,
+                dir_count: 3
+            }
+        );
+        let missing = root.join("missing").to_string_lossy().to_string();
+        assert_eq!(indexed_dir_totals(&conn, &missing).unwrap(), None);
+        let file = proj.join("a.txt").to_string_lossy().to_string();
+        assert!(indexed_dir_totals(&conn, &file).is_err());
+
+        assert_eq!(
+            walk_dir_totals(&proj).unwrap(),
+            DirTotals {
+                size: 50,
+                file_count: 5,
+                dir_count: 3
+            }
+        );
+        assert!(walk_dir_totals(&proj.join("a.txt")).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,219 @@
+//! `largest_entries`: the "what's eating my disk" view. Everything comes
+//! from the indexed `size` column — the biggest files under a root, plus
+//! optional totals per top-level entry of that root and per extension — so
+//! it answers in one pass over the index without touching the disk.
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use serde::Serialize;
+
+use crate::{row_to_entry, subtree_range_bounds, AppResult, EntryDto};
+
+pub(crate) const DEFAULT_LARGEST_LIMIT: usize = 100;
+pub(crate) const MAX_LARGEST_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBucketDto {
+    /// Top-level entry name, or extension ("" for files without one).
+    pub key: String,
+    /// Full path of the top-level entry; `None` for extension buckets.
+    pub path: Option<String>,
+    pub size: u64,
+    pub file_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsageDto {
+    pub root: String,
+    pub total_size: u64,
+    pub file_count: u64,
+    pub largest: Vec<EntryDto>,
+    pub by_folder: Option<Vec<UsageBucketDto>>,
+    pub by_extension: Option<Vec<UsageBucketDto>>,
+    pub elapsed_ms: u64,
+}
+
+/// `WHERE` fragment and its parameters restricting rows to files under
+/// `root`. A filesystem root needs no range.
+fn file_scope(root: &str) -> (String, Vec<Value>) {
+    if root == "/" || root == "\\" {
+        return ("is_dir = 0".to_string(), Vec::new());
+    }
+    let (lo, hi) = subtree_range_bounds(root);
+    (
+        "is_dir = 0 AND path >= ?1 AND path < ?2".to_string(),
+        vec![Value::Text(lo), Value::Text(hi)],
+    )
+}
+
+/// Path prefix every row under `root` starts with.
+fn child_prefix(root: &str) -> String {
+    if root == "/" || root == "\\" {
+        root.to_string()
+    } else {
+        subtree_range_bounds(root).0
+    }
+}
+
+/// (total bytes, file count) under `root`.
+pub(crate) fn usage_totals(conn: &Connection, root: &str) -> AppResult<(u64, u64)> {
+    let (scope, args) = file_scope(root);
+    conn.query_row(
+        &format!("SELECT COALESCE(SUM(size), 0), COUNT(*) FROM entries WHERE {scope}"),
+        params_from_iter(args.iter()),
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)),
+    )
+    .map(|(size, count)| (size.max(0) as u64, count.max(0) as u64))
+    .map_err(|e| e.to_string())
+}
+
+/// The `limit` biggest files under `root`, largest first.
+pub(crate) fn largest_files(
+    conn: &Connection,
+    root: &str,
+    limit: usize,
+) -> AppResult<Vec<EntryDto>> {
+    let (scope, args) = file_scope(root);
+    let sql = format!(
+        "SELECT path, name, dir, is_dir, ext, size, mtime, cloud_only FROM entries \
+         WHERE {scope} AND size IS NOT NULL ORDER BY size DESC, path LIMIT {limit}"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(args.iter()), row_to_entry)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+fn usage_buckets(
+    conn: &Connection,
+    root: &str,
+    key_sql: &str,
+    limit: usize,
+) -> AppResult<Vec<(String, u64, u64)>> {
+    let (scope, args) = file_scope(root);
+    let sql = format!(
+        "SELECT {key_sql} AS bucket, COALESCE(SUM(size), 0) AS total, COUNT(*) FROM entries \
+         WHERE {scope} GROUP BY bucket ORDER BY total DESC, bucket LIMIT {limit}"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(args.iter()), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?.max(0) as u64,
+                row.get::<_, i64>(2)?.max(0) as u64,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+}
+
+/// File bytes per entry directly under `root`: a folder counts everything
+/// inside it, a file at the top level counts itself.
+pub(crate) fn usage_by_top_folder(
+    conn: &Connection,
+    root: &str,
+    limit: usize,
+) -> AppResult<Vec<UsageBucketDto>> {
+    let prefix = child_prefix(root);
+    let sep = std::path::MAIN_SEPARATOR;
+    // Everything after the prefix, cut at the next separator.
+    let rest = format!("substr(path, {})", prefix.chars().count() + 1);
+    let key_sql = format!(
+        "CASE WHEN instr({rest}, '{sep}') > 0 \
+         THEN substr({rest}, 1, instr({rest}, '{sep}') - 1) ELSE {rest} END"
+    );
+    Ok(usage_buckets(conn, root, &key_sql, limit)?
+        .into_iter()
+        .map(|(key, size, file_count)| UsageBucketDto {
+            path: Some(format!("{prefix}{key}")),
+            key,
+            size,
+            file_count,
+        })
+        .collect())
+}
+
+/// File bytes per extension (lowercased as indexed) under `root`.
+pub(crate) fn usage_by_extension(
+    conn: &Connection,
+    root: &str,
+    limit: usize,
+) -> AppResult<Vec<UsageBucketDto>> {
+    Ok(usage_buckets(conn, root, "COALESCE(ext, '')", limit)?
+        .into_iter()
+        .map(|(key, size, file_count)| UsageBucketDto {
+            key,
+            path: None,
+            size,
+            file_count,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn ranks_files_folders_and_extensions_by_size() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let case_dir = crate::temp_case_dir("disk_usage");
+        std::fs::create_dir_all(&case_dir).unwrap();
+        let db_path = case_dir.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+        let root = format!("{sep}home");
+        for (rel, ext, size) in [
+            ("Movies", None, None),
+            (&*format!("Movies{sep}trip.mov"), Some("mov"), Some(900)),
+            (
+                &*format!("Movies{sep}clips{sep}a.mov"),
+                Some("mov"),
+                Some(100),
+            ),
+            ("Docs", None, None),
+            (&*format!("Docs{sep}report.pdf"), Some("pdf"), Some(300)),
+            ("notes", None, Some(50)),
+        ] {
+            let path = PathBuf::from(format!("{root}{sep}{rel}"));
+            crate::insert_test_entry(&conn, &path, size.is_none(), 1);
+            crate::set_test_entry_stats(&conn, &path, ext, Some(0), size);
+        }
+        // Outside the root: never counted.
+        let outside = PathBuf::from(format!("{root}0{sep}big.iso"));
+        crate::insert_test_entry(&conn, &outside, false, 1);
+        crate::set_test_entry_stats(&conn, &outside, Some("iso"), Some(0), Some(5000));
+
+        assert_eq!(usage_totals(&conn, &root).unwrap(), (1350, 4));
+        let largest = largest_files(&conn, &root, 2).unwrap();
+        let names: Vec<&str> = largest.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["trip.mov", "report.pdf"]);
+
+        let folders = usage_by_top_folder(&conn, &root, 10).unwrap();
+        let summary: Vec<(&str, u64, u64)> = folders
+            .iter()
+            .map(|b| (b.key.as_str(), b.size, b.file_count))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("Movies", 1000, 2), ("Docs", 300, 1), ("notes", 50, 1)]
+        );
+        assert_eq!(
+            folders[0].path.as_deref(),
+            Some(&*format!("{root}{sep}Movies"))
+        );
+
+        let exts = usage_by_extension(&conn, &root, 2).unwrap();
+        let summary: Vec<(&str, u64)> = exts.iter().map(|b| (b.key.as_str(), b.size)).collect();
+        assert_eq!(summary, vec![("mov", 1000), ("pdf", 300)]);
+
+        assert_eq!(usage_totals(&conn, "/").unwrap().0, 6350);
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&case_dir);
+    }
+}
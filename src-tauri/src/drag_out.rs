@@ -0,0 +1,57 @@
+//! The file list handed to the drag plugin when a selection is dragged out
+//! of the window. The plugin turns it into one `NSDraggingItem` per file URL
+//! on macOS and a shell data object (`CF_HDROP`) on Windows, but it gives up
+//! on the whole drag if any path is missing, so the selection is cleaned
+//! first: duplicates and vanished files are dropped, and so is anything
+//! inside another selected folder, which the drop target would otherwise
+//! copy twice.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::long_path;
+
+pub(crate) fn drag_items(paths: &[String]) -> Vec<String> {
+    let selected: HashSet<&Path> = paths.iter().map(Path::new).collect();
+    let mut seen = HashSet::new();
+    paths
+        .iter()
+        .filter(|path| seen.insert(path.as_str()))
+        .filter(|path| {
+            !Path::new(path)
+                .ancestors()
+                .skip(1)
+                .any(|ancestor| selected.contains(ancestor))
+        })
+        .filter(|path| long_path::symlink_metadata(path).is_ok())
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_duplicates_missing_and_nested_paths() {
+        let root = crate::temp_case_dir("drag_out");
+        let folder = root.join("folder");
+        std::fs::create_dir_all(&folder).unwrap();
+        for file in [root.join("a.txt"), folder.join("b.txt")] {
+            std::fs::write(file, b"x").unwrap();
+        }
+        let path = |p: &Path| p.to_string_lossy().to_string();
+
+        let items = drag_items(&[
+            path(&root.join("a.txt")),
+            path(&folder.join("b.txt")),
+            path(&folder),
+            path(&root.join("a.txt")),
+            path(&root.join("gone.txt")),
+        ]);
+        assert_eq!(items, vec![path(&root.join("a.txt")), path(&folder)]);
+        assert!(drag_items(&[path(&root.join("gone.txt"))]).is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
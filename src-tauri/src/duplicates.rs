@@ -0,0 +1,314 @@
+//! `find_duplicates`: files with identical content. Candidates come from the
+//! index grouped by size, so only same-size files are ever read. Each size
+//! group is narrowed by a hash of the first and last 64 KB, and survivors
+//! are confirmed with a full SHA-256. Groups are reported as they are
+//! confirmed, biggest files first.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::file_hash::{self, HashAlgorithm, HASH_CANCELLED};
+use crate::long_path;
+use crate::search_cancel::{is_cancelled, SearchCancelToken};
+use crate::{subtree_range_bounds, AppResult};
+
+/// Empty files all match each other and free nothing; skip them by default.
+pub(crate) const DEFAULT_MIN_DUPLICATE_SIZE: u64 = 1;
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroupDto {
+    pub size: u64,
+    /// SHA-256 of the content, lowercase hex.
+    pub hash: String,
+    pub paths: Vec<String>,
+    /// Space freed by keeping one copy: size × (copies − 1).
+    pub reclaimable_bytes: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct DuplicateScan {
+    pub(crate) groups: u64,
+    pub(crate) files: u64,
+    pub(crate) reclaimable_bytes: u64,
+    /// Same-size files taken from the index.
+    pub(crate) candidates: u64,
+    pub(crate) cancelled: bool,
+}
+
+/// Indexed files (under `root`, if given) whose size is shared with at least
+/// one other, grouped by size, largest first.
+pub(crate) fn size_candidates(
+    conn: &Connection,
+    root: Option<&str>,
+    min_size: u64,
+) -> AppResult<Vec<(u64, Vec<String>)>> {
+    let mut args = vec![Value::Integer(min_size.min(i64::MAX as u64) as i64)];
+    let scope = match root {
+        Some(root) => {
+            let (lo, hi) = subtree_range_bounds(root);
+            args.push(Value::Text(lo));
+            args.push(Value::Text(hi));
+            "AND path >= ?2 AND path < ?3"
+        }
+        None => "",
+    };
+    let sql = format!(
+        "SELECT size, path FROM entries
+         WHERE is_dir = 0 {scope} AND size IN (
+             SELECT size FROM entries
+             WHERE is_dir = 0 AND size >= ?1 {scope}
+             GROUP BY size HAVING COUNT(*) > 1)
+         ORDER BY size DESC, path"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let mut rows = stmt
+        .query(params_from_iter(args.iter()))
+        .map_err(|e| e.to_string())?;
+
+    let mut groups: Vec<(u64, Vec<String>)> = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        let size: i64 = row.get(0).map_err(|e| e.to_string())?;
+        let path: String = row.get(1).map_err(|e| e.to_string())?;
+        let size = size.max(0) as u64;
+        match groups.last_mut() {
+            Some((last, paths)) if *last == size => paths.push(path),
+            _ => groups.push((size, vec![path])),
+        }
+    }
+    Ok(groups)
+}
+
+/// SHA-256 of the first and last `PARTIAL_HASH_BYTES`; for files no larger
+/// than both together, the whole content, which is then the full hash.
+fn partial_hash(path: &Path, size: u64) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = Vec::with_capacity(size.min(PARTIAL_HASH_BYTES * 2) as usize);
+    if size <= PARTIAL_HASH_BYTES * 2 {
+        file.read_to_end(&mut buf)?;
+    } else {
+        file.by_ref()
+            .take(PARTIAL_HASH_BYTES)
+            .read_to_end(&mut buf)?;
+        file.seek(SeekFrom::Start(size - PARTIAL_HASH_BYTES))?;
+        file.read_to_end(&mut buf)?;
+    }
+    hasher.update(&buf);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Drop paths that vanished, changed size, or can't be read without side
+/// effects (cloud placeholders); keep one path per hard-linked file, since
+/// deleting a link frees nothing.
+fn live_paths(size: u64, paths: Vec<String>) -> Vec<String> {
+    let mut seen_links = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|path| {
+            let Ok(meta) = long_path::symlink_metadata(path) else {
+                return false;
+            };
+            meta.is_file()
+                && meta.len() == size
+                && !is_cloud_placeholder(Path::new(path))
+                && hard_link_id(&meta).is_none_or(|id| seen_links.insert(id))
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn hard_link_id(meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn hard_link_id(_meta: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(target_os = "windows")]
+fn is_cloud_placeholder(path: &Path) -> bool {
+    crate::win::cloud::is_placeholder_path(path)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_cloud_placeholder(_path: &Path) -> bool {
+    false
+}
+
+/// Bucket `paths` by `hash`, dropping unreadable files and singletons.
+/// Buckets keep the input (path) order.
+fn bucket_by(
+    paths: Vec<String>,
+    cancel: Option<&SearchCancelToken>,
+    mut hash: impl FnMut(&str) -> AppResult<Option<String>>,
+) -> AppResult<Vec<(String, Vec<String>)>> {
+    let mut order: Vec<String> = Vec::new();
+    let mut buckets: HashMap<String, Vec<String>> = HashMap::new();
+    for path in paths {
+        if is_cancelled(cancel) {
+            return Err(HASH_CANCELLED.to_string());
+        }
+        let Some(digest) = hash(&path)? else {
+            continue;
+        };
+        let bucket = buckets.entry(digest.clone()).or_default();
+        if bucket.is_empty() {
+            order.push(digest);
+        }
+        bucket.push(path);
+    }
+    Ok(order
+        .into_iter()
+        .filter_map(|digest| {
+            let paths = buckets.remove(&digest)?;
+            (paths.len() > 1).then_some((digest, paths))
+        })
+        .collect())
+}
+
+/// Confirm duplicates among `candidates` (from `size_candidates`), calling
+/// `on_group` with each group and the running reclaimable total. A cancel
+/// stops early with `cancelled` set; groups already reported stand.
+pub(crate) fn find_duplicates(
+    candidates: Vec<(u64, Vec<String>)>,
+    cancel: Option<&SearchCancelToken>,
+    mut on_group: impl FnMut(&DuplicateGroupDto, u64),
+) -> AppResult<DuplicateScan> {
+    let mut scan = DuplicateScan {
+        candidates: candidates.iter().map(|(_, p)| p.len() as u64).sum(),
+        ..DuplicateScan::default()
+    };
+    for (size, paths) in candidates {
+        let confirmed = match confirm_group(size, paths, cancel) {
+            Ok(confirmed) => confirmed,
+            Err(e) if e == HASH_CANCELLED => {
+                scan.cancelled = true;
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        for (hash, paths) in confirmed {
+            let group = DuplicateGroupDto {
+                size,
+                hash,
+                reclaimable_bytes: size * (paths.len() as u64 - 1),
+                paths,
+            };
+            scan.groups += 1;
+            scan.files += group.paths.len() as u64;
+            scan.reclaimable_bytes += group.reclaimable_bytes;
+            on_group(&group, scan.reclaimable_bytes);
+        }
+    }
+    Ok(scan)
+}
+
+fn confirm_group(
+    size: u64,
+    paths: Vec<String>,
+    cancel: Option<&SearchCancelToken>,
+) -> AppResult<Vec<(String, Vec<String>)>> {
+    let paths = live_paths(size, paths);
+    if paths.len() < 2 {
+        return Ok(Vec::new());
+    }
+    let partial = bucket_by(paths, cancel, |path| {
+        Ok(partial_hash(Path::new(path), size).ok())
+    })?;
+    if size <= PARTIAL_HASH_BYTES * 2 {
+        return Ok(partial);
+    }
+    let mut confirmed = Vec::new();
+    for (_, paths) in partial {
+        confirmed.extend(bucket_by(
+            paths,
+            cancel,
+            |path| match file_hash::hash_file(
+                Path::new(path),
+                HashAlgorithm::Sha256,
+                cancel,
+                |_, _| {},
+            ) {
+                Ok(hash) => Ok(Some(hash)),
+                Err(e) if e == HASH_CANCELLED => Err(e),
+                Err(_) => Ok(None),
+            },
+        )?);
+    }
+    Ok(confirmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_by_size_then_confirms_by_content() {
+        let root = crate::temp_case_dir("find_duplicates");
+        std::fs::create_dir_all(root.join("copies")).unwrap();
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+
+        // Same size, same head and tail, different middle: only the full
+        // hash tells them apart.
+        let big = |middle: u8| {
+            let mut data = vec![7u8; (PARTIAL_HASH_BYTES * 3) as usize];
+            data[PARTIAL_HASH_BYTES as usize + 10] = middle;
+            data
+        };
+        let files: [(&str, Vec<u8>); 6] = [
+            ("a.bin", big(1)),
+            ("copies/a.bin", big(1)),
+            ("b.bin", big(2)),
+            ("note.txt", b"same".to_vec()),
+            ("copies/note.txt", b"same".to_vec()),
+            ("other.txt", b"diff".to_vec()),
+        ];
+        for (name, data) in &files {
+            let path = root.join(name);
+            std::fs::write(&path, data).unwrap();
+            crate::insert_test_entry(&conn, &path, false, 1);
+            crate::set_test_entry_stats(&conn, &path, None, None, Some(data.len() as i64));
+        }
+        crate::insert_test_entry(&conn, &root.join("copies"), true, 1);
+
+        let candidates = size_candidates(&conn, None, DEFAULT_MIN_DUPLICATE_SIZE).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].0, PARTIAL_HASH_BYTES * 3, "largest first");
+        let scoped = root.join("copies").to_string_lossy().to_string();
+        assert!(size_candidates(&conn, Some(&scoped), 1).unwrap().is_empty());
+
+        let mut seen = Vec::new();
+        let scan = find_duplicates(candidates, None, |group, total| {
+            seen.push((group.paths.len(), total))
+        })
+        .unwrap();
+        let big_total = PARTIAL_HASH_BYTES * 3;
+        assert_eq!(seen, vec![(2, big_total), (2, big_total + 4)]);
+        assert_eq!(
+            scan,
+            DuplicateScan {
+                groups: 2,
+                files: 4,
+                reclaimable_bytes: big_total + 4,
+                candidates: 6,
+                cancelled: false,
+            }
+        );
+
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,400 @@
+//! `everything --es …`: a drop-in for voidtools' `es.exe` command line, so
+//! scripts and launcher integrations written against Everything can query
+//! this index instead. It reads the index DB directly, like `--mcp`, and
+//! understands the common `es` switches (either `-` or `/`, any case):
+//!
+//! `-n`/`-max-results <n>`, `-o`/`-offset <n>`, `-p`/`-match-path`,
+//! `-path <folder>`, `/ad` (folders), `/a-d` (files), `-s` (sort by path),
+//! `-sort <name|path|size|date-modified>`, `-sort-<key>[-ascending|-descending]`,
+//! `-sort-ascending`, `-sort-descending`, `-size`, `-dm`/`-date-modified`,
+//! `-csv`, `-tsv`, `-json`, `-get-result-count`, `-h`/`-help`.
+//!
+//! Exit codes follow `es`: 4 for a switch missing its value, 6 for an unknown
+//! switch, 7 when the search fails and 8 when no usable index exists (es's
+//! "Everything IPC window not found").
+
+use std::io::Write;
+
+use rusqlite::Connection;
+
+use crate::export::{ExportColumn, ExportFormat, ResultWriter};
+use crate::mcp_server::{default_db_path, index_is_usable, open_search_connection};
+use crate::query::parse_query;
+use crate::{fts_usable, resolve_home_dir, run_db_search, sort_entries_with_relevance, EntryDto};
+
+const ES_DEFAULT_LIMIT: u32 = 1000;
+/// Rows fetched per round when `/ad` or `/a-d` filters them afterwards.
+const ES_PAGE: u32 = 500;
+
+const EXIT_MISSING_VALUE: i32 = 4;
+const EXIT_UNKNOWN_SWITCH: i32 = 6;
+const EXIT_SEARCH_FAILED: i32 = 7;
+const EXIT_NO_INDEX: i32 = 8;
+
+const USAGE: &str = "\
+Usage: everything --es [options] <search text>
+
+  -n, -max-results <n>    Show at most <n> results.
+  -o, -offset <n>         Skip the first <n> results.
+  -p, -match-path         Match the full path instead of the name.
+  -path <folder>          Search inside <folder>.
+  /ad, /a-d               Folders only / files only.
+  -s                      Sort by full path.
+  -sort <key>             name, path, size or date-modified.
+  -sort-ascending, -sort-descending
+  -size, -dm              Show the size / date-modified columns.
+  -csv, -tsv, -json       Write results in that format.
+  -get-result-count       Print the number of results only.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Attributes {
+    Any,
+    FoldersOnly,
+    FilesOnly,
+}
+
+#[derive(Debug, PartialEq)]
+struct EsOptions {
+    query: String,
+    limit: u32,
+    offset: u32,
+    sort_by: &'static str,
+    sort_dir: &'static str,
+    attributes: Attributes,
+    size: bool,
+    date_modified: bool,
+    format: Option<ExportFormat>,
+    count_only: bool,
+    help: bool,
+}
+
+#[derive(Debug, PartialEq)]
+struct EsError {
+    code: i32,
+    message: String,
+}
+
+impl EsError {
+    fn new(code: i32, message: String) -> Self {
+        EsError { code, message }
+    }
+}
+
+fn sort_key(name: &str) -> Option<&'static str> {
+    match name {
+        "name" => Some("name"),
+        "path" | "full-path" => Some("dir"),
+        "size" => Some("size"),
+        "date-modified" | "dm" => Some("mtime"),
+        _ => None,
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<EsOptions, EsError> {
+    let mut opts = EsOptions {
+        query: String::new(),
+        limit: ES_DEFAULT_LIMIT,
+        offset: 0,
+        sort_by: "name",
+        sort_dir: "asc",
+        attributes: Attributes::Any,
+        size: false,
+        date_modified: false,
+        format: None,
+        count_only: false,
+        help: false,
+    };
+    let mut terms: Vec<String> = Vec::new();
+    let mut match_path = false;
+    let mut folder: Option<String> = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(switch) = arg
+            .strip_prefix('-')
+            .or_else(|| arg.strip_prefix('/'))
+            .filter(|s| !s.is_empty())
+        else {
+            terms.push(arg.clone());
+            continue;
+        };
+        let switch = switch.to_ascii_lowercase();
+        let mut value = |name: &str| {
+            iter.next().cloned().ok_or_else(|| {
+                EsError::new(EXIT_MISSING_VALUE, format!("-{name} expects a value."))
+            })
+        };
+        let number = |name: &str, raw: String| {
+            raw.parse::<u32>().map_err(|_| {
+                EsError::new(
+                    EXIT_MISSING_VALUE,
+                    format!("-{name} expects a number, got {raw:?}."),
+                )
+            })
+        };
+        match switch.as_str() {
+            "n" | "max-results" => opts.limit = number(&switch, value(&switch)?)?,
+            "o" | "offset" => opts.offset = number(&switch, value(&switch)?)?,
+            "p" | "match-path" => match_path = true,
+            "path" | "parent-path" => folder = Some(value(&switch)?),
+            "ad" => opts.attributes = Attributes::FoldersOnly,
+            "a-d" => opts.attributes = Attributes::FilesOnly,
+            "s" => opts.sort_by = "dir",
+            "sort" => {
+                let key = value(&switch)?;
+                opts.sort_by = sort_key(&key.to_ascii_lowercase()).ok_or_else(|| {
+                    EsError::new(EXIT_UNKNOWN_SWITCH, format!("Unknown sort {key:?}."))
+                })?;
+            }
+            "sort-ascending" => opts.sort_dir = "asc",
+            "sort-descending" => opts.sort_dir = "desc",
+            "size" => opts.size = true,
+            "dm" | "date-modified" => opts.date_modified = true,
+            "csv" => opts.format = Some(ExportFormat::Csv),
+            "tsv" => opts.format = Some(ExportFormat::Tsv),
+            "json" => opts.format = Some(ExportFormat::Json),
+            "get-result-count" => opts.count_only = true,
+            "h" | "help" | "?" => opts.help = true,
+            other => {
+                let sorted = other.strip_prefix("sort-").and_then(|rest| {
+                    let (key, dir) = match rest.rsplit_once('-') {
+                        Some((key, "ascending")) => (key, "asc"),
+                        Some((key, "descending")) => (key, "desc"),
+                        _ => (rest, opts.sort_dir),
+                    };
+                    sort_key(key).map(|key| (key, dir))
+                });
+                let Some((key, dir)) = sorted else {
+                    // `/usr/bin`-style terms are paths, not switches.
+                    if arg.starts_with('/') {
+                        terms.push(arg.clone());
+                        continue;
+                    }
+                    return Err(EsError::new(
+                        EXIT_UNKNOWN_SWITCH,
+                        format!("Unknown switch: {arg}"),
+                    ));
+                };
+                opts.sort_by = key;
+                opts.sort_dir = dir;
+            }
+        }
+    }
+
+    let text = terms.join(" ");
+    opts.query = match folder {
+        Some(folder) => {
+            let folder = folder.trim_end_matches(['/', '\\']);
+            format!("{folder}{}{text}", std::path::MAIN_SEPARATOR)
+        }
+        None if match_path && !text.is_empty() => format!("path:{text}"),
+        None => text,
+    };
+    opts.limit = opts.limit.clamp(1, crate::MAX_LIMIT);
+    Ok(opts)
+}
+
+fn keep(attributes: Attributes, entry: &EntryDto) -> bool {
+    match attributes {
+        Attributes::Any => true,
+        Attributes::FoldersOnly => entry.is_dir,
+        Attributes::FilesOnly => !entry.is_dir,
+    }
+}
+
+fn search(conn: &Connection, opts: &EsOptions) -> Result<Vec<EntryDto>, String> {
+    let home_dir = resolve_home_dir();
+    let mode = parse_query(&opts.query);
+    let fts_ready = fts_usable(conn);
+    let page = if opts.attributes == Attributes::Any {
+        opts.limit
+    } else {
+        opts.limit.max(ES_PAGE)
+    };
+    let mut results = Vec::new();
+    let mut skipped = 0u32;
+    let mut db_offset = 0u32;
+    loop {
+        let mut batch = run_db_search(
+            conn,
+            &home_dir,
+            fts_ready,
+            &mode,
+            &opts.query,
+            page,
+            db_offset,
+            opts.sort_by,
+            opts.sort_dir,
+            None,
+        )?;
+        if db_offset == 0 && opts.sort_by == "name" {
+            sort_entries_with_relevance(&mut batch, &opts.query, opts.sort_by, opts.sort_dir);
+        }
+        let fetched = batch.len() as u32;
+        for entry in batch.into_iter().filter(|e| keep(opts.attributes, e)) {
+            if skipped < opts.offset {
+                skipped += 1;
+            } else if results.len() < opts.limit as usize {
+                results.push(entry);
+            }
+        }
+        db_offset += fetched;
+        if fetched < page || results.len() >= opts.limit as usize {
+            return Ok(results);
+        }
+    }
+}
+
+fn format_date(conn: &Connection, mtime: Option<i64>) -> String {
+    mtime
+        .and_then(|t| {
+            conn.query_row(
+                "SELECT strftime('%Y-%m-%d %H:%M', ?1, 'unixepoch', 'localtime')",
+                [t],
+                |row| row.get::<_, String>(0),
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+fn write_results(
+    out: &mut impl Write,
+    conn: &Connection,
+    opts: &EsOptions,
+    results: &[EntryDto],
+) -> Result<(), String> {
+    if let Some(format) = opts.format {
+        let mut columns = vec![ExportColumn::Path];
+        if opts.size {
+            columns.push(ExportColumn::Size);
+        }
+        if opts.date_modified {
+            columns.push(ExportColumn::Mtime);
+        }
+        let mut writer = ResultWriter::new(out, format, columns)?;
+        for entry in results {
+            writer.write_row(entry)?;
+        }
+        writer.finish()?;
+        return Ok(());
+    }
+    for entry in results {
+        let mut line = String::new();
+        if opts.size {
+            let size = entry.size.map(|s| s.to_string()).unwrap_or_default();
+            line.push_str(&format!("{size:>15} "));
+        }
+        if opts.date_modified {
+            line.push_str(&format!("{:<16} ", format_date(conn, entry.mtime)));
+        }
+        line.push_str(&entry.path);
+        writeln!(out, "{line}").map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+/// Release builds on Windows are GUI-subsystem binaries with no console;
+/// borrow the calling shell's so output shows up when not redirected.
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{
+        AttachConsole, GetStdHandle, ATTACH_PARENT_PROCESS, STD_OUTPUT_HANDLE,
+    };
+    unsafe {
+        let redirected =
+            GetStdHandle(STD_OUTPUT_HANDLE).is_ok_and(|h| !h.is_invalid() && !h.0.is_null());
+        if !redirected {
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+    }
+}
+
+/// Run one `es`-style query and return the process exit code.
+pub fn run(args: &[String]) -> i32 {
+    #[cfg(target_os = "windows")]
+    attach_parent_console();
+
+    let opts = match parse_args(args) {
+        Ok(opts) => opts,
+        Err(err) => {
+            eprintln!("{}\n\n{USAGE}", err.message);
+            return err.code;
+        }
+    };
+    if opts.help || opts.query.trim().is_empty() {
+        println!("{USAGE}");
+        return 0;
+    }
+    let db_path = default_db_path();
+    if !index_is_usable(&db_path) {
+        eprintln!("The Everything index is not ready. Launch the app to build it.");
+        return EXIT_NO_INDEX;
+    }
+    let outcome = open_search_connection(&db_path).and_then(|conn| {
+        let results = search(&conn, &opts)?;
+        let stdout = std::io::stdout();
+        let mut out = stdout.lock();
+        if opts.count_only {
+            writeln!(out, "{}", results.len()).map_err(|e| e.to_string())
+        } else {
+            write_results(&mut out, &conn, &opts, &results)
+        }
+    });
+    match outcome {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("Search failed: {e}");
+            EXIT_SEARCH_FAILED
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_es_switches() {
+        let opts = parse_args(&args(&[
+            "-n",
+            "5",
+            "/a-d",
+            "-sort-size-descending",
+            "-DM",
+            "report",
+            "pdf",
+        ]))
+        .unwrap();
+        assert_eq!(opts.query, "report pdf");
+        assert_eq!(opts.limit, 5);
+        assert_eq!(opts.attributes, Attributes::FilesOnly);
+        assert_eq!((opts.sort_by, opts.sort_dir), ("size", "desc"));
+        assert!(opts.date_modified && !opts.size);
+
+        let opts =
+            parse_args(&args(&["-p", "src/main", "-sort", "date-modified", "-csv"])).unwrap();
+        assert_eq!(opts.query, "path:src/main");
+        assert_eq!(opts.sort_by, "mtime");
+        assert_eq!(opts.format, Some(ExportFormat::Csv));
+
+        let opts = parse_args(&args(&["-path", "/home/u/docs/", "*.pdf"])).unwrap();
+        assert_eq!(
+            opts.query,
+            format!("/home/u/docs{}*.pdf", std::path::MAIN_SEPARATOR)
+        );
+
+        assert_eq!(parse_args(&args(&["/usr/lib"])).unwrap().query, "/usr/lib");
+        assert_eq!(
+            parse_args(&args(&["-n"])).unwrap_err().code,
+            EXIT_MISSING_VALUE
+        );
+        assert_eq!(
+            parse_args(&args(&["-bogus"])).unwrap_err().code,
+            EXIT_UNKNOWN_SWITCH
+        );
+    }
+}
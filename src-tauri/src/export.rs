@@ -0,0 +1,251 @@
+//! Result-set export writers for `export_results`. Rows arrive page by page
+//! and are written straight through, so memory stays flat however many rows
+//! the query matches.
+
+use std::io::Write;
+
+use crate::{AppResult, EntryDto};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl ExportFormat {
+    pub(crate) fn parse(value: &str) -> AppResult<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "tsv" => Ok(ExportFormat::Tsv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("Unknown export format: {other}")),
+        }
+    }
+
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportColumn {
+    Path,
+    Name,
+    Size,
+    Mtime,
+    Ext,
+}
+
+const DEFAULT_COLUMNS: &[ExportColumn] = &[
+    ExportColumn::Path,
+    ExportColumn::Name,
+    ExportColumn::Size,
+    ExportColumn::Mtime,
+    ExportColumn::Ext,
+];
+
+impl ExportColumn {
+    fn key(self) -> &'static str {
+        match self {
+            ExportColumn::Path => "path",
+            ExportColumn::Name => "name",
+            ExportColumn::Size => "size",
+            ExportColumn::Mtime => "mtime",
+            ExportColumn::Ext => "ext",
+        }
+    }
+}
+
+/// Columns in the caller's order; `None` or empty selects all of them.
+pub(crate) fn parse_columns(columns: Option<&[String]>) -> AppResult<Vec<ExportColumn>> {
+    let Some(columns) = columns.filter(|c| !c.is_empty()) else {
+        return Ok(DEFAULT_COLUMNS.to_vec());
+    };
+    let mut parsed = Vec::with_capacity(columns.len());
+    for column in columns {
+        let col = DEFAULT_COLUMNS
+            .iter()
+            .copied()
+            .find(|c| c.key().eq_ignore_ascii_case(column.trim()))
+            .ok_or_else(|| format!("Unknown export column: {column}"))?;
+        if !parsed.contains(&col) {
+            parsed.push(col);
+        }
+    }
+    Ok(parsed)
+}
+
+pub(crate) struct ResultWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    columns: Vec<ExportColumn>,
+    rows: u64,
+}
+
+impl<W: Write> ResultWriter<W> {
+    pub(crate) fn new(out: W, format: ExportFormat, columns: Vec<ExportColumn>) -> AppResult<Self> {
+        let mut writer = ResultWriter {
+            out,
+            format,
+            columns,
+            rows: 0,
+        };
+        match format {
+            ExportFormat::Json => writer.out.write_all(b"[").map_err(|e| e.to_string())?,
+            ExportFormat::Csv | ExportFormat::Tsv => {
+                let header: Vec<&str> = writer.columns.iter().map(|c| c.key()).collect();
+                writer.write_delimited(&header)?;
+            }
+        }
+        Ok(writer)
+    }
+
+    pub(crate) fn write_row(&mut self, entry: &EntryDto) -> AppResult<()> {
+        if self.format == ExportFormat::Json {
+            let mut obj = serde_json::Map::new();
+            for col in &self.columns {
+                let value = match col {
+                    ExportColumn::Path => entry.path.clone().into(),
+                    ExportColumn::Name => entry.name.clone().into(),
+                    ExportColumn::Size => entry.size.into(),
+                    ExportColumn::Mtime => entry.mtime.into(),
+                    ExportColumn::Ext => entry.ext.clone().into(),
+                };
+                obj.insert(col.key().to_string(), value);
+            }
+            let sep: &[u8] = if self.rows == 0 { b"\n" } else { b",\n" };
+            self.out.write_all(sep).map_err(|e| e.to_string())?;
+            serde_json::to_writer(&mut self.out, &obj).map_err(|e| e.to_string())?;
+        } else {
+            let fields: Vec<String> = self
+                .columns
+                .iter()
+                .map(|col| match col {
+                    ExportColumn::Path => entry.path.clone(),
+                    ExportColumn::Name => entry.name.clone(),
+                    ExportColumn::Size => entry.size.map(|v| v.to_string()).unwrap_or_default(),
+                    ExportColumn::Mtime => entry.mtime.map(|v| v.to_string()).unwrap_or_default(),
+                    ExportColumn::Ext => entry.ext.clone().unwrap_or_default(),
+                })
+                .collect();
+            let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+            self.write_delimited(&fields)?;
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Close the document and flush; returns the number of rows written.
+    pub(crate) fn finish(mut self) -> AppResult<u64> {
+        if self.format == ExportFormat::Json {
+            self.out.write_all(b"\n]\n").map_err(|e| e.to_string())?;
+        }
+        self.out.flush().map_err(|e| e.to_string())?;
+        Ok(self.rows)
+    }
+
+    fn write_delimited(&mut self, fields: &[&str]) -> AppResult<()> {
+        let line = match self.format {
+            ExportFormat::Tsv => fields
+                .iter()
+                .map(|f| defuse_formula(&f.replace(['\t', '\n', '\r'], " ")))
+                .collect::<Vec<_>>()
+                .join("\t"),
+            _ => fields
+                .iter()
+                .map(|f| csv_field(f))
+                .collect::<Vec<_>>()
+                .join(","),
+        };
+        self.out
+            .write_all(line.as_bytes())
+            .map_err(|e| e.to_string())?;
+        self.out.write_all(b"\n").map_err(|e| e.to_string())
+    }
+}
+
+/// RFC 4180 quoting: only fields containing a delimiter, quote or newline.
+fn csv_field(field: &str) -> String {
+    let field = defuse_formula(field);
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// Prefix `'` to a cell a spreadsheet would read as a formula, since file
+/// names are attacker-controlled (`=HYPERLINK(...)` is a valid name).
+fn defuse_formula(field: &str) -> String {
+    if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, size: Option<i64>) -> EntryDto {
+        EntryDto {
+            ext: Some("txt".to_string()),
+            size,
+            mtime: Some(1_700_000_000),
+            ..crate::test_entry(path)
+        }
+    }
+
+    fn render(format: ExportFormat, columns: &[&str]) -> String {
+        let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+        let mut buf = Vec::new();
+        let mut writer =
+            ResultWriter::new(&mut buf, format, parse_columns(Some(&columns)).unwrap()).unwrap();
+        writer.write_row(&entry("/a/b,c.txt", Some(3))).unwrap();
+        writer.write_row(&entry("/a/\"q\".txt", None)).unwrap();
+        assert_eq!(writer.finish().unwrap(), 2);
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn writes_selected_columns_in_each_format() {
+        assert_eq!(
+            render(ExportFormat::Csv, &["name", "size"]),
+            "name,size\n\"b,c.txt\",3\n\"\"\"q\"\".txt\",\n"
+        );
+        assert_eq!(
+            render(ExportFormat::Tsv, &["size", "path"]),
+            "size\tpath\n3\t/a/b,c.txt\n\t/a/\"q\".txt\n"
+        );
+        assert_eq!(
+            render(ExportFormat::Json, &["name", "size"]),
+            "[\n{\"name\":\"b,c.txt\",\"size\":3},\n{\"name\":\"\\\"q\\\".txt\",\"size\":null}\n]\n"
+        );
+
+        assert_eq!(parse_columns(None).unwrap(), DEFAULT_COLUMNS.to_vec());
+        assert!(parse_columns(Some(&["owner".to_string()])).is_err());
+        assert!(ExportFormat::parse("xlsx").is_err());
+    }
+
+    #[test]
+    fn defuses_formula_cells() {
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+        assert_eq!(csv_field("@SUM(A1)"), "'@SUM(A1)");
+        assert_eq!(csv_field("-x,y"), "\"'-x,y\"");
+        assert_eq!(csv_field("\rcmd"), "\"'\rcmd\"");
+        assert_eq!(csv_field("a=b"), "a=b");
+
+        let mut buf = Vec::new();
+        let columns = parse_columns(Some(&["name".to_string()])).unwrap();
+        let mut writer = ResultWriter::new(&mut buf, ExportFormat::Tsv, columns).unwrap();
+        writer.write_row(&entry("/a/+cmd.txt", None)).unwrap();
+        writer.finish().unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "name\n'+cmd.txt\n");
+    }
+}
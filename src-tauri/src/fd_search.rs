@@ -9,7 +9,8 @@ use std::os::unix::ffi::OsStrExt;
 
 use jwalk::WalkDir;
 
-use crate::{should_skip_path, EntryDto, IgnorePattern};
+use crate::glob_class::CharClass;
+use crate::{should_skip_path, EntryDto, IgnorePattern, PartialFlush};
 
 #[derive(Debug)]
 pub struct FdSearchCache {
@@ -61,7 +62,7 @@ fn parse_live_query(query: &str) -> QueryMode {
 
         let name_mode = if name_part.is_empty() {
             NameMatch::Any
-        } else if name_part.contains('*') || name_part.contains('?') {
+        } else if crate::contains_glob_meta(name_part) {
             NameMatch::Glob(name_part.to_lowercase())
         } else {
             NameMatch::Terms(
@@ -86,7 +87,7 @@ fn parse_live_query(query: &str) -> QueryMode {
         };
     }
 
-    if trimmed.contains('*') || trimmed.contains('?') {
+    if crate::contains_glob_meta(trimmed) {
         return QueryMode::GlobName(trimmed.to_lowercase());
     }
 
@@ -218,7 +219,14 @@ fn glob_matches(pattern: &str, text: &str) -> bool {
     let mut star_tx = 0usize;
 
     while tx < tn {
-        if px < pn && (p[px] == '?' || p[px] == t[tx]) {
+        let class_hit = (px < pn && p[px] == '[')
+            .then(|| CharClass::parse(&p, px))
+            .flatten()
+            .map(|(class, end)| (class.matches(t[tx]), end));
+        if let Some((true, end)) = class_hit {
+            px = end;
+            tx += 1;
+        } else if px < pn && class_hit.is_none() && (p[px] == '?' || p[px] == t[tx]) {
             px += 1;
             tx += 1;
         } else if px < pn && p[px] == '*' {
@@ -286,6 +294,7 @@ pub fn run_fd_search(
     query: &str,
     sort_by: &str,
     sort_dir: &str,
+    mut partial: Option<&mut PartialFlush<'_>>,
 ) -> LiveSearchResult {
     let trimmed = query.trim();
     if trimmed.is_empty() {
@@ -380,6 +389,10 @@ pub fn run_fd_search(
 
         let meta = dir_entry.metadata().ok();
         let size = meta.as_ref().filter(|m| m.is_file()).map(|m| m.len() as i64);
+        let cloud_only = meta
+            .as_ref()
+            .is_some_and(crate::is_cloud_only_metadata)
+            .then_some(true);
         let mtime = meta
             .and_then(|m| m.modified().ok())
             .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
@@ -398,11 +411,18 @@ pub fn run_fd_search(
             ext,
             size,
             mtime,
+            spoken_label: None,
+            mtime_display: None,
+            cloud_only,
+            linked_from: None,
         });
 
         if entries.len() >= MAX_COLLECT {
             break;
         }
+        if let Some(p) = partial.as_mut() {
+            p.maybe_flush(&entries);
+        }
     }
 
     let query_lower = trimmed.to_lowercase();
@@ -491,6 +511,17 @@ mod tests {
         assert!(!glob_matches("t*t?.md", "test.md"));
     }
 
+    #[test]
+    fn glob_matches_class() {
+        assert!(glob_matches("img_[0-9]*", "img_2024.jpg"));
+        assert!(!glob_matches("img_[0-9]*", "img_new.jpg"));
+        assert!(glob_matches("*.[!o]", "main.c"));
+        assert!(!glob_matches("*.[!o]", "main.o"));
+        // unclosed: a literal bracket
+        assert!(glob_matches("a[b*", "a[bc"));
+        assert!(matches!(parse_live_query("IMG_[0-9]*"), QueryMode::GlobName(_)));
+    }
+
     #[test]
     fn glob_matches_exact() {
         assert!(glob_matches("hello", "hello"));
@@ -0,0 +1,202 @@
+//! File checksums for `compute_hash`, so a download can be verified straight
+//! from the results list. The digests come from `md-5`, `sha2` and `blake3`;
+//! files are streamed in fixed-size chunks, so memory stays
+//! flat and the caller can report progress and cancel between chunks.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::search_cancel::{is_cancelled, SearchCancelToken};
+use crate::AppResult;
+
+pub(crate) const HASH_CANCELLED: &str = "Hash cancelled.";
+
+const READ_CHUNK: usize = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashAlgorithm {
+    Md5,
+    Sha256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub(crate) fn parse(value: &str) -> AppResult<Self> {
+        match value.to_ascii_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "blake3" => Ok(HashAlgorithm::Blake3),
+            other => Err(format!("Unknown hash algorithm: {other}")),
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+}
+
+enum StreamHasher {
+    Md5(Md5),
+    Sha256(Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl StreamHasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => StreamHasher::Md5(Md5::new()),
+            HashAlgorithm::Sha256 => StreamHasher::Sha256(Sha256::new()),
+            HashAlgorithm::Blake3 => StreamHasher::Blake3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            StreamHasher::Md5(h) => h.update(data),
+            StreamHasher::Sha256(h) => h.update(data),
+            StreamHasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            StreamHasher::Md5(h) => h.finalize().to_vec(),
+            StreamHasher::Sha256(h) => h.finalize().to_vec(),
+            StreamHasher::Blake3(h) => h.finalize().as_bytes().to_vec(),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Lowercase hex digest of the file at `path`. `progress` is called after
+/// every chunk with (bytes hashed, file size).
+pub(crate) fn hash_file(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    cancel: Option<&SearchCancelToken>,
+    mut progress: impl FnMut(u64, u64),
+) -> AppResult<String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let meta = file.metadata().map_err(|e| e.to_string())?;
+    if meta.is_dir() {
+        return Err(format!("Not a file: {}", path.display()));
+    }
+    let total = meta.len();
+    let mut hasher = StreamHasher::new(algorithm);
+    let mut buf = vec![0u8; READ_CHUNK];
+    let mut done = 0u64;
+    loop {
+        if is_cancelled(cancel) {
+            return Err(HASH_CANCELLED.to_string());
+        }
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        hasher.update(&buf[..n]);
+        done += n as u64;
+        progress(done, total);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_bytes(algorithm: HashAlgorithm, data: &[u8]) -> String {
+        let mut hasher = StreamHasher::new(algorithm);
+        hasher.update(data);
+        to_hex(&hasher.finalize())
+    }
+
+    #[test]
+    fn digests_match_reference_vectors() {
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Md5, b""),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
+        assert_eq!(
+            hash_bytes(
+                HashAlgorithm::Md5,
+                b"The quick brown fox jumps over the lazy dog"
+            ),
+            "9e107d9d372bb6826bd81d3542a419d6"
+        );
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Sha256, b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Blake3, b""),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            hash_bytes(HashAlgorithm::Blake3, b"abc"),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+
+        // Three chunks (exercises the subtree merge) fed in uneven pieces; the
+        // input pattern is the one used by the official BLAKE3 test vectors.
+        let data: Vec<u8> = (0..3072u32).map(|i| (i % 251) as u8).collect();
+        let mut streamed = StreamHasher::new(HashAlgorithm::Blake3);
+        for piece in data.chunks(333) {
+            streamed.update(piece);
+        }
+        assert_eq!(
+            to_hex(&streamed.finalize()),
+            "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2"
+        );
+
+        assert_eq!(
+            HashAlgorithm::parse("SHA-256").unwrap(),
+            HashAlgorithm::Sha256
+        );
+        assert!(HashAlgorithm::parse("crc32").is_err());
+    }
+
+    #[test]
+    fn hash_file_streams_with_progress_and_honours_cancel() {
+        let dir = crate::temp_case_dir("file_hash");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("data.bin");
+        std::fs::write(&path, vec![7u8; READ_CHUNK + 10]).unwrap();
+
+        let mut reports = Vec::new();
+        let digest = hash_file(&path, HashAlgorithm::Md5, None, |done, total| {
+            reports.push((done, total))
+        })
+        .unwrap();
+        assert_eq!(
+            digest,
+            hash_bytes(HashAlgorithm::Md5, &vec![7u8; READ_CHUNK + 10])
+        );
+        let total = (READ_CHUNK + 10) as u64;
+        assert_eq!(reports, vec![(READ_CHUNK as u64, total), (total, total)]);
+
+        let registry = crate::search_cancel::SearchCancelRegistry::default();
+        registry.cancel(1);
+        let active = registry.begin(Some(1));
+        assert_eq!(
+            hash_file(&path, HashAlgorithm::Sha256, Some(&active.token), |_, _| {}),
+            Err(HASH_CANCELLED.to_string())
+        );
+        assert!(hash_file(&dir, HashAlgorithm::Sha256, None, |_, _| {}).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,285 @@
+//! Filesystem side of `batch_move` / `batch_copy`: one selected item at a
+//! time into a destination folder. A move is a rename when source and
+//! destination share a volume and a copy followed by deleting the source
+//! otherwise. The DB update and watcher suppression live with the commands.
+//! `create_link` is the filesystem side of `create_symlink`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::long_path;
+use crate::preflight::OperationKind;
+use crate::search_cancel::{is_cancelled, SearchCancelToken};
+use crate::{validate_new_name, AppResult};
+
+pub(crate) const TRANSFER_CANCELLED: &str = "Transfer cancelled.";
+
+/// Number of files (and symlinks) under `source`, for per-file progress
+/// totals. Folders are not counted: creating one is instant.
+pub(crate) fn count_files(source: &Path) -> u64 {
+    WalkDir::new(source)
+        .follow_links(false)
+        .into_iter()
+        .flatten()
+        .filter(|e| !e.file_type().is_dir())
+        .count() as u64
+}
+
+/// Move or copy `source` into `dest_dir`, keeping its name. Returns the new
+/// path. A copy interrupted by an error or cancellation is removed again, and
+/// a cross-volume move deletes the source only after the copy completed.
+/// `progress` is called with each destination file as it is written; a
+/// same-volume move is a single rename and reports nothing.
+pub(crate) fn transfer_path(
+    kind: OperationKind,
+    source: &Path,
+    dest_dir: &Path,
+    cancel: Option<&SearchCancelToken>,
+    progress: impl FnMut(&Path),
+) -> AppResult<PathBuf> {
+    let name = source
+        .file_name()
+        .ok_or_else(|| format!("Cannot transfer {}", source.display()))?;
+    let dest = dest_dir.join(name);
+    if dest == source {
+        return Err("Source and destination are the same.".to_string());
+    }
+    if long_path::symlink_metadata(&dest).is_ok() {
+        return Err(format!("{} already exists.", dest.display()));
+    }
+    if is_cancelled(cancel) {
+        return Err(TRANSFER_CANCELLED.to_string());
+    }
+
+    if kind == OperationKind::Move {
+        match fs::rename(source, &dest) {
+            Ok(()) => return Ok(dest),
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    if let Err(e) = copy_tree(source, &dest, cancel, progress) {
+        remove_path(&dest);
+        return Err(e);
+    }
+    if kind == OperationKind::Move {
+        let meta = long_path::symlink_metadata(source).map_err(|e| e.to_string())?;
+        if meta.is_dir() {
+            fs::remove_dir_all(source)
+        } else {
+            fs::remove_file(source)
+        }
+        .map_err(|e| format!("Copied, but could not remove the original: {e}"))?;
+    }
+    Ok(dest)
+}
+
+/// Recursively copy `source` to `dest` (which must not exist). Symlinks are
+/// recreated as links rather than followed.
+fn copy_tree(
+    source: &Path,
+    dest: &Path,
+    cancel: Option<&SearchCancelToken>,
+    mut progress: impl FnMut(&Path),
+) -> AppResult<()> {
+    for entry in WalkDir::new(source).follow_links(false) {
+        if is_cancelled(cancel) {
+            return Err(TRANSFER_CANCELLED.to_string());
+        }
+        let entry = entry.map_err(|e| e.to_string())?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(|e| e.to_string())?;
+        let target = if relative.as_os_str().is_empty() {
+            dest.to_path_buf()
+        } else {
+            dest.join(relative)
+        };
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir(&target).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if file_type.is_symlink() {
+            copy_symlink(entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target).map_err(|e| e.to_string())?;
+        }
+        progress(&target);
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(link: &Path, target: &Path) -> AppResult<()> {
+    let points_to = fs::read_link(link).map_err(|e| e.to_string())?;
+    std::os::unix::fs::symlink(points_to, target).map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+fn copy_symlink(link: &Path, target: &Path) -> AppResult<()> {
+    let points_to = fs::read_link(link).map_err(|e| e.to_string())?;
+    let resolved = link
+        .parent()
+        .map_or(points_to.clone(), |p| p.join(&points_to));
+    if resolved.is_dir() {
+        std::os::windows::fs::symlink_dir(points_to, target)
+    } else {
+        std::os::windows::fs::symlink_file(points_to, target)
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Create a link to `target` inside `dest_dir`, named `name` or after the
+/// target: a symlink on Unix, an Explorer `.lnk` shortcut on Windows (where
+/// symlinks need Developer Mode). Returns the link's path.
+pub(crate) fn create_link(
+    target: &Path,
+    dest_dir: &Path,
+    name: Option<&str>,
+) -> AppResult<PathBuf> {
+    if long_path::symlink_metadata(target).is_err() {
+        return Err(format!("{} does not exist.", target.display()));
+    }
+    if !dest_dir.is_dir() {
+        return Err("Destination folder does not exist.".to_string());
+    }
+    let name = match name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => validate_new_name(name)?,
+        None => target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Cannot link to {}", target.display()))?,
+    };
+    #[cfg(windows)]
+    let name = if name.to_ascii_lowercase().ends_with(".lnk") {
+        name
+    } else {
+        format!("{name}.lnk")
+    };
+    let link = dest_dir.join(name);
+    if link == target {
+        return Err("Link and target are the same.".to_string());
+    }
+    if long_path::symlink_metadata(&link).is_ok() {
+        return Err(format!("{} already exists.", link.display()));
+    }
+    make_link(target, &link)?;
+    Ok(link)
+}
+
+#[cfg(unix)]
+fn make_link(target: &Path, link: &Path) -> AppResult<()> {
+    std::os::unix::fs::symlink(target, link).map_err(|e| e.to_string())
+}
+
+#[cfg(windows)]
+fn make_link(target: &Path, link: &Path) -> AppResult<()> {
+    crate::win::shortcut::create_shortcut(target, link)
+}
+
+fn remove_path(path: &Path) {
+    match long_path::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => {
+            let _ = fs::remove_dir_all(path);
+        }
+        Ok(_) => {
+            let _ = fs::remove_file(path);
+        }
+        Err(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_and_moves_trees_and_cleans_up_cancelled_copies() {
+        let root = crate::temp_case_dir("file_ops_transfer");
+        let src = root.join("src");
+        let dest = root.join("dest");
+        fs::create_dir_all(src.join("album").join("raw")).unwrap();
+        fs::create_dir_all(&dest).unwrap();
+        fs::write(src.join("album").join("a.jpg"), b"aaa").unwrap();
+        fs::write(src.join("album").join("raw").join("a.cr2"), b"raw").unwrap();
+        fs::write(src.join("note.txt"), b"note").unwrap();
+
+        assert_eq!(count_files(&src.join("album")), 2);
+        let mut written = Vec::new();
+        let copied = transfer_path(OperationKind::Copy, &src.join("album"), &dest, None, |p| {
+            written.push(p.to_path_buf())
+        })
+        .unwrap();
+        assert_eq!(copied, dest.join("album"));
+        written.sort();
+        assert_eq!(
+            written,
+            vec![copied.join("a.jpg"), copied.join("raw").join("a.cr2")]
+        );
+        assert_eq!(fs::read(copied.join("raw").join("a.cr2")).unwrap(), b"raw");
+        assert!(src.join("album").join("a.jpg").exists());
+
+        // The destination now holds "album": a second copy must not merge.
+        assert!(
+            transfer_path(OperationKind::Copy, &src.join("album"), &dest, None, |_| {}).is_err()
+        );
+
+        let moved = transfer_path(
+            OperationKind::Move,
+            &src.join("note.txt"),
+            &dest,
+            None,
+            |_| {},
+        )
+        .unwrap();
+        assert_eq!(fs::read(&moved).unwrap(), b"note");
+        assert!(!src.join("note.txt").exists());
+
+        let registry = crate::search_cancel::SearchCancelRegistry::default();
+        registry.cancel(1);
+        let active = registry.begin(Some(1));
+        let other = root.join("other");
+        fs::create_dir_all(&other).unwrap();
+        assert_eq!(
+            transfer_path(
+                OperationKind::Copy,
+                &src.join("album"),
+                &other,
+                Some(&active.token),
+                |_| {}
+            ),
+            Err(TRANSFER_CANCELLED.to_string())
+        );
+        assert!(!other.join("album").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn creates_named_and_default_symlinks() {
+        let root = crate::temp_case_dir("file_ops_link");
+        let gather = root.join("gather");
+        fs::create_dir_all(&gather).unwrap();
+        fs::write(root.join("report.pdf"), b"pdf").unwrap();
+
+        let link = create_link(&root.join("report.pdf"), &gather, None).unwrap();
+        assert_eq!(link, gather.join("report.pdf"));
+        assert_eq!(fs::read_link(&link).unwrap(), root.join("report.pdf"));
+        assert_eq!(fs::read(&link).unwrap(), b"pdf");
+
+        let named = create_link(&root.join("report.pdf"), &gather, Some("q3.pdf")).unwrap();
+        assert_eq!(named, gather.join("q3.pdf"));
+        assert!(create_link(&root.join("report.pdf"), &gather, None).is_err());
+        assert!(create_link(&root.join("report.pdf"), &gather, Some("a/b")).is_err());
+        assert!(create_link(&root.join("missing"), &gather, None).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
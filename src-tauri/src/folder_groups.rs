@@ -0,0 +1,102 @@
+//! Grouping search results by parent folder. When the caller asks for it, the
+//! returned page is reordered so rows sharing a `dir` sit together, and a
+//! `FolderGroup` per folder says where its rows start and how many there are.
+//! The UI renders the group as a header over its rows without re-sorting or
+//! bucketing thousands of entries itself.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::EntryDto;
+
+/// One folder's rows in a grouped result: `entries[start..start + count]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FolderGroup {
+    pub dir: String,
+    pub start: u32,
+    pub count: u32,
+}
+
+/// Reorder `entries` so each folder's rows are contiguous. Folders come in
+/// the order of their first row, and rows keep their order within a folder,
+/// so the best-ranked match still leads the page. Only the rows passed in
+/// are grouped: a folder whose matches span pages shows up on each.
+pub(crate) fn group_by_folder(entries: &mut Vec<EntryDto>) -> Vec<FolderGroup> {
+    let mut slots: HashMap<String, usize> = HashMap::new();
+    let mut buckets: Vec<Vec<EntryDto>> = Vec::new();
+    for entry in entries.drain(..) {
+        let slot = match slots.get(entry.dir.as_str()) {
+            Some(&slot) => slot,
+            None => {
+                slots.insert(entry.dir.clone(), buckets.len());
+                buckets.push(Vec::new());
+                buckets.len() - 1
+            }
+        };
+        buckets[slot].push(entry);
+    }
+
+    let mut groups = Vec::with_capacity(buckets.len());
+    for bucket in buckets {
+        groups.push(FolderGroup {
+            dir: bucket[0].dir.clone(),
+            start: entries.len() as u32,
+            count: bucket.len() as u32,
+        });
+        entries.extend(bucket);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dir: &str, name: &str) -> EntryDto {
+        crate::test_entry(&format!("{dir}/{name}"))
+    }
+
+    #[test]
+    fn groups_rows_by_folder_in_first_seen_order() {
+        let mut entries = vec![
+            entry("/home/u/docs", "report.pdf"),
+            entry("/home/u/music", "report.mp3"),
+            entry("/home/u/docs", "report-old.pdf"),
+            entry("/tmp", "report.txt"),
+            entry("/home/u/music", "report-live.mp3"),
+        ];
+        let groups = group_by_folder(&mut entries);
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "report.pdf",
+                "report-old.pdf",
+                "report.mp3",
+                "report-live.mp3",
+                "report.txt",
+            ]
+        );
+        let spans: Vec<(&str, u32, u32)> = groups
+            .iter()
+            .map(|g| (g.dir.as_str(), g.start, g.count))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("/home/u/docs", 0, 2),
+                ("/home/u/music", 2, 2),
+                ("/tmp", 4, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_results_have_no_groups() {
+        let mut entries = Vec::new();
+        assert!(group_by_folder(&mut entries).is_empty());
+    }
+}
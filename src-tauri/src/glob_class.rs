@@ -0,0 +1,154 @@
+//! `[...]` character classes in globs (`IMG_[0-9]*`, `*.[ch]`, `[!.]*`).
+//! Ignore patterns, name queries and the `find` fallback share one reading:
+//! a `[` opens a class only when a `]` closes it later on, so an unclosed `[`
+//! stays a literal character, as it was before classes were understood.
+
+/// A parsed `[...]`: its members as inclusive ranges (a single character is a
+/// range of one), and whether it was negated with `!` or `^`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CharClass {
+    negated: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    /// Parse the class opening at `pat[start]` (a `[`). Returns the class and
+    /// the index just past its closing `]`, or `None` when nothing closes it.
+    /// A `]` right after the opening (or after the negation) is a member.
+    pub(crate) fn parse(pat: &[char], start: usize) -> Option<(CharClass, usize)> {
+        let mut i = start + 1;
+        let negated = matches!(pat.get(i), Some('!' | '^'));
+        if negated {
+            i += 1;
+        }
+        let first = i;
+        let mut ranges = Vec::new();
+        while i < pat.len() {
+            let ch = pat[i];
+            if ch == ']' && i > first {
+                return Some((CharClass { negated, ranges }, i + 1));
+            }
+            if pat.get(i + 1) == Some(&'-') && pat.get(i + 2).is_some_and(|&hi| hi != ']') {
+                ranges.push((ch, pat[i + 2]));
+                i += 3;
+            } else {
+                ranges.push((ch, ch));
+                i += 1;
+            }
+        }
+        None
+    }
+
+    pub(crate) fn matches(&self, ch: char) -> bool {
+        let hit = self.ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi);
+        hit != self.negated
+    }
+
+    /// The class in SQLite `GLOB` syntax, which negates with `^` only.
+    fn to_sqlite(&self, out: &mut String) {
+        out.push('[');
+        if self.negated {
+            out.push('^');
+        }
+        // SQLite reads a leading `]` as a member, like the glob syntax here.
+        let (close, rest): (Vec<_>, Vec<_>) = self.ranges.iter().partition(|&&(lo, _)| lo == ']');
+        for &(lo, hi) in close.into_iter().chain(rest) {
+            out.push(lo);
+            if hi != lo {
+                out.push('-');
+                out.push(hi);
+            }
+        }
+        out.push(']');
+    }
+}
+
+/// Whether `s` holds at least one closed `[...]` class.
+pub(crate) fn has_class(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .any(|(i, &ch)| ch == '[' && CharClass::parse(&chars, i).is_some())
+}
+
+/// `pattern` (with `*`, `?` and classes) as a SQLite `GLOB` pattern over
+/// `name_norm`: folded like the column, and with literal `[`s bracketed so
+/// SQLite doesn't read them as classes of their own.
+pub(crate) fn to_sqlite_glob(pattern: &str) -> String {
+    let folded = crate::name_norm::fold(pattern);
+    let chars: Vec<char> = folded.chars().collect();
+    let mut out = String::with_capacity(folded.len() + 4);
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '[' {
+            if let Some((class, end)) = CharClass::parse(&chars, i) {
+                class.to_sqlite(&mut out);
+                i = end;
+                continue;
+            }
+            out.push_str("[[]");
+        } else {
+            out.push(chars[i]);
+        }
+        i += 1;
+    }
+    out
+}
+
+/// `pattern` for `find -iname`/`-ipath`, which reads classes natively but
+/// would also take an unclosed `[` for one: those are escaped.
+pub(crate) fn escape_unclosed_for_find(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut out = String::with_capacity(pattern.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '[' {
+            if let Some((_, end)) = CharClass::parse(&chars, i) {
+                out.extend(&chars[i..end]);
+                i = end;
+                continue;
+            }
+            out.push('\\');
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(s: &str) -> CharClass {
+        let chars: Vec<char> = s.chars().collect();
+        CharClass::parse(&chars, 0).unwrap().0
+    }
+
+    #[test]
+    fn classes_parse_ranges_negation_and_leading_bracket() {
+        let digits = class("[0-9]");
+        assert!(digits.matches('7') && !digits.matches('a'));
+        let not_dot = class("[!.]");
+        assert!(not_dot.matches('a') && !not_dot.matches('.'));
+        assert_eq!(class("[^.]"), not_dot);
+        let close = class("[]a]");
+        assert!(close.matches(']') && close.matches('a') && !close.matches('b'));
+        let dash = class("[a-]");
+        assert!(dash.matches('-') && dash.matches('a'));
+
+        assert!(has_class("IMG_[0-9]*"));
+        assert!(!has_class("notes [draft"));
+        assert!(!has_class("[]"));
+    }
+
+    #[test]
+    fn sqlite_and_find_spellings_keep_unclosed_brackets_literal() {
+        assert_eq!(to_sqlite_glob("IMG_[0-9]*.JPG"), "img_[0-9]*.jpg");
+        assert_eq!(to_sqlite_glob("[!.]*"), "[^.]*");
+        assert_eq!(to_sqlite_glob("a[b*"), "a[[]b*");
+        assert_eq!(escape_unclosed_for_find("*[ch]"), "*[ch]");
+        assert_eq!(escape_unclosed_for_find("*a[b*"), "*a\\[b*");
+    }
+}
@@ -0,0 +1,507 @@
+//! Opt-in HTTP API so scripts, browser extensions and (when allowed) other
+//! machines on the LAN can query the running app's index, like Everything's
+//! HTTP server. Off by default; the settings live in `http_api.json` in the
+//! app data dir so an index reset leaves them alone.
+//!
+//! Endpoints (all JSON, all need the token as `Authorization: Bearer <token>`
+//! or `?token=`):
+//!
+//! * `GET /search?q=…&limit=…&offset=…&sort=name|mtime|size|dir&dir=asc|desc`
+//! * `GET /launcher?q=…&limit=…&format=alfred` — results with a score,
+//!   subtitle and actions for launcher extensions (see `launcher`)
+//! * `GET /status`
+//!
+//! Read-only on purpose: nothing here opens or runs files, so a leaked token
+//! (or a LAN host that has it) can list the index but not launch programs.
+//! No CORS headers either: browser pages can't read responses, while
+//! extensions with host permissions aren't bound by CORS.
+//!
+//! Deliberately small: HTTP/1.1 with `Connection: close`, one thread per
+//! request up to `MAX_CONNECTIONS` at a time (extra connections are dropped),
+//! no TLS — enable LAN access only on trusted networks.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{AppResult, AppState, SORT_DIRS, SORT_KEYS};
+
+pub(crate) const DEFAULT_HTTP_PORT: u16 = 7590;
+const CONFIG_FILE_NAME: &str = "http_api.json";
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_BODY_BYTES: usize = 64 * 1024;
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Connections handled at once. A flood beyond this is dropped at accept,
+/// before a thread exists or the token is even read.
+const MAX_CONNECTIONS: usize = 16;
+const LAUNCHER_DEFAULT_LIMIT: u32 = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct HttpApiConfig {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on all interfaces instead of loopback only.
+    pub allow_lan: bool,
+    pub token: String,
+}
+
+impl Default for HttpApiConfig {
+    fn default() -> Self {
+        HttpApiConfig {
+            enabled: false,
+            port: DEFAULT_HTTP_PORT,
+            allow_lan: false,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpApiStatusDto {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub allow_lan: bool,
+    pub token: String,
+    pub url: Option<String>,
+}
+
+pub(crate) fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONFIG_FILE_NAME)
+}
+
+pub(crate) fn load_config(data_dir: &Path) -> HttpApiConfig {
+    std::fs::read_to_string(config_path(data_dir))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+pub(crate) fn save_config(data_dir: &Path, config: &HttpApiConfig) -> AppResult<()> {
+    let text = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(config_path(data_dir), text).map_err(|e| e.to_string())
+}
+
+/// 256 bits from the OS CSPRNG, hex-encoded.
+pub(crate) fn generate_token() -> AppResult<String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| format!("Could not generate an API token: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+#[derive(Debug)]
+pub(crate) struct HttpServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HttpServer {
+    pub(crate) fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    pub(crate) fn stop(mut self) {
+        self.stop.store(true, Ordering::Release);
+        // Wake the blocking accept so the loop sees the flag.
+        let wake = if self.addr.ip().is_unspecified() {
+            SocketAddr::from(([127, 0, 0, 1], self.addr.port()))
+        } else {
+            self.addr
+        };
+        let _ = TcpStream::connect_timeout(&wake, Duration::from_millis(500));
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub(crate) fn start(state: AppState, config: &HttpApiConfig) -> AppResult<HttpServer> {
+    let ip = if config.allow_lan {
+        [0, 0, 0, 0]
+    } else {
+        [127, 0, 0, 1]
+    };
+    let listener = TcpListener::bind(SocketAddr::from((ip, config.port)))
+        .map_err(|e| format!("HTTP API could not listen on port {}: {e}", config.port))?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let token: Arc<str> = Arc::from(config.token.as_str());
+    let loop_stop = Arc::clone(&stop);
+    let active = Arc::new(AtomicUsize::new(0));
+    let handle = std::thread::Builder::new()
+        .name("http-api".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if loop_stop.load(Ordering::Acquire) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let _ = stream.set_read_timeout(Some(IO_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(IO_TIMEOUT));
+                let Some(slot) = ConnectionSlot::acquire(&active) else {
+                    continue;
+                };
+                let state = state.clone();
+                let token = Arc::clone(&token);
+                let spawned = std::thread::Builder::new()
+                    .name("http-api-conn".to_string())
+                    .spawn(move || {
+                        let _slot = slot;
+                        handle_connection(stream, &state, &token);
+                    });
+                if let Err(e) = spawned {
+                    eprintln!("[http_api] could not spawn a connection thread: {e}");
+                }
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    eprintln!("[http_api] listening on {addr}");
+    Ok(HttpServer {
+        addr,
+        stop,
+        handle: Some(handle),
+    })
+}
+
+/// One of the `MAX_CONNECTIONS` slots, released when the connection's
+/// thread finishes.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn acquire(active: &Arc<AtomicUsize>) -> Option<ConnectionSlot> {
+        active
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| ConnectionSlot(Arc::clone(active)))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Start the server at app startup when the saved settings enable it.
+pub(crate) fn start_if_enabled(state: &AppState) {
+    let config = load_config(&state.data_dir);
+    if !config.enabled || config.token.is_empty() {
+        return;
+    }
+    match start(state.clone(), &config) {
+        Ok(server) => *state.http_server.lock() = Some(server),
+        Err(e) => eprintln!("[http_api] {e}"),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Requests
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    params: HashMap<String, String>,
+    /// Header names lowercased.
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+fn percent_decode(raw: &str) -> String {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let bytes = raw.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match (
+                bytes.get(i + 1).and_then(|&b| hex(b)),
+                bytes.get(i + 2).and_then(|&b| hex(b)),
+            ) {
+                (Some(hi), Some(lo)) => {
+                    out.push(hi << 4 | lo);
+                    i += 2;
+                }
+                _ => out.push(b'%'),
+            },
+            other => out.push(other),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub(crate) fn parse_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn read_request(reader: &mut impl BufRead) -> Result<Request, (u16, String)> {
+    let bad = |msg: &str| (400, msg.to_string());
+    let mut head = Vec::new();
+    loop {
+        let mut line = Vec::new();
+        let read = reader
+            .by_ref()
+            .take((MAX_HEADER_BYTES - head.len().min(MAX_HEADER_BYTES)) as u64)
+            .read_until(b'\n', &mut line)
+            .map_err(|e| (400, e.to_string()))?;
+        if read == 0 || !line.ends_with(b"\n") {
+            return Err(bad("Incomplete request"));
+        }
+        if line == b"\r\n" || line == b"\n" {
+            break;
+        }
+        head.extend_from_slice(&line);
+    }
+    let head = String::from_utf8(head).map_err(|_| bad("Request head is not UTF-8"))?;
+    let mut lines = head.lines();
+    let request_line = lines.next().ok_or_else(|| bad("Empty request"))?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| bad("Missing method"))?
+        .to_string();
+    let target = parts.next().ok_or_else(|| bad("Missing target"))?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let length = headers
+        .get("content-length")
+        .map(|v| v.parse::<usize>().map_err(|_| bad("Bad Content-Length")))
+        .transpose()?
+        .unwrap_or(0);
+    if length > MAX_BODY_BYTES {
+        return Err((413, "Request body too large".to_string()));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad("Incomplete body"))?;
+    Ok(Request {
+        method,
+        path: percent_decode(path),
+        params: parse_params(query),
+        headers,
+        body,
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn authorized(request: &Request, token: &str) -> bool {
+    if token.is_empty() {
+        return false;
+    }
+    let presented = request
+        .headers
+        .get("authorization")
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| request.params.get("token").map(String::as_str));
+    presented.is_some_and(|p| constant_time_eq(p.trim().as_bytes(), token.as_bytes()))
+}
+
+fn route(request: &Request, state: &AppState, token: &str) -> (u16, Value) {
+    if !authorized(request, token) {
+        return (401, json!({ "error": "Missing or invalid token." }));
+    }
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/search") => search(request, state),
+        ("GET", "/launcher") => launcher(request, state),
+        ("GET", "/status") => serde_json::to_value(crate::index_status_snapshot(state))
+            .map_err(|e| (500, e.to_string())),
+        (_, "/search" | "/launcher" | "/status") => Err((405, "Method not allowed.".to_string())),
+        _ => Err((404, "Not found.".to_string())),
+    };
+    match result {
+        Ok(value) => (200, value),
+        Err((status, message)) => (status, json!({ "error": message })),
+    }
+}
+
+fn search(request: &Request, state: &AppState) -> Result<Value, (u16, String)> {
+    let query = request
+        .params
+        .get("q")
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .ok_or_else(|| (400, "`q` is required.".to_string()))?;
+    let number = |key: &str| {
+        request
+            .params
+            .get(key)
+            .map(|v| {
+                v.parse::<u32>()
+                    .map_err(|_| (400, format!("`{key}` must be a number.")))
+            })
+            .transpose()
+    };
+    let limit = number("limit")?;
+    let offset = number("offset")?;
+    let sort_by = request.params.get("sort").cloned();
+    if let Some(sort) = sort_by.as_deref().filter(|s| !SORT_KEYS.contains(s)) {
+        return Err((
+            400,
+            format!("Invalid sort {sort:?} (one of {SORT_KEYS:?})."),
+        ));
+    }
+    let sort_dir = request.params.get("dir").cloned();
+    if let Some(dir) = sort_dir.as_deref().filter(|d| !SORT_DIRS.contains(d)) {
+        return Err((400, format!("Invalid dir {dir:?} (one of {SORT_DIRS:?}).")));
+    }
+    let execution = crate::execute_search(state, query, limit, offset, sort_by, sort_dir)
+        .map_err(|e| (503, e))?;
+    Ok(json!({
+        "query": execution.query,
+        "mode": execution.mode_label,
+        "offset": execution.offset,
+        "limit": execution.effective_limit,
+        "results": execution.results,
+    }))
+}
+
+fn launcher(request: &Request, state: &AppState) -> Result<Value, (u16, String)> {
+    let query = request
+        .params
+        .get("q")
+        .map(|q| q.trim().to_string())
+        .unwrap_or_default();
+    let limit = request
+        .params
+        .get("limit")
+        .map(|v| {
+            v.parse::<u32>()
+                .map_err(|_| (400, "`limit` must be a number.".to_string()))
+        })
+        .transpose()?
+        .unwrap_or(LAUNCHER_DEFAULT_LIMIT);
+    let alfred = match request.params.get("format").map(String::as_str) {
+        None | Some("default") => false,
+        Some("alfred") => true,
+        Some(other) => return Err((400, format!("Invalid format {other:?}."))),
+    };
+    // Launchers query on every keystroke; an empty box is an empty list.
+    let items = if query.is_empty() {
+        Vec::new()
+    } else {
+        let execution = crate::execute_search(state, query.clone(), Some(limit), None, None, None)
+            .map_err(|e| (503, e))?;
+        crate::launcher::launcher_items(&execution.results, &query, &state.home_dir)
+    };
+    if alfred {
+        return Ok(crate::launcher::alfred_items(&items));
+    }
+    Ok(json!({ "query": query, "items": items }))
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
+
+fn write_response(stream: &mut impl Write, status: u16, body: &Value) -> std::io::Result<()> {
+    let payload = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\n\
+         Content-Type: application/json; charset=utf-8\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{payload}",
+        status_text(status),
+        payload.len(),
+    )?;
+    stream.flush()
+}
+
+fn handle_connection(stream: TcpStream, state: &AppState, token: &str) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    let (status, body) = match read_request(&mut reader) {
+        Ok(request) => route(&request, state, token),
+        Err((status, message)) => (status, json!({ "error": message })),
+    };
+    let _ = write_response(&mut writer, status, &body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests_and_checks_tokens() {
+        let raw = b"POST /search?path=%2Ftmp%2Fa+b.txt&x HTTP/1.1\r\n\
+                    Host: localhost\r\n\
+                    Authorization: Bearer secret\r\n\
+                    Content-Length: 2\r\n\r\n{}";
+        let request = read_request(&mut &raw[..]).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/search");
+        assert_eq!(request.params["path"], "/tmp/a b.txt");
+        assert_eq!(request.params["x"], "");
+        assert_eq!(request.body, b"{}");
+        assert!(authorized(&request, "secret"));
+        assert!(!authorized(&request, "other"));
+        assert!(!authorized(&request, ""));
+
+        let raw = b"GET /status?token=secret HTTP/1.1\r\n\r\n";
+        let request = read_request(&mut &raw[..]).unwrap();
+        assert!(authorized(&request, "secret"));
+
+        assert_eq!(
+            read_request(&mut &b"GET / HTTP/1.1\r\n"[..]).unwrap_err().0,
+            400
+        );
+        let huge = b"POST /search HTTP/1.1\r\nContent-Length: 999999\r\n\r\n";
+        assert_eq!(read_request(&mut &huge[..]).unwrap_err().0, 413);
+
+        assert_eq!(percent_decode("100%25%zz"), "100%%zz");
+        let active = Arc::new(AtomicUsize::new(0));
+        let slots: Vec<_> = (0..MAX_CONNECTIONS)
+            .map(|_| ConnectionSlot::acquire(&active).unwrap())
+            .collect();
+        assert!(ConnectionSlot::acquire(&active).is_none());
+        drop(slots);
+        assert_eq!(active.load(Ordering::Acquire), 0);
+        assert!(ConnectionSlot::acquire(&active).is_some());
+
+        let token = generate_token().unwrap();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token().unwrap());
+    }
+}
@@ -0,0 +1,305 @@
+//! Human-readable descriptions of index entries, computed once in Rust so the
+//! frontend doesn't re-derive them per row.
+
+use chrono::{Local, TimeZone};
+
+use crate::{now_epoch, EntryDto};
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+const MONTH: i64 = 30 * DAY;
+const YEAR: i64 = 365 * DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// Languages with their own relative-time phrasing. Anything else falls back
+/// to English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimeLocale {
+    En,
+    Ko,
+    Ja,
+}
+
+impl TimeLocale {
+    /// Parse a BCP 47 tag or POSIX locale ("ko-KR", "ja_JP.UTF-8", "en").
+    pub(crate) fn parse(tag: &str) -> Self {
+        let lang = tag
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "ko" => TimeLocale::Ko,
+            "ja" => TimeLocale::Ja,
+            _ => TimeLocale::En,
+        }
+    }
+
+    /// The caller's locale if given, otherwise the user's preferred
+    /// language from the OS. Apps launched from Finder get no LANG, so the
+    /// environment alone would read as English on every Mac.
+    pub(crate) fn resolve(tag: Option<&str>) -> Self {
+        if let Some(tag) = tag.filter(|t| !t.trim().is_empty()) {
+            return Self::parse(tag.trim());
+        }
+        sys_locale::get_locale()
+            .map(|tag| Self::parse(&tag))
+            .unwrap_or(TimeLocale::En)
+    }
+
+    fn just_now(self) -> &'static str {
+        match self {
+            TimeLocale::En => "just now",
+            TimeLocale::Ko => "방금 전",
+            TimeLocale::Ja => "たった今",
+        }
+    }
+
+    fn yesterday(self) -> &'static str {
+        match self {
+            TimeLocale::En => "yesterday",
+            TimeLocale::Ko => "어제",
+            TimeLocale::Ja => "昨日",
+        }
+    }
+
+    fn ago(self, n: i64, unit: Unit) -> String {
+        match self {
+            TimeLocale::En => {
+                let name = match unit {
+                    Unit::Minute => "minute",
+                    Unit::Hour => "hour",
+                    Unit::Day => "day",
+                    Unit::Week => "week",
+                    Unit::Month => "month",
+                    Unit::Year => "year",
+                };
+                if n == 1 {
+                    format!("1 {name} ago")
+                } else {
+                    format!("{n} {name}s ago")
+                }
+            }
+            TimeLocale::Ko => {
+                let name = match unit {
+                    Unit::Minute => "분",
+                    Unit::Hour => "시간",
+                    Unit::Day => "일",
+                    Unit::Week => "주",
+                    Unit::Month => "개월",
+                    Unit::Year => "년",
+                };
+                format!("{n}{name} 전")
+            }
+            TimeLocale::Ja => {
+                let name = match unit {
+                    Unit::Minute => "分",
+                    Unit::Hour => "時間",
+                    Unit::Day => "日",
+                    Unit::Week => "週間",
+                    Unit::Month => "か月",
+                    Unit::Year => "年",
+                };
+                format!("{n}{name}前")
+            }
+        }
+    }
+}
+
+/// Day number of `ts`'s date on the local calendar.
+fn local_day(ts: i64) -> i64 {
+    let offset = Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map_or(0, |t| t.offset().local_minus_utc());
+    (ts + i64::from(offset)).div_euclid(DAY)
+}
+
+/// Relative age of `mtime` as seen at `now` (both epoch seconds), in
+/// `locale`'s phrasing. Timestamps in the future (clock skew, files from
+/// other machines) read as "just now".
+pub(crate) fn relative_time_in(locale: TimeLocale, mtime: i64, now: i64) -> String {
+    relative_time_by(locale, mtime, now, local_day)
+}
+
+/// Past a day, "yesterday" and "N days ago" count calendar dates (per
+/// `day_of`), not 24-hour spans: Monday 23:00 seen Wednesday 01:00 is two
+/// days ago.
+fn relative_time_by(
+    locale: TimeLocale,
+    mtime: i64,
+    now: i64,
+    day_of: impl Fn(i64) -> i64,
+) -> String {
+    let age = now.saturating_sub(mtime);
+    let days = day_of(now) - day_of(mtime);
+    match age {
+        a if a < MINUTE => locale.just_now().to_string(),
+        a if a < HOUR => locale.ago(a / MINUTE, Unit::Minute),
+        a if a < DAY => locale.ago(a / HOUR, Unit::Hour),
+        _ if days <= 1 => locale.yesterday().to_string(),
+        _ if days < 7 => locale.ago(days, Unit::Day),
+        a if a < MONTH => locale.ago((a / WEEK).max(1), Unit::Week),
+        a if a < YEAR => locale.ago(a / MONTH, Unit::Month),
+        a => locale.ago(a / YEAR, Unit::Year),
+    }
+}
+
+/// English relative age: "just now", "5 minutes ago", "yesterday",
+/// "3 weeks ago", ...
+pub(crate) fn relative_time(mtime: i64, now: i64) -> String {
+    relative_time_in(TimeLocale::En, mtime, now)
+}
+
+fn kind_label(entry: &EntryDto) -> String {
+    if entry.is_dir {
+        return "folder".to_string();
+    }
+    match entry.ext.as_deref() {
+        Some(ext) if !ext.is_empty() => format!("{} file", ext.to_uppercase()),
+        _ => "file".to_string(),
+    }
+}
+
+fn parent_folder_name(dir: &str) -> &str {
+    let trimmed = dir.trim_end_matches(['/', '\\']);
+    match trimmed.rfind(['/', '\\']) {
+        Some(pos) => &trimmed[pos + 1..],
+        None if trimmed.is_empty() => dir,
+        None => trimmed,
+    }
+}
+
+/// Screen-reader announcement for one entry, e.g.
+/// "report.pdf, PDF file, in Documents, modified 2 days ago".
+pub(crate) fn spoken_label(entry: &EntryDto, now: i64) -> String {
+    let mut label = format!("{}, {}", entry.name, kind_label(entry));
+    let parent = parent_folder_name(&entry.dir);
+    if !parent.is_empty() {
+        label.push_str(", in ");
+        label.push_str(parent);
+    }
+    if let Some(mtime) = entry.mtime {
+        label.push_str(", modified ");
+        label.push_str(&relative_time(mtime, now));
+    }
+    label
+}
+
+/// Fill `spoken_label` on every entry (opt-in per request; the field is
+/// omitted from the payload otherwise).
+pub(crate) fn annotate_spoken_labels(entries: &mut [EntryDto]) {
+    let now = now_epoch();
+    for entry in entries {
+        entry.spoken_label = Some(spoken_label(entry, now));
+    }
+}
+
+/// Fill `mtime_display` on every entry that has an mtime, so the list,
+/// preview, and exports all show the same wording.
+pub(crate) fn annotate_mtime_display(entries: &mut [EntryDto], locale: TimeLocale) {
+    let now = now_epoch();
+    for entry in entries {
+        entry.mtime_display = entry.mtime.map(|mtime| relative_time_in(locale, mtime, now));
+    }
+}
+
+/// Relative-time strings for a batch of mtimes (None stays empty).
+pub(crate) fn format_times(mtimes: &[Option<i64>], locale: TimeLocale) -> Vec<String> {
+    let now = now_epoch();
+    mtimes
+        .iter()
+        .map(|mtime| match mtime {
+            Some(mtime) => relative_time_in(locale, *mtime, now),
+            None => String::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, dir: &str, is_dir: bool, ext: Option<&str>, mtime: Option<i64>) -> EntryDto {
+        EntryDto {
+            name: name.to_string(),
+            dir: dir.to_string(),
+            is_dir,
+            ext: ext.map(str::to_string),
+            mtime,
+            ..crate::test_entry(&format!("{dir}/{name}"))
+        }
+    }
+
+    #[test]
+    fn relative_time_buckets() {
+        let now = 1_000_000_000;
+        assert_eq!(relative_time(now - 5, now), "just now");
+        assert_eq!(relative_time(now + 500, now), "just now");
+        assert_eq!(relative_time(now - 60, now), "1 minute ago");
+        assert_eq!(relative_time(now - 5 * HOUR, now), "5 hours ago");
+        assert_eq!(relative_time(now - 2 * DAY, now), "2 days ago");
+        assert_eq!(relative_time(now - 3 * WEEK, now), "3 weeks ago");
+        assert_eq!(relative_time(now - 2 * MONTH, now), "2 months ago");
+        assert_eq!(relative_time(now - 3 * YEAR, now), "3 years ago");
+    }
+
+    #[test]
+    fn days_count_calendar_dates_not_24_hour_spans() {
+        let utc = |ts: i64| ts.div_euclid(DAY);
+        let plus_9h = |ts: i64| (ts + 9 * HOUR).div_euclid(DAY);
+        // 01:00 UTC.
+        let now = 1_000_000_000 - 1_000_000_000 % DAY + 2 * DAY + HOUR;
+        let at = |days_back: i64, hour: i64| now - HOUR - days_back * DAY + hour * HOUR;
+        let en = TimeLocale::En;
+        assert_eq!(relative_time_by(en, at(1, 2), now, utc), "23 hours ago");
+        assert_eq!(relative_time_by(en, at(1, 0), now, utc), "yesterday");
+        assert_eq!(relative_time_by(en, at(2, 23), now, utc), "2 days ago");
+        assert_eq!(relative_time_by(en, at(2, 23), now, plus_9h), "yesterday");
+        assert_eq!(relative_time_by(en, at(6, 12), now, utc), "6 days ago");
+        assert_eq!(relative_time_by(en, at(7, 12), now, utc), "1 week ago");
+        assert_eq!(relative_time_by(TimeLocale::Ko, at(1, 0), now, utc), "어제");
+    }
+
+    #[test]
+    fn relative_time_follows_locale() {
+        let now = 1_000_000_000;
+        assert_eq!(TimeLocale::parse("ko-KR"), TimeLocale::Ko);
+        assert_eq!(TimeLocale::parse("ja_JP.UTF-8"), TimeLocale::Ja);
+        assert_eq!(TimeLocale::parse("fr-FR"), TimeLocale::En);
+        assert_eq!(TimeLocale::resolve(Some("ko")), TimeLocale::Ko);
+        assert_eq!(relative_time_in(TimeLocale::Ko, now - 3 * WEEK, now), "3주 전");
+        assert_eq!(relative_time_in(TimeLocale::Ja, now - 5 * HOUR, now), "5時間前");
+        assert_eq!(
+            format_times(&[Some(now_epoch() - 2 * DAY), None], TimeLocale::En),
+            vec!["2 days ago".to_string(), String::new()]
+        );
+    }
+
+    #[test]
+    fn spoken_label_names_kind_parent_and_age() {
+        let now = 1_000_000_000;
+        let file = entry("report.pdf", "/Users/me/Documents", false, Some("pdf"), Some(now - 2 * DAY));
+        assert_eq!(
+            spoken_label(&file, now),
+            "report.pdf, PDF file, in Documents, modified 2 days ago"
+        );
+
+        let folder = entry("src", "C:\\work\\proj\\", true, None, None);
+        assert_eq!(spoken_label(&folder, now), "src, folder, in proj");
+
+        let bare = entry("Makefile", "/", false, None, None);
+        assert_eq!(spoken_label(&bare, now), "Makefile, file, in /");
+    }
+}
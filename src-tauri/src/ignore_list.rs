@@ -0,0 +1,205 @@
+//! `list_ignore_rules`: every rule that can keep a path out of the index,
+//! tagged with where it came from, so the UI can answer "why isn't this file
+//! indexed?". Mirrors the merge in `should_skip_path` and
+//! `effective_ignore_rules`: builtin names, suffixes and path fragments, the
+//! app data dir, each `.pathignore` line (including rules added at runtime by
+//! `add_ignore_rule`), macOS TCC roots, Windows noisy roots and builtin globs.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    builtin_ignore_patterns, contains_glob_meta, ignore_pattern_key, macos_tcc_ignore_roots,
+    parse_ignore_pattern, pathignore_files, resolve_ignore_path, windows_noisy_roots,
+    IgnorePattern, BUILTIN_SKIP_NAMES, BUILTIN_SKIP_PATHS, BUILTIN_SKIP_SUFFIXES,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreRuleInfoDto {
+    /// The rule as written.
+    pub rule: String,
+    /// "name" (any path segment), "suffix" (segment ending), "path" (path
+    /// fragment anywhere), "root" (a directory and its contents) or
+    /// "pattern" (glob).
+    pub kind: String,
+    /// "builtin", "app_data", "pathignore", "tcc" or "windows_noisy".
+    pub source: String,
+    /// The `.pathignore` file and 1-based line, for rules read from one.
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    /// The path or glob the rule is matched as, when it differs from `rule`.
+    pub resolved: Option<String>,
+    /// An earlier rule already covers the same path or glob.
+    pub duplicate: bool,
+}
+
+fn rule(kind: &str, source: &str, rule: String) -> IgnoreRuleInfoDto {
+    IgnoreRuleInfoDto {
+        rule,
+        kind: kind.to_string(),
+        source: source.to_string(),
+        file: None,
+        line: None,
+        resolved: None,
+        duplicate: false,
+    }
+}
+
+fn pattern_text(pattern: &IgnorePattern) -> String {
+    match pattern {
+        IgnorePattern::AnySegment { segment, .. } => format!("**/{segment}"),
+        IgnorePattern::Glob(glob) => glob.clone(),
+    }
+}
+
+/// `fixed_roots` are the startup roots not read from `.pathignore`
+/// (`fixed_ignore_roots`).
+pub(crate) fn list_ignore_rules(
+    config_file: &Path,
+    home_dir: &Path,
+    cwd: &Path,
+    fixed_roots: &[PathBuf],
+) -> Vec<IgnoreRuleInfoDto> {
+    let mut rules = Vec::new();
+    rules.extend(
+        BUILTIN_SKIP_NAMES
+            .iter()
+            .map(|n| rule("name", "builtin", n.to_string())),
+    );
+    rules.extend(
+        BUILTIN_SKIP_SUFFIXES
+            .iter()
+            .map(|s| rule("suffix", "builtin", s.to_string())),
+    );
+    rules.extend(
+        BUILTIN_SKIP_PATHS
+            .iter()
+            .map(|p| rule("path", "builtin", p.to_string())),
+    );
+
+    let mut seen_roots: HashSet<String> = HashSet::new();
+    let mut seen_patterns: HashSet<String> = HashSet::new();
+    let mut push_root =
+        |rules: &mut Vec<IgnoreRuleInfoDto>, mut info: IgnoreRuleInfoDto, root: &Path| {
+            let resolved = root.to_string_lossy().to_string();
+            info.duplicate = !seen_roots.insert(resolved.clone());
+            if resolved != info.rule {
+                info.resolved = Some(resolved);
+            }
+            rules.push(info);
+        };
+
+    for root in fixed_roots {
+        let text = root.to_string_lossy().to_string();
+        push_root(&mut rules, rule("root", "app_data", text), root);
+    }
+
+    let mut file_patterns = Vec::new();
+    for file in pathignore_files(config_file, home_dir, cwd) {
+        let Ok(contents) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let base_dir = file.parent().unwrap_or_else(|| Path::new("/"));
+        for (index, line) in contents.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
+                continue;
+            }
+            let mut info = rule("root", "pathignore", trimmed.to_string());
+            info.file = Some(file.to_string_lossy().to_string());
+            info.line = Some(index + 1);
+            if contains_glob_meta(trimmed) {
+                if let Some(pattern) = parse_ignore_pattern(trimmed, base_dir, home_dir) {
+                    info.kind = "pattern".to_string();
+                    file_patterns.push((info, pattern));
+                }
+            } else if let Some(root) = resolve_ignore_path(trimmed, base_dir, home_dir) {
+                push_root(&mut rules, info, &root);
+            }
+        }
+    }
+
+    for root in macos_tcc_ignore_roots(home_dir) {
+        let text = root.to_string_lossy().to_string();
+        push_root(&mut rules, rule("root", "tcc", text), &root);
+    }
+    for root in windows_noisy_roots(home_dir) {
+        let text = root.to_string_lossy().to_string();
+        push_root(&mut rules, rule("root", "windows_noisy", text), &root);
+    }
+
+    // Patterns follow the roots, in the order `effective_ignore_rules`
+    // merges them.
+    let builtin = builtin_ignore_patterns(home_dir)
+        .into_iter()
+        .map(|p| (rule("pattern", "builtin", pattern_text(&p)), p));
+    for (mut info, pattern) in file_patterns.into_iter().chain(builtin) {
+        info.duplicate = !seen_patterns.insert(ignore_pattern_key(&pattern));
+        let resolved = pattern_text(&pattern);
+        if resolved != info.rule {
+            info.resolved = Some(resolved);
+        }
+        rules.push(info);
+    }
+    rules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_pathignore_lines_with_file_line_and_duplicates() {
+        let home = crate::temp_case_dir("ignore_list");
+        fs::create_dir_all(home.join("secret")).unwrap();
+        // Root rules are canonicalized (macOS temp dirs sit behind /var).
+        let home = if cfg!(windows) {
+            home
+        } else {
+            fs::canonicalize(&home).unwrap()
+        };
+        let config_file = home.join(".pathignore");
+        let app_data = home.join("app");
+        fs::write(
+            &config_file,
+            "# rules\n**/dist\n~/secret/\n**/dist/\n~/logs/*.log\n",
+        )
+        .unwrap();
+
+        let rules = list_ignore_rules(&config_file, &home, &home, std::slice::from_ref(&app_data));
+        assert!(rules
+            .iter()
+            .any(|r| r.rule == "node_modules" && r.kind == "name" && r.source == "builtin"));
+        assert!(rules.iter().any(|r| r.source == "app_data" && !r.duplicate));
+
+        let from_file: Vec<&IgnoreRuleInfoDto> =
+            rules.iter().filter(|r| r.source == "pathignore").collect();
+        let summary: Vec<(&str, &str, Option<usize>, bool)> = from_file
+            .iter()
+            .map(|r| (r.rule.as_str(), r.kind.as_str(), r.line, r.duplicate))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("~/secret/", "root", Some(3), false),
+                ("**/dist", "pattern", Some(2), false),
+                ("**/dist/", "pattern", Some(4), true),
+                ("~/logs/*.log", "pattern", Some(5), false),
+            ]
+        );
+        assert_eq!(
+            from_file[0].resolved.as_deref(),
+            Some(home.join("secret").to_string_lossy().as_ref())
+        );
+        assert_eq!(
+            from_file[0].file.as_deref(),
+            Some(config_file.to_string_lossy().as_ref())
+        );
+
+        let _ = fs::remove_dir_all(&home);
+    }
+}
@@ -0,0 +1,148 @@
+//! Index status for the frontend, coalesced into one throttled
+//! `index_events` channel. A full index reports progress, counts and state
+//! from several threads; emitted one by one, the webview spent its time
+//! parsing updates it overwrote a frame later. Progress and counts keep only
+//! their latest value and go out at most every `FLUSH_INTERVAL`; a state
+//! change flushes at once (with whatever was pending before it), so
+//! transitions are never delayed, merged away or overtaken.
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+const FLUSH_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexProgressEvent {
+    scanned: u64,
+    indexed: u64,
+    current_path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexStateEvent {
+    state: String,
+    message: Option<String>,
+    is_catchup: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexUpdatedEvent {
+    entries_count: u64,
+    last_updated: i64,
+    permission_errors: u64,
+}
+
+/// One `index_events` payload. The frontend applies the parts in field
+/// order: progress, counts, then state.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Batch {
+    progress: Option<IndexProgressEvent>,
+    counts: Option<IndexUpdatedEvent>,
+    state: Option<IndexStateEvent>,
+}
+
+impl Batch {
+    fn is_empty(&self) -> bool {
+        self.progress.is_none() && self.counts.is_none() && self.state.is_none()
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    batch: Batch,
+    last_flush: Option<Instant>,
+}
+
+struct Channel {
+    /// Also held while emitting, so batches reach the webview in the order
+    /// they were taken.
+    pending: Mutex<Pending>,
+    wake: Condvar,
+}
+
+static CHANNEL: OnceLock<Channel> = OnceLock::new();
+
+/// The channel, with its flusher thread started on first use.
+fn channel(app: &AppHandle) -> &'static Channel {
+    CHANNEL.get_or_init(|| {
+        let app = app.clone();
+        std::thread::spawn(move || flush_loop(&app));
+        Channel {
+            pending: Mutex::new(Pending::default()),
+            wake: Condvar::new(),
+        }
+    })
+}
+
+pub(crate) fn progress(app: &AppHandle, scanned: u64, indexed: u64, current_path: String) {
+    let channel = channel(app);
+    channel.pending.lock().batch.progress = Some(IndexProgressEvent {
+        scanned,
+        indexed,
+        current_path,
+    });
+    channel.wake.notify_one();
+}
+
+pub(crate) fn counts(
+    app: &AppHandle,
+    entries_count: u64,
+    last_updated: i64,
+    permission_errors: u64,
+) {
+    let channel = channel(app);
+    channel.pending.lock().batch.counts = Some(IndexUpdatedEvent {
+        entries_count,
+        last_updated,
+        permission_errors,
+    });
+    channel.wake.notify_one();
+}
+
+pub(crate) fn state(app: &AppHandle, state: &str, message: Option<String>) {
+    let is_catchup = message.as_ref().is_some_and(|m| m.starts_with("Catchup:"));
+    let mut pending = channel(app).pending.lock();
+    pending.batch.state = Some(IndexStateEvent {
+        state: state.to_string(),
+        message,
+        is_catchup,
+    });
+    flush(app, &mut pending);
+}
+
+fn flush(app: &AppHandle, pending: &mut Pending) {
+    let batch = std::mem::take(&mut pending.batch);
+    if batch.is_empty() {
+        return;
+    }
+    let _ = app.emit("index_events", batch);
+    pending.last_flush = Some(Instant::now());
+}
+
+fn flush_loop(app: &AppHandle) {
+    let channel = channel(app);
+    let mut pending = channel.pending.lock();
+    loop {
+        while pending.batch.is_empty() {
+            channel.wake.wait(&mut pending);
+        }
+        // Let updates pile up until an interval has passed since the last
+        // flush; a state change may flush them first.
+        if let Some(due) = pending.last_flush.map(|at| at + FLUSH_INTERVAL) {
+            let now = Instant::now();
+            if due > now {
+                channel.wake.wait_for(&mut pending, due - now);
+                continue;
+            }
+        }
+        flush(app, &mut pending);
+    }
+}
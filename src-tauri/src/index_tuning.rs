@@ -0,0 +1,192 @@
+//! Indexing knobs for very large or very slow filesystems: the depth of the
+//! Windows non-admin indexer's shallow first pass, the scan's DB flush batch
+//! size, the watcher debounce, the scan pool size and whether Windows
+//! walks descend junctions/directory symlinks. Saved in
+//! `index_tuning.json` in the app data dir (like `http_api.json`); a
+//! hand-edited file is picked up at the start of the next index run, with
+//! out-of-range values clamped rather than trusted.
+
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::scan_tuning::{self, StorageKind};
+use crate::{AppResult, AppState, BATCH_SIZE, WATCH_DEBOUNCE};
+
+const CONFIG_FILE_NAME: &str = "index_tuning.json";
+
+/// Depth of the non-admin indexer's first pass over the home directory,
+/// which makes most user-visible files searchable quickly.
+pub(crate) const DEFAULT_SHALLOW_SCAN_DEPTH: usize = 6;
+
+const SHALLOW_SCAN_DEPTH_RANGE: RangeInclusive<usize> = 1..=32;
+const BATCH_SIZE_RANGE: RangeInclusive<usize> = 500..=200_000;
+const WATCH_DEBOUNCE_MS_RANGE: RangeInclusive<u64> = 50..=10_000;
+/// 0 means "size the pool for the storage".
+const SCAN_THREADS_RANGE: RangeInclusive<usize> = 0..=256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IndexTuning {
+    pub shallow_scan_depth: usize,
+    pub batch_size: usize,
+    pub watch_debounce_ms: u64,
+    /// Scan pool size; 0 leaves it to `scan_tuning::scan_threads`.
+    pub scan_threads: usize,
+    /// Descend junctions, directory symlinks and mount points in the
+    /// Windows WalkDir fallback and RDCW root collection. Off by default:
+    /// they usually point back into an already indexed tree.
+    pub follow_reparse_points: bool,
+}
+
+impl Default for IndexTuning {
+    fn default() -> Self {
+        IndexTuning {
+            shallow_scan_depth: DEFAULT_SHALLOW_SCAN_DEPTH,
+            batch_size: BATCH_SIZE,
+            watch_debounce_ms: WATCH_DEBOUNCE.as_millis() as u64,
+            scan_threads: 0,
+            follow_reparse_points: false,
+        }
+    }
+}
+
+impl IndexTuning {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        check(
+            "shallowScanDepth",
+            self.shallow_scan_depth,
+            SHALLOW_SCAN_DEPTH_RANGE,
+        )?;
+        check("batchSize", self.batch_size, BATCH_SIZE_RANGE)?;
+        check(
+            "watchDebounceMs",
+            self.watch_debounce_ms,
+            WATCH_DEBOUNCE_MS_RANGE,
+        )?;
+        check("scanThreads", self.scan_threads, SCAN_THREADS_RANGE)
+    }
+
+    fn clamped(self) -> Self {
+        IndexTuning {
+            shallow_scan_depth: clamp(self.shallow_scan_depth, SHALLOW_SCAN_DEPTH_RANGE),
+            batch_size: clamp(self.batch_size, BATCH_SIZE_RANGE),
+            watch_debounce_ms: clamp(self.watch_debounce_ms, WATCH_DEBOUNCE_MS_RANGE),
+            scan_threads: clamp(self.scan_threads, SCAN_THREADS_RANGE),
+            follow_reparse_points: self.follow_reparse_points,
+        }
+    }
+
+    pub(crate) fn watch_debounce(&self) -> Duration {
+        Duration::from_millis(self.watch_debounce_ms)
+    }
+
+    /// The configured pool size, or the storage-based default.
+    pub(crate) fn scan_threads(&self, n_cpus: usize, storage: StorageKind) -> usize {
+        match self.scan_threads {
+            0 => scan_tuning::scan_threads(n_cpus, storage),
+            threads => threads,
+        }
+    }
+}
+
+fn check<T: PartialOrd + std::fmt::Display>(
+    name: &str,
+    value: T,
+    range: RangeInclusive<T>,
+) -> AppResult<()> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{name} must be between {} and {} (got {value})",
+            range.start(),
+            range.end()
+        ))
+    }
+}
+
+fn clamp<T: Ord + Copy>(value: T, range: RangeInclusive<T>) -> T {
+    value.clamp(*range.start(), *range.end())
+}
+
+pub(crate) fn config_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONFIG_FILE_NAME)
+}
+
+pub(crate) fn load(data_dir: &Path) -> IndexTuning {
+    std::fs::read_to_string(config_path(data_dir))
+        .ok()
+        .and_then(|text| serde_json::from_str::<IndexTuning>(&text).ok())
+        .unwrap_or_default()
+        .clamped()
+}
+
+pub(crate) fn save(data_dir: &Path, tuning: &IndexTuning) -> AppResult<()> {
+    tuning.validate()?;
+    let text = serde_json::to_string_pretty(tuning).map_err(|e| e.to_string())?;
+    std::fs::write(config_path(data_dir), text).map_err(|e| e.to_string())
+}
+
+/// Re-read the saved tuning into `state` (at the start of an index run and
+/// after `set_index_tuning`) and return it. Every watcher (FSEvents, inotify,
+/// USN, RDCW) reads the debounce from `state` before each flush, so a change
+/// reaches running watchers here too.
+pub(crate) fn apply(state: &AppState) -> IndexTuning {
+    let tuning = load(&state.data_dir);
+    let previous = std::mem::replace(&mut *state.index_tuning.write(), tuning);
+    if previous != tuning {
+        eprintln!("[index_tuning] applied {tuning:?}");
+    }
+    tuning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_tuning_is_validated_and_hand_edits_are_clamped() {
+        let dir = crate::temp_case_dir("index_tuning");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(load(&dir), IndexTuning::default());
+        assert!(IndexTuning::default().validate().is_ok());
+
+        let tuning = IndexTuning {
+            batch_size: 50_000,
+            scan_threads: 4,
+            ..IndexTuning::default()
+        };
+        save(&dir, &tuning).unwrap();
+        assert_eq!(load(&dir), tuning);
+        assert_eq!(tuning.scan_threads(16, StorageKind::Unknown), 4);
+        assert_eq!(
+            IndexTuning::default().scan_threads(16, StorageKind::Unknown),
+            32
+        );
+
+        let too_small = IndexTuning {
+            watch_debounce_ms: 1,
+            ..IndexTuning::default()
+        };
+        let err = save(&dir, &too_small).unwrap_err();
+        assert!(err.contains("watchDebounceMs"), "{err}");
+        assert_eq!(load(&dir), tuning);
+
+        std::fs::write(
+            config_path(&dir),
+            r#"{"shallowScanDepth": 0, "batchSize": 99999999, "scanThreads": 8}"#,
+        )
+        .unwrap();
+        let loaded = load(&dir);
+        assert_eq!(loaded.shallow_scan_depth, 1);
+        assert_eq!(loaded.batch_size, 200_000);
+        assert_eq!(loaded.watch_debounce(), WATCH_DEBOUNCE);
+        assert_eq!(loaded.scan_threads, 8);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
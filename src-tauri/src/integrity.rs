@@ -0,0 +1,273 @@
+//! Index health report: SQLite's own integrity check, the FTS shadow index
+//! against its content table, meta bookkeeping against the rows, and a random
+//! sample of indexed paths against the filesystem. Read-only — problems are
+//! reported, repairs are left to reindex/rebuild. Checks that only hold once
+//! the index has settled are skipped while it is being written.
+
+use rusqlite::{Connection, ErrorCode, OptionalExtension};
+use serde::Serialize;
+
+use crate::long_path;
+use crate::{get_meta, AppResult};
+
+/// `PRAGMA integrity_check(N)` stops after this many messages.
+const INTEGRITY_MAX_ERRORS: u32 = 20;
+/// Sampled paths listed per category; counts stay exact.
+const MAX_REPORTED_PATHS: usize = 20;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReportDto {
+    /// True when every check below passed.
+    pub ok: bool,
+    /// Messages from `PRAGMA integrity_check`; empty when it reported "ok".
+    pub sqlite_errors: Vec<String>,
+    /// `None` when the FTS index was skipped (rebuild pending, the index
+    /// wasn't idle, or another writer held the lock).
+    pub fts_ok: Option<bool>,
+    /// A full index was running: its rows are written before `last_run_id`
+    /// and the cached counts catch up, so those meta checks were skipped.
+    pub indexing: bool,
+    pub entries_count: u64,
+    pub cached_entries_count: Option<u64>,
+    pub last_run_id: i64,
+    pub max_row_run_id: i64,
+    /// Human-readable meta inconsistencies.
+    pub meta_issues: Vec<String>,
+    pub sampled: u32,
+    pub missing_count: u32,
+    pub type_mismatch_count: u32,
+    pub missing_paths: Vec<String>,
+    pub type_mismatch_paths: Vec<String>,
+}
+
+/// `idle` gates the FTS check: its `'integrity-check'` command is an INSERT,
+/// so it takes the write lock for as long as it runs and would stall the
+/// indexer or watcher behind it.
+pub fn check_index_integrity(
+    conn: &Connection,
+    fts_ready: bool,
+    indexing: bool,
+    idle: bool,
+    sample_size: u32,
+) -> AppResult<IntegrityReportDto> {
+    let mut report = IntegrityReportDto {
+        indexing,
+        ..Default::default()
+    };
+
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA integrity_check({INTEGRITY_MAX_ERRORS})"))
+        .map_err(|e| e.to_string())?;
+    let messages = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    for message in messages {
+        let message = message.map_err(|e| e.to_string())?;
+        if message != "ok" {
+            report.sqlite_errors.push(message);
+        }
+    }
+
+    if fts_ready && idle {
+        report.fts_ok = match conn
+            .execute_batch("INSERT INTO entries_fts(entries_fts) VALUES('integrity-check');")
+        {
+            Ok(()) => Some(true),
+            // A writer got in after the idle check: skipped, not failed.
+            Err(e)
+                if matches!(
+                    e.sqlite_error_code(),
+                    Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+                ) =>
+            {
+                None
+            }
+            Err(e) if e.sqlite_error_code() == Some(ErrorCode::DatabaseCorrupt) => Some(false),
+            Err(e) => return Err(e.to_string()),
+        };
+    }
+
+    let (count, max_run_id): (i64, Option<i64>) = conn
+        .query_row("SELECT COUNT(*), MAX(run_id) FROM entries", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    report.entries_count = count.max(0) as u64;
+    report.max_row_run_id = max_run_id.unwrap_or(0);
+    report.last_run_id = get_meta(conn, "last_run_id")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(0);
+    report.cached_entries_count =
+        get_meta(conn, "cached_entries_count").and_then(|v| v.parse::<u64>().ok());
+
+    if !indexing {
+        check_run_bookkeeping(&mut report);
+    }
+    if get_meta(conn, "fts_dirty").as_deref() == Some("1") && fts_ready {
+        report
+            .meta_issues
+            .push("fts_dirty is set while the FTS index is in use".to_string());
+    }
+
+    sample_paths(conn, sample_size, &mut report)?;
+
+    report.ok = report.sqlite_errors.is_empty()
+        && report.fts_ok != Some(false)
+        && report.meta_issues.is_empty()
+        && report.missing_count == 0
+        && report.type_mismatch_count == 0;
+    Ok(report)
+}
+
+fn check_run_bookkeeping(report: &mut IntegrityReportDto) {
+    if report.max_row_run_id > report.last_run_id {
+        report.meta_issues.push(format!(
+            "rows carry run_id {} but last_run_id is {}",
+            report.max_row_run_id, report.last_run_id
+        ));
+    }
+    if report.last_run_id == 0 && report.entries_count > 0 {
+        report
+            .meta_issues
+            .push("entries present but no completed index run recorded".to_string());
+    }
+    if let Some(cached) = report.cached_entries_count {
+        if cached != report.entries_count {
+            report.meta_issues.push(format!(
+                "cached_entries_count is {cached} but entries has {} rows",
+                report.entries_count
+            ));
+        }
+    }
+}
+
+/// Probe random rows by id (MIN/MAX on the rowid are O(1), unlike
+/// `ORDER BY RANDOM()` over millions of rows) and lstat each path.
+fn sample_paths(conn: &Connection, sample_size: u32, report: &mut IntegrityReportDto) -> AppResult<()> {
+    if sample_size == 0 || report.entries_count == 0 {
+        return Ok(());
+    }
+    let mut stmt = conn
+        .prepare_cached(
+            "SELECT path, is_dir FROM entries
+             WHERE id >= (SELECT abs(random()) % (MAX(id) - MIN(id) + 1) + MIN(id) FROM entries)
+             ORDER BY id LIMIT 1",
+        )
+        .map_err(|e| e.to_string())?;
+    for _ in 0..sample_size {
+        let Some((path, is_dir)) = stmt
+            .query_row([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? != 0)))
+            .optional()
+            .map_err(|e| e.to_string())?
+        else {
+            continue;
+        };
+        report.sampled += 1;
+        match long_path::symlink_metadata(&path) {
+            Ok(meta) => {
+                // A symlinked directory is stored as a file row (lstat view).
+                if meta.is_dir() != is_dir {
+                    report.type_mismatch_count += 1;
+                    if report.type_mismatch_paths.len() < MAX_REPORTED_PATHS
+                        && !report.type_mismatch_paths.contains(&path)
+                    {
+                        report.type_mismatch_paths.push(path);
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                report.missing_count += 1;
+                if report.missing_paths.len() < MAX_REPORTED_PATHS
+                    && !report.missing_paths.contains(&path)
+                {
+                    report.missing_paths.push(path);
+                }
+            }
+            // Permission errors say nothing about index staleness.
+            Err(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        db_connection, init_db_tables, insert_test_entry, persist_cached_counts, set_meta,
+        temp_case_dir,
+    };
+
+    #[test]
+    fn reports_stale_rows_and_meta_drift() {
+        let root = temp_case_dir("integrity_report");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+
+        let present = root.join("present.txt");
+        fs::write(&present, "x").unwrap();
+        let gone = root.join("gone.txt");
+        insert_test_entry(&conn, &present, false, 1);
+        insert_test_entry(&conn, &gone, false, 2);
+        set_meta(&conn, "last_run_id", "1").unwrap();
+        persist_cached_counts(&conn, 5, None);
+
+        let report = check_index_integrity(&conn, true, false, true, 50).unwrap();
+        assert!(!report.ok);
+        assert!(report.sqlite_errors.is_empty());
+        assert_eq!(report.fts_ok, Some(true));
+        assert_eq!(report.entries_count, 2);
+        assert_eq!(report.max_row_run_id, 2);
+        assert_eq!(report.meta_issues.len(), 2, "{:?}", report.meta_issues);
+        assert_eq!(report.sampled, 50);
+        assert!(report.missing_count > 0);
+        assert_eq!(report.missing_paths, vec![gone.to_string_lossy().to_string()]);
+        assert_eq!(report.type_mismatch_count, 0);
+
+        // Mid-index the current run's rows are expected ahead of the meta,
+        // and the FTS check waits for an idle index.
+        let report = check_index_integrity(&conn, true, true, false, 50).unwrap();
+        assert!(report.indexing);
+        assert!(report.meta_issues.is_empty(), "{:?}", report.meta_issues);
+        assert_eq!(report.fts_ok, None);
+        assert!(report.missing_count > 0);
+
+        drop(conn);
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn fts_check_skips_a_locked_index_and_fails_a_corrupt_one() {
+        let root = temp_case_dir("integrity_fts");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+        insert_test_entry(&conn, &root.join("a.txt"), false, 1);
+
+        let writer = db_connection(&db_path).unwrap();
+        writer.execute_batch("BEGIN IMMEDIATE;").unwrap();
+        conn.busy_timeout(std::time::Duration::ZERO).unwrap();
+        let report = check_index_integrity(&conn, true, false, true, 0).unwrap();
+        assert_eq!(report.fts_ok, None);
+        writer.execute_batch("ROLLBACK;").unwrap();
+
+        // Zero the FTS index's segment pages (ids 1 and 10 are its structure
+        // and averages records).
+        conn.execute_batch(
+            "UPDATE entries_fts_data SET block = zeroblob(length(block)) WHERE id > 10;",
+        )
+        .unwrap();
+        let report = check_index_integrity(&conn, true, false, true, 0).unwrap();
+        assert_eq!(report.fts_ok, Some(false));
+        assert!(!report.ok);
+
+        drop((conn, writer));
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,159 @@
+//! Results shaped for launcher extensions (Raycast, Alfred, PowerToys Run,
+//! Flow Launcher), served by the HTTP API's `GET /launcher`. Each item has a
+//! title, a home-relative subtitle, a 0–1 score the launcher can merge with
+//! its own results, and ready-made actions, so an extension is a thin
+//! adapter rather than its own file walker.
+//!
+//! `format=alfred` returns Alfred's Script Filter JSON directly, so a
+//! Script Filter can simply `curl` the endpoint.
+
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::copy_as::{file_url, home_relative};
+use crate::{relevance_rank, EntryDto};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherActionDto {
+    /// `open`, `reveal` or `copyPath`.
+    pub kind: &'static str,
+    pub title: &'static str,
+    /// Path for `open`/`reveal`, text for `copyPath`.
+    pub target: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LauncherItemDto {
+    pub id: String,
+    pub title: String,
+    pub subtitle: String,
+    pub path: String,
+    /// `file://` URL, for launchers that open links rather than paths.
+    pub url: String,
+    pub is_dir: bool,
+    pub ext: Option<String>,
+    /// 1.0 for an exact name match, falling with match quality and then
+    /// slightly with position.
+    pub score: f64,
+    pub actions: Vec<LauncherActionDto>,
+}
+
+fn score(rank: u8, position: usize) -> f64 {
+    let base = f64::from(5 - rank.min(5)) / 5.0;
+    let score = (base - position as f64 * 0.001).max(0.0);
+    (score * 1000.0).round() / 1000.0
+}
+
+/// `results` in their search order, as launcher items.
+pub(crate) fn launcher_items(
+    results: &[EntryDto],
+    query: &str,
+    home: &Path,
+) -> Vec<LauncherItemDto> {
+    let query_lower = query.trim().to_lowercase();
+    let path_suffix = format!("/{query_lower}");
+    let mut buf = String::new();
+    results
+        .iter()
+        .enumerate()
+        .map(|(position, entry)| LauncherItemDto {
+            id: entry.path.clone(),
+            title: entry.name.clone(),
+            subtitle: home_relative(&entry.dir, home),
+            path: entry.path.clone(),
+            url: file_url(&entry.path),
+            is_dir: entry.is_dir,
+            ext: entry.ext.clone(),
+            score: score(relevance_rank(entry, &query_lower, &path_suffix, &mut buf), position),
+            actions: vec![
+                LauncherActionDto {
+                    kind: "open",
+                    title: "Open",
+                    target: entry.path.clone(),
+                },
+                LauncherActionDto {
+                    kind: "reveal",
+                    title: if cfg!(target_os = "windows") {
+                        "Show in Explorer"
+                    } else {
+                        "Reveal in Finder"
+                    },
+                    target: entry.path.clone(),
+                },
+                LauncherActionDto {
+                    kind: "copyPath",
+                    title: "Copy Path",
+                    target: entry.path.clone(),
+                },
+            ],
+        })
+        .collect()
+}
+
+/// Alfred Script Filter JSON: ⌘ reveals, ⌥ copies the path.
+pub(crate) fn alfred_items(items: &[LauncherItemDto]) -> Value {
+    let items: Vec<Value> = items
+        .iter()
+        .map(|item| {
+            json!({
+                "uid": item.id,
+                "type": "file",
+                "title": item.title,
+                "subtitle": item.subtitle,
+                "arg": item.path,
+                "quicklookurl": item.path,
+                "icon": { "type": "fileicon", "path": item.path },
+                "mods": {
+                    "cmd": { "arg": item.path, "subtitle": "Reveal in Finder" },
+                    "alt": { "arg": item.path, "subtitle": "Copy Path" },
+                },
+            })
+        })
+        .collect();
+    json!({ "items": items })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, is_dir: bool) -> EntryDto {
+        EntryDto {
+            is_dir,
+            ..crate::test_entry(path)
+        }
+    }
+
+    #[test]
+    fn scores_by_match_quality_and_position() {
+        let home = Path::new("/home/u");
+        let results = vec![
+            entry("/home/u/docs/report", true),
+            entry("/home/u/report draft.txt", false),
+            entry("/srv/old_report.txt", false),
+        ];
+        let items = launcher_items(&results, "Report", home);
+        let summary: Vec<(&str, &str, f64)> = items
+            .iter()
+            .map(|i| (i.title.as_str(), i.subtitle.as_str(), i.score))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("report", "~/docs", 1.0),
+                ("report draft.txt", "~", 0.799),
+                ("old_report.txt", "/srv", 0.598),
+            ]
+        );
+        assert_eq!(items[1].url, "file:///home/u/report%20draft.txt");
+        assert_eq!(items[0].actions[0].kind, "open");
+
+        let alfred = alfred_items(&items);
+        assert_eq!(alfred["items"][2]["arg"], "/srv/old_report.txt");
+        assert_eq!(alfred["items"][0]["icon"]["type"], "fileicon");
+    }
+}
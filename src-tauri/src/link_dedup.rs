@@ -0,0 +1,113 @@
+//! Folding symlinks into their targets in search results. ~/Library and
+//! /Volumes hold links to folders that are indexed in their own right, so a
+//! query could list the same item twice under two paths. When the caller asks
+//! for it, a link whose target is also in the results is dropped and the
+//! target row lists it in `linked_from`, which the UI shows as a link badge.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::long_path;
+use crate::EntryDto;
+
+/// Collapse every symlink in `entries` whose resolved target is another row
+/// of `entries`. Links to something outside the results stay as they are.
+/// Returns how many rows were folded away.
+pub(crate) fn collapse_links(entries: &mut Vec<EntryDto>) -> usize {
+    let by_path: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| (entry.path.as_str(), i))
+        .collect();
+
+    let mut folded: Vec<(usize, usize)> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let path = Path::new(&entry.path);
+        let is_link = long_path::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink());
+        if !is_link {
+            continue;
+        }
+        let Ok(target) = fs::canonicalize(path) else {
+            continue;
+        };
+        let target = long_path::strip(&target).to_string_lossy().into_owned();
+        if let Some(&target_idx) = by_path.get(target.as_str()) {
+            if target_idx != i {
+                folded.push((i, target_idx));
+            }
+        }
+    }
+    if folded.is_empty() {
+        return 0;
+    }
+
+    for &(link, target) in &folded {
+        let link_path = entries[link].path.clone();
+        entries[target]
+            .linked_from
+            .get_or_insert_with(Vec::new)
+            .push(link_path);
+    }
+    let mut index = 0;
+    entries.retain(|_| {
+        let keep = !folded.iter().any(|&(link, _)| link == index);
+        index += 1;
+        keep
+    });
+    folded.len()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn entry(path: &Path) -> EntryDto {
+        EntryDto {
+            is_dir: path.is_dir(),
+            ..crate::test_entry(&path.to_string_lossy())
+        }
+    }
+
+    #[test]
+    fn links_fold_into_targets_that_are_also_results() {
+        let root = crate::temp_case_dir("link_dedup");
+        fs::create_dir_all(root.join("Projects")).unwrap();
+        // macOS temp dirs sit behind /var -> /private/var.
+        let root = fs::canonicalize(root).unwrap();
+        let target = root.join("Projects");
+        let link = root.join("Projects link");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let dangling = root.join("gone link");
+        std::os::unix::fs::symlink(root.join("gone"), &dangling).unwrap();
+        let other = root.join("other.txt");
+        fs::write(&other, "x").unwrap();
+        let outside_link = root.join("other link");
+        std::os::unix::fs::symlink(&other, &outside_link).unwrap();
+
+        let mut entries = vec![
+            entry(&link),
+            entry(&target),
+            entry(&dangling),
+            entry(&outside_link),
+        ];
+        assert_eq!(collapse_links(&mut entries), 1);
+
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                target.to_str().unwrap(),
+                dangling.to_str().unwrap(),
+                outside_link.to_str().unwrap(),
+            ]
+        );
+        assert_eq!(
+            entries[0].linked_from,
+            Some(vec![link.to_string_lossy().to_string()])
+        );
+        assert_eq!(entries[2].linked_from, None, "target not among the results");
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,410 @@
+//! File-type icons from the freedesktop shared MIME database and icon
+//! themes: extension → MIME type via `mime/globs2`, MIME type → icon names
+//! (`mime/icons`, the type itself with `/` → `-`, `mime/generic-icons`,
+//! `<media>-x-generic`), then the first PNG among the user's icon theme, its
+//! `Inherits` chain and `hicolor`, closest to `ICON_SIZE`. Scalable (SVG)
+//! directories are skipped since results render PNG only.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const ICON_SIZE: u32 = 32;
+const FALLBACK_THEME: &str = "hicolor";
+const DEFAULT_THEME: &str = "Adwaita";
+
+/// Extension and icon tables merged from every `mime` dir.
+#[derive(Debug, Default)]
+pub struct MimeDb {
+    /// Lowercase extension → (MIME type, glob weight).
+    globs: HashMap<String, (String, u32)>,
+    icons: HashMap<String, String>,
+    generic_icons: HashMap<String, String>,
+}
+
+impl MimeDb {
+    /// `dirs` in XDG precedence order (user data dir first).
+    pub fn load(dirs: &[PathBuf]) -> Self {
+        let mut db = MimeDb::default();
+        for dir in dirs {
+            let mime_dir = dir.join("mime");
+            if let Ok(text) = fs::read_to_string(mime_dir.join("globs2")) {
+                for line in text.lines().filter(|l| !l.starts_with('#')) {
+                    // weight:type:glob[:flags]
+                    let mut parts = line.split(':');
+                    let (Some(weight), Some(mime), Some(glob)) =
+                        (parts.next(), parts.next(), parts.next())
+                    else {
+                        continue;
+                    };
+                    db.add_glob(glob, mime, weight.parse().unwrap_or(50));
+                }
+            } else if let Ok(text) = fs::read_to_string(mime_dir.join("globs")) {
+                for line in text.lines().filter(|l| !l.starts_with('#')) {
+                    if let Some((mime, glob)) = line.split_once(':') {
+                        db.add_glob(glob, mime, 50);
+                    }
+                }
+            }
+            for (file, table) in [
+                ("icons", &mut db.icons),
+                ("generic-icons", &mut db.generic_icons),
+            ] {
+                let Ok(text) = fs::read_to_string(mime_dir.join(file)) else {
+                    continue;
+                };
+                for (mime, icon) in text.lines().filter_map(|l| l.split_once(':')) {
+                    table
+                        .entry(mime.to_string())
+                        .or_insert_with(|| icon.to_string());
+                }
+            }
+        }
+        db
+    }
+
+    fn add_glob(&mut self, glob: &str, mime: &str, weight: u32) {
+        // Only plain "*.ext" globs map to an extension.
+        let Some(ext) = glob.strip_prefix("*.") else {
+            return;
+        };
+        if ext.is_empty() || ext.contains(['*', '?', '[', '.']) {
+            return;
+        }
+        let ext = ext.to_lowercase();
+        // Earlier dirs win ties, so user overrides beat system entries.
+        match self.globs.get(&ext) {
+            Some((_, existing)) if *existing >= weight => {}
+            _ => {
+                self.globs.insert(ext, (mime.to_string(), weight));
+            }
+        }
+    }
+
+    pub fn mime_for_ext(&self, ext: &str) -> Option<&str> {
+        self.globs
+            .get(&ext.to_lowercase())
+            .map(|(mime, _)| mime.as_str())
+    }
+
+    /// Icon names to try for `ext`, most specific first. `folder` and the
+    /// `__default__` placeholder map to the folder and unknown-file icons.
+    pub fn icon_names(&self, ext: &str) -> Vec<String> {
+        if ext.eq_ignore_ascii_case("folder") {
+            return vec!["folder".to_string(), "inode-directory".to_string()];
+        }
+        let mut names = Vec::new();
+        if let Some(mime) = self.mime_for_ext(ext) {
+            if let Some(icon) = self.icons.get(mime) {
+                names.push(icon.clone());
+            }
+            names.push(mime.replace('/', "-"));
+            if let Some(icon) = self.generic_icons.get(mime) {
+                names.push(icon.clone());
+            }
+            if let Some((media, _)) = mime.split_once('/') {
+                names.push(format!("{media}-x-generic"));
+            }
+        }
+        names.push("application-octet-stream".to_string());
+        names.push("unknown".to_string());
+        names.push("text-x-generic".to_string());
+        let mut seen = HashSet::new();
+        names.retain(|n| seen.insert(n.clone()));
+        names
+    }
+}
+
+/// `$XDG_DATA_HOME` (or `~/.local/share`), then `$XDG_DATA_DIRS` (or
+/// `/usr/local/share:/usr/share`).
+fn data_dirs() -> Vec<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = Vec::new();
+    match std::env::var_os("XDG_DATA_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => dirs.extend(home.map(|h| h.join(".local").join("share"))),
+    }
+    let system = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(
+        system
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+    dirs
+}
+
+/// Icon theme from GTK settings or KDE's `kdeglobals`, else Adwaita.
+fn current_theme() -> String {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")));
+    if let Some(config) = config {
+        let sources = [
+            (
+                config.join("gtk-4.0").join("settings.ini"),
+                "gtk-icon-theme-name",
+            ),
+            (
+                config.join("gtk-3.0").join("settings.ini"),
+                "gtk-icon-theme-name",
+            ),
+            (config.join("kdeglobals"), "Theme"),
+        ];
+        for (file, key) in sources {
+            let Ok(text) = fs::read_to_string(&file) else {
+                continue;
+            };
+            let theme = text.lines().find_map(|line| {
+                let (k, v) = line.split_once('=')?;
+                (k.trim() == key).then(|| v.trim().trim_matches('"').to_string())
+            });
+            if let Some(theme) = theme.filter(|t| !t.is_empty()) {
+                return theme;
+            }
+        }
+    }
+    DEFAULT_THEME.to_string()
+}
+
+/// One icon theme: its base directories and the fixed-size PNG
+/// subdirectories, nearest to `ICON_SIZE` first.
+#[derive(Debug)]
+struct Theme {
+    bases: Vec<PathBuf>,
+    dirs: Vec<String>,
+    inherits: Vec<String>,
+}
+
+fn load_theme(name: &str, icon_roots: &[PathBuf]) -> Option<Theme> {
+    let bases: Vec<PathBuf> = icon_roots
+        .iter()
+        .map(|root| root.join(name))
+        .filter(|base| base.is_dir())
+        .collect();
+    let index = bases
+        .iter()
+        .find_map(|base| fs::read_to_string(base.join("index.theme")).ok())?;
+
+    let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut current = String::new();
+    for line in index.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = section.to_string();
+        } else if let Some((key, value)) = line.split_once('=') {
+            sections
+                .entry(current.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    let header = sections.get("Icon Theme").cloned().unwrap_or_default();
+    let list = |key: &str| -> Vec<String> {
+        header
+            .get(key)
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut dirs: Vec<(u32, String)> = list("Directories")
+        .into_iter()
+        .filter_map(|dir| {
+            let section = sections.get(&dir)?;
+            if section
+                .get("Type")
+                .is_some_and(|t| t.eq_ignore_ascii_case("scalable"))
+            {
+                return None;
+            }
+            let size: u32 = section.get("Size")?.parse().ok()?;
+            let scale: u32 = section
+                .get("Scale")
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1);
+            Some((size * scale, dir))
+        })
+        .collect();
+    // Nearest to ICON_SIZE; on a tie the larger image scales down better.
+    dirs.sort_by_key(|(size, _)| (size.abs_diff(ICON_SIZE), u32::MAX - size));
+    Some(Theme {
+        bases,
+        dirs: dirs.into_iter().map(|(_, dir)| dir).collect(),
+        inherits: list("Inherits"),
+    })
+}
+
+/// `theme`, everything it inherits (depth first) and `hicolor` last.
+fn theme_chain(theme: &str, icon_roots: &[PathBuf]) -> Vec<Theme> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack = vec![theme.to_string()];
+    while let Some(name) = stack.pop() {
+        if name == FALLBACK_THEME || !seen.insert(name.clone()) {
+            continue;
+        }
+        if let Some(loaded) = load_theme(&name, icon_roots) {
+            stack.extend(loaded.inherits.iter().rev().cloned());
+            chain.push(loaded);
+        }
+    }
+    chain.extend(load_theme(FALLBACK_THEME, icon_roots));
+    chain
+}
+
+fn find_icon(names: &[String], chain: &[Theme], data_dirs: &[PathBuf]) -> Option<PathBuf> {
+    for name in names {
+        let file = format!("{name}.png");
+        for theme in chain {
+            for dir in &theme.dirs {
+                for base in &theme.bases {
+                    let candidate = base.join(dir).join(&file);
+                    if candidate.is_file() {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+    }
+    // Unthemed icons.
+    names.iter().find_map(|name| {
+        data_dirs
+            .iter()
+            .map(|dir| dir.join("pixmaps").join(format!("{name}.png")))
+            .find(|p| p.is_file())
+    })
+}
+
+fn icon_roots(data_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = std::env::var_os("HOME")
+        .map(|h| PathBuf::from(h).join(".icons"))
+        .into_iter()
+        .collect();
+    roots.extend(data_dirs.iter().map(|d| d.join("icons")));
+    roots
+}
+
+struct IconLookup {
+    data_dirs: Vec<PathBuf>,
+    mime: MimeDb,
+    chain: Vec<Theme>,
+}
+
+fn lookup() -> &'static IconLookup {
+    static LOOKUP: OnceLock<IconLookup> = OnceLock::new();
+    LOOKUP.get_or_init(|| {
+        let data_dirs = data_dirs();
+        let roots = icon_roots(&data_dirs);
+        IconLookup {
+            mime: MimeDb::load(&data_dirs),
+            chain: theme_chain(&current_theme(), &roots),
+            data_dirs,
+        }
+    })
+}
+
+fn icon_path_for_ext(
+    ext: &str,
+    mime: &MimeDb,
+    chain: &[Theme],
+    data_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    find_icon(&mime.icon_names(ext), chain, data_dirs)
+}
+
+/// PNG bytes of the themed icon for files with extension `ext`.
+pub fn load_icon_png_by_ext(ext: &str) -> Option<Vec<u8>> {
+    let lookup = lookup();
+    let path = icon_path_for_ext(ext, &lookup.mime, &lookup.chain, &lookup.data_dirs)?;
+    fs::read(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_ext_to_nearest_png_through_theme_chain() {
+        let root = crate::temp_case_dir("linux_icon");
+        let data = root.join("share");
+        let mime = data.join("mime");
+        fs::create_dir_all(&mime).unwrap();
+        fs::write(
+            mime.join("globs2"),
+            "# generated\n50:text/plain:*.txt\n80:text/x-rust:*.rs\n50:application/x-foo:*.tar.foo\n",
+        )
+        .unwrap();
+        fs::write(mime.join("generic-icons"), "text/x-rust:text-x-script\n").unwrap();
+
+        let icons = data.join("icons");
+        let write_theme = |name: &str, index: &str| {
+            fs::create_dir_all(icons.join(name)).unwrap();
+            fs::write(icons.join(name).join("index.theme"), index).unwrap();
+        };
+        write_theme(
+            "Custom",
+            "[Icon Theme]\nInherits=hicolor\nDirectories=16x16/mimetypes,scalable/mimetypes\n\n\
+             [16x16/mimetypes]\nSize=16\nType=Fixed\n\n[scalable/mimetypes]\nSize=64\nType=Scalable\n",
+        );
+        write_theme(
+            "hicolor",
+            "[Icon Theme]\nDirectories=16x16/mimetypes,48x48/mimetypes,32x32/mimetypes\n\n\
+             [16x16/mimetypes]\nSize=16\n\n[32x32/mimetypes]\nSize=32\n\n[48x48/mimetypes]\nSize=48\n",
+        );
+        let put = |rel: &str| {
+            let path = icons.join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, rel.as_bytes()).unwrap();
+        };
+        put("Custom/scalable/mimetypes/text-plain.png");
+        put("hicolor/16x16/mimetypes/text-plain.png");
+        put("hicolor/32x32/mimetypes/text-plain.png");
+        put("hicolor/48x48/mimetypes/text-x-script.png");
+        put("Custom/16x16/mimetypes/unknown.png");
+
+        let dirs = vec![data.clone()];
+        let db = MimeDb::load(&dirs);
+        assert_eq!(db.mime_for_ext("RS"), Some("text/x-rust"));
+        assert_eq!(db.mime_for_ext("foo"), None);
+        assert_eq!(
+            db.icon_names("rs")[..3],
+            ["text-x-rust", "text-x-script", "text-x-generic"]
+        );
+
+        let chain = theme_chain("Custom", std::slice::from_ref(&icons));
+        assert_eq!(chain.len(), 2);
+        let found = |ext: &str| {
+            icon_path_for_ext(ext, &db, &chain, &dirs).map(|p| {
+                p.strip_prefix(&icons)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+        };
+        // Scalable dirs are skipped; hicolor's 32px beats its 16px.
+        assert_eq!(
+            found("txt").as_deref(),
+            Some("hicolor/32x32/mimetypes/text-plain.png")
+        );
+        assert_eq!(
+            found("rs").as_deref(),
+            Some("hicolor/48x48/mimetypes/text-x-script.png")
+        );
+        assert_eq!(
+            found("zzz").as_deref(),
+            Some("Custom/16x16/mimetypes/unknown.png")
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
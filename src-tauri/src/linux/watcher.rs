@@ -0,0 +1,519 @@
+//! Live updates on Linux. With `CAP_SYS_ADMIN` (and `CAP_DAC_READ_SEARCH`
+//! to turn directory handles back into paths), one fanotify filesystem mark
+//! per watched filesystem reports every create/delete/rename/write on it, so
+//! there is no per-directory watch and no `max_user_watches` ceiling. Without
+//! the capabilities, or on kernels before 5.9 (`FAN_REPORT_DFID_NAME`), it
+//! falls back to recursive inotify watches through `notify`. Roots on network
+//! and FUSE mounts, which neither sees remote changes on, are polled instead.
+//!
+//! Every source only says *which* paths changed; the batch goes through the
+//! same `process_watcher_paths` pipeline FSEvents feeds on macOS.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CString, OsStr};
+use std::fs;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering as AtomicOrdering;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::AppHandle;
+
+use crate::{
+    cached_effective_ignore_rules, emit_and_persist_cached_counts, pathpriority, poll_watcher,
+    process_watcher_paths, reload_pathignore, should_skip_path, start_full_index_worker, AppResult,
+    AppState, WatcherHints, HOT_WATCH_DEBOUNCE, STATUS_EMIT_MIN_INTERVAL,
+};
+
+/// How long one wait for events may block before `watcher_stop` is checked.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const FAN_EVENTS: u64 = libc::FAN_CREATE
+    | libc::FAN_DELETE
+    | libc::FAN_MOVED_FROM
+    | libc::FAN_MOVED_TO
+    | libc::FAN_CLOSE_WRITE
+    | libc::FAN_ATTRIB
+    | libc::FAN_ONDIR;
+
+/// Size of `struct fanotify_event_metadata`.
+const EVENT_METADATA_LEN: usize = 24;
+/// `struct fanotify_event_info_header` plus the `fsid` that follows it.
+const INFO_FID_HEADER_LEN: usize = 12;
+
+/// One change from a fanotify read: the parent directory's handle (a
+/// `struct file_handle`, verbatim) on the filesystem `fsid`, plus the entry
+/// name, `.` when the directory itself changed.
+#[derive(Debug, PartialEq, Eq)]
+struct FanEvent {
+    fsid: [u8; 8],
+    handle: Vec<u8>,
+    name: Vec<u8>,
+}
+
+/// Split a fanotify read buffer into DFID_NAME events. Returns the events
+/// and whether the kernel queue overflowed.
+fn parse_fan_events(buf: &[u8]) -> (Vec<FanEvent>, bool) {
+    let mut events = Vec::new();
+    let mut overflow = false;
+    let mut offset = 0;
+    while offset + EVENT_METADATA_LEN <= buf.len() {
+        let meta = &buf[offset..];
+        let event_len = u32::from_ne_bytes(meta[0..4].try_into().unwrap()) as usize;
+        let metadata_len = u16::from_ne_bytes(meta[6..8].try_into().unwrap()) as usize;
+        let mask = u64::from_ne_bytes(meta[8..16].try_into().unwrap());
+        if event_len < metadata_len || offset + event_len > buf.len() {
+            break;
+        }
+        if mask & libc::FAN_Q_OVERFLOW != 0 {
+            overflow = true;
+        }
+        let mut info = &meta[metadata_len..event_len];
+        while info.len() >= INFO_FID_HEADER_LEN + 8 {
+            let info_type = info[0];
+            let info_len = u16::from_ne_bytes(info[2..4].try_into().unwrap()) as usize;
+            if info_len < INFO_FID_HEADER_LEN + 8 || info_len > info.len() {
+                break;
+            }
+            if info_type == libc::FAN_EVENT_INFO_TYPE_DFID_NAME {
+                let record = &info[..info_len];
+                let fid = &record[INFO_FID_HEADER_LEN..];
+                let handle_bytes = u32::from_ne_bytes(fid[0..4].try_into().unwrap()) as usize;
+                let handle_len = 8 + handle_bytes;
+                if handle_len <= fid.len() {
+                    let name = &fid[handle_len..];
+                    let name = &name[..name.iter().position(|&b| b == 0).unwrap_or(name.len())];
+                    events.push(FanEvent {
+                        fsid: record[4..12].try_into().unwrap(),
+                        handle: fid[..handle_len].to_vec(),
+                        name: name.to_vec(),
+                    });
+                }
+            }
+            info = &info[info_len..];
+        }
+        offset += event_len;
+    }
+    (events, overflow)
+}
+
+/// The filesystem id `fanotify` reports for events on `path`'s filesystem.
+fn fsid_of(path: &Path) -> Option<[u8; 8]> {
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    // fsid_t is two ints with private fields; the event carries the same bytes.
+    Some(unsafe { std::mem::transmute::<libc::fsid_t, [u8; 8]>(stat.f_fsid) })
+}
+
+struct Fanotify {
+    fd: OwnedFd,
+    /// An open directory per marked filesystem, for `open_by_handle_at`.
+    mounts: HashMap<[u8; 8], OwnedFd>,
+}
+
+impl Fanotify {
+    fn new(roots: &[PathBuf]) -> AppResult<Self> {
+        let raw = unsafe {
+            libc::fanotify_init(
+                libc::FAN_CLASS_NOTIF
+                    | libc::FAN_CLOEXEC
+                    | libc::FAN_NONBLOCK
+                    | libc::FAN_REPORT_DFID_NAME,
+                (libc::O_RDONLY | libc::O_CLOEXEC) as libc::c_uint,
+            )
+        };
+        if raw < 0 {
+            return Err(format!(
+                "fanotify_init: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw) };
+        let mut mounts = HashMap::new();
+        for root in roots {
+            let Some(fsid) = fsid_of(root) else {
+                continue;
+            };
+            if mounts.contains_key(&fsid) {
+                continue;
+            }
+            let c_root = CString::new(root.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+            let marked = unsafe {
+                libc::fanotify_mark(
+                    fd.as_raw_fd(),
+                    libc::FAN_MARK_ADD | libc::FAN_MARK_FILESYSTEM,
+                    FAN_EVENTS,
+                    libc::AT_FDCWD,
+                    c_root.as_ptr(),
+                )
+            };
+            if marked != 0 {
+                return Err(format!(
+                    "fanotify_mark {}: {}",
+                    root.display(),
+                    std::io::Error::last_os_error()
+                ));
+            }
+            let mount = fs::File::open(root).map_err(|e| e.to_string())?;
+            mounts.insert(fsid, OwnedFd::from(mount));
+        }
+        if mounts.is_empty() {
+            return Err("no filesystem could be marked".to_string());
+        }
+        Ok(Self { fd, mounts })
+    }
+
+    /// Wait up to `timeout` and drain everything queued. Returns the changed
+    /// paths and whether events were lost to a queue overflow.
+    fn poll(&self, timeout: Duration) -> (Vec<PathBuf>, bool) {
+        let mut pollfd = libc::pollfd {
+            fd: self.fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        if unsafe { libc::poll(&mut pollfd, 1, timeout.as_millis() as libc::c_int) } <= 0 {
+            return (Vec::new(), false);
+        }
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut paths = Vec::new();
+        let mut overflow = false;
+        // Directory handles repeat heavily within one drain (a build writing
+        // hundreds of files into one folder); resolve each once.
+        let mut dirs: HashMap<([u8; 8], Vec<u8>), Option<PathBuf>> = HashMap::new();
+        loop {
+            let n = unsafe { libc::read(self.fd.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                break;
+            }
+            let (events, lost) = parse_fan_events(&buf[..n as usize]);
+            overflow |= lost;
+            for event in events {
+                let dir = dirs
+                    .entry((event.fsid, event.handle))
+                    .or_insert_with_key(|(fsid, handle)| self.resolve(fsid, handle));
+                let Some(dir) = dir else {
+                    continue; // the directory is already gone
+                };
+                if event.name.is_empty() || event.name == b"." {
+                    paths.push(dir.clone());
+                } else {
+                    paths.push(dir.join(OsStr::from_bytes(&event.name)));
+                }
+            }
+        }
+        (paths, overflow)
+    }
+
+    /// Path of the directory behind a `struct file_handle`.
+    fn resolve(&self, fsid: &[u8; 8], handle: &[u8]) -> Option<PathBuf> {
+        let mount = self.mounts.get(fsid)?;
+        // open_by_handle_at wants the handle int-aligned.
+        let mut aligned = vec![0u32; handle.len().div_ceil(4)];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                handle.as_ptr(),
+                aligned.as_mut_ptr().cast(),
+                handle.len(),
+            );
+        }
+        let raw = unsafe {
+            libc::syscall(
+                libc::SYS_open_by_handle_at,
+                mount.as_raw_fd(),
+                aligned.as_mut_ptr(),
+                libc::O_PATH | libc::O_CLOEXEC,
+            )
+        };
+        if raw < 0 {
+            return None;
+        }
+        let fd = unsafe { OwnedFd::from_raw_fd(raw as libc::c_int) };
+        fs::read_link(format!("/proc/self/fd/{}", fd.as_raw_fd())).ok()
+    }
+}
+
+enum Source {
+    Fanotify(Fanotify),
+    Inotify {
+        _watcher: RecommendedWatcher,
+        rx: mpsc::Receiver<notify::Result<Event>>,
+    },
+}
+
+impl Source {
+    fn open(roots: &[PathBuf]) -> AppResult<Self> {
+        match Fanotify::new(roots) {
+            Ok(fanotify) => {
+                eprintln!(
+                    "[linux/watcher] fanotify on {} filesystem(s)",
+                    fanotify.mounts.len()
+                );
+                return Ok(Source::Fanotify(fanotify));
+            }
+            Err(e) => eprintln!("[linux/watcher] fanotify unavailable ({e}); using inotify"),
+        }
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = RecommendedWatcher::new(tx, Config::default())
+            .map_err(|e| format!("inotify watcher creation failed: {e}"))?;
+        let mut watched = 0;
+        for root in roots {
+            // A partial recursive watch (max_user_watches hit) still covers
+            // whatever it reached, so only a root that got nothing is skipped.
+            match watcher.watch(root, RecursiveMode::Recursive) {
+                Ok(()) => watched += 1,
+                Err(e) => eprintln!("[linux/watcher] inotify on {}: {e}", root.display()),
+            }
+        }
+        if watched == 0 {
+            return Err("no directories could be watched".to_string());
+        }
+        Ok(Source::Inotify {
+            _watcher: watcher,
+            rx,
+        })
+    }
+
+    fn poll(&self, timeout: Duration) -> (Vec<PathBuf>, bool) {
+        match self {
+            Source::Fanotify(fanotify) => fanotify.poll(timeout),
+            Source::Inotify { rx, .. } => {
+                let mut paths = Vec::new();
+                let mut overflow = false;
+                let first = rx.recv_timeout(timeout).ok();
+                for event in first.into_iter().chain(rx.try_iter()) {
+                    match event {
+                        Ok(event) => {
+                            overflow |= event.need_rescan();
+                            paths.extend(event.paths);
+                        }
+                        Err(e) => eprintln!("[linux/watcher] inotify: {e}"),
+                    }
+                }
+                (paths, overflow)
+            }
+        }
+    }
+}
+
+/// The indexed roots, each paired with its canonical form: fanotify reports
+/// paths as `/proc/self/fd` spells them.
+fn watch_roots(state: &AppState) -> Vec<(PathBuf, PathBuf)> {
+    let roots = poll_watcher::indexed_roots(state);
+    let mut pairs: Vec<(PathBuf, PathBuf)> = roots
+        .into_iter()
+        .map(|stored| {
+            (
+                fs::canonicalize(&stored).unwrap_or_else(|_| stored.clone()),
+                stored,
+            )
+        })
+        .collect();
+    // Longest canonical prefix first so nested roots map correctly.
+    pairs.sort_by_key(|(canonical, _)| std::cmp::Reverse(canonical.as_os_str().len()));
+    pairs
+}
+
+/// `path` in the form rows are stored under, or `None` outside every root.
+fn to_stored(path: &Path, roots: &[(PathBuf, PathBuf)]) -> Option<PathBuf> {
+    roots.iter().find_map(|(canonical, stored)| {
+        if let Ok(rest) = path.strip_prefix(stored) {
+            return Some(stored.join(rest));
+        }
+        path.strip_prefix(canonical)
+            .ok()
+            .map(|rest| stored.join(rest))
+    })
+}
+
+/// Start live updates over the current roots: fanotify or inotify for local
+/// filesystems, a polling thread for network and FUSE mounts, and polling
+/// for everything when neither notifier can watch anything.
+pub(crate) fn start(app: AppHandle, state: AppState) {
+    let roots = watch_roots(&state);
+    let (polled, watched): (Vec<PathBuf>, Vec<PathBuf>) = roots
+        .iter()
+        .map(|(canonical, _)| canonical.clone())
+        .partition(|root| poll_watcher::needs_polling(root));
+    let source = match Source::open(&watched) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("[linux/watcher] no notifier ({e}), polling instead");
+            poll_watcher::start(
+                Some(app),
+                state.clone(),
+                poll_watcher::indexed_roots(&state),
+            );
+            return;
+        }
+    };
+    let (poll_tx, poll_rx) = mpsc::channel::<Vec<PathBuf>>();
+    let poller = (!polled.is_empty())
+        .then(|| poll_watcher::spawn(&state, polled, move |changed| poll_tx.send(changed).is_ok()));
+    state.watcher_active.store(true, AtomicOrdering::Release);
+    std::thread::spawn(move || {
+        event_loop(&app, &state, &source, &poll_rx, &roots);
+        drop(poller);
+        state.watcher_active.store(false, AtomicOrdering::Release);
+        eprintln!("[linux/watcher] watcher stopped");
+    });
+}
+
+fn event_loop(
+    app: &AppHandle,
+    state: &AppState,
+    source: &Source,
+    polled: &mpsc::Receiver<Vec<PathBuf>>,
+    roots: &[(PathBuf, PathBuf)],
+) {
+    let mut pending: HashSet<PathBuf> = HashSet::new();
+    let mut deadline: Option<Instant> = None;
+    let mut last_status_emit = Instant::now();
+    let mut pending_status_emit = false;
+
+    while !state.watcher_stop.load(AtomicOrdering::Acquire) {
+        let wait = deadline
+            .map(|due| {
+                due.saturating_duration_since(Instant::now())
+                    .min(POLL_INTERVAL)
+            })
+            .unwrap_or(POLL_INTERVAL);
+        let (mut paths, overflow) = source.poll(wait);
+        paths.extend(polled.try_iter().flatten());
+        if overflow {
+            // Changes were dropped; only a full pass can reconcile them.
+            eprintln!("[linux/watcher] event queue overflowed, reindexing");
+            let _ = start_full_index_worker(app.clone(), state.clone());
+        }
+
+        if !paths.is_empty() {
+            let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+            let hot_dirs = state.hot_dirs.lock().clone();
+            for path in paths {
+                if path == state.config_file_path {
+                    reload_pathignore(Some(app), state);
+                    continue;
+                }
+                if path == state.pathpriority_file_path {
+                    *state.hot_dirs.lock() =
+                        pathpriority::load_hot_dirs(&state.pathpriority_file_path, &state.home_dir);
+                    continue;
+                }
+                let Some(path) = to_stored(&path, roots) else {
+                    continue;
+                };
+                if !should_skip_path(&path, &ignored_roots, &ignored_patterns) {
+                    // A change in a hot directory pulls the whole batch forward.
+                    let debounce = if pathpriority::is_hot_path(&path, &hot_dirs) {
+                        HOT_WATCH_DEBOUNCE
+                    } else {
+                        state.index_tuning.read().watch_debounce()
+                    };
+                    let due = Instant::now() + debounce;
+                    deadline = Some(deadline.map_or(due, |d| d.min(due)));
+                    pending.insert(path);
+                }
+            }
+        }
+
+        if deadline.is_some_and(|due| Instant::now() >= due) {
+            process_watcher_paths(
+                Some(app),
+                state,
+                &mut pending,
+                &mut WatcherHints::default(),
+                &mut deadline,
+                &mut last_status_emit,
+                &mut pending_status_emit,
+            );
+        }
+        if pending_status_emit && last_status_emit.elapsed() >= STATUS_EMIT_MIN_INTERVAL {
+            emit_and_persist_cached_counts(Some(app), state);
+            last_status_emit = Instant::now();
+            pending_status_emit = false;
+        }
+        state
+            .watcher_backlog
+            .store(pending.len(), AtomicOrdering::Relaxed);
+    }
+
+    process_watcher_paths(
+        Some(app),
+        state,
+        &mut pending,
+        &mut WatcherHints::default(),
+        &mut deadline,
+        &mut last_status_emit,
+        &mut pending_status_emit,
+    );
+    state.watcher_backlog.store(0, AtomicOrdering::Relaxed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dfid_name_event(mask: u64, fsid: [u8; 8], handle: &[u8], name: &[u8]) -> Vec<u8> {
+        let mut info = vec![libc::FAN_EVENT_INFO_TYPE_DFID_NAME, 0, 0, 0];
+        info.extend_from_slice(&fsid);
+        info.extend_from_slice(&(handle.len() as u32).to_ne_bytes());
+        info.extend_from_slice(&1i32.to_ne_bytes());
+        info.extend_from_slice(handle);
+        info.extend_from_slice(name);
+        info.push(0);
+        while info.len() % 4 != 0 {
+            info.push(0);
+        }
+        let info_len = info.len() as u16;
+        info[2..4].copy_from_slice(&info_len.to_ne_bytes());
+
+        let event_len = (EVENT_METADATA_LEN + info.len()) as u32;
+        let mut event = Vec::new();
+        event.extend_from_slice(&event_len.to_ne_bytes());
+        event.extend_from_slice(&[libc::FANOTIFY_METADATA_VERSION, 0]);
+        event.extend_from_slice(&(EVENT_METADATA_LEN as u16).to_ne_bytes());
+        event.extend_from_slice(&mask.to_ne_bytes());
+        event.extend_from_slice(&libc::FAN_NOFD.to_ne_bytes());
+        event.extend_from_slice(&42i32.to_ne_bytes());
+        event.extend_from_slice(&info);
+        event
+    }
+
+    #[test]
+    fn parses_dfid_name_records() {
+        let fsid = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut buf = dfid_name_event(libc::FAN_CREATE, fsid, &[9; 8], b"report.pdf");
+        buf.extend(dfid_name_event(
+            libc::FAN_ATTRIB | libc::FAN_ONDIR,
+            fsid,
+            &[7; 12],
+            b".",
+        ));
+        let (events, overflow) = parse_fan_events(&buf);
+        assert!(!overflow);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].fsid, fsid);
+        assert_eq!(events[0].handle.len(), 16);
+        assert_eq!(&events[0].handle[8..], &[9; 8]);
+        assert_eq!(events[0].name, b"report.pdf");
+        assert_eq!(events[1].name, b".");
+
+        let mut overflow_event = vec![0u8; EVENT_METADATA_LEN];
+        overflow_event[0..4].copy_from_slice(&(EVENT_METADATA_LEN as u32).to_ne_bytes());
+        overflow_event[6..8].copy_from_slice(&(EVENT_METADATA_LEN as u16).to_ne_bytes());
+        overflow_event[8..16].copy_from_slice(&libc::FAN_Q_OVERFLOW.to_ne_bytes());
+        assert_eq!(parse_fan_events(&overflow_event), (Vec::new(), true));
+
+        let roots = vec![(PathBuf::from("/real/home"), PathBuf::from("/home/u"))];
+        assert_eq!(
+            to_stored(Path::new("/real/home/a.txt"), &roots),
+            Some(PathBuf::from("/home/u/a.txt"))
+        );
+        assert_eq!(to_stored(Path::new("/etc/passwd"), &roots), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! `watch_query`: live result views. A watch holds the parsed query and the
+//! paths its view shows; each watcher batch is checked against every watch
+//! and turned into an added / updated / removed diff, emitted as
+//! `query_watch_diff`, so an open view stays current without re-searching.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::mem_search::EntryMatcher;
+use crate::query::parse_query;
+use crate::EntryDto;
+
+pub(crate) const QUERY_WATCH_DIFF_EVENT: &str = "query_watch_diff";
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryWatchDiffEvent {
+    pub watch_id: String,
+    /// Entries that now match and weren't in the view.
+    pub added: Vec<EntryDto>,
+    /// Entries in the view that changed (size, mtime) and still match.
+    pub updated: Vec<EntryDto>,
+    /// Paths to drop: deleted, or renamed/changed so they no longer match.
+    /// A removed folder's contents in the view are listed too.
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Watch {
+    matcher: EntryMatcher,
+    shown: HashSet<String>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct LiveQueryRegistry {
+    next_id: AtomicU64,
+    watches: Mutex<HashMap<String, Watch>>,
+}
+
+/// Lowercase extension of a file name, as the indexer stores it.
+fn ext_of(name: &str) -> Option<String> {
+    name.rsplit_once('.')
+        .filter(|(stem, ext)| !stem.is_empty() && !ext.is_empty())
+        .map(|(_, ext)| ext.to_lowercase())
+}
+
+impl LiveQueryRegistry {
+    /// Register `query` with the paths its view starts with; returns the id
+    /// diffs are emitted under.
+    pub(crate) fn watch(&self, query: &str, shown: impl IntoIterator<Item = String>) -> String {
+        let id = format!("watch-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let watch = Watch {
+            matcher: EntryMatcher::new(&parse_query(query)),
+            shown: shown.into_iter().collect(),
+        };
+        self.watches.lock().insert(id.clone(), watch);
+        id
+    }
+
+    /// Returns false when `id` wasn't registered.
+    pub(crate) fn unwatch(&self, id: &str) -> bool {
+        self.watches.lock().remove(id).is_some()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.watches.lock().is_empty()
+    }
+
+    /// Diffs for one watcher batch, one per watch that saw a change.
+    pub(crate) fn diff(
+        &self,
+        upserted: &[EntryDto],
+        deleted: &[String],
+    ) -> Vec<QueryWatchDiffEvent> {
+        let sep = std::path::MAIN_SEPARATOR;
+        let mut watches = self.watches.lock();
+        let mut diffs = Vec::new();
+        for (id, watch) in watches.iter_mut() {
+            let mut diff = QueryWatchDiffEvent {
+                watch_id: id.clone(),
+                ..QueryWatchDiffEvent::default()
+            };
+            for path in deleted {
+                let prefix = format!("{path}{sep}");
+                let gone: Vec<String> = watch
+                    .shown
+                    .iter()
+                    .filter(|p| *p == path || p.starts_with(&prefix))
+                    .cloned()
+                    .collect();
+                if gone.is_empty() {
+                    // Not in the snapshot, but the view may have paged it in.
+                    let (dir, name) = path.rsplit_once(sep).unwrap_or(("", path));
+                    if watch.matcher.matches(name, dir, ext_of(name).as_deref()) {
+                        diff.removed.push(path.clone());
+                    }
+                }
+                for path in gone {
+                    watch.shown.remove(&path);
+                    diff.removed.push(path);
+                }
+            }
+            for entry in upserted {
+                let ext = if entry.is_dir {
+                    None
+                } else {
+                    entry.ext.as_deref()
+                };
+                let matched = watch.matcher.matches(&entry.name, &entry.dir, ext);
+                match (matched, watch.shown.contains(&entry.path)) {
+                    (true, true) => diff.updated.push(entry.clone()),
+                    (true, false) => {
+                        watch.shown.insert(entry.path.clone());
+                        diff.added.push(entry.clone());
+                    }
+                    (false, true) => {
+                        watch.shown.remove(&entry.path);
+                        diff.removed.push(entry.path.clone());
+                    }
+                    (false, false) => {}
+                }
+            }
+            if !(diff.added.is_empty() && diff.updated.is_empty() && diff.removed.is_empty()) {
+                diffs.push(diff);
+            }
+        }
+        diffs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dir: &str, name: &str) -> EntryDto {
+        let sep = std::path::MAIN_SEPARATOR;
+        EntryDto {
+            ext: ext_of(name),
+            size: Some(1),
+            mtime: Some(1),
+            ..crate::test_entry(&format!("{dir}{sep}{name}"))
+        }
+    }
+
+    #[test]
+    fn diffs_batches_against_each_watch() {
+        let sep = std::path::MAIN_SEPARATOR;
+        let docs = format!("{sep}home{sep}docs");
+        let registry = LiveQueryRegistry::default();
+        let report = entry(&docs, "report.pdf");
+        let pdfs = registry.watch("*.pdf", [report.path.clone()]);
+        let notes = registry.watch("notes", Vec::new());
+        assert_ne!(pdfs, notes);
+
+        let mut diffs = registry.diff(
+            &[
+                report.clone(),
+                entry(&docs, "plan.pdf"),
+                entry(&docs, "notes.txt"),
+                entry(&docs, "other.md"),
+            ],
+            &[],
+        );
+        diffs.sort_by(|a, b| a.watch_id.cmp(&b.watch_id));
+        let names = |v: &[EntryDto]| v.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(names(&diffs[0].updated), vec!["report.pdf"]);
+        assert_eq!(names(&diffs[0].added), vec!["plan.pdf"]);
+        assert_eq!(names(&diffs[1].added), vec!["notes.txt"]);
+
+        // Renamed out of the query, then the folder holding the rest deleted.
+        let renamed = entry(&docs, "report.txt");
+        let diffs = registry.diff(&[renamed], std::slice::from_ref(&report.path));
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].removed, vec![report.path.clone()]);
+        let diffs = registry.diff(&[], std::slice::from_ref(&docs));
+        let removed: HashSet<String> = diffs.into_iter().flat_map(|d| d.removed).collect();
+        assert_eq!(
+            removed,
+            HashSet::from([
+                format!("{docs}{sep}plan.pdf"),
+                format!("{docs}{sep}notes.txt")
+            ])
+        );
+
+        assert!(registry.unwatch(&pdfs));
+        assert!(!registry.unwatch(&pdfs));
+        assert!(registry.diff(&[entry(&docs, "new.pdf")], &[]).is_empty());
+    }
+}
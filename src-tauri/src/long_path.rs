@@ -0,0 +1,126 @@
+//! Windows paths of `MAX_PATH` (260) characters or more. Win32 rejects them
+//! unless they're spelled extended-length (`\\?\C:\…`, `\\?\UNC\server\…`),
+//! so walks past that depth errored out and were counted as permission
+//! errors. Index rows, ignore rules and the UI keep the plain spelling:
+//! `extended` is applied at the filesystem call and `strip` to whatever a
+//! walk started from an extended root hands back. Elsewhere both are no-ops.
+
+use std::borrow::Cow;
+use std::fs;
+use std::io;
+use std::path::Path;
+#[cfg(target_os = "windows")]
+use std::path::PathBuf;
+
+#[cfg(target_os = "windows")]
+const VERBATIM_PREFIX: &str = r"\\?\";
+#[cfg(target_os = "windows")]
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+/// Longest path the shell (`start`, `explorer /select`) accepts as is.
+#[cfg(target_os = "windows")]
+const MAX_SHELL_PATH: usize = 259;
+
+/// `path` in extended-length form. Relative paths and ones already in a
+/// `\\?\` or `\\.\` form are returned unchanged; `/` separators become `\`,
+/// since the extended form turns off Win32's normalization.
+#[cfg(target_os = "windows")]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    let Some(text) = path.to_str() else {
+        return Cow::Borrowed(path);
+    };
+    if text.starts_with(VERBATIM_PREFIX) || text.starts_with(r"\\.\") || !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+    let native = text.replace('/', "\\");
+    let extended = match native.strip_prefix(r"\\") {
+        Some(unc) => format!("{VERBATIM_UNC_PREFIX}{unc}"),
+        None => format!("{VERBATIM_PREFIX}{native}"),
+    };
+    Cow::Owned(PathBuf::from(extended))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// `path` back in the plain spelling rows are stored in.
+#[cfg(target_os = "windows")]
+pub(crate) fn strip(path: &Path) -> Cow<'_, Path> {
+    let Some(text) = path.to_str() else {
+        return Cow::Borrowed(path);
+    };
+    if let Some(unc) = text.strip_prefix(VERBATIM_UNC_PREFIX) {
+        return Cow::Owned(PathBuf::from(format!(r"\\{unc}")));
+    }
+    match text.strip_prefix(VERBATIM_PREFIX) {
+        Some(plain) if plain.as_bytes().get(1) == Some(&b':') => {
+            Cow::Owned(PathBuf::from(plain))
+        }
+        _ => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn strip(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// `strip` for path strings, as watchers and IPC callers pass them.
+pub(crate) fn strip_str(path: &str) -> Cow<'_, str> {
+    match strip(Path::new(path)) {
+        Cow::Borrowed(_) => Cow::Borrowed(path),
+        Cow::Owned(plain) => Cow::Owned(plain.to_string_lossy().into_owned()),
+    }
+}
+
+pub(crate) fn symlink_metadata<P: AsRef<Path>>(path: P) -> io::Result<fs::Metadata> {
+    fs::symlink_metadata(extended(path.as_ref()))
+}
+
+/// A spelling of `path` the shell can open or select: the path itself when
+/// it is short enough, else its 8.3 short name (if the volume keeps those).
+#[cfg(target_os = "windows")]
+pub(crate) fn shell_path(path: &str) -> String {
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetShortPathNameW;
+
+    use crate::win::com_guard::to_wide;
+
+    let path = strip_str(path);
+    if path.chars().count() <= MAX_SHELL_PATH {
+        return path.into_owned();
+    }
+    let wide = to_wide(&extended(Path::new(path.as_ref())).to_string_lossy());
+    let mut buf = vec![0u16; wide.len()];
+    let len = unsafe { GetShortPathNameW(PCWSTR(wide.as_ptr()), Some(&mut buf)) } as usize;
+    if len == 0 || len > buf.len() {
+        return path.into_owned();
+    }
+    let short = PathBuf::from(String::from_utf16_lossy(&buf[..len]));
+    strip(&short).to_string_lossy().into_owned()
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_and_strip_round_trip_drive_and_unc_paths() {
+        for (plain, long) in [
+            (r"C:\Users\me\deep", r"\\?\C:\Users\me\deep"),
+            (r"\\nas\share\dir", r"\\?\UNC\nas\share\dir"),
+        ] {
+            assert_eq!(extended(Path::new(plain)), Path::new(long));
+            assert_eq!(strip(Path::new(long)), Path::new(plain));
+            assert_eq!(extended(Path::new(long)), Path::new(long));
+        }
+        assert_eq!(extended(Path::new("C:/a/b")), Path::new(r"\\?\C:\a\b"));
+        assert_eq!(extended(Path::new(r"rel\dir")), Path::new(r"rel\dir"));
+        assert_eq!(strip(Path::new(r"C:\plain")), Path::new(r"C:\plain"));
+        assert_eq!(strip_str(r"\\?\C:\x"), r"C:\x");
+        // Volume GUID paths have no plain spelling.
+        let volume = r"\\?\Volume{0b1c}\dir";
+        assert_eq!(strip(Path::new(volume)), Path::new(volume));
+    }
+}
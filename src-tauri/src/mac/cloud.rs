@@ -0,0 +1,24 @@
+//! iCloud Drive "dataless" files: evicted items whose content lives only in
+//! the cloud. `lstat` on them is free, but reading them (or handing them to
+//! an app) makes the File Provider download the whole file, so callers check
+//! the flag before doing anything that would materialize one. The indexer
+//! stores the flag in the `cloud_only` column, refreshed whenever FSEvents
+//! reports an inode change (eviction and download both do).
+
+use std::fs::Metadata;
+use std::os::macos::fs::MetadataExt;
+use std::path::Path;
+
+/// `SF_DATALESS` from <sys/stat.h>: the file's content is not on disk.
+const SF_DATALESS: u32 = 0x4000_0000;
+
+pub fn is_dataless(meta: &Metadata) -> bool {
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+/// `lstat`-only check; never triggers a download.
+pub fn is_dataless_path(path: &Path) -> bool {
+    std::fs::symlink_metadata(path)
+        .map(|m| is_dataless(&m))
+        .unwrap_or(false)
+}
@@ -3,7 +3,7 @@ use fsevent_sys::core_foundation as cf;
 use fsevent_sys::core_foundation::CFRunLoopRef;
 
 use std::ffi::CStr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::Arc;
@@ -13,6 +13,10 @@ const LATENCY_SECONDS: cf::CFTimeInterval = 2.0;
 
 pub enum FsEvent {
     Paths(Vec<PathBuf>),
+    /// Paths whose flags say they're gone; the consumer needn't stat them.
+    Removed(Vec<PathBuf>),
+    /// Renames seen as old→new pairs.
+    Renames(Vec<(PathBuf, PathBuf)>),
     MustScanSubDirs(PathBuf),
     HistoryDone,
 }
@@ -35,6 +39,46 @@ extern "C" {
     fn CFRunLoopIsWaiting(runloop: CFRunLoopRef) -> cf::Boolean;
 }
 
+#[derive(Debug, PartialEq, Eq)]
+enum ItemChange {
+    /// Removed and not since created or renamed into place.
+    Removed,
+    /// Only xattrs, Finder info or ownership changed, none of which an
+    /// indexed row records.
+    MetadataOnly,
+    Changed,
+}
+
+/// FSEvents coalesces everything that happened to a path since the last
+/// callback into one set of flags, so a path deleted and recreated carries
+/// both ItemRemoved and ItemCreated.
+fn classify_item(flag: fs::FSEventStreamEventFlags) -> ItemChange {
+    let recreated = fs::kFSEventStreamEventFlagItemCreated | fs::kFSEventStreamEventFlagItemRenamed;
+    let row_fields = recreated
+        | fs::kFSEventStreamEventFlagItemRemoved
+        | fs::kFSEventStreamEventFlagItemModified
+        | fs::kFSEventStreamEventFlagItemInodeMetaMod;
+    let metadata_only = fs::kFSEventStreamEventFlagItemXattrMod
+        | fs::kFSEventStreamEventFlagItemFinderInfoMod
+        | fs::kFSEventStreamEventFlagItemChangeOwner;
+    if flag & fs::kFSEventStreamEventFlagItemRemoved != 0 && flag & recreated == 0 {
+        ItemChange::Removed
+    } else if flag & row_fields == 0 && flag & metadata_only != 0 {
+        ItemChange::MetadataOnly
+    } else {
+        ItemChange::Changed
+    }
+}
+
+/// FSEvents reports a rename as two ItemRenamed events with consecutive ids,
+/// old path first. Whether the event is the first or second half isn't
+/// flagged, so a pair also needs the old path gone and the new one present.
+fn is_rename_pair(from: &Path, from_id: u64, to: &Path, to_id: u64) -> bool {
+    to_id == from_id + 1
+        && std::fs::symlink_metadata(from).is_err()
+        && std::fs::symlink_metadata(to).is_ok()
+}
+
 extern "C" fn stream_callback(
     _stream_ref: fs::FSEventStreamRef,
     info: *mut std::os::raw::c_void,
@@ -47,6 +91,10 @@ extern "C" fn stream_callback(
         let info = &*(info as *const CallbackInfo);
         let paths_ptr = event_paths as *const *const std::os::raw::c_char;
         let mut normal_paths: Vec<PathBuf> = Vec::new();
+        let mut removed_paths: Vec<PathBuf> = Vec::new();
+        let mut renames: Vec<(PathBuf, PathBuf)> = Vec::new();
+        // The first half of a possible rename pair, waiting on the next event.
+        let mut rename_from: Option<(PathBuf, u64)> = None;
 
         for i in 0..num_events {
             let flag = *event_flags.add(i);
@@ -68,6 +116,24 @@ extern "C" fn stream_callback(
             let c_str = CStr::from_ptr(*paths_ptr.add(i));
             let path = PathBuf::from(c_str.to_string_lossy().into_owned());
 
+            // Only folders: a file has no rows under it to move, and editors
+            // saving through a temp file would otherwise pair up too.
+            let renamed_dir =
+                fs::kFSEventStreamEventFlagItemRenamed | fs::kFSEventStreamEventFlagItemIsDir;
+            if flag & renamed_dir == renamed_dir {
+                match rename_from.take() {
+                    Some((from, from_id)) if is_rename_pair(&from, from_id, &path, event_id) => {
+                        renames.push((from, path));
+                    }
+                    previous => {
+                        normal_paths.extend(previous.map(|(from, _)| from));
+                        rename_from = Some((path, event_id));
+                    }
+                }
+                continue;
+            }
+            normal_paths.extend(rename_from.take().map(|(from, _)| from));
+
             if flag & fs::kFSEventStreamEventFlagHistoryDone != 0 {
                 let _ = info.tx.send(FsEvent::HistoryDone);
                 continue;
@@ -78,9 +144,22 @@ extern "C" fn stream_callback(
                 continue;
             }
 
-            normal_paths.push(path);
+            match classify_item(flag) {
+                ItemChange::Removed => removed_paths.push(path),
+                ItemChange::MetadataOnly => {}
+                ItemChange::Changed => normal_paths.push(path),
+            }
         }
 
+        normal_paths.extend(rename_from.map(|(from, _)| from));
+        if !renames.is_empty() {
+            let _ = info.tx.send(FsEvent::Renames(renames));
+        }
+        // Before Paths, so a path that came back later in this batch ends up
+        // treated as present.
+        if !removed_paths.is_empty() {
+            let _ = info.tx.send(FsEvent::Removed(removed_paths));
+        }
         if !normal_paths.is_empty() {
             let _ = info.tx.send(FsEvent::Paths(normal_paths));
         }
@@ -229,3 +308,36 @@ impl Drop for FsEventWatcher {
         self.stop();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_coalesced_item_flags() {
+        let file = fs::kFSEventStreamEventFlagItemIsFile;
+        let removed = fs::kFSEventStreamEventFlagItemRemoved | file;
+        assert_eq!(classify_item(removed), ItemChange::Removed);
+        assert_eq!(
+            classify_item(removed | fs::kFSEventStreamEventFlagItemModified),
+            ItemChange::Removed
+        );
+        assert_eq!(
+            classify_item(removed | fs::kFSEventStreamEventFlagItemCreated),
+            ItemChange::Changed
+        );
+        assert_eq!(
+            classify_item(fs::kFSEventStreamEventFlagItemXattrMod | file),
+            ItemChange::MetadataOnly
+        );
+        assert_eq!(
+            classify_item(
+                fs::kFSEventStreamEventFlagItemXattrMod
+                    | fs::kFSEventStreamEventFlagItemInodeMetaMod
+                    | file
+            ),
+            ItemChange::Changed
+        );
+        assert_eq!(classify_item(0), ItemChange::Changed);
+    }
+}
@@ -1,2 +1,10 @@
+pub mod cloud;
 pub mod fsevent_watcher;
+pub mod pasteboard;
+pub mod quick_look;
+pub mod services;
+pub mod share;
+pub mod workspace;
 pub mod spotlight_search;
+pub mod storage;
+pub mod xattr;
@@ -0,0 +1,27 @@
+//! File copies on the general pasteboard, written as `NSURL` objects the way
+//! Finder's Copy does, so Paste in Finder, Mail or any file-accepting app
+//! gets the files themselves rather than their paths.
+
+use objc2::rc::{autoreleasepool, Retained};
+use objc2::runtime::ProtocolObject;
+use objc2_app_kit::{NSPasteboard, NSPasteboardWriting};
+use objc2_foundation::{NSArray, NSString, NSURL};
+
+pub fn write_file_urls(paths: &[String]) -> Result<(), String> {
+    autoreleasepool(|_| {
+        let urls: Vec<Retained<ProtocolObject<dyn NSPasteboardWriting>>> = paths
+            .iter()
+            .map(|path| {
+                ProtocolObject::from_retained(NSURL::fileURLWithPath(&NSString::from_str(path)))
+            })
+            .collect();
+        let objects = NSArray::from_retained_slice(&urls);
+        let pasteboard = NSPasteboard::generalPasteboard();
+        pasteboard.clearContents();
+        if pasteboard.writeObjects(&objects) {
+            Ok(())
+        } else {
+            Err("Failed to copy files to clipboard".to_string())
+        }
+    })
+}
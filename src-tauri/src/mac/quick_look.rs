@@ -0,0 +1,106 @@
+//! In-process Quick Look: the shared `QLPreviewPanel` fed by a small data
+//! source over the selected paths, so Space previews inside the app (with
+//! the panel's own arrow-key navigation across the selection) instead of
+//! spawning a separate `qlmanage` window. Must run on the main thread.
+
+use std::cell::RefCell;
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject, NSObject, NSObjectProtocol};
+use objc2::{define_class, msg_send, AnyThread, DefinedClass};
+use objc2_foundation::{NSString, NSURL};
+
+// QLPreviewPanel lives in QuickLookUI, re-exported by the Quartz umbrella.
+#[link(name = "Quartz", kind = "framework")]
+extern "C" {}
+
+struct PreviewItems {
+    urls: Vec<Retained<NSURL>>,
+}
+
+define_class!(
+    // SAFETY: NSObject has no subclassing requirements and there is no Drop.
+    #[unsafe(super(NSObject))]
+    #[name = "EverythingQuickLookSource"]
+    #[ivars = PreviewItems]
+    struct PreviewSource;
+
+    impl PreviewSource {
+        #[unsafe(method(numberOfPreviewItemsInPreviewPanel:))]
+        fn number_of_items(&self, _panel: &AnyObject) -> isize {
+            self.ivars().urls.len() as isize
+        }
+
+        // NSURL conforms to QLPreviewItem.
+        #[unsafe(method_id(previewPanel:previewItemAtIndex:))]
+        fn item_at(&self, _panel: &AnyObject, index: isize) -> Option<Retained<NSURL>> {
+            usize::try_from(index)
+                .ok()
+                .and_then(|i| self.ivars().urls.get(i).cloned())
+        }
+    }
+
+    unsafe impl NSObjectProtocol for PreviewSource {}
+);
+
+impl PreviewSource {
+    fn new(paths: &[String]) -> Retained<Self> {
+        let urls = paths
+            .iter()
+            .map(|path| NSURL::fileURLWithPath(&NSString::from_str(path)))
+            .collect();
+        let this = Self::alloc().set_ivars(PreviewItems { urls });
+        unsafe { msg_send![super(this), init] }
+    }
+}
+
+thread_local! {
+    // The panel holds its data source weakly; keep the current one alive.
+    static SOURCE: RefCell<Option<Retained<PreviewSource>>> = const { RefCell::new(None) };
+}
+
+fn shared_panel() -> Result<Retained<AnyObject>, String> {
+    let class = AnyClass::get(c"QLPreviewPanel")
+        .ok_or_else(|| "Quick Look is not available.".to_string())?;
+    let panel: Option<Retained<AnyObject>> = unsafe { msg_send![class, sharedPreviewPanel] };
+    panel.ok_or_else(|| "Quick Look panel could not be created.".to_string())
+}
+
+/// Preview `paths` starting at `index`. Calling it again while the panel is
+/// showing the same items closes it, like Space in Finder.
+pub fn toggle(paths: &[String], index: usize) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let panel = shared_panel()?;
+    let visible: bool = unsafe { msg_send![&*panel, isVisible] };
+    let same_items = SOURCE.with(|source| {
+        source.borrow().as_ref().is_some_and(|s| {
+            s.ivars().urls.len() == paths.len()
+                && s.ivars()
+                    .urls
+                    .iter()
+                    .zip(paths)
+                    .all(|(url, path)| url.path().is_some_and(|p| p.to_string() == *path))
+        })
+    });
+    if visible && same_items {
+        unsafe {
+            let _: () = msg_send![&*panel, orderOut: None::<&AnyObject>];
+        }
+        return Ok(());
+    }
+
+    let source = PreviewSource::new(paths);
+    unsafe {
+        let _: () = msg_send![&*panel, setDataSource: &*source];
+        let _: () = msg_send![&*panel, reloadData];
+        let _: () = msg_send![
+            &*panel,
+            setCurrentPreviewItemIndex: index.min(paths.len() - 1) as isize
+        ];
+        let _: () = msg_send![&*panel, makeKeyAndOrderFront: None::<&AnyObject>];
+    }
+    SOURCE.with(|current| *current.borrow_mut() = Some(source));
+    Ok(())
+}
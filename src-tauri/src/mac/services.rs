@@ -0,0 +1,113 @@
+//! "Search in Everything" in the system Services menu. Info.plist declares
+//! the `searchInEverything` message; this registers the object that answers
+//! it. Selected text is searched as typed; a selected folder searches its
+//! contents (`<folder>/`) and a selected file searches its name.
+
+use std::cell::RefCell;
+use std::path::Path;
+
+use objc2::rc::Retained;
+use objc2::runtime::{AnyObject, NSObject, NSObjectProtocol};
+use objc2::{define_class, msg_send, AnyThread, DefinedClass, MainThreadMarker};
+use objc2_app_kit::{
+    NSApplication, NSPasteboard, NSPasteboardTypeFileURL, NSPasteboardTypeString,
+    NSUpdateDynamicServices,
+};
+use objc2_foundation::{NSString, NSURL};
+
+struct ProviderIvars {
+    on_query: Box<dyn Fn(String)>,
+}
+
+define_class!(
+    // SAFETY: NSObject has no subclassing requirements and there is no Drop.
+    #[unsafe(super(NSObject))]
+    #[name = "EverythingServicesProvider"]
+    #[ivars = ProviderIvars]
+    struct ServicesProvider;
+
+    impl ServicesProvider {
+        #[unsafe(method(searchInEverything:userData:error:))]
+        fn search_in_everything(
+            &self,
+            pboard: &NSPasteboard,
+            _user_data: Option<&NSString>,
+            _error: *mut *mut NSString,
+        ) {
+            if let Some(query) = query_from_pasteboard(pboard) {
+                (self.ivars().on_query)(query);
+            }
+        }
+    }
+
+    unsafe impl NSObjectProtocol for ServicesProvider {}
+);
+
+thread_local! {
+    // NSApplication does not retain its services provider.
+    static PROVIDER: RefCell<Option<Retained<ServicesProvider>>> = const { RefCell::new(None) };
+}
+
+/// The search a Services invocation asks for: the first selected file or
+/// folder, otherwise the selected text.
+fn query_from_pasteboard(pboard: &NSPasteboard) -> Option<String> {
+    let file_path = pboard.pasteboardItems().and_then(|items| {
+        items.iter().find_map(|item| {
+            let url = item.stringForType(unsafe { NSPasteboardTypeFileURL })?;
+            NSURL::URLWithString(&url)?.path().map(|p| p.to_string())
+        })
+    });
+    if let Some(path) = file_path {
+        return query_for_path(Path::new(&path));
+    }
+    let text = pboard.stringForType(unsafe { NSPasteboardTypeString })?;
+    let text = text.to_string();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn query_for_path(path: &Path) -> Option<String> {
+    if path.is_dir() {
+        Some(format!("{}/", path.to_string_lossy().trim_end_matches('/')))
+    } else {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }
+}
+
+/// Install the services provider; `on_query` runs on the main thread with
+/// the query to show. Call once from app setup.
+pub fn register(on_query: impl Fn(String) + 'static) -> Result<(), String> {
+    let mtm = MainThreadMarker::new()
+        .ok_or_else(|| "Services must be registered on the main thread.".to_string())?;
+    let provider = ServicesProvider::alloc().set_ivars(ProviderIvars {
+        on_query: Box::new(on_query),
+    });
+    let provider: Retained<ServicesProvider> = unsafe { msg_send![super(provider), init] };
+    let object: &AnyObject = &provider;
+    unsafe { NSApplication::sharedApplication(mtm).setServicesProvider(Some(object)) };
+    NSUpdateDynamicServices();
+    PROVIDER.with(|current| *current.borrow_mut() = Some(provider));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_search_their_contents_and_files_their_name() {
+        let dir = std::env::temp_dir().join(format!("everything-services-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, b"x").unwrap();
+
+        assert_eq!(
+            query_for_path(&dir),
+            Some(format!("{}/", dir.to_string_lossy()))
+        );
+        assert_eq!(query_for_path(&file).as_deref(), Some("notes.txt"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,50 @@
+//! The standard Share menu (AirDrop, Mail, Messages, …) for selected files,
+//! via `NSSharingServicePicker` anchored at the click point in the webview.
+//! Must run on the main thread.
+
+use std::ffi::c_void;
+
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::AnyThread;
+use objc2_app_kit::{NSSharingServicePicker, NSView};
+use objc2_foundation::{NSArray, NSPoint, NSRect, NSRectEdge, NSSize, NSString, NSURL};
+
+/// Show the share picker for `paths` at (`x`, `y`), window-relative logical
+/// pixels with a top-left origin like `clientX`/`clientY`.
+///
+/// # Safety
+///
+/// `ns_view` must be a live `NSView`, e.g. from `WebviewWindow::ns_view`.
+pub unsafe fn show_picker(
+    ns_view: *mut c_void,
+    paths: &[String],
+    x: f64,
+    y: f64,
+) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    let view = unsafe { (ns_view as *const NSView).as_ref() }
+        .ok_or_else(|| "Window has no view.".to_string())?;
+    let urls: Vec<Retained<AnyObject>> = paths
+        .iter()
+        .map(|path| {
+            Retained::into_super(Retained::into_super(NSURL::fileURLWithPath(
+                &NSString::from_str(path),
+            )))
+        })
+        .collect();
+    let items = NSArray::from_retained_slice(&urls);
+    let picker =
+        unsafe { NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &items) };
+    // AppKit views have a bottom-left origin unless flipped.
+    let y = if view.isFlipped() {
+        y
+    } else {
+        view.bounds().size.height - y
+    };
+    let anchor = NSRect::new(NSPoint::new(x, y), NSSize::new(1.0, 1.0));
+    picker.showRelativeToRect_ofView_preferredEdge(anchor, view, NSRectEdge::MinY);
+    Ok(())
+}
@@ -73,7 +73,11 @@ pub fn search_spotlight(home_dir: &Path, query: &str) -> SpotlightResult {
             .map(|p| p.to_string_lossy().to_string())
             .unwrap_or_else(|| "/".to_string());
 
-        let is_dir = path.is_dir();
+        // One lstat per hit: mdfind happily returns evicted iCloud files, and
+        // anything beyond lstat on those can start a download.
+        let meta = std::fs::symlink_metadata(path).ok();
+        let is_dir = meta.as_ref().is_some_and(|m| m.is_dir());
+        let cloud_only = meta.as_ref().is_some_and(super::cloud::is_dataless);
 
         let ext = if is_dir {
             None
@@ -83,7 +87,6 @@ pub fn search_spotlight(home_dir: &Path, query: &str) -> SpotlightResult {
                 .map(|e| e.to_lowercase())
         };
 
-        let meta = std::fs::symlink_metadata(path).ok();
         let size = meta.as_ref().filter(|m| m.is_file()).map(|m| m.len() as i64);
         let mtime = meta
             .and_then(|m| m.modified().ok())
@@ -98,6 +101,10 @@ pub fn search_spotlight(home_dir: &Path, query: &str) -> SpotlightResult {
             ext,
             size,
             mtime,
+            spoken_label: None,
+            mtime_display: None,
+            cloud_only: cloud_only.then_some(true),
+            linked_from: None,
         });
 
         if entries.len() >= SPOTLIGHT_MAX_RESULTS {
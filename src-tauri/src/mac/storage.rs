@@ -0,0 +1,34 @@
+//! Storage class of the volume holding a path, used to size the index walk.
+//! macOS has no cheap rotational flag outside IOKit, so local volumes are
+//! classified by filesystem: APFS is the SSD default, HFS+ survives mostly on
+//! spinning and external disks.
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::scan_tuning::StorageKind;
+
+pub fn storage_kind(path: &Path) -> StorageKind {
+    let Ok(c_path) = CString::new(path.as_os_str().as_bytes()) else {
+        return StorageKind::Unknown;
+    };
+    let mut buf: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut buf) } != 0 {
+        return StorageKind::Unknown;
+    }
+    if buf.f_flags & libc::MNT_LOCAL as u32 == 0 {
+        return StorageKind::Network;
+    }
+    let name_bytes: Vec<u8> = buf
+        .f_fstypename
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    match name_bytes.as_slice() {
+        b"apfs" => StorageKind::Ssd,
+        b"hfs" => StorageKind::Hdd,
+        _ => StorageKind::Unknown,
+    }
+}
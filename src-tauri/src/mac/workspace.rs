@@ -0,0 +1,26 @@
+//! Opening and revealing through `NSWorkspace` rather than spawning `open`,
+//! so a path goes to Launch Services as a file URL, whatever its length or
+//! the characters in its name.
+
+use objc2::rc::{autoreleasepool, Retained};
+use objc2_app_kit::NSWorkspace;
+use objc2_foundation::{NSArray, NSString, NSURL};
+
+fn file_url(path: &str) -> Retained<NSURL> {
+    NSURL::fileURLWithPath(&NSString::from_str(path))
+}
+
+/// Open `path` with its default app. False when Launch Services could not,
+/// e.g. when no app claims the file type.
+pub fn open_path(path: &str) -> bool {
+    autoreleasepool(|_| NSWorkspace::sharedWorkspace().openURL(&file_url(path)))
+}
+
+/// Bring up Finder with `paths` selected, one window per folder.
+pub fn reveal_paths(paths: &[String]) {
+    autoreleasepool(|_| {
+        let urls: Vec<Retained<NSURL>> = paths.iter().map(|path| file_url(path)).collect();
+        NSWorkspace::sharedWorkspace()
+            .activateFileViewerSelectingURLs(&NSArray::from_retained_slice(&urls));
+    })
+}
@@ -0,0 +1,81 @@
+//! Extended attributes of a path (not following symlinks), for
+//! `get_properties`: the attribute names and the `com.apple.quarantine` flag
+//! Gatekeeper sets on downloads.
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+pub const QUARANTINE_ATTR: &str = "com.apple.quarantine";
+
+fn c_path(path: &Path) -> Option<CString> {
+    CString::new(path.as_os_str().as_bytes()).ok()
+}
+
+pub fn list(path: &Path) -> Vec<String> {
+    let Some(c_path) = c_path(path) else {
+        return Vec::new();
+    };
+    let len = unsafe {
+        libc::listxattr(
+            c_path.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+    if len <= 0 {
+        return Vec::new();
+    }
+    let mut buf = vec![0u8; len as usize];
+    let len = unsafe {
+        libc::listxattr(
+            c_path.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_char,
+            buf.len(),
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+    if len <= 0 {
+        return Vec::new();
+    }
+    buf.truncate(len as usize);
+    buf.split(|&b| b == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).to_string())
+        .collect()
+}
+
+pub fn read(path: &Path, name: &str) -> Option<Vec<u8>> {
+    let c_path = c_path(path)?;
+    let c_name = CString::new(name).ok()?;
+    let len = unsafe {
+        libc::getxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            0,
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+    if len < 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; len as usize];
+    let len = unsafe {
+        libc::getxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+            0,
+            libc::XATTR_NOFOLLOW,
+        )
+    };
+    if len < 0 {
+        return None;
+    }
+    buf.truncate(len as usize);
+    Some(buf)
+}
@@ -9,7 +9,7 @@ use std::{
     path::{Path, PathBuf},
     process::{Command, Stdio},
     sync::{
-        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering as AtomicOrdering},
         Arc, OnceLock,
     },
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
@@ -19,23 +19,75 @@ use parking_lot::{Mutex, RwLock};
 use rusqlite::{
     params, params_from_iter, types::Value as SqlValue, Connection, OptionalExtension,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager, State};
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+mod archive;
+mod autostart;
+mod backups;
+mod case_rename;
+mod copy_as;
 mod fd_search;
+mod glob_class;
+mod file_hash;
+mod file_ops;
+mod folder_groups;
+mod http_api;
+mod humanize;
+mod ignore_list;
+mod index_events;
+mod index_tuning;
+mod integrity;
+mod launcher;
+mod link_dedup;
 mod daemon;
+mod db_location;
+mod deep_link;
+mod dir_size;
+mod drag_out;
+mod disk_usage;
+mod duplicates;
+mod es_cli;
+mod export;
+#[cfg(target_os = "linux")]
+mod linux;
 #[cfg(target_os = "macos")]
 mod mac;
+mod live_query;
+mod long_path;
 mod mcp_server;
+mod media_meta;
+mod mem_resident;
 mod mem_search;
+mod name_norm;
 mod pathindexing;
+mod pathpriority;
+mod permission_log;
+mod pins;
+mod poll_watcher;
+mod portable;
+mod preflight;
+mod preview;
+mod profiles;
+mod properties;
+mod putback;
 mod query;
+mod recent;
 mod rescan;
+mod saved_searches;
+mod scan_tuning;
+mod search_cache;
+mod search_cancel;
+mod search_history;
+mod terminal;
+mod thumbnails;
+mod tray;
+mod undo;
 #[cfg(target_os = "windows")]
 mod win;
 use fd_search::{FdSearchCache, FdSearchResultDto};
 use query::{escape_like, parse_query, SearchMode};
+use search_cancel::SearchCancelToken;
+
 
 const DEFAULT_LIMIT: u32 = 300;
 const SHORT_QUERY_LIMIT: u32 = 100;
@@ -47,9 +99,11 @@ pub(crate) const BATCH_SIZE: usize = 10_000;
 const SCAN_CHANNEL_CAP: usize = 8;
 const RECENT_OP_TTL: Duration = Duration::from_secs(2);
 pub(crate) const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Debounce for watcher batches that touch a `.pathpriority` hot directory.
+pub(crate) const HOT_WATCH_DEBOUNCE: Duration = Duration::from_millis(50);
 const NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(60);
 const NEGATIVE_CACHE_FALLBACK_WINDOW: Duration = Duration::from_millis(550);
-const DB_VERSION: i32 = 7;
+const DB_VERSION: i32 = 10;
 /// Index DB filename inside the app data dir. Shared with the MCP server's
 /// fallback path derivation (`mcp_server::default_db_path`).
 pub(crate) const DB_FILE_NAME: &str = "index.db";
@@ -82,12 +136,16 @@ CREATE TABLE IF NOT EXISTS entries (
     path       TEXT NOT NULL UNIQUE,
     name       TEXT NOT NULL,
     dir        TEXT NOT NULL,
+    name_norm  TEXT NOT NULL,
+    dir_norm   TEXT NOT NULL,
     is_dir     INTEGER NOT NULL,
     ext        TEXT,
     mtime      INTEGER,
+    mtime_nsec INTEGER,
     size       INTEGER,
     indexed_at INTEGER NOT NULL,
-    run_id     INTEGER NOT NULL DEFAULT 0
+    run_id     INTEGER NOT NULL DEFAULT 0,
+    cloud_only INTEGER NOT NULL DEFAULT 0
 );";
 
 const DROP_FTS_TRIGGERS_SQL: &str = "\
@@ -112,11 +170,20 @@ const REBUILD_FTS_SQL: &str = "INSERT INTO entries_fts(entries_fts) VALUES('rebu
 /// Secondary indexes on `entries`. Single source of truth shared by
 /// `ensure_db_indexes` (startup/catchup) and `finalize_fresh_index` (which
 /// builds them before ANALYZE so the planner gets stats for all of them).
+/// The `_size`/`_mtime` composites mirror `sort_clause` term for term (the
+/// mtime ones index `COALESCE(mtime, 0)` and the sub-second tiebreak), so
+/// size/mtime-sorted ext searches and exact-name lookups read rows in index
+/// order instead of sorting.
 const CREATE_ENTRIES_INDEXES_SQL: &str = "\
 CREATE INDEX IF NOT EXISTS idx_entries_dir_ext_name_nocase ON entries(dir, ext, name COLLATE NOCASE);
-CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime);
+CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime, mtime_nsec);
 CREATE INDEX IF NOT EXISTS idx_entries_name_nocase ON entries(name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_entries_name_norm ON entries(name_norm COLLATE NOCASE);
 CREATE INDEX IF NOT EXISTS idx_entries_ext_name ON entries(ext, name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS idx_entries_ext_size ON entries(ext, size);
+CREATE INDEX IF NOT EXISTS idx_entries_ext_mtime ON entries(ext, COALESCE(mtime, 0), COALESCE(mtime_nsec, 0));
+CREATE INDEX IF NOT EXISTS idx_entries_name_mtime
+    ON entries(name COLLATE NOCASE, COALESCE(mtime, 0), COALESCE(mtime_nsec, 0));
 CREATE INDEX IF NOT EXISTS idx_entries_indexed_at ON entries(indexed_at);";
 const DEFERRED_DIR_NAMES: &[&str] = &[
     "Library", ".Trash", ".Trashes",
@@ -195,6 +262,23 @@ pub struct EntryDto {
     pub ext: Option<String>,
     pub size: Option<i64>,
     pub mtime: Option<i64>,
+    /// Screen-reader announcement ("report.pdf, PDF file, in Documents,
+    /// modified 2 days ago"). Only filled when the caller asks for it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spoken_label: Option<String>,
+    /// Locale-formatted relative mtime ("yesterday", "3 weeks ago"). Only
+    /// filled when the caller passes a time locale.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime_display: Option<String>,
+    /// Set when the file's content isn't on local disk (iCloud dataless file
+    /// or files-on-demand placeholder); opening it starts a download. Read
+    /// from the index's `cloud_only` column, not stat'd per result.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cloud_only: Option<bool>,
+    /// Symlinks to this entry folded into it by `collapse_links`; shown as a
+    /// link badge instead of as rows of their own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linked_from: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -209,30 +293,164 @@ struct IndexStatusDto {
     indexed: u64,
     current_path: String,
     background_active: bool,
+    paused: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct IndexProgressEvent {
-    scanned: u64,
-    indexed: u64,
-    current_path: String,
+struct ReindexSubtreeDto {
+    upserted: usize,
+    deleted: usize,
+    elapsed_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct IndexStateEvent {
-    state: String,
-    message: Option<String>,
-    is_catchup: bool,
+struct TransferredDto {
+    source: String,
+    dest: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferFailureDto {
+    path: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchTransferDto {
+    request_id: u64,
+    completed: Vec<TransferredDto>,
+    failed: Vec<TransferFailureDto>,
+    /// Items after a cancel are neither completed nor failed.
+    cancelled: bool,
+    elapsed_ms: u64,
+}
+
+/// Emitted after each selected item of a `batch_move` / `batch_copy`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferProgressEvent {
+    request_id: u64,
+    op: &'static str,
+    index: usize,
+    total: usize,
+    source: String,
+    dest: Option<String>,
+    error: Option<String>,
+}
+
+/// Emitted while a `batch_move` / `batch_copy` writes files, so a single
+/// large folder shows progress too. `done` / `total` count files across the
+/// whole selection.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransferFileProgressEvent {
+    request_id: u64,
+    op: &'static str,
+    done: u64,
+    total: u64,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompressToZipDto {
+    request_id: u64,
+    path: String,
+    entries: u64,
+    size: u64,
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtractArchiveDto {
+    request_id: u64,
+    /// The extracted folder, or the lone top-level item of the archive.
+    path: String,
+    entries: u64,
+    /// Index rows written for the extracted tree.
+    indexed: usize,
+    elapsed_ms: u64,
+}
+
+/// Emitted while `extract_archive` writes members, at most every 200ms.
+/// `total` is unknown for tarballs, which are read as a stream.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtractProgressEvent {
+    request_id: u64,
+    done: u64,
+    total: Option<u64>,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FindDuplicatesDto {
+    request_id: u64,
+    groups: u64,
+    files: u64,
+    reclaimable_bytes: u64,
+    /// Same-size files that had to be hashed.
+    candidates: u64,
+    cancelled: bool,
+    elapsed_ms: u64,
+}
+
+/// Emitted by `find_duplicates` for each confirmed group.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateGroupEvent {
+    request_id: u64,
+    group: duplicates::DuplicateGroupDto,
+    /// Running total over every group reported so far.
+    reclaimable_bytes: u64,
+}
+
+/// Emitted while `compress_to_zip` writes entries, at most every 200ms.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ZipProgressEvent {
+    request_id: u64,
+    done: u64,
+    total: u64,
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompactIndexDto {
+    before_bytes: u64,
+    after_bytes: u64,
+    elapsed_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct IndexUpdatedEvent {
+struct IndexStatsDto {
     entries_count: u64,
-    last_updated: i64,
-    permission_errors: u64,
+    file_count: u64,
+    dir_count: u64,
+    /// Sum of indexed file sizes.
+    total_size: u64,
+    /// Files and bytes per extension, biggest first.
+    by_extension: Vec<disk_usage::UsageBucketDto>,
+    /// Files and bytes per entry directly under the scan root, plus one
+    /// bucket per extra root outside it, biggest first.
+    by_top_folder: Vec<disk_usage::UsageBucketDto>,
+    db_size: u64,
+    wal_size: u64,
+    /// Duration of the last from-scratch index; `None` before one finished.
+    last_full_index_ms: Option<u64>,
+    last_full_index_at: Option<i64>,
+    watcher_active: bool,
+    /// Changes queued by the watcher and not yet written to the index.
+    watcher_backlog: u64,
+    elapsed_ms: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -249,12 +467,84 @@ struct SearchExecution {
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SearchResultDto {
+    /// The caller's request id, or one assigned here; pass it to
+    /// `cancel_search` to abandon the query.
+    request_id: u64,
     entries: Vec<EntryDto>,
     mode_label: String,
     /// Total number of results matching the query (ignoring LIMIT/OFFSET).
     total_count: u32,
     /// True when total_count is exact. False means frontend should treat it as unknown.
     total_known: bool,
+    /// total_count is a sampled estimate (total_known is false): fine to show
+    /// as "~12,400", not to drive pagination.
+    total_approximate: bool,
+    /// Folder spans over `entries` when the caller asked for `group_by_folder`;
+    /// `entries` is then ordered folder by folder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    groups: Option<Vec<folder_groups::FolderGroup>>,
+}
+
+/// Exact total for a `search` that returned without one (see `spawn_exact_count`).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchCountReadyEvent {
+    request_id: u64,
+    total_count: u32,
+}
+
+/// Progress of a `compute_hash` over a large file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HashProgressEvent {
+    request_id: u64,
+    path: String,
+    bytes_hashed: u64,
+    total_bytes: u64,
+}
+
+/// Files smaller than this hash in well under a second; no progress events.
+const HASH_PROGRESS_MIN_BYTES: u64 = 64 * 1024 * 1024;
+const HASH_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const ZIP_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const TRANSFER_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Minimum gap between `search_partial` batches from one slow search.
+const SEARCH_PARTIAL_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Rows found so far by a slow search mode (find fallback, fd_search),
+/// unsorted and in discovery order. The final command result replaces them.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPartialEvent {
+    request_id: u64,
+    entries: Vec<EntryDto>,
+}
+
+/// Hands rows collected since the last flush to a `search_partial` sink, at
+/// most once per `SEARCH_PARTIAL_INTERVAL`, while a slow search is running.
+pub(crate) struct PartialFlush<'a> {
+    sink: &'a mut dyn FnMut(&[EntryDto]),
+    sent: usize,
+    last: Instant,
+}
+
+impl<'a> PartialFlush<'a> {
+    fn new(sink: &'a mut dyn FnMut(&[EntryDto])) -> Self {
+        PartialFlush {
+            sink,
+            sent: 0,
+            last: Instant::now(),
+        }
+    }
+
+    pub(crate) fn maybe_flush(&mut self, entries: &[EntryDto]) {
+        if entries.len() > self.sent && self.last.elapsed() >= SEARCH_PARTIAL_INTERVAL {
+            (self.sink)(&entries[self.sent..]);
+            self.sent = entries.len();
+            self.last = Instant::now();
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -394,19 +684,34 @@ pub(crate) struct IgnoreRulesCache {
 #[derive(Debug, Clone)]
 pub(crate) struct AppState {
     pub(crate) db_path: PathBuf,
+    /// The active profile's directory: `.pathignore` and friends, settings
+    /// and caches. Holds the DB too unless `set_db_location` moved it.
+    pub(crate) data_dir: PathBuf,
     pub(crate) home_dir: PathBuf,
     pub(crate) scan_root: PathBuf,
     pub(crate) cwd: PathBuf,
     pub(crate) config_file_path: PathBuf,
     pub(crate) pathindexing_file_path: PathBuf,
     pub(crate) extra_roots: Arc<Mutex<Vec<PathBuf>>>,
+    pub(crate) pathpriority_file_path: PathBuf,
+    /// User-marked hot directories from `.pathpriority`: scanned first and
+    /// flushed with a shorter watcher debounce.
+    pub(crate) hot_dirs: Arc<Mutex<Vec<PathBuf>>>,
     pub(crate) path_ignores: Arc<Vec<PathBuf>>,
-    pub(crate) path_ignore_patterns: Arc<Vec<IgnorePattern>>,
+    /// Active `.pathignore` entries the index currently reflects. A watcher
+    /// seeing the file differ from these reloads the rules.
+    pub(crate) applied_pathignore: Arc<Mutex<Vec<String>>>,
     pub(crate) db_ready: Arc<AtomicBool>,
     pub(crate) indexing_active: Arc<AtomicBool>,
     pub(crate) status: Arc<Mutex<IndexStatus>>,
     pub(crate) recent_ops: Arc<Mutex<Vec<RecentOp>>>,
+    /// Renames and trash batches `undo_last_operation` can reverse.
+    pub(crate) undo_log: Arc<Mutex<undo::UndoLog>>,
+    /// Queries registered by `watch_query`, diffed against watcher batches.
+    pub(crate) live_queries: Arc<live_query::LiveQueryRegistry>,
     pub(crate) icon_cache: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    /// On-disk content thumbnails under the app data dir (see `get_thumbnail`).
+    pub(crate) thumbnail_cache: Arc<thumbnails::ThumbnailCache>,
     pub(crate) fd_search_cache: Arc<Mutex<Option<FdSearchCache>>>,
     pub(crate) negative_name_cache: Arc<Mutex<HashMap<String, NegativeNameEntry>>>,
     pub(crate) ignore_cache: Arc<Mutex<Option<IgnoreRulesCache>>>,
@@ -414,13 +719,20 @@ pub(crate) struct AppState {
     /// (triggers dropped for bulk insert), set to true after FTS rebuild completes.
     /// When false, search falls back to LIKE-based queries instead of FTS.
     pub(crate) fts_ready: Arc<AtomicBool>,
-    /// In-memory index for instant search while DB upsert runs in background.
-    /// Set by MFT scan, cleared after background DB upsert completes.
+    /// In-memory index that serves every search once built. Set by the
+    /// Windows full scans and kept current by `mem_resident`; cleared on reset.
     pub(crate) mem_index: Arc<RwLock<Option<Arc<mem_search::MemIndex>>>>,
+    /// Rebuild bookkeeping for `mem_index` (see `mem_resident`).
+    pub(crate) mem_resident: Arc<mem_resident::Resident>,
     /// Signal to stop the file watcher (RDCW / USN). Set to true on reset_index.
     pub(crate) watcher_stop: Arc<AtomicBool>,
     /// Set to true while a file watcher event loop is running.
     pub(crate) watcher_active: Arc<AtomicBool>,
+    /// Live updates paused by the user (tray menu): the watcher is stopped
+    /// until resumed, when FSEvents / USN replay what was missed.
+    pub(crate) indexing_paused: Arc<AtomicBool>,
+    /// Changes the watcher has queued but not yet written to the index.
+    pub(crate) watcher_backlog: Arc<AtomicUsize>,
     /// Set to true once frontend onMount has completed enough to accept user input.
     pub(crate) frontend_ready: Arc<AtomicBool>,
     /// Guards against concurrent pathindexing background threads.
@@ -432,12 +744,50 @@ pub(crate) struct AppState {
     /// Persistent write connection for watcher-driven incremental updates.
     /// Opening a connection per event batch dominated single-file update cost.
     pub(crate) watcher_conn: Arc<Mutex<Option<Connection>>>,
+    /// Set while the first index of an empty DB is being built without MFT
+    /// access; searches then also ask the Windows Search indexer.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub(crate) wsearch_fallback: Arc<AtomicBool>,
+    /// Smoothed latency of recent searches; scan workers back off while it
+    /// is over budget so indexing does not starve interactive queries.
+    pub(crate) search_pressure: Arc<scan_tuning::SearchPressure>,
+    /// Paths the last index run failed to read (`get_permission_errors`).
+    pub(crate) permission_log: Arc<permission_log::PermissionErrorLog>,
+    /// Cancel tokens of in-flight `search` calls, keyed by request id.
+    pub(crate) search_cancels: Arc<search_cancel::SearchCancelRegistry>,
+    pub(crate) search_result_cache: Arc<Mutex<search_cache::SearchResultCache>>,
+    /// Paths in `pinned_entries`, loaded at startup and kept in step by
+    /// `pin_entry` / `unpin_entry`.
+    pub(crate) pinned_paths: Arc<RwLock<HashSet<String>>>,
+    /// Recently computed `get_extended_metadata` results.
+    pub(crate) metadata_cache: Arc<Mutex<media_meta::MetadataCache>>,
+    /// Cancel tokens of in-flight `compute_hash` calls, keyed by request id.
+    pub(crate) hash_cancels: Arc<search_cancel::SearchCancelRegistry>,
+    /// Cancel tokens of in-flight `find_duplicates` scans.
+    pub(crate) duplicate_cancels: Arc<search_cancel::SearchCancelRegistry>,
+    /// Cancel tokens of in-flight `batch_move` / `batch_copy` calls.
+    pub(crate) transfer_cancels: Arc<search_cancel::SearchCancelRegistry>,
+    /// Epoch seconds of the last search or index write; the idle WAL
+    /// checkpoint scheduler waits for this to go quiet.
+    pub(crate) last_db_activity: Arc<AtomicI64>,
+    /// Query passed with `--search` (from a jump list item), handed to the
+    /// frontend once by `take_launch_query`.
+    pub(crate) launch_query: Arc<Mutex<Option<String>>>,
+    /// The opt-in HTTP API server, when running.
+    pub(crate) http_server: Arc<Mutex<Option<http_api::HttpServer>>>,
+    /// Entered by DB writes outside index runs; `set_db_location` closes it
+    /// for the move.
+    pub(crate) db_writes: Arc<db_location::WriteGate>,
+    /// Indexing knobs in effect, re-read from `index_tuning.json` when saved
+    /// and at the start of each index run.
+    pub(crate) index_tuning: Arc<RwLock<index_tuning::IndexTuning>>,
 }
 
 /// Construct `AppState` from resolved paths, without Tauri. Shared by GUI
 /// startup (`setup_app`) and the headless MCP index builder, which has no
-/// `AppHandle`. `app_data_dir` (the DB's parent) holds the `.pathignore` and
-/// `.pathindexing` sidecars and is itself excluded from indexing.
+/// `AppHandle`. `app_data_dir` (the DB's parent) holds the `.pathignore`,
+/// `.pathindexing` and `.pathpriority` sidecars and is itself excluded from
+/// indexing.
 pub(crate) fn build_app_state(db_path: PathBuf, home_dir: PathBuf, app_data_dir: &Path) -> AppState {
     let scan_root = if cfg!(windows) {
         PathBuf::from("C:\\")
@@ -446,39 +796,78 @@ pub(crate) fn build_app_state(db_path: PathBuf, home_dir: PathBuf, app_data_dir:
     };
     let cwd = std::env::current_dir().unwrap_or_else(|_| home_dir.clone());
     let config_file_path = app_data_dir.join(".pathignore");
-    let (mut path_ignores, path_ignore_patterns) =
-        load_pathignore_rules(&config_file_path, &home_dir, &cwd);
+    let (mut path_ignores, _) = load_pathignore_rules(&config_file_path, &home_dir, &cwd);
     if !path_ignores.iter().any(|r| r == app_data_dir) {
         path_ignores.push(app_data_dir.to_path_buf());
     }
+    // A DB moved out of the data dir (`set_db_location`) still mustn't index
+    // its own writes.
+    if let Some(db_dir) = db_path.parent() {
+        if !path_ignores.iter().any(|r| db_dir.starts_with(r)) {
+            path_ignores.push(db_dir.to_path_buf());
+        }
+    }
+    let applied_pathignore =
+        pathignore_active_entries(&fs::read_to_string(&config_file_path).unwrap_or_default());
     let pathindexing_file_path = app_data_dir.join(".pathindexing");
     let extra_roots = pathindexing::load_pathindexing_roots(&pathindexing_file_path);
+    let pathpriority_file_path = app_data_dir.join(".pathpriority");
+    let hot_dirs = pathpriority::load_hot_dirs(&pathpriority_file_path, &home_dir);
+    let tuning = index_tuning::load(app_data_dir);
     AppState {
         db_path,
+        data_dir: app_data_dir.to_path_buf(),
         home_dir,
         scan_root,
         cwd,
         config_file_path,
         pathindexing_file_path,
         extra_roots: Arc::new(Mutex::new(extra_roots)),
+        pathpriority_file_path,
+        hot_dirs: Arc::new(Mutex::new(hot_dirs)),
         path_ignores: Arc::new(path_ignores),
-        path_ignore_patterns: Arc::new(path_ignore_patterns),
+        applied_pathignore: Arc::new(Mutex::new(applied_pathignore)),
         db_ready: Arc::new(AtomicBool::new(false)),
         indexing_active: Arc::new(AtomicBool::new(false)),
         status: Arc::new(Mutex::new(IndexStatus::default())),
         recent_ops: Arc::new(Mutex::new(Vec::new())),
+        undo_log: Arc::new(Mutex::new(undo::UndoLog::default())),
+        live_queries: Arc::new(live_query::LiveQueryRegistry::default()),
         icon_cache: Arc::new(Mutex::new(HashMap::new())),
+        thumbnail_cache: Arc::new(thumbnails::ThumbnailCache::new(
+            app_data_dir.join("thumbnails"),
+        )),
         fd_search_cache: Arc::new(Mutex::new(None)),
         negative_name_cache: Arc::new(Mutex::new(HashMap::new())),
         ignore_cache: Arc::new(Mutex::new(None)),
         fts_ready: Arc::new(AtomicBool::new(true)),
         mem_index: Arc::new(RwLock::new(None)),
+        mem_resident: Arc::new(mem_resident::Resident::default()),
         watcher_stop: Arc::new(AtomicBool::new(false)),
         watcher_active: Arc::new(AtomicBool::new(false)),
+        indexing_paused: Arc::new(AtomicBool::new(false)),
+        watcher_backlog: Arc::new(AtomicUsize::new(0)),
         frontend_ready: Arc::new(AtomicBool::new(false)),
         pathindexing_active: Arc::new(AtomicBool::new(false)),
         search_conn_pool: Arc::new(Mutex::new(Vec::new())),
         watcher_conn: Arc::new(Mutex::new(None)),
+        wsearch_fallback: Arc::new(AtomicBool::new(false)),
+        search_pressure: Arc::new(scan_tuning::SearchPressure::default()),
+        search_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+        permission_log: Arc::new(permission_log::PermissionErrorLog::default()),
+        search_result_cache: Arc::new(Mutex::new(search_cache::SearchResultCache::default())),
+        metadata_cache: Arc::new(Mutex::new(media_meta::MetadataCache::default())),
+        pinned_paths: Arc::new(RwLock::new(HashSet::new())),
+        hash_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+        duplicate_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+        transfer_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+        last_db_activity: Arc::new(AtomicI64::new(0)),
+        launch_query: Arc::new(Mutex::new(search_history::launch_query_from_args(
+            &std::env::args().skip(1).collect::<Vec<_>>(),
+        ))),
+        http_server: Arc::new(Mutex::new(None)),
+        db_writes: Arc::new(db_location::WriteGate::default()),
+        index_tuning: Arc::new(RwLock::new(tuning)),
     }
 }
 
@@ -500,12 +889,21 @@ pub(crate) struct IndexRow {
     pub(crate) path: String,
     pub(crate) name: String,
     pub(crate) dir: String,
+    /// `name_norm::fold` of `name`/`dir`, what name search matches against.
+    pub(crate) name_norm: String,
+    pub(crate) dir_norm: String,
     pub(crate) is_dir: i64,
     pub(crate) ext: Option<String>,
     pub(crate) mtime: Option<i64>,
+    /// Nanoseconds past `mtime`'s second, so edits within one second still
+    /// register as changes. None when the mtime itself is unknown. Windows
+    /// FILETIMEs only resolve 100 ns, so their values are multiples of 100.
+    pub(crate) mtime_nsec: Option<i64>,
     pub(crate) size: Option<i64>,
     pub(crate) indexed_at: i64,
     pub(crate) run_id: i64,
+    /// Content lives only in the cloud (`is_cloud_only_metadata`).
+    pub(crate) cloud_only: bool,
 }
 
 pub(crate) fn now_epoch() -> i64 {
@@ -558,6 +956,76 @@ pub(crate) fn temp_case_dir(case: &str) -> PathBuf {
     ))
 }
 
+/// Insert a bare `entries` row (no ext, mtime or size) for `path` into a DB
+/// set up by `init_db_tables`. Shared by the test modules.
+#[cfg(test)]
+pub(crate) fn insert_test_entry(conn: &Connection, path: &Path, is_dir: bool, run_id: i64) {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+    let dir = path
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_else(|| "/".to_string());
+    conn.execute(
+        "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                              name_norm, dir_norm)
+         VALUES(?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5, ?6, lower(?2), lower(?3))",
+        params![
+            path.to_string_lossy().to_string(),
+            name,
+            dir,
+            if is_dir { 1 } else { 0 },
+            now_epoch(),
+            run_id,
+        ],
+    )
+    .unwrap();
+}
+
+/// Result row for `path` with every optional field empty, for tests to
+/// build on with struct update syntax.
+#[cfg(test)]
+pub(crate) fn test_entry(path: &str) -> EntryDto {
+    let path_ref = Path::new(path);
+    EntryDto {
+        path: path.to_string(),
+        name: path_ref
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string()),
+        dir: path_ref
+            .parent()
+            .map(|parent| parent.to_string_lossy().to_string())
+            .unwrap_or_else(|| "/".to_string()),
+        is_dir: false,
+        ext: None,
+        size: None,
+        mtime: None,
+        spoken_label: None,
+        mtime_display: None,
+        cloud_only: None,
+        linked_from: None,
+    }
+}
+
+/// Fill in the ext, mtime and size `insert_test_entry` leaves NULL.
+#[cfg(test)]
+pub(crate) fn set_test_entry_stats(
+    conn: &Connection,
+    path: &Path,
+    ext: Option<&str>,
+    mtime: Option<i64>,
+    size: Option<i64>,
+) {
+    conn.execute(
+        "UPDATE entries SET ext = ?1, mtime = ?2, size = ?3 WHERE path = ?4",
+        params![ext, mtime, size, path.to_string_lossy().to_string()],
+    )
+    .unwrap();
+}
+
 pub(crate) fn perf_log(message: impl AsRef<str>) {
     if perf_log_enabled() {
         eprintln!("[perf] {}", message.as_ref());
@@ -775,6 +1243,14 @@ fn init_db_tables(db_path: &Path) -> AppResult<()> {
     )
     .map_err(|e| e.to_string())?;
     conn.execute_batch(CREATE_FTS_TRIGGERS_SQL).map_err(|e| e.to_string())?;
+    conn.execute_batch(saved_searches::CREATE_SAVED_SEARCHES_SQL)
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(search_history::CREATE_SEARCH_HISTORY_SQL)
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(pins::CREATE_PINNED_ENTRIES_SQL)
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(putback::CREATE_TRASH_PUTBACK_SQL)
+        .map_err(|e| e.to_string())?;
     eprintln!("[init_db] +{}ms tables ensured", t.elapsed().as_millis());
 
     Ok(())
@@ -832,6 +1308,13 @@ pub(crate) fn set_meta(conn: &Connection, key: &str, value: &str) -> AppResult<(
     Ok(())
 }
 
+/// Remember how long the last from-scratch index took, for `get_index_stats`.
+/// Best-effort like the cached counts below.
+pub(crate) fn record_full_index_duration(conn: &Connection, elapsed: Duration) {
+    let _ = set_meta(conn, "last_full_index_ms", &elapsed.as_millis().to_string());
+    let _ = set_meta(conn, "last_full_index_at", &now_epoch().to_string());
+}
+
 /// Persist the status-bar counts so the next startup can seed them without a
 /// COUNT(*)/MAX() scan over `entries`. Best-effort: write errors are ignored.
 pub(crate) fn persist_cached_counts(conn: &Connection, entries_count: u64, last_updated: Option<i64>) {
@@ -850,6 +1333,26 @@ pub(crate) fn load_cached_counts(conn: &Connection) -> (Option<u64>, Option<i64>
     (count, last_updated)
 }
 
+/// Index behind the Windows full-index stale sweep. Not part of
+/// `CREATE_ENTRIES_INDEXES_SQL`: only the MFT and non-admin indexers sweep by
+/// run_id, and they keep this index through their bulk upsert (unlike the
+/// secondary indexes they drop) so the sweep reads only the stale rows.
+const CREATE_RUN_ID_INDEX_SQL: &str =
+    "CREATE INDEX IF NOT EXISTS idx_entries_run_id ON entries(run_id);";
+
+/// Delete the rows a full index run didn't see (run_id older than
+/// `current_run_id`) and return how many went. Builds the run_id index first
+/// when the DB predates it, so only the first sweep pays for a table scan.
+pub(crate) fn delete_stale_run_rows(conn: &Connection, current_run_id: i64) -> AppResult<usize> {
+    conn.execute_batch(CREATE_RUN_ID_INDEX_SQL)
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM entries WHERE run_id < ?1",
+        params![current_run_id],
+    )
+    .map_err(|e| e.to_string())
+}
+
 pub(crate) fn cleanup_entries_gc_tables(conn: &Connection) -> AppResult<()> {
     let tables: Vec<String> = {
         let mut stmt = conn
@@ -900,12 +1403,39 @@ fn update_counts(conn: &Connection) -> AppResult<(u64, Option<i64>)> {
     Ok((entries_count, last_updated))
 }
 
+/// Emit `query_watch_diff` for every `watch_query` a watcher batch touched.
+pub(crate) fn notify_live_queries(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    upserted: &[IndexRow],
+    deleted: &[String],
+) {
+    let Some(app) = app else {
+        return;
+    };
+    if state.live_queries.is_empty() {
+        return;
+    }
+    let entries: Vec<EntryDto> = upserted.iter().cloned().map(entry_from_index_row).collect();
+    for diff in state.live_queries.diff(&entries, deleted) {
+        let _ = app.emit(live_query::QUERY_WATCH_DIFF_EVENT, diff);
+    }
+}
+
 pub(crate) fn invalidate_search_caches(state: &AppState) {
     // Note: search_conn_pool is intentionally NOT cleared here — pooled
     // connections stay valid across data changes (this runs on every watcher
     // batch), and dropping them would re-cold-start the page cache.
     state.fd_search_cache.lock().take();
     state.negative_name_cache.lock().clear();
+    state.search_result_cache.lock().clear();
+    // Every index write path ends here, so this doubles as the write-activity
+    // mark for the idle WAL checkpoint scheduler.
+    note_db_activity(state);
+}
+
+fn note_db_activity(state: &AppState) {
+    state.last_db_activity.store(now_epoch(), AtomicOrdering::Relaxed);
 }
 
 fn prune_negative_name_cache(cache: &mut HashMap<String, NegativeNameEntry>) {
@@ -989,52 +1519,78 @@ fn sort_clause(sort_by: &str, sort_dir: &str, prefix: &str) -> String {
         ("name", "desc") => {
             format!("{prefix}name COLLATE NOCASE DESC, {prefix}path COLLATE NOCASE DESC")
         }
-        ("mtime", "asc") => {
-            format!("COALESCE({prefix}mtime, 0) ASC, {prefix}name COLLATE NOCASE ASC")
-        }
-        ("mtime", "desc") => {
-            format!("COALESCE({prefix}mtime, 0) DESC, {prefix}name COLLATE NOCASE ASC")
-        }
+        ("mtime", "asc") => format!(
+            "COALESCE({prefix}mtime, 0) ASC, COALESCE({prefix}mtime_nsec, 0) ASC, \
+             {prefix}name COLLATE NOCASE ASC"
+        ),
+        ("mtime", "desc") => format!(
+            "COALESCE({prefix}mtime, 0) DESC, COALESCE({prefix}mtime_nsec, 0) DESC, \
+             {prefix}name COLLATE NOCASE ASC"
+        ),
         ("dir", "asc") => {
             format!("{prefix}dir COLLATE NOCASE ASC, {prefix}name COLLATE NOCASE ASC")
         }
         ("dir", "desc") => {
             format!("{prefix}dir COLLATE NOCASE DESC, {prefix}name COLLATE NOCASE ASC")
         }
+        // NULL sizes (directories) sort last either way: explicitly for ASC,
+        // and by SQLite's default for DESC. Spelled without `size IS NULL` so
+        // idx_entries_ext_size can supply the order.
         ("size", "asc") => {
-            format!("{prefix}size IS NULL ASC, {prefix}size ASC, {prefix}name COLLATE NOCASE ASC")
-        }
-        ("size", "desc") => {
-            format!("{prefix}size IS NULL ASC, {prefix}size DESC, {prefix}name COLLATE NOCASE ASC")
+            format!("{prefix}size ASC NULLS LAST, {prefix}name COLLATE NOCASE ASC")
         }
+        ("size", "desc") => format!("{prefix}size DESC, {prefix}name COLLATE NOCASE ASC"),
         _ => format!("{prefix}name COLLATE NOCASE ASC, {prefix}path COLLATE NOCASE ASC"),
     }
 }
 
-fn contains_glob_meta(s: &str) -> bool {
-    s.contains('*') || s.contains('?')
+/// Separator count of `{prefix}path`: SQL's `path_depth`, for the depth
+/// tie-break of relevance order.
+fn sql_path_depth(prefix: &str) -> String {
+    let sep = std::path::MAIN_SEPARATOR;
+    format!("(length({prefix}path) - length(replace({prefix}path, '{sep}', '')))")
+}
+
+pub(crate) fn contains_glob_meta(s: &str) -> bool {
+    s.contains('*') || s.contains('?') || glob_class::has_class(s)
 }
 
 /// Quote a plain query string as an FTS5 phrase (`"..."`), so trigram MATCH
-/// performs a substring lookup with no query-syntax interpretation.
+/// performs a substring lookup with no query-syntax interpretation. The FTS
+/// table indexes raw names, so a query with composable characters matches
+/// either its NFC or its NFD spelling (`"café" OR "cafe\u{301}"`).
 fn fts_phrase(query: &str) -> String {
-    format!("\"{}\"", query.replace('"', "\"\""))
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    let (composed, decomposed) = (name_norm::nfc(query), name_norm::nfd(query));
+    if composed == decomposed {
+        quote(query)
+    } else {
+        format!("{} OR {}", quote(&composed), quote(&decomposed))
+    }
 }
 
 /// Build an FTS5 trigram prefilter for a glob pattern: every literal run of
 /// 3+ chars must be present as a substring (`"lit1" AND "lit2"`). Returns None
 /// when no run is long enough to form a trigram (prefilter would not narrow).
 fn glob_fts_match_expr(glob: &str) -> Option<String> {
+    let chars: Vec<char> = glob.chars().collect();
     let mut runs: Vec<String> = Vec::new();
     let mut cur = String::new();
-    for ch in glob.chars() {
-        if ch == '*' || ch == '?' {
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let class_end = (ch == '[')
+            .then(|| glob_class::CharClass::parse(&chars, i))
+            .flatten()
+            .map(|(_, end)| end);
+        if ch == '*' || ch == '?' || class_end.is_some() {
             if !cur.is_empty() {
                 runs.push(std::mem::take(&mut cur));
             }
         } else {
             cur.push(ch);
         }
+        i = class_end.unwrap_or(i + 1);
     }
     if !cur.is_empty() {
         runs.push(cur);
@@ -1087,6 +1643,76 @@ fn extract_ext_from_like(like_pattern: &str) -> Option<String> {
     Some(ext.to_lowercase())
 }
 
+/// Most directories a `path:` term may match by name before the trigram
+/// plan gives up on per-directory range scans and scans `path LIKE` instead.
+const PATH_CONTAINS_MAX_DIRS: usize = 256;
+
+/// WHERE clause (over alias `e`) and its params for a `path:term` filter.
+///
+/// With the trigram index in sync and a separator-free term of 3+ chars, any
+/// matching path has the term inside one component: either the entry's own
+/// name (FTS postings) or the name of an ancestor directory (FTS lookup of
+/// directories, then a `dir` range scan under each). Terms spanning a
+/// separator, short terms, or too many matching directories fall back to a
+/// full `path LIKE '%term%'` scan.
+fn path_contains_filter(conn: &Connection, fts_ready: bool, term: &str) -> (String, Vec<SqlValue>) {
+    let sep = std::path::MAIN_SEPARATOR;
+    let native = term.replace(['/', '\\'], &sep.to_string());
+    let like_fallback = || {
+        (
+            "e.path LIKE ?1 ESCAPE '\\'".to_string(),
+            vec![SqlValue::Text(format!("%{}%", escape_like(&native)))],
+        )
+    };
+    if !fts_ready || native.contains(sep) || native.chars().count() < 3 {
+        return like_fallback();
+    }
+
+    let fts_match = fts_phrase(&native);
+    let hit_dirs: Vec<String> = match conn.prepare_cached(
+        "SELECT e.path FROM entries_fts f JOIN entries e ON e.id = f.rowid \
+         WHERE entries_fts MATCH ?1 AND e.is_dir = 1 ORDER BY e.path LIMIT ?2",
+    ) {
+        Ok(mut stmt) => match stmt.query_map(
+            params![fts_match, PATH_CONTAINS_MAX_DIRS as i64 + 1],
+            |r| r.get::<_, String>(0),
+        ) {
+            Ok(rows) => rows.filter_map(Result::ok).collect(),
+            Err(_) => return like_fallback(),
+        },
+        Err(_) => return like_fallback(),
+    };
+    if hit_dirs.len() > PATH_CONTAINS_MAX_DIRS {
+        return like_fallback();
+    }
+
+    let mut sql_params = vec![SqlValue::Text(fts_match)];
+    let mut conditions = vec!["e.id IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ?1)".to_string()];
+    // Sorted by path, so a nested hit directly follows its matching ancestor
+    // and is already covered by the ancestor's range.
+    let mut last_kept: Option<String> = None;
+    for dir in hit_dirs {
+        if let Some(kept) = &last_kept {
+            if dir.starts_with(kept.as_str()) && dir[kept.len()..].starts_with(sep) {
+                continue;
+            }
+        }
+        let i = sql_params.len();
+        let (pfx, pfx_end) = subtree_range_bounds(&dir);
+        sql_params.push(SqlValue::Text(dir.clone()));
+        sql_params.push(SqlValue::Text(pfx));
+        sql_params.push(SqlValue::Text(pfx_end));
+        conditions.push(format!(
+            "e.dir = ?{} OR (e.dir >= ?{} AND e.dir < ?{})",
+            i + 1,
+            i + 2,
+            i + 3
+        ));
+        last_kept = Some(dir);
+    }
+    (conditions.join(" OR "), sql_params)
+}
+
 fn normalize_hint_to_native(dir_hint: &str) -> (String, bool) {
     let native = dir_hint.replace('/', &std::path::MAIN_SEPARATOR.to_string());
     let is_abs = Path::new(&native).is_absolute();
@@ -1142,7 +1768,7 @@ fn resolve_dirs_from_db(conn: &Connection, dir_hint: &str) -> Vec<String> {
         format!("%{escaped_sep}{}", escape_like(&native_hint))
     };
     let fetch_limit = (RESOLVE_DIRS_MAX + 1) as i64;
-    let mut stmt = match conn.prepare(
+    let mut stmt = match conn.prepare_cached(
         "SELECT path FROM entries WHERE name = ?1 COLLATE NOCASE AND is_dir = 1 AND path LIKE ?2 ESCAPE '\\' LIMIT ?3",
     ) {
         Ok(s) => s,
@@ -1165,7 +1791,7 @@ fn normalize_slashes(s: String) -> String {
     if s.contains('\\') { s.replace('\\', "/") } else { s }
 }
 
-fn resolve_ignore_path(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<PathBuf> {
+pub(crate) fn resolve_ignore_path(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<PathBuf> {
     let trimmed = raw.trim();
     if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('!') {
         return None;
@@ -1183,7 +1809,9 @@ fn resolve_ignore_path(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<Pa
         path = base_dir.join(path);
     }
 
-    Some(fs::canonicalize(&path).unwrap_or(path))
+    let canonical = fs::canonicalize(&path).unwrap_or(path);
+    // Windows canonical paths are verbatim (`\\?\C:\...`); rows are not.
+    Some(long_path::strip(&canonical).into_owned())
 }
 
 fn normalize_ignore_pattern(raw: &str, base_dir: &Path, home_dir: &Path) -> String {
@@ -1200,7 +1828,7 @@ fn normalize_ignore_pattern(raw: &str, base_dir: &Path, home_dir: &Path) -> Stri
     normalize_slashes(base_dir.join(&trimmed).to_string_lossy().to_string())
 }
 
-fn parse_ignore_pattern(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<IgnorePattern> {
+pub(crate) fn parse_ignore_pattern(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<IgnorePattern> {
     let trimmed = raw.trim().trim_end_matches('/');
     if trimmed.is_empty() {
         return None;
@@ -1217,7 +1845,7 @@ fn parse_ignore_pattern(raw: &str, base_dir: &Path, home_dir: &Path) -> Option<I
     )))
 }
 
-fn ignore_pattern_key(pattern: &IgnorePattern) -> String {
+pub(crate) fn ignore_pattern_key(pattern: &IgnorePattern) -> String {
     match pattern {
         IgnorePattern::AnySegment { segment, .. } => format!("seg:{segment}"),
         IgnorePattern::Glob(glob) => format!("glob:{glob}"),
@@ -1293,9 +1921,9 @@ fn ensure_pathignore_exists(path: &Path) -> AppResult<()> {
     Ok(())
 }
 
-fn load_pathignore_rules(config_file: &Path, home_dir: &Path, cwd: &Path) -> (Vec<PathBuf>, Vec<IgnorePattern>) {
-    let _ = ensure_pathignore_exists(config_file);
-
+/// `.pathignore` files consulted, highest priority first: the app-data config
+/// file, then the legacy cwd (or nearest ancestor) and home copies.
+pub(crate) fn pathignore_files(config_file: &Path, home_dir: &Path, cwd: &Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
     // App-data config file takes priority.
     files.push(config_file.to_path_buf());
@@ -1315,13 +1943,18 @@ fn load_pathignore_rules(config_file: &Path, home_dir: &Path, cwd: &Path) -> (Ve
     if !files.iter().any(|p| p == &home_file) {
         files.push(home_file);
     }
+    files
+}
+
+fn load_pathignore_rules(config_file: &Path, home_dir: &Path, cwd: &Path) -> (Vec<PathBuf>, Vec<IgnorePattern>) {
+    let _ = ensure_pathignore_exists(config_file);
 
     let mut roots = Vec::new();
     let mut patterns = Vec::new();
     let mut seen_roots = HashSet::new();
     let mut seen_patterns = HashSet::new();
 
-    for file in files {
+    for file in pathignore_files(config_file, home_dir, cwd) {
         let Ok(contents) = fs::read_to_string(&file) else {
             continue;
         };
@@ -1359,7 +1992,7 @@ fn load_pathignore_rules(config_file: &Path, home_dir: &Path, cwd: &Path) -> (Ve
 
 
 #[cfg(target_os = "macos")]
-fn macos_tcc_ignore_roots(home_dir: &Path) -> Vec<PathBuf> {
+pub(crate) fn macos_tcc_ignore_roots(home_dir: &Path) -> Vec<PathBuf> {
     let library = home_dir.join("Library");
     let app_support = library.join("Application Support");
     let caches = library.join("Caches");
@@ -1422,7 +2055,7 @@ fn macos_tcc_ignore_roots(home_dir: &Path) -> Vec<PathBuf> {
 }
 
 #[cfg(not(target_os = "macos"))]
-fn macos_tcc_ignore_roots(_home_dir: &Path) -> Vec<PathBuf> {
+pub(crate) fn macos_tcc_ignore_roots(_home_dir: &Path) -> Vec<PathBuf> {
     Vec::new()
 }
 
@@ -1430,7 +2063,7 @@ fn macos_tcc_ignore_roots(_home_dir: &Path) -> Vec<PathBuf> {
 /// (browser caches, Windows Update temp files, UWP package state, etc.).
 /// Pruning these from the MFT subtree eliminates noisy USN records.
 #[cfg(target_os = "windows")]
-fn windows_noisy_roots(home_dir: &Path) -> Vec<PathBuf> {
+pub(crate) fn windows_noisy_roots(home_dir: &Path) -> Vec<PathBuf> {
     let local = home_dir.join("AppData").join("Local");
     let dirs = vec![
         local.join("Temp"),
@@ -1447,7 +2080,7 @@ fn windows_noisy_roots(home_dir: &Path) -> Vec<PathBuf> {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn windows_noisy_roots(_home_dir: &Path) -> Vec<PathBuf> {
+pub(crate) fn windows_noisy_roots(_home_dir: &Path) -> Vec<PathBuf> {
     Vec::new()
 }
 
@@ -1491,7 +2124,7 @@ pub(crate) fn effective_ignore_rules(
     (roots, patterns)
 }
 
-fn builtin_ignore_patterns(home_dir: &Path) -> Vec<IgnorePattern> {
+pub(crate) fn builtin_ignore_patterns(home_dir: &Path) -> Vec<IgnorePattern> {
     let home = normalize_slashes(home_dir.to_string_lossy().to_string());
     vec![
         // ~/Library/Application Support/*/Cache*
@@ -1505,6 +2138,19 @@ fn builtin_ignore_patterns(home_dir: &Path) -> Vec<IgnorePattern> {
     ]
 }
 
+/// Startup ignore roots that are not re-read from `.pathignore` (the app
+/// data dir). The startup copies of `.pathignore` rules are left out of the
+/// merge so a rule dropped by `remove_ignore_rule` stops applying.
+pub(crate) fn fixed_ignore_roots(state: &AppState) -> Vec<PathBuf> {
+    let app_data_dir = state.config_file_path.parent();
+    state
+        .path_ignores
+        .iter()
+        .filter(|root| Some(root.as_path()) == app_data_dir)
+        .cloned()
+        .collect()
+}
+
 pub(crate) fn cached_effective_ignore_rules(state: &AppState) -> (Vec<PathBuf>, Vec<IgnorePattern>) {
     let home_dir = &state.home_dir;
     let cwd = &state.cwd;
@@ -1533,10 +2179,14 @@ pub(crate) fn cached_effective_ignore_rules(state: &AppState) -> (Vec<PathBuf>,
         config_file,
         home_dir,
         cwd,
-        state.path_ignores.as_ref(),
-        state.path_ignore_patterns.as_ref(),
+        &fixed_ignore_roots(state),
+        &[],
     );
 
+    // Cached results were filtered with the previous rules.
+    if cache.is_some() {
+        state.search_result_cache.lock().clear();
+    }
     *cache = Some(IgnoreRulesCache {
         roots: roots.clone(),
         patterns: patterns.clone(),
@@ -1588,9 +2238,105 @@ fn purge_ignored_entries(db_path: &Path, ignored_roots: &[PathBuf]) -> AppResult
     Ok(())
 }
 
+/// Literal directory a glob is anchored under (the part before the first
+/// wildcard, cut at the last `/`), in native separators; `None` when the
+/// glob can match anywhere.
+fn glob_literal_dir(glob: &str) -> Option<String> {
+    let literal = &glob[..glob.find(['*', '?']).unwrap_or(glob.len())];
+    let dir = literal[..literal.rfind('/')?].trim_end_matches('/');
+    if dir.is_empty() {
+        return None;
+    }
+    Some(dir.replace('/', std::path::MAIN_SEPARATOR_STR))
+}
+
+/// Delete indexed rows matching `pattern`, with everything under them.
+fn purge_pattern_entries(conn: &mut Connection, pattern: &IgnorePattern) -> AppResult<usize> {
+    let (sql, args): (&str, Vec<SqlValue>) = match pattern {
+        IgnorePattern::AnySegment { segment, .. } => (
+            "SELECT path FROM entries WHERE name = ?1 ORDER BY path",
+            vec![SqlValue::Text(segment.clone())],
+        ),
+        IgnorePattern::Glob(glob) => match glob_literal_dir(glob) {
+            Some(dir) => {
+                let (lo, hi) = subtree_range_bounds(&dir);
+                (
+                    "SELECT path FROM entries WHERE path >= ?1 AND path < ?2 ORDER BY path",
+                    vec![SqlValue::Text(lo), SqlValue::Text(hi)],
+                )
+            }
+            None => ("SELECT path FROM entries ORDER BY path", Vec::new()),
+        },
+    };
+    let mut matched: Vec<String> = Vec::new();
+    {
+        let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+        let mut rows = stmt
+            .query(params_from_iter(args.iter()))
+            .map_err(|e| e.to_string())?;
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let path: String = row.get(0).map_err(|e| e.to_string())?;
+            // Sorted, so a matched folder's contents follow it directly and
+            // go with it.
+            let covered = matched.last().is_some_and(|top| {
+                path.starts_with(top.as_str())
+                    && path[top.len()..].starts_with(['/', '\\'])
+            });
+            if !covered && matches_ignore_pattern(&normalize_slashes(path.clone()), pattern) {
+                matched.push(path);
+            }
+        }
+    }
+    delete_paths(conn, &matched)
+}
+
+/// Append `rule` to a `.pathignore` file unless an equal active line is
+/// already there. Returns false in that case.
+fn append_pathignore_rule(config_file: &Path, rule: &str) -> AppResult<bool> {
+    ensure_pathignore_exists(config_file)?;
+    let mut contents = fs::read_to_string(config_file).map_err(|e| e.to_string())?;
+    let key = rule.trim_end_matches('/');
+    if pathignore_active_entries(&contents)
+        .iter()
+        .any(|l| l.trim_end_matches('/') == key)
+    {
+        return Ok(false);
+    }
+    if !contents.is_empty() && !contents.ends_with('\n') {
+        contents.push('\n');
+    }
+    contents.push_str(rule);
+    contents.push('\n');
+    fs::write(config_file, contents).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Drop every active line of a `.pathignore` file equal to `rule` (trailing
+/// `/` aside). Returns false when none matched.
+fn remove_pathignore_rule(config_file: &Path, rule: &str) -> AppResult<bool> {
+    let Ok(contents) = fs::read_to_string(config_file) else {
+        return Ok(false);
+    };
+    let key = rule.trim_end_matches('/');
+    let mut removed = false;
+    let kept: String = contents
+        .lines()
+        .filter(|l| {
+            let hit = l.trim().trim_end_matches('/') == key;
+            removed |= hit;
+            !hit
+        })
+        .map(|l| format!("{l}\n"))
+        .collect();
+    if removed {
+        fs::write(config_file, kept).map_err(|e| e.to_string())?;
+    }
+    Ok(removed)
+}
+
 fn glob_match_path(pattern: &str, text: &str) -> bool {
-    let pat = pattern.as_bytes();
-    let txt = text.as_bytes();
+    let pat: Vec<char> = pattern.chars().collect();
+    let txt: Vec<char> = text.chars().collect();
     let (pn, tn) = (pat.len(), txt.len());
 
     let mut px = 0usize;
@@ -1600,25 +2346,33 @@ fn glob_match_path(pattern: &str, text: &str) -> bool {
     let mut star_is_double = false;
 
     while tx < tn {
-        if px < pn && px + 1 < pn && pat[px] == b'*' && pat[px + 1] == b'*' {
+        // Like `?`, a class never matches the separator.
+        let class_hit = (px < pn && pat[px] == '[')
+            .then(|| glob_class::CharClass::parse(&pat, px))
+            .flatten()
+            .map(|(class, end)| (txt[tx] != '/' && class.matches(txt[tx]), end));
+        if px < pn && px + 1 < pn && pat[px] == '*' && pat[px + 1] == '*' {
             star_px = px;
             star_tx = tx;
             star_is_double = true;
             px += 2;
-        } else if px < pn && pat[px] == b'*' {
+        } else if px < pn && pat[px] == '*' {
             star_px = px;
             star_tx = tx;
             star_is_double = false;
             px += 1;
-        } else if px < pn && (pat[px] == b'?' && txt[tx] != b'/') {
+        } else if px < pn && (pat[px] == '?' && txt[tx] != '/') {
             px += 1;
             tx += 1;
-        } else if px < pn && pat[px] == txt[tx] {
+        } else if let Some((true, end)) = class_hit {
+            px = end;
+            tx += 1;
+        } else if px < pn && class_hit.is_none() && pat[px] == txt[tx] {
             px += 1;
             tx += 1;
         } else if star_px != usize::MAX {
             star_tx += 1;
-            if !star_is_double && txt[star_tx - 1] == b'/' {
+            if !star_is_double && txt[star_tx - 1] == '/' {
                 return false;
             }
             px = if star_is_double {
@@ -1632,7 +2386,7 @@ fn glob_match_path(pattern: &str, text: &str) -> bool {
         }
     }
 
-    while px < pn && pat[px] == b'*' {
+    while px < pn && pat[px] == '*' {
         px += 1;
     }
 
@@ -1734,11 +2488,12 @@ pub(crate) fn index_row_from_path_and_metadata(path: &Path, metadata: &fs::Metad
         .map(|p| p.to_string_lossy().to_string())
         .unwrap_or_else(|| "/".to_string());
 
-    let mtime = metadata
+    let modified = metadata
         .modified()
         .ok()
-        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64);
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok());
+    let mtime = modified.map(|d| d.as_secs() as i64);
+    let mtime_nsec = modified.map(|d| i64::from(d.subsec_nanos()));
 
     let size = if metadata.is_file() {
         Some(metadata.len() as i64)
@@ -1748,19 +2503,42 @@ pub(crate) fn index_row_from_path_and_metadata(path: &Path, metadata: &fs::Metad
 
     Some(IndexRow {
         path: path.to_string_lossy().to_string(),
+        name_norm: name_norm::fold(&name),
+        dir_norm: name_norm::fold(&dir),
         name,
         dir,
         is_dir: if is_dir { 1 } else { 0 },
         ext: extension_for(path, is_dir),
         mtime,
+        mtime_nsec,
         size,
         indexed_at: now_epoch(),
         run_id: 0,
+        cloud_only: is_cloud_only_metadata(metadata),
     })
 }
 
+/// Whether `metadata` is a cloud placeholder whose content isn't on local
+/// disk: an iCloud dataless file, or a OneDrive/Dropbox files-on-demand
+/// placeholder. Reads attributes only, so it never starts a download.
+#[cfg(target_os = "macos")]
+pub(crate) fn is_cloud_only_metadata(metadata: &fs::Metadata) -> bool {
+    !metadata.is_dir() && mac::cloud::is_dataless(metadata)
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn is_cloud_only_metadata(metadata: &fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    win::cloud::is_placeholder_attrs(metadata.file_attributes())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn is_cloud_only_metadata(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
 fn index_row_from_path(path: &Path) -> Option<IndexRow> {
-    let metadata = fs::symlink_metadata(path).ok()?;
+    let metadata = long_path::symlink_metadata(path).ok()?;
     index_row_from_path_and_metadata(path, &metadata)
 }
 
@@ -1779,6 +2557,10 @@ fn entry_from_index_row(row: IndexRow) -> EntryDto {
         ext: row.ext,
         size: row.size,
         mtime: row.mtime,
+        spoken_label: None,
+        mtime_display: None,
+        cloud_only: row.cloud_only.then_some(true),
+        linked_from: None,
     }
 }
 
@@ -1786,85 +2568,56 @@ pub fn entry_from_path(path: &Path) -> Option<EntryDto> {
     index_row_from_path(path).map(entry_from_index_row)
 }
 
-fn entry_cmp(a: &EntryDto, b: &EntryDto, sort_by: &str, sort_dir: &str) -> Ordering {
-    match sort_by {
-        "mtime" => {
-            let lhs = a.mtime.unwrap_or(0);
-            let rhs = b.mtime.unwrap_or(0);
-            let primary = if sort_dir == "desc" {
-                rhs.cmp(&lhs)
-            } else {
-                lhs.cmp(&rhs)
-            };
-
-            if primary != Ordering::Equal {
-                return primary;
-            }
-
-            a.name
-                .to_lowercase()
-                .cmp(&b.name.to_lowercase())
-                .then(a.path.to_lowercase().cmp(&b.path.to_lowercase()))
-        }
-        "dir" => {
-            let lhs = a.dir.to_lowercase();
-            let rhs = b.dir.to_lowercase();
-            let primary = if sort_dir == "desc" {
-                rhs.cmp(&lhs)
-            } else {
-                lhs.cmp(&rhs)
-            };
-            if primary != Ordering::Equal {
-                return primary;
-            }
-            a.name.to_lowercase().cmp(&b.name.to_lowercase())
-        }
-        "size" => {
-            let lhs = a.size.unwrap_or(0);
-            let rhs = b.size.unwrap_or(0);
-            let primary = if sort_dir == "desc" {
-                rhs.cmp(&lhs)
-            } else {
-                lhs.cmp(&rhs)
-            };
-            if primary != Ordering::Equal {
-                return primary;
-            }
-            a.name
-                .to_lowercase()
-                .cmp(&b.name.to_lowercase())
-                .then(a.path.to_lowercase().cmp(&b.path.to_lowercase()))
-        }
-        _ => {
-            let lhs_name = a.name.to_lowercase();
-            let rhs_name = b.name.to_lowercase();
-            let primary = if sort_dir == "desc" {
-                rhs_name.cmp(&lhs_name)
-            } else {
-                lhs_name.cmp(&rhs_name)
-            };
-
-            if primary != Ordering::Equal {
-                return primary;
-            }
+/// `a.to_lowercase().cmp(&b.to_lowercase())` without allocating: sorts call
+/// this O(n log n) times.
+fn cmp_lowercase(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+}
 
-            let lhs_path = a.path.to_lowercase();
-            let rhs_path = b.path.to_lowercase();
-            if sort_dir == "desc" {
-                rhs_path.cmp(&lhs_path)
-            } else {
-                lhs_path.cmp(&rhs_path)
-            }
-        }
-    }
+/// Lowercase `s` into `buf`, reusing its allocation across entries.
+fn lowercase_into<'a>(buf: &'a mut String, s: &str) -> &'a str {
+    buf.clear();
+    buf.extend(s.chars().flat_map(char::to_lowercase));
+    buf
 }
 
-fn relevance_rank(entry: &EntryDto, query_lower: &str, path_suffix: &str) -> u8 {
+fn entry_cmp(a: &EntryDto, b: &EntryDto, sort_by: &str, sort_dir: &str) -> Ordering {
+    let directed = |order: Ordering| {
+        if sort_dir == "desc" {
+            order.reverse()
+        } else {
+            order
+        }
+    };
+    let by_name = || cmp_lowercase(&a.name, &b.name);
+    let by_path = || cmp_lowercase(&a.path, &b.path);
+    match sort_by {
+        "mtime" => directed(a.mtime.unwrap_or(0).cmp(&b.mtime.unwrap_or(0)))
+            .then_with(by_name)
+            .then_with(by_path),
+        "dir" => directed(cmp_lowercase(&a.dir, &b.dir)).then_with(by_name),
+        "size" => directed(a.size.unwrap_or(0).cmp(&b.size.unwrap_or(0)))
+            .then_with(by_name)
+            .then_with(by_path),
+        _ => directed(by_name().then_with(by_path)),
+    }
+}
+
+/// `buf` is scratch space, reused across calls so ranking a result set
+/// doesn't allocate per entry.
+fn relevance_rank(
+    entry: &EntryDto,
+    query_lower: &str,
+    path_suffix: &str,
+    buf: &mut String,
+) -> u8 {
     if query_lower.is_empty() {
         return 255;
     }
 
-    let name = entry.name.to_lowercase();
+    let name = lowercase_into(buf, &entry.name);
 
     if name == query_lower {
         return 0;
@@ -1877,7 +2630,7 @@ fn relevance_rank(entry: &EntryDto, query_lower: &str, path_suffix: &str) -> u8
     }
 
     // Path lowercasing is deferred: most entries rank 0-2 on name alone.
-    let path = entry.path.to_lowercase();
+    let path = lowercase_into(buf, &entry.path);
     if path.ends_with(path_suffix) {
         return 3;
     }
@@ -1896,6 +2649,10 @@ pub fn sort_entries(entries: &mut Vec<EntryDto>, sort_by: &str, sort_dir: &str)
     entries.sort_by(|a, b| entry_cmp(a, b, sort_by, sort_dir));
 }
 
+/// Relevance order within one page. DB name searches already come back in
+/// rank and depth order (`run_db_search`), so LIMIT picked the right rows;
+/// this adds what SQL can't (backup copies after their originals) and ranks
+/// the `find`/Windows Search fallbacks.
 fn sort_entries_with_relevance(
     entries: &mut Vec<EntryDto>,
     query: &str,
@@ -1911,26 +2668,33 @@ fn sort_entries_with_relevance(
 
     // Rank every entry once (decorate–sort–undecorate): relevance_rank
     // lowercases name/path, far too expensive to recompute per comparison.
-    let mut decorated: Vec<(u8, usize, EntryDto)> = entries
+    let mut buf = String::new();
+    let mut decorated: Vec<(u8, bool, usize, EntryDto)> = entries
         .drain(..)
         .map(|entry| {
-            let rank = relevance_rank(&entry, &query_lower, &path_suffix);
+            let rank = relevance_rank(&entry, &query_lower, &path_suffix, &mut buf);
+            // Within a rank, "report (1).pdf" / "report copy.pdf" follow the
+            // original instead of interleaving with it.
+            let backup = backups::is_backup_name(&entry.name);
             // For highly-relevant matches, prefer shallower paths first
             // so `~/name` ranks above deep descendants with the same name.
             let depth = if rank <= 3 { path_depth(&entry.path) } else { 0 };
-            (rank, depth, entry)
+            (rank, backup, depth, entry)
         })
         .collect();
     decorated.sort_by(|a, b| {
         if a.0 != b.0 {
             return a.0.cmp(&b.0);
         }
-        if a.0 <= 3 && a.1 != b.1 {
+        if a.1 != b.1 {
             return a.1.cmp(&b.1);
         }
-        entry_cmp(&a.2, &b.2, sort_by, sort_dir)
+        if a.0 <= 3 && a.2 != b.2 {
+            return a.2.cmp(&b.2);
+        }
+        entry_cmp(&a.3, &b.3, sort_by, sort_dir)
     });
-    entries.extend(decorated.into_iter().map(|(_, _, entry)| entry));
+    entries.extend(decorated.into_iter().map(|(_, _, _, entry)| entry));
 }
 
 fn filter_ignored_entries(
@@ -1944,6 +2708,7 @@ fn filter_ignored_entries(
         .collect()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn find_search(
     home_dir: &Path,
     ignored_roots: &[PathBuf],
@@ -1952,6 +2717,7 @@ fn find_search(
     limit: usize,
     sort_by: &str,
     sort_dir: &str,
+    mut partial: Option<&mut PartialFlush<'_>>,
 ) -> Vec<EntryDto> {
     let trimmed = query.trim();
     if trimmed.is_empty() || limit == 0 {
@@ -1977,10 +2743,7 @@ fn find_search(
                 let p = PathBuf::from(dir_part);
                 if p.is_absolute() {
                     Some(p)
-                } else if !dir_part.contains('*')
-                    && !dir_part.contains('?')
-                    && !dir_part.contains('[')
-                {
+                } else if !contains_glob_meta(dir_part) {
                     Some(home_dir.join(dir_part))
                 } else {
                     None
@@ -1999,19 +2762,22 @@ fn find_search(
         }
 
         if !name_part.is_empty() {
-            if name_part.contains('*') || name_part.contains('?') {
+            if contains_glob_meta(name_part) {
                 name_filter_pattern = Some(name_part.to_string());
                 name_filter_glob = true;
             } else {
                 name_filter_pattern = Some(format!("*{}*", name_part));
             }
         }
-    } else if trimmed.contains('*') || trimmed.contains('?') {
+    } else if contains_glob_meta(trimmed) {
         name_filter_pattern = Some(trimmed.to_string());
         name_filter_glob = true;
     } else {
         name_filter_pattern = Some(format!("*{}*", trimmed));
     }
+    // find reads `[...]` classes itself; an unclosed `[` has to stay literal.
+    let dir_filter_pattern = dir_filter_pattern.map(|p| glob_class::escape_unclosed_for_find(&p));
+    let name_filter_pattern = name_filter_pattern.map(|p| glob_class::escape_unclosed_for_find(&p));
 
     let mut cmd = Command::new("find");
     cmd.arg(&search_root);
@@ -2063,6 +2829,9 @@ fn find_search(
             if entries.len() >= limit {
                 break;
             }
+            if let Some(p) = partial.as_mut() {
+                p.maybe_flush(&entries);
+            }
         }
     }
 
@@ -2077,10 +2846,11 @@ fn find_search(
 /// arm is unnecessary. OR IGNORE keeps overlapping extra roots harmless.
 /// Callers on the hot path pre-sort batches by path in the scan workers so
 /// the UNIQUE(path) b-tree sees near-sequential inserts.
-/// Run one transaction binding every row of `rows` to `sql` — a 9-placeholder
+/// Run one transaction binding every row of `rows` to `sql` — a 13-placeholder
 /// INSERT variant (`path, name, dir, is_dir, ext, mtime, size, indexed_at,
-/// run_id`). Shared by `insert_rows_fresh` and `upsert_rows`, which differ only
-/// in the INSERT conflict clause.
+/// run_id, name_norm, dir_norm, mtime_nsec, cloud_only`). Shared by
+/// `insert_rows_fresh` and `upsert_rows`, which differ only in the INSERT
+/// conflict clause.
 fn write_rows(conn: &mut Connection, rows: &[IndexRow], sql: &str) -> AppResult<usize> {
     if rows.is_empty() {
         return Ok(0);
@@ -2099,7 +2869,11 @@ fn write_rows(conn: &mut Connection, rows: &[IndexRow], sql: &str) -> AppResult<
                 row.mtime,
                 row.size,
                 row.indexed_at,
-                row.run_id
+                row.run_id,
+                row.name_norm,
+                row.dir_norm,
+                row.mtime_nsec,
+                row.cloud_only
             ])
             .map_err(|e| e.to_string())?;
         }
@@ -2113,8 +2887,9 @@ pub(crate) fn insert_rows_fresh(conn: &mut Connection, rows: &[IndexRow]) -> App
         conn,
         rows,
         r#"
-        INSERT OR IGNORE INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        INSERT OR IGNORE INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm, mtime_nsec, cloud_only)
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
         "#,
     )
 }
@@ -2124,17 +2899,22 @@ pub(crate) fn upsert_rows(conn: &mut Connection, rows: &[IndexRow]) -> AppResult
         conn,
         rows,
         r#"
-        INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                            name_norm, dir_norm, mtime_nsec, cloud_only)
+        VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
         ON CONFLICT(path) DO UPDATE SET
           name = excluded.name,
           dir = excluded.dir,
+          name_norm = excluded.name_norm,
+          dir_norm = excluded.dir_norm,
           is_dir = excluded.is_dir,
           ext = excluded.ext,
           mtime = excluded.mtime,
+          mtime_nsec = excluded.mtime_nsec,
           size = excluded.size,
           indexed_at = excluded.indexed_at,
-          run_id = excluded.run_id
+          run_id = excluded.run_id,
+          cloud_only = excluded.cloud_only
         "#,
     )
 }
@@ -2171,9 +2951,11 @@ pub(crate) fn delete_paths(conn: &mut Connection, raw_paths: &[String]) -> AppRe
             .map_err(|e| e.to_string())?;
 
         for path in raw_paths {
+            // Watchers may report extended-length spellings; rows never use them.
+            let path = long_path::strip_str(path);
             let is_root = path == "/" || path == "\\";
             let normalized = if is_root {
-                path.clone()
+                path.to_string()
             } else {
                 path.trim_end_matches(&['/', '\\'][..]).to_string()
             };
@@ -2204,17 +2986,99 @@ pub(crate) fn delete_paths(conn: &mut Connection, raw_paths: &[String]) -> AppRe
     Ok(deleted)
 }
 
-pub(crate) fn emit_index_state(app: &AppHandle, state: &str, message: Option<String>) {
-    let is_catchup = message.as_ref().map_or(false, |m| m.starts_with("Catchup:"));
-    let _ = app.emit(
-        "index_state",
-        IndexStateEvent {
-            state: state.to_string(),
-            message,
-            is_catchup,
-        },
-    );
-}
+/// Move the rows under directory `old` to `new` in place — one UPDATE
+/// rewriting the path and dir prefixes — for a rename the watcher saw as an
+/// old→new pair, instead of deleting the subtree and walking it again. Rows
+/// already under `new` are stale and dropped first. The rows for `old` and
+/// `new` themselves are left to the caller. Returns (moved, removed).
+pub(crate) fn rename_subtree(
+    conn: &mut Connection,
+    old: &str,
+    new: &str,
+) -> AppResult<(usize, usize)> {
+    let old = old.trim_end_matches(&['/', '\\'][..]);
+    let new = new.trim_end_matches(&['/', '\\'][..]);
+    let (old_start, old_end) = subtree_range_bounds(old);
+    let (new_start, new_end) = subtree_range_bounds(new);
+    // Nested ends (a folder moved into itself can't happen; its parent into
+    // it would make the stale-row sweep take the moved rows along) are left
+    // to the plain delete-and-upsert.
+    if old.is_empty()
+        || new.is_empty()
+        || old == new
+        || new.starts_with(&old_start)
+        || old.starts_with(&new_start)
+    {
+        return Ok((0, 0));
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let removed = tx
+        .execute(
+            "DELETE FROM entries WHERE path >= ?1 AND path < ?2",
+            params![&new_start, &new_end],
+        )
+        .map_err(|e| e.to_string())?;
+    // substr() counts characters, and every moved path and dir starts with
+    // `old` (every `dir_norm` with its folded form: folding works per path
+    // component); names are untouched, so the FTS trigger doesn't fire.
+    let (old_norm, new_norm) = (name_norm::fold(old), name_norm::fold(new));
+    let moved = tx
+        .execute(
+            "UPDATE entries SET path = ?3 || substr(path, ?4), dir = ?3 || substr(dir, ?4),
+                 dir_norm = ?5 || substr(dir_norm, ?6)
+             WHERE path >= ?1 AND path < ?2",
+            params![
+                &old_start,
+                &old_end,
+                new,
+                old.chars().count() as i64 + 1,
+                new_norm,
+                old_norm.chars().count() as i64 + 1
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((moved, removed))
+}
+
+/// Respell the row at `old` — and, for a folder, the rows under it — as
+/// `new`, which differs only in case (see `case_rename`). A stale row already
+/// at `new` is replaced. Returns (moved, removed) like `rename_subtree`.
+pub(crate) fn rename_case_only(
+    conn: &mut Connection,
+    old: &str,
+    new: &str,
+) -> AppResult<(usize, usize)> {
+    let (mut moved, mut removed) = rename_subtree(conn, old, new)?;
+    let name = Path::new(new)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| new.to_string());
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let exists: bool = tx
+        .query_row("SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)", params![old], |r| {
+            r.get(0)
+        })
+        .map_err(|e| e.to_string())?;
+    if exists {
+        removed += tx
+            .execute("DELETE FROM entries WHERE path = ?1", params![new])
+            .map_err(|e| e.to_string())?;
+        moved += tx
+            .execute(
+                "UPDATE entries SET path = ?2, name = ?3, name_norm = ?4 WHERE path = ?1",
+                params![old, new, name, name_norm::fold(&name)],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((moved, removed))
+}
+
+pub(crate) fn emit_index_state(app: &AppHandle, state: &str, message: Option<String>) {
+    index_events::state(app, state, message);
+}
 
 pub(crate) fn emit_index_updated(
     app: &AppHandle,
@@ -2222,25 +3086,11 @@ pub(crate) fn emit_index_updated(
     last_updated: i64,
     permission_errors: u64,
 ) {
-    let _ = app.emit(
-        "index_updated",
-        IndexUpdatedEvent {
-            entries_count,
-            last_updated,
-            permission_errors,
-        },
-    );
+    index_events::counts(app, entries_count, last_updated, permission_errors);
 }
 
 pub(crate) fn emit_index_progress(app: &AppHandle, scanned: u64, indexed: u64, current_path: String) {
-    let _ = app.emit(
-        "index_progress",
-        IndexProgressEvent {
-            scanned,
-            indexed,
-            current_path,
-        },
-    );
+    index_events::progress(app, scanned, indexed, current_path);
 }
 
 pub(crate) fn set_state(state: &AppState, next: IndexState, message: Option<String>) {
@@ -2300,7 +3150,6 @@ pub(crate) fn refresh_and_emit_status_counts(app: Option<&AppHandle>, state: &Ap
 /// Unlike `refresh_and_emit_status_counts` this never queries `entries`, so
 /// it is safe on every watcher batch: the COUNT(*)/MAX() recount there was a
 /// periodic whole-table scan — a visible CPU spike every ~2s on large DBs.
-#[cfg(target_os = "macos")]
 fn emit_and_persist_cached_counts(app: Option<&AppHandle>, state: &AppState) {
     let (entries_count, last_updated) = {
         let status = state.status.lock();
@@ -2339,7 +3188,6 @@ pub(crate) fn set_ready_with_cached_counts(app: &AppHandle, state: &AppState) {
     emit_index_updated(app, count, last_updated.unwrap_or_else(now_epoch), 0);
 }
 
-#[cfg(target_os = "macos")]
 fn touch_status_updated(state: &AppState) {
     state.status.lock().last_updated = Some(now_epoch());
 }
@@ -2474,6 +3322,60 @@ fn run_db_maintenance(state: &AppState) {
     let _ = conn.execute_batch("PRAGMA shrink_memory;");
 }
 
+/// Quiet period (no searches, no index writes) before the WAL is truncated.
+const WAL_IDLE_CHECKPOINT_AFTER: Duration = Duration::from_secs(30);
+const WAL_IDLE_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A checkpoint is due once activity has been quiet for the idle period and
+/// there has been activity since the last idle checkpoint.
+fn idle_checkpoint_due(last_activity: i64, last_checkpointed: i64, now: i64) -> bool {
+    last_activity > last_checkpointed
+        && now.saturating_sub(last_activity) >= WAL_IDLE_CHECKPOINT_AFTER.as_secs() as i64
+}
+
+/// Background task that truncates the WAL once the app goes idle. Watcher
+/// bursts between full index runs otherwise grow the WAL without bound
+/// (autocheckpoint only copies frames back; it never shrinks the file).
+fn start_wal_checkpoint_scheduler(state: AppState) {
+    std::thread::spawn(move || {
+        let mut last_checkpointed: i64 = 0;
+        loop {
+            std::thread::sleep(WAL_IDLE_POLL_INTERVAL);
+            if !state.db_ready.load(AtomicOrdering::Acquire)
+                || state.indexing_active.load(AtomicOrdering::Acquire)
+            {
+                continue;
+            }
+            let last_activity = state.last_db_activity.load(AtomicOrdering::Relaxed);
+            if !idle_checkpoint_due(last_activity, last_checkpointed, now_epoch()) {
+                continue;
+            }
+            let Ok(_writing) = state.db_writes.enter() else {
+                continue;
+            };
+            let Ok(conn) = db_connection(&state.db_path) else {
+                continue;
+            };
+            let t = Instant::now();
+            match conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+            }) {
+                // busy=1: a reader or writer held the WAL; retry next idle window.
+                Ok((0, frames)) => {
+                    last_checkpointed = last_activity;
+                    eprintln!(
+                        "[maintenance] idle wal checkpoint frames={} in {}ms",
+                        frames,
+                        t.elapsed().as_millis()
+                    );
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[maintenance] idle wal checkpoint failed: {e}"),
+            }
+        }
+    });
+}
+
 /// Ask macOS malloc to hand freed-but-retained regions back to the OS. Without
 /// this a large indexing pass leaves hundreds of MB of empty malloc regions
 /// resident until system memory pressure reclaims them.
@@ -2490,19 +3392,33 @@ fn release_memory_to_os() {
     }
 }
 
+/// A stored row's (mtime, mtime_nsec, size), what the incremental scan
+/// compares a fresh stat against.
+type RowSignature = (Option<i64>, Option<i64>, Option<i64>, bool);
+
+/// Whether `row` matches a stored signature. The sub-second part only counts
+/// when both sides have one, so rows from a whole-second scan aren't all
+/// rewritten by the next incremental pass.
+fn signature_matches(signature: &RowSignature, row: &IndexRow) -> bool {
+    let (mtime, mtime_nsec, size, cloud_only) = *signature;
+    mtime == row.mtime
+        && size == row.size
+        && cloud_only == row.cloud_only
+        && (mtime_nsec.is_none() || row.mtime_nsec.is_none() || mtime_nsec == row.mtime_nsec)
+}
+
 /// Remove `row`'s path from the direct-children preload map, returning true
 /// when it was present AND unchanged (same mtime + size). Paths left in the
 /// map after the scan are only reconciliation candidates; callers must confirm
 /// policy exclusion or NotFound before deletion. (Whole-subtree diffs use the
 /// hash-compacted `rescan::SubtreeDiff` instead; this map holds one level.)
 fn row_unchanged(
-    existing: &mut HashMap<String, (Option<i64>, Option<i64>)>,
+    existing: &mut HashMap<String, RowSignature>,
     row: &IndexRow,
 ) -> bool {
-    matches!(
-        existing.remove(&row.path),
-        Some((old_mtime, old_size)) if old_mtime == row.mtime && old_size == row.size
-    )
+    existing
+        .remove(&row.path)
+        .is_some_and(|signature| signature_matches(&signature, row))
 }
 
 /// Existing DIRECT children of `dir_path` only. The root row is deliberately
@@ -2511,23 +3427,25 @@ fn row_unchanged(
 fn preload_direct_children(
     conn: &Connection,
     dir_path: &str,
-) -> AppResult<HashMap<String, (Option<i64>, Option<i64>)>> {
+) -> AppResult<HashMap<String, RowSignature>> {
     let mut map = HashMap::new();
     let mut stmt = conn
-        .prepare("SELECT path, mtime, size FROM entries WHERE dir = ?1 AND path <> ?1")
+        .prepare(
+            "SELECT path, mtime, mtime_nsec, size, cloud_only FROM entries \
+             WHERE dir = ?1 AND path <> ?1",
+        )
         .map_err(|e| e.to_string())?;
     let rows = stmt
         .query_map(params![dir_path], |row| {
             Ok((
                 row.get::<_, String>(0)?,
-                row.get::<_, Option<i64>>(1)?,
-                row.get::<_, Option<i64>>(2)?,
+                (row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?),
             ))
         })
         .map_err(|e| e.to_string())?;
     for row in rows {
-        let (path, mtime, size) = row.map_err(|e| e.to_string())?;
-        map.insert(path, (mtime, size));
+        let (path, signature) = row.map_err(|e| e.to_string())?;
+        map.insert(path, signature);
     }
     Ok(map)
 }
@@ -2535,11 +3453,11 @@ fn preload_direct_children(
 fn preload_path_signature(
     conn: &Connection,
     path: &str,
-) -> AppResult<Option<(Option<i64>, Option<i64>)>> {
+) -> AppResult<Option<RowSignature>> {
     conn.query_row(
-        "SELECT mtime, size FROM entries WHERE path = ?1",
+        "SELECT mtime, mtime_nsec, size, cloud_only FROM entries WHERE path = ?1",
         params![path],
-        |row| Ok((row.get(0)?, row.get(1)?)),
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
     )
     .optional()
     .map_err(|e| e.to_string())
@@ -2614,7 +3532,7 @@ struct DirectChildReconciliation {
 /// final metadata probe confirms NotFound. Every other error aborts the
 /// reconciliation before any delete is applied.
 fn reconcile_unseen_direct_children<Metadata, Skip>(
-    existing: &HashMap<String, (Option<i64>, Option<i64>)>,
+    existing: &HashMap<String, RowSignature>,
     mut metadata_for: Metadata,
     mut should_skip: Skip,
 ) -> AppResult<DirectChildReconciliation>
@@ -2662,7 +3580,7 @@ fn stage_direct_child(
     child: ObservedDirectChild,
     scan_root: &Path,
     current_run_id: i64,
-    existing: &mut HashMap<String, (Option<i64>, Option<i64>)>,
+    existing: &mut HashMap<String, RowSignature>,
     batch: &mut Vec<IndexRow>,
     priority_roots: &mut Vec<PathBuf>,
     deferred_roots: &mut Vec<PathBuf>,
@@ -2687,22 +3605,32 @@ fn stage_direct_child(
     }
 }
 
-/// Build the shared rayon thread pool for a parallel filesystem scan. Scanning
-/// is I/O-bound (stat() blocks on disk), so we oversubscribe cores. Returns the
-/// pool, its thread count, and the CPU count (callers derive worker count from
-/// it differently). Shared by the fresh and catchup scan branches.
-fn build_scan_pool() -> (Arc<jwalk::rayon::ThreadPool>, usize, usize) {
-    let n_cpus = std::thread::available_parallelism()
-        .map(|n| n.get())
-        .unwrap_or(4);
-    let pool_threads = (n_cpus * 2).max(8);
+/// Build the shared rayon thread pool for a parallel filesystem scan, sized
+/// for the storage under `scan_root` (see `scan_tuning::scan_threads`) unless
+/// `tuning` fixes the thread count.
+/// Returns the pool, its thread count, and the worker budget (CPU count,
+/// capped by the pool on spinning disks; callers derive worker count from it
+/// differently). Shared by the fresh and catchup scan branches.
+fn build_scan_pool(
+    scan_root: &Path,
+    tuning: &index_tuning::IndexTuning,
+) -> (Arc<jwalk::rayon::ThreadPool>, usize, usize) {
+    let n_cpus = scan_tuning::available_cpus();
+    let storage = scan_tuning::detect_storage(scan_root);
+    let pool_threads = tuning.scan_threads(n_cpus, storage);
+    perf_log(format!(
+        "scan_pool storage={} cpus={} threads={}",
+        storage.label(),
+        n_cpus,
+        pool_threads
+    ));
     let pool = Arc::new(
         jwalk::rayon::ThreadPoolBuilder::new()
             .num_threads(pool_threads)
             .build()
             .expect("failed to build rayon pool for parallel scan"),
     );
-    (pool, pool_threads, n_cpus)
+    (pool, pool_threads, n_cpus.min(pool_threads))
 }
 
 /// `app: None` runs the index pipeline without UI event emission and without
@@ -2715,6 +3643,7 @@ pub(crate) fn run_incremental_index(app: Option<&AppHandle>, state: &AppState) -
         state.home_dir.to_string_lossy(),
         state.db_path.to_string_lossy(),
     ));
+    index_tuning::apply(state);
 
     let mut conn = db_connection(&state.db_path)?;
     set_indexing_pragmas(&conn)?;
@@ -2740,7 +3669,11 @@ pub(crate) fn run_incremental_index(app: Option<&AppHandle>, state: &AppState) -
             "DROP INDEX IF EXISTS idx_entries_dir_ext_name_nocase;
              DROP INDEX IF EXISTS idx_entries_mtime;
              DROP INDEX IF EXISTS idx_entries_name_nocase;
-             DROP INDEX IF EXISTS idx_entries_ext_name;",
+             DROP INDEX IF EXISTS idx_entries_name_norm;
+             DROP INDEX IF EXISTS idx_entries_ext_name;
+             DROP INDEX IF EXISTS idx_entries_ext_size;
+             DROP INDEX IF EXISTS idx_entries_ext_mtime;
+             DROP INDEX IF EXISTS idx_entries_name_mtime;",
         );
     }
 
@@ -2765,6 +3698,9 @@ pub(crate) fn run_incremental_index(app: Option<&AppHandle>, state: &AppState) -
     match &result {
         Ok(_) => {
             let _ = set_meta(&conn, "index_complete", "1");
+            if is_fresh_run {
+                record_full_index_duration(&conn, started.elapsed());
+            }
             let extra_roots = state.extra_roots.lock().clone();
             let roots_str: Vec<String> = extra_roots.iter().map(|r| r.to_string_lossy().to_string()).collect();
             let _ = set_meta(&conn, "indexed_extra_roots", &roots_str.join("\n"));
@@ -2858,8 +3794,8 @@ fn run_incremental_index_inner(
         &state.config_file_path,
         &state.home_dir,
         &state.cwd,
-        state.path_ignores.as_ref(),
-        state.path_ignore_patterns.as_ref(),
+        &fixed_ignore_roots(state),
+        &[],
     );
 
     let last_run_id: i64 = get_meta(conn, "last_run_id")
@@ -2871,7 +3807,8 @@ fn run_incremental_index_inner(
 
     // Fresh index has no secondary indexes to maintain, so we can use larger
     // batches to reduce transaction overhead (fewer commits = faster).
-    let flush_batch_size = if is_fresh { BATCH_SIZE * 5 } else { BATCH_SIZE };
+    let tuning = *state.index_tuning.read();
+    let flush_batch_size = if is_fresh { tuning.batch_size * 5 } else { tuning.batch_size };
 
     // Fresh index: drop FTS triggers to avoid per-row FTS overhead during bulk insert.
     // A single FTS rebuild runs after all rows are written (much faster than 3.6M trigger fires).
@@ -2887,6 +3824,7 @@ fn run_incremental_index_inner(
     let mut scanned: u64 = 0;
     let mut indexed: u64 = 0;
     let mut permission_errors: u64 = 0;
+    state.permission_log.clear();
     let mut current_path = String::new();
     let mut batch: Vec<IndexRow> = Vec::with_capacity(flush_batch_size);
     // Paths removed because they vanished from disk (catchup set-difference).
@@ -2903,16 +3841,15 @@ fn run_incremental_index_inner(
 
     // Index scan_root itself. Failure aborts this pass before any entry delete
     // is applied; the old root row and every descendant remain searchable.
-    let root_metadata = fs::symlink_metadata(&state.scan_root).map_err(|error| {
+    let root_metadata = long_path::symlink_metadata(&state.scan_root).map_err(|error| {
         filesystem_observation_error("scan_root_metadata", &state.scan_root, &error)
     })?;
     if let Some(mut row) = index_row_from_path_and_metadata(&state.scan_root, &root_metadata) {
         scanned += 1;
         row.run_id = current_run_id;
-        let unchanged = matches!(
-            root_signature,
-            Some((mtime, size)) if mtime == row.mtime && size == row.size
-        );
+        let unchanged = root_signature
+            .as_ref()
+            .is_some_and(|signature| signature_matches(signature, &row));
         if !unchanged {
             batch.push(row);
         }
@@ -2928,7 +3865,7 @@ fn run_incremental_index_inner(
             fs::read_dir(root)
                 .map(|entries| entries.map(|entry| entry.map(|entry| entry.path())))
         },
-        |path| fs::symlink_metadata(path),
+        |path| long_path::symlink_metadata(path),
         |path| {
             should_skip_path(
                 path,
@@ -2957,7 +3894,7 @@ fn run_incremental_index_inner(
     if !is_fresh && !root_existing.is_empty() {
         let reconciliation = reconcile_unseen_direct_children(
             &root_existing,
-            |path| fs::symlink_metadata(path),
+            |path| long_path::symlink_metadata(path),
             |path| {
                 should_skip_path(
                     path,
@@ -3015,6 +3952,10 @@ fn run_incremental_index_inner(
             Ok(metadata) if metadata.is_dir() => scanned_extra_roots.push(root),
             Ok(_) => {
                 permission_errors += 1;
+                state.permission_log.record(
+                    &root,
+                    &io::Error::new(io::ErrorKind::NotADirectory, "extra root is not a directory"),
+                );
                 eprintln!(
                     "[index] configured extra root is not a directory; preserving existing rows: {}",
                     root.display()
@@ -3022,6 +3963,7 @@ fn run_incremental_index_inner(
             }
             Err(error) => {
                 permission_errors += 1;
+                state.permission_log.record(&root, &error);
                 eprintln!(
                     "[index] {}; preserving existing extra-root rows",
                     filesystem_observation_error("extra_root_metadata", &root, &error)
@@ -3054,6 +3996,8 @@ fn run_incremental_index_inner(
         .chain(deferred_roots)
         .chain(scanned_extra_roots)
         .collect();
+    let hot_dirs = state.hot_dirs.lock().clone();
+    let roots = pathpriority::order_roots_by_priority(roots, &hot_dirs);
     perf_log(format!(
         "index_scan_roots total={} (incl. extra pathindexing roots)",
         roots.len()
@@ -3079,7 +4023,7 @@ fn run_incremental_index_inner(
         }
 
         // Shared rayon pool: all workers share one pool sized for maximum I/O throughput.
-        let (shared_pool, pool_threads, n_cpus) = build_scan_pool();
+        let (shared_pool, pool_threads, n_cpus) = build_scan_pool(&state.scan_root, &tuning);
         let n_workers = n_cpus.min(roots.len().max(1));
         eprintln!(
             "[index] fresh parallel scan: {} workers, {} pool threads, {} roots",
@@ -3102,9 +4046,11 @@ fn run_incremental_index_inner(
                 let run_id = current_run_id;
                 let n_w = n_workers;
                 let pool = shared_pool.clone();
-                // Use BATCH_SIZE (not flush_batch_size) so workers send batches every
-                // 10k rows instead of 50k → progress updates reach the main thread sooner.
-                let flush_size = BATCH_SIZE;
+                let pressure = state.search_pressure.clone();
+                let perm_log = state.permission_log.clone();
+                // Use the tuned batch size (not flush_batch_size) so workers send
+                // batches 5x as often → progress updates reach the main thread sooner.
+                let flush_size = tuning.batch_size;
 
                 scope.spawn(move || {
                     let mut local_batch: Vec<IndexRow> = Vec::with_capacity(flush_size);
@@ -3124,6 +4070,7 @@ fn run_incremental_index_inner(
 
                         let s_roots = skip_roots.clone();
                         let s_patterns = skip_patterns.clone();
+                        let s_pressure = pressure.clone();
                         let walker =
                             jwalk::WalkDirGeneric::<((), Option<fs::Metadata>)>::new(root)
                                 .follow_links(false)
@@ -3133,6 +4080,7 @@ fn run_incremental_index_inner(
                                     busy_timeout: None,
                                 })
                                 .process_read_dir(move |_depth, path, _state, children| {
+                                    s_pressure.yield_if_degraded();
                                     children.retain_mut(|entry_result| match entry_result {
                                         Ok(entry) => {
                                             let full_path = path.join(&entry.file_name);
@@ -3144,7 +4092,7 @@ fn run_incremental_index_inner(
                                                 return false;
                                             }
                                             entry.client_state =
-                                                fs::symlink_metadata(&full_path).ok();
+                                                long_path::symlink_metadata(&full_path).ok();
                                             true
                                         }
                                         Err(_) => false,
@@ -3167,6 +4115,12 @@ fn run_incremental_index_inner(
                                         None => {
                                             local_perm_errors += 1;
                                             root_perm_errors += 1;
+                                            let error = long_path::symlink_metadata(&path)
+                                                .err()
+                                                .unwrap_or_else(|| {
+                                                    io::Error::other("metadata unavailable")
+                                                });
+                                            perm_log.record(&path, &error);
                                             continue;
                                         }
                                     };
@@ -3203,6 +4157,7 @@ fn run_incremental_index_inner(
                                     root_scanned += 1;
                                     local_perm_errors += 1;
                                     root_perm_errors += 1;
+                                    perm_log.record_walk(root, &err);
                                     if local_perm_errors <= 20 || perf_log_enabled() {
                                         eprintln!("[index] permission error: {}", err);
                                     }
@@ -3280,7 +4235,7 @@ fn run_incremental_index_inner(
             batch.clear();
         }
 
-        let (shared_pool, pool_threads, n_cpus) = build_scan_pool();
+        let (shared_pool, pool_threads, n_cpus) = build_scan_pool(&state.scan_root, &tuning);
         // Each worker holds one root's preload map in memory; cap workers to
         // bound peak memory when several large roots are processed at once.
         let n_workers = n_cpus.min(roots.len().max(1)).min(8);
@@ -3307,7 +4262,9 @@ fn run_incremental_index_inner(
                 let run_id = current_run_id;
                 let n_w = n_workers;
                 let pool = shared_pool.clone();
-                let flush_size = BATCH_SIZE;
+                let pressure = state.search_pressure.clone();
+                let perm_log = state.permission_log.clone();
+                let flush_size = tuning.batch_size;
                 let worker_db = worker_db_path.clone();
 
                 scope.spawn(move || {
@@ -3337,6 +4294,7 @@ fn run_incremental_index_inner(
 
                         let s_roots = skip_roots.clone();
                         let s_patterns = skip_patterns.clone();
+                        let s_pressure = pressure.clone();
                         let walker =
                             jwalk::WalkDirGeneric::<((), Option<fs::Metadata>)>::new(root)
                                 .follow_links(false)
@@ -3346,6 +4304,7 @@ fn run_incremental_index_inner(
                                     busy_timeout: None,
                                 })
                                 .process_read_dir(move |_depth, path, _state, children| {
+                                    s_pressure.yield_if_degraded();
                                     children.retain_mut(|entry_result| match entry_result {
                                         Ok(entry) => {
                                             let full_path = path.join(&entry.file_name);
@@ -3357,7 +4316,7 @@ fn run_incremental_index_inner(
                                                 return false;
                                             }
                                             entry.client_state =
-                                                fs::symlink_metadata(&full_path).ok();
+                                                long_path::symlink_metadata(&full_path).ok();
                                             true
                                         }
                                         // Preserve read-dir errors so the
@@ -3380,7 +4339,7 @@ fn run_incremental_index_inner(
 
                                     let metadata = match entry.client_state {
                                         Some(m) => m,
-                                        None => match fs::symlink_metadata(&path) {
+                                        None => match long_path::symlink_metadata(&path) {
                                             // A transient first failure gets
                                             // one cheap retry before deciding.
                                             Ok(metadata) => metadata,
@@ -3394,6 +4353,7 @@ fn run_incremental_index_inner(
                                             Err(error) => {
                                                 local_perm_errors += 1;
                                                 root_perm_errors += 1;
+                                                perm_log.record(&path, &error);
                                                 existing.mark_errored(&path);
                                                 if local_perm_errors <= 20
                                                     || perf_log_enabled()
@@ -3449,6 +4409,7 @@ fn run_incremental_index_inner(
                                     // NotFound means it truly vanished and its
                                     // rows should become deletion leftovers.
                                     let error_path = err.path().unwrap_or(root.as_path());
+                                    perm_log.record_walk(root, &err);
                                     existing.observe_walk_error(
                                         err.io_error().map(|e| e.kind()),
                                         error_path,
@@ -3629,13 +4590,12 @@ fn remember_op(
     });
 }
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
 pub(crate) fn is_recently_touched(state: &AppState, path: &str) -> bool {
     let mut ops = state.recent_ops.lock();
     trim_recent_ops(&mut ops);
 
     ops.iter().any(|op| match op.op_type {
-        "rename" | "trash" => {
+        "rename" | "trash" | "move" | "copy" | "create" => {
             op.old_path
                 .as_deref()
                 .map(|old| old == path)
@@ -3654,21 +4614,23 @@ pub(crate) fn is_recently_touched(state: &AppState, path: &str) -> bool {
 /// `count_delta` is the exact net change in `entries` row count (inserted
 /// minus deleted), so callers can maintain `entries_count` incrementally
 /// instead of re-running `COUNT(*)` over the whole table.
-#[cfg(target_os = "macos")]
 struct PathChangeOutcome {
     changed: usize,
     count_delta: i64,
     retry_paths: Vec<PathBuf>,
+    /// What was written, for live query diffs.
+    upserted: Vec<IndexRow>,
+    deleted: Vec<String>,
+    /// Both ends of every move, whose rows were rewritten in place.
+    moved: Vec<String>,
 }
 
-#[cfg(target_os = "macos")]
 struct PreparedPathChanges {
     to_upsert: Vec<IndexRow>,
     to_delete: Vec<String>,
     retry_paths: Vec<PathBuf>,
 }
 
-#[cfg(target_os = "macos")]
 fn prepare_path_changes<Metadata>(
     state: &AppState,
     paths: &[PathBuf],
@@ -3722,7 +4684,6 @@ where
 /// How many of `rows` already exist in `entries`, checked in chunks that stay
 /// under SQLite's bound-parameter limit. Point lookups on the UNIQUE path
 /// index — cheap even for large watcher batches.
-#[cfg(target_os = "macos")]
 fn count_existing_paths(conn: &Connection, rows: &[IndexRow]) -> AppResult<usize> {
     let mut existing: i64 = 0;
     for chunk in rows.chunks(500) {
@@ -3737,26 +4698,85 @@ fn count_existing_paths(conn: &Connection, rows: &[IndexRow]) -> AppResult<usize
     Ok(existing.max(0) as usize)
 }
 
-#[cfg(target_os = "macos")]
-fn apply_path_changes(state: &AppState, paths: &[PathBuf]) -> AppResult<PathChangeOutcome> {
+/// What a watcher knows about a batch beyond its paths.
+#[derive(Default)]
+struct WatcherHints {
+    /// Folder renames seen as old→new pairs.
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// Pending paths the event itself reported deleted; no stat needed.
+    removed: HashSet<PathBuf>,
+}
+
+/// Apply one watcher batch. Renames in `hints` have the rows under each
+/// moved by `rename_subtree`, then both ends go through the usual
+/// stat-and-upsert/delete. A pair with either end ignored or just touched by
+/// the app is handled as two plain paths. Case-only renames, hinted or not,
+/// respell the existing rows instead; the old spelling would still stat as
+/// present on a case-insensitive volume.
+fn apply_path_changes(
+    state: &AppState,
+    paths: &[PathBuf],
+    hints: &WatcherHints,
+) -> AppResult<PathChangeOutcome> {
     let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+    let mut paths = paths.to_vec();
+    let mut moves: Vec<(String, String)> = Vec::new();
+    for (old, new) in &hints.renames {
+        paths.push(old.clone());
+        paths.push(new.clone());
+        let (old_str, new_str) = (
+            old.to_string_lossy().to_string(),
+            new.to_string_lossy().to_string(),
+        );
+        let plain = [old, new]
+            .iter()
+            .any(|p| should_skip_path(p, &ignored_roots, &ignored_patterns))
+            || is_recently_touched(state, &old_str)
+            || is_recently_touched(state, &new_str);
+        if !plain {
+            moves.push((old_str, new_str));
+        }
+    }
+    let mut case_moves: Vec<(String, String)> = Vec::new();
+    for (stale, current) in case_rename::find_in_batch(&paths) {
+        paths.retain(|p| *p != stale);
+        let (old_str, new_str) = (
+            stale.to_string_lossy().to_string(),
+            current.to_string_lossy().to_string(),
+        );
+        moves.retain(|(old, new)| *old != old_str || *new != new_str);
+        let plain = should_skip_path(&current, &ignored_roots, &ignored_patterns)
+            || is_recently_touched(state, &old_str)
+            || is_recently_touched(state, &new_str);
+        if !plain {
+            case_moves.push((old_str, new_str));
+        }
+    }
     let PreparedPathChanges {
         to_upsert,
         to_delete,
         retry_paths,
     } = prepare_path_changes(
         state,
-        paths,
+        &paths,
         &ignored_roots,
         &ignored_patterns,
-        |path| fs::symlink_metadata(path),
+        |path| {
+            if hints.removed.contains(path) {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            long_path::symlink_metadata(path)
+        },
     );
 
-    if to_upsert.is_empty() && to_delete.is_empty() {
+    if to_upsert.is_empty() && to_delete.is_empty() && moves.is_empty() && case_moves.is_empty() {
         return Ok(PathChangeOutcome {
             changed: 0,
             count_delta: 0,
             retry_paths,
+            upserted: Vec::new(),
+            deleted: Vec::new(),
+            moved: Vec::new(),
         });
     }
 
@@ -3780,13 +4800,33 @@ fn apply_path_changes(state: &AppState, paths: &[PathBuf]) -> AppResult<PathChan
     let conn = conn_slot.as_mut().expect("watcher connection present");
 
     let result: AppResult<PathChangeOutcome> = (|| {
+        // Before the deletes: `old` is gone from disk, and deleting it first
+        // would sweep the rows this is about to move.
+        let (mut moved, mut removed) = (0, 0);
+        for (old, new) in &moves {
+            let (m, r) = rename_subtree(conn, old, new)?;
+            moved += m;
+            removed += r;
+        }
+        for (old, new) in &case_moves {
+            let (m, r) = rename_case_only(conn, old, new)?;
+            moved += m;
+            removed += r;
+        }
         let existing = count_existing_paths(conn, &to_upsert)?;
         let up = upsert_rows(conn, &to_upsert)?;
         let del = delete_paths(conn, &to_delete)?;
         Ok(PathChangeOutcome {
-            changed: up + del,
-            count_delta: to_upsert.len() as i64 - existing as i64 - del as i64,
+            changed: up + del + moved + removed,
+            count_delta: to_upsert.len() as i64 - existing as i64 - del as i64 - removed as i64,
             retry_paths,
+            upserted: to_upsert,
+            deleted: to_delete,
+            moved: moves
+                .iter()
+                .chain(&case_moves)
+                .flat_map(|(old, new)| [old.clone(), new.clone()])
+                .collect(),
         })
     })();
     if result.is_err() {
@@ -3797,25 +4837,22 @@ fn apply_path_changes(state: &AppState, paths: &[PathBuf]) -> AppResult<PathChan
     result
 }
 
-#[cfg(target_os = "macos")]
 const STATUS_EMIT_MIN_INTERVAL: Duration = Duration::from_secs(2);
 
-#[cfg(target_os = "macos")]
 const DB_BUSY_RETRY_DELAY: Duration = Duration::from_secs(3);
 
-#[cfg(target_os = "macos")]
 const FS_OBSERVATION_RETRY_DELAY: Duration = Duration::from_secs(3);
 
-#[cfg(target_os = "macos")]
 fn process_watcher_paths(
     app: Option<&AppHandle>,
     state: &AppState,
     pending: &mut HashSet<PathBuf>,
+    hints: &mut WatcherHints,
     deadline: &mut Option<Instant>,
     last_status_emit: &mut Instant,
     pending_status_emit: &mut bool,
 ) {
-    if pending.is_empty() {
+    if pending.is_empty() && hints.renames.is_empty() {
         return;
     }
 
@@ -3825,13 +4862,17 @@ fn process_watcher_paths(
 
     let mut batch: Vec<PathBuf> = pending.drain().collect();
     batch.sort();
+    let hint_batch = std::mem::take(hints);
 
-    match apply_path_changes(state, &batch) {
+    match apply_path_changes(state, &batch, &hint_batch) {
         Ok(outcome) => {
             let PathChangeOutcome {
                 changed,
                 count_delta,
                 retry_paths,
+                upserted,
+                deleted,
+                moved,
             } = outcome;
             if retry_paths.is_empty() {
                 *deadline = None;
@@ -3843,6 +4884,9 @@ fn process_watcher_paths(
             }
             if changed > 0 {
                 invalidate_search_caches(state);
+                notify_live_queries(app, state, &upserted, &deleted);
+                mem_resident::resync(state, &moved);
+                mem_resident::apply_batch(state, &upserted, &deleted);
                 {
                     // Maintain counts incrementally — the rows just written
                     // carry indexed_at = now, and count_delta is the
@@ -3873,6 +4917,21 @@ fn process_watcher_paths(
                 for path in batch {
                     pending.insert(path);
                 }
+                // The moves may have gone through before the failure, and a
+                // second rename_subtree would sweep them as stale; retry both
+                // ends as plain paths instead. Removal hints are dropped too,
+                // as the paths may be back by then.
+                let moved: Vec<String> = hint_batch
+                    .renames
+                    .iter()
+                    .flat_map(|(old, new)| [old, new])
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect();
+                mem_resident::resync(state, &moved);
+                for (old, new) in hint_batch.renames {
+                    pending.insert(old);
+                    pending.insert(new);
+                }
                 *deadline = Some(Instant::now() + DB_BUSY_RETRY_DELAY);
                 return;
             }
@@ -3963,6 +5022,18 @@ fn remap_fs_event(
                 .map(|p| remap_event_path(p, remaps))
                 .collect(),
         ),
+        FsEvent::Renames(pairs) => FsEvent::Renames(
+            pairs
+                .into_iter()
+                .map(|(from, to)| (remap_event_path(from, remaps), remap_event_path(to, remaps)))
+                .collect(),
+        ),
+        FsEvent::Removed(paths) => FsEvent::Removed(
+            paths
+                .into_iter()
+                .map(|p| remap_event_path(p, remaps))
+                .collect(),
+        ),
         FsEvent::MustScanSubDirs(p) => FsEvent::MustScanSubDirs(remap_event_path(p, remaps)),
         FsEvent::HistoryDone => FsEvent::HistoryDone,
     }
@@ -4157,7 +5228,22 @@ fn start_fsevent_watcher_worker(
         // event-id continuity) when .pathindexing roots change.
         loop {
             let (tx, rx) = std::sync::mpsc::channel();
-            let (watch_roots, remaps) = fsevent_watch_roots(&state);
+            let (mut watch_roots, remaps) = fsevent_watch_roots(&state);
+            // FSEvents only hears local writes, so network and FUSE roots
+            // are polled; their changes arrive on the same channel.
+            let polled: Vec<PathBuf> = watch_roots
+                .iter()
+                .skip(1)
+                .filter(|root| poll_watcher::needs_polling(root))
+                .cloned()
+                .collect();
+            watch_roots.retain(|root| !polled.contains(root));
+            let poll_tx = tx.clone();
+            let _poller = (!polled.is_empty()).then(|| {
+                poll_watcher::spawn(&state, polled, move |changed| {
+                    poll_tx.send(mac::fsevent_watcher::FsEvent::Paths(changed)).is_ok()
+                })
+            });
 
             let built = mac::fsevent_watcher::FsEventWatcher::new(
                 &watch_roots,
@@ -4181,23 +5267,11 @@ fn start_fsevent_watcher_worker(
             let mut watcher = match built {
                 Ok(w) => w,
                 Err(err) => {
-                    set_state(
-                        &state,
-                        IndexState::Error,
-                        Some(format!("FSEvents watcher initialization failed: {err}")),
-                    );
-                    if let Some(app) = app.as_ref() {
-                        emit_index_state(
-                            app,
-                            "Error",
-                            Some(format!("FSEvents watcher initialization failed: {err}")),
-                        );
-                    }
-                    // A prior iteration may have set watcher_active on a
-                    // successful build; clear it before bailing so reset_index
-                    // doesn't spin for its full 5s deadline waiting on a
-                    // watcher thread that has already exited.
-                    state.watcher_active.store(false, AtomicOrdering::Release);
+                    // Poll everything instead; the polling thread takes over
+                    // watcher_active, so reset_index waits on it rather than
+                    // on this thread.
+                    eprintln!("[watcher] FSEvents watcher initialization failed ({err}), polling instead");
+                    poll_watcher::start(app, state.clone(), poll_watcher::indexed_roots(&state));
                     return;
                 }
             };
@@ -4259,6 +5333,7 @@ fn run_fsevent_stream(
     use std::sync::mpsc::RecvTimeoutError;
 
     let mut pending_paths: HashSet<PathBuf> = HashSet::new();
+    let mut hints = WatcherHints::default();
     let mut deadline: Option<Instant> = None;
     let mut last_flush = Instant::now();
     let mut last_status_emit = Instant::now();
@@ -4269,17 +5344,20 @@ fn run_fsevent_stream(
     let mut full_scan_triggered = false;
     let mut rebuild_requested = false;
 
-    // Snapshot config file entries at stream start so we only emit
-    // pathignore_changed when actual rule entries change (ignores whitespace/comments).
-    let mut last_config_entries =
-        pathignore_active_entries(&fs::read_to_string(&state.config_file_path).unwrap_or_default());
     let mut last_pathindexing_entries =
         pathindexing::pathindexing_active_entries(&fs::read_to_string(&state.pathindexing_file_path).unwrap_or_default());
 
+    // A pending batch touching a hot directory keeps the short debounce until
+    // it is flushed (process_watcher_paths clears `deadline`).
+    let mut hot_pending = false;
+
     loop {
         if state.watcher_stop.load(AtomicOrdering::Acquire) {
             break;
         }
+        if deadline.is_none() {
+            hot_pending = false;
+        }
 
         let wait = match deadline {
             Some(due) => {
@@ -4292,21 +5370,24 @@ fn run_fsevent_stream(
             }
             None => Duration::from_secs(1),
         };
-        match rx.recv_timeout(wait).map(|ev| remap_fs_event(ev, remaps)) {
-            Ok(mac::fsevent_watcher::FsEvent::Paths(paths)) => {
+        let event = rx.recv_timeout(wait).map(|ev| remap_fs_event(ev, remaps));
+        let removed = matches!(event, Ok(mac::fsevent_watcher::FsEvent::Removed(_)));
+        match event {
+            Ok(
+                mac::fsevent_watcher::FsEvent::Paths(paths)
+                | mac::fsevent_watcher::FsEvent::Removed(paths),
+            ) => {
                 let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(&state);
+                let hot_dirs = state.hot_dirs.lock().clone();
                 let prev_len = pending_paths.len();
                 for path in paths {
+                    if path == state.pathpriority_file_path {
+                        *state.hot_dirs.lock() =
+                            pathpriority::load_hot_dirs(&state.pathpriority_file_path, &state.home_dir);
+                        continue;
+                    }
                     if path == state.config_file_path {
-                        let new_entries = pathignore_active_entries(
-                            &fs::read_to_string(&state.config_file_path).unwrap_or_default(),
-                        );
-                        if new_entries != last_config_entries {
-                            last_config_entries = new_entries;
-                            if let Some(app) = app {
-                                app.emit("pathignore_changed", ()).ok();
-                            }
-                        }
+                        reload_pathignore(app, state);
                         continue;
                     }
                     if path == state.pathindexing_file_path {
@@ -4381,13 +5462,30 @@ fn run_fsevent_stream(
                         continue;
                     }
                     if !should_skip_path(&path, &ignored_roots, &ignored_patterns) {
+                        hot_pending |= pathpriority::is_hot_path(&path, &hot_dirs);
+                        // The latest event for a path decides whether it
+                        // still needs a stat.
+                        if removed {
+                            hints.removed.insert(path.clone());
+                        } else {
+                            hints.removed.remove(&path);
+                        }
                         pending_paths.insert(path);
                     }
                 }
                 if pending_paths.len() > prev_len {
-                    deadline = Some(Instant::now() + WATCH_DEBOUNCE);
+                    let debounce = if hot_pending {
+                        HOT_WATCH_DEBOUNCE
+                    } else {
+                        state.index_tuning.read().watch_debounce()
+                    };
+                    deadline = Some(Instant::now() + debounce);
                 }
             }
+            Ok(mac::fsevent_watcher::FsEvent::Renames(pairs)) => {
+                hints.renames.extend(pairs);
+                deadline = Some(Instant::now() + state.index_tuning.read().watch_debounce());
+            }
             Ok(mac::fsevent_watcher::FsEvent::MustScanSubDirs(path)) => {
                 must_scan_count += 1;
                 if replay_phase
@@ -4418,6 +5516,7 @@ fn run_fsevent_stream(
                     app,
                     &state,
                     &mut pending_paths,
+                    &mut hints,
                     &mut deadline,
                     &mut last_status_emit,
                     &mut pending_status_emit,
@@ -4445,6 +5544,7 @@ fn run_fsevent_stream(
                     app,
                     &state,
                     &mut pending_paths,
+                    &mut hints,
                     &mut deadline,
                     &mut last_status_emit,
                     &mut pending_status_emit,
@@ -4457,6 +5557,9 @@ fn run_fsevent_stream(
             last_status_emit = Instant::now();
             pending_status_emit = false;
         }
+        state
+            .watcher_backlog
+            .store(pending_paths.len() + hints.renames.len(), AtomicOrdering::Relaxed);
 
         // Hold rescans until replay finishes: during replay the MustScanSubDirs
         // count decides whether to escalate to a full scan, and a rescan
@@ -4490,10 +5593,14 @@ fn run_fsevent_stream(
         app,
         state,
         &mut pending_paths,
+        &mut hints,
         &mut deadline,
         &mut last_status_emit,
         &mut pending_status_emit,
     );
+    state
+        .watcher_backlog
+        .store(pending_paths.len() + hints.renames.len(), AtomicOrdering::Relaxed);
 
     if rebuild_requested && !state.watcher_stop.load(AtomicOrdering::Acquire) {
         WatcherExit::Rebuild
@@ -4502,7 +5609,7 @@ fn run_fsevent_stream(
     }
 }
 
-fn validate_new_name(new_name: &str) -> AppResult<String> {
+pub(crate) fn validate_new_name(new_name: &str) -> AppResult<String> {
     let trimmed = new_name.trim();
 
     if trimmed.is_empty() {
@@ -4518,7 +5625,7 @@ fn validate_new_name(new_name: &str) -> AppResult<String> {
     Ok(trimmed.to_string())
 }
 
-fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<EntryDto> {
+pub(crate) fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<EntryDto> {
     Ok(EntryDto {
         path: row.get(0)?,
         name: row.get(1)?,
@@ -4527,6 +5634,10 @@ fn row_to_entry(row: &rusqlite::Row<'_>) -> rusqlite::Result<EntryDto> {
         ext: row.get(4)?,
         size: row.get(5)?,
         mtime: row.get(6)?,
+        spoken_label: None,
+        mtime_display: None,
+        cloud_only: (row.get::<_, i64>(7)? != 0).then_some(true),
+        linked_from: None,
     })
 }
 
@@ -4637,7 +5748,12 @@ fn load_system_icon_png(ext: &str) -> Option<Vec<u8>> {
     win::icon::load_icon_png_by_ext(ext)
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+#[cfg(target_os = "linux")]
+fn load_system_icon_png(ext: &str) -> Option<Vec<u8>> {
+    linux::icon::load_icon_png_by_ext(ext)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 fn load_system_icon_png(_ext: &str) -> Option<Vec<u8>> {
     None
 }
@@ -4660,6 +5776,11 @@ fn is_per_file_icon_ext(_ext: &str) -> bool {
 
 #[cfg(target_os = "windows")]
 fn load_icon_from_path(path: &str, _ext: &str) -> Option<Vec<u8>> {
+    // Extracting an embedded icon reads file content, which would hydrate a
+    // cloud placeholder; fall back to the per-extension icon instead.
+    if win::cloud::is_placeholder_path(Path::new(path)) {
+        return None;
+    }
     win::icon::load_icon_png(path)
 }
 
@@ -4677,9 +5798,8 @@ fn load_icon_from_path(_path: &str, _ext: &str) -> Option<Vec<u8>> {
     None
 }
 
-#[tauri::command]
-fn get_index_status(state: State<'_, AppState>) -> IndexStatusDto {
-    let started = Instant::now();
+/// The status the frontend shows; also served by the HTTP API's `/status`.
+fn index_status_snapshot(state: &AppState) -> IndexStatusDto {
     let snapshot = state.status.lock().clone();
     let snapshot_state = snapshot.state.as_str().to_string();
     let db_ready = state.db_ready.load(AtomicOrdering::Acquire);
@@ -4703,11 +5823,11 @@ fn get_index_status(state: State<'_, AppState>) -> IndexStatusDto {
         indexed: snapshot.indexed,
         current_path: snapshot.current_path,
         background_active: indexing_active,
+        paused: state.indexing_paused.load(AtomicOrdering::Acquire),
     };
     if cfg!(debug_assertions) {
         eprintln!(
-            "[rpc/get_index_status] elapsed={}ms state={} snapshot_state={} db_ready={} indexing_active={} entries={} scanned={} indexed={}",
-            started.elapsed().as_millis(),
+            "[rpc/get_index_status] state={} snapshot_state={} db_ready={} indexing_active={} entries={} scanned={} indexed={}",
             dto.state,
             snapshot_state,
             db_ready,
@@ -4720,6 +5840,116 @@ fn get_index_status(state: State<'_, AppState>) -> IndexStatusDto {
     dto
 }
 
+#[tauri::command]
+fn get_index_status(state: State<'_, AppState>) -> IndexStatusDto {
+    let started = Instant::now();
+    let dto = index_status_snapshot(&state);
+    if cfg!(debug_assertions) {
+        eprintln!("[rpc/get_index_status] elapsed={}ms", started.elapsed().as_millis());
+    }
+    dto
+}
+
+/// Paths the last index run could not read, with the OS error for each.
+#[tauri::command]
+fn get_permission_errors(state: State<'_, AppState>) -> permission_log::PermissionErrorReport {
+    state.permission_log.report()
+}
+
+fn http_api_status(state: &AppState, config: &http_api::HttpApiConfig) -> http_api::HttpApiStatusDto {
+    let running_port = state.http_server.lock().as_ref().map(|server| server.port());
+    http_api::HttpApiStatusDto {
+        enabled: config.enabled,
+        running: running_port.is_some(),
+        port: config.port,
+        allow_lan: config.allow_lan,
+        token: config.token.clone(),
+        url: running_port.map(|port| format!("http://127.0.0.1:{port}/")),
+    }
+}
+
+#[tauri::command]
+fn get_http_api_status(state: State<'_, AppState>) -> http_api::HttpApiStatusDto {
+    http_api_status(&state, &http_api::load_config(&state.data_dir))
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsChangedEvent {
+    key: &'static str,
+}
+
+/// Announce a saved setting as `settings_changed` once whatever depends on
+/// it has been re-derived, so open views can re-read it without a restart.
+/// `key` is `indexTuning`, `httpApi`, `launchAtLogin`, `pathignore` or
+/// `scanRoots`.
+pub(crate) fn emit_settings_changed(app: Option<&AppHandle>, key: &'static str) {
+    if let Some(app) = app {
+        let _ = app.emit("settings_changed", SettingsChangedEvent { key });
+    }
+}
+
+/// Save the HTTP API settings and start, restart or stop the server to
+/// match. A token is generated the first time it is enabled.
+#[tauri::command]
+async fn set_http_api_config(
+    enabled: bool,
+    port: Option<u16>,
+    allow_lan: Option<bool>,
+    regenerate_token: Option<bool>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<http_api::HttpApiStatusDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut config = http_api::load_config(&state.data_dir);
+        config.enabled = enabled;
+        if let Some(port) = port {
+            config.port = port;
+        }
+        if let Some(allow_lan) = allow_lan {
+            config.allow_lan = allow_lan;
+        }
+        if config.token.is_empty() || regenerate_token.unwrap_or(false) {
+            config.token = http_api::generate_token()?;
+        }
+        http_api::save_config(&state.data_dir, &config)?;
+
+        let mut server = state.http_server.lock();
+        if let Some(running) = server.take() {
+            running.stop();
+        }
+        if config.enabled {
+            *server = Some(http_api::start(state.clone(), &config)?);
+        }
+        drop(server);
+        emit_settings_changed(Some(&app), "httpApi");
+        Ok(http_api_status(&state, &config))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The saved indexing knobs (see `index_tuning`).
+#[tauri::command]
+fn get_index_tuning(state: State<'_, AppState>) -> index_tuning::IndexTuning {
+    index_tuning::load(&state.data_dir)
+}
+
+/// Validate, save and apply the indexing knobs. Watchers pick up the new
+/// debounce on their next batch; scan sizes apply from the next index run.
+#[tauri::command]
+fn set_index_tuning(
+    tuning: index_tuning::IndexTuning,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<index_tuning::IndexTuning> {
+    index_tuning::save(&state.data_dir, &tuning)?;
+    let applied = index_tuning::apply(&state);
+    emit_settings_changed(Some(&app), "indexTuning");
+    Ok(applied)
+}
+
 #[tauri::command]
 fn get_home_dir(state: State<'_, AppState>) -> String {
     state.home_dir.to_string_lossy().to_string()
@@ -4747,651 +5977,1017 @@ fn open_privacy_settings() {
     }
 }
 
-#[tauri::command]
-fn open_pathignore(state: State<'_, AppState>) -> AppResult<()> {
-    let path = &state.config_file_path;
-    ensure_pathignore_exists(path)?;
-    #[cfg(target_os = "macos")]
-    Command::new("open").arg(path).spawn().map_err(|e| e.to_string())?;
-    #[cfg(target_os = "windows")]
-    Command::new("cmd")
-        .args(["/C", "start", "", &path.to_string_lossy().to_string()])
-        .spawn()
-        .map_err(|e| e.to_string())?;
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    let _ = path;
-    Ok(())
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IgnoreRuleChangeDto {
+    rule: String,
+    /// "root" for a path, "pattern" for a glob.
+    kind: String,
+    /// False when the rule was already present (add) or absent (remove).
+    changed: bool,
+    /// Rows deleted because the new rule excludes them.
+    purged: usize,
+    /// Rows written back by rescanning what a removed rule had excluded.
+    reindexed: usize,
+}
+
+enum IgnoreRule {
+    Root(PathBuf),
+    Pattern(IgnorePattern),
+}
+
+/// Parse a rule in `.pathignore` syntax. Relative rules would resolve
+/// against the app data dir, so only `~`, absolute and `**/` forms are taken.
+fn parse_ignore_rule(rule: &str, state: &AppState) -> AppResult<IgnoreRule> {
+    let anchored = rule.starts_with('~')
+        || rule.starts_with("**/")
+        || rule.starts_with('/')
+        || Path::new(rule).is_absolute();
+    if rule.is_empty() || !anchored {
+        return Err("Ignore rule must be an absolute path, ~/path or **/name.".to_string());
+    }
+    pathignore_entry_rule(rule, state).ok_or_else(|| format!("Invalid ignore rule: {rule}"))
+}
+
+/// The rule an active `.pathignore` line stands for, resolved against the
+/// app data dir like `load_pathignore_rules` does.
+fn pathignore_entry_rule(entry: &str, state: &AppState) -> Option<IgnoreRule> {
+    let base_dir = state
+        .config_file_path
+        .parent()
+        .unwrap_or_else(|| Path::new("/"));
+    if contains_glob_meta(entry) {
+        parse_ignore_pattern(entry, base_dir, &state.home_dir).map(IgnoreRule::Pattern)
+    } else {
+        resolve_ignore_path(entry, base_dir, &state.home_dir).map(IgnoreRule::Root)
+    }
 }
 
-#[tauri::command]
-fn open_pathindexing(state: State<'_, AppState>) -> AppResult<()> {
-    pathindexing::open_pathindexing_file(&state.pathindexing_file_path)
+/// Drop the cached merged rules so the next lookup re-reads `.pathignore`
+/// even when its mtime did not visibly change.
+fn invalidate_ignore_rules(state: &AppState) {
+    state.ignore_cache.lock().take();
+    invalidate_search_caches(state);
 }
 
-#[tauri::command]
-fn restart_app(app: AppHandle) {
-    app.restart();
+/// Indexed roots a removed rule may have hidden rows under.
+fn ignore_rule_rescan_scope(state: &AppState, rule: &IgnoreRule) -> Vec<PathBuf> {
+    let anchor = match rule {
+        IgnoreRule::Root(root) => Some(root.clone()),
+        IgnoreRule::Pattern(IgnorePattern::Glob(glob)) => glob_literal_dir(glob).map(PathBuf::from),
+        IgnoreRule::Pattern(IgnorePattern::AnySegment { .. }) => None,
+    };
+    let mut roots = vec![state.scan_root.clone()];
+    roots.extend(
+        state
+            .extra_roots
+            .lock()
+            .iter()
+            .filter(|r| !r.starts_with(&state.scan_root))
+            .cloned(),
+    );
+    match anchor {
+        Some(anchor) if roots.iter().any(|r| anchor.starts_with(r)) => vec![anchor],
+        // Above the indexed roots: everything under them may be affected.
+        Some(anchor) => roots.into_iter().filter(|r| r.starts_with(&anchor)).collect(),
+        None => roots,
+    }
 }
 
-#[tauri::command]
-fn start_full_index(app: AppHandle, state: State<'_, AppState>) -> AppResult<()> {
-    #[cfg(target_os = "windows")]
-    {
-        win::start_windows_indexing(app, state.inner().clone());
-        Ok(())
+/// Bring the index in line with rules just added to or removed from
+/// `.pathignore`: drop the rows an added rule excludes, then rescan what a
+/// removed one had hidden. Returns (purged, reindexed).
+fn apply_ignore_rule_changes(
+    state: &AppState,
+    conn: &mut Connection,
+    added: &[IgnoreRule],
+    removed: &[IgnoreRule],
+) -> AppResult<(usize, usize)> {
+    let mut purged = 0;
+    for rule in added {
+        purged += match rule {
+            IgnoreRule::Root(root) => delete_paths(conn, &[root.to_string_lossy().to_string()])?,
+            IgnoreRule::Pattern(pattern) => purge_pattern_entries(conn, pattern)?,
+        };
     }
-    #[cfg(not(target_os = "windows"))]
-    {
-        start_full_index_worker(app, state.inner().clone())
+    let mut scopes: Vec<PathBuf> = removed
+        .iter()
+        .flat_map(|rule| ignore_rule_rescan_scope(state, rule))
+        .collect();
+    // Sorted, a scope under another one follows it; the wider rescan covers it.
+    scopes.sort();
+    scopes.dedup_by(|scope, wider| scope.starts_with(wider));
+    let mut reindexed = 0;
+    if !scopes.is_empty() {
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+        for root in scopes {
+            if root.is_dir() && !should_skip_path(&root, &ignored_roots, &ignored_patterns) {
+                let (upserted, _) =
+                    rescan::rescan_subtree(conn, &root, &ignored_roots, &ignored_patterns)?;
+                reindexed += upserted;
+            }
+        }
     }
+    Ok((purged, reindexed))
 }
 
-#[tauri::command]
-async fn reset_index(app: AppHandle, state: State<'_, AppState>) -> AppResult<()> {
-    let state = state.inner().clone();
-    tauri::async_runtime::spawn_blocking(move || {
-        if state.indexing_active.load(AtomicOrdering::Acquire) {
-            return Err("Cannot reset while indexing is in progress.".to_string());
-        }
-
-        // Stop existing file watcher and wait for it to fully exit
-        state.watcher_stop.store(true, AtomicOrdering::Release);
-        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
-        while state.watcher_active.load(AtomicOrdering::Acquire) {
-            if std::time::Instant::now() >= deadline {
-                eprintln!("[reset] watcher did not stop within 5s, proceeding anyway");
-                break;
+fn change_ignore_rule_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    rule: &str,
+    add: bool,
+) -> AppResult<IgnoreRuleChangeDto> {
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        return Err("Index is not ready yet.".to_string());
+    }
+    let rule = rule.trim();
+    let parsed = parse_ignore_rule(rule, state)?;
+    if state
+        .indexing_active
+        .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+        .is_err()
+    {
+        return Err("Cannot change ignore rules while indexing is in progress.".to_string());
+    }
+    let result: AppResult<IgnoreRuleChangeDto> = (|| {
+        let changed = if add {
+            append_pathignore_rule(&state.config_file_path, rule)?
+        } else {
+            remove_pathignore_rule(&state.config_file_path, rule)?
+        };
+        let mut dto = IgnoreRuleChangeDto {
+            rule: rule.to_string(),
+            kind: match parsed {
+                IgnoreRule::Root(_) => "root",
+                IgnoreRule::Pattern(_) => "pattern",
             }
-            std::thread::sleep(std::time::Duration::from_millis(50));
+            .to_string(),
+            changed,
+            purged: 0,
+            reindexed: 0,
+        };
+        if !changed {
+            return Ok(dto);
         }
-
-        let conn = db_connection(&state.db_path)?;
-
-        // Fast reset: rename entries (O(1), no data movement) → create new empty table
-        // → FTS rebuild from empty content table (instant) → background DROP old table.
-        // This avoids per-row WAL writes that make DELETE FROM entries slow at scale.
-        let _ = set_meta(&conn, "fts_dirty", "1");
-        let _ = conn.execute_batch(DROP_FTS_TRIGGERS_SQL);
-        let _ = conn.execute_batch("DROP TABLE IF EXISTS entries_gc_reset;");
-        conn.execute_batch("ALTER TABLE entries RENAME TO entries_gc_reset;")
-            .map_err(|e| e.to_string())?;
-        conn.execute_batch(CREATE_ENTRIES_TABLE_SQL).map_err(|e| e.to_string())?;
-        conn.execute_batch(CREATE_FTS_TRIGGERS_SQL).map_err(|e| e.to_string())?;
-        let _ = conn.execute_batch(REBUILD_FTS_SQL);
-
-        // entries_gc_reset will be dropped by the GC cleanup in the finalizing thread
-        // after indexing completes — avoids a race between DROP TABLE and the new indexer
-        // both competing for the SQLite WAL write lock.
-
-        // Clears every meta row (last_run_id, cached counts, and the fts_dirty=1
-        // set above) so the follow-up index runs fresh with a clean FTS.
-        conn.execute("DELETE FROM meta", [])
-            .map_err(|e| e.to_string())?;
-
+        // Applied here, so the watchers seeing the write have nothing to reload.
+        *state.applied_pathignore.lock() = pathignore_active_entries(
+            &fs::read_to_string(&state.config_file_path).unwrap_or_default(),
+        );
+        invalidate_ignore_rules(state);
+        let mut conn = db_connection(&state.db_path)?;
+        let rules = [parsed];
+        let (added, removed) = if add { (&rules[..], &[][..]) } else { (&[][..], &rules[..]) };
+        (dto.purged, dto.reindexed) = apply_ignore_rule_changes(state, &mut conn, added, removed)?;
+        invalidate_search_caches(state);
+        Ok(dto)
+    })();
+    state.indexing_active.store(false, AtomicOrdering::Release);
+    let dto = result?;
+    if dto.changed {
+        mem_resident::reload(state);
+        refresh_and_emit_status_counts(app, state)?;
+        emit_settings_changed(app, "pathignore");
+    }
+    Ok(dto)
+}
+
+/// Apply an edit of `.pathignore` made outside the app; watchers call this
+/// when they see the file change. Waits out a running index, then purges
+/// rows under newly ignored paths and rescans what dropped rules had hidden,
+/// so the new rules hold without a restart. Emits `pathignore_changed` and
+/// `settings_changed` once applied.
+pub(crate) fn reload_pathignore(app: Option<&AppHandle>, state: &AppState) {
+    let current =
+        pathignore_active_entries(&fs::read_to_string(&state.config_file_path).unwrap_or_default());
+    if *state.applied_pathignore.lock() == current {
+        return;
+    }
+    let (app, state) = (app.cloned(), state.clone());
+    std::thread::spawn(move || {
+        while state
+            .indexing_active
+            .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+            .is_err()
         {
-            let mut status = state.status.lock();
-            status.state = IndexState::Indexing;
-            status.entries_count = 0;
-            status.last_updated = None;
-            status.permission_errors = 0;
-            status.message = None;
-            status.scanned = 0;
-            status.indexed = 0;
-            status.current_path.clear();
+            std::thread::sleep(Duration::from_secs(1));
         }
+        let result = reload_pathignore_locked(&state);
+        state.indexing_active.store(false, AtomicOrdering::Release);
+        match result {
+            Ok(Some((purged, reindexed))) => {
+                eprintln!("[pathignore] reloaded: purged={purged} reindexed={reindexed}");
+                mem_resident::reload(&state);
+                let _ = refresh_and_emit_status_counts(app.as_ref(), &state);
+                if let Some(app) = &app {
+                    app.emit("pathignore_changed", ()).ok();
+                }
+                emit_settings_changed(app.as_ref(), "pathignore");
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("[pathignore] reload failed: {e}"),
+        }
+    });
+}
 
-        invalidate_search_caches(&state);
-        // Schema was swapped (entries renamed + recreated): drop pooled search
-        // connections so nothing holds statements against the old table.
-        state.search_conn_pool.lock().clear();
-
-        emit_index_state(&app, "Indexing", None);
-        emit_index_updated(&app, 0, now_epoch(), 0);
+/// `reload_pathignore` under the indexing guard. `None` when another reload
+/// or an ignore command already applied the file as it is now.
+fn reload_pathignore_locked(state: &AppState) -> AppResult<Option<(usize, usize)>> {
+    let current =
+        pathignore_active_entries(&fs::read_to_string(&state.config_file_path).unwrap_or_default());
+    let previous = state.applied_pathignore.lock().clone();
+    if previous == current {
+        return Ok(None);
+    }
+    let rules = |entries: &[String], other: &[String]| -> Vec<IgnoreRule> {
+        entries
+            .iter()
+            .filter(|entry| !other.contains(entry))
+            .filter_map(|entry| pathignore_entry_rule(entry, state))
+            .collect()
+    };
+    let (added, removed) = (rules(&current, &previous), rules(&previous, &current));
+    let _writing = state.db_writes.enter()?;
+    invalidate_ignore_rules(state);
+    let mut conn = db_connection(&state.db_path)?;
+    let counts = apply_ignore_rule_changes(state, &mut conn, &added, &removed)?;
+    *state.applied_pathignore.lock() = current;
+    invalidate_search_caches(state);
+    Ok(Some(counts))
+}
 
-        // Allow new watcher to start
-        state.watcher_stop.store(false, AtomicOrdering::Release);
+/// Add an ignore root or glob to the app's `.pathignore` and drop the rows
+/// it now excludes.
+#[tauri::command]
+async fn add_ignore_rule(
+    rule: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<IgnoreRuleChangeDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        change_ignore_rule_blocking(Some(&app), &state, &rule, true)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        #[cfg(target_os = "windows")]
-        {
-            win::start_windows_indexing(app, state);
-            Ok(())
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            start_full_index_worker(app.clone(), state.clone())?;
-            start_fsevent_watcher_worker(Some(app), state, None, false);
-            Ok(())
-        }
+/// Remove a rule from the app's `.pathignore` and index what it had hidden.
+#[tauri::command]
+async fn remove_ignore_rule(
+    rule: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<IgnoreRuleChangeDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        change_ignore_rule_blocking(Some(&app), &state, &rule, false)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
-fn log_search(db_path: &Path, query: &str, mode: &str, results: &[EntryDto]) {
-    if !search_log_enabled() {
-        return;
+fn exclude_folder_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    path: &str,
+) -> AppResult<IgnoreRuleChangeDto> {
+    let folder = PathBuf::from(path.trim());
+    if !folder.is_absolute() || !folder.is_dir() {
+        return Err(format!("Not a folder: {path}"));
+    }
+    if folder == state.scan_root {
+        return Err("Cannot exclude the scan root.".to_string());
     }
+    // The watchers read the same merged rules, so they stop indexing the
+    // folder on their next batch.
+    change_ignore_rule_blocking(app, state, &folder.to_string_lossy(), true)
+}
 
-    let log_path = db_path.with_file_name("search.log");
-    let Ok(mut f) = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_path)
-    else {
-        return;
-    };
-    let top: Vec<&str> = results.iter().take(10).map(|e| e.name.as_str()).collect();
-    let _ = writeln!(
-        f,
-        "[{}] Q={:?} MODE={} COUNT={} TOP={:?}",
-        now_epoch(),
-        query,
-        mode,
-        results.len(),
-        top,
-    );
+/// Context-menu "Exclude folder": ignore the folder from now on, drop its
+/// rows and keep the watchers from re-adding them.
+#[tauri::command]
+async fn exclude_folder(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<IgnoreRuleChangeDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || exclude_folder_blocking(Some(&app), &state, &path))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
-fn search_log_enabled() -> bool {
-    *SEARCH_LOG_ENABLED.get_or_init(|| env_truthy("FASTFIND_SEARCH_LOG"))
+/// Every rule that can exclude a path from the index, with its source.
+#[tauri::command]
+fn list_ignore_rules(state: State<'_, AppState>) -> Vec<ignore_list::IgnoreRuleInfoDto> {
+    ignore_list::list_ignore_rules(
+        &state.config_file_path,
+        &state.home_dir,
+        &state.cwd,
+        &fixed_ignore_roots(&state),
+    )
 }
 
-/// Returns the total number of entries matching `query` without LIMIT/OFFSET.
-/// Returns `None` when the total is unknown or intentionally skipped.
-fn compute_total_count(state: &AppState, execution: &SearchExecution) -> Option<u32> {
-    let home_dir = &state.home_dir;
-    // Read once here, as `execute_search` does independently — the count path
-    // and the result path each snapshot `fts_ready` after the search runs.
-    let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
-    // When fewer results than the limit were returned the total is exact.
-    if (execution.results.len() as u32) < execution.effective_limit {
-        return Some(
-            execution
-                .offset
-                .saturating_add(execution.results.len() as u32),
-        );
+#[tauri::command]
+fn open_pathignore(state: State<'_, AppState>) -> AppResult<()> {
+    let path = &state.config_file_path;
+    ensure_pathignore_exists(path)?;
+    #[cfg(target_os = "macos")]
+    Command::new("open").arg(path).spawn().map_err(|e| e.to_string())?;
+    #[cfg(target_os = "windows")]
+    win::shell_open::open_path(&path.to_string_lossy())?;
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = path;
+    Ok(())
+}
+
+#[tauri::command]
+fn open_pathindexing(state: State<'_, AppState>) -> AppResult<()> {
+    pathindexing::open_pathindexing_file(&state.pathindexing_file_path)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanRootsDto {
+    scan_root: String,
+    extra_roots: Vec<String>,
+}
+
+/// Signal the live watcher to stop and wait up to 5s for its loop to exit.
+/// Returns false on timeout.
+fn stop_watcher_and_wait(state: &AppState) -> bool {
+    state.watcher_stop.store(true, AtomicOrdering::Release);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while state.watcher_active.load(AtomicOrdering::Acquire) {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
     }
-    // For paginated pages after the first we leave total tracking to the frontend.
-    if execution.offset > 0 {
-        return None;
+    true
+}
+
+/// Start the platform watcher over the current roots, after
+/// `stop_watcher_and_wait`. FSEvents resumes from the persisted event id so
+/// nothing from the gap is lost; fanotify/inotify on Linux start from now.
+fn restart_live_watcher(app: &AppHandle, state: &AppState) {
+    state.watcher_stop.store(false, AtomicOrdering::Release);
+    #[cfg(target_os = "macos")]
+    {
+        let since_event_id = db_connection(&state.db_path)
+            .ok()
+            .and_then(|c| get_meta(&c, "last_event_id"))
+            .and_then(|v| v.parse::<u64>().ok());
+        start_fsevent_watcher_worker(Some(app.clone()), state.clone(), since_event_id, false);
     }
-    // Non-SQL fast paths already return all matching entries.
-    if execution.mode_label.starts_with("mem_")
-        || execution.mode_label == "spotlight"
-        || execution.mode_label == "spotlight_timeout"
-        || execution.mode_label == "find_fallback"
-        || execution.mode_label == "name_neg_cache"
+    #[cfg(target_os = "windows")]
+    win::restart_watcher(app.clone(), state.clone());
+    #[cfg(target_os = "linux")]
+    linux::watcher::start(app.clone(), state.clone());
+}
+
+/// Pause or resume live updates. Pausing stops the watcher; resuming
+/// restarts it, replaying changes made in between.
+pub(crate) fn set_indexing_paused_blocking(
+    app: &AppHandle,
+    state: &AppState,
+    paused: bool,
+) -> AppResult<()> {
+    if state.indexing_paused.load(AtomicOrdering::Acquire) == paused {
+        return Ok(());
+    }
+    if paused {
+        if state.indexing_active.load(AtomicOrdering::Acquire) {
+            return Err("Cannot pause while indexing is in progress.".to_string());
+        }
+        if !stop_watcher_and_wait(state) {
+            eprintln!("[pause] watcher did not stop within 5s");
+        }
+        state.indexing_paused.store(true, AtomicOrdering::Release);
+    } else {
+        state.indexing_paused.store(false, AtomicOrdering::Release);
+        restart_live_watcher(app, state);
+    }
+    let _ = app.emit("indexing_paused", paused);
+    Ok(())
+}
+
+#[tauri::command]
+async fn set_indexing_paused(
+    paused: bool,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || set_indexing_paused_blocking(&app, &state, paused))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn set_scan_roots_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    roots: &[String],
+) -> AppResult<ScanRootsDto> {
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        return Err("Index is not ready yet.".to_string());
+    }
+    let new_roots = pathindexing::normalize_scan_roots(roots)?;
+    if state.indexing_active.load(AtomicOrdering::Acquire) {
+        return Err("Cannot change scan roots while indexing is in progress.".to_string());
+    }
+    let _writing = state.db_writes.enter()?;
+    if state
+        .pathindexing_active
+        .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+        .is_err()
     {
-        return Some(execution.results.len() as u32);
+        return Err("Scan roots are already being updated.".to_string());
     }
-    let Ok(conn) = pooled_search_connection(state) else {
-        return None;
-    };
-    // Counting matches from the trigram postings alone: joining entries just to
-    // count doubles the work, and the FTS index is authoritative while in sync.
-    let fts_only_count = |query: &str| -> u32 {
-        conn.query_row(
-            "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH ?1",
-            params![fts_phrase(query)],
-            |r| r.get(0),
-        )
-        .unwrap_or(0)
-    };
-    let mode = parse_query(&execution.query);
-    let total = match mode {
-        SearchMode::Empty => conn
-            .query_row("SELECT COUNT(*) FROM entries", [], |r| r.get(0))
-            .unwrap_or(0),
-        SearchMode::NameSearch { name_like } => {
-            let fts_ok = fts_ready && execution.query.chars().count() >= 3;
-            if execution.sort_by != "name" && fts_ok {
-                fts_only_count(&execution.query)
-            } else {
-                let escaped = escape_like(&execution.query);
-                let prefix_like = format!("{}%", escaped);
-                let prefix_count: u32 = conn
-                    .query_row(
-                        "SELECT COUNT(*) FROM entries WHERE name LIKE ?1 ESCAPE '\\'",
-                        params![prefix_like],
-                        |r| r.get(0),
-                    )
-                    .unwrap_or(0);
-                if prefix_count > 0 {
-                    prefix_count
-                } else if fts_ok {
-                    // Phase-2 contains fallback was served by FTS.
-                    fts_only_count(&execution.query)
-                } else {
-                    // Phase-2 contains fallback was used.
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM entries WHERE name LIKE ?1 ESCAPE '\\'",
-                        params![name_like],
-                        |r| r.get(0),
-                    )
-                    .unwrap_or(0)
-                }
-            }
+
+    let result = (|| {
+        let old_roots = state.extra_roots.lock().clone();
+        // Stop the watcher before touching .pathindexing so it does not pick
+        // up the edit and start its own scan of the new roots.
+        let restart = app.filter(|_| state.watcher_active.load(AtomicOrdering::Acquire));
+        if restart.is_some() && !stop_watcher_and_wait(state) {
+            eprintln!("[scan_roots] watcher did not stop within 5s, proceeding anyway");
         }
-        SearchMode::GlobName { name_like } => {
-            let fts_prefilter = glob_fts_prefilter(fts_ready, &name_like, &execution.query);
-            if let Some(match_expr) = fts_prefilter {
-                conn.query_row(
-                    "SELECT COUNT(*) FROM entries_fts f JOIN entries e ON e.id = f.rowid \
-                     WHERE entries_fts MATCH ?1 AND e.name LIKE ?2 ESCAPE '\\'",
-                    params![match_expr, name_like],
-                    |r| r.get(0),
-                )
-                .unwrap_or(0)
-            } else {
-                conn.query_row(
-                    "SELECT COUNT(*) FROM entries WHERE name LIKE ?1 ESCAPE '\\'",
-                    params![name_like],
-                    |r| r.get(0),
-                )
-                .unwrap_or(0)
-            }
+        let written = pathindexing::write_pathindexing_roots(&state.pathindexing_file_path, &new_roots);
+        if written.is_ok() {
+            *state.extra_roots.lock() = new_roots.clone();
         }
-        SearchMode::ExtSearch { ext, .. } => conn
-            .query_row(
-                "SELECT COUNT(*) FROM entries WHERE ext = ?1",
-                params![ext],
-                |r| r.get(0),
-            )
-            .unwrap_or(0),
-        SearchMode::PathSearch {
-            name_like,
-            dir_hint,
-            ..
-        } => {
-            let resolved_dirs = resolve_dir_hint(home_dir, &dir_hint)
-                .map(|p| vec![p.to_string_lossy().to_string()])
-                .unwrap_or_default();
-            let resolved_dirs = if resolved_dirs.is_empty() {
-                resolve_dirs_from_db(&conn, &dir_hint)
-            } else {
-                resolved_dirs
-            };
+        if let Some(app) = restart {
+            restart_live_watcher(app, state);
+        }
+        written?;
 
-            if !resolved_dirs.is_empty() {
-                let mut sql_params: Vec<SqlValue> = Vec::new();
-                let mut dir_conditions = Vec::new();
-                let sep = std::path::MAIN_SEPARATOR;
-                for d in &resolved_dirs {
-                    let i = sql_params.len();
-                    let pfx = format!("{d}{sep}");
-                    let pfx_end = format!("{d}\x7F");
-                    sql_params.push(SqlValue::Text(d.clone()));
-                    sql_params.push(SqlValue::Text(pfx));
-                    sql_params.push(SqlValue::Text(pfx_end));
-                    dir_conditions.push(format!(
-                        "(e.dir = ?{} OR (e.dir >= ?{} AND e.dir < ?{}))",
-                        i + 1,
-                        i + 2,
-                        i + 3
-                    ));
-                }
-                let dir_where = dir_conditions.join(" OR ");
-                let name_filter = if name_like == "%" {
-                    String::new()
-                } else {
-                    let ext_shortcut = extract_ext_from_like(&name_like);
-                    if let Some(ext_val) = ext_shortcut {
-                        let i = sql_params.len();
-                        sql_params.push(SqlValue::Text(ext_val));
-                        format!(" AND e.ext = ?{}", i + 1)
-                    } else {
-                        let i = sql_params.len();
-                        sql_params.push(SqlValue::Text(name_like.clone()));
-                        format!(" AND e.name LIKE ?{} ESCAPE '\\'", i + 1)
-                    }
-                };
-                let sql =
-                    format!("SELECT COUNT(*) FROM entries e WHERE ({dir_where}){name_filter}");
-                conn.query_row(&sql, params_from_iter(sql_params.iter()), |r| r.get(0))
-                    .unwrap_or(0)
-            } else {
-                let sep = std::path::MAIN_SEPARATOR;
-                let native_hint = dir_hint.replace('/', &sep.to_string());
-                let escaped_sep = escape_like(&sep.to_string());
-                let dir_suffix = escape_like(&native_hint);
-                let dir_like_exact = format!("%{escaped_sep}{dir_suffix}");
-                let dir_like_sub = format!("%{escaped_sep}{dir_suffix}{escaped_sep}%");
-                let ext_shortcut = extract_ext_from_like(&name_like);
-                if let Some(ext_val) = ext_shortcut {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM entries e WHERE e.ext = ?1 AND (e.dir LIKE ?2 ESCAPE '\\' OR e.dir LIKE ?3 ESCAPE '\\')",
-                        params![ext_val, dir_like_exact, dir_like_sub],
-                        |r| r.get(0),
-                    )
-                    .unwrap_or(0)
-                } else if name_like == "%" {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM entries e WHERE e.dir LIKE ?1 ESCAPE '\\' OR e.dir LIKE ?2 ESCAPE '\\'",
-                        params![dir_like_exact, dir_like_sub],
-                        |r| r.get(0),
-                    )
-                    .unwrap_or(0)
-                } else {
-                    conn.query_row(
-                        "SELECT COUNT(*) FROM entries e WHERE (e.dir LIKE ?1 ESCAPE '\\' OR e.dir LIKE ?2 ESCAPE '\\') AND e.name LIKE ?3 ESCAPE '\\'",
-                        params![dir_like_exact, dir_like_sub, name_like],
-                        |r| r.get(0),
-                    )
-                    .unwrap_or(0)
-                }
-            }
+        set_state(state, IndexState::Indexing, None);
+        if let Some(app) = app {
+            emit_index_state(app, "Indexing", None);
         }
-    };
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+        let changed = pathindexing::handle_pathindexing_change(
+            state,
+            &old_roots,
+            &new_roots,
+            &ignored_roots,
+            &ignored_patterns,
+        );
+        let extra_roots: Vec<String> =
+            new_roots.iter().map(|r| r.to_string_lossy().to_string()).collect();
+        if changed.is_ok() {
+            let conn = db_connection(&state.db_path)?;
+            set_meta(&conn, "indexed_extra_roots", &extra_roots.join("\n"))?;
+        }
+        set_state(state, IndexState::Ready, None);
+        if let Some(app) = app {
+            emit_index_state(app, "Ready", None);
+            app.emit("pathindexing_changed", ()).ok();
+        }
+        changed?;
+        refresh_and_emit_status_counts(app, state)?;
+        emit_settings_changed(app, "scanRoots");
+        Ok(ScanRootsDto {
+            scan_root: state.scan_root.to_string_lossy().to_string(),
+            extra_roots,
+        })
+    })();
+    state.pathindexing_active.store(false, AtomicOrdering::Release);
+    result
+}
 
-    Some(total)
+/// Replace the `.pathindexing` extra roots at runtime: persist them, purge
+/// the rows of dropped roots, scan added ones and restart the live watcher
+/// over the new set.
+#[tauri::command]
+async fn set_scan_roots(
+    roots: Vec<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<ScanRootsDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || set_scan_roots_blocking(Some(&app), &state, &roots))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
-/// Core DB search shared by the Tauri `search` command and the MCP server
-/// (`mcp_server::run_stdio_server`): dispatches the parsed `mode` to the
-/// mode-specific SQL against `conn`. Takes no AppState so it can run against a
-/// bare read connection when the app itself is not running.
-#[allow(clippy::too_many_arguments)]
-fn run_db_search(
-    conn: &Connection,
-    home_dir: &Path,
-    fts_ready: bool,
-    mode: &SearchMode,
-    query: &str,
-    effective_limit: u32,
-    offset: u32,
-    sort_by: &str,
-    sort_dir: &str,
-) -> AppResult<Vec<EntryDto>> {
-    let order_by = sort_clause(sort_by, sort_dir, "e.");
-    let mut results = Vec::with_capacity(effective_limit as usize);
-    match mode {
-        SearchMode::Empty => {
-            let sql = format!(
-                r#"
-                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                FROM entries e
-                ORDER BY {order_by}
-                LIMIT ?1 OFFSET ?2
-                "#,
-            );
-            let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-            let rows = stmt
-                .query_map(params![effective_limit, offset], row_to_entry)
-                .map_err(|e| e.to_string())?;
-            for row in rows {
-                results.push(row.map_err(|e| e.to_string())?);
-            }
-        }
+#[tauri::command]
+fn open_pathpriority(state: State<'_, AppState>) -> AppResult<()> {
+    pathpriority::open_pathpriority_file(&state.pathpriority_file_path)
+}
 
-        SearchMode::NameSearch { name_like } => {
-            if sort_by != "name" && query.chars().count() >= 3 && fts_ready {
-                // Non-name sort: use FTS5 trigram for globally correct ordering.
-                // The 3-phase approach only returns prefix matches for non-empty
-                // prefix results, causing contains matches to be silently excluded
-                // (e.g. a large file "myapp_foo.zip" missing from size-desc results).
-                // FTS5 trigram index covers all substring matches in one indexed pass.
-                let fts_match = fts_phrase(query);
-                let sql = format!(
-                    r#"
-                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                    FROM entries_fts f
-                    JOIN entries e ON e.id = f.rowid
-                    WHERE entries_fts MATCH ?1
-                    ORDER BY {order_by}
-                    LIMIT ?2 OFFSET ?3
-                    "#,
-                );
-                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map(params![fts_match, effective_limit, offset], row_to_entry)
-                    .map_err(|e| e.to_string())?;
-                for row in rows {
-                    results.push(row.map_err(|e| e.to_string())?);
-                }
-            } else {
+#[tauri::command]
+fn get_launch_at_login(app: AppHandle) -> bool {
+    autostart::is_enabled(&app.config().identifier)
+}
 
-            let escaped_query = escape_like(query);
-            let exact_query = query.to_string();
-            let prefix_like = format!("{}%", escaped_query);
-            let bare_order = sort_clause(sort_by, sort_dir, "");
+#[tauri::command]
+fn set_launch_at_login(enabled: bool, app: AppHandle) -> AppResult<bool> {
+    autostart::set_enabled(&app.config().identifier, &app.package_info().name, enabled)?;
+    emit_settings_changed(Some(&app), "launchAtLogin");
+    Ok(autostart::is_enabled(&app.config().identifier))
+}
 
-            if offset == 0 {
-                let exact_sql = format!(
-                    r#"
-                    SELECT path, name, dir, is_dir, ext, size, mtime
-                    FROM entries
-                    WHERE name COLLATE NOCASE = ?1
-                    ORDER BY {bare_order}
-                    LIMIT ?2
-                    "#,
-                );
-                let mut stmt = conn.prepare_cached(&exact_sql).map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map(params![exact_query, effective_limit], row_to_entry)
-                    .map_err(|e| e.to_string())?;
-                for row in rows {
-                    results.push(row.map_err(|e| e.to_string())?);
-                }
-            }
+#[tauri::command]
+fn restart_app(app: AppHandle) {
+    app.restart();
+}
 
-            if (results.len() as u32) < effective_limit {
-                let remaining = effective_limit - results.len() as u32;
-                let adj_offset = if offset > 0 {
-                    offset.saturating_sub(results.len() as u32)
-                } else {
-                    0
-                };
+#[tauri::command]
+fn list_profiles(app: AppHandle) -> AppResult<Vec<profiles::ProfileDto>> {
+    Ok(profiles::list(&portable::app_data_dir(&app)?))
+}
 
-                // Try indexed prefix search first; fall back to unindexed if
-                // the index is temporarily unavailable (during background DB
-                // rebuild the index may be dropped then recreated).
-                let prefix_sql_indexed = format!(
-                    r#"
-                    SELECT path, name, dir, is_dir, ext, size, mtime
-                    FROM entries INDEXED BY idx_entries_name_nocase
-                    WHERE name LIKE ?1 ESCAPE '\'
-                      AND name COLLATE NOCASE != ?2
-                    ORDER BY {bare_order}
-                    LIMIT ?3 OFFSET ?4
-                    "#,
-                );
-                let prefix_sql_fallback = format!(
-                    r#"
-                    SELECT path, name, dir, is_dir, ext, size, mtime
-                    FROM entries
-                    WHERE name LIKE ?1 ESCAPE '\'
-                      AND name COLLATE NOCASE != ?2
-                    ORDER BY {bare_order}
-                    LIMIT ?3 OFFSET ?4
-                    "#,
-                );
-                let mut stmt = match conn.prepare_cached(&prefix_sql_indexed) {
-                    Ok(s) => s,
-                    Err(_) => {
-                        eprintln!("[search] idx_entries_name_nocase unavailable, using fallback");
-                        conn.prepare_cached(&prefix_sql_fallback).map_err(|e| e.to_string())?
-                    }
-                };
-                let rows = stmt
-                    .query_map(
-                        params![prefix_like, exact_query, remaining, adj_offset],
-                        row_to_entry,
-                    )
-                    .map_err(|e| e.to_string())?;
-                for row in rows {
-                    results.push(row.map_err(|e| e.to_string())?);
-                }
-            }
+#[tauri::command]
+fn create_profile(
+    name: String,
+    root: Option<String>,
+    app: AppHandle,
+) -> AppResult<profiles::ProfileDto> {
+    profiles::create(&portable::app_data_dir(&app)?, &name, root.as_deref())
+}
 
-            if results.is_empty() && fts_ready && query.chars().count() >= 3 {
-                // Phase 2: contains-match via FTS5 trigram — indexed, complete
-                // (no time budget), and paginatable. Reached only when exact and
-                // prefix found nothing, so no exclusion predicates are needed.
-                // For offset>0 the page belongs to the contains result set only
-                // when the query has no prefix matches at all (guard below).
-                let serve_contains_page = if offset == 0 {
-                    true
-                } else {
-                    !conn
-                        .query_row(
-                            "SELECT EXISTS(SELECT 1 FROM entries WHERE name LIKE ?1 ESCAPE '\\')",
-                            params![prefix_like],
-                            |r| r.get::<_, bool>(0),
-                        )
-                        .unwrap_or(true)
-                };
-                if serve_contains_page {
-                    let fts_match = fts_phrase(query);
-                    let phase2_sql = format!(
-                        r#"
-                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                        FROM entries_fts f
-                        JOIN entries e ON e.id = f.rowid
-                        WHERE entries_fts MATCH ?1
-                        ORDER BY {order_by}
-                        LIMIT ?2 OFFSET ?3
-                        "#,
-                    );
-                    let mut stmt2 =
-                        conn.prepare_cached(&phase2_sql).map_err(|e| e.to_string())?;
-                    let rows2 = stmt2
-                        .query_map(params![fts_match, effective_limit, offset], row_to_entry)
-                        .map_err(|e| e.to_string())?;
-                    for row in rows2 {
-                        results.push(row.map_err(|e| e.to_string())?);
-                    }
-                }
-            } else if results.is_empty() && offset == 0 {
-                // Phase 2 fallback (query < 3 chars or FTS rebuilding):
-                // contains-match (LIKE '%q%') with tight time budget.
-                let phase2_start = Instant::now();
-                conn.progress_handler(
-                    2_000,
-                    Some(move || phase2_start.elapsed().as_millis() > 5),
-                );
+#[tauri::command]
+fn delete_profile(id: String, app: AppHandle) -> AppResult<()> {
+    profiles::delete(&portable::app_data_dir(&app)?, &id)
+}
 
-                {
+#[tauri::command]
+fn get_db_location(state: State<'_, AppState>) -> String {
+    state.db_path.to_string_lossy().to_string()
+}
 
-                    let phase2_sql = format!(
-                        r#"
-                        SELECT path, name, dir, is_dir, ext, size, mtime
-                        FROM entries
-                        WHERE name LIKE ?1 ESCAPE '\'
-                          AND name COLLATE NOCASE != ?2
-                          AND name NOT LIKE ?3 ESCAPE '\'
-                        ORDER BY {bare_order}
-                        LIMIT ?4
-                        "#,
-                    );
-                    if let Ok(mut stmt2) = conn.prepare(&phase2_sql) {
-                        if let Ok(rows2) = stmt2.query_map(
-                            params![name_like, exact_query, prefix_like, effective_limit],
-                            row_to_entry,
-                        ) {
-                            for row in rows2 {
-                                match row {
-                                    Ok(entry) => results.push(entry),
-                                    Err(_) => break,
-                                }
-                            }
-                        }
-                    }
-                }
+/// Move this profile's `index.db` into `dir`, e.g. onto a faster disk or out
+/// of a synced folder (see `db_location`). Every connection and writer holds
+/// the current path, so other writes are shut out (`db_writes`) and the
+/// watcher, the HTTP API and pooled connections are shut down first; once the
+/// DB has moved, the app relaunches to reopen everything on the new path. A
+/// failed move leaves the DB where it was and the watcher running.
+#[tauri::command]
+async fn set_db_location(
+    dir: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = PathBuf::from(dir.trim());
+        if !dir.is_absolute() {
+            return Err(format!("Not an absolute path: {}", dir.display()));
+        }
+        let extra_roots = state.extra_roots.lock().clone();
+        if std::iter::once(&state.scan_root)
+            .chain(&extra_roots)
+            .any(|root| root.starts_with(&dir))
+        {
+            return Err("Choose a dedicated folder, not an indexed root.".to_string());
+        }
+        if state
+            .indexing_active
+            .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+            .is_err()
+        {
+            return Err("Cannot move the index while indexing is in progress.".to_string());
+        }
+        // Waits out pins, saved searches and file operations already writing,
+        // and refuses new ones until the restart (or a failed move) reopens it.
+        let writes_closed = state.db_writes.close();
+        let watching = !state.indexing_paused.load(AtomicOrdering::Acquire);
+        if !stop_watcher_and_wait(&state) {
+            eprintln!("[db_location] watcher did not stop within 5s");
+        }
+        let http_server = state.http_server.lock().take();
+        if let Some(server) = http_server {
+            server.stop();
+        }
+        state.search_conn_pool.lock().clear();
+        *state.watcher_conn.lock() = None;
 
-                conn.progress_handler(0, None::<fn() -> bool>);
+        let result = db_location::relocate(&state.data_dir, &state.db_path, &dir);
+        match &result {
+            Ok(moved) if *moved != state.db_path => {
+                eprintln!("[db_location] moved index to {}", moved.display());
+                app.restart();
             }
-
-            } // end sort_by == "name" branch
+            Ok(_) => {}
+            Err(e) => eprintln!("[db_location] move failed: {e}"),
+        }
+        drop(writes_closed);
+        state.indexing_active.store(false, AtomicOrdering::Release);
+        if watching {
+            restart_live_watcher(&app, &state);
         }
+        http_api::start_if_enabled(&state);
+        result.map(|_| ())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        SearchMode::GlobName { name_like } => {
-            // Leading-wildcard patterns can't use the name index; narrow with the
-            // FTS trigram index on literal runs first, then verify the full glob
-            // with LIKE. Prefix-shaped patterns keep the plain LIKE (index range).
-            let fts_prefilter = glob_fts_prefilter(fts_ready, name_like, query);
-            if let Some(match_expr) = fts_prefilter {
-                let sql = format!(
-                    r#"
-                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                    FROM entries_fts f
-                    JOIN entries e ON e.id = f.rowid
-                    WHERE entries_fts MATCH ?1
-                      AND e.name LIKE ?2 ESCAPE '\'
-                    ORDER BY {order_by}
-                    LIMIT ?3 OFFSET ?4
-                    "#,
-                );
-                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map(
-                        params![match_expr, name_like, effective_limit, offset],
-                        row_to_entry,
+/// Open another profile's index. Every watcher, the tray and the HTTP API
+/// hold the current profile's paths, so the switch stops the live writers and
+/// relaunches; startup then opens the new profile's DB and starts its
+/// watchers like any launch.
+#[tauri::command]
+fn switch_profile(id: String, app: AppHandle, state: State<'_, AppState>) -> AppResult<()> {
+    let app_data_dir = portable::app_data_dir(&app)?;
+    if profiles::active_profile_id(&app_data_dir) == id {
+        return Ok(());
+    }
+    profiles::set_active(&app_data_dir, &id)?;
+    eprintln!("[profiles] switching to {id}");
+    state.watcher_stop.store(true, AtomicOrdering::Release);
+    if let Some(server) = state.http_server.lock().take() {
+        server.stop();
+    }
+    app.restart();
+}
+
+#[tauri::command]
+fn start_full_index(app: AppHandle, state: State<'_, AppState>) -> AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        win::start_windows_indexing(app, state.inner().clone());
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        start_full_index_worker(app, state.inner().clone())
+    }
+}
+
+#[tauri::command]
+async fn reset_index(app: AppHandle, state: State<'_, AppState>) -> AppResult<()> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || reset_index_blocking(app, state))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Drop the index and rebuild it from scratch (Settings and the tray's
+/// "Rebuild Index"). Also resumes live updates if they were paused.
+pub(crate) fn reset_index_blocking(app: AppHandle, state: AppState) -> AppResult<()> {
+    let writing = state.db_writes.enter()?;
+    if state.indexing_active.load(AtomicOrdering::Acquire) {
+        return Err("Cannot reset while indexing is in progress.".to_string());
+    }
+
+    // Stop existing file watcher and wait for it to fully exit
+    if !stop_watcher_and_wait(&state) {
+        eprintln!("[reset] watcher did not stop within 5s, proceeding anyway");
+    }
+
+    let conn = db_connection(&state.db_path)?;
+
+    // Fast reset: rename entries (O(1), no data movement) → create new empty table
+    // → FTS rebuild from empty content table (instant) → background DROP old table.
+    // This avoids per-row WAL writes that make DELETE FROM entries slow at scale.
+    let _ = set_meta(&conn, "fts_dirty", "1");
+    let _ = conn.execute_batch(DROP_FTS_TRIGGERS_SQL);
+    let _ = conn.execute_batch("DROP TABLE IF EXISTS entries_gc_reset;");
+    conn.execute_batch("ALTER TABLE entries RENAME TO entries_gc_reset;")
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch(CREATE_ENTRIES_TABLE_SQL).map_err(|e| e.to_string())?;
+    conn.execute_batch(CREATE_FTS_TRIGGERS_SQL).map_err(|e| e.to_string())?;
+    let _ = conn.execute_batch(REBUILD_FTS_SQL);
+
+    // entries_gc_reset will be dropped by the GC cleanup in the finalizing thread
+    // after indexing completes — avoids a race between DROP TABLE and the new indexer
+    // both competing for the SQLite WAL write lock.
+
+    // Clears every meta row (last_run_id, cached counts, and the fts_dirty=1
+    // set above) so the follow-up index runs fresh with a clean FTS.
+    conn.execute("DELETE FROM meta", [])
+        .map_err(|e| e.to_string())?;
+
+    {
+        let mut status = state.status.lock();
+        status.state = IndexState::Indexing;
+        status.entries_count = 0;
+        status.last_updated = None;
+        status.permission_errors = 0;
+        status.message = None;
+        status.scanned = 0;
+        status.indexed = 0;
+        status.current_path.clear();
+    }
+
+    *state.mem_index.write() = None;
+    invalidate_search_caches(&state);
+    // Schema was swapped (entries renamed + recreated): drop pooled search
+    // connections so nothing holds statements against the old table.
+    state.search_conn_pool.lock().clear();
+
+    emit_index_state(&app, "Indexing", None);
+    emit_index_updated(&app, 0, now_epoch(), 0);
+    drop(conn);
+    drop(writing);
+
+    // Allow new watcher to start
+    state.watcher_stop.store(false, AtomicOrdering::Release);
+    if state.indexing_paused.swap(false, AtomicOrdering::AcqRel) {
+        let _ = app.emit("indexing_paused", false);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        win::start_windows_indexing(app, state);
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        start_full_index_worker(app.clone(), state.clone())?;
+        // meta was cleared above, so FSEvents starts from now.
+        restart_live_watcher(&app, &state);
+        Ok(())
+    }
+}
+
+fn log_search(db_path: &Path, query: &str, mode: &str, results: &[EntryDto]) {
+    if !search_log_enabled() {
+        return;
+    }
+
+    let log_path = db_path.with_file_name("search.log");
+    let Ok(mut f) = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+    else {
+        return;
+    };
+    let top: Vec<&str> = results.iter().take(10).map(|e| e.name.as_str()).collect();
+    let _ = writeln!(
+        f,
+        "[{}] Q={:?} MODE={} COUNT={} TOP={:?}",
+        now_epoch(),
+        query,
+        mode,
+        results.len(),
+        top,
+    );
+}
+
+fn search_log_enabled() -> bool {
+    *SEARCH_LOG_ENABLED.get_or_init(|| env_truthy("FASTFIND_SEARCH_LOG"))
+}
+
+/// Returns the total number of entries matching `query` without LIMIT/OFFSET.
+/// Returns `None` when the total is unknown or intentionally skipped.
+/// Total matches for `execution` as `(count, approximate)`. Full-scan modes
+/// on a large index are extrapolated from a sample rather than counted.
+fn compute_total_count(state: &AppState, execution: &SearchExecution) -> Option<(u32, bool)> {
+    let home_dir = &state.home_dir;
+    // Read once here, as `execute_search` does independently — the count path
+    // and the result path each snapshot `fts_ready` after the search runs.
+    let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
+    // When fewer results than the limit were returned the total is exact.
+    if (execution.results.len() as u32) < execution.effective_limit {
+        return Some((
+            execution
+                .offset
+                .saturating_add(execution.results.len() as u32),
+            false,
+        ));
+    }
+    // For paginated pages after the first we leave total tracking to the frontend.
+    if execution.offset > 0 {
+        return None;
+    }
+    // Non-SQL fast paths already return all matching entries.
+    if execution.mode_label.starts_with("mem_")
+        || execution.mode_label == "spotlight"
+        || execution.mode_label == "spotlight_timeout"
+        || execution.mode_label == "wsearch"
+        || execution.mode_label == "find_fallback"
+        || execution.mode_label == "name_neg_cache"
+    {
+        return Some((execution.results.len() as u32, false));
+    }
+    let Ok(conn) = pooled_search_connection(state) else {
+        return None;
+    };
+    let entries_count = state.status.lock().entries_count;
+    let (count, approximate) = count_query_matches(
+        &conn,
+        home_dir,
+        fts_ready,
+        &execution.query,
+        &execution.sort_by,
+        Some(entries_count),
+    );
+    // A full page came back, so there are at least that many.
+    Some((count.max(execution.results.len() as u32), approximate))
+}
+
+/// Below this many indexed rows a full-scan COUNT(*) is quick enough to run.
+const COUNT_ESTIMATE_MIN_ROWS: u64 = 200_000;
+const COUNT_SAMPLE_WINDOWS: u64 = 8;
+const COUNT_SAMPLE_WINDOW_ROWS: i64 = 2_500;
+
+/// Extrapolate `SELECT COUNT(*) FROM entries e WHERE {where_sql}` from a few
+/// id windows spread evenly over the table instead of scanning every row.
+/// Ids follow scan order, so one window would only see one corner of the
+/// tree; fixed window starts keep the estimate stable between keystrokes.
+fn estimate_match_count(
+    conn: &Connection,
+    where_sql: &str,
+    sql_params: &[SqlValue],
+    total_rows: u64,
+) -> Option<u32> {
+    let (min_id, max_id): (Option<i64>, Option<i64>) = conn
+        .query_row("SELECT MIN(id), MAX(id) FROM entries", [], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .ok()?;
+    let (min_id, max_id) = (min_id?, max_id?);
+    let n = sql_params.len();
+    let sql = format!(
+        "SELECT COUNT(*), COALESCE(SUM(CASE WHEN {where_sql} THEN 1 ELSE 0 END), 0) \
+         FROM (SELECT * FROM entries WHERE id >= ?{} ORDER BY id LIMIT ?{}) e",
+        n + 1,
+        n + 2
+    );
+    let mut stmt = conn.prepare_cached(&sql).ok()?;
+    let span = (max_id - min_id + 1) as u64;
+    let (mut scanned, mut matched) = (0u64, 0u64);
+    for w in 0..COUNT_SAMPLE_WINDOWS {
+        let start = min_id + (span * w / COUNT_SAMPLE_WINDOWS) as i64;
+        let mut window_params = sql_params.to_vec();
+        window_params.push(SqlValue::Integer(start));
+        window_params.push(SqlValue::Integer(COUNT_SAMPLE_WINDOW_ROWS));
+        let (rows, hits): (i64, i64) = stmt
+            .query_row(params_from_iter(window_params.iter()), |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .ok()?;
+        scanned += rows as u64;
+        matched += hits as u64;
+    }
+    if scanned == 0 {
+        return None;
+    }
+    let estimate = (matched as f64 / scanned as f64) * total_rows as f64;
+    Some(round_estimate(estimate.min(u32::MAX as f64) as u32))
+}
+
+/// Three significant digits: a sampled 12,437 is shown as ~12,400.
+fn round_estimate(n: u32) -> u32 {
+    let mut unit = 1u32;
+    while n / unit >= 1_000 {
+        unit *= 10;
+    }
+    (n + unit / 2) / unit * unit
+}
+
+/// Number of entries matching `query`, planned exactly as the count shown
+/// next to `search` results (same mode dispatch, FTS/LIKE choices, and
+/// name-search phases for `sort_by`) but without fetching any rows. With
+/// `estimate_rows` (the index size) the unindexed full-scan branches are
+/// sampled instead; the returned flag marks such approximate counts.
+fn count_query_matches(
+    conn: &Connection,
+    home_dir: &Path,
+    fts_ready: bool,
+    query: &str,
+    sort_by: &str,
+    estimate_rows: Option<u64>,
+) -> (u32, bool) {
+    // Counting matches from the trigram postings alone: joining entries just to
+    // count doubles the work, and the FTS index is authoritative while in sync.
+    let fts_only_count = |query: &str| -> u32 {
+        conn.query_row(
+            "SELECT COUNT(*) FROM entries_fts WHERE entries_fts MATCH ?1",
+            params![fts_phrase(query)],
+            |r| r.get(0),
+        )
+        .unwrap_or(0)
+    };
+    // COUNT(*) over a WHERE no index can serve: every row is visited.
+    let scan_count = |where_sql: &str, sql_params: Vec<SqlValue>| -> (u32, bool) {
+        if let Some(total_rows) = estimate_rows.filter(|&n| n >= COUNT_ESTIMATE_MIN_ROWS) {
+            if let Some(estimate) = estimate_match_count(conn, where_sql, &sql_params, total_rows)
+            {
+                return (estimate, true);
+            }
+        }
+        let count = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM entries e WHERE {where_sql}"),
+                params_from_iter(sql_params.iter()),
+                |r| r.get(0),
+            )
+            .unwrap_or(0);
+        (count, false)
+    };
+    let exact = |count: u32| (count, false);
+    let mode = parse_query(query);
+    match mode {
+        SearchMode::Empty => exact(
+            conn.query_row("SELECT COUNT(*) FROM entries", [], |r| r.get(0))
+                .unwrap_or(0),
+        ),
+        SearchMode::NameSearch { name_like } => {
+            let fts_ok = fts_ready && query.chars().count() >= 3;
+            if sort_by != "name" && fts_ok {
+                exact(fts_only_count(query))
+            } else {
+                let escaped = escape_like(&name_norm::fold(query));
+                let prefix_like = format!("{}%", escaped);
+                let prefix_count: u32 = conn
+                    .query_row(
+                        "SELECT COUNT(*) FROM entries WHERE name_norm LIKE ?1 ESCAPE '\\'",
+                        params![prefix_like],
+                        |r| r.get(0),
+                    )
+                    .unwrap_or(0);
+                if prefix_count > 0 {
+                    exact(prefix_count)
+                } else if fts_ok {
+                    // Phase-2 contains fallback was served by FTS.
+                    exact(fts_only_count(query))
+                } else {
+                    // Phase-2 contains fallback was used.
+                    scan_count(
+                        "e.name_norm LIKE ?1 ESCAPE '\\'",
+                        vec![SqlValue::Text(name_like)],
                     )
-                    .map_err(|e| e.to_string())?;
-                for row in rows {
-                    results.push(row.map_err(|e| e.to_string())?);
                 }
+            }
+        }
+        SearchMode::GlobName {
+            name_like,
+            name_glob,
+        } => {
+            let fts_prefilter = glob_fts_prefilter(fts_ready, &name_like, query);
+            if let Some(match_expr) = fts_prefilter {
+                exact(
+                    conn.query_row(
+                        "SELECT COUNT(*) FROM entries_fts f JOIN entries e ON e.id = f.rowid \
+                         WHERE entries_fts MATCH ?1 AND e.name_norm LIKE ?2 ESCAPE '\\' \
+                           AND (?3 IS NULL OR e.name_norm GLOB ?3)",
+                        params![match_expr, name_like, name_glob],
+                        |r| r.get(0),
+                    )
+                    .unwrap_or(0),
+                )
             } else {
-                let sql = format!(
-                    r#"
-                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                    FROM entries e
-                    WHERE e.name LIKE ?1 ESCAPE '\'
-                    ORDER BY {order_by}
-                    LIMIT ?2 OFFSET ?3
-                    "#,
-                );
-                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-                let rows = stmt
-                    .query_map(params![name_like, effective_limit, offset], row_to_entry)
-                    .map_err(|e| e.to_string())?;
-                for row in rows {
-                    results.push(row.map_err(|e| e.to_string())?);
-                }
+                scan_count(
+                    "e.name_norm LIKE ?1 ESCAPE '\\' AND (?2 IS NULL OR e.name_norm GLOB ?2)",
+                    vec![
+                        SqlValue::Text(name_like),
+                        name_glob.map_or(SqlValue::Null, SqlValue::Text),
+                    ],
+                )
             }
         }
-
-        SearchMode::ExtSearch { ext, name_like: _ } => {
-            let sql = format!(
-                r#"
-                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                FROM entries e
-                WHERE e.ext = ?1
-                ORDER BY {order_by}
-                LIMIT ?2 OFFSET ?3
-                "#,
-            );
-            let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-            let rows = stmt
-                .query_map(params![ext, effective_limit, offset], row_to_entry)
-                .map_err(|e| e.to_string())?;
-            for row in rows {
-                results.push(row.map_err(|e| e.to_string())?);
+        SearchMode::ExtSearch { ext, .. } => exact(
+            conn.query_row(
+                "SELECT COUNT(*) FROM entries WHERE ext = ?1",
+                params![ext],
+                |r| r.get(0),
+            )
+            .unwrap_or(0),
+        ),
+        SearchMode::PathContains { term } => {
+            let (where_sql, sql_params) = path_contains_filter(conn, fts_ready, &term);
+            if where_sql.contains("entries_fts") {
+                exact(
+                    conn.query_row(
+                        &format!("SELECT COUNT(*) FROM entries e WHERE {where_sql}"),
+                        params_from_iter(sql_params.iter()),
+                        |r| r.get(0),
+                    )
+                    .unwrap_or(0),
+                )
+            } else {
+                scan_count(&where_sql, sql_params)
             }
         }
-
         SearchMode::PathSearch {
-            path_like: _,
             name_like,
+            name_glob,
             dir_hint,
+            ..
         } => {
-            let resolved_dirs: Vec<String> = resolve_dir_hint(home_dir, dir_hint)
+            let resolved_dirs = resolve_dir_hint(home_dir, &dir_hint)
                 .map(|p| vec![p.to_string_lossy().to_string()])
                 .unwrap_or_default();
             let resolved_dirs = if resolved_dirs.is_empty() {
-                resolve_dirs_from_db(conn, dir_hint)
+                resolve_dirs_from_db(conn, &dir_hint)
             } else {
                 resolved_dirs
             };
 
             if !resolved_dirs.is_empty() {
-                let ext_shortcut = extract_ext_from_like(name_like);
                 let mut sql_params: Vec<SqlValue> = Vec::new();
                 let mut dir_conditions = Vec::new();
-
+                let sep = std::path::MAIN_SEPARATOR;
                 for d in &resolved_dirs {
                     let i = sql_params.len();
-                    let (pfx, pfx_end) = subtree_range_bounds(d);
+                    let pfx = format!("{d}{sep}");
+                    let pfx_end = format!("{d}\x7F");
                     sql_params.push(SqlValue::Text(d.clone()));
                     sql_params.push(SqlValue::Text(pfx));
                     sql_params.push(SqlValue::Text(pfx_end));
@@ -5403,147 +6999,551 @@ fn run_db_search(
                     ));
                 }
                 let dir_where = dir_conditions.join(" OR ");
-
                 let name_filter = if name_like == "%" {
                     String::new()
-                } else if let Some(ref ext_val) = ext_shortcut {
-                    let i = sql_params.len();
-                    sql_params.push(SqlValue::Text(ext_val.clone()));
-                    format!(" AND e.ext = ?{}", i + 1)
                 } else {
-                    let i = sql_params.len();
-                    sql_params.push(SqlValue::Text(name_like.clone()));
-                    format!(" AND e.name LIKE ?{} ESCAPE '\\'", i + 1)
+                    let ext_shortcut = extract_ext_from_like(&name_like);
+                    if let Some(ext_val) = ext_shortcut {
+                        let i = sql_params.len();
+                        sql_params.push(SqlValue::Text(ext_val));
+                        format!(" AND e.ext = ?{}", i + 1)
+                    } else {
+                        let i = sql_params.len();
+                        sql_params.push(SqlValue::Text(name_like.clone()));
+                        format!(" AND e.name_norm LIKE ?{} ESCAPE '\\'", i + 1)
+                    }
                 };
+                let name_filter = match name_glob {
+                    Some(glob) => {
+                        sql_params.push(SqlValue::Text(glob));
+                        format!("{name_filter} AND e.name_norm GLOB ?{}", sql_params.len())
+                    }
+                    None => name_filter,
+                };
+                let sql =
+                    format!("SELECT COUNT(*) FROM entries e WHERE ({dir_where}){name_filter}");
+                exact(
+                    conn.query_row(&sql, params_from_iter(sql_params.iter()), |r| r.get(0))
+                        .unwrap_or(0),
+                )
+            } else {
+                let sep = std::path::MAIN_SEPARATOR;
+                let native_hint = dir_hint.replace('/', &sep.to_string());
+                let escaped_sep = escape_like(&sep.to_string());
+                let dir_suffix = escape_like(&native_hint);
+                let dir_like_exact = format!("%{escaped_sep}{dir_suffix}");
+                let dir_like_sub = format!("%{escaped_sep}{dir_suffix}{escaped_sep}%");
+                let ext_shortcut = extract_ext_from_like(&name_like);
+                if let Some(ext_val) = ext_shortcut {
+                    exact(
+                        conn.query_row(
+                            "SELECT COUNT(*) FROM entries e WHERE e.ext = ?1 AND (e.dir LIKE ?2 ESCAPE '\\' OR e.dir LIKE ?3 ESCAPE '\\')",
+                            params![ext_val, dir_like_exact, dir_like_sub],
+                            |r| r.get(0),
+                        )
+                        .unwrap_or(0),
+                    )
+                } else if name_like == "%" {
+                    scan_count(
+                        "(e.dir LIKE ?1 ESCAPE '\\' OR e.dir LIKE ?2 ESCAPE '\\')",
+                        vec![SqlValue::Text(dir_like_exact), SqlValue::Text(dir_like_sub)],
+                    )
+                } else {
+                    scan_count(
+                        "(e.dir LIKE ?1 ESCAPE '\\' OR e.dir LIKE ?2 ESCAPE '\\') AND e.name_norm LIKE ?3 ESCAPE '\\' \
+                         AND (?4 IS NULL OR e.name_norm GLOB ?4)",
+                        vec![
+                            SqlValue::Text(dir_like_exact),
+                            SqlValue::Text(dir_like_sub),
+                            SqlValue::Text(name_like),
+                            name_glob.map_or(SqlValue::Null, SqlValue::Text),
+                        ],
+                    )
+                }
+            }
+        }
+    }
+}
 
-                let limit_idx = sql_params.len() + 1;
-                let offset_idx = sql_params.len() + 2;
-                sql_params.push(SqlValue::Integer(effective_limit as i64));
-                sql_params.push(SqlValue::Integer(offset as i64));
+/// Installs the progress handler for a search: the running statement is
+/// interrupted once `cancel` fires or, with `budget_ms`, once that many
+/// milliseconds have passed (the time-budgeted contains fallbacks).
+fn set_search_progress_handler(
+    conn: &Connection,
+    cancel: Option<&SearchCancelToken>,
+    budget_ms: Option<u128>,
+) {
+    if cancel.is_none() && budget_ms.is_none() {
+        conn.progress_handler(0, None::<fn() -> bool>);
+        return;
+    }
+    let cancel = cancel.cloned();
+    let started = Instant::now();
+    conn.progress_handler(
+        2_000,
+        Some(move || {
+            search_cancel::is_cancelled(cancel.as_ref())
+                || budget_ms.is_some_and(|ms| started.elapsed().as_millis() > ms)
+        }),
+    );
+}
+
+/// Core DB search shared by the Tauri `search` command and the MCP server
+/// (`mcp_server::run_stdio_server`): dispatches the parsed `mode` to the
+/// mode-specific SQL against `conn`. Takes no AppState so it can run against a
+/// bare read connection when the app itself is not running.
+#[allow(clippy::too_many_arguments)]
+fn run_db_search(
+    conn: &Connection,
+    home_dir: &Path,
+    fts_ready: bool,
+    mode: &SearchMode,
+    query: &str,
+    effective_limit: u32,
+    offset: u32,
+    sort_by: &str,
+    sort_dir: &str,
+    cancel: Option<&SearchCancelToken>,
+) -> AppResult<Vec<EntryDto>> {
+    set_search_progress_handler(conn, cancel, None);
+    let order_by = sort_clause(sort_by, sort_dir, "e.");
+    let mut results = Vec::with_capacity(effective_limit as usize);
+    match mode {
+        SearchMode::Empty => {
+            let sql = format!(
+                r#"
+                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                FROM entries e
+                ORDER BY {order_by}
+                LIMIT ?1 OFFSET ?2
+                "#,
+            );
+            let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![effective_limit, offset], row_to_entry)
+                .map_err(|e| e.to_string())?;
+            for row in rows {
+                results.push(row.map_err(|e| e.to_string())?);
+            }
+        }
 
+        SearchMode::NameSearch { name_like } => {
+            if sort_by != "name" && query.chars().count() >= 3 && fts_ready {
+                // Non-name sort: use FTS5 trigram for globally correct ordering.
+                // The 3-phase approach only returns prefix matches for non-empty
+                // prefix results, causing contains matches to be silently excluded
+                // (e.g. a large file "myapp_foo.zip" missing from size-desc results).
+                // FTS5 trigram index covers all substring matches in one indexed pass.
+                let fts_match = fts_phrase(query);
                 let sql = format!(
                     r#"
-                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                    FROM entries e
-                    WHERE ({dir_where}){name_filter}
+                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                    FROM entries_fts f
+                    JOIN entries e ON e.id = f.rowid
+                    WHERE entries_fts MATCH ?1
                     ORDER BY {order_by}
-                    LIMIT ?{limit_idx} OFFSET ?{offset_idx}
+                    LIMIT ?2 OFFSET ?3
                     "#,
                 );
-                // Dynamic shape (one condition per resolved dir): not worth caching.
-                let mut stmt = conn.prepare(sql.as_str()).map_err(|e| e.to_string())?;
+                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
                 let rows = stmt
-                    .query_map(params_from_iter(sql_params.iter()), row_to_entry)
+                    .query_map(params![fts_match, effective_limit, offset], row_to_entry)
                     .map_err(|e| e.to_string())?;
                 for row in rows {
                     results.push(row.map_err(|e| e.to_string())?);
                 }
             } else {
-                let sep = std::path::MAIN_SEPARATOR;
-                let sep_str = sep.to_string();
-                let escaped_sep = escape_like(&sep_str);
-                let (native_hint, is_absolute) = normalize_hint_to_native(dir_hint);
-                let dir_suffix = escape_like(&native_hint);
-                let dir_prefix = if is_absolute {
-                    String::new()
+
+            // Exact and prefix phases compare `name_norm`, so a query typed in
+            // NFC finds names the filesystem stored decomposed.
+            let exact_query = name_norm::fold(query);
+            let escaped_query = escape_like(&exact_query);
+            let prefix_like = format!("{}%", escaped_query);
+            let bare_order = sort_clause(sort_by, sort_dir, "");
+
+            // Relevance order in SQL, so LIMIT keeps the globally best
+            // matches instead of `sort_entries_with_relevance` reordering
+            // whichever page came back: exact names before prefix matches
+            // and, under name sort, shallower paths first within each.
+            let depth_first = |prefix: &str| {
+                if sort_by == "name" {
+                    format!("{} ASC, ", sql_path_depth(prefix))
                 } else {
-                    escaped_sep.clone()
+                    String::new()
+                }
+            };
+            let bare_depth = depth_first("");
+
+            // Try indexed prefix search first; fall back to unindexed if
+            // the index is temporarily unavailable (during background DB
+            // rebuild the index may be dropped then recreated).
+            let prefix_sql = |from: &str| {
+                format!(
+                    r#"
+                    SELECT path, name, dir, is_dir, ext, size, mtime, cloud_only
+                    FROM {from}
+                    WHERE name_norm LIKE ?1 ESCAPE '\'
+                    ORDER BY CASE WHEN name_norm COLLATE NOCASE = ?2 THEN 0 ELSE 1 END,
+                             {bare_depth}{bare_order}
+                    LIMIT ?3 OFFSET ?4
+                    "#,
+                )
+            };
+            let mut stmt =
+                match conn.prepare_cached(&prefix_sql("entries INDEXED BY idx_entries_name_norm")) {
+                    Ok(s) => s,
+                    Err(_) => {
+                        eprintln!("[search] idx_entries_name_norm unavailable, using fallback");
+                        conn.prepare_cached(&prefix_sql("entries")).map_err(|e| e.to_string())?
+                    }
                 };
-                let dir_like_exact = format!("%{dir_prefix}{dir_suffix}");
-                let dir_like_sub = format!("%{dir_prefix}{dir_suffix}{escaped_sep}%");
-                let ext_shortcut = extract_ext_from_like(name_like);
+            let rows = stmt
+                .query_map(
+                    params![prefix_like, exact_query, effective_limit, offset],
+                    row_to_entry,
+                )
+                .map_err(|e| e.to_string())?;
+            for row in rows {
+                results.push(row.map_err(|e| e.to_string())?);
+            }
 
-                if let Some(ext_val) = ext_shortcut {
-                    let sql = format!(
+            if results.is_empty() && fts_ready && query.chars().count() >= 3 {
+                // Phase 2: contains-match via FTS5 trigram — indexed, complete
+                // (no time budget), and paginatable. Reached only when exact and
+                // prefix found nothing, so no exclusion predicates are needed.
+                // For offset>0 the page belongs to the contains result set only
+                // when the query has no prefix matches at all (guard below).
+                let serve_contains_page = if offset == 0 {
+                    true
+                } else {
+                    !conn
+                        .query_row(
+                            "SELECT EXISTS(SELECT 1 FROM entries WHERE name_norm LIKE ?1 ESCAPE '\\')",
+                            params![prefix_like],
+                            |r| r.get::<_, bool>(0),
+                        )
+                        .unwrap_or(true)
+                };
+                if serve_contains_page {
+                    let fts_match = fts_phrase(query);
+                    let e_depth = depth_first("e.");
+                    let phase2_sql = format!(
                         r#"
-                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                        FROM entries e
-                        WHERE e.ext = ?1
-                          AND (e.dir LIKE ?2 ESCAPE '\' OR e.dir LIKE ?3 ESCAPE '\')
-                        ORDER BY {order_by}
-                        LIMIT ?4 OFFSET ?5
+                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                        FROM entries_fts f
+                        JOIN entries e ON e.id = f.rowid
+                        WHERE entries_fts MATCH ?1
+                        ORDER BY {e_depth}{order_by}
+                        LIMIT ?2 OFFSET ?3
                         "#,
                     );
-                    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-                    let rows = stmt
-                        .query_map(
-                            params![
-                                ext_val,
-                                dir_like_exact,
-                                dir_like_sub,
-                                effective_limit,
-                                offset
-                            ],
-                            row_to_entry,
-                        )
+                    let mut stmt2 =
+                        conn.prepare_cached(&phase2_sql).map_err(|e| e.to_string())?;
+                    let rows2 = stmt2
+                        .query_map(params![fts_match, effective_limit, offset], row_to_entry)
                         .map_err(|e| e.to_string())?;
-                    for row in rows {
+                    for row in rows2 {
                         results.push(row.map_err(|e| e.to_string())?);
                     }
-                } else if name_like == "%" {
-                    // Directory listing: no name filter needed, no time budget
-                    let sql = format!(
+                }
+            } else if results.is_empty() && offset == 0 {
+                // Phase 2 fallback (query < 3 chars or FTS rebuilding):
+                // contains-match (LIKE '%q%') with tight time budget.
+                set_search_progress_handler(conn, cancel, Some(5));
+
+                {
+
+                    let phase2_sql = format!(
                         r#"
-                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                        FROM entries e
-                        WHERE e.dir LIKE ?1 ESCAPE '\' OR e.dir LIKE ?2 ESCAPE '\'
-                        ORDER BY {order_by}
-                        LIMIT ?3 OFFSET ?4
+                        SELECT path, name, dir, is_dir, ext, size, mtime, cloud_only
+                        FROM entries
+                        WHERE name_norm LIKE ?1 ESCAPE '\'
+                          AND name_norm COLLATE NOCASE != ?2
+                          AND name_norm NOT LIKE ?3 ESCAPE '\'
+                        ORDER BY {bare_depth}{bare_order}
+                        LIMIT ?4
                         "#,
                     );
-                    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
-                    let rows = stmt
-                        .query_map(
-                            params![dir_like_exact, dir_like_sub, effective_limit, offset],
+                    if let Ok(mut stmt2) = conn.prepare_cached(&phase2_sql) {
+                        if let Ok(rows2) = stmt2.query_map(
+                            params![name_like, exact_query, prefix_like, effective_limit],
                             row_to_entry,
-                        )
-                        .map_err(|e| e.to_string())?;
-                    for row in rows {
-                        results.push(row.map_err(|e| e.to_string())?);
-                    }
-                } else {
-                    // Phase A: fast prefix search via name index
-                    // "%main%" -> strip leading '%' -> "main%" can use idx_entries_name_nocase
-                    let prefix_like = if name_like.starts_with('%') {
-                        let rest = &name_like[1..];
-                        if !rest.is_empty() && !rest.starts_with('%') {
-                            Some(rest.to_string())
-                        } else {
-                            None
-                        }
-                    } else {
-                        // Already a prefix pattern (e.g. "test%"), use as-is
-                        Some(name_like.clone())
-                    };
-
-                    if offset == 0 {
-                        if let Some(ref pfx) = prefix_like {
-                            let sql = format!(
-                                r#"
-                                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
-                                FROM entries e INDEXED BY idx_entries_name_nocase
-                                WHERE e.name LIKE ?1 ESCAPE '\'
-                                  AND (e.dir LIKE ?2 ESCAPE '\' OR e.dir LIKE ?3 ESCAPE '\')
-                                ORDER BY {order_by}
-                                LIMIT ?4
-                                "#,
-                            );
-                            if let Ok(mut stmt) = conn.prepare_cached(&sql) {
-                                if let Ok(rows) = stmt.query_map(
-                                    params![
-                                        pfx,
-                                        dir_like_exact,
-                                        dir_like_sub,
-                                        effective_limit
-                                    ],
-                                    row_to_entry,
-                                ) {
-                                    for row in rows {
-                                        match row {
-                                            Ok(entry) => results.push(entry),
-                                            Err(_) => break,
-                                        }
-                                    }
+                        ) {
+                            for row in rows2 {
+                                match row {
+                                    Ok(entry) => results.push(entry),
+                                    Err(_) => break,
+                                }
+                            }
+                        }
+                    }
+                }
+
+                set_search_progress_handler(conn, cancel, None);
+            }
+
+            } // end sort_by == "name" branch
+        }
+
+        SearchMode::GlobName {
+            name_like,
+            name_glob,
+        } => {
+            // Leading-wildcard patterns can't use the name index; narrow with the
+            // FTS trigram index on literal runs first, then verify the full glob
+            // with LIKE. Prefix-shaped patterns keep the plain LIKE (index range).
+            // LIKE has no `[...]` classes; `name_glob` settles those.
+            let fts_prefilter = glob_fts_prefilter(fts_ready, name_like, query);
+            if let Some(match_expr) = fts_prefilter {
+                let sql = format!(
+                    r#"
+                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                    FROM entries_fts f
+                    JOIN entries e ON e.id = f.rowid
+                    WHERE entries_fts MATCH ?1
+                      AND e.name_norm LIKE ?2 ESCAPE '\'
+                      AND (?5 IS NULL OR e.name_norm GLOB ?5)
+                    ORDER BY {order_by}
+                    LIMIT ?3 OFFSET ?4
+                    "#,
+                );
+                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map(
+                        params![match_expr, name_like, effective_limit, offset, name_glob],
+                        row_to_entry,
+                    )
+                    .map_err(|e| e.to_string())?;
+                for row in rows {
+                    results.push(row.map_err(|e| e.to_string())?);
+                }
+            } else {
+                let sql = format!(
+                    r#"
+                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                    FROM entries e
+                    WHERE e.name_norm LIKE ?1 ESCAPE '\'
+                      AND (?4 IS NULL OR e.name_norm GLOB ?4)
+                    ORDER BY {order_by}
+                    LIMIT ?2 OFFSET ?3
+                    "#,
+                );
+                let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map(
+                        params![name_like, effective_limit, offset, name_glob],
+                        row_to_entry,
+                    )
+                    .map_err(|e| e.to_string())?;
+                for row in rows {
+                    results.push(row.map_err(|e| e.to_string())?);
+                }
+            }
+        }
+
+        SearchMode::ExtSearch { ext, name_like: _ } => {
+            let sql = format!(
+                r#"
+                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                FROM entries e
+                WHERE e.ext = ?1
+                ORDER BY {order_by}
+                LIMIT ?2 OFFSET ?3
+                "#,
+            );
+            let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![ext, effective_limit, offset], row_to_entry)
+                .map_err(|e| e.to_string())?;
+            for row in rows {
+                results.push(row.map_err(|e| e.to_string())?);
+            }
+        }
+
+        SearchMode::PathSearch {
+            path_like: _,
+            name_like,
+            name_glob,
+            dir_hint,
+        } => {
+            let resolved_dirs: Vec<String> = resolve_dir_hint(home_dir, dir_hint)
+                .map(|p| vec![p.to_string_lossy().to_string()])
+                .unwrap_or_default();
+            let resolved_dirs = if resolved_dirs.is_empty() {
+                resolve_dirs_from_db(conn, dir_hint)
+            } else {
+                resolved_dirs
+            };
+
+            if !resolved_dirs.is_empty() {
+                let ext_shortcut = extract_ext_from_like(name_like);
+                let mut sql_params: Vec<SqlValue> = Vec::new();
+                let mut dir_conditions = Vec::new();
+
+                for d in &resolved_dirs {
+                    let i = sql_params.len();
+                    let (pfx, pfx_end) = subtree_range_bounds(d);
+                    sql_params.push(SqlValue::Text(d.clone()));
+                    sql_params.push(SqlValue::Text(pfx));
+                    sql_params.push(SqlValue::Text(pfx_end));
+                    dir_conditions.push(format!(
+                        "(e.dir = ?{} OR (e.dir >= ?{} AND e.dir < ?{}))",
+                        i + 1,
+                        i + 2,
+                        i + 3
+                    ));
+                }
+                let dir_where = dir_conditions.join(" OR ");
+
+                let name_filter = if name_like == "%" {
+                    String::new()
+                } else if let Some(ref ext_val) = ext_shortcut {
+                    let i = sql_params.len();
+                    sql_params.push(SqlValue::Text(ext_val.clone()));
+                    format!(" AND e.ext = ?{}", i + 1)
+                } else {
+                    let i = sql_params.len();
+                    sql_params.push(SqlValue::Text(name_like.clone()));
+                    format!(" AND e.name_norm LIKE ?{} ESCAPE '\\'", i + 1)
+                };
+                let name_filter = match name_glob {
+                    Some(glob) => {
+                        sql_params.push(SqlValue::Text(glob.clone()));
+                        format!("{name_filter} AND e.name_norm GLOB ?{}", sql_params.len())
+                    }
+                    None => name_filter,
+                };
+
+                let limit_idx = sql_params.len() + 1;
+                let offset_idx = sql_params.len() + 2;
+                sql_params.push(SqlValue::Integer(effective_limit as i64));
+                sql_params.push(SqlValue::Integer(offset as i64));
+
+                let sql = format!(
+                    r#"
+                    SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                    FROM entries e
+                    WHERE ({dir_where}){name_filter}
+                    ORDER BY {order_by}
+                    LIMIT ?{limit_idx} OFFSET ?{offset_idx}
+                    "#,
+                );
+                // Dynamic shape (one condition per resolved dir): not worth caching.
+                let mut stmt = conn.prepare(sql.as_str()).map_err(|e| e.to_string())?;
+                let rows = stmt
+                    .query_map(params_from_iter(sql_params.iter()), row_to_entry)
+                    .map_err(|e| e.to_string())?;
+                for row in rows {
+                    results.push(row.map_err(|e| e.to_string())?);
+                }
+            } else {
+                let sep = std::path::MAIN_SEPARATOR;
+                let sep_str = sep.to_string();
+                let escaped_sep = escape_like(&sep_str);
+                let (native_hint, is_absolute) = normalize_hint_to_native(dir_hint);
+                let dir_suffix = escape_like(&native_hint);
+                let dir_prefix = if is_absolute {
+                    String::new()
+                } else {
+                    escaped_sep.clone()
+                };
+                let dir_like_exact = format!("%{dir_prefix}{dir_suffix}");
+                let dir_like_sub = format!("%{dir_prefix}{dir_suffix}{escaped_sep}%");
+                let ext_shortcut = extract_ext_from_like(name_like);
+
+                if let Some(ext_val) = ext_shortcut {
+                    let sql = format!(
+                        r#"
+                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                        FROM entries e
+                        WHERE e.ext = ?1
+                          AND (e.dir LIKE ?2 ESCAPE '\' OR e.dir LIKE ?3 ESCAPE '\')
+                        ORDER BY {order_by}
+                        LIMIT ?4 OFFSET ?5
+                        "#,
+                    );
+                    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+                    let rows = stmt
+                        .query_map(
+                            params![
+                                ext_val,
+                                dir_like_exact,
+                                dir_like_sub,
+                                effective_limit,
+                                offset
+                            ],
+                            row_to_entry,
+                        )
+                        .map_err(|e| e.to_string())?;
+                    for row in rows {
+                        results.push(row.map_err(|e| e.to_string())?);
+                    }
+                } else if name_like == "%" {
+                    // Directory listing: no name filter needed, no time budget
+                    let sql = format!(
+                        r#"
+                        SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                        FROM entries e
+                        WHERE e.dir LIKE ?1 ESCAPE '\' OR e.dir LIKE ?2 ESCAPE '\'
+                        ORDER BY {order_by}
+                        LIMIT ?3 OFFSET ?4
+                        "#,
+                    );
+                    let mut stmt = conn.prepare_cached(&sql).map_err(|e| e.to_string())?;
+                    let rows = stmt
+                        .query_map(
+                            params![dir_like_exact, dir_like_sub, effective_limit, offset],
+                            row_to_entry,
+                        )
+                        .map_err(|e| e.to_string())?;
+                    for row in rows {
+                        results.push(row.map_err(|e| e.to_string())?);
+                    }
+                } else {
+                    // Phase A: fast prefix search via name index
+                    // "%main%" -> strip leading '%' -> "main%" can use idx_entries_name_norm
+                    let prefix_like = if name_like.starts_with('%') {
+                        let rest = &name_like[1..];
+                        if !rest.is_empty() && !rest.starts_with('%') {
+                            Some(rest.to_string())
+                        } else {
+                            None
+                        }
+                    } else {
+                        // Already a prefix pattern (e.g. "test%"), use as-is
+                        Some(name_like.clone())
+                    };
+
+                    if offset == 0 {
+                        if let Some(ref pfx) = prefix_like {
+                            let sql = format!(
+                                r#"
+                                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                                FROM entries e INDEXED BY idx_entries_name_norm
+                                WHERE e.name_norm LIKE ?1 ESCAPE '\'
+                                  AND (e.dir LIKE ?2 ESCAPE '\' OR e.dir LIKE ?3 ESCAPE '\')
+                                  AND (?5 IS NULL OR e.name_norm GLOB ?5)
+                                ORDER BY {order_by}
+                                LIMIT ?4
+                                "#,
+                            );
+                            if let Ok(mut stmt) = conn.prepare_cached(&sql) {
+                                if let Ok(rows) = stmt.query_map(
+                                    params![
+                                        pfx,
+                                        dir_like_exact,
+                                        dir_like_sub,
+                                        effective_limit,
+                                        name_glob
+                                    ],
+                                    row_to_entry,
+                                ) {
+                                    for row in rows {
+                                        match row {
+                                            Ok(entry) => results.push(entry),
+                                            Err(_) => break,
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -5551,18 +7551,15 @@ fn run_db_search(
 
                     // Phase B: time-budgeted contains fallback if prefix found too few
                     if results.len() < effective_limit as usize {
-                        let path_start = Instant::now();
-                        conn.progress_handler(
-                            2_000,
-                            Some(move || path_start.elapsed().as_millis() > 5),
-                        );
+                        set_search_progress_handler(conn, cancel, Some(5));
 
                         let sql = format!(
                             r#"
-                            SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime
+                            SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
                             FROM entries e
                             WHERE (e.dir LIKE ?1 ESCAPE '\' OR e.dir LIKE ?2 ESCAPE '\')
-                              AND e.name LIKE ?3 ESCAPE '\'
+                              AND e.name_norm LIKE ?3 ESCAPE '\'
+                              AND (?6 IS NULL OR e.name_norm GLOB ?6)
                             ORDER BY {order_by}
                             LIMIT ?4 OFFSET ?5
                             "#,
@@ -5574,7 +7571,8 @@ fn run_db_search(
                                     dir_like_sub,
                                     name_like,
                                     effective_limit,
-                                    offset
+                                    offset,
+                                    name_glob
                                 ],
                                 row_to_entry,
                             ) {
@@ -5587,381 +7585,1694 @@ fn run_db_search(
                             }
                         }
 
-                        conn.progress_handler(0, None::<fn() -> bool>);
+                        set_search_progress_handler(conn, cancel, None);
+
+                        // Deduplicate (Phase A prefix results overlap with Phase B contains)
+                        let mut seen = std::collections::HashSet::new();
+                        results.retain(|e| seen.insert(e.path.clone()));
+                        results.truncate(effective_limit as usize);
+                    }
+                }
+            }
+        }
+
+        SearchMode::PathContains { term } => {
+            let (where_sql, mut sql_params) = path_contains_filter(conn, fts_ready, term);
+            let limit_idx = sql_params.len() + 1;
+            let offset_idx = sql_params.len() + 2;
+            sql_params.push(SqlValue::Integer(effective_limit as i64));
+            sql_params.push(SqlValue::Integer(offset as i64));
+            let sql = format!(
+                r#"
+                SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only
+                FROM entries e
+                WHERE {where_sql}
+                ORDER BY {order_by}
+                LIMIT ?{limit_idx} OFFSET ?{offset_idx}
+                "#,
+            );
+            // Dynamic shape (one condition per matching dir): not worth caching.
+            let mut stmt = conn.prepare(sql.as_str()).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params_from_iter(sql_params.iter()), row_to_entry)
+                .map_err(|e| e.to_string())?;
+            for row in rows {
+                results.push(row.map_err(|e| e.to_string())?);
+            }
+        }
+    }
+    Ok(results)
+}
+
+fn execute_search(
+    state: &AppState,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+) -> AppResult<SearchExecution> {
+    execute_search_streaming(state, query, limit, offset, sort_by, sort_dir, None, None)
+}
+
+/// `execute_search` that also streams rows from the slow `find` fallback
+/// through `partial` as they are found, and gives up with `SEARCH_CANCELLED`
+/// once `cancel` fires.
+#[allow(clippy::too_many_arguments)]
+fn execute_search_streaming(
+    state: &AppState,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    partial: Option<&mut PartialFlush<'_>>,
+    cancel: Option<&SearchCancelToken>,
+) -> AppResult<SearchExecution> {
+    let query = query.trim().to_string();
+    let effective_limit = effective_search_limit(&query, limit, DEFAULT_LIMIT);
+    let offset = offset.unwrap_or(0);
+
+    let sort_by = sort_by.unwrap_or_else(|| "name".to_string());
+    let sort_dir = sort_dir.unwrap_or_else(|| "asc".to_string());
+    let (runtime_ignored_roots, runtime_ignored_patterns) = cached_effective_ignore_rules(state);
+
+    #[cfg(target_os = "macos")]
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        let spotlight = mac::spotlight_search::search_spotlight(&state.home_dir, &query);
+        let mode_label = if spotlight.entries.is_empty() {
+            "db_not_ready".to_string()
+        } else if spotlight.timed_out {
+            "spotlight_timeout".to_string()
+        } else {
+            "spotlight".to_string()
+        };
+        perf_log(format!(
+            "spotlight_fallback db_not_ready query={:?} results={} timed_out={}",
+            query,
+            spotlight.entries.len(),
+            spotlight.timed_out
+        ));
+        return Ok(SearchExecution {
+            query,
+            sort_by,
+            sort_dir,
+            effective_limit,
+            offset,
+            mode_label,
+            results: spotlight.entries,
+        });
+    }
+
+    let is_indexing = matches!(state.status.lock().state, IndexState::Indexing);
+    let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
+
+    // Only a placeholder for the DB-unavailable path; every successful path
+    // wholly reassigns it (run_db_search owns the real allocation).
+    let mut results = Vec::new();
+    let mode = parse_query(&query);
+    let is_name_mode = matches!(&mode, SearchMode::NameSearch { .. });
+    let allow_find_fallback = !is_indexing
+        && matches!(
+            &mode,
+            SearchMode::GlobName { .. } | SearchMode::ExtSearch { .. }
+        );
+    let mut mode_label = mode.label().to_string();
+
+    // Fast path: the resident in-memory index, when one was built
+    {
+        let guard = state.mem_index.read();
+        if let Some(ref mi) = *guard {
+            let mem_results = mem_search::search_mem_index(
+                mi, &query, &mode, effective_limit, offset, &sort_by, &sort_dir, cancel,
+            );
+            if search_cancel::is_cancelled(cancel) {
+                return Err(search_cancel::SEARCH_CANCELLED.to_string());
+            }
+            mode_label = format!("mem_{mode_label}");
+            return Ok(SearchExecution {
+                query,
+                sort_by,
+                sort_dir,
+                effective_limit,
+                offset,
+                mode_label,
+                results: mem_results,
+            });
+        }
+    }
+
+    if is_name_mode && !is_indexing && offset == 0 {
+        if let Some(cache_hit) = negative_name_cache_lookup(state, &query) {
+            if cache_hit.age >= WATCH_DEBOUNCE
+                && cache_hit.age <= NEGATIVE_CACHE_FALLBACK_WINDOW
+                && !cache_hit.fallback_checked
+            {
+                let fallback_results = find_search(
+                    &state.home_dir,
+                    &runtime_ignored_roots,
+                    &runtime_ignored_patterns,
+                    &query,
+                    effective_limit as usize,
+                    &sort_by,
+                    &sort_dir,
+                    partial,
+                );
+                if !fallback_results.is_empty() {
+                    remove_negative_name_query(state, &cache_hit.query_lower);
+                    perf_log(format!(
+                        "search_negative_cache_fallback_hit query={:?} age_ms={} results={}",
+                        query,
+                        cache_hit.age.as_millis(),
+                        fallback_results.len()
+                    ));
+                    return Ok(SearchExecution {
+                        query,
+                        sort_by,
+                        sort_dir,
+                        effective_limit,
+                        offset,
+                        mode_label: "find_fallback".to_string(),
+                        results: fallback_results,
+                    });
+                }
+                mark_negative_name_fallback_checked(state, &cache_hit.query_lower);
+            }
+
+            perf_log(format!(
+                "search_negative_cache_hit query={:?} age_ms={}",
+                query,
+                cache_hit.age.as_millis()
+            ));
+            return Ok(SearchExecution {
+                query,
+                sort_by,
+                sort_dir,
+                effective_limit,
+                offset,
+                mode_label: "name_neg_cache".to_string(),
+                results: Vec::new(),
+            });
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    let db_unavailable;
+    match pooled_search_connection(state) {
+        Ok(conn) => {
+            #[cfg(target_os = "macos")]
+            { db_unavailable = false; }
+            let searched = run_db_search(
+                &conn,
+                &state.home_dir,
+                fts_ready,
+                &mode,
+                &query,
+                effective_limit,
+                offset,
+                &sort_by,
+                &sort_dir,
+                cancel,
+            );
+            // Checked before the error: an interrupted statement surfaces as
+            // an error, a cut-short budgeted phase as a short result. Neither
+            // may feed the find fallback or the negative cache.
+            if search_cancel::is_cancelled(cancel) {
+                return Err(search_cancel::SEARCH_CANCELLED.to_string());
+            }
+            results = searched?;
+
+            if results.is_empty() && !query.is_empty() && offset == 0 && allow_find_fallback {
+                results = find_search(
+                    &state.home_dir,
+                    &runtime_ignored_roots,
+                    &runtime_ignored_patterns,
+                    &query,
+                    effective_limit as usize,
+                    &sort_by,
+                    &sort_dir,
+                    partial,
+                );
+                mode_label = "find_fallback".to_string();
+            }
+        }
+        Err(_) => {
+            #[cfg(target_os = "macos")]
+            { db_unavailable = true; }
+            perf_log(format!("search_db_unavailable query={:?}", query));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    if !query.is_empty() && offset == 0 {
+        let should_spotlight = if results.is_empty() {
+            true
+        } else if results.len() >= effective_limit as usize {
+            false
+        } else {
+            is_indexing || db_unavailable
+        };
+        if should_spotlight {
+            let spotlight = mac::spotlight_search::search_spotlight(&state.home_dir, &query);
+            if !spotlight.entries.is_empty() {
+                if results.is_empty() {
+                    perf_log(format!(
+                        "spotlight_fallback empty_results query={:?} results={} timed_out={}",
+                        query,
+                        spotlight.entries.len(),
+                        spotlight.timed_out
+                    ));
+                    results = spotlight.entries;
+                    mode_label = if spotlight.timed_out {
+                        "spotlight_timeout".to_string()
+                    } else {
+                        "spotlight".to_string()
+                    };
+                } else {
+                    let existing_paths: std::collections::HashSet<String> =
+                        results.iter().map(|e| e.path.clone()).collect();
+                    let mut merged_count = 0usize;
+                    for entry in spotlight.entries {
+                        if !existing_paths.contains(&entry.path) {
+                            results.push(entry);
+                            merged_count += 1;
+                        }
+                    }
+                    if merged_count > 0 {
+                        perf_log(format!(
+                            "spotlight_merge indexing query={:?} merged={} timed_out={}",
+                            query, merged_count, spotlight.timed_out
+                        ));
+                        mode_label = format!("{}_+spotlight", mode_label);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    if !query.is_empty()
+        && offset == 0
+        && is_indexing
+        && state.wsearch_fallback.load(AtomicOrdering::Acquire)
+        && results.len() < effective_limit as usize
+    {
+        let wsearch = win::wsearch_search::search_windows_index(&state.scan_root, &query);
+        let existing_paths: HashSet<String> = results.iter().map(|e| e.path.clone()).collect();
+        let before = results.len();
+        results.extend(
+            wsearch
+                .entries
+                .into_iter()
+                .filter(|entry| !existing_paths.contains(&entry.path)),
+        );
+        let merged_count = results.len() - before;
+        if merged_count > 0 {
+            perf_log(format!(
+                "wsearch_merge indexing query={:?} merged={} timed_out={}",
+                query, merged_count, wsearch.timed_out
+            ));
+            mode_label = if before == 0 {
+                "wsearch".to_string()
+            } else {
+                format!("{}_+wsearch", mode_label)
+            };
+        }
+    }
+
+    results = filter_ignored_entries(results, &runtime_ignored_roots, &runtime_ignored_patterns);
+    results.truncate(effective_limit as usize);
+    if offset == 0 {
+        if sort_by == "name" {
+            sort_entries_with_relevance(&mut results, &query, &sort_by, &sort_dir);
+            pins::float_pinned(&mut results, &state.pinned_paths.read());
+        } else {
+            sort_entries(&mut results, &sort_by, &sort_dir);
+        }
+    }
+    if is_name_mode && !is_indexing && offset == 0 && results.is_empty() && !query.is_empty() {
+        remember_negative_name_query(state, &query);
+    }
+
+    Ok(SearchExecution {
+        query,
+        sort_by,
+        sort_dir,
+        effective_limit,
+        offset,
+        mode_label,
+        results,
+    })
+}
+
+/// Per-row presentation fields requested by the caller, applied to final and
+/// partial result rows alike.
+fn annotate_result_entries(
+    entries: &mut [EntryDto],
+    spoken_labels: bool,
+    time_locale: Option<humanize::TimeLocale>,
+) {
+    if spoken_labels {
+        humanize::annotate_spoken_labels(entries);
+    }
+    if let Some(locale) = time_locale {
+        humanize::annotate_mtime_display(entries, locale);
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+async fn search(
+    app: AppHandle,
+    query: String,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    include_total: Option<bool>,
+    spoken_labels: Option<bool>,
+    time_locale: Option<String>,
+    collapse_links: Option<bool>,
+    group_by_folder: Option<bool>,
+    request_id: Option<u64>,
+    state: State<'_, AppState>,
+) -> AppResult<SearchResultDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let rpc_started = Instant::now();
+        let spoken_labels = spoken_labels.unwrap_or(false);
+        let collapse_links = collapse_links.unwrap_or(false);
+        let group_by_folder = group_by_folder.unwrap_or(false);
+        let time_locale = time_locale
+            .as_deref()
+            .map(|locale| humanize::TimeLocale::resolve(Some(locale)));
+        let mut emit_partial = |found: &[EntryDto]| {
+            let mut entries = found.to_vec();
+            if collapse_links {
+                link_dedup::collapse_links(&mut entries);
+            }
+            annotate_result_entries(&mut entries, spoken_labels, time_locale);
+            app.emit(
+                "search_partial",
+                SearchPartialEvent {
+                    request_id: request_id.unwrap_or_default(),
+                    entries,
+                },
+            )
+            .ok();
+        };
+        let mut partial = request_id.map(|_| PartialFlush::new(&mut emit_partial));
+        let active = state.search_cancels.begin(request_id);
+        let include_total = include_total.unwrap_or(true);
+
+        let trimmed = query.trim();
+        let cache_key = search_cache::SearchCacheKey {
+            query: trimmed.to_string(),
+            sort_by: sort_by.clone().unwrap_or_else(|| "name".to_string()),
+            sort_dir: sort_dir.clone().unwrap_or_else(|| "asc".to_string()),
+            limit: effective_search_limit(trimmed, limit, DEFAULT_LIMIT),
+            offset: offset.unwrap_or(0),
+        };
+        // Revalidate the ignore rules first: if they were edited this clears
+        // the cache, since its rows were filtered with the old rules.
+        cached_effective_ignore_rules(&state);
+        let cached = state.search_result_cache.lock().get(&cache_key);
+        if let Some(hit) = cached.filter(|hit| !include_total || hit.total_count.is_some()) {
+            perf_log(format!(
+                "search_cache_hit query={:?} mode={} results={}",
+                cache_key.query,
+                hit.mode_label,
+                hit.results.len()
+            ));
+            let mut entries = hit.results;
+            if collapse_links {
+                link_dedup::collapse_links(&mut entries);
+            }
+            annotate_result_entries(&mut entries, spoken_labels, time_locale);
+            let groups = group_by_folder.then(|| folder_groups::group_by_folder(&mut entries));
+            return Ok(SearchResultDto {
+                request_id: active.id,
+                entries,
+                mode_label: hit.mode_label,
+                total_count: hit.total_count.unwrap_or(0),
+                total_known: hit.total_count.is_some(),
+                total_approximate: false,
+                groups,
+            });
+        }
+        let cache_generation = state.search_result_cache.lock().generation();
+        let cacheable_state = !matches!(state.status.lock().state, IndexState::Indexing);
+
+        let execute_started = Instant::now();
+        let execution = execute_search_streaming(
+            &state,
+            query,
+            limit,
+            offset,
+            sort_by,
+            sort_dir,
+            partial.as_mut(),
+            Some(&active.token),
+        )?;
+        let execute_elapsed = execute_started.elapsed();
+        state.search_pressure.record(execute_elapsed);
+        note_db_activity(&state);
+        let execute_elapsed_ms = execute_elapsed.as_secs_f64() * 1000.0;
+
+        log_search(
+            &state.db_path,
+            &execution.query,
+            &execution.mode_label,
+            &execution.results,
+        );
+
+        if perf_log_enabled() {
+            let top: Vec<&str> = execution
+                .results
+                .iter()
+                .take(5)
+                .map(|entry| entry.name.as_str())
+                .collect();
+            perf_log(format!(
+                "search query={:?} mode={} sort={}/{} limit={} offset={} results={} elapsed_ms={:.3} top={:?}",
+                execution.query,
+                execution.mode_label,
+                execution.sort_by,
+                execution.sort_dir,
+                execution.effective_limit,
+                execution.offset,
+                execution.results.len(),
+                execute_elapsed_ms,
+                top,
+            ));
+        }
+
+        if active.token.is_cancelled() {
+            return Err(search_cancel::SEARCH_CANCELLED.to_string());
+        }
+        let count_started = Instant::now();
+        let (total_count, total_known, total_approximate) = if include_total {
+            match compute_total_count(&state, &execution) {
+                Some((v, true)) => (v, false, true),
+                Some((v, false)) => (v, true, false),
+                None => (0, false, false),
+            }
+        } else {
+            (0, false, false)
+        };
+        let count_elapsed_ms = if include_total {
+            count_started.elapsed().as_secs_f64() * 1000.0
+        } else {
+            0.0
+        };
+
+        let rpc_elapsed_ms = rpc_started.elapsed().as_secs_f64() * 1000.0;
+        if cfg!(debug_assertions) {
+            let db_ready = state.db_ready.load(AtomicOrdering::Acquire);
+            let indexing_active = state.indexing_active.load(AtomicOrdering::Acquire);
+            eprintln!(
+                "[rpc/search] total={:.3}ms execute={:.3}ms total_count={:.3}ms include_total={} query={:?} mode={} sort={}/{} limit={} offset={} results={} total_count={} total_known={} db_ready={} indexing_active={}",
+                rpc_elapsed_ms,
+                execute_elapsed_ms,
+                count_elapsed_ms,
+                include_total,
+                execution.query,
+                execution.mode_label,
+                execution.sort_by,
+                execution.sort_dir,
+                execution.effective_limit,
+                execution.offset,
+                execution.results.len(),
+                total_count,
+                total_known,
+                db_ready,
+                indexing_active,
+            );
+        }
+        let cacheable = cacheable_state && search_cache::is_cacheable_mode(&execution.mode_label);
+        if cacheable {
+            state.search_result_cache.lock().insert(
+                cache_generation,
+                cache_key.clone(),
+                search_cache::CachedSearch {
+                    mode_label: execution.mode_label.clone(),
+                    results: execution.results.clone(),
+                    total_count: total_known.then_some(total_count),
+                },
+            );
+        }
+        // After the cache insert, so the count's backfill finds the entry.
+        if include_total && execution.offset == 0 && !total_known {
+            spawn_exact_count(
+                app.clone(),
+                state.clone(),
+                active.id,
+                execution.query.clone(),
+                execution.sort_by.clone(),
+                cacheable.then_some((cache_generation, cache_key)),
+            );
+        }
+        let mut entries = execution.results;
+        if collapse_links {
+            link_dedup::collapse_links(&mut entries);
+        }
+        annotate_result_entries(&mut entries, spoken_labels, time_locale);
+        let groups = group_by_folder.then(|| folder_groups::group_by_folder(&mut entries));
+        Ok(SearchResultDto {
+            request_id: active.id,
+            entries,
+            mode_label: execution.mode_label,
+            total_count,
+            total_known,
+            total_approximate,
+            groups,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Exact follow-up for a search whose total came back approximate (or not at
+/// all): counts off the request thread, then emits `search_count_ready` and
+/// backfills the cached result, unless the request is cancelled first.
+fn spawn_exact_count(
+    app: AppHandle,
+    state: AppState,
+    request_id: u64,
+    query: String,
+    sort_by: String,
+    cache_slot: Option<(u64, search_cache::SearchCacheKey)>,
+) {
+    std::thread::spawn(move || {
+        // Shares the search's registration, so `cancel_search(request_id)`
+        // reaches this count whether or not the search has returned yet.
+        let active = state.search_cancels.begin(Some(request_id));
+        let Ok(conn) = pooled_search_connection(&state) else {
+            return;
+        };
+        set_search_progress_handler(&conn, Some(&active.token), None);
+        let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
+        let (total_count, _) =
+            count_query_matches(&conn, &state.home_dir, fts_ready, &query, &sort_by, None);
+        // An interrupted COUNT(*) reads as 0; never report it.
+        if active.token.is_cancelled() {
+            return;
+        }
+        if let Some((generation, key)) = cache_slot {
+            state
+                .search_result_cache
+                .lock()
+                .set_total(generation, &key, total_count);
+        }
+        app.emit(
+            "search_count_ready",
+            SearchCountReadyEvent {
+                request_id,
+                total_count,
+            },
+        )
+        .ok();
+    });
+}
+
+/// Abandon a `search` the user has typed past. Returns true when it was still
+/// running; a cancel that arrives first is remembered for when it starts.
+#[tauri::command]
+fn cancel_search(request_id: u64, state: State<'_, AppState>) -> bool {
+    state.search_cancels.cancel(request_id)
+}
+
+/// Total number of entries matching `query` without fetching any rows, for
+/// live counts in dashboards and rules ("37 screenshots this week"). Planned
+/// exactly like the total next to `search` results (default name sort).
+fn count_blocking(state: &AppState, query: &str) -> AppResult<u32> {
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        return Err("Index is not ready yet.".to_string());
+    }
+    // While a full scan's in-memory index is live the DB is still being
+    // bulk-written, so a SQL count would undercount.
+    if state.mem_index.read().is_some() && state.indexing_active.load(AtomicOrdering::Acquire) {
+        return Err("Index is still being written; try again shortly.".to_string());
+    }
+    let conn = pooled_search_connection(state)?;
+    let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
+    let (count, _) = count_query_matches(
+        &conn,
+        &state.home_dir,
+        fts_ready,
+        query.trim(),
+        "name",
+        None,
+    );
+    Ok(count)
+}
+
+#[tauri::command]
+async fn count(query: String, state: State<'_, AppState>) -> AppResult<u32> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || count_blocking(&state, &query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportResultDto {
+    path: String,
+    rows: u64,
+    elapsed_ms: u64,
+}
+
+/// Write every row `query` matches, not just the loaded page, to `dest` in
+/// `format`. Pages through `execute_search` so order and ignore filtering
+/// match the result list, writing each page out before fetching the next.
+fn export_results_blocking(
+    state: &AppState,
+    query: &str,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    format: export::ExportFormat,
+    columns: Vec<export::ExportColumn>,
+    dest: &Path,
+) -> AppResult<ExportResultDto> {
+    let started = Instant::now();
+    let file = fs::File::create(dest).map_err(|e| format!("{}: {e}", dest.display()))?;
+    let write_all = || -> AppResult<u64> {
+        let mut writer =
+            export::ResultWriter::new(std::io::BufWriter::new(file), format, columns)?;
+        let mut offset = 0u32;
+        loop {
+            let page = execute_search(
+                state,
+                query.to_string(),
+                Some(MAX_LIMIT),
+                Some(offset),
+                sort_by.clone(),
+                sort_dir.clone(),
+            )?;
+            for entry in &page.results {
+                writer.write_row(entry)?;
+            }
+            if page.results.is_empty() || (page.results.len() as u32) < page.effective_limit {
+                break;
+            }
+            offset = offset.saturating_add(page.results.len() as u32);
+        }
+        writer.finish()
+    };
+    let rows = write_all().inspect_err(|_| {
+        let _ = fs::remove_file(dest);
+    })?;
+    Ok(ExportResultDto {
+        path: dest.to_string_lossy().to_string(),
+        rows,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+async fn save_search(
+    name: String,
+    query: String,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    filters: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+) -> AppResult<saved_searches::SavedSearchDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        let saved = saved_searches::save_search(
+            &conn,
+            &name,
+            &query,
+            sort_by.as_deref(),
+            sort_dir.as_deref(),
+            filters.as_ref(),
+        )?;
+        refresh_jump_list(&state);
+        Ok(saved)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn list_saved_searches(
+    state: State<'_, AppState>,
+) -> AppResult<Vec<saved_searches::SavedSearchDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = db_connection(&state.db_path)?;
+        saved_searches::list_saved_searches(&conn)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn delete_saved_search(
+    id: i64,
+    state: State<'_, AppState>,
+) -> AppResult<Option<saved_searches::SavedSearchDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        let deleted = saved_searches::delete_saved_search(&conn, id)?;
+        refresh_jump_list(&state);
+        Ok(deleted)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Rebuild the Windows taskbar jump list from saved and recent searches.
+/// Failures are logged; the jump list is a convenience.
+fn refresh_jump_list(state: &AppState) {
+    #[cfg(target_os = "windows")]
+    {
+        let result = db_connection(&state.db_path).and_then(|conn| {
+            let saved: Vec<(String, String)> = saved_searches::list_saved_searches(&conn)?
+                .into_iter()
+                .map(|s| (s.name, s.query))
+                .collect();
+            let recent: Vec<String> =
+                search_history::recent_searches(&conn, search_history::DEFAULT_HISTORY_LIMIT)?
+                    .into_iter()
+                    .map(|h| h.query)
+                    .collect();
+            win::jump_list::update(search_history::LAUNCH_QUERY_FLAG, &saved, &recent)
+        });
+        if let Err(e) = result {
+            eprintln!("[jump_list] update failed: {e}");
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = state;
+}
+
+#[tauri::command]
+async fn record_search_history(query: String, state: State<'_, AppState>) -> AppResult<()> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        search_history::record_search(&conn, &query)?;
+        refresh_jump_list(&state);
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn list_search_history(
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<search_history::SearchHistoryDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = db_connection(&state.db_path)?;
+        search_history::recent_searches(
+            &conn,
+            limit.unwrap_or(search_history::DEFAULT_HISTORY_LIMIT),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn clear_search_history(state: State<'_, AppState>) -> AppResult<usize> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        let cleared = search_history::clear_search_history(&conn)?;
+        refresh_jump_list(&state);
+        Ok(cleared)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The `--search` query the app was launched with, returned once.
+#[tauri::command]
+fn take_launch_query(state: State<'_, AppState>) -> Option<String> {
+    state.launch_query.lock().take()
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchQueryDto {
+    watch_id: String,
+    /// The first page of results; diffs are relative to it.
+    results: Vec<EntryDto>,
+}
+
+/// Run `query` and keep watching it: watcher batches that change matching
+/// entries emit `query_watch_diff` with added/updated/removed entries until
+/// `unwatch_query`.
+#[tauri::command]
+async fn watch_query(
+    query: String,
+    limit: Option<u32>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<WatchQueryDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if query.trim().is_empty() {
+            return Err("Query is empty.".to_string());
+        }
+        let executed = execute_search(&state, query, limit, None, sort_by, sort_dir)?;
+        let watch_id = state.live_queries.watch(
+            &executed.query,
+            executed.results.iter().map(|e| e.path.clone()),
+        );
+        Ok(WatchQueryDto {
+            watch_id,
+            results: executed.results,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Returns false when `watch_id` was not being watched.
+#[tauri::command]
+async fn unwatch_query(watch_id: String, state: State<'_, AppState>) -> AppResult<bool> {
+    Ok(state.live_queries.unwatch(&watch_id))
+}
+
+/// Pin `path` so it ranks first whenever a search matches it.
+#[tauri::command]
+async fn pin_entry(path: String, state: State<'_, AppState>) -> AppResult<pins::PinnedEntryDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = dir_size::normalize_dir(path.trim());
+        if path.is_empty() || !Path::new(&path).exists() {
+            return Err(format!("Path does not exist: {path}"));
+        }
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        let pin = pins::pin_entry(&conn, &path)?;
+        state.pinned_paths.write().insert(path);
+        state.search_result_cache.lock().clear();
+        Ok(pin)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Returns false when `path` was not pinned.
+#[tauri::command]
+async fn unpin_entry(path: String, state: State<'_, AppState>) -> AppResult<bool> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = dir_size::normalize_dir(path.trim());
+        let _writing = state.db_writes.enter()?;
+        let conn = db_connection(&state.db_path)?;
+        let removed = pins::unpin_entry(&conn, &path)?;
+        if state.pinned_paths.write().remove(&path) || removed {
+            state.search_result_cache.lock().clear();
+        }
+        Ok(removed)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn list_pins(state: State<'_, AppState>) -> AppResult<Vec<pins::PinnedEntryDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let conn = db_connection(&state.db_path)?;
+        pins::list_pins(&conn)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Export the full result set of `query` as CSV, TSV or JSON. `columns`
+/// picks and orders path/name/size/mtime/ext (all by default); without
+/// `dest_path` the file lands in ~/Downloads.
+#[tauri::command]
+async fn export_results(
+    query: String,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
+    format: String,
+    columns: Option<Vec<String>>,
+    dest_path: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<ExportResultDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let format = export::ExportFormat::parse(&format)?;
+        let columns = export::parse_columns(columns.as_deref())?;
+        let dest = match dest_path {
+            Some(p) => PathBuf::from(p),
+            None => {
+                let downloads = state.home_dir.join("Downloads");
+                let dir = if downloads.is_dir() { downloads } else { state.home_dir.clone() };
+                dir.join(format!("everything-export-{}.{}", now_epoch(), format.extension()))
+            }
+        };
+        export_results_blocking(&state, &query, sort_by, sort_dir, format, columns, &dest)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// "Probable duplicates by name": backup-named copies ("x (1).pdf",
+/// "x copy.pdf", "~$x.docx") grouped by the original they came from. Name
+/// patterns only — nothing is read or hashed.
+#[tauri::command]
+async fn find_probable_duplicates(
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<backups::DuplicateGroupDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if !state.db_ready.load(AtomicOrdering::Acquire) {
+            return Err("Index is not ready yet.".to_string());
+        }
+        let limit = limit.unwrap_or(200).clamp(1, 5000) as usize;
+        let conn = pooled_search_connection(&state)?;
+        backups::find_probable_duplicates(&conn, limit)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FileHashDto {
+    path: String,
+    algorithm: String,
+    hash: String,
+    size: u64,
+    elapsed_ms: u64,
+}
+
+/// MD5, SHA-256 or BLAKE3 of a file. Files over `HASH_PROGRESS_MIN_BYTES`
+/// emit `hash_progress`; `cancel_hash` with the same request id stops it.
+#[tauri::command]
+async fn compute_hash(
+    app: AppHandle,
+    path: String,
+    algorithm: Option<String>,
+    request_id: Option<u64>,
+    state: State<'_, AppState>,
+) -> AppResult<FileHashDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let algorithm = file_hash::HashAlgorithm::parse(algorithm.as_deref().unwrap_or("sha256"))?;
+        let active = state.hash_cancels.begin(request_id);
+        let mut last_emit: Option<Instant> = None;
+        let mut size = 0;
+        let hash = file_hash::hash_file(
+            Path::new(&path),
+            algorithm,
+            Some(&active.token),
+            |done, total| {
+                size = total;
+                if total < HASH_PROGRESS_MIN_BYTES
+                    || (done < total
+                        && last_emit.is_some_and(|t| t.elapsed() < HASH_PROGRESS_INTERVAL))
+                {
+                    return;
+                }
+                last_emit = Some(Instant::now());
+                let _ = app.emit(
+                    "hash_progress",
+                    HashProgressEvent {
+                        request_id: active.id,
+                        path: path.clone(),
+                        bytes_hashed: done,
+                        total_bytes: total,
+                    },
+                );
+            },
+        )?;
+        Ok(FileHashDto {
+            path,
+            algorithm: algorithm.label().to_string(),
+            hash,
+            size,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The biggest files under `root` (default: the scan root), straight from
+/// the index, with optional totals per top-level entry of `root` and per
+/// extension.
+#[tauri::command]
+async fn largest_entries(
+    root: Option<String>,
+    limit: Option<usize>,
+    by_folder: Option<bool>,
+    by_extension: Option<bool>,
+    state: State<'_, AppState>,
+) -> AppResult<disk_usage::DiskUsageDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        if !state.db_ready.load(AtomicOrdering::Acquire) {
+            return Err("Index is not ready yet.".to_string());
+        }
+        let root = root
+            .map(|r| dir_size::normalize_dir(r.trim()))
+            .filter(|r| !r.is_empty())
+            .unwrap_or_else(|| dir_size::normalize_dir(&state.scan_root.to_string_lossy()));
+        let limit = limit
+            .unwrap_or(disk_usage::DEFAULT_LARGEST_LIMIT)
+            .clamp(1, disk_usage::MAX_LARGEST_LIMIT);
+        let conn = pooled_search_connection(&state)?;
+        let (total_size, file_count) = disk_usage::usage_totals(&conn, &root)?;
+        let largest = disk_usage::largest_files(&conn, &root, limit)?;
+        let by_folder = if by_folder.unwrap_or(false) {
+            Some(disk_usage::usage_by_top_folder(&conn, &root, limit)?)
+        } else {
+            None
+        };
+        let by_extension = if by_extension.unwrap_or(false) {
+            Some(disk_usage::usage_by_extension(&conn, &root, limit)?)
+        } else {
+            None
+        };
+        Ok(disk_usage::DiskUsageDto {
+            root,
+            total_size,
+            file_count,
+            largest,
+            by_folder,
+            by_extension,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Files modified between `since` (default: a week ago) and `until`
+/// (default: now), newest first, under `root` if given. Caches, logs and
+/// temp files are left out unless `include_noise` is set.
+#[tauri::command]
+async fn recent_files(
+    root: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+    limit: Option<usize>,
+    include_noise: Option<bool>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<EntryDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if !state.db_ready.load(AtomicOrdering::Acquire) {
+            return Err("Index is not ready yet.".to_string());
+        }
+        let now = now_epoch();
+        // Files dated in the future (bad clocks, archives) would otherwise
+        // sit at the top forever.
+        let until = until.unwrap_or(now + 60);
+        let since = since.unwrap_or(now - recent::DEFAULT_RECENT_WINDOW_SECS);
+        let root = root
+            .map(|r| dir_size::normalize_dir(r.trim()))
+            .filter(|r| !r.is_empty());
+        let limit = limit
+            .unwrap_or(recent::DEFAULT_RECENT_LIMIT)
+            .clamp(1, recent::MAX_RECENT_LIMIT);
+        let conn = pooled_search_connection(&state)?;
+        recent::recent_files(
+            &conn,
+            root.as_deref(),
+            since,
+            until,
+            limit,
+            include_noise.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Camera EXIF, audio tags, codecs and duration, or PDF page count for the
+/// details pane, read from the file on demand. Cached per (path, mtime,
+/// size) so reselecting a file doesn't reread it.
+#[tauri::command]
+async fn get_extended_metadata(
+    path: String,
+    state: State<'_, AppState>,
+) -> AppResult<media_meta::ExtendedMetadataDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = PathBuf::from(path);
+        let key = media_meta::MetadataKey::for_path(&path)?;
+        if let Some(hit) = state.metadata_cache.lock().get(&key) {
+            return Ok(hit);
+        }
+        let meta = media_meta::extended_metadata(&path)?;
+        state.metadata_cache.lock().insert(key, meta.clone());
+        Ok(meta)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Platform "Get Info" properties of `path`: symlink target, hard links,
+/// permissions and ACL, allocated size, quarantine flag and xattrs (macOS),
+/// alternate data streams (Windows).
+#[tauri::command]
+async fn get_properties(
+    path: String,
+    state: State<'_, AppState>,
+) -> AppResult<properties::PropertiesDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // Hard links are looked up in the index; without one only the
+        // link count is reported.
+        let conn = state
+            .db_ready
+            .load(AtomicOrdering::Acquire)
+            .then(|| pooled_search_connection(&state).ok())
+            .flatten();
+        properties::properties(Path::new(&path), conn.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-                        // Deduplicate (Phase A prefix results overlap with Phase B contains)
-                        let mut seen = std::collections::HashSet::new();
-                        results.retain(|e| seen.insert(e.path.clone()));
-                        results.truncate(effective_limit as usize);
-                    }
-                }
-            }
-        }
-    }
-    Ok(results)
+/// Stop a running `compute_hash`. Returns true when it was still running.
+#[tauri::command]
+fn cancel_hash(request_id: u64, state: State<'_, AppState>) -> bool {
+    state.hash_cancels.cancel(request_id)
 }
 
-fn execute_search(
-    state: &AppState,
-    query: String,
-    limit: Option<u32>,
-    offset: Option<u32>,
-    sort_by: Option<String>,
-    sort_dir: Option<String>,
-) -> AppResult<SearchExecution> {
-    let query = query.trim().to_string();
-    let effective_limit = effective_search_limit(&query, limit, DEFAULT_LIMIT);
-    let offset = offset.unwrap_or(0);
+/// Find files with identical content among indexed files under `root` (or
+/// everywhere) of at least `min_size` bytes. Groups stream out as
+/// "duplicate_group" events while the scan runs; the result has the totals.
+#[tauri::command]
+async fn find_duplicates(
+    app: AppHandle,
+    root: Option<String>,
+    min_size: Option<u64>,
+    request_id: Option<u64>,
+    state: State<'_, AppState>,
+) -> AppResult<FindDuplicatesDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        if !state.db_ready.load(AtomicOrdering::Acquire) {
+            return Err("Index is not ready yet.".to_string());
+        }
+        let root = root
+            .map(|r| dir_size::normalize_dir(r.trim()))
+            .filter(|r| !r.is_empty());
+        let active = state.duplicate_cancels.begin(request_id);
+        let candidates = {
+            let conn = pooled_search_connection(&state)?;
+            duplicates::size_candidates(
+                &conn,
+                root.as_deref(),
+                min_size.unwrap_or(duplicates::DEFAULT_MIN_DUPLICATE_SIZE),
+            )?
+        };
+        let scan = duplicates::find_duplicates(candidates, Some(&active.token), |group, total| {
+            let _ = app.emit(
+                "duplicate_group",
+                DuplicateGroupEvent {
+                    request_id: active.id,
+                    group: group.clone(),
+                    reclaimable_bytes: total,
+                },
+            );
+        })?;
+        Ok(FindDuplicatesDto {
+            request_id: active.id,
+            groups: scan.groups,
+            files: scan.files,
+            reclaimable_bytes: scan.reclaimable_bytes,
+            candidates: scan.candidates,
+            cancelled: scan.cancelled,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    let sort_by = sort_by.unwrap_or_else(|| "name".to_string());
-    let sort_dir = sort_dir.unwrap_or_else(|| "asc".to_string());
-    let (runtime_ignored_roots, runtime_ignored_patterns) = cached_effective_ignore_rules(state);
+/// Stop a running `find_duplicates`; groups already reported stand.
+#[tauri::command]
+fn cancel_find_duplicates(request_id: u64, state: State<'_, AppState>) -> bool {
+    state.duplicate_cancels.cancel(request_id)
+}
 
-    #[cfg(target_os = "macos")]
-    if !state.db_ready.load(AtomicOrdering::Acquire) {
-        let spotlight = mac::spotlight_search::search_spotlight(&state.home_dir, &query);
-        let mode_label = if spotlight.entries.is_empty() {
-            "db_not_ready".to_string()
-        } else if spotlight.timed_out {
-            "spotlight_timeout".to_string()
+/// Recursive size of `path`, from the index when it covers the directory and
+/// from a live walk otherwise.
+#[tauri::command]
+async fn get_dir_size(path: String, state: State<'_, AppState>) -> AppResult<dir_size::DirSizeDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = Instant::now();
+        let dir = dir_size::normalize_dir(path.trim());
+        if dir.is_empty() {
+            return Err("Path is empty.".to_string());
+        }
+        let indexed = if state.db_ready.load(AtomicOrdering::Acquire) {
+            let conn = pooled_search_connection(&state)?;
+            dir_size::indexed_dir_totals(&conn, &dir)?
         } else {
-            "spotlight".to_string()
+            None
         };
-        perf_log(format!(
-            "spotlight_fallback db_not_ready query={:?} results={} timed_out={}",
-            query,
-            spotlight.entries.len(),
-            spotlight.timed_out
-        ));
-        return Ok(SearchExecution {
-            query,
-            sort_by,
-            sort_dir,
-            effective_limit,
-            offset,
-            mode_label,
-            results: spotlight.entries,
-        });
-    }
-
-    let is_indexing = matches!(state.status.lock().state, IndexState::Indexing);
-    let fts_ready = state.fts_ready.load(AtomicOrdering::Acquire);
+        let (totals, source) = match indexed {
+            // A running scan may not have reached every subtree yet.
+            Some(totals) if state.indexing_active.load(AtomicOrdering::Acquire) => {
+                (totals, "partial")
+            }
+            Some(totals) => (totals, "index"),
+            None => (dir_size::walk_dir_totals(Path::new(&dir))?, "walk"),
+        };
+        Ok(dir_size::DirSizeDto {
+            path: dir,
+            size: totals.size,
+            file_count: totals.file_count,
+            dir_count: totals.dir_count,
+            source: source.to_string(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    // Only a placeholder for the DB-unavailable path; every successful path
-    // wholly reassigns it (run_db_search owns the real allocation).
-    let mut results = Vec::new();
-    let mode = parse_query(&query);
-    let is_name_mode = matches!(&mode, SearchMode::NameSearch { .. });
-    let allow_find_fallback = !is_indexing
-        && matches!(
-            &mode,
-            SearchMode::GlobName { .. } | SearchMode::ExtSearch { .. }
+#[tauri::command]
+async fn check_index_integrity(
+    sample_size: Option<u32>,
+    state: State<'_, AppState>,
+) -> AppResult<integrity::IntegrityReportDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if !state.db_ready.load(AtomicOrdering::Acquire) {
+            return Err("Index is not ready yet.".to_string());
+        }
+        let sample_size = sample_size.unwrap_or(200).min(5000);
+        let conn = db_connection(&state.db_path)?;
+        let indexing = state.indexing_active.load(AtomicOrdering::Acquire);
+        let idle = !indexing && state.watcher_backlog.load(AtomicOrdering::Relaxed) == 0;
+        let report = integrity::check_index_integrity(
+            &conn,
+            state.fts_ready.load(AtomicOrdering::Acquire),
+            indexing,
+            idle,
+            sample_size,
+        )?;
+        eprintln!(
+            "[integrity] ok={} sqlite_errors={} fts_ok={:?} meta_issues={} sampled={} missing={} type_mismatch={}",
+            report.ok,
+            report.sqlite_errors.len(),
+            report.fts_ok,
+            report.meta_issues.len(),
+            report.sampled,
+            report.missing_count,
+            report.type_mismatch_count
         );
-    let mut mode_label = mode.label().to_string();
+        Ok(report)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    // Fast path: search in-memory index if available (DB upsert still in progress)
+/// Preview `path`. On macOS this opens the in-app Quick Look panel over
+/// `paths` (the selection, defaulting to just `path`) so arrow keys move
+/// through it; calling it again for the same items closes the panel.
+#[tauri::command]
+async fn quick_look(path: String, paths: Option<Vec<String>>, app: AppHandle) -> AppResult<()> {
+    #[cfg(target_os = "macos")]
     {
-        let guard = state.mem_index.read();
-        if let Some(ref mi) = *guard {
-            let mem_results = mem_search::search_mem_index(
-                mi, &query, &mode, effective_limit, offset, &sort_by, &sort_dir,
-            );
-            mode_label = format!("mem_{mode_label}");
-            return Ok(SearchExecution {
-                query,
-                sort_by,
-                sort_dir,
-                effective_limit,
-                offset,
-                mode_label,
-                results: mem_results,
-            });
-        }
+        let paths = paths
+            .filter(|p| p.contains(&path))
+            .unwrap_or_else(|| vec![path.clone()]);
+        let index = paths.iter().position(|p| *p == path).unwrap_or(0);
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Result<(), String>>(1);
+        app.run_on_main_thread(move || {
+            let _ = tx.send(mac::quick_look::toggle(&paths, index));
+        })
+        .map_err(|e| e.to_string())?;
+        tauri::async_runtime::spawn_blocking(move || {
+            rx.recv().map_err(|e| format!("quick look channel: {e}"))?
+        })
+        .await
+        .map_err(|e| e.to_string())?
     }
-
-    if is_name_mode && !is_indexing && offset == 0 {
-        if let Some(cache_hit) = negative_name_cache_lookup(state, &query) {
-            if cache_hit.age >= WATCH_DEBOUNCE
-                && cache_hit.age <= NEGATIVE_CACHE_FALLBACK_WINDOW
-                && !cache_hit.fallback_checked
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (paths, app);
+        tauri::async_runtime::spawn_blocking(move || {
+            #[cfg(target_os = "windows")]
             {
-                let fallback_results = find_search(
-                    &state.home_dir,
-                    &runtime_ignored_roots,
-                    &runtime_ignored_patterns,
-                    &query,
-                    effective_limit as usize,
-                    &sort_by,
-                    &sort_dir,
-                );
-                if !fallback_results.is_empty() {
-                    remove_negative_name_query(state, &cache_hit.query_lower);
-                    perf_log(format!(
-                        "search_negative_cache_fallback_hit query={:?} age_ms={} results={}",
-                        query,
-                        cache_hit.age.as_millis(),
-                        fallback_results.len()
-                    ));
-                    return Ok(SearchExecution {
-                        query,
-                        sort_by,
-                        sort_dir,
-                        effective_limit,
-                        offset,
-                        mode_label: "find_fallback".to_string(),
-                        results: fallback_results,
-                    });
-                }
-                mark_negative_name_fallback_checked(state, &cache_hit.query_lower);
+                Command::new("explorer")
+                    .arg(&path)
+                    .spawn()
+                    .map_err(|e| e.to_string())?;
             }
-
-            perf_log(format!(
-                "search_negative_cache_hit query={:?} age_ms={}",
-                query,
-                cache_hit.age.as_millis()
-            ));
-            return Ok(SearchExecution {
-                query,
-                sort_by,
-                sort_dir,
-                effective_limit,
-                offset,
-                mode_label: "name_neg_cache".to_string(),
-                results: Vec::new(),
-            });
-        }
+            #[cfg(not(target_os = "windows"))]
+            {
+                Command::new("xdg-open")
+                    .arg(&path)
+                    .spawn()
+                    .map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
     }
+}
 
+/// Show the macOS Share menu (AirDrop, Mail, Messages, …) for `paths`,
+/// anchored at window-relative (`x`, `y`) like `show_context_menu`.
+#[tauri::command]
+async fn share_items(paths: Vec<String>, x: f64, y: f64, app: AppHandle) -> AppResult<()> {
     #[cfg(target_os = "macos")]
-    let db_unavailable;
-    match pooled_search_connection(state) {
-        Ok(conn) => {
-            #[cfg(target_os = "macos")]
-            { db_unavailable = false; }
-            results = run_db_search(
-                &conn,
-                &state.home_dir,
-                fts_ready,
-                &mode,
-                &query,
-                effective_limit,
-                offset,
-                &sort_by,
-                &sort_dir,
-            )?;
+    {
+        let window = app
+            .get_webview_window("main")
+            .ok_or_else(|| "Main window not found".to_string())?;
+        let (tx, rx) = std::sync::mpsc::sync_channel::<Result<(), String>>(1);
+        app.run_on_main_thread(move || {
+            let result = window
+                .ns_view()
+                .map_err(|e| e.to_string())
+                .and_then(|view| unsafe { mac::share::show_picker(view, &paths, x, y) });
+            let _ = tx.send(result);
+        })
+        .map_err(|e| e.to_string())?;
+        tauri::async_runtime::spawn_blocking(move || {
+            rx.recv().map_err(|e| format!("share channel: {e}"))?
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (paths, x, y, app);
+        Err("Sharing is only available on macOS.".to_string())
+    }
+}
 
-            if results.is_empty() && !query.is_empty() && offset == 0 && allow_find_fallback {
-                results = find_search(
-                    &state.home_dir,
-                    &runtime_ignored_roots,
-                    &runtime_ignored_patterns,
-                    &query,
-                    effective_limit as usize,
-                    &sort_by,
-                    &sort_dir,
-                );
-                mode_label = "find_fallback".to_string();
+/// Error prefix `open` uses when a path's content is only in the cloud
+/// (iCloud dataless file, OneDrive/Dropbox placeholder) and the caller hasn't
+/// agreed to download it. The rest of the message is the path.
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+const CLOUD_ONLY_OPEN_ERROR: &str = "cloud-only:";
+
+#[cfg(target_os = "macos")]
+fn is_cloud_only_path(path: &Path) -> bool {
+    mac::cloud::is_dataless_path(path)
+}
+
+#[cfg(target_os = "windows")]
+fn is_cloud_only_path(path: &Path) -> bool {
+    win::cloud::is_placeholder_path(path)
+}
+
+#[tauri::command]
+async fn open(paths: Vec<String>, materialize: Option<bool>) -> AppResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        open_paths_blocking(&paths, materialize.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Open each path with its default app; also used by the MCP `open` tool.
+fn open_paths_blocking(paths: &[String], materialize: bool) -> AppResult<()> {
+    #[cfg(any(target_os = "macos", target_os = "windows"))]
+    if !materialize {
+        if let Some(path) = paths.iter().find(|p| is_cloud_only_path(Path::new(p))) {
+            return Err(format!("{CLOUD_ONLY_OPEN_ERROR}{path}"));
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = materialize;
+
+    for path in paths {
+        #[cfg(target_os = "macos")]
+        {
+            if mac::workspace::open_path(path) {
+                continue;
+            }
+            if Path::new(path).is_dir() {
+                mac::workspace::reveal_paths(std::slice::from_ref(path));
+                continue;
+            }
+            // Most likely no app claims this file type. Hand the open to
+            // Finder, which shows the system "no application set to open"
+            // chooser dialog.
+            let fallback = Command::new("open")
+                .args(["-a", "Finder", path])
+                .status()
+                .map_err(|e| e.to_string())?;
+            if !fallback.success() {
+                return Err(format!("Failed to open: {path}"));
             }
         }
-        Err(_) => {
-            #[cfg(target_os = "macos")]
-            { db_unavailable = true; }
-            perf_log(format!("search_db_unavailable query={:?}", query));
+        #[cfg(target_os = "windows")]
+        win::shell_open::open_path(path)?;
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        {
+            let status = Command::new("xdg-open")
+                .arg(path)
+                .status()
+                .map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!("Failed to open: {path}"));
+            }
         }
     }
 
+    Ok(())
+}
+
+fn reveal_in_finder_impl(paths: Vec<String>) -> AppResult<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
     #[cfg(target_os = "macos")]
-    if !query.is_empty() && offset == 0 {
-        let should_spotlight = if results.is_empty() {
-            true
-        } else if results.len() >= effective_limit as usize {
-            false
-        } else {
-            is_indexing || db_unavailable
-        };
-        if should_spotlight {
-            let spotlight = mac::spotlight_search::search_spotlight(&state.home_dir, &query);
-            if !spotlight.entries.is_empty() {
-                if results.is_empty() {
-                    perf_log(format!(
-                        "spotlight_fallback empty_results query={:?} results={} timed_out={}",
-                        query,
-                        spotlight.entries.len(),
-                        spotlight.timed_out
-                    ));
-                    results = spotlight.entries;
-                    mode_label = if spotlight.timed_out {
-                        "spotlight_timeout".to_string()
-                    } else {
-                        "spotlight".to_string()
-                    };
-                } else {
-                    let existing_paths: std::collections::HashSet<String> =
-                        results.iter().map(|e| e.path.clone()).collect();
-                    let mut merged_count = 0usize;
-                    for entry in spotlight.entries {
-                        if !existing_paths.contains(&entry.path) {
-                            results.push(entry);
-                            merged_count += 1;
-                        }
-                    }
-                    if merged_count > 0 {
-                        perf_log(format!(
-                            "spotlight_merge indexing query={:?} merged={} timed_out={}",
-                            query, merged_count, spotlight.timed_out
-                        ));
-                        mode_label = format!("{}_+spotlight", mode_label);
-                    }
-                }
-            }
-        }
+    mac::workspace::reveal_paths(&paths);
+
+    #[cfg(target_os = "windows")]
+    for path in &paths {
+        win::shell_open::reveal_path(path)?;
     }
 
-    results = filter_ignored_entries(results, &runtime_ignored_roots, &runtime_ignored_patterns);
-    results.truncate(effective_limit as usize);
-    if offset == 0 {
-        if sort_by == "name" {
-            sort_entries_with_relevance(&mut results, &query, &sort_by, &sort_dir);
-        } else {
-            sort_entries(&mut results, &sort_by, &sort_dir);
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        for path in &paths {
+            let target = Path::new(path);
+            let dir = if target.is_dir() {
+                path.as_str()
+            } else {
+                target
+                    .parent()
+                    .map(|p| p.to_str().unwrap_or("/"))
+                    .unwrap_or("/")
+            };
+            let _ = Command::new("xdg-open").arg(dir).status();
         }
     }
-    if is_name_mode && !is_indexing && offset == 0 && results.is_empty() && !query.is_empty() {
-        remember_negative_name_query(state, &query);
+
+    Ok(())
+}
+
+fn copy_with_command(program: &str, args: &[&str], text: &str) -> AppResult<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(text.as_bytes())
+            .map_err(|e| format!("Failed to write to clipboard: {e}"))?;
+    } else {
+        return Err("Cannot open clipboard input stream.".to_string());
     }
 
-    Ok(SearchExecution {
-        query,
-        sort_by,
-        sort_dir,
-        effective_limit,
-        offset,
-        mode_label,
-        results,
-    })
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to wait for {program}: {e}"))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} execution failed."))
+    }
 }
 
-#[tauri::command]
-async fn search(
-    _app: AppHandle,
-    query: String,
-    limit: Option<u32>,
-    offset: Option<u32>,
-    sort_by: Option<String>,
-    sort_dir: Option<String>,
-    include_total: Option<bool>,
-    state: State<'_, AppState>,
-) -> AppResult<SearchResultDto> {
-    let state = state.inner().clone();
-    tauri::async_runtime::spawn_blocking(move || {
-        let rpc_started = Instant::now();
-        let execute_started = Instant::now();
-        let execution = execute_search(&state, query, limit, offset, sort_by, sort_dir)?;
-        let execute_elapsed_ms = execute_started.elapsed().as_secs_f64() * 1000.0;
+#[cfg(target_os = "macos")]
+fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
+    copy_with_command("pbcopy", &[], text)
+}
 
-        log_search(
-            &state.db_path,
-            &execution.query,
-            &execution.mode_label,
-            &execution.results,
-        );
+#[cfg(target_os = "windows")]
+fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
+    copy_with_command("cmd", &["/C", "clip"], text)
+}
 
-        if perf_log_enabled() {
-            let top: Vec<&str> = execution
-                .results
-                .iter()
-                .take(5)
-                .map(|entry| entry.name.as_str())
-                .collect();
-            perf_log(format!(
-                "search query={:?} mode={} sort={}/{} limit={} offset={} results={} elapsed_ms={:.3} top={:?}",
-                execution.query,
-                execution.mode_label,
-                execution.sort_by,
-                execution.sort_dir,
-                execution.effective_limit,
-                execution.offset,
-                execution.results.len(),
-                execute_elapsed_ms,
-                top,
-            ));
-        }
+#[cfg(all(unix, not(target_os = "macos")))]
+fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
+    let mut last_error = None;
 
-        let include_total = include_total.unwrap_or(true);
-        let count_started = Instant::now();
-        let (total_count, total_known) = if include_total {
-            match compute_total_count(&state, &execution) {
-                Some(v) => (v, true),
-                None => (0, false),
-            }
-        } else {
-            (0, false)
-        };
-        let count_elapsed_ms = if include_total {
-            count_started.elapsed().as_secs_f64() * 1000.0
-        } else {
-            0.0
-        };
+    match copy_with_command("wl-copy", &[], text) {
+        Ok(()) => return Ok(()),
+        Err(err) => last_error = Some(err),
+    }
+    match copy_with_command("xclip", &["-selection", "clipboard"], text) {
+        Ok(()) => return Ok(()),
+        Err(err) => last_error = Some(err),
+    }
+    match copy_with_command("xsel", &["--clipboard", "--input"], text) {
+        Ok(()) => return Ok(()),
+        Err(err) => last_error = Some(err),
+    }
 
-        let rpc_elapsed_ms = rpc_started.elapsed().as_secs_f64() * 1000.0;
-        if cfg!(debug_assertions) {
-            let db_ready = state.db_ready.load(AtomicOrdering::Acquire);
-            let indexing_active = state.indexing_active.load(AtomicOrdering::Acquire);
-            eprintln!(
-                "[rpc/search] total={:.3}ms execute={:.3}ms total_count={:.3}ms include_total={} query={:?} mode={} sort={}/{} limit={} offset={} results={} total_count={} total_known={} db_ready={} indexing_active={}",
-                rpc_elapsed_ms,
-                execute_elapsed_ms,
-                count_elapsed_ms,
-                include_total,
-                execution.query,
-                execution.mode_label,
-                execution.sort_by,
-                execution.sort_dir,
-                execution.effective_limit,
-                execution.offset,
-                execution.results.len(),
-                total_count,
-                total_known,
-                db_ready,
-                indexing_active,
-            );
-        }
-        Ok(SearchResultDto {
-            entries: execution.results,
-            mode_label: execution.mode_label,
-            total_count,
-            total_known,
-        })
+    Err(last_error.unwrap_or_else(|| {
+        "No supported clipboard tool found. Please install wl-copy, xclip, or xsel.".to_string()
+    }))
+}
+
+#[tauri::command]
+async fn open_with(path: String) -> AppResult<()> {
+    tauri::async_runtime::spawn_blocking(move || reveal_in_finder_impl(vec![path]))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Cleaned-up paths for a drag-out of the selection (see `drag_out`).
+#[tauri::command]
+fn prepare_drag(paths: Vec<String>) -> AppResult<Vec<String>> {
+    let items = drag_out::drag_items(&paths);
+    if items.is_empty() {
+        return Err("The selected files no longer exist.".to_string());
+    }
+    Ok(items)
+}
+
+#[tauri::command]
+async fn reveal_in_finder(paths: Vec<String>) -> AppResult<()> {
+    tauri::async_runtime::spawn_blocking(move || reveal_in_finder_impl(paths))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Open a terminal in `path` (or its folder, for files). `terminal` names the
+/// app to use — e.g. "iTerm", "pwsh", "kitty" — and defaults to the
+/// platform's standard terminal.
+#[tauri::command]
+async fn open_terminal_here(path: String, terminal: Option<String>) -> AppResult<()> {
+    tauri::async_runtime::spawn_blocking(move || {
+        terminal::open_terminal(terminal.as_deref(), Path::new(&path))
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-async fn quick_look(path: String) -> AppResult<()> {
+fn copy_paths(paths: Vec<String>) -> AppResult<()> {
+    copy_text_to_clipboard(&paths.join("\n"))
+}
+
+/// Copy paths as POSIX/Windows paths, file:// URLs, shell arguments,
+/// ~-relative paths or markdown links (see `copy_as::COPY_AS_FORMATS`).
+#[tauri::command]
+fn copy_as(paths: Vec<String>, format: String, state: State<'_, AppState>) -> AppResult<()> {
+    copy_text_to_clipboard(&copy_as::format_paths(&paths, &format, &state.home_dir)?)
+}
+
+#[cfg(target_os = "macos")]
+fn copy_files_to_clipboard(paths: &[String]) -> AppResult<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    if let Some(missing) = paths.iter().find(|p| long_path::symlink_metadata(p).is_err()) {
+        return Err(format!("{missing} no longer exists."));
+    }
+    mac::pasteboard::write_file_urls(paths)
+}
+
+#[cfg(target_os = "macos")]
+#[tauri::command]
+fn copy_files(paths: Vec<String>) -> AppResult<()> {
+    copy_files_to_clipboard(&paths)
+}
+
+#[cfg(not(target_os = "macos"))]
+#[tauri::command]
+fn copy_files(_paths: Vec<String>) -> AppResult<()> {
+    Err("copy_files is only supported on macOS".to_string())
+}
+
+/// Directory extensions Finder presents as packages (bundles). Gates the
+/// "Show Package Contents" context-menu item.
+#[cfg(target_os = "macos")]
+const PACKAGE_EXTENSIONS: &[&str] = &[
+    "app",
+    "bundle",
+    "framework",
+    "plugin",
+    "kext",
+    "prefpane",
+    "appex",
+    "xpc",
+    "qlgenerator",
+    "xcodeproj",
+    "photoslibrary",
+];
+
+#[cfg(target_os = "macos")]
+fn has_package_extension(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| PACKAGE_EXTENSIONS.iter().any(|pkg| pkg.eq_ignore_ascii_case(e)))
+}
+
+/// Finder-style "Show Package Contents": browse a package directory (e.g. an
+/// .app bundle) as a folder. Plain `open` would launch the bundle and Finder
+/// rejects the `folder` coercion for packages, so a new Finder window is
+/// pointed at the package root instead; the path travels via argv to avoid
+/// AppleScript string escaping.
+#[cfg(target_os = "macos")]
+#[tauri::command]
+async fn show_package_contents(path: String) -> AppResult<()> {
     tauri::async_runtime::spawn_blocking(move || {
-        #[cfg(target_os = "macos")]
-        {
-            Command::new("qlmanage")
-                .args(["-p", &path])
-                .stdout(std::process::Stdio::null())
-                .stderr(std::process::Stdio::null())
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(target_os = "windows")]
-        {
-            Command::new("explorer")
-                .arg(&path)
-                .spawn()
-                .map_err(|e| e.to_string())?;
-        }
-        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-        {
-            Command::new("xdg-open")
-                .arg(&path)
-                .spawn()
-                .map_err(|e| e.to_string())?;
+        let status = Command::new("osascript")
+            .args([
+                "-e", "on run argv",
+                "-e", "tell application \"Finder\"",
+                "-e", "set w to make new Finder window",
+                "-e", "set target of w to (POSIX file (item 1 of argv) as alias)",
+                "-e", "activate",
+                "-e", "end tell",
+                "-e", "end run",
+                &path,
+            ])
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err(format!("Failed to show package contents: {path}"));
         }
         Ok(())
     })
@@ -5969,453 +9280,1035 @@ async fn quick_look(path: String) -> AppResult<()> {
     .map_err(|e| e.to_string())?
 }
 
+#[cfg(not(target_os = "macos"))]
 #[tauri::command]
-async fn open(paths: Vec<String>) -> AppResult<()> {
-    tauri::async_runtime::spawn_blocking(move || {
-        for path in &paths {
-            #[cfg(target_os = "macos")]
-            {
-                let output = Command::new("open")
-                    .arg(path)
-                    .output()
-                    .map_err(|e| e.to_string())?;
+async fn show_package_contents(_path: String) -> AppResult<()> {
+    Err("show_package_contents is only supported on macOS".to_string())
+}
 
-                if !output.status.success() && Path::new(path).is_dir() {
-                    let fallback = Command::new("open")
-                        .args(["-R", path])
-                        .status()
-                        .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn move_to_trash(
+    paths: Vec<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<()> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let ctx = preflight::PreflightContext::new(&state.home_dir, &state.db_path);
+        preflight::ensure_allowed(&preflight::check_operation(
+            preflight::OperationKind::Trash,
+            &paths,
+            None,
+            &ctx,
+        )?)?;
 
-                    if !fallback.success() {
-                        return Err(format!("Failed to open: {path}"));
-                    }
-                } else if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    // kLSApplicationNotFoundErr: no app claims this file type.
-                    // Hand the open to Finder, which shows the system
-                    // "no application set to open" chooser dialog.
-                    if stderr.contains("kLSApplicationNotFoundErr") || stderr.contains("-10814") {
-                        let fallback = Command::new("open")
-                            .args(["-a", "Finder", path])
-                            .status()
-                            .map_err(|e| e.to_string())?;
-                        if fallback.success() {
-                            continue;
-                        }
-                    }
-                    return Err(format!("Failed to open: {path} ({stderr})",));
-                }
-            }
-            #[cfg(target_os = "windows")]
-            {
-                let mut cmd = Command::new("cmd");
-                cmd.raw_arg(format!("/C start \"\" \"{}\"", path.replace('"', "")));
-                let status = cmd.status().map_err(|e| e.to_string())?;
-                if !status.success() {
-                    return Err(format!("Failed to open: {path}"));
-                }
+        let mut deleted_targets = Vec::new();
+        let mut trashed = Vec::new();
+
+        let trash_result = (|| -> AppResult<()> {
+            for path in &paths {
+                #[cfg(target_os = "macos")]
+                let before = undo::trash_snapshot(&state.home_dir);
+                trash::delete(path).map_err(|e| e.to_string())?;
+                remember_op(&state, "trash", Some(path.clone()), None);
+                deleted_targets.push(path.clone());
+                #[cfg(target_os = "macos")]
+                let trashed_as = undo::locate_trashed(&state.home_dir, &before, Path::new(path));
+                #[cfg(not(target_os = "macos"))]
+                let trashed_as = None;
+                trashed.push(undo::TrashedItem {
+                    original: path.clone(),
+                    trashed_as,
+                });
             }
-            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-            {
-                let status = Command::new("xdg-open")
-                    .arg(path)
-                    .status()
-                    .map_err(|e| e.to_string())?;
-                if !status.success() {
-                    return Err(format!("Failed to open: {path}"));
-                }
+            Ok(())
+        })();
+        // A batch that failed part-way can still be undone for what it did.
+        if !trashed.is_empty() {
+            if let Ok(conn) = db_connection(&state.db_path) {
+                let _ = putback::record(&conn, &trashed);
             }
+            state.undo_log.lock().push(undo::UndoOp::Trash { items: trashed });
         }
+        trash_result?;
+
+        let mut conn = db_connection(&state.db_path)?;
+        let _ = delete_paths(&mut conn, &deleted_targets)?;
+        invalidate_search_caches(&state);
+        mem_resident::apply_batch(&state, &[], &deleted_targets);
 
+        refresh_and_emit_status_counts(Some(&app), &state)?;
         Ok(())
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
-fn reveal_in_finder_impl(paths: Vec<String>) -> AppResult<()> {
-    if paths.is_empty() {
-        return Ok(());
-    }
+#[tauri::command]
+async fn validate_operation(
+    kind: String,
+    paths: Vec<String>,
+    target: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<preflight::OperationCheckDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let kind = preflight::OperationKind::parse(&kind)?;
+        let ctx = preflight::PreflightContext::new(&state.home_dir, &state.db_path);
+        let warnings = preflight::check_operation(kind, &paths, target.as_deref(), &ctx)?;
+        Ok(preflight::OperationCheckDto {
+            ok: !warnings.iter().any(|w| w.blocking),
+            warnings,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        if paths.len() == 1 {
-            let status = Command::new("open")
-                .arg("-R")
-                .arg(&paths[0])
-                .status()
-                .map_err(|e| e.to_string())?;
+#[tauri::command]
+async fn rename(
+    path: String,
+    new_name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<EntryDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let _writing = state.db_writes.enter()?;
+        let validated_name = validate_new_name(&new_name)?;
+        let old_path = PathBuf::from(&path);
 
-            if !status.success() {
-                return Err(format!("Failed to reveal in Finder: {}", paths[0]));
-            }
+        let ctx = preflight::PreflightContext::new(&state.home_dir, &state.db_path);
+        preflight::ensure_allowed(&preflight::check_operation(
+            preflight::OperationKind::Rename,
+            std::slice::from_ref(&path),
+            Some(&validated_name),
+            &ctx,
+        )?)?;
 
-            return Ok(());
-        }
+        let parent = old_path
+            .parent()
+            .ok_or_else(|| "Parent directory not found.".to_string())?;
 
-        let mut unique_parents: HashSet<PathBuf> = HashSet::new();
-        for path in &paths {
-            let p = PathBuf::from(path);
-            if let Some(parent) = p.parent() {
-                unique_parents.insert(parent.to_path_buf());
-            }
+        let new_path = parent.join(&validated_name);
+        if new_path == old_path {
+            let meta = long_path::symlink_metadata(&old_path).ok();
+            return Ok(EntryDto {
+                path: path.clone(),
+                name: old_path
+                    .file_name()
+                    .map(|v| v.to_string_lossy().to_string())
+                    .unwrap_or_else(|| validated_name.clone()),
+                dir: parent.to_string_lossy().to_string(),
+                is_dir: old_path.is_dir(),
+                ext: extension_for(&old_path, old_path.is_dir()),
+                size: meta
+                    .as_ref()
+                    .filter(|m| m.is_file())
+                    .map(|m| m.len() as i64),
+                mtime: meta
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs() as i64),
+                spoken_label: None,
+                mtime_display: None,
+                cloud_only: None,
+                linked_from: None,
+            });
         }
 
-        for parent in unique_parents {
-            let status = Command::new("open")
-                .arg(&parent)
-                .status()
-                .map_err(|e| e.to_string())?;
+        let original_is_dir = old_path.is_dir();
+        fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
 
-            if !status.success() {
-                return Err(format!(
-                    "Failed to open in Finder: {}",
-                    parent.to_string_lossy()
-                ));
+        let mut conn = db_connection(&state.db_path)?;
+        if case_rename::is_case_only_change(&old_path, &new_path) {
+            // Same entry respelled: keep its rows rather than rescanning.
+            rename_case_only(&mut conn, &path, &new_path.to_string_lossy())?;
+            if let Some(row) = index_row_from_path(&new_path) {
+                let _ = upsert_rows(&mut conn, &[row])?;
             }
+        } else if original_is_dir {
+            let _ = delete_paths(&mut conn, std::slice::from_ref(&path))?;
+            let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(&state);
+            let _ = rescan::rescan_subtree(
+                &mut conn,
+                &new_path,
+                &ignored_roots,
+                &ignored_patterns,
+            )?;
+        } else {
+            let _ = delete_paths(&mut conn, std::slice::from_ref(&path))?;
+            let row = index_row_from_path(&new_path)
+                .ok_or_else(|| "Cannot read renamed file info.".to_string())?;
+            let _ = upsert_rows(&mut conn, &[row])?;
         }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        for path in &paths {
-            let mut cmd = Command::new("explorer");
-            cmd.raw_arg(format!("/select,\"{}\"", path.replace('"', "")));
-            let _ = cmd.status();
-        }
-    }
-
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    {
-        for path in &paths {
-            let target = Path::new(path);
-            let dir = if target.is_dir() {
-                path.as_str()
-            } else {
-                target
-                    .parent()
-                    .map(|p| p.to_str().unwrap_or("/"))
-                    .unwrap_or("/")
-            };
-            let _ = Command::new("xdg-open").arg(dir).status();
-        }
-    }
 
-    Ok(())
-}
+        invalidate_search_caches(&state);
+        mem_resident::resync(&state, &[path.clone(), new_path.to_string_lossy().to_string()]);
 
-fn copy_with_command(program: &str, args: &[&str], text: &str) -> AppResult<()> {
-    let mut child = Command::new(program)
-        .args(args)
-        .stdin(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to run {program}: {e}"))?;
+        remember_op(
+            &state,
+            "rename",
+            Some(old_path.to_string_lossy().to_string()),
+            Some(new_path.to_string_lossy().to_string()),
+        );
+        state.undo_log.lock().push(undo::UndoOp::Rename {
+            from: path.clone(),
+            to: new_path.to_string_lossy().to_string(),
+        });
 
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin
-            .write_all(text.as_bytes())
-            .map_err(|e| format!("Failed to write to clipboard: {e}"))?;
-    } else {
-        return Err("Cannot open clipboard input stream.".to_string());
-    }
+        refresh_and_emit_status_counts(Some(&app), &state)?;
 
-    let status = child
-        .wait()
-        .map_err(|e| format!("Failed to wait for {program}: {e}"))?;
+        let new_meta = long_path::symlink_metadata(&new_path).ok();
+        Ok(EntryDto {
+            path: new_path.to_string_lossy().to_string(),
+            name: validated_name,
+            dir: parent.to_string_lossy().to_string(),
+            is_dir: original_is_dir,
+            ext: extension_for(&new_path, original_is_dir),
+            size: new_meta
+                .as_ref()
+                .filter(|m| m.is_file())
+                .map(|m| m.len() as i64),
+            mtime: new_meta
+                .and_then(|m| m.modified().ok())
+                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs() as i64),
+            spoken_label: None,
+            mtime_display: None,
+            cloud_only: None,
+            linked_from: None,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    if status.success() {
-        Ok(())
-    } else {
-        Err(format!("{program} execution failed."))
+/// Index `path` (and everything under it, for a folder) right away after it
+/// reappeared, instead of waiting for the watcher.
+fn index_restored_path(state: &AppState, conn: &mut Connection, path: &Path) -> AppResult<()> {
+    if path.is_dir() {
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+        rescan::rescan_subtree(conn, path, &ignored_roots, &ignored_patterns)?;
+    } else if let Some(row) = index_row_from_path(path) {
+        upsert_rows(conn, &[row])?;
     }
+    Ok(())
 }
 
-#[cfg(target_os = "macos")]
-fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
-    copy_with_command("pbcopy", &[], text)
+fn undo_last_operation_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+) -> AppResult<Option<undo::UndoResultDto>> {
+    let _writing = state.db_writes.enter()?;
+    let Some(op) = state.undo_log.lock().pop() else {
+        return Ok(None);
+    };
+    let mut conn = db_connection(&state.db_path)?;
+    let result = match op {
+        undo::UndoOp::Rename { from, to } => {
+            remember_op(state, "rename", Some(to.clone()), Some(from.clone()));
+            undo::undo_rename(&from, &to)?;
+            delete_paths(&mut conn, std::slice::from_ref(&to))?;
+            index_restored_path(state, &mut conn, Path::new(&from))?;
+            undo::UndoResultDto {
+                kind: "rename".to_string(),
+                restored: vec![from],
+                removed: vec![to],
+                failed: Vec::new(),
+            }
+        }
+        undo::UndoOp::Trash { items } => {
+            for item in &items {
+                remember_op(state, "create", None, Some(item.original.clone()));
+            }
+            let (restored, failed) = undo::restore_trashed(&items);
+            putback::forget(&conn, &restored)?;
+            for path in &restored {
+                index_restored_path(state, &mut conn, Path::new(path))?;
+            }
+            undo::UndoResultDto {
+                kind: "trash".to_string(),
+                restored,
+                removed: Vec::new(),
+                failed,
+            }
+        }
+    };
+    invalidate_search_caches(state);
+    let touched: Vec<String> = result.restored.iter().chain(&result.removed).cloned().collect();
+    mem_resident::resync(state, &touched);
+    refresh_and_emit_status_counts(app, state)?;
+    Ok(Some(result))
 }
 
-#[cfg(target_os = "windows")]
-fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
-    copy_with_command("cmd", &["/C", "clip"], text)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RestoreFromTrashDto {
+    /// Original paths that are back in place (and indexed).
+    restored: Vec<String>,
+    /// One message per path that could not be restored.
+    failed: Vec<String>,
 }
 
-#[cfg(all(unix, not(target_os = "macos")))]
-fn copy_text_to_clipboard(text: &str) -> AppResult<()> {
-    let mut last_error = None;
-
-    match copy_with_command("wl-copy", &[], text) {
-        Ok(()) => return Ok(()),
-        Err(err) => last_error = Some(err),
+fn restore_from_trash_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    paths: &[String],
+) -> AppResult<RestoreFromTrashDto> {
+    let _writing = state.db_writes.enter()?;
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| dir_size::normalize_dir(p.trim()))
+        .filter(|p| !p.is_empty())
+        .collect();
+    let mut conn = db_connection(&state.db_path)?;
+    let mut putbacks = putback::lookup(&conn, &paths)?;
+    let items: Vec<undo::TrashedItem> = paths
+        .iter()
+        .map(|path| undo::TrashedItem {
+            original: path.clone(),
+            trashed_as: putbacks.remove(path),
+        })
+        .collect();
+    for item in &items {
+        remember_op(state, "create", None, Some(item.original.clone()));
     }
-    match copy_with_command("xclip", &["-selection", "clipboard"], text) {
-        Ok(()) => return Ok(()),
-        Err(err) => last_error = Some(err),
+    let (restored, failed) = undo::restore_trashed(&items);
+    putback::forget(&conn, &restored)?;
+    for path in &restored {
+        index_restored_path(state, &mut conn, Path::new(path))?;
     }
-    match copy_with_command("xsel", &["--clipboard", "--input"], text) {
-        Ok(()) => return Ok(()),
-        Err(err) => last_error = Some(err),
+    if !restored.is_empty() {
+        invalidate_search_caches(state);
+        mem_resident::resync(state, &restored);
+        refresh_and_emit_status_counts(app, state)?;
     }
-
-    Err(last_error.unwrap_or_else(|| {
-        "No supported clipboard tool found. Please install wl-copy, xclip, or xsel.".to_string()
-    }))
+    Ok(RestoreFromTrashDto { restored, failed })
 }
 
+/// Put items trashed by `move_to_trash` (or anything else, where the platform
+/// trash records origins) back at their original `paths` and index them
+/// right away. Paths that are taken again or no longer in the trash are
+/// reported in `failed`; the rest are still restored.
 #[tauri::command]
-async fn open_with(path: String) -> AppResult<()> {
-    tauri::async_runtime::spawn_blocking(move || reveal_in_finder_impl(vec![path]))
-        .await
-        .map_err(|e| e.to_string())?
+async fn restore_from_trash(
+    paths: Vec<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<RestoreFromTrashDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        restore_from_trash_blocking(Some(&app), &state, &paths)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Reverse the newest `rename` or `move_to_trash`: rename back, or restore
+/// the trashed batch to its original paths, and update the index to match.
+/// Returns `None` when there is nothing left to undo. An undo that fails is
+/// dropped from the log rather than retried.
 #[tauri::command]
-async fn reveal_in_finder(paths: Vec<String>) -> AppResult<()> {
-    tauri::async_runtime::spawn_blocking(move || reveal_in_finder_impl(paths))
+async fn undo_last_operation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<Option<undo::UndoResultDto>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || undo_last_operation_blocking(Some(&app), &state))
         .await
         .map_err(|e| e.to_string())?
 }
 
+/// Inline preview of `path`: decoded text, a hex dump, or a small image as a
+/// `data:` URL. `max_kb` caps how much of the file is read (default 64 KB).
 #[tauri::command]
-fn copy_paths(paths: Vec<String>) -> AppResult<()> {
-    copy_text_to_clipboard(&paths.join("\n"))
+async fn get_file_preview(path: String, max_kb: Option<u32>) -> AppResult<preview::FilePreviewDto> {
+    tauri::async_runtime::spawn_blocking(move || {
+        preview::file_preview(
+            Path::new(&path),
+            max_kb.unwrap_or(preview::DEFAULT_PREVIEW_KB),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-#[cfg(target_os = "macos")]
-fn copy_files_to_clipboard(paths: &[String]) -> AppResult<()> {
-    if paths.is_empty() {
-        return Ok(());
+/// Create an empty file or folder `name` inside `dir` and index it right
+/// away, so it shows up in results before the watcher debounce fires.
+fn create_entry_blocking(
+    state: &AppState,
+    dir: &str,
+    name: &str,
+    is_dir: bool,
+) -> AppResult<EntryDto> {
+    let _writing = state.db_writes.enter()?;
+    let name = validate_new_name(name)?;
+    let parent = PathBuf::from(dir);
+    if !parent.is_dir() {
+        return Err("Destination folder does not exist.".to_string());
     }
-    let file_exprs: Vec<String> = paths
-        .iter()
-        .map(|p| {
-            let escaped = p.replace('\\', "\\\\").replace('"', "\\\"");
-            format!("POSIX file \"{}\"", escaped)
-        })
-        .collect();
-    let script = if file_exprs.len() == 1 {
-        format!("set the clipboard to {}", file_exprs[0])
+    let path = parent.join(&name);
+    if long_path::symlink_metadata(&path).is_ok() {
+        return Err("A file/folder with the same name already exists.".to_string());
+    }
+    remember_op(state, "create", None, Some(path.to_string_lossy().to_string()));
+    if is_dir {
+        fs::create_dir(&path).map_err(|e| e.to_string())?;
     } else {
-        format!("set the clipboard to {{{}}}", file_exprs.join(", "))
-    };
-    let status = Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .status()
-        .map_err(|e| e.to_string())?;
-    if !status.success() {
-        return Err("Failed to copy files to clipboard".to_string());
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
     }
-    Ok(())
+
+    let row = index_row_from_path(&path)
+        .ok_or_else(|| "Cannot read created file info.".to_string())?;
+    let mut conn = db_connection(&state.db_path)?;
+    let _ = upsert_rows(&mut conn, std::slice::from_ref(&row))?;
+    invalidate_search_caches(state);
+    mem_resident::apply_batch(state, std::slice::from_ref(&row), &[]);
+    Ok(entry_from_index_row(row))
 }
 
-#[cfg(target_os = "macos")]
 #[tauri::command]
-fn copy_files(paths: Vec<String>) -> AppResult<()> {
-    copy_files_to_clipboard(&paths)
+async fn create_file(
+    dir: String,
+    name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<EntryDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let entry = create_entry_blocking(&state, &dir, &name, false)?;
+        refresh_and_emit_status_counts(Some(&app), &state)?;
+        Ok(entry)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-#[cfg(not(target_os = "macos"))]
 #[tauri::command]
-fn copy_files(_paths: Vec<String>) -> AppResult<()> {
-    Err("copy_files is only supported on macOS".to_string())
+async fn create_folder(
+    dir: String,
+    name: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<EntryDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let entry = create_entry_blocking(&state, &dir, &name, true)?;
+        refresh_and_emit_status_counts(Some(&app), &state)?;
+        Ok(entry)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-/// Directory extensions Finder presents as packages (bundles). Gates the
-/// "Show Package Contents" context-menu item.
-#[cfg(target_os = "macos")]
-const PACKAGE_EXTENSIONS: &[&str] = &[
-    "app",
-    "bundle",
-    "framework",
-    "plugin",
-    "kext",
-    "prefpane",
-    "appex",
-    "xpc",
-    "qlgenerator",
-    "xcodeproj",
-    "photoslibrary",
-];
+fn create_symlink_blocking(
+    state: &AppState,
+    target: &str,
+    dir: &str,
+    name: Option<&str>,
+) -> AppResult<EntryDto> {
+    let _writing = state.db_writes.enter()?;
+    let link = file_ops::create_link(Path::new(target), Path::new(dir), name)?;
+    remember_op(state, "create", None, Some(link.to_string_lossy().to_string()));
 
-#[cfg(target_os = "macos")]
-fn has_package_extension(path: &str) -> bool {
-    Path::new(path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .is_some_and(|e| PACKAGE_EXTENSIONS.iter().any(|pkg| pkg.eq_ignore_ascii_case(e)))
+    let row = index_row_from_path(&link)
+        .ok_or_else(|| "Cannot read created link info.".to_string())?;
+    let mut conn = db_connection(&state.db_path)?;
+    let _ = upsert_rows(&mut conn, std::slice::from_ref(&row))?;
+    invalidate_search_caches(state);
+    mem_resident::apply_batch(state, std::slice::from_ref(&row), &[]);
+    Ok(entry_from_index_row(row))
 }
 
-/// Finder-style "Show Package Contents": browse a package directory (e.g. an
-/// .app bundle) as a folder. Plain `open` would launch the bundle and Finder
-/// rejects the `folder` coercion for packages, so a new Finder window is
-/// pointed at the package root instead; the path travels via argv to avoid
-/// AppleScript string escaping.
-#[cfg(target_os = "macos")]
+/// Link `target` into `dir` (named `name`, or after the target) so results
+/// can be gathered in one folder without copying: a symlink on macOS and
+/// Linux, a `.lnk` shortcut on Windows. The link is indexed right away.
 #[tauri::command]
-async fn show_package_contents(path: String) -> AppResult<()> {
+async fn create_symlink(
+    target: String,
+    dir: String,
+    name: Option<String>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<EntryDto> {
+    let state = state.inner().clone();
     tauri::async_runtime::spawn_blocking(move || {
-        let status = Command::new("osascript")
-            .args([
-                "-e", "on run argv",
-                "-e", "tell application \"Finder\"",
-                "-e", "set w to make new Finder window",
-                "-e", "set target of w to (POSIX file (item 1 of argv) as alias)",
-                "-e", "activate",
-                "-e", "end tell",
-                "-e", "end run",
-                &path,
-            ])
-            .status()
-            .map_err(|e| e.to_string())?;
-        if !status.success() {
-            return Err(format!("Failed to show package contents: {path}"));
+        let entry = create_symlink_blocking(&state, &target, &dir, name.as_deref())?;
+        refresh_and_emit_status_counts(Some(&app), &state)?;
+        Ok(entry)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Shared body of `batch_move` and `batch_copy`. Items are transferred one
+/// at a time so a failure or cancel leaves earlier ones done; the index is
+/// updated for whatever completed instead of waiting for the watcher.
+fn batch_transfer_blocking(
+    app: &AppHandle,
+    state: &AppState,
+    kind: preflight::OperationKind,
+    paths: &[String],
+    dest_dir: &str,
+    request_id: Option<u64>,
+) -> AppResult<BatchTransferDto> {
+    let _writing = state.db_writes.enter()?;
+    let started = Instant::now();
+    let ctx = preflight::PreflightContext::new(&state.home_dir, &state.db_path);
+    preflight::ensure_allowed(&preflight::check_operation(
+        kind,
+        paths,
+        Some(dest_dir),
+        &ctx,
+    )?)?;
+
+    let (op, op_type) = match kind {
+        preflight::OperationKind::Move => ("batch_move", "move"),
+        _ => ("batch_copy", "copy"),
+    };
+    let active = state.transfer_cancels.begin(request_id);
+    let dest_dir = PathBuf::from(dest_dir);
+    let mut completed = Vec::new();
+    let mut failed = Vec::new();
+    let mut cancelled = false;
+
+    let item_files: Vec<u64> = paths
+        .iter()
+        .map(|path| file_ops::count_files(Path::new(path)))
+        .collect();
+    let total_files: u64 = item_files.iter().sum();
+    let mut files_done = 0u64;
+    let mut last_file_emit = Instant::now();
+
+    for (index, path) in paths.iter().enumerate() {
+        let source = PathBuf::from(path);
+        let item_start = files_done;
+        let result =
+            file_ops::transfer_path(kind, &source, &dest_dir, Some(&active.token), |file| {
+                files_done += 1;
+                if files_done == total_files
+                    || last_file_emit.elapsed() >= TRANSFER_PROGRESS_INTERVAL
+                {
+                    last_file_emit = Instant::now();
+                    let _ = app.emit(
+                        "transfer_file_progress",
+                        TransferFileProgressEvent {
+                            request_id: active.id,
+                            op,
+                            done: files_done,
+                            total: total_files,
+                            path: file.to_string_lossy().to_string(),
+                        },
+                    );
+                }
+            });
+        // A rename reports no files and a failed copy stops short; either way
+        // the item is finished now.
+        files_done = item_start + item_files[index];
+        let (dest, error) = match result {
+            Ok(dest) => {
+                let dest = dest.to_string_lossy().to_string();
+                let old_path = (op_type == "move").then(|| path.clone());
+                remember_op(state, op_type, old_path, Some(dest.clone()));
+                completed.push(TransferredDto {
+                    source: path.clone(),
+                    dest: dest.clone(),
+                });
+                (Some(dest), None)
+            }
+            Err(e) if e == file_ops::TRANSFER_CANCELLED => {
+                cancelled = true;
+                break;
+            }
+            Err(e) => {
+                failed.push(TransferFailureDto {
+                    path: path.clone(),
+                    error: e.clone(),
+                });
+                (None, Some(e))
+            }
+        };
+        let _ = app.emit(
+            "transfer_progress",
+            TransferProgressEvent {
+                request_id: active.id,
+                op,
+                index,
+                total: paths.len(),
+                source: path.clone(),
+                dest,
+                error,
+            },
+        );
+    }
+
+    if !completed.is_empty() {
+        let mut conn = db_connection(&state.db_path)?;
+        if kind == preflight::OperationKind::Move {
+            let sources: Vec<String> = completed.iter().map(|t| t.source.clone()).collect();
+            let _ = delete_paths(&mut conn, &sources)?;
+        }
+        let mut rows = Vec::new();
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+        for transferred in &completed {
+            let dest = Path::new(&transferred.dest);
+            if dest.is_dir() {
+                let _ = rescan::rescan_subtree(
+                    &mut conn,
+                    dest,
+                    &ignored_roots,
+                    &ignored_patterns,
+                )?;
+            } else if let Some(row) = index_row_from_path(dest) {
+                rows.push(row);
+            }
         }
-        Ok(())
+        let _ = upsert_rows(&mut conn, &rows)?;
+        invalidate_search_caches(state);
+        let touched: Vec<String> = completed
+            .iter()
+            .flat_map(|t| [t.source.clone(), t.dest.clone()])
+            .collect();
+        mem_resident::resync(state, &touched);
+        refresh_and_emit_status_counts(Some(app), state)?;
+    }
+
+    Ok(BatchTransferDto {
+        request_id: active.id,
+        completed,
+        failed,
+        cancelled,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+async fn batch_move(
+    paths: Vec<String>,
+    dest_dir: String,
+    request_id: Option<u64>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<BatchTransferDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        batch_transfer_blocking(
+            &app,
+            &state,
+            preflight::OperationKind::Move,
+            &paths,
+            &dest_dir,
+            request_id,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn batch_copy(
+    paths: Vec<String>,
+    dest_dir: String,
+    request_id: Option<u64>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<BatchTransferDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        batch_transfer_blocking(
+            &app,
+            &state,
+            preflight::OperationKind::Copy,
+            &paths,
+            &dest_dir,
+            request_id,
+        )
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
-#[cfg(not(target_os = "macos"))]
-#[tauri::command]
-async fn show_package_contents(_path: String) -> AppResult<()> {
-    Err("show_package_contents is only supported on macOS".to_string())
+/// Stop a running `batch_move` / `batch_copy` / `compress_to_zip` /
+/// `extract_archive`; a half-copied item, half-written archive or partial
+/// extraction is removed.
+#[tauri::command]
+fn cancel_transfer(request_id: u64, state: State<'_, AppState>) -> bool {
+    state.transfer_cancels.cancel(request_id)
+}
+
+/// Zip `paths` into `dest_dir` (default: the first path's folder) as
+/// "<name>.zip" for a single item or "Archive.zip" for several, numbered
+/// when taken, then index the archive right away.
+fn compress_to_zip_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    paths: &[String],
+    dest_dir: Option<&str>,
+    request_id: Option<u64>,
+) -> AppResult<CompressToZipDto> {
+    let _writing = state.db_writes.enter()?;
+    let started = Instant::now();
+    let sources: Vec<PathBuf> = paths.iter().map(PathBuf::from).collect();
+    let first = sources
+        .first()
+        .ok_or_else(|| "Nothing to compress.".to_string())?;
+    if let Some(missing) = sources.iter().find(|s| long_path::symlink_metadata(s).is_err()) {
+        return Err(format!("{} does not exist.", missing.display()));
+    }
+    let dest_dir = match dest_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => first
+            .parent()
+            .map(Path::to_path_buf)
+            .ok_or_else(|| format!("Cannot compress {}", first.display()))?,
+    };
+    if !dest_dir.is_dir() {
+        return Err("Destination folder does not exist.".to_string());
+    }
+    let stem = match sources.as_slice() {
+        [single] => single
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Archive".to_string()),
+        _ => "Archive".to_string(),
+    };
+    let dest = archive::unique_archive_path(&dest_dir, &stem);
+    let dest_str = dest.to_string_lossy().to_string();
+
+    let active = state.transfer_cancels.begin(request_id);
+    let total = archive::count_entries(&sources);
+    let mut last_emit = Instant::now();
+    remember_op(state, "create", None, Some(dest_str.clone()));
+    let entries = archive::zip_paths(&sources, &dest, Some(&active.token), |done, path| {
+        if done == total || last_emit.elapsed() >= ZIP_PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            if let Some(app) = app {
+                let _ = app.emit(
+                    "zip_progress",
+                    ZipProgressEvent {
+                        request_id: active.id,
+                        done,
+                        total,
+                        path: path.to_string_lossy().to_string(),
+                    },
+                );
+            }
+        }
+    })?;
+
+    let row =
+        index_row_from_path(&dest).ok_or_else(|| "Cannot read archive file info.".to_string())?;
+    let size = row.size.unwrap_or(0).max(0) as u64;
+    let mut conn = db_connection(&state.db_path)?;
+    let _ = upsert_rows(&mut conn, std::slice::from_ref(&row))?;
+    invalidate_search_caches(state);
+    mem_resident::apply_batch(state, std::slice::from_ref(&row), &[]);
+    refresh_and_emit_status_counts(app, state)?;
+
+    Ok(CompressToZipDto {
+        request_id: active.id,
+        path: dest_str,
+        entries,
+        size,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
 }
 
 #[tauri::command]
-async fn move_to_trash(
+async fn compress_to_zip(
     paths: Vec<String>,
+    dest_dir: Option<String>,
+    request_id: Option<u64>,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> AppResult<()> {
+) -> AppResult<CompressToZipDto> {
     let state = state.inner().clone();
     tauri::async_runtime::spawn_blocking(move || {
-        let mut deleted_targets = Vec::new();
+        compress_to_zip_blocking(
+            Some(&app),
+            &state,
+            &paths,
+            dest_dir.as_deref(),
+            request_id,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        for path in &paths {
-            trash::delete(path).map_err(|e| e.to_string())?;
-            remember_op(&state, "trash", Some(path.clone()), None);
-            deleted_targets.push(path.clone());
-        }
+/// Extract a zip, tar, tar.gz or 7z archive next to itself (see
+/// `archive::extract_archive` for naming) and index the result right away.
+fn extract_archive_blocking(
+    app: Option<&AppHandle>,
+    state: &AppState,
+    path: &str,
+    request_id: Option<u64>,
+) -> AppResult<ExtractArchiveDto> {
+    let _writing = state.db_writes.enter()?;
+    let started = Instant::now();
+    let archive_path = PathBuf::from(path);
+    if !archive_path.is_file() {
+        return Err(format!("{path} does not exist."));
+    }
+    let active = state.transfer_cancels.begin(request_id);
+    let mut last_emit = Instant::now();
+    let (target, entries) =
+        archive::extract_archive(&archive_path, Some(&active.token), |done, total, member| {
+            if total == Some(done) || last_emit.elapsed() >= ZIP_PROGRESS_INTERVAL {
+                last_emit = Instant::now();
+                if let Some(app) = app {
+                    let _ = app.emit(
+                        "extract_progress",
+                        ExtractProgressEvent {
+                            request_id: active.id,
+                            done,
+                            total,
+                            path: member.to_string_lossy().to_string(),
+                        },
+                    );
+                }
+            }
+        })?;
+    let target_str = target.to_string_lossy().to_string();
+    remember_op(state, "create", None, Some(target_str.clone()));
 
-        let mut conn = db_connection(&state.db_path)?;
-        let _ = delete_paths(&mut conn, &deleted_targets)?;
-        invalidate_search_caches(&state);
+    let mut conn = db_connection(&state.db_path)?;
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+    let (indexed, _) = rescan::rescan_subtree(
+        &mut conn,
+        &target,
+        &ignored_roots,
+        &ignored_patterns,
+    )?;
+    invalidate_search_caches(state);
+    mem_resident::resync(state, std::slice::from_ref(&target_str));
+    refresh_and_emit_status_counts(app, state)?;
+
+    Ok(ExtractArchiveDto {
+        request_id: active.id,
+        path: target_str,
+        entries,
+        indexed,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
 
-        refresh_and_emit_status_counts(Some(&app), &state)?;
-        Ok(())
+#[tauri::command]
+async fn extract_archive(
+    path: String,
+    request_id: Option<u64>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> AppResult<ExtractArchiveDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        extract_archive_blocking(Some(&app), &state, &path, request_id)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+/// Reconcile one directory subtree with the filesystem: upsert new/changed
+/// rows and delete rows whose files are gone. Runs under the exclusive-writer
+/// guard so it never races the indexer, watcher rescans, or .pathindexing.
+fn reindex_subtree_blocking(state: &AppState, path: &str) -> AppResult<ReindexSubtreeDto> {
+    let root = PathBuf::from(path);
+    if !root.is_absolute() {
+        return Err("Path must be absolute.".to_string());
+    }
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+    if should_skip_path(&root, &ignored_roots, &ignored_patterns) {
+        return Err("Path is excluded by ignore rules.".to_string());
+    }
+    if state
+        .indexing_active
+        .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+        .is_err()
+    {
+        return Err("Cannot reindex while indexing is in progress.".to_string());
+    }
+    let started = Instant::now();
+    let result = db_connection(&state.db_path).and_then(|mut conn| {
+        rescan::rescan_subtree(&mut conn, &root, &ignored_roots, &ignored_patterns)
+    });
+    state.indexing_active.store(false, AtomicOrdering::Release);
+    let (upserted, deleted) = result?;
+    if upserted + deleted > 0 {
+        invalidate_search_caches(state);
+        mem_resident::resync(state, &[root.to_string_lossy().to_string()]);
+    }
+    Ok(ReindexSubtreeDto {
+        upserted,
+        deleted,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
 #[tauri::command]
-async fn rename(
+async fn reindex_subtree(
     path: String,
-    new_name: String,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> AppResult<EntryDto> {
+) -> AppResult<ReindexSubtreeDto> {
     let state = state.inner().clone();
     tauri::async_runtime::spawn_blocking(move || {
-        let validated_name = validate_new_name(&new_name)?;
-        let old_path = PathBuf::from(&path);
-
-        if !old_path.exists() {
-            return Err("Source file does not exist.".to_string());
-        }
+        let result = reindex_subtree_blocking(&state, &path)?;
+        eprintln!(
+            "[reindex] {}: upserted={} deleted={} {}ms",
+            path, result.upserted, result.deleted, result.elapsed_ms
+        );
+        refresh_and_emit_status_counts(Some(&app), &state)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-        let parent = old_path
-            .parent()
-            .ok_or_else(|| "Parent directory not found.".to_string())?;
+/// Sizes of the DB file and its WAL; 0 for a missing file.
+fn db_file_sizes(db_path: &Path) -> (u64, u64) {
+    let mut wal = db_path.as_os_str().to_owned();
+    wal.push("-wal");
+    let len = |p: &Path| fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    (len(db_path), len(Path::new(&wal)))
+}
 
-        let new_path = parent.join(&validated_name);
-        if new_path == old_path {
-            let meta = fs::symlink_metadata(&old_path).ok();
-            return Ok(EntryDto {
-                path: path.clone(),
-                name: old_path
-                    .file_name()
-                    .map(|v| v.to_string_lossy().to_string())
-                    .unwrap_or_else(|| validated_name.clone()),
-                dir: parent.to_string_lossy().to_string(),
-                is_dir: old_path.is_dir(),
-                ext: extension_for(&old_path, old_path.is_dir()),
-                size: meta
-                    .as_ref()
-                    .filter(|m| m.is_file())
-                    .map(|m| m.len() as i64),
-                mtime: meta
-                    .and_then(|m| m.modified().ok())
-                    .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs() as i64),
-            });
-        }
+/// On-disk footprint of the index: the DB file plus its WAL.
+fn db_size_on_disk(db_path: &Path) -> u64 {
+    let (db, wal) = db_file_sizes(db_path);
+    db + wal
+}
 
-        if new_path.exists() {
-            return Err("A file/folder with the same name already exists.".to_string());
-        }
+const INDEX_STATS_BUCKET_LIMIT: usize = 50;
 
-        let original_is_dir = old_path.is_dir();
-        fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
+fn index_stats_blocking(state: &AppState) -> AppResult<IndexStatsDto> {
+    let started = Instant::now();
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        return Err("Index is not ready yet.".to_string());
+    }
+    let conn = pooled_search_connection(state)?;
+    let entries_count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    let entries_count = entries_count.max(0) as u64;
+    let (total_size, file_count) = disk_usage::usage_totals(&conn, "/")?;
+    let by_extension = disk_usage::usage_by_extension(&conn, "/", INDEX_STATS_BUCKET_LIMIT)?;
 
-        let mut conn = db_connection(&state.db_path)?;
-        let _ = delete_paths(&mut conn, &[path.clone()])?;
+    let scan_root = dir_size::normalize_dir(&state.scan_root.to_string_lossy());
+    let mut by_top_folder =
+        disk_usage::usage_by_top_folder(&conn, &scan_root, INDEX_STATS_BUCKET_LIMIT)?;
+    let extra_roots = state.extra_roots.lock().clone();
+    for root in extra_roots.iter().filter(|r| !r.starts_with(&state.scan_root)) {
+        let root = dir_size::normalize_dir(&root.to_string_lossy());
+        let (size, file_count) = disk_usage::usage_totals(&conn, &root)?;
+        by_top_folder.push(disk_usage::UsageBucketDto {
+            key: root.clone(),
+            path: Some(root),
+            size,
+            file_count,
+        });
+    }
+    by_top_folder.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.key.cmp(&b.key)));
+    by_top_folder.truncate(INDEX_STATS_BUCKET_LIMIT);
+
+    let (db_size, wal_size) = db_file_sizes(&state.db_path);
+    Ok(IndexStatsDto {
+        entries_count,
+        file_count,
+        dir_count: entries_count.saturating_sub(file_count),
+        total_size,
+        by_extension,
+        by_top_folder,
+        db_size,
+        wal_size,
+        last_full_index_ms: get_meta(&conn, "last_full_index_ms").and_then(|v| v.parse().ok()),
+        last_full_index_at: get_meta(&conn, "last_full_index_at").and_then(|v| v.parse().ok()),
+        watcher_active: state.watcher_active.load(AtomicOrdering::Acquire),
+        watcher_backlog: state.watcher_backlog.load(AtomicOrdering::Relaxed) as u64,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
 
-        if original_is_dir {
-            let _ = rescan::rescan_subtree(
-                &mut conn,
-                &new_path,
-                &state.path_ignores,
-                &state.path_ignore_patterns,
-            )?;
-        } else {
-            let row = index_row_from_path(&new_path)
-                .ok_or_else(|| "Cannot read renamed file info.".to_string())?;
-            let _ = upsert_rows(&mut conn, &[row])?;
+/// User-requested storage maintenance: rebuild the B-tree indexes, merge the
+/// FTS segments, VACUUM, refresh planner stats and truncate the WAL. Holds the
+/// exclusive-writer guard like `reindex_subtree` so no index run interleaves.
+fn compact_index_blocking(state: &AppState) -> AppResult<CompactIndexDto> {
+    if !state.db_ready.load(AtomicOrdering::Acquire) {
+        return Err("Index is not ready yet.".to_string());
+    }
+    if state
+        .indexing_active
+        .compare_exchange(false, true, AtomicOrdering::AcqRel, AtomicOrdering::Acquire)
+        .is_err()
+    {
+        return Err("Cannot compact while indexing is in progress.".to_string());
+    }
+    let started = Instant::now();
+    let before_bytes = db_size_on_disk(&state.db_path);
+    let result = db_connection_for_maintenance(&state.db_path).and_then(|conn| {
+        conn.execute_batch("REINDEX;").map_err(|e| e.to_string())?;
+        if state.fts_ready.load(AtomicOrdering::Acquire) {
+            conn.execute_batch("INSERT INTO entries_fts(entries_fts) VALUES('optimize');")
+                .map_err(|e| e.to_string())?;
         }
+        conn.execute_batch("VACUUM; ANALYZE; PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| e.to_string())
+    });
+    state.indexing_active.store(false, AtomicOrdering::Release);
+    result?;
+    Ok(CompactIndexDto {
+        before_bytes,
+        after_bytes: db_size_on_disk(&state.db_path),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
 
-        invalidate_search_caches(&state);
-
-        remember_op(
-            &state,
-            "rename",
-            Some(old_path.to_string_lossy().to_string()),
-            Some(new_path.to_string_lossy().to_string()),
+#[tauri::command]
+async fn compact_index(app: AppHandle, state: State<'_, AppState>) -> AppResult<CompactIndexDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = compact_index_blocking(&state)?;
+        eprintln!(
+            "[maintenance] compact: {}MB -> {}MB in {}ms",
+            result.before_bytes / (1024 * 1024),
+            result.after_bytes / (1024 * 1024),
+            result.elapsed_ms
         );
-
-        refresh_and_emit_status_counts(Some(&app), &state)?;
-
-        let new_meta = fs::symlink_metadata(&new_path).ok();
-        Ok(EntryDto {
-            path: new_path.to_string_lossy().to_string(),
-            name: validated_name,
-            dir: parent.to_string_lossy().to_string(),
-            is_dir: original_is_dir,
-            ext: extension_for(&new_path, original_is_dir),
-            size: new_meta
-                .as_ref()
-                .filter(|m| m.is_file())
-                .map(|m| m.len() as i64),
-            mtime: new_meta
-                .and_then(|m| m.modified().ok())
-                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-                .map(|d| d.as_secs() as i64),
-        })
+        app.emit("index_compacted", result.clone()).ok();
+        Ok(result)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
+/// Index statistics for the diagnostics page: counts and bytes per
+/// extension and top-level folder, DB and WAL size, the last full index
+/// duration and the watcher backlog.
+#[tauri::command]
+async fn get_index_stats(state: State<'_, AppState>) -> AppResult<IndexStatsDto> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || index_stats_blocking(&state))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn fd_search(
     query: String,
     limit: Option<u32>,
     offset: Option<u32>,
     sort_by: Option<String>,
     sort_dir: Option<String>,
+    spoken_labels: Option<bool>,
+    time_locale: Option<String>,
+    request_id: Option<u64>,
+    app: AppHandle,
     state: State<'_, AppState>,
 ) -> AppResult<FdSearchResultDto> {
     let state = state.inner().clone();
@@ -6425,6 +10318,10 @@ async fn fd_search(
         let offset = offset.unwrap_or(0) as usize;
         let sort_by = sort_by.unwrap_or_else(|| "name".to_string());
         let sort_dir = sort_dir.unwrap_or_else(|| "asc".to_string());
+        let spoken_labels = spoken_labels.unwrap_or(false);
+        let time_locale = time_locale
+            .as_deref()
+            .map(|locale| humanize::TimeLocale::resolve(Some(locale)));
         let (runtime_ignored_roots, runtime_ignored_patterns) =
             cached_effective_ignore_rules(&state);
         let ignore_fingerprint =
@@ -6448,11 +10345,12 @@ async fn fd_search(
                 if cache_hit {
                     let total = cached.entries.len() as u64;
                     let end = (offset + limit).min(cached.entries.len());
-                    let page = if offset < cached.entries.len() {
+                    let mut page = if offset < cached.entries.len() {
                         cached.entries[offset..end].to_vec()
                     } else {
                         Vec::new()
                     };
+                    annotate_result_entries(&mut page, spoken_labels, time_locale);
                     return Ok(FdSearchResultDto {
                         entries: page,
                         total,
@@ -6462,6 +10360,19 @@ async fn fd_search(
             }
         }
 
+        let mut emit_partial = |found: &[EntryDto]| {
+            let mut entries = found.to_vec();
+            annotate_result_entries(&mut entries, spoken_labels, time_locale);
+            app.emit(
+                "search_partial",
+                SearchPartialEvent {
+                    request_id: request_id.unwrap_or_default(),
+                    entries,
+                },
+            )
+            .ok();
+        };
+        let mut partial = request_id.map(|_| PartialFlush::new(&mut emit_partial));
         let result = fd_search::run_fd_search(
             &state.scan_root,
             &runtime_ignored_roots,
@@ -6469,14 +10380,16 @@ async fn fd_search(
             &query,
             &sort_by,
             &sort_dir,
+            partial.as_mut(),
         );
         let total = result.entries.len() as u64;
         let end = (offset + limit).min(result.entries.len());
-        let page = if offset < result.entries.len() {
+        let mut page = if offset < result.entries.len() {
             result.entries[offset..end].to_vec()
         } else {
             Vec::new()
         };
+        annotate_result_entries(&mut page, spoken_labels, time_locale);
 
         {
             let mut cache = state.fd_search_cache.lock();
@@ -6499,6 +10412,13 @@ async fn fd_search(
     .map_err(|e| e.to_string())?
 }
 
+/// Relative-time strings for arbitrary mtimes (preview pane, exports), in
+/// the same wording `search` uses for `mtimeDisplay`.
+#[tauri::command]
+fn format_times(mtimes: Vec<Option<i64>>, locale: Option<String>) -> Vec<String> {
+    humanize::format_times(&mtimes, humanize::TimeLocale::resolve(locale.as_deref()))
+}
+
 #[tauri::command]
 fn frontend_log(msg: String) {
     eprintln!("{msg}");
@@ -6617,6 +10537,7 @@ async fn show_context_menu(
                 MenuItem::with_id(&app, "ctx_copy_files", "Copy", true, None::<&str>)?;
             let copy_path =
                 MenuItem::with_id(&app, "ctx_copy_path", "Copy Path", true, None::<&str>)?;
+            let share = MenuItem::with_id(&app, "ctx_share", "Share...", true, None::<&str>)?;
             let sep3 = PredefinedMenuItem::separator(&app)?;
             let trash = MenuItem::with_id(
                 &app,
@@ -6627,9 +10548,16 @@ async fn show_context_menu(
             )?;
             let rename =
                 MenuItem::with_id(&app, "ctx_rename", "Rename", true, None::<&str>)?;
+            let exclude = MenuItem::with_id(
+                &app,
+                "ctx_exclude_folder",
+                "Exclude from Index",
+                true,
+                None::<&str>,
+            )?;
 
             let mut items: Vec<&dyn IsMenuItem<tauri::Wry>> = vec![
-                &open, &quick_look, &open_with, &sep1, &reveal, &sep2, &copy_files, &copy_path, &sep3, &trash,
+                &open, &quick_look, &open_with, &sep1, &reveal, &sep2, &copy_files, &copy_path, &share, &sep3, &trash,
             ];
             if let Some(show_pkg) = &show_pkg {
                 // Finder places "Show Package Contents" directly after "Open".
@@ -6638,6 +10566,9 @@ async fn show_context_menu(
             if single_selection {
                 items.push(&rename);
             }
+            if single_selection && single_is_dir {
+                items.push(&exclude);
+            }
 
             let menu = Menu::with_items(&app, &items)?;
             // Position is window-relative logical pixels, matching clientX/clientY.
@@ -6689,6 +10620,57 @@ async fn set_native_theme(theme: String, app: AppHandle) -> AppResult<()> {
     Ok(())
 }
 
+/// PNG icon for a file of type `ext`, through `icon_cache`: per-file icons
+/// (`.app` bundles, Windows executables) are keyed by path, the rest by
+/// extension. Empty when the OS has none.
+fn resolve_icon(state: &AppState, path: Option<&str>, ext: &str) -> Vec<u8> {
+    let ext_lower = if ext.trim().is_empty() {
+        "__default__".to_string()
+    } else {
+        ext.to_lowercase()
+    };
+
+    let cache_key = if is_per_file_icon_ext(&ext_lower) {
+        path.unwrap_or(&ext_lower).to_string()
+    } else {
+        ext_lower.clone()
+    };
+
+    if let Some(cached) = state.icon_cache.lock().get(&cache_key).cloned() {
+        return cached;
+    }
+
+    let per_path_key = cache_key != ext_lower;
+    let icon = path
+        .filter(|p| !p.is_empty())
+        .and_then(|p| load_icon_from_path(p, &ext_lower))
+        .or_else(|| {
+            // Per-path miss: serve the generic ext icon from the ext-keyed
+            // cache (prewarmed for common exts) instead of regenerating it
+            // for every path, and store it back under the ext key.
+            if per_path_key {
+                if let Some(cached) = state.icon_cache.lock().get(&ext_lower).cloned() {
+                    return Some(cached);
+                }
+            }
+            let system = load_system_icon_png(&ext_lower);
+            if per_path_key {
+                if let Some(png) = &system {
+                    state.icon_cache.lock().insert(ext_lower.clone(), png.clone());
+                }
+            }
+            system
+        });
+
+    let icon = icon.unwrap_or_default();
+    // Don't cache failures: a transient miss (e.g. an .app bundle still being
+    // written) would otherwise block every future retry for this key.
+    if !icon.is_empty() {
+        state.icon_cache.lock().insert(cache_key, icon.clone());
+    }
+    icon
+}
+
 #[tauri::command]
 async fn get_file_icon(
     path: Option<String>,
@@ -6697,57 +10679,63 @@ async fn get_file_icon(
 ) -> AppResult<Vec<u8>> {
     let state = state.inner().clone();
     Ok(tauri::async_runtime::spawn_blocking(move || {
-        let ext_lower = if ext.trim().is_empty() {
-            "__default__".to_string()
-        } else {
-            ext.to_lowercase()
-        };
-
-        let cache_key = if is_per_file_icon_ext(&ext_lower) {
-            path.as_deref().unwrap_or(&ext_lower).to_string()
-        } else {
-            ext_lower.clone()
-        };
-
-        if let Some(cached) = state.icon_cache.lock().get(&cache_key).cloned() {
-            return cached;
-        }
+        resolve_icon(&state, path.as_deref(), &ext)
+    })
+    .await
+    .unwrap_or_default())
+}
 
-        let per_path_key = cache_key != ext_lower;
-        let icon = path
-            .as_deref()
-            .filter(|p| !p.is_empty())
-            .and_then(|p| load_icon_from_path(p, &ext_lower))
-            .or_else(|| {
-                // Per-path miss: serve the generic ext icon from the ext-keyed
-                // cache (prewarmed for common exts) instead of regenerating it
-                // for every path, and store it back under the ext key.
-                if per_path_key {
-                    if let Some(cached) = state.icon_cache.lock().get(&ext_lower).cloned() {
-                        return Some(cached);
-                    }
-                }
-                let system = load_system_icon_png(&ext_lower);
-                if per_path_key {
-                    if let Some(png) = &system {
-                        state.icon_cache.lock().insert(ext_lower.clone(), png.clone());
-                    }
-                }
-                system
-            });
+/// One row of a `prefetch_icons` page: the `path` and `ext` that
+/// `get_file_icon` takes, with `ext` already "folder"/"app" for directories.
+#[derive(Debug, Deserialize)]
+struct IconRequest {
+    path: String,
+    ext: String,
+}
 
-        let icon = icon.unwrap_or_default();
-        // Don't cache failures: a transient miss (e.g. an .app bundle still being
-        // written) would otherwise block every future retry for this key.
-        if !icon.is_empty() {
-            state.icon_cache.lock().insert(cache_key, icon.clone());
-        }
-        icon
+/// Icons for a page of results in one background pass, in `items` order
+/// (empty where the OS has none), so the table fills its visible rows with
+/// one call instead of one `get_file_icon` per row. Types already resolved
+/// come straight from `icon_cache`.
+#[tauri::command]
+async fn prefetch_icons(
+    items: Vec<IconRequest>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<Vec<u8>>> {
+    let state = state.inner().clone();
+    Ok(tauri::async_runtime::spawn_blocking(move || {
+        items
+            .iter()
+            .map(|item| resolve_icon(&state, Some(&item.path), &item.ext))
+            .collect()
     })
     .await
     .unwrap_or_default())
 }
 
+/// PNG thumbnail of an image, video or document, at most `size` pixels on
+/// the long edge (default 256). Empty when the OS has no thumbnail for it,
+/// so the frontend keeps showing the type icon.
+#[tauri::command]
+async fn get_thumbnail(
+    path: String,
+    size: Option<u32>,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<u8>> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let size = size
+            .unwrap_or(thumbnails::DEFAULT_THUMBNAIL_SIZE)
+            .clamp(16, thumbnails::MAX_THUMBNAIL_SIZE);
+        state
+            .thumbnail_cache
+            .get_or_render(Path::new(&path), size, thumbnails::render_thumbnail_png)
+            .unwrap_or_default()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 fn default_bench_cases() -> Vec<BenchCase> {
     vec![
         BenchCase {
@@ -7091,20 +11079,19 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
         eprintln!("[startup +{}ms] windows setup: window.show() returned", startup_elapsed_ms());
     }
 
-    let app_data_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {e}"))?;
+    let app_data_dir = portable::app_data_dir(app.handle())?;
     fs::create_dir_all(&app_data_dir).map_err(|e| e.to_string())?;
 
-    let db_path = app_data_dir.join(DB_FILE_NAME);
+    let data_dir = profiles::active_data_dir(&app_data_dir);
+    let db_path = db_location::db_path(&data_dir);
     let home_dir = resolve_home_dir();
 
     // Register this binary as an MCP server for Claude Code / Codex so agents
     // pick it up automatically. Passes the Tauri-resolved DB path so the
     // standalone MCP process serves exactly this index instead of guessing
-    // the app-data layout. Best-effort, off the startup path.
-    {
+    // the app-data layout. Best-effort, off the startup path. Portable runs
+    // leave the agents' configs alone.
+    if !portable::is_enabled() {
         let mcp_db_path = db_path.clone();
         std::thread::spawn(move || mcp_server::register_all_and_log(Some(mcp_db_path)));
     }
@@ -7112,9 +11099,36 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
     // lifetime so the headless MCP-spawned daemon defers to us (single writer).
     daemon::hold_gui_beacon(&db_path);
     eprintln!("[startup] +{}ms building AppState (pathignore + pathindexing)...", setup_started.elapsed().as_millis());
-    let state = build_app_state(db_path, home_dir, &app_data_dir);
+    let mut state = build_app_state(db_path, home_dir, &data_dir);
+    profiles::apply_to_state(&mut state);
     eprintln!("[startup] +{}ms AppState created ({} extra roots)", setup_started.elapsed().as_millis(), state.extra_roots.lock().len());
     app.manage(state.clone());
+    // Opt-in HTTP API (off unless enabled in settings).
+    http_api::start_if_enabled(&state);
+    if !bench_mode {
+        match tray::install(app.handle(), state.clone()) {
+            // Started at login: stay in the tray until the user asks.
+            Ok(()) if autostart::launched_at_login() => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
+            Ok(()) => {}
+            Err(e) => eprintln!("[tray] unavailable: {e}"),
+        }
+    }
+    if let Some(link) = deep_link::from_args(&std::env::args().skip(1).collect::<Vec<_>>()) {
+        deep_link::handle(app.handle(), &state, link);
+    }
+    #[cfg(not(target_os = "macos"))]
+    if !portable::is_enabled() {
+        let identifier = app.config().identifier.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = deep_link::register(&identifier) {
+                eprintln!("[deep_link] registration failed: {e}");
+            }
+        });
+    }
     // Context menu item IDs use the "ctx_" prefix by convention.
     // All matching IDs are forwarded as "context_menu_action" events to the frontend.
     #[cfg(target_os = "macos")]
@@ -7128,12 +11142,26 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
                 "ctx_reveal" => "reveal",
                 "ctx_copy_files" => "copy_files",
                 "ctx_copy_path" => "copy_path",
+                "ctx_share" => "share",
                 "ctx_trash" => "trash",
                 "ctx_rename" => "rename",
+                "ctx_exclude_folder" => "exclude_folder",
                 _ => return,
             };
             let _ = app.emit("context_menu_action", action);
         });
+        // Services > "Search in Everything": bring the window up and hand
+        // the query to the frontend as `service_search`.
+        let services_app = app.handle().clone();
+        if let Err(e) = mac::services::register(move |query| {
+            if let Some(window) = services_app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            let _ = services_app.emit("service_search", query);
+        }) {
+            eprintln!("[services] registration failed: {e}");
+        }
     }
     if bench_mode {
         if let Some(window) = app.get_webview_window("main") {
@@ -7155,6 +11183,9 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
             return;
         }
         eprintln!("[startup/thread] +{}ms init_db_tables done", thread_started.elapsed().as_millis());
+        if let Ok(pinned) = db_connection(&state.db_path).and_then(|c| pins::load_pinned_paths(&c)) {
+            *state.pinned_paths.write() = pinned;
+        }
 
         state.db_ready.store(true, AtomicOrdering::Release);
         eprintln!("[startup/thread] +{}ms db_ready=true -- launching indexing immediately", thread_started.elapsed().as_millis());
@@ -7176,6 +11207,7 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
         // DB), so search is live immediately; only writes wait. Bounded — falls
         // through (WAL-safe: the daemon skips VACUUM while the beacon is held).
         daemon::wait_for_daemon_exit(&state.db_path);
+        start_wal_checkpoint_scheduler(state.clone());
 
         // Deferred housekeeping -- purge + status counts run in background
         {
@@ -7366,11 +11398,15 @@ fn setup_app(app: &mut tauri::App) -> AppResult<()> {
         #[cfg(target_os = "windows")]
         {
             win::start_windows_indexing(app_handle.clone(), state.clone());
+            let jump_state = state.clone();
+            std::thread::spawn(move || refresh_jump_list(&jump_state));
         }
 
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         {
             let _ = start_full_index_worker(app_handle.clone(), state.clone());
+            #[cfg(target_os = "linux")]
+            linux::watcher::start(app_handle.clone(), state.clone());
         }
 
         if bench_mode {
@@ -7419,10 +11455,24 @@ pub fn run() {
         );
     }
     let mut builder = tauri::Builder::default()
+        // First, so a second launch (jump-list item, `everything://` link,
+        // `--search`) hands its arguments to the running app and exits
+        // before it opens the index or starts a watcher.
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            deep_link::handle_forwarded_args(app, &args);
+        }))
         .plugin(tauri_plugin_decorum::init())
         .plugin(tauri_plugin_drag::init());
 
-    if !cfg!(debug_assertions) {
+    if let Some(dir) = portable::data_dir() {
+        eprintln!("[startup] portable mode, data in {}", dir.display());
+        // Keep WebView2's profile (cache, local storage) with the rest.
+        #[cfg(target_os = "windows")]
+        std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", dir.join("webview"));
+    }
+    // The window-state plugin saves to the user's config dir, so portable
+    // runs go without it.
+    if !cfg!(debug_assertions) && !portable::is_enabled() {
         builder = builder.plugin(tauri_plugin_window_state::Builder::default().build());
     } else {
         eprintln!(
@@ -7441,6 +11491,16 @@ pub fn run() {
                 payload.url()
             );
         })
+        .on_window_event(|window, event| {
+            // With the tray up, closing the window keeps the app (and the
+            // watcher) running; Quit lives in the tray menu.
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main" && window.app_handle().tray_by_id("main").is_some() {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
+            }
+        })
         .setup(|app| {
             setup_app(app).map_err(|e| {
                 Box::<dyn std::error::Error>::from(io::Error::new(io::ErrorKind::Other, e))
@@ -7448,21 +11508,80 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_index_status,
+            get_permission_errors,
             get_home_dir,
             start_full_index,
             reset_index,
             search,
+            cancel_search,
+            count,
+            export_results,
+            save_search,
+            list_saved_searches,
+            delete_saved_search,
+            record_search_history,
+            list_search_history,
+            clear_search_history,
+            take_launch_query,
+            get_http_api_status,
+            set_http_api_config,
+            get_index_tuning,
+            set_index_tuning,
+            list_profiles,
+            create_profile,
+            delete_profile,
+            switch_profile,
+            get_db_location,
+            set_db_location,
+            pin_entry,
+            unpin_entry,
+            list_pins,
+            get_dir_size,
+            compute_hash,
+            cancel_hash,
+            batch_move,
+            batch_copy,
+            cancel_transfer,
+            create_file,
+            create_folder,
+            create_symlink,
+            get_file_preview,
+            get_thumbnail,
+            open_terminal_here,
+            compress_to_zip,
+            extract_archive,
+            find_duplicates,
+            cancel_find_duplicates,
+            largest_entries,
+            recent_files,
+            get_extended_metadata,
+            get_properties,
+            undo_last_operation,
+            restore_from_trash,
+            watch_query,
+            unwatch_query,
+            get_index_stats,
+            find_probable_duplicates,
             fd_search,
+            format_times,
             quick_look,
+            share_items,
             open,
             open_with,
             reveal_in_finder,
+            prepare_drag,
             show_package_contents,
             copy_paths,
+            copy_as,
             copy_files,
             move_to_trash,
+            validate_operation,
             rename,
+            reindex_subtree,
+            compact_index,
+            check_index_integrity,
             get_file_icon,
+            prefetch_icons,
             get_platform,
             show_context_menu,
             set_native_theme,
@@ -7472,10 +11591,38 @@ pub fn run() {
             open_privacy_settings,
             open_pathignore,
             open_pathindexing,
-            restart_app
+            set_scan_roots,
+            add_ignore_rule,
+            remove_ignore_rule,
+            exclude_folder,
+            list_ignore_rules,
+            open_pathpriority,
+            restart_app,
+            set_indexing_paused,
+            get_launch_at_login,
+            set_launch_at_login
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, _event| {
+            #[cfg(target_os = "macos")]
+            match _event {
+                // Dock icon click with the window hidden by close.
+                tauri::RunEvent::Reopen {
+                    has_visible_windows: false,
+                    ..
+                } => tray::show_main_window(_app),
+                tauri::RunEvent::Opened { urls } => {
+                    let Some(state) = _app.try_state::<AppState>() else {
+                        return;
+                    };
+                    for link in urls.iter().filter_map(|url| deep_link::parse(url.as_str())) {
+                        deep_link::handle(_app, &state, link);
+                    }
+                }
+                _ => {}
+            }
+        });
 }
 
 fn main() {
@@ -7535,76 +11682,61 @@ mod tests {
         );
     }
 
-    fn mk_entry(path: &str, name: &str) -> EntryDto {
-        let parent = Path::new(path)
-            .parent()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "/".to_string());
-        EntryDto {
-            path: path.to_string(),
-            name: name.to_string(),
-            dir: parent,
-            is_dir: false,
-            ext: None,
-            size: None,
-            mtime: None,
-        }
-    }
 
     fn test_state_for(db_path: PathBuf, home_dir: PathBuf, cwd: PathBuf) -> AppState {
         AppState {
             config_file_path: home_dir.join(".pathignore"),
             pathindexing_file_path: home_dir.join(".pathindexing"),
             extra_roots: Arc::new(Mutex::new(Vec::new())),
+            pathpriority_file_path: home_dir.join(".pathpriority"),
+            hot_dirs: Arc::new(Mutex::new(Vec::new())),
+            data_dir: db_path.parent().unwrap_or(&home_dir).to_path_buf(),
             db_path,
             home_dir: home_dir.clone(),
             scan_root: home_dir.clone(),
             cwd,
             path_ignores: Arc::new(Vec::new()),
-            path_ignore_patterns: Arc::new(Vec::new()),
+            applied_pathignore: Arc::new(Mutex::new(Vec::new())),
             db_ready: Arc::new(AtomicBool::new(true)),
             indexing_active: Arc::new(AtomicBool::new(false)),
             status: Arc::new(Mutex::new(IndexStatus::default())),
             recent_ops: Arc::new(Mutex::new(Vec::new())),
+            undo_log: Arc::new(Mutex::new(undo::UndoLog::default())),
+            live_queries: Arc::new(live_query::LiveQueryRegistry::default()),
             icon_cache: Arc::new(Mutex::new(HashMap::new())),
+            thumbnail_cache: Arc::new(thumbnails::ThumbnailCache::new(home_dir.join("thumbnails"))),
             fd_search_cache: Arc::new(Mutex::new(None)),
             negative_name_cache: Arc::new(Mutex::new(HashMap::new())),
             ignore_cache: Arc::new(Mutex::new(None)),
             fts_ready: Arc::new(AtomicBool::new(true)),
             mem_index: Arc::new(RwLock::new(None)),
+            mem_resident: Arc::new(mem_resident::Resident::default()),
             watcher_stop: Arc::new(AtomicBool::new(false)),
             watcher_active: Arc::new(AtomicBool::new(false)),
+            indexing_paused: Arc::new(AtomicBool::new(false)),
+            watcher_backlog: Arc::new(AtomicUsize::new(0)),
             frontend_ready: Arc::new(AtomicBool::new(true)),
             pathindexing_active: Arc::new(AtomicBool::new(false)),
             search_conn_pool: Arc::new(Mutex::new(Vec::new())),
             watcher_conn: Arc::new(Mutex::new(None)),
+                wsearch_fallback: Arc::new(AtomicBool::new(false)),
+            search_pressure: Arc::new(scan_tuning::SearchPressure::default()),
+            search_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+            permission_log: Arc::new(permission_log::PermissionErrorLog::default()),
+            search_result_cache: Arc::new(Mutex::new(search_cache::SearchResultCache::default())),
+            metadata_cache: Arc::new(Mutex::new(media_meta::MetadataCache::default())),
+            pinned_paths: Arc::new(RwLock::new(HashSet::new())),
+            hash_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+            duplicate_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+            transfer_cancels: Arc::new(search_cancel::SearchCancelRegistry::default()),
+            last_db_activity: Arc::new(AtomicI64::new(0)),
+            launch_query: Arc::new(Mutex::new(None)),
+            http_server: Arc::new(Mutex::new(None)),
+            db_writes: Arc::new(db_location::WriteGate::default()),
+            index_tuning: Arc::new(RwLock::new(index_tuning::IndexTuning::default())),
         }
     }
 
-    fn insert_test_entry(conn: &Connection, path: &Path, is_dir: bool, run_id: i64) {
-        let name = path
-            .file_name()
-            .map(|name| name.to_string_lossy().to_string())
-            .unwrap_or_else(|| path.to_string_lossy().to_string());
-        let dir = path
-            .parent()
-            .map(|parent| parent.to_string_lossy().to_string())
-            .unwrap_or_else(|| "/".to_string());
-        conn.execute(
-            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-             VALUES(?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5, ?6)",
-            params![
-                path.to_string_lossy().to_string(),
-                name,
-                dir,
-                if is_dir { 1 } else { 0 },
-                now_epoch(),
-                run_id,
-            ],
-        )
-        .unwrap();
-    }
-
     #[test]
     fn should_skip_path_for_ignored_descendant() {
         let ignored = vec![PathBuf::from(
@@ -7623,11 +11755,10 @@ mod tests {
             "/Users/al02402336/Library/Developer/Xcode/DerivedData",
         )];
         let entries = vec![
-            mk_entry(
+            test_entry(
                 "/Users/al02402336/Library/Developer/Xcode/DerivedData/LINE-bqqx/Localization/Strings/-Users-al02402336-a_desktop",
-                "-Users-al02402336-a_desktop",
             ),
-            mk_entry("/Users/al02402336/a_desktop", "a_desktop"),
+            test_entry("/Users/al02402336/a_desktop"),
         ];
 
         let filtered = filter_ignored_entries(entries, &ignored, &[]);
@@ -7658,84 +11789,686 @@ mod tests {
     }
 
     #[test]
-    fn relevance_sort_prefers_shallow_exact_match() {
-        let mut entries = vec![
-            mk_entry("/Users/al02402336/work/a_desktop", "a_desktop"),
-            mk_entry("/Users/al02402336/a_desktop", "a_desktop"),
-            mk_entry(
-                "/Users/al02402336/Library/Developer/Xcode/DerivedData/-Users-al02402336-a_desktop",
-                "-Users-al02402336-a_desktop",
-            ),
-        ];
+    fn ignore_glob_understands_character_classes() {
+        let base = Path::new("/");
+        let home = Path::new("/Users/me");
+        let build = parse_ignore_pattern("**/build-[0-9]*", base, home).unwrap();
+        assert!(matches!(build, IgnorePattern::Glob(_)));
+        assert!(matches_ignore_pattern("/Users/me/app/build-2024", &build));
+        assert!(!matches_ignore_pattern("/Users/me/app/build-next", &build));
+
+        let objects = parse_ignore_pattern("~/src/*.[!c]", base, home).unwrap();
+        assert!(matches_ignore_pattern("/Users/me/src/main.o", &objects));
+        assert!(!matches_ignore_pattern("/Users/me/src/main.c", &objects));
+
+        // Unclosed: `[` is a literal character, and keeps the segment fast path.
+        let literal = parse_ignore_pattern("**/notes[old", base, home).unwrap();
+        assert!(matches!(literal, IgnorePattern::AnySegment { .. }));
+        assert!(glob_match_path("/a/[tmp*", "/a/[tmp-1"));
+        // A class never matches the separator.
+        assert!(!glob_match_path("/a[/]b", "/a/b"));
+    }
+
+    #[test]
+    fn relevance_sort_prefers_shallow_exact_match() {
+        let mut entries = vec![
+            test_entry("/Users/al02402336/work/a_desktop"),
+            test_entry("/Users/al02402336/a_desktop"),
+            test_entry(
+                "/Users/al02402336/Library/Developer/Xcode/DerivedData/-Users-al02402336-a_desktop",
+            ),
+        ];
+
+        sort_entries_with_relevance(&mut entries, "a_desktop", "name", "asc");
+
+        assert_eq!(entries[0].path, "/Users/al02402336/a_desktop");
+    }
+
+    #[test]
+    fn name_sort_ignores_case_in_both_directions() {
+        let mut entries = vec![
+            test_entry("/r/b/Beta"),
+            test_entry("/r/alpha"),
+            test_entry("/r/a/Beta"),
+            test_entry("/r/ÄRGER"),
+        ];
+        let paths = |entries: &[EntryDto]| -> Vec<String> {
+            entries.iter().map(|e| e.path.clone()).collect()
+        };
+        sort_entries(&mut entries, "name", "asc");
+        assert_eq!(paths(&entries), ["/r/alpha", "/r/a/Beta", "/r/b/Beta", "/r/ÄRGER"]);
+        sort_entries(&mut entries, "name", "desc");
+        assert_eq!(paths(&entries), ["/r/ÄRGER", "/r/b/Beta", "/r/a/Beta", "/r/alpha"]);
+        assert_eq!(cmp_lowercase("ÄRGER", "ärger"), Ordering::Equal);
+    }
+
+    #[test]
+    fn resolved_dir_range_excludes_sibling_with_same_prefix() {
+        let dir_exact = "/Users/user/Projects";
+        let dir_prefix = format!("{}/", dir_exact);
+        let dir_prefix_end = format!("{}0", dir_exact);
+
+        let in_scope = |dir: &str| {
+            dir == dir_exact || (dir >= dir_prefix.as_str() && dir < dir_prefix_end.as_str())
+        };
+
+        assert!(in_scope("/Users/user/Projects"));
+        assert!(in_scope("/Users/user/Projects/src"));
+        assert!(in_scope("/Users/user/Projects/src/deeper"));
+
+        assert!(!in_scope("/Users/user/Projects-archived"));
+        assert!(!in_scope("/Users/user/Projects.archived"));
+        assert!(!in_scope("/Users/user/Projects_archived"));
+        assert!(!in_scope("/Users/user/Projectz"));
+    }
+
+    #[test]
+    fn delete_paths_root_clears_all_entries() {
+        let root = temp_case_dir("delete_paths_root");
+        let dir_a = root.join("alpha");
+        let dir_b = root.join("beta").join("nested");
+        fs::create_dir_all(&dir_a).unwrap();
+        fs::create_dir_all(&dir_b).unwrap();
+
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+        let now = now_epoch();
+
+        for (path, name, dir, ext) in [
+            (dir_a.join("a.txt"), "a.txt", dir_a.clone(), "txt"),
+            (dir_b.join("b.rs"), "b.rs", dir_b.clone(), "rs"),
+        ] {
+            conn.execute(
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
+                params![
+                    path.to_string_lossy().to_string(),
+                    name,
+                    dir.to_string_lossy().to_string(),
+                    ext,
+                    now
+                ],
+            )
+            .unwrap();
+        }
+
+        let deleted = delete_paths(&mut conn, &["/".to_string()]).unwrap();
+        assert!(deleted >= 2);
+
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rename_subtree_moves_descendants_in_place() {
+        let root = temp_case_dir("rename_subtree");
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+        let now = now_epoch();
+
+        let old = root.join("old");
+        let new = root.join("new");
+        let sibling = root.join("old-sibling");
+        for (path, dir, is_dir) in [
+            (old.clone(), root.clone(), 1),
+            (old.join("a.txt"), old.clone(), 0),
+            (old.join("sub"), old.clone(), 1),
+            (old.join("sub").join("b.txt"), old.join("sub"), 0),
+            (sibling.join("c.txt"), sibling.clone(), 0),
+            (new.join("stale.txt"), new.clone(), 0),
+        ] {
+            conn.execute(
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
+                params![
+                    path.to_string_lossy().to_string(),
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    dir.to_string_lossy().to_string(),
+                    is_dir,
+                    now
+                ],
+            )
+            .unwrap();
+        }
+
+        let path_str = |p: &Path| p.to_string_lossy().to_string();
+        let (moved, removed) = rename_subtree(&mut conn, &path_str(&old), &path_str(&new)).unwrap();
+        assert_eq!((moved, removed), (3, 1));
+
+        let mut rows: Vec<(String, String)> = conn
+            .prepare("SELECT path, dir FROM entries ORDER BY path")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        rows.sort();
+        let mut expected = vec![
+            (path_str(&old), path_str(&root)),
+            (path_str(&new.join("a.txt")), path_str(&new)),
+            (path_str(&new.join("sub")), path_str(&new)),
+            (path_str(&new.join("sub").join("b.txt")), path_str(&new.join("sub"))),
+            (path_str(&sibling.join("c.txt")), path_str(&sibling)),
+        ];
+        expected.sort();
+        assert_eq!(rows, expected);
+
+        // Nested ends are left to the plain path handling.
+        assert_eq!(
+            rename_subtree(&mut conn, &path_str(&new), &path_str(&new.join("sub"))).unwrap(),
+            (0, 0)
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rename_case_only_respells_rows_without_duplicating() {
+        let root = temp_case_dir("rename_case_only");
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+        let now = now_epoch();
+
+        let old = root.join("Docs");
+        let new = root.join("DOCS");
+        for (path, dir, is_dir) in [
+            (old.clone(), root.clone(), 1),
+            (old.join("a.txt"), old.clone(), 0),
+            // What a stat-and-upsert of the new spelling left behind.
+            (new.clone(), root.clone(), 1),
+        ] {
+            conn.execute(
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, ?4, NULL, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
+                params![
+                    path.to_string_lossy().to_string(),
+                    path.file_name().unwrap().to_string_lossy().to_string(),
+                    dir.to_string_lossy().to_string(),
+                    is_dir,
+                    now
+                ],
+            )
+            .unwrap();
+        }
+
+        let path_str = |p: &Path| p.to_string_lossy().to_string();
+        let (moved, removed) =
+            rename_case_only(&mut conn, &path_str(&old), &path_str(&new)).unwrap();
+        assert_eq!((moved, removed), (2, 1));
+
+        let mut rows: Vec<(String, String, String)> = conn
+            .prepare("SELECT path, name, dir FROM entries ORDER BY path")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .map(Result::unwrap)
+            .collect();
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![
+                (path_str(&new), "DOCS".to_string(), path_str(&root)),
+                (path_str(&new.join("a.txt")), "a.txt".to_string(), path_str(&new)),
+            ]
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn reindex_subtree_reconciles_only_target_and_respects_guard() {
+        let root = temp_case_dir("reindex_subtree");
+        let target = root.join("target_dir");
+        let sibling = root.join("sibling");
+        fs::create_dir_all(&target).unwrap();
+        fs::create_dir_all(&sibling).unwrap();
+        fs::write(target.join("new.txt"), "n").unwrap();
+
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+        insert_test_entry(&conn, &target.join("stale.txt"), false, 1);
+        insert_test_entry(&conn, &sibling.join("untouched.txt"), false, 1);
+
+        let state = test_state_for(db_path, root.clone(), root.clone());
+        let result =
+            reindex_subtree_blocking(&state, &target.to_string_lossy()).unwrap();
+        assert_eq!(result.deleted, 1, "stale row under target removed");
+        assert!(result.upserted >= 2, "target dir + new.txt upserted");
+        assert!(!state.indexing_active.load(AtomicOrdering::Acquire));
+
+        let paths: Vec<String> = conn
+            .prepare("SELECT path FROM entries ORDER BY path")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .flatten()
+            .collect();
+        assert!(paths.contains(&target.join("new.txt").to_string_lossy().to_string()));
+        assert!(!paths.contains(&target.join("stale.txt").to_string_lossy().to_string()));
+        assert!(
+            paths.contains(&sibling.join("untouched.txt").to_string_lossy().to_string()),
+            "rows outside the subtree must be left alone"
+        );
+
+        state.indexing_active.store(true, AtomicOrdering::Release);
+        assert!(reindex_subtree_blocking(&state, &target.to_string_lossy()).is_err());
+        assert!(reindex_subtree_blocking(&state, "relative/path").is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn create_entry_indexes_new_file_and_folder_immediately() {
+        let root = temp_case_dir("create_entry");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        let dir = root.to_string_lossy().to_string();
+
+        let file = create_entry_blocking(&state, &dir, " notes.md ", false).unwrap();
+        assert_eq!(file.name, "notes.md");
+        assert_eq!(file.ext.as_deref(), Some("md"));
+        assert_eq!(file.size, Some(0));
+        let folder = create_entry_blocking(&state, &dir, "drafts", true).unwrap();
+        assert!(folder.is_dir && root.join("drafts").is_dir());
+
+        let conn = db_connection(&db_path).unwrap();
+        for path in [&file.path, &folder.path] {
+            let indexed: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM entries WHERE path = ?1",
+                    params![path],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(indexed, 1, "{path} indexed without waiting for the watcher");
+        }
+
+        assert!(create_entry_blocking(&state, &dir, "notes.md", false).is_err());
+        assert!(create_entry_blocking(&state, &dir, "a/b", true).is_err());
+        let missing = root.join("missing").to_string_lossy().to_string();
+        assert!(create_entry_blocking(&state, &missing, "x.txt", false).is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn undo_last_operation_renames_back_and_reindexes() {
+        let root = temp_case_dir("undo_last_operation");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        assert!(undo_last_operation_blocking(None, &state).unwrap().is_none());
+
+        let from = root.join("draft.txt");
+        let to = root.join("final.txt");
+        fs::write(&to, b"text").unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+        upsert_rows(&mut conn, &[index_row_from_path(&to).unwrap()]).unwrap();
+        state.undo_log.lock().push(undo::UndoOp::Rename {
+            from: from.to_string_lossy().to_string(),
+            to: to.to_string_lossy().to_string(),
+        });
+
+        let undone = undo_last_operation_blocking(None, &state).unwrap().unwrap();
+        assert_eq!(undone.kind, "rename");
+        assert_eq!(undone.restored, vec![from.to_string_lossy().to_string()]);
+        assert!(from.exists() && !to.exists());
+        let indexed: Vec<String> = conn
+            .prepare("SELECT name FROM entries WHERE dir = ?1")
+            .unwrap()
+            .query_map(params![root.to_string_lossy()], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(indexed, vec!["draft.txt"]);
+        assert!(undo_last_operation_blocking(None, &state).unwrap().is_none());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn index_stats_count_extensions_folders_and_full_index_duration() {
+        let root = temp_case_dir("index_stats");
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs").join("a.pdf"), b"12345").unwrap();
+        fs::write(root.join("docs").join("b.pdf"), b"123").unwrap();
+        fs::write(root.join("notes.txt"), b"12").unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        let mut conn = db_connection(&db_path).unwrap();
+        let rows: Vec<IndexRow> = [
+            root.join("docs"),
+            root.join("docs").join("a.pdf"),
+            root.join("docs").join("b.pdf"),
+            root.join("notes.txt"),
+        ]
+        .iter()
+        .map(|p| index_row_from_path(p).unwrap())
+        .collect();
+        upsert_rows(&mut conn, &rows).unwrap();
+
+        let stats = index_stats_blocking(&state).unwrap();
+        assert_eq!((stats.entries_count, stats.file_count, stats.dir_count), (4, 3, 1));
+        assert_eq!(stats.total_size, 10);
+        assert_eq!(stats.by_extension[0].key, "pdf");
+        assert_eq!(stats.by_extension[0].file_count, 2);
+        assert_eq!(stats.by_top_folder[0].key, "docs");
+        assert_eq!(stats.by_top_folder[0].size, 8);
+        assert!(stats.db_size > 0);
+        assert_eq!(stats.last_full_index_ms, None);
+
+        record_full_index_duration(&conn, Duration::from_millis(1234));
+        state.watcher_backlog.store(7, AtomicOrdering::Relaxed);
+        let stats = index_stats_blocking(&state).unwrap();
+        assert_eq!(stats.last_full_index_ms, Some(1234));
+        assert!(stats.last_full_index_at.is_some());
+        assert_eq!(stats.watcher_backlog, 7);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn set_scan_roots_persists_scans_and_purges_extra_roots() {
+        let home = temp_case_dir("scan_roots_home");
+        let extra = temp_case_dir("scan_roots_extra");
+        fs::create_dir_all(&home).unwrap();
+        fs::create_dir_all(&extra).unwrap();
+        fs::write(extra.join("data.csv"), b"1,2").unwrap();
+        let db_path = home.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), home.clone(), home.clone());
+        let conn = db_connection(&db_path).unwrap();
+        let indexed = |path: &Path| -> bool {
+            conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)",
+                params![path.to_string_lossy()],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        let extra_str = extra.to_string_lossy().to_string();
+        let set = set_scan_roots_blocking(None, &state, std::slice::from_ref(&extra_str)).unwrap();
+        assert_eq!(set.extra_roots, vec![extra_str.clone()]);
+        assert_eq!(*state.extra_roots.lock(), vec![extra.clone()]);
+        assert_eq!(
+            pathindexing::load_pathindexing_roots(&state.pathindexing_file_path),
+            vec![extra.clone()]
+        );
+        assert!(indexed(&extra.join("data.csv")));
+        assert_eq!(get_meta(&conn, "indexed_extra_roots"), Some(extra_str));
+
+        assert!(set_scan_roots_blocking(None, &state, &["relative".to_string()]).is_err());
+        assert!(set_scan_roots_blocking(None, &state, &[]).unwrap().extra_roots.is_empty());
+        assert!(!indexed(&extra.join("data.csv")));
+        assert!(!indexed(&extra));
+        assert!(!state.pathindexing_active.load(AtomicOrdering::Acquire));
+
+        let _ = fs::remove_dir_all(home);
+        let _ = fs::remove_dir_all(extra);
+    }
+
+    #[test]
+    fn ignore_rules_added_purge_and_removed_reindex() {
+        let home = temp_case_dir("ignore_rule_change");
+        fs::create_dir_all(home.join("proj").join("cachedir")).unwrap();
+        fs::create_dir_all(home.join("secret")).unwrap();
+        fs::create_dir_all(home.join("logs")).unwrap();
+        fs::write(home.join("proj").join("cachedir").join("x.bin"), b"x").unwrap();
+        fs::write(home.join("proj").join("a.txt"), b"a").unwrap();
+        fs::write(home.join("secret").join("k.txt"), b"k").unwrap();
+        fs::write(home.join("logs").join("app.log"), b"l").unwrap();
+        // Root rules are canonicalized (macOS temp dirs sit behind /var).
+        let home = if cfg!(windows) {
+            home
+        } else {
+            fs::canonicalize(&home).unwrap()
+        };
+        let db_path = home.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        // As if `secret` had been in .pathignore at startup: removing the
+        // rule must still bring it back.
+        let state = AppState {
+            path_ignores: Arc::new(vec![home.join("secret")]),
+            ..test_state_for(db_path.clone(), home.clone(), home.clone())
+        };
+        let mut conn = db_connection(&db_path).unwrap();
+        rescan::rescan_subtree(&mut conn, &home, &[], &[]).unwrap();
+        let indexed = |path: &Path| -> bool {
+            conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)",
+                params![path.to_string_lossy()],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+
+        let added = change_ignore_rule_blocking(None, &state, "**/cachedir", true).unwrap();
+        assert_eq!((added.kind.as_str(), added.changed, added.purged), ("pattern", true, 2));
+        assert!(!indexed(&home.join("proj").join("cachedir").join("x.bin")));
+        assert!(indexed(&home.join("proj").join("a.txt")));
+        assert!(!change_ignore_rule_blocking(None, &state, "**/cachedir/", true).unwrap().changed);
+
+        let secret = home.join("secret").to_string_lossy().to_string();
+        let added = change_ignore_rule_blocking(None, &state, &secret, true).unwrap();
+        assert_eq!((added.kind.as_str(), added.purged), ("root", 2));
+        let logs = format!("{}/logs/*.log", normalize_slashes(home.to_string_lossy().to_string()));
+        assert_eq!(change_ignore_rule_blocking(None, &state, &logs, true).unwrap().purged, 1);
+        assert!(indexed(&home.join("logs")));
+        assert!(!indexed(&home.join("logs").join("app.log")));
+        let rules = pathignore_active_entries(&fs::read_to_string(&state.config_file_path).unwrap());
+        assert!(rules.contains(&secret) && rules.contains(&logs));
+
+        let removed = change_ignore_rule_blocking(None, &state, &secret, false).unwrap();
+        assert!(removed.changed && removed.reindexed >= 2);
+        assert!(indexed(&home.join("secret").join("k.txt")));
+        let removed = change_ignore_rule_blocking(None, &state, "**/cachedir", false).unwrap();
+        assert!(removed.changed);
+        assert!(indexed(&home.join("proj").join("cachedir").join("x.bin")));
+        assert!(!change_ignore_rule_blocking(None, &state, "**/cachedir", false).unwrap().changed);
+
+        assert!(change_ignore_rule_blocking(None, &state, "relative/dir", true).is_err());
+        assert!(!state.indexing_active.load(AtomicOrdering::Acquire));
+
+        let _ = fs::remove_dir_all(home);
+    }
+
+    #[test]
+    fn pathignore_reload_applies_hand_edits() {
+        let home = temp_case_dir("pathignore_reload");
+        fs::create_dir_all(home.join("proj").join("cachedir")).unwrap();
+        fs::write(home.join("proj").join("cachedir").join("x.bin"), b"x").unwrap();
+        fs::write(home.join("proj").join("a.txt"), b"a").unwrap();
+        let home = if cfg!(windows) {
+            home
+        } else {
+            fs::canonicalize(&home).unwrap()
+        };
+        let db_path = home.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), home.clone(), home.clone());
+        let mut conn = db_connection(&db_path).unwrap();
+        rescan::rescan_subtree(&mut conn, &home, &[], &[]).unwrap();
+        let indexed = |path: &Path| -> bool {
+            conn.query_row(
+                "SELECT EXISTS(SELECT 1 FROM entries WHERE path = ?1)",
+                params![path.to_string_lossy()],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        let cached = home.join("proj").join("cachedir").join("x.bin");
 
-        sort_entries_with_relevance(&mut entries, "a_desktop", "name", "asc");
+        assert_eq!(reload_pathignore_locked(&state).unwrap(), None);
+        fs::write(&state.config_file_path, "# edited by hand\n**/cachedir\n").unwrap();
+        let (purged, _) = reload_pathignore_locked(&state).unwrap().unwrap();
+        assert_eq!(purged, 2);
+        assert!(!indexed(&cached));
+        assert!(indexed(&home.join("proj").join("a.txt")));
+        let (roots, patterns) = cached_effective_ignore_rules(&state);
+        assert!(should_skip_path(&cached, &roots, &patterns));
+        assert_eq!(reload_pathignore_locked(&state).unwrap(), None);
 
-        assert_eq!(entries[0].path, "/Users/al02402336/a_desktop");
+        fs::write(&state.config_file_path, "# edited by hand\n").unwrap();
+        let (_, reindexed) = reload_pathignore_locked(&state).unwrap().unwrap();
+        assert!(reindexed >= 2);
+        assert!(indexed(&cached));
+        assert!(state.applied_pathignore.lock().is_empty());
+
+        let _ = fs::remove_dir_all(home);
     }
 
     #[test]
-    fn resolved_dir_range_excludes_sibling_with_same_prefix() {
-        let dir_exact = "/Users/user/Projects";
-        let dir_prefix = format!("{}/", dir_exact);
-        let dir_prefix_end = format!("{}0", dir_exact);
-
-        let in_scope = |dir: &str| {
-            dir == dir_exact || (dir >= dir_prefix.as_str() && dir < dir_prefix_end.as_str())
+    fn exclude_folder_purges_rows_and_skips_future_changes() {
+        let home = temp_case_dir("exclude_folder");
+        fs::create_dir_all(home.join("build").join("out")).unwrap();
+        fs::write(home.join("build").join("out").join("a.o"), b"o").unwrap();
+        fs::write(home.join("keep.txt"), b"k").unwrap();
+        let home = if cfg!(windows) {
+            home
+        } else {
+            fs::canonicalize(&home).unwrap()
         };
+        let db_path = home.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), home.clone(), home.clone());
+        let mut conn = db_connection(&db_path).unwrap();
+        rescan::rescan_subtree(&mut conn, &home, &[], &[]).unwrap();
 
-        assert!(in_scope("/Users/user/Projects"));
-        assert!(in_scope("/Users/user/Projects/src"));
-        assert!(in_scope("/Users/user/Projects/src/deeper"));
+        let build = home.join("build");
+        let dto = exclude_folder_blocking(None, &state, &build.to_string_lossy()).unwrap();
+        assert_eq!((dto.kind.as_str(), dto.changed, dto.purged), ("root", true, 3));
+        let remaining: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM entries WHERE path LIKE ?1",
+                params![format!("{}%", build.to_string_lossy())],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(remaining, 0);
+        let (roots, patterns) = cached_effective_ignore_rules(&state);
+        assert!(should_skip_path(&build.join("out").join("b.o"), &roots, &patterns));
+        assert!(!should_skip_path(&home.join("keep.txt"), &roots, &patterns));
 
-        assert!(!in_scope("/Users/user/Projects-archived"));
-        assert!(!in_scope("/Users/user/Projects.archived"));
-        assert!(!in_scope("/Users/user/Projects_archived"));
-        assert!(!in_scope("/Users/user/Projectz"));
+        assert!(exclude_folder_blocking(None, &state, &home.join("keep.txt").to_string_lossy()).is_err());
+        assert!(exclude_folder_blocking(None, &state, &home.to_string_lossy()).is_err());
+
+        let _ = fs::remove_dir_all(home);
     }
 
     #[test]
-    fn delete_paths_root_clears_all_entries() {
-        let root = temp_case_dir("delete_paths_root");
-        let dir_a = root.join("alpha");
-        let dir_b = root.join("beta").join("nested");
-        fs::create_dir_all(&dir_a).unwrap();
-        fs::create_dir_all(&dir_b).unwrap();
+    fn compress_and_extract_round_trip_and_index_results() {
+        let root = temp_case_dir("zip_round_trip");
+        let album = root.join("album");
+        fs::create_dir_all(album.join("raw")).unwrap();
+        fs::write(album.join("a.jpg"), b"jpeg").unwrap();
+        fs::write(album.join("raw").join("a.cr2"), b"raw").unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+
+        let album_str = album.to_string_lossy().to_string();
+        let zipped = compress_to_zip_blocking(None, &state, &[album_str], None, None).unwrap();
+        assert_eq!(zipped.path, root.join("album.zip").to_string_lossy());
+        assert_eq!(zipped.entries, 4);
+
+        // "album" is taken, so the lone top-level folder lands as "album 2".
+        let extracted = extract_archive_blocking(None, &state, &zipped.path, None).unwrap();
+        let target = root.join("album 2");
+        assert_eq!(extracted.path, target.to_string_lossy());
+        assert_eq!(fs::read(target.join("raw").join("a.cr2")).unwrap(), b"raw");
+        assert_eq!(extracted.indexed, 4);
+
+        let conn = db_connection(&db_path).unwrap();
+        for path in [&zipped.path, &extracted.path] {
+            let indexed: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM entries WHERE path = ?1",
+                    params![path],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert_eq!(indexed, 1, "{path} indexed without waiting for the watcher");
+        }
+        let leftovers = fs::read_dir(&root)
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".extracting"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        let _ = fs::remove_dir_all(root);
+    }
 
+    #[test]
+    fn compact_index_reclaims_deleted_rows_and_respects_guard() {
+        let root = temp_case_dir("compact_index");
+        fs::create_dir_all(&root).unwrap();
         let db_path = root.join("index.db");
         init_db_tables(&db_path).unwrap();
         ensure_db_indexes(&db_path).unwrap();
         let mut conn = db_connection(&db_path).unwrap();
-        let now = now_epoch();
-
-        for (path, name, dir, ext) in [
-            (dir_a.join("a.txt"), "a.txt", dir_a.clone(), "txt"),
-            (dir_b.join("b.rs"), "b.rs", dir_b.clone(), "rs"),
-        ] {
-            conn.execute(
-                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
-                params![
-                    path.to_string_lossy().to_string(),
-                    name,
-                    dir.to_string_lossy().to_string(),
-                    ext,
-                    now
-                ],
-            )
-            .unwrap();
-        }
+        let bulk = root.join("bulk");
+        conn.execute_batch("BEGIN").unwrap();
+        for i in 0..3_000 {
+            insert_test_entry(&conn, &bulk.join(format!("file_{i:05}_with_a_long_name.txt")), false, 1);
+        }
+        conn.execute_batch("COMMIT").unwrap();
+        insert_test_entry(&conn, &root.join("keep.txt"), false, 1);
+        delete_paths(&mut conn, &[bulk.to_string_lossy().to_string()]).unwrap();
+        drop(conn);
 
-        let deleted = delete_paths(&mut conn, &["/".to_string()]).unwrap();
-        assert!(deleted >= 2);
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        let result = compact_index_blocking(&state).unwrap();
+        assert!(result.after_bytes < result.before_bytes, "{result:?}");
+        assert!(!state.indexing_active.load(AtomicOrdering::Acquire));
 
+        let conn = db_connection(&db_path).unwrap();
         let remaining: i64 = conn
             .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
             .unwrap();
-        assert_eq!(remaining, 0);
+        assert_eq!(remaining, 1);
+
+        state.indexing_active.store(true, AtomicOrdering::Release);
+        assert!(compact_index_blocking(&state).is_err());
 
         let _ = fs::remove_dir_all(root);
     }
 
+    #[test]
+    fn signature_matches_tells_apart_edits_within_one_second() {
+        let mut row = index_row_from_path(&std::env::temp_dir()).unwrap();
+        row.mtime = Some(1_700_000_000);
+        row.mtime_nsec = Some(250_000_000);
+        row.size = Some(10);
+
+        assert!(signature_matches(&(Some(1_700_000_000), Some(250_000_000), Some(10)), &row));
+        assert!(!signature_matches(&(Some(1_700_000_000), Some(750_000_000), Some(10)), &row));
+        assert!(!signature_matches(&(Some(1_700_000_000), Some(250_000_000), Some(11)), &row));
+        // A row from a whole-second scan compares on seconds alone.
+        assert!(signature_matches(&(Some(1_700_000_000), None, Some(10)), &row));
+        row.mtime_nsec = None;
+        assert!(signature_matches(&(Some(1_700_000_000), Some(750_000_000), Some(10)), &row));
+    }
+
     #[test]
     fn preload_direct_children_never_includes_scan_root_or_grandchildren() {
         let root = temp_case_dir("preload_direct_children");
@@ -7816,9 +12549,9 @@ mod tests {
         fs::write(&present, "present").unwrap();
 
         let existing = HashMap::from([
-            (ignored.to_string_lossy().to_string(), (None, None)),
-            (missing.to_string_lossy().to_string(), (None, None)),
-            (present.to_string_lossy().to_string(), (None, None)),
+            (ignored.to_string_lossy().to_string(), (None, None, None)),
+            (missing.to_string_lossy().to_string(), (None, None, None)),
+            (present.to_string_lossy().to_string(), (None, None, None)),
         ]);
         let reconciliation = reconcile_unseen_direct_children(
             &existing,
@@ -7847,7 +12580,7 @@ mod tests {
         let denied = root.join("denied");
         let denied_existing = HashMap::from([(
             denied.to_string_lossy().to_string(),
-            (None, None),
+            (None, None, None),
         )]);
         let error = reconcile_unseen_direct_children(
             &denied_existing,
@@ -8035,23 +12768,43 @@ mod tests {
         fs::write(&file_b, "b").unwrap();
 
         // Two new files → +2
-        let out = apply_path_changes(&state, &[file_a.clone(), file_b.clone()]).unwrap();
+        let out = apply_path_changes(
+            &state,
+            &[file_a.clone(), file_b.clone()],
+            &WatcherHints::default(),
+        )
+        .unwrap();
         assert_eq!(out.count_delta, 2);
         assert_eq!(out.changed, 2);
 
         // Re-upserting an existing path → no net change
-        let out = apply_path_changes(&state, &[file_a.clone()]).unwrap();
+        let out = apply_path_changes(
+            &state,
+            &[file_a.clone()],
+            &WatcherHints::default(),
+        )
+        .unwrap();
         assert_eq!(out.count_delta, 0);
 
         // Vanished file → -1
         fs::remove_file(&file_b).unwrap();
-        let out = apply_path_changes(&state, &[file_b.clone()]).unwrap();
+        let out = apply_path_changes(
+            &state,
+            &[file_b.clone()],
+            &WatcherHints::default(),
+        )
+        .unwrap();
         assert_eq!(out.count_delta, -1);
 
         // A vanished directory sweeps its remaining subtree rows: a.txt is
         // still in the DB and goes with the range delete → -1.
         fs::remove_dir_all(&docs).unwrap();
-        let out = apply_path_changes(&state, &[docs.clone()]).unwrap();
+        let out = apply_path_changes(
+            &state,
+            &[docs.clone()],
+            &WatcherHints::default(),
+        )
+        .unwrap();
         assert_eq!(out.count_delta, -1);
 
         // Accumulated deltas must equal the authoritative COUNT(*)
@@ -8082,8 +12835,9 @@ mod tests {
         let archived_path = archived_src.join("legacy.rs");
 
         conn.execute(
-            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
+            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                  name_norm, dir_norm)
+             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
             params![
                 project_path.to_string_lossy().to_string(),
                 "main.rs",
@@ -8095,8 +12849,9 @@ mod tests {
         .unwrap();
 
         conn.execute(
-            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
+            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                  name_norm, dir_norm)
+             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
             params![
                 archived_path.to_string_lossy().to_string(),
                 "legacy.rs",
@@ -8184,6 +12939,162 @@ mod tests {
         assert!(hit_after.is_none());
     }
 
+    #[test]
+    fn name_search_matches_decomposed_names_with_composed_query() {
+        let root = temp_case_dir("nfd_name_search");
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+
+        // As macOS returns it: "Résumé" with combining acute accents.
+        let file = root.join("Re\u{301}sume\u{301}.txt");
+        fs::write(&file, b"x").unwrap();
+        let row = index_row_from_path_and_metadata(&file, &fs::metadata(&file).unwrap()).unwrap();
+        assert_eq!(row.name_norm, "r\u{e9}sum\u{e9}.txt");
+        upsert_rows(&mut conn, &[row]).unwrap();
+
+        let state = test_state_for(db_path, root.clone(), root.clone());
+        for query in ["R\u{e9}sum\u{e9}.txt", "R\u{c9}SUM", "sum\u{e9}"] {
+            let result = execute_search(
+                &state,
+                query.to_string(),
+                Some(50),
+                Some(0),
+                Some("name".to_string()),
+                Some("asc".to_string()),
+            )
+            .unwrap();
+            let names: Vec<&str> = result.results.iter().map(|e| e.name.as_str()).collect();
+            assert_eq!(names, vec!["Re\u{301}sume\u{301}.txt"], "query {query:?}");
+        }
+    }
+
+    #[test]
+    fn name_search_ranks_in_sql_before_limit() {
+        let root = temp_case_dir("relevance_sql");
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+
+        // By path alone the deep copy and the prefix match come first.
+        let deep = root.join("a").join("b").join("c");
+        let shallow = root.join("z");
+        for (dir, name) in [
+            (&deep, "report.txt"),
+            (&root, "report.txt.bak"),
+            (&shallow, "report.txt"),
+        ] {
+            conn.execute(
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, 0, NULL, NULL, NULL, 0, 1, lower(?2), lower(?3))",
+                params![
+                    dir.join(name).to_string_lossy().to_string(),
+                    name,
+                    dir.to_string_lossy().to_string(),
+                ],
+            )
+            .unwrap();
+        }
+        drop(conn);
+
+        let state = test_state_for(db_path, root.clone(), root.clone());
+        let page = |limit: u32, offset: u32| -> Vec<String> {
+            execute_search(
+                &state,
+                "report.txt".to_string(),
+                Some(limit),
+                Some(offset),
+                Some("name".to_string()),
+                Some("asc".to_string()),
+            )
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|e| e.path)
+            .collect()
+        };
+        let expected = [
+            shallow.join("report.txt"),
+            deep.join("report.txt"),
+            root.join("report.txt.bak"),
+        ]
+        .map(|p| p.to_string_lossy().to_string());
+        assert_eq!(page(1, 0), expected[..1]);
+        assert_eq!(page(10, 0), expected);
+        assert_eq!(page(10, 1), expected[1..]);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn size_and_mtime_sorts_read_composite_index_order() {
+        let root = temp_case_dir("sort_composite_idx");
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+
+        let plan = |sql: &str| -> Vec<String> {
+            let mut stmt = conn.prepare(&format!("EXPLAIN QUERY PLAN {sql}")).unwrap();
+            stmt.query_map([], |row| row.get::<_, String>(3))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+        for (sort_by, sort_dir, index) in [
+            ("size", "asc", "idx_entries_ext_size"),
+            ("size", "desc", "idx_entries_ext_size"),
+            ("mtime", "asc", "idx_entries_ext_mtime"),
+            ("mtime", "desc", "idx_entries_ext_mtime"),
+        ] {
+            let order_by = sort_clause(sort_by, sort_dir, "e.");
+            let details = plan(&format!(
+                "SELECT e.path FROM entries e WHERE e.ext = 'png' ORDER BY {order_by} LIMIT 300"
+            ));
+            assert!(details.iter().any(|d| d.contains(index)), "{sort_by}/{sort_dir}: {details:?}");
+            assert!(
+                !details.iter().any(|d| d.contains("TEMP B-TREE FOR ORDER BY")),
+                "{sort_by}/{sort_dir}: {details:?}"
+            );
+        }
+        let bare = sort_clause("mtime", "desc", "");
+        let details = plan(&format!(
+            "SELECT path FROM entries WHERE name COLLATE NOCASE = 'readme.md' ORDER BY {bare} LIMIT 300"
+        ));
+        assert!(details.iter().any(|d| d.contains("idx_entries_name_mtime")), "{details:?}");
+
+        // Directories (NULL size) stay last in both directions.
+        for (path, size) in [("/r/a.png", Some(5)), ("/r/dir.png", None), ("/r/b.png", Some(9))] {
+            conn.execute(
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, '/r', ?3, 'png', NULL, ?4, 0, 1, lower(?2), '/r')",
+                params![path, &path[3..], size.is_none() as i64, size],
+            )
+            .unwrap();
+        }
+        let names = |sort_dir: &str| -> Vec<String> {
+            let order_by = sort_clause("size", sort_dir, "e.");
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT e.name FROM entries e WHERE e.ext = 'png' ORDER BY {order_by}"
+                ))
+                .unwrap();
+            stmt.query_map([], |row| row.get::<_, String>(0))
+                .unwrap()
+                .map(|r| r.unwrap())
+                .collect()
+        };
+        assert_eq!(names("asc"), vec!["a.png", "b.png", "dir.png"]);
+        assert_eq!(names("desc"), vec!["b.png", "a.png", "dir.png"]);
+
+        drop(conn);
+        let _ = fs::remove_dir_all(root);
+    }
+
     #[test]
     fn dir_listing_deep_nested_path_without_time_budget() {
         let root = temp_case_dir("dir_listing_deep");
@@ -8214,8 +13125,9 @@ mod tests {
             (&file2, "old.log", &sub_dir, "log"),
         ] {
             conn.execute(
-                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
                 params![
                     path.to_string_lossy().to_string(),
                     name,
@@ -8319,8 +13231,9 @@ mod tests {
 
         let file_path = proj_dir.join("archive.tar.gz");
         conn.execute(
-            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
+            "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                  name_norm, dir_norm)
+             VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
             params![
                 file_path.to_string_lossy().to_string(),
                 "archive.tar.gz",
@@ -8352,6 +13265,10 @@ mod tests {
     fn fts_phrase_quotes_and_escapes() {
         assert_eq!(fts_phrase("test"), "\"test\"");
         assert_eq!(fts_phrase("a\"b"), "\"a\"\"b\"");
+        assert_eq!(
+            fts_phrase("caf\u{e9}"),
+            "\"caf\u{e9}\" OR \"cafe\u{301}\""
+        );
     }
 
     #[test]
@@ -8370,6 +13287,12 @@ mod tests {
             Some("\"spec\" AND \".md\"".to_string())
         );
         assert_eq!(glob_fts_match_expr("***"), None);
+        // a class splits runs too; an unclosed `[` is literal
+        assert_eq!(
+            glob_fts_match_expr("*report[0-9].pdf"),
+            Some("\"report\" AND \".pdf\"".to_string())
+        );
+        assert_eq!(glob_fts_match_expr("*a[bcd*"), Some("\"a[bcd\"".to_string()));
     }
 
     #[test]
@@ -8396,8 +13319,9 @@ mod tests {
             let name = format!("file_zzfrag_{i:02}.txt");
             let path = root.join(&name);
             conn.execute(
-                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-                 VALUES(?1, ?2, ?3, 0, 'txt', NULL, NULL, ?4, 1)",
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, 0, 'txt', NULL, NULL, ?4, 1, lower(?2), lower(?3))",
                 params![
                     path.to_string_lossy().to_string(),
                     name,
@@ -8446,7 +13370,7 @@ mod tests {
 
         // Total count matches the contains set.
         let total = compute_total_count(&state, &page1);
-        assert_eq!(total, Some(10));
+        assert_eq!(total, Some((10, false)));
 
         let _ = fs::remove_dir_all(root);
     }
@@ -8471,8 +13395,9 @@ mod tests {
         ] {
             let path = root.join(name);
             conn.execute(
-                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id)
-                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1)",
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at, run_id,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, 0, ?4, NULL, NULL, ?5, 1, lower(?2), lower(?3))",
                 params![
                     path.to_string_lossy().to_string(),
                     name,
@@ -8505,6 +13430,160 @@ mod tests {
             vec!["TEST_upper.JS", "alpha_test_one.js", "beta_test_two.js"]
         );
 
+        // LIKE reads `[!a]` as any one character; the GLOB check narrows it.
+        let result = execute_search(
+            &state,
+            "[!a]*_test_*.js".to_string(),
+            Some(300),
+            Some(0),
+            Some("name".to_string()),
+            Some("asc".to_string()),
+        )
+        .unwrap();
+        let names: Vec<&str> = result.results.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["beta_test_two.js"]);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn execute_search_path_contains_matches_any_component() {
+        let root = temp_case_dir("path_contains");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+        for (rel, is_dir) in [
+            ("2023-taxes", true),
+            ("2023-taxes/w2.pdf", false),
+            ("2023-taxes/deep", true),
+            ("2023-taxes/deep/receipt.png", false),
+            ("other", true),
+            ("other/2023-taxes-summary.txt", false),
+            ("other/unrelated.txt", false),
+        ] {
+            insert_test_entry(&conn, &root.join(rel), is_dir, 1);
+        }
+        drop(conn);
+
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        state.status.lock().state = IndexState::Ready;
+
+        // Trigram plan first, then the LIKE scan; both must agree.
+        for fts in [true, false] {
+            state.fts_ready.store(fts, AtomicOrdering::Release);
+            let result = execute_search(
+                &state,
+                "path:2023-taxes".to_string(),
+                Some(300),
+                Some(0),
+                Some("name".to_string()),
+                Some("asc".to_string()),
+            )
+            .unwrap();
+            assert_eq!(result.mode_label, "path_contains");
+            let mut names: Vec<&str> = result.results.iter().map(|e| e.name.as_str()).collect();
+            names.sort();
+            assert_eq!(
+                names,
+                vec!["2023-taxes", "2023-taxes-summary.txt", "deep", "receipt.png", "w2.pdf"],
+                "fts_ready={fts}"
+            );
+        }
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn idle_checkpoint_waits_for_quiet_period_after_new_activity() {
+        let idle = WAL_IDLE_CHECKPOINT_AFTER.as_secs() as i64;
+        // No activity since startup: nothing to checkpoint.
+        assert!(!idle_checkpoint_due(0, 0, 1_000));
+        // Activity too recent.
+        assert!(!idle_checkpoint_due(1_000, 0, 1_000 + idle - 1));
+        assert!(idle_checkpoint_due(1_000, 0, 1_000 + idle));
+        // Already checkpointed this quiet period.
+        assert!(!idle_checkpoint_due(1_000, 1_000, 1_000 + idle * 3));
+    }
+
+    #[test]
+    fn count_matches_search_total_without_rows() {
+        let root = temp_case_dir("count_query");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        ensure_db_indexes(&db_path).unwrap();
+        let conn = db_connection(&db_path).unwrap();
+        for i in 0..12 {
+            insert_test_entry(&conn, &root.join(format!("screenshot_{i:02}.png")), false, 1);
+        }
+        insert_test_entry(&conn, &root.join("notes.txt"), false, 1);
+        drop(conn);
+
+        let state = test_state_for(db_path.clone(), root.clone(), root.clone());
+        state.status.lock().state = IndexState::Ready;
+
+        assert_eq!(count_blocking(&state, "screenshot").unwrap(), 12);
+        assert_eq!(count_blocking(&state, " path:screenshot_0 ").unwrap(), 10);
+        assert_eq!(count_blocking(&state, "").unwrap(), 13);
+
+        // Same number `search` reports as its total when the page is truncated.
+        let page = execute_search(
+            &state,
+            "screenshot".to_string(),
+            Some(5),
+            Some(0),
+            Some("name".to_string()),
+            Some("asc".to_string()),
+        )
+        .unwrap();
+        assert_eq!(compute_total_count(&state, &page), Some((12, false)));
+
+        state.db_ready.store(false, AtomicOrdering::Release);
+        assert!(count_blocking(&state, "screenshot").is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn full_scan_counts_are_sampled_on_large_indexes() {
+        let root = temp_case_dir("count_estimate");
+        fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        init_db_tables(&db_path).unwrap();
+        let mut conn = db_connection(&db_path).unwrap();
+        let tx = conn.transaction().unwrap();
+        for i in 0..1_000 {
+            let name = if i % 4 == 0 { format!("a_draft_{i}.txt") } else { format!("a_{i}.txt") };
+            insert_test_entry(&tx, &root.join(name), false, 1);
+        }
+        tx.commit().unwrap();
+
+        // Every 4th row matches. On a table this small the windows overlap,
+        // so the sample lands just under a quarter (1122 of 4500 rows).
+        let like = vec![SqlValue::Text("%draft%".to_string())];
+        assert_eq!(
+            estimate_match_count(&conn, "e.name LIKE ?1 ESCAPE '\\'", &like, 2_000_000),
+            Some(499_000)
+        );
+        let home = root.clone();
+        // "draft" is not a name prefix and FTS is off: the LIKE scan branch.
+        assert_eq!(
+            count_query_matches(&conn, &home, false, "draft", "name", Some(1_000)),
+            (250, false)
+        );
+        assert_eq!(
+            count_query_matches(&conn, &home, false, "draft", "name", Some(COUNT_ESTIMATE_MIN_ROWS)),
+            (49_900, true)
+        );
+        assert_eq!(count_query_matches(&conn, &home, false, "draft", "name", None), (250, false));
+
+        assert_eq!(round_estimate(12_437), 12_400);
+        assert_eq!(round_estimate(999), 999);
+        assert_eq!(round_estimate(1_005), 1_010);
+
+        drop(conn);
         let _ = fs::remove_dir_all(root);
     }
 
@@ -8627,7 +13706,7 @@ mod tests {
             fs::write(p, format!("touch_{i}_x")).unwrap();
             let batch = vec![p.clone()];
             let t = Instant::now();
-            apply_path_changes(&state, &batch).expect("watcher single");
+            apply_path_changes(&state, &batch, &WatcherHints::default()).expect("watcher single");
             single_ms.push(t.elapsed().as_secs_f64() * 1000.0);
         }
         let mut burst_ms = Vec::new();
@@ -8640,7 +13719,7 @@ mod tests {
                 })
                 .collect();
             let t = Instant::now();
-            apply_path_changes(&state, &batch).expect("watcher burst");
+            apply_path_changes(&state, &batch, &WatcherHints::default()).expect("watcher burst");
             burst_ms.push(t.elapsed().as_secs_f64() * 1000.0);
         }
         let med = |v: &mut Vec<f64>| -> f64 {
@@ -8736,7 +13815,7 @@ mod tests {
             let mut count_ms = Vec::new();
             let mut mode = String::new();
             let mut n_results = 0usize;
-            let mut total_count: Option<u32> = None;
+            let mut total_count: Option<(u32, bool)> = None;
             let mut results_hash: u64 = 0;
             let mut first_paths: Vec<String> = Vec::new();
             for _ in 0..iters {
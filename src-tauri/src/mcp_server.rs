@@ -2,7 +2,8 @@
 //!
 //! `everything --mcp` runs a standalone MCP server over stdin/stdout serving a
 //! `search` tool straight from the SQLite index, so agents get results even
-//! when the GUI app is not running. On normal app startup `register_all`
+//! when the GUI app is not running, plus an `open` tool that opens (or
+//! reveals) a result with the user's default app. On normal app startup `register_all`
 //! writes the server entry into Claude Code (`~/.claude.json`) and Codex
 //! (`~/.codex/config.toml`) so both agents pick it up automatically.
 
@@ -15,9 +16,9 @@ use serde_json::{json, Value};
 
 use crate::query::parse_query;
 use crate::{
-    db_connection_for_search, effective_search_limit, fts_usable, get_meta, resolve_home_dir,
-    run_db_search, sort_entries_with_relevance, AppResult, EntryDto, DB_FILE_NAME, MAX_LIMIT,
-    SORT_DIRS, SORT_KEYS,
+    db_connection_for_search, db_location, effective_search_limit, fts_usable, get_meta, portable, profiles,
+    resolve_home_dir, run_db_search, sort_entries_with_relevance, AppResult, EntryDto,
+    MAX_LIMIT, SORT_DIRS, SORT_KEYS,
 };
 
 const SERVER_NAME: &str = "everything";
@@ -39,13 +40,31 @@ extension; a query containing '/' restricts matches to a directory (e.g. \
 in the output have a trailing '/'. The index covers the user's home directory \
 (macOS) or C:\\ (Windows), minus build/cache noise like .git and node_modules.";
 
+const OPEN_TOOL_DESCRIPTION: &str = "Open a local file or folder with the \
+user's default application, or reveal it in Finder/Explorer with \
+reveal=true. Takes an absolute path, typically one returned by `search`. \
+Only folders and common document, image, audio and video files are opened; \
+anything else (programs, scripts, shortcuts, app bundles) can only be \
+revealed: this opens documents, it does not run code.";
+
+/// Document, image, audio and video extensions `open` hands to the default
+/// application. An allow-list: far more types run code when opened (hta,
+/// msc, appref-ms, terminal, fileloc, …) than a deny-list can keep up with.
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "aac", "aif", "aiff", "avi", "bmp", "csv", "doc", "docx", "epub", "flac", "gif", "heic",
+    "heif", "ico", "jpeg", "jpg", "key", "log", "m4a", "m4v", "markdown", "md", "mkv", "mov",
+    "mp3", "mp4", "mpeg", "mpg", "numbers", "odp", "ods", "odt", "ogg", "opus", "pages", "pdf",
+    "png", "ppt", "pptx", "psd", "rtf", "tif", "tiff", "tsv", "txt", "wav", "webm", "webp", "wma",
+    "wmv", "xls", "xlsx",
+];
+
 // ---------------------------------------------------------------------------
 // CLI entry
 // ---------------------------------------------------------------------------
 
-/// Handles MCP-related CLI flags before Tauri boots. Returns `true` when the
-/// invocation was fully handled and the process should exit without starting
-/// the GUI.
+/// Handles headless CLI flags (`--mcp`, `--daemon`, `--es`, …) before Tauri
+/// boots. Returns `true` when the invocation was fully handled and the process
+/// should exit without starting the GUI.
 pub fn handle_cli_args() -> bool {
     let args: Vec<String> = std::env::args().skip(1).collect();
     if args.iter().any(|a| a == "--daemon") {
@@ -60,6 +79,9 @@ pub fn handle_cli_args() -> bool {
         register_all_and_log(None);
         return true;
     }
+    if args.first().is_some_and(|a| a == "--es") {
+        std::process::exit(crate::es_cli::run(&args[1..]));
+    }
     false
 }
 
@@ -72,41 +94,36 @@ pub fn handle_cli_args() -> bool {
 const APP_BUNDLE_ID: &str = "com.everything.app";
 
 /// The index DB to serve. Registration entries written by the app pin the
-/// Tauri-resolved path via `EVERYTHING_MCP_DB`; the hand-derived guess below
-/// (Tauri's `app_data_dir()` layout for `APP_BUNDLE_ID`) is only the
-/// fallback for entries or invocations without that pin.
+/// Tauri-resolved path via `EVERYTHING_MCP_DB`; otherwise a portable data dir
+/// wins, and the hand-derived guess below (Tauri's `app_data_dir()` layout
+/// for `APP_BUNDLE_ID`, then the active profile's directory in it) is the
+/// last fallback.
 pub(crate) fn default_db_path() -> PathBuf {
     if let Ok(p) = std::env::var("EVERYTHING_MCP_DB") {
         return PathBuf::from(p);
     }
-    #[cfg(target_os = "macos")]
-    {
-        resolve_home_dir()
-            .join("Library/Application Support")
-            .join(APP_BUNDLE_ID)
-            .join(DB_FILE_NAME)
+    if let Some(dir) = portable::data_dir() {
+        return db_location::db_path(&profiles::active_data_dir(dir));
     }
+    #[cfg(target_os = "macos")]
+    let app_data_dir = resolve_home_dir()
+        .join("Library/Application Support")
+        .join(APP_BUNDLE_ID);
     #[cfg(target_os = "windows")]
-    {
-        let roaming = std::env::var("APPDATA")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| resolve_home_dir().join("AppData").join("Roaming"));
-        roaming.join(APP_BUNDLE_ID).join(DB_FILE_NAME)
-    }
+    let app_data_dir = std::env::var("APPDATA")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| resolve_home_dir().join("AppData").join("Roaming"))
+        .join(APP_BUNDLE_ID);
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    {
-        resolve_home_dir()
-            .join(".local/share")
-            .join(APP_BUNDLE_ID)
-            .join(DB_FILE_NAME)
-    }
+    let app_data_dir = resolve_home_dir().join(".local/share").join(APP_BUNDLE_ID);
+    db_location::db_path(&profiles::active_data_dir(&app_data_dir))
 }
 
 // ---------------------------------------------------------------------------
 // Search over the index DB
 // ---------------------------------------------------------------------------
 
-fn open_search_connection(db_path: &Path) -> AppResult<Connection> {
+pub(crate) fn open_search_connection(db_path: &Path) -> AppResult<Connection> {
     if !db_path.exists() {
         return Err(format!(
             "Index database not found at {}. Launch the Everything app once to build the index.",
@@ -142,7 +159,7 @@ once to build the index (the background indexer may lack filesystem access).";
 /// and left rows behind — the same readiness rule the GUI uses at startup.
 /// Anything else (missing DB, half-built, empty) means the daemon is still
 /// preparing it.
-fn index_is_usable(db_path: &Path) -> bool {
+pub(crate) fn index_is_usable(db_path: &Path) -> bool {
     if !db_path.exists() {
         return false;
     }
@@ -301,7 +318,9 @@ impl McpServer {
         let result = match method {
             "initialize" => Ok(handle_initialize(params)),
             "ping" => Ok(json!({})),
-            "tools/list" => Ok(json!({ "tools": [search_tool_definition()] })),
+            "tools/list" => Ok(json!({
+                "tools": [search_tool_definition(), open_tool_definition()]
+            })),
             "tools/call" => self.handle_tools_call(params),
             _ => Err((-32601, format!("Method not found: {method}"))),
         };
@@ -313,11 +332,13 @@ impl McpServer {
 
     fn handle_tools_call(&mut self, params: &Value) -> Result<Value, (i64, String)> {
         let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
-        if name != "search" {
-            return Err((-32602, format!("Unknown tool: {name:?}")));
-        }
         let arguments = params.get("arguments").unwrap_or(&Value::Null);
-        Ok(match parse_search_args(arguments).and_then(|args| self.do_search(&args)) {
+        let outcome = match name {
+            "search" => parse_search_args(arguments).and_then(|args| self.do_search(&args)),
+            "open" => do_open(arguments),
+            _ => return Err((-32602, format!("Unknown tool: {name:?}"))),
+        };
+        Ok(match outcome {
             Ok(text) => tool_result(&text, false),
             Err(message) => tool_result(&message, true),
         })
@@ -341,6 +362,7 @@ impl McpServer {
             args.offset,
             &args.sort_by,
             &args.sort_dir,
+            None,
         );
         let mut results = match searched {
             Ok(r) => r,
@@ -422,6 +444,77 @@ fn search_tool_definition() -> Value {
     })
 }
 
+fn open_tool_definition() -> Value {
+    json!({
+        "name": "open",
+        "title": "Open file",
+        "description": OPEN_TOOL_DESCRIPTION,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Absolute path of the file or folder."
+                },
+                "reveal": {
+                    "type": "boolean",
+                    "default": false,
+                    "description": "Show it in Finder/Explorer instead of opening it."
+                }
+            },
+            "required": ["path"]
+        }
+    })
+}
+
+/// Whether opening `path` shows a document or a folder rather than running
+/// something. Folders laid out as bundles (`X.app/Contents`, `.workflow`,
+/// `.mpkg`, …) are programs too.
+fn opens_as_document(path: &Path, meta: &fs::Metadata) -> bool {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    if meta.is_dir() {
+        return ext.is_none() || !path.join("Contents").is_dir();
+    }
+    let is_document = ext.is_some_and(|e| DOCUMENT_EXTENSIONS.contains(&e.as_str()));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        is_document && meta.permissions().mode() & 0o111 == 0
+    }
+    #[cfg(not(unix))]
+    {
+        is_document
+    }
+}
+
+fn do_open(arguments: &Value) -> Result<String, String> {
+    let path = arguments
+        .get("path")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| "`path` is required and must be a non-empty string.".to_string())?;
+    let reveal = arguments.get("reveal").and_then(|v| v.as_bool()).unwrap_or(false);
+    if !Path::new(path).is_absolute() {
+        return Err(format!("{path:?} is not an absolute path."));
+    }
+    let meta = fs::metadata(path).map_err(|_| format!("No such file or folder: {path}"))?;
+    if reveal {
+        crate::reveal_in_finder_impl(vec![path.to_string()])?;
+        return Ok(format!("Revealed {path}"));
+    }
+    if !opens_as_document(Path::new(path), &meta) {
+        return Err(format!(
+            "Refusing to open {path}: it is not a folder or a known document type, and opening it could run code. Use reveal=true to show it instead."
+        ));
+    }
+    crate::open_paths_blocking(&[path.to_string()], false)?;
+    Ok(format!("Opened {path}"))
+}
+
 fn tool_result(text: &str, is_error: bool) -> Value {
     json!({
         "content": [{ "type": "text", "text": text }],
@@ -673,8 +766,9 @@ mod tests {
         ];
         for (i, (path, name, dir, is_dir, ext)) in rows.iter().enumerate() {
             conn.execute(
-                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0)",
+                "INSERT INTO entries(path, name, dir, is_dir, ext, mtime, size, indexed_at,
+                                      name_norm, dir_norm)
+                 VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, lower(?2), lower(?3))",
                 rusqlite::params![path, name, dir, is_dir, ext, 100 + i as i64, 10 * i as i64],
             )
             .unwrap();
@@ -750,9 +844,57 @@ mod tests {
         let req = json!({ "jsonrpc": "2.0", "id": 5, "method": "tools/list" });
         let resp = server.handle_line(&req.to_string()).unwrap();
         let tools = resp["result"]["tools"].as_array().unwrap();
-        assert_eq!(tools.len(), 1);
+        assert_eq!(tools.len(), 2);
         assert_eq!(tools[0]["name"], "search");
         assert_eq!(tools[0]["inputSchema"]["required"], json!(["query"]));
+        assert_eq!(tools[1]["name"], "open");
+        assert_eq!(tools[1]["inputSchema"]["required"], json!(["path"]));
+    }
+
+    #[test]
+    fn open_refuses_missing_relative_and_program_paths() {
+        let root = temp_case_dir("open");
+        let mut server = test_server(&root);
+        let mut call_open = |arguments: Value| {
+            let req = json!({
+                "jsonrpc": "2.0", "id": 8, "method": "tools/call",
+                "params": { "name": "open", "arguments": arguments },
+            });
+            let resp = server.handle_line(&req.to_string()).unwrap();
+            let result = &resp["result"];
+            assert_eq!(result["isError"], true);
+            result["content"][0]["text"].as_str().unwrap().to_string()
+        };
+
+        assert!(call_open(json!({})).contains("`path` is required"));
+        assert!(call_open(json!({ "path": "docs/report.pdf" })).contains("not an absolute path"));
+        let missing = root.join("missing.pdf");
+        assert!(call_open(json!({ "path": missing })).contains("No such file"));
+        let script = root.join("install.BAT");
+        fs::write(&script, b"echo hi").unwrap();
+        assert!(call_open(json!({ "path": script })).contains("Refusing to open"));
+        for name in ["setup.hta", "console.msc", "shell.terminal", "site.fileloc", "notes"] {
+            let file = root.join(name);
+            fs::write(&file, b"x").unwrap();
+            assert!(call_open(json!({ "path": file })).contains("Refusing to open"), "{name}");
+        }
+        let bundle = root.join("Tool.app");
+        fs::create_dir_all(bundle.join("Contents")).unwrap();
+        assert!(call_open(json!({ "path": bundle })).contains("Refusing to open"));
+    }
+
+    #[test]
+    fn opens_folders_and_document_types_only() {
+        let root = temp_case_dir("open_kinds");
+        let doc = root.join("Report.PDF");
+        fs::write(&doc, b"%PDF").unwrap();
+        assert!(opens_as_document(&doc, &fs::metadata(&doc).unwrap()));
+        let folder = root.join("v1.2");
+        fs::create_dir_all(&folder).unwrap();
+        assert!(opens_as_document(&folder, &fs::metadata(&folder).unwrap()));
+        let shortcut = root.join("app.appref-ms");
+        fs::write(&shortcut, b"x").unwrap();
+        assert!(!opens_as_document(&shortcut, &fs::metadata(&shortcut).unwrap()));
     }
 
     #[test]
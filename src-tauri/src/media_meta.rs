@@ -0,0 +1,1359 @@
+//! `get_extended_metadata`: details-pane fields read from the file itself —
+//! camera EXIF from JPEG/TIFF/raw, ID3 and Vorbis tags, audio/video codecs
+//! and duration from MP3, FLAC, WAV and MP4/MOV, and PDF page counts. The
+//! parsers are deliberately small: they read headers and tag blocks only,
+//! never decode media, and skip anything they don't recognise. Results are
+//! kept in a small LRU keyed by path, mtime and size.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::SystemTime;
+
+use flate2::read::ZlibDecoder;
+use serde::Serialize;
+
+use crate::AppResult;
+
+const METADATA_CACHE_CAPACITY: usize = 64;
+/// Enough for EXIF (one 64 KB APP1 segment), ID3v2 tags with small cover
+/// art, and the header chunks of FLAC/WAV.
+const HEAD_BYTES: u64 = 512 * 1024;
+/// PDFs and MP4 `moov` boxes larger than this are not parsed.
+const MAX_PARSE_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtendedMetadataDto {
+    pub path: String,
+    /// "image", "audio", "video", "pdf" or "other".
+    pub kind: String,
+    /// Container format: "jpeg", "tiff", "png", "gif", "mp3", "flac",
+    /// "wav", "mp4", "pdf".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// EXIF orientation (1-8); 5-8 mean width and height display swapped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orientation: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_make: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub camera_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lens_model: Option<String>,
+    /// EXIF DateTimeOriginal as written by the camera ("2024:05:01 12:00:00").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub taken_at: Option<String>,
+    /// Shutter speed, e.g. "1/250" or "2".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exposure_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub f_number: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iso: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focal_length_mm: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub album: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub video_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio_codec: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channels: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitrate_kbps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_count: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MetadataKey {
+    path: String,
+    mtime_ns: u128,
+    len: u64,
+}
+
+impl MetadataKey {
+    pub(crate) fn for_path(path: &Path) -> AppResult<Self> {
+        let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+        if meta.is_dir() {
+            return Err(format!("Not a file: {}", path.display()));
+        }
+        let mtime_ns = meta
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        Ok(MetadataKey {
+            path: path.to_string_lossy().to_string(),
+            mtime_ns,
+            len: meta.len(),
+        })
+    }
+}
+
+/// Most recently used first; an edited file gets a new key and simply misses.
+#[derive(Debug, Default)]
+pub(crate) struct MetadataCache {
+    entries: VecDeque<(MetadataKey, ExtendedMetadataDto)>,
+}
+
+impl MetadataCache {
+    pub(crate) fn get(&mut self, key: &MetadataKey) -> Option<ExtendedMetadataDto> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos)?;
+        let hit = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(hit)
+    }
+
+    pub(crate) fn insert(&mut self, key: MetadataKey, value: ExtendedMetadataDto) {
+        self.entries.retain(|(k, _)| k.path != key.path);
+        self.entries.push_front((key, value));
+        self.entries.truncate(METADATA_CACHE_CAPACITY);
+    }
+}
+
+fn be16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn be64(b: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(b.get(at..at + 8)?.try_into().ok()?))
+}
+
+fn le16(b: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(b.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le32(b: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(b.get(at..at + 4)?.try_into().ok()?))
+}
+
+/// Trimmed, NUL-free, non-empty text.
+fn clean(text: &str) -> Option<String> {
+    let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn utf16(bytes: &[u8], big_endian: bool) -> String {
+    let units = bytes.chunks_exact(2).map(|p| {
+        if big_endian {
+            u16::from_be_bytes([p[0], p[1]])
+        } else {
+            u16::from_le_bytes([p[0], p[1]])
+        }
+    });
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+fn set_if_none<T>(slot: &mut Option<T>, value: Option<T>) {
+    if slot.is_none() {
+        *slot = value;
+    }
+}
+
+pub(crate) fn extended_metadata(path: &Path) -> AppResult<ExtendedMetadataDto> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut head = Vec::with_capacity(len.min(HEAD_BYTES) as usize);
+    file.by_ref()
+        .take(HEAD_BYTES)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+
+    let mut meta = ExtendedMetadataDto {
+        path: path.to_string_lossy().to_string(),
+        kind: "other".to_string(),
+        ..ExtendedMetadataDto::default()
+    };
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default();
+    let format = |meta: &mut ExtendedMetadataDto, kind: &str, format: &str| {
+        meta.kind = kind.to_string();
+        meta.format = Some(format.to_string());
+    };
+
+    if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        format(&mut meta, "image", "jpeg");
+        parse_jpeg(&head, &mut meta);
+    } else if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        // TIFF, and the TIFF-based raw formats (DNG, CR2, NEF, ARW, ...).
+        format(&mut meta, "image", "tiff");
+        apply_exif(&head, &mut meta);
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        format(&mut meta, "image", "png");
+        meta.width = be32(&head, 16);
+        meta.height = be32(&head, 20);
+    } else if head.starts_with(b"GIF8") {
+        format(&mut meta, "image", "gif");
+        meta.width = le16(&head, 6).map(u32::from);
+        meta.height = le16(&head, 8).map(u32::from);
+    } else if head.starts_with(b"fLaC") {
+        format(&mut meta, "audio", "flac");
+        parse_flac(&head, &mut meta);
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        format(&mut meta, "audio", "wav");
+        parse_wav(&head, &mut meta);
+    } else if head.starts_with(b"ID3") || ext == "mp3" {
+        format(&mut meta, "audio", "mp3");
+        parse_mp3(&mut file, len, &head, &mut meta);
+    } else if head.get(4..8) == Some(b"ftyp") {
+        let brand = head.get(8..12).unwrap_or_default();
+        if [&b"heic"[..], b"heix", b"mif1", b"avif"].contains(&brand) {
+            format(&mut meta, "image", "heif");
+        } else {
+            format(&mut meta, "video", "mp4");
+            parse_mp4(&mut file, len, &mut meta);
+        }
+    } else if head.starts_with(b"%PDF") {
+        format(&mut meta, "pdf", "pdf");
+        if len <= MAX_PARSE_BYTES {
+            let mut data = head;
+            file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+            meta.page_count = pdf_page_count(&data);
+        }
+    } else if matches!(
+        ext.as_str(),
+        "mkv" | "webm" | "avi" | "wmv" | "flv" | "mpg" | "mpeg"
+    ) {
+        meta.kind = "video".to_string();
+    }
+    Ok(meta)
+}
+
+// --- EXIF / TIFF ---------------------------------------------------------
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+/// One IFD entry: tag, field type, value count, offset of the value bytes.
+struct IfdEntry {
+    tag: u16,
+    field_type: u16,
+    count: u32,
+    value_at: usize,
+}
+
+impl Tiff<'_> {
+    fn u16(&self, at: usize) -> Option<u16> {
+        if self.little_endian {
+            le16(self.data, at)
+        } else {
+            be16(self.data, at)
+        }
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        if self.little_endian {
+            le32(self.data, at)
+        } else {
+            be32(self.data, at)
+        }
+    }
+
+    fn entries(&self, ifd: usize) -> Vec<IfdEntry> {
+        let count = self.u16(ifd).unwrap_or(0) as usize;
+        (0..count)
+            .filter_map(|i| {
+                let at = ifd + 2 + i * 12;
+                let field_type = self.u16(at + 2)?;
+                let count = self.u32(at + 4)?;
+                let unit = match field_type {
+                    3 | 8 => 2,
+                    4 | 9 | 11 => 4,
+                    5 | 10 | 12 => 8,
+                    _ => 1,
+                };
+                let value_at = if unit * count as usize <= 4 {
+                    at + 8
+                } else {
+                    self.u32(at + 8)? as usize
+                };
+                Some(IfdEntry {
+                    tag: self.u16(at)?,
+                    field_type,
+                    count,
+                    value_at,
+                })
+            })
+            .collect()
+    }
+
+    fn ascii(&self, e: &IfdEntry) -> Option<String> {
+        let bytes = self.data.get(e.value_at..e.value_at + e.count as usize)?;
+        clean(&String::from_utf8_lossy(bytes))
+    }
+
+    fn uint(&self, e: &IfdEntry) -> Option<u32> {
+        match e.field_type {
+            3 => self.u16(e.value_at).map(u32::from),
+            4 => self.u32(e.value_at),
+            _ => None,
+        }
+    }
+
+    fn rational(&self, e: &IfdEntry) -> Option<(u32, u32)> {
+        if e.field_type != 5 {
+            return None;
+        }
+        let den = self.u32(e.value_at + 4)?;
+        (den != 0).then_some((self.u32(e.value_at)?, den))
+    }
+}
+
+/// Parse a TIFF structure (EXIF APP1 payload or a whole TIFF/raw file).
+fn apply_exif(data: &[u8], meta: &mut ExtendedMetadataDto) {
+    let little_endian = match data.get(0..2) {
+        Some(b"II") => true,
+        Some(b"MM") => false,
+        _ => return,
+    };
+    let tiff = Tiff {
+        data,
+        little_endian,
+    };
+    let Some(ifd0) = tiff.u32(4) else {
+        return;
+    };
+    let mut exif_ifd = None;
+    for e in tiff.entries(ifd0 as usize) {
+        match e.tag {
+            0x0100 => set_if_none(&mut meta.width, tiff.uint(&e)),
+            0x0101 => set_if_none(&mut meta.height, tiff.uint(&e)),
+            0x010F => meta.camera_make = tiff.ascii(&e),
+            0x0110 => meta.camera_model = tiff.ascii(&e),
+            0x0112 => meta.orientation = tiff.u16(e.value_at).filter(|o| (1..=8).contains(o)),
+            0x0132 => set_if_none(&mut meta.taken_at, tiff.ascii(&e)),
+            0x8769 => exif_ifd = tiff.uint(&e),
+            _ => {}
+        }
+    }
+    let Some(exif_ifd) = exif_ifd else {
+        return;
+    };
+    for e in tiff.entries(exif_ifd as usize) {
+        match e.tag {
+            0x829A => {
+                meta.exposure_time = tiff.rational(&e).map(|(num, den)| {
+                    if num >= den {
+                        format!("{}", (num as f64 / den as f64 * 10.0).round() / 10.0)
+                    } else {
+                        format!("1/{}", (den as f64 / num.max(1) as f64).round())
+                    }
+                })
+            }
+            0x829D => meta.f_number = tiff.rational(&e).map(|(n, d)| n as f64 / d as f64),
+            0x8827 => meta.iso = tiff.uint(&e),
+            // DateTimeOriginal wins over IFD0's DateTime (last edit).
+            0x9003 => meta.taken_at = tiff.ascii(&e).or(meta.taken_at.take()),
+            0x920A => meta.focal_length_mm = tiff.rational(&e).map(|(n, d)| n as f64 / d as f64),
+            0xA002 => set_if_none(&mut meta.width, tiff.uint(&e)),
+            0xA003 => set_if_none(&mut meta.height, tiff.uint(&e)),
+            0xA434 => meta.lens_model = tiff.ascii(&e),
+            _ => {}
+        }
+    }
+}
+
+fn parse_jpeg(data: &[u8], meta: &mut ExtendedMetadataDto) {
+    let mut i = 2;
+    while i + 4 <= data.len() {
+        if data[i] != 0xFF {
+            break;
+        }
+        let marker = data[i + 1];
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD8).contains(&marker) {
+            i += 2;
+            continue;
+        }
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let Some(seg_len) = be16(data, i + 2).map(usize::from) else {
+            break;
+        };
+        let segment = data
+            .get(i + 4..(i + 2 + seg_len).min(data.len()))
+            .unwrap_or_default();
+        match marker {
+            0xE1 if segment.starts_with(b"Exif\0\0") => apply_exif(&segment[6..], meta),
+            // Start-of-frame markers, except DHT (C4), JPG (C8) and DAC (CC).
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                meta.height = be16(segment, 1).map(u32::from);
+                meta.width = be16(segment, 3).map(u32::from);
+            }
+            _ => {}
+        }
+        i += 2 + seg_len;
+    }
+}
+
+// --- Audio ---------------------------------------------------------------
+
+/// ID3v2 text frame body: encoding byte, then text (first value only).
+fn id3_text(body: &[u8]) -> Option<String> {
+    let (&encoding, text) = body.split_first()?;
+    let decoded = match encoding {
+        0 => latin1(text),
+        1 => match text.get(0..2) {
+            Some([0xFE, 0xFF]) => utf16(&text[2..], true),
+            Some([0xFF, 0xFE]) => utf16(&text[2..], false),
+            _ => utf16(text, false),
+        },
+        2 => utf16(text, true),
+        _ => String::from_utf8_lossy(text).to_string(),
+    };
+    clean(decoded.split('\0').next().unwrap_or_default())
+}
+
+fn syncsafe(b: &[u8], at: usize) -> Option<usize> {
+    let bytes = b.get(at..at + 4)?;
+    Some(
+        bytes
+            .iter()
+            .fold(0usize, |n, &x| (n << 7) | usize::from(x & 0x7F)),
+    )
+}
+
+/// Read ID3v2 tags into `meta`; returns the tag's total size.
+fn parse_id3v2(data: &[u8], meta: &mut ExtendedMetadataDto) -> usize {
+    if !data.starts_with(b"ID3") || data.len() < 10 {
+        return 0;
+    }
+    let version = data[3];
+    let flags = data[5];
+    let size = syncsafe(data, 6).unwrap_or(0);
+    let total = 10 + size + if flags & 0x10 != 0 { 10 } else { 0 };
+    let end = (10 + size).min(data.len());
+    let mut i = 10;
+    if flags & 0x40 != 0 {
+        i += match version {
+            3 => be32(data, 10).map_or(0, |n| n as usize + 4),
+            _ => syncsafe(data, 10).unwrap_or(0),
+        };
+    }
+    let header_len = if version == 2 { 6 } else { 10 };
+    while i + header_len <= end {
+        let (id, frame_len) = if version == 2 {
+            let len = data[i + 3..i + 6]
+                .iter()
+                .fold(0usize, |n, &b| (n << 8) | usize::from(b));
+            (&data[i..i + 3], len)
+        } else if version == 4 {
+            (&data[i..i + 4], syncsafe(data, i + 4).unwrap_or(0))
+        } else {
+            (&data[i..i + 4], be32(data, i + 4).unwrap_or(0) as usize)
+        };
+        if id[0] == 0 || frame_len == 0 {
+            break;
+        }
+        let body_start = i + header_len;
+        let Some(body) = data.get(body_start..body_start + frame_len) else {
+            break;
+        };
+        let slot = match id {
+            b"TIT2" | b"TT2" => Some(&mut meta.title),
+            b"TPE1" | b"TP1" => Some(&mut meta.artist),
+            b"TALB" | b"TAL" => Some(&mut meta.album),
+            b"TYER" | b"TYE" | b"TDRC" => Some(&mut meta.year),
+            b"TRCK" | b"TRK" => Some(&mut meta.track),
+            b"TCON" | b"TCO" => Some(&mut meta.genre),
+            _ => None,
+        };
+        if let Some(slot) = slot {
+            set_if_none(slot, id3_text(body));
+        }
+        i = body_start + frame_len;
+    }
+    total
+}
+
+/// ID3v1 trailer: fills whatever ID3v2 left empty.
+fn parse_id3v1(tail: &[u8], meta: &mut ExtendedMetadataDto) {
+    if tail.len() != 128 || !tail.starts_with(b"TAG") {
+        return;
+    }
+    set_if_none(&mut meta.title, clean(&latin1(&tail[3..33])));
+    set_if_none(&mut meta.artist, clean(&latin1(&tail[33..63])));
+    set_if_none(&mut meta.album, clean(&latin1(&tail[63..93])));
+    set_if_none(&mut meta.year, clean(&latin1(&tail[93..97])));
+    if tail[125] == 0 && tail[126] != 0 {
+        set_if_none(&mut meta.track, Some(tail[126].to_string()));
+    }
+}
+
+const MP3_BITRATES_V1: [u32; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const MP3_BITRATES_V2: [u32; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+/// Tags, then duration from the first MPEG Layer III frame: exact from a
+/// Xing/Info or VBRI header, estimated from the bitrate otherwise.
+fn parse_mp3(file: &mut File, len: u64, head: &[u8], meta: &mut ExtendedMetadataDto) {
+    let tag_len = parse_id3v2(head, meta);
+    let mut has_v1 = false;
+    if len >= 128 && file.seek(SeekFrom::Start(len - 128)).is_ok() {
+        let mut tail = [0u8; 128];
+        if file.read_exact(&mut tail).is_ok() {
+            has_v1 = tail.starts_with(b"TAG");
+            parse_id3v1(&tail, meta);
+        }
+    }
+
+    let search_end = head.len().min(tag_len + 64 * 1024);
+    let Some(frame) = (tag_len..search_end.saturating_sub(4))
+        .find(|&i| head[i] == 0xFF && head[i + 1] & 0xE6 == 0xE2 && head[i + 2] >> 4 != 0x0F)
+    else {
+        return;
+    };
+    let (b1, b2, b3) = (head[frame + 1], head[frame + 2], head[frame + 3]);
+    let mpeg1 = (b1 >> 3) & 3 == 3;
+    let rates: [u32; 3] = match (b1 >> 3) & 3 {
+        3 => [44_100, 48_000, 32_000],
+        2 => [22_050, 24_000, 16_000],
+        _ => [11_025, 12_000, 8_000],
+    };
+    let Some(&sample_rate) = rates.get(usize::from((b2 >> 2) & 3)) else {
+        return;
+    };
+    let bitrate = if mpeg1 {
+        MP3_BITRATES_V1[usize::from(b2 >> 4)]
+    } else {
+        MP3_BITRATES_V2[usize::from(b2 >> 4)]
+    };
+    let mono = b3 >> 6 == 3;
+    let samples_per_frame = if mpeg1 { 1152.0 } else { 576.0 };
+    meta.audio_codec = Some("MP3".to_string());
+    meta.sample_rate = Some(sample_rate);
+    meta.channels = Some(if mono { 1 } else { 2 });
+
+    let side_info = match (mpeg1, mono) {
+        (true, true) => 17,
+        (true, false) => 32,
+        (false, true) => 9,
+        (false, false) => 17,
+    };
+    let xing = frame + 4 + side_info;
+    let vbr_frames = match head.get(xing..xing + 4) {
+        Some(b"Xing") | Some(b"Info") => be32(head, xing + 4)
+            .filter(|flags| flags & 1 != 0)
+            .and_then(|_| be32(head, xing + 8)),
+        _ if head.get(frame + 36..frame + 40) == Some(b"VBRI") => be32(head, frame + 50),
+        _ => None,
+    };
+    let audio_bytes = len.saturating_sub(frame as u64 + if has_v1 { 128 } else { 0 });
+    match vbr_frames.filter(|&f| f > 0) {
+        Some(frames) => {
+            let secs = f64::from(frames) * samples_per_frame / f64::from(sample_rate);
+            meta.duration_secs = Some(secs);
+            meta.bitrate_kbps = Some((audio_bytes as f64 * 8.0 / secs / 1000.0).round() as u32);
+        }
+        None if bitrate > 0 => {
+            meta.duration_secs = Some(audio_bytes as f64 * 8.0 / (f64::from(bitrate) * 1000.0));
+            meta.bitrate_kbps = Some(bitrate);
+        }
+        None => {}
+    }
+}
+
+fn apply_vorbis_comment(key: &str, value: &str, meta: &mut ExtendedMetadataDto) {
+    let slot = match key.to_ascii_uppercase().as_str() {
+        "TITLE" => &mut meta.title,
+        "ARTIST" => &mut meta.artist,
+        "ALBUM" => &mut meta.album,
+        "DATE" | "YEAR" => &mut meta.year,
+        "TRACKNUMBER" => &mut meta.track,
+        "GENRE" => &mut meta.genre,
+        _ => return,
+    };
+    set_if_none(slot, clean(value));
+}
+
+fn parse_flac(data: &[u8], meta: &mut ExtendedMetadataDto) {
+    meta.audio_codec = Some("FLAC".to_string());
+    let mut i = 4;
+    while i + 4 <= data.len() {
+        let header = data[i];
+        let block_len = data[i + 1..i + 4]
+            .iter()
+            .fold(0usize, |n, &b| (n << 8) | usize::from(b));
+        let Some(block) = data.get(i + 4..i + 4 + block_len) else {
+            break;
+        };
+        match header & 0x7F {
+            0 if block.len() >= 18 => {
+                let sample_rate = (u32::from(block[10]) << 12)
+                    | (u32::from(block[11]) << 4)
+                    | (u32::from(block[12]) >> 4);
+                let samples =
+                    (u64::from(block[13] & 0x0F) << 32) | u64::from(be32(block, 14).unwrap_or(0));
+                meta.sample_rate = Some(sample_rate);
+                meta.channels = Some(u16::from((block[12] >> 1) & 7) + 1);
+                if sample_rate > 0 && samples > 0 {
+                    meta.duration_secs = Some(samples as f64 / f64::from(sample_rate));
+                }
+            }
+            4 => {
+                let vendor_len = le32(block, 0).unwrap_or(0) as usize;
+                let mut at = 4 + vendor_len;
+                let count = le32(block, at).unwrap_or(0);
+                at += 4;
+                for _ in 0..count {
+                    let Some(len) = le32(block, at).map(|n| n as usize) else {
+                        break;
+                    };
+                    let Some(comment) = block.get(at + 4..at + 4 + len) else {
+                        break;
+                    };
+                    if let Some((key, value)) = String::from_utf8_lossy(comment).split_once('=') {
+                        apply_vorbis_comment(key, value, meta);
+                    }
+                    at += 4 + len;
+                }
+            }
+            _ => {}
+        }
+        if header & 0x80 != 0 {
+            break;
+        }
+        i += 4 + block_len;
+    }
+}
+
+fn parse_wav(data: &[u8], meta: &mut ExtendedMetadataDto) {
+    let mut i = 12;
+    let mut byte_rate = 0u32;
+    while i + 8 <= data.len() {
+        let id = &data[i..i + 4];
+        let size = le32(data, i + 4).unwrap_or(0);
+        let body = &data[i + 8..];
+        if id == b"fmt " {
+            meta.audio_codec = Some(match le16(body, 0) {
+                Some(1) => "PCM".to_string(),
+                Some(3) => "PCM (float)".to_string(),
+                Some(tag) => format!("WAV format 0x{tag:04x}"),
+                None => break,
+            });
+            meta.channels = le16(body, 2);
+            meta.sample_rate = le32(body, 4);
+            byte_rate = le32(body, 8).unwrap_or(0);
+            meta.bitrate_kbps = (byte_rate > 0).then(|| (u64::from(byte_rate) * 8 / 1000) as u32);
+        } else if id == b"data" {
+            if byte_rate > 0 {
+                meta.duration_secs = Some(f64::from(size) / f64::from(byte_rate));
+            }
+            break;
+        }
+        i += 8 + size as usize + (size as usize & 1);
+    }
+}
+
+// --- MP4 / MOV -----------------------------------------------------------
+
+/// Child boxes of `data` as (type, body).
+fn mp4_boxes(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut boxes = Vec::new();
+    let mut i = 0;
+    while i + 8 <= data.len() {
+        let size = be32(data, i).unwrap_or(0) as u64;
+        let kind: [u8; 4] = data[i + 4..i + 8].try_into().unwrap_or_default();
+        let (header, size) = match size {
+            0 => (8, (data.len() - i) as u64),
+            1 => (16, be64(data, i + 8).unwrap_or(0)),
+            n => (8, n),
+        };
+        if size < header as u64 || size > (data.len() - i) as u64 {
+            break;
+        }
+        boxes.push((kind, &data[i + header..i + size as usize]));
+        i += size as usize;
+    }
+    boxes
+}
+
+fn mp4_child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    mp4_boxes(data)
+        .into_iter()
+        .find(|(k, _)| k == kind)
+        .map(|(_, body)| body)
+}
+
+fn fourcc_name(fourcc: &[u8]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "H.264".to_string(),
+        b"hvc1" | b"hev1" => "HEVC".to_string(),
+        b"av01" => "AV1".to_string(),
+        b"vp09" => "VP9".to_string(),
+        b"mp4v" => "MPEG-4".to_string(),
+        b"mp4a" => "AAC".to_string(),
+        b"alac" => "ALAC".to_string(),
+        b"ac-3" => "AC-3".to_string(),
+        b"ec-3" => "E-AC-3".to_string(),
+        b"Opus" => "Opus".to_string(),
+        b"fLaC" => "FLAC".to_string(),
+        [b'a', b'p', _, _] => "ProRes".to_string(),
+        other => String::from_utf8_lossy(other).trim().to_string(),
+    }
+}
+
+fn parse_mp4(file: &mut File, len: u64, meta: &mut ExtendedMetadataDto) {
+    // `moov` may follow a multi-GB `mdat`, so walk top-level headers by seeking.
+    let mut pos = 0u64;
+    let mut header = [0u8; 16];
+    while pos + 8 <= len {
+        if file.seek(SeekFrom::Start(pos)).is_err() || file.read_exact(&mut header[..8]).is_err() {
+            return;
+        }
+        let (header_len, size) = match be32(&header, 0).unwrap_or(0) {
+            0 => (8, len - pos),
+            1 => {
+                if file.read_exact(&mut header[8..16]).is_err() {
+                    return;
+                }
+                (16, be64(&header, 8).unwrap_or(0))
+            }
+            n => (8, u64::from(n)),
+        };
+        if size < header_len || size > len - pos {
+            return;
+        }
+        if &header[4..8] == b"moov" {
+            if size > MAX_PARSE_BYTES {
+                return;
+            }
+            let mut moov = vec![0u8; (size - header_len) as usize];
+            if file.read_exact(&mut moov).is_ok() {
+                parse_moov(&moov, meta);
+            }
+            return;
+        }
+        pos += size;
+    }
+}
+
+fn parse_moov(moov: &[u8], meta: &mut ExtendedMetadataDto) {
+    let mut has_video = false;
+    let mut has_audio = false;
+    for (kind, body) in mp4_boxes(moov) {
+        match &kind {
+            b"mvhd" => {
+                let (timescale, duration) = if body.first() == Some(&1) {
+                    (be32(body, 20), be64(body, 24))
+                } else {
+                    (be32(body, 12), be32(body, 16).map(u64::from))
+                };
+                if let (Some(scale), Some(duration)) = (timescale.filter(|&s| s > 0), duration) {
+                    meta.duration_secs = Some(duration as f64 / f64::from(scale));
+                }
+            }
+            b"trak" => {
+                let Some(mdia) = mp4_child(body, b"mdia") else {
+                    continue;
+                };
+                let handler = mp4_child(mdia, b"hdlr").and_then(|h| h.get(8..12));
+                let entry = mp4_child(mdia, b"minf")
+                    .and_then(|m| mp4_child(m, b"stbl"))
+                    .and_then(|s| mp4_child(s, b"stsd"))
+                    .and_then(|s| s.get(8..));
+                let fourcc = entry.and_then(|e| e.get(4..8));
+                match handler {
+                    Some(b"vide") if !has_video => {
+                        has_video = true;
+                        meta.video_codec = fourcc.map(fourcc_name);
+                        if let Some(tkhd) = mp4_child(body, b"tkhd") {
+                            let at = if tkhd.first() == Some(&1) { 88 } else { 76 };
+                            meta.width = be32(tkhd, at).map(|w| w >> 16).filter(|&w| w > 0);
+                            meta.height = be32(tkhd, at + 4).map(|h| h >> 16).filter(|&h| h > 0);
+                        }
+                    }
+                    Some(b"soun") if !has_audio => {
+                        has_audio = true;
+                        meta.audio_codec = fourcc.map(fourcc_name);
+                        // Audio sample entry: channels at 24, 16.16 rate at 32.
+                        meta.channels = entry.and_then(|e| be16(e, 24));
+                        meta.sample_rate = entry.and_then(|e| be32(e, 32)).map(|r| r >> 16);
+                    }
+                    _ => {}
+                }
+            }
+            b"udta" => {
+                let ilst = mp4_child(body, b"meta")
+                    .and_then(|m| m.get(4..))
+                    .and_then(|m| mp4_child(m, b"ilst"));
+                if let Some(ilst) = ilst {
+                    parse_ilst(ilst, meta);
+                }
+            }
+            _ => {}
+        }
+    }
+    if !has_video && has_audio {
+        meta.kind = "audio".to_string();
+    }
+}
+
+/// iTunes-style tags (`moov/udta/meta/ilst`) from M4A and MP4 files.
+fn parse_ilst(ilst: &[u8], meta: &mut ExtendedMetadataDto) {
+    for (kind, item) in mp4_boxes(ilst) {
+        let Some(value) = mp4_child(item, b"data").and_then(|d| d.get(8..)) else {
+            continue;
+        };
+        let slot = match &kind {
+            b"\xa9nam" => &mut meta.title,
+            b"\xa9ART" => &mut meta.artist,
+            b"\xa9alb" => &mut meta.album,
+            b"\xa9day" => &mut meta.year,
+            b"\xa9gen" => &mut meta.genre,
+            b"trkn" => {
+                let track = be16(value, 2).filter(|&n| n > 0).map(|n| n.to_string());
+                set_if_none(&mut meta.track, track);
+                continue;
+            }
+            _ => continue,
+        };
+        set_if_none(slot, clean(&String::from_utf8_lossy(value)));
+    }
+}
+
+// --- PDF -----------------------------------------------------------------
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+/// The `<< ... >>` dictionary enclosing `pos`.
+fn enclosing_dict(data: &[u8], pos: usize) -> Option<&[u8]> {
+    let mut depth = 0usize;
+    let mut start = None;
+    let mut i = pos;
+    while i >= 2 {
+        i -= 1;
+        match &data[i - 1..=i] {
+            b">>" => {
+                depth += 1;
+                i -= 1;
+            }
+            b"<<" if depth == 0 => {
+                start = Some(i - 1);
+                break;
+            }
+            b"<<" => {
+                depth -= 1;
+                i -= 1;
+            }
+            _ => {}
+        }
+    }
+    let start = start?;
+    let mut depth = 0usize;
+    let mut i = start;
+    while i + 1 < data.len() {
+        match &data[i..i + 2] {
+            b"<<" => {
+                depth += 1;
+                i += 2;
+            }
+            b">>" => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return Some(&data[start..i]);
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Largest `/Count` of any `/Type /Pages` node: the root's, i.e. the total.
+fn pages_count_in(data: &[u8]) -> Option<u32> {
+    let mut best: Option<u32> = None;
+    let mut from = 0;
+    while let Some(pos) = find(data, b"/Type", from) {
+        from = pos + 5;
+        let rest = &data[from..];
+        let skip = rest.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        let rest = &rest[skip..];
+        if !rest.starts_with(b"/Pages") || rest.get(6).is_some_and(|b| b.is_ascii_alphanumeric()) {
+            continue;
+        }
+        let Some(dict) = enclosing_dict(data, pos) else {
+            continue;
+        };
+        let Some(count_at) = find(dict, b"/Count", 0) else {
+            continue;
+        };
+        let digits: String = dict[count_at + 6..]
+            .iter()
+            .skip_while(|b| b.is_ascii_whitespace())
+            .take_while(|b| b.is_ascii_digit())
+            .map(|&b| b as char)
+            .collect();
+        if let Ok(count) = digits.parse::<u32>() {
+            best = Some(best.map_or(count, |b| b.max(count)));
+        }
+    }
+    best
+}
+
+/// Page count from the plain-text page tree, or from Flate-compressed
+/// object streams (PDF 1.5+) when the tree isn't visible.
+fn pdf_page_count(data: &[u8]) -> Option<u32> {
+    if let Some(count) = pages_count_in(data) {
+        return Some(count);
+    }
+    let mut best: Option<u32> = None;
+    let mut from = 0;
+    while let Some(pos) = find(data, b"stream", from) {
+        from = pos + 6;
+        if data[..pos].ends_with(b"end") {
+            continue;
+        }
+        let dict_start = data[..pos]
+            .windows(2)
+            .rposition(|w| w == b"<<")
+            .unwrap_or(pos);
+        if find(&data[dict_start..pos], b"/ObjStm", 0).is_none() {
+            continue;
+        }
+        let mut start = from;
+        if data.get(start) == Some(&b'\r') {
+            start += 1;
+        }
+        if data.get(start) == Some(&b'\n') {
+            start += 1;
+        }
+        let end = find(data, b"endstream", start).unwrap_or(data.len());
+        let mut inflated = Vec::new();
+        let _ = ZlibDecoder::new(&data[start..end])
+            .take(MAX_PARSE_BYTES)
+            .read_to_end(&mut inflated);
+        if let Some(count) = pages_count_in(&inflated) {
+            best = Some(best.map_or(count, |b| b.max(count)));
+        }
+        from = end;
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Big-endian TIFF with Make/Model and an EXIF IFD (exposure, f-number,
+    /// ISO, DateTimeOriginal).
+    fn exif_tiff() -> Vec<u8> {
+        let mut t = b"MM\0\x2a\0\0\0\x08".to_vec();
+        // IFD0 at 8: 3 entries, next-IFD 0. Values follow at 8+2+36+4 = 50.
+        let entry = |t: &mut Vec<u8>, tag: u16, typ: u16, count: u32, value: u32| {
+            t.extend_from_slice(&tag.to_be_bytes());
+            t.extend_from_slice(&typ.to_be_bytes());
+            t.extend_from_slice(&count.to_be_bytes());
+            t.extend_from_slice(&value.to_be_bytes());
+        };
+        t.extend_from_slice(&3u16.to_be_bytes());
+        entry(&mut t, 0x010F, 2, 6, 50); // "Canon\0"
+        entry(&mut t, 0x0110, 2, 4, 0x52350000); // "R5\0\0" inline
+        entry(&mut t, 0x8769, 4, 1, 56);
+        t.extend_from_slice(&0u32.to_be_bytes());
+        t.extend_from_slice(b"Canon\0");
+        // EXIF IFD at 56: 4 entries; values after 56+2+48+4 = 110.
+        t.extend_from_slice(&4u16.to_be_bytes());
+        entry(&mut t, 0x829A, 5, 1, 110);
+        entry(&mut t, 0x829D, 5, 1, 118);
+        entry(&mut t, 0x8827, 3, 1, 400 << 16);
+        entry(&mut t, 0x9003, 2, 20, 126);
+        t.extend_from_slice(&0u32.to_be_bytes());
+        for (n, d) in [(1u32, 250u32), (28, 10)] {
+            t.extend_from_slice(&n.to_be_bytes());
+            t.extend_from_slice(&d.to_be_bytes());
+        }
+        t.extend_from_slice(b"2024:05:01 12:34:56\0");
+        t
+    }
+
+    /// `exif_tiff` in an APP1 segment, then a 4000x3000 SOF0.
+    fn jpeg_sample() -> Vec<u8> {
+        let tiff = exif_tiff();
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        jpeg.extend_from_slice(&((tiff.len() + 8) as u16).to_be_bytes());
+        jpeg.extend_from_slice(b"Exif\0\0");
+        jpeg.extend_from_slice(&tiff);
+        // SOF0: length 17, precision 8, height 3000, width 4000.
+        jpeg.extend_from_slice(&[0xFF, 0xC0, 0, 17, 8, 0x0B, 0xB8, 0x0F, 0xA0]);
+        jpeg.extend_from_slice(&[0; 10]);
+        jpeg.extend_from_slice(&[0xFF, 0xD9]);
+        jpeg
+    }
+
+    /// ID3v2.3 title (UTF-16) and artist, then `audio_bytes` of CBR 128
+    /// kbps MPEG1 audio.
+    fn mp3_sample(audio_bytes: usize) -> Vec<u8> {
+        let mut frames = Vec::new();
+        let mut frame = |id: &[u8], body: &[u8]| {
+            frames.extend_from_slice(id);
+            frames.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            frames.extend_from_slice(&[0, 0]);
+            frames.extend_from_slice(body);
+        };
+        frame(b"TIT2", &[1, 0xFF, 0xFE, b'H', 0, b'i', 0]);
+        frame(b"TPE1", b"\0Band");
+        let mut mp3 = b"ID3\x03\0\0".to_vec();
+        mp3.extend_from_slice(&[0, 0, 0, frames.len() as u8]);
+        mp3.extend_from_slice(&frames);
+        let audio_start = mp3.len();
+        mp3.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        mp3.resize(audio_start + audio_bytes, 0);
+        mp3
+    }
+
+    /// 44.1 kHz stereo STREAMINFO (2 s), then a last Vorbis comment block.
+    fn flac_sample() -> Vec<u8> {
+        let mut info = [0u8; 34];
+        info[10..13].copy_from_slice(&[0x0A, 0xC4, 0x42]);
+        info[13] = 0xF0;
+        info[14..18].copy_from_slice(&88_200u32.to_be_bytes());
+        let comment = b"TITLE=Song";
+        let mut tags = 0u32.to_le_bytes().to_vec();
+        tags.extend_from_slice(&1u32.to_le_bytes());
+        tags.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+        tags.extend_from_slice(comment);
+        let mut flac = b"fLaC\0\0\0\x22".to_vec();
+        flac.extend_from_slice(&info);
+        flac.extend_from_slice(&[0x84, 0, 0, tags.len() as u8]);
+        flac.extend_from_slice(&tags);
+        flac
+    }
+
+    /// 16-bit stereo 44.1 kHz PCM header with a 2 s `data` chunk header.
+    fn wav_sample() -> Vec<u8> {
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&44_100u32.to_le_bytes());
+        wav.extend_from_slice(&176_400u32.to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&352_800u32.to_le_bytes());
+        wav
+    }
+
+    fn mp4_box(kind: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut b = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        b.extend_from_slice(kind);
+        b.extend_from_slice(body);
+        b
+    }
+
+    /// The body of a `moov` with a 90 s `mvhd` and a 1920x1080 H.264 track.
+    fn moov_sample() -> Vec<u8> {
+        let mut mvhd = vec![0u8; 100];
+        mvhd[12..16].copy_from_slice(&600u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&(600u32 * 90).to_be_bytes());
+        let mut tkhd = vec![0u8; 84];
+        tkhd[76..80].copy_from_slice(&(1920u32 << 16).to_be_bytes());
+        tkhd[80..84].copy_from_slice(&(1080u32 << 16).to_be_bytes());
+        let mut hdlr = vec![0u8; 24];
+        hdlr[8..12].copy_from_slice(b"vide");
+        let mut stsd = vec![0u8; 8];
+        stsd.extend_from_slice(&mp4_box(b"avc1", &[0u8; 78]));
+        let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+        let mdia = [mp4_box(b"hdlr", &hdlr), mp4_box(b"minf", &stbl)].concat();
+        let trak = [mp4_box(b"tkhd", &tkhd), mp4_box(b"mdia", &mdia)].concat();
+        [mp4_box(b"mvhd", &mvhd), mp4_box(b"trak", &trak)].concat()
+    }
+
+    fn mp4_sample() -> Vec<u8> {
+        [
+            mp4_box(b"ftyp", b"isom\0\0\0\0"),
+            mp4_box(b"mdat", &[0u8; 64]),
+            mp4_box(b"moov", &moov_sample()),
+        ]
+        .concat()
+    }
+
+    /// Page tree inside a compressed object stream.
+    fn pdf_sample() -> Vec<u8> {
+        let mut z = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        z.write_all(b"2 0 << /Type /Pages /Kids [3 0 R 4 0 R] /Count 12 >>")
+            .unwrap();
+        let compressed = z.finish().unwrap();
+        let mut pdf =
+            b"%PDF-1.7\n5 0 obj\n<< /Type /ObjStm /Filter /FlateDecode >>\nstream\n".to_vec();
+        pdf.extend_from_slice(&compressed);
+        pdf.extend_from_slice(b"\nendstream\nendobj\n%%EOF\n");
+        pdf
+    }
+
+    /// Deterministic xorshift bytes, so a failing case reproduces.
+    fn noise(seed: u64, len: usize) -> Vec<u8> {
+        let mut x = seed | 1;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect()
+    }
+
+    fn fields(meta: &ExtendedMetadataDto) -> serde_json::Map<String, serde_json::Value> {
+        match serde_json::to_value(meta).unwrap() {
+            serde_json::Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn reads_exif_id3_and_pdf_metadata() {
+        let dir = crate::temp_case_dir("extended_metadata");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let photo = dir.join("IMG_0001.jpg");
+        std::fs::write(&photo, jpeg_sample()).unwrap();
+        let meta = extended_metadata(&photo).unwrap();
+        assert_eq!(
+            (meta.kind.as_str(), meta.format.as_deref()),
+            ("image", Some("jpeg"))
+        );
+        assert_eq!((meta.width, meta.height), (Some(4000), Some(3000)));
+        assert_eq!(meta.camera_make.as_deref(), Some("Canon"));
+        assert_eq!(meta.camera_model.as_deref(), Some("R5"));
+        assert_eq!(meta.exposure_time.as_deref(), Some("1/250"));
+        assert_eq!(meta.f_number, Some(2.8));
+        assert_eq!(meta.iso, Some(400));
+        assert_eq!(meta.taken_at.as_deref(), Some("2024:05:01 12:34:56"));
+
+        let song = dir.join("song.mp3");
+        std::fs::write(&song, mp3_sample(16_000)).unwrap();
+        let meta = extended_metadata(&song).unwrap();
+        assert_eq!(meta.kind, "audio");
+        assert_eq!(meta.title.as_deref(), Some("Hi"));
+        assert_eq!(meta.artist.as_deref(), Some("Band"));
+        assert_eq!(
+            (meta.sample_rate, meta.bitrate_kbps),
+            (Some(44_100), Some(128))
+        );
+        assert_eq!(meta.duration_secs, Some(1.0));
+
+        let doc = dir.join("report.pdf");
+        std::fs::write(&doc, pdf_sample()).unwrap();
+        assert_eq!(extended_metadata(&doc).unwrap().page_count, Some(12));
+        assert_eq!(
+            pages_count_in(b"<< /Type /Pages /Kids [<< /Type /Page >>] /Count 3 >>"),
+            Some(3)
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reads_flac_and_wav_stream_info() {
+        let mut meta = ExtendedMetadataDto::default();
+        parse_flac(&flac_sample(), &mut meta);
+        assert_eq!(meta.audio_codec.as_deref(), Some("FLAC"));
+        assert_eq!((meta.sample_rate, meta.channels), (Some(44_100), Some(2)));
+        assert_eq!(meta.duration_secs, Some(2.0));
+        assert_eq!(meta.title.as_deref(), Some("Song"));
+
+        let mut meta = ExtendedMetadataDto::default();
+        parse_wav(&wav_sample(), &mut meta);
+        assert_eq!(meta.audio_codec.as_deref(), Some("PCM"));
+        assert_eq!((meta.sample_rate, meta.channels), (Some(44_100), Some(2)));
+        assert_eq!(meta.bitrate_kbps, Some(1411));
+        assert_eq!(meta.duration_secs, Some(2.0));
+    }
+
+    type SliceParser = fn(&[u8], &mut ExtendedMetadataDto);
+
+    /// Every in-memory parser with a well-formed input for it.
+    fn slice_parsers() -> Vec<(&'static str, SliceParser, Vec<u8>)> {
+        vec![
+            ("exif", apply_exif, exif_tiff()),
+            ("jpeg", parse_jpeg, jpeg_sample()),
+            (
+                "id3v2",
+                |d, m| {
+                    parse_id3v2(d, m);
+                },
+                mp3_sample(0),
+            ),
+            ("id3v1", parse_id3v1, {
+                let mut tag = b"TAG".to_vec();
+                tag.resize(128, b'x');
+                tag[125..].copy_from_slice(&[0, 7, 0]);
+                tag
+            }),
+            ("flac", parse_flac, flac_sample()),
+            ("wav", parse_wav, wav_sample()),
+            ("moov", parse_moov, moov_sample()),
+            ("pdf", |d, m| m.page_count = pdf_page_count(d), pdf_sample()),
+        ]
+    }
+
+    #[test]
+    fn truncated_input_yields_a_subset_of_the_full_fields() {
+        for (name, parse, sample) in slice_parsers() {
+            let mut full = ExtendedMetadataDto::default();
+            parse(&sample, &mut full);
+            let full = fields(&full);
+            assert!(full.len() > 1, "{name}: sample parses");
+            for cut in 0..sample.len() {
+                let mut meta = ExtendedMetadataDto::default();
+                parse(&sample[..cut], &mut meta);
+                // A cut page tree can still read as a smaller `/Count`.
+                if name == "pdf" {
+                    continue;
+                }
+                for (key, value) in fields(&meta) {
+                    if key != "path" && key != "kind" {
+                        assert_eq!(full.get(&key), Some(&value), "{name} cut at {cut}: {key}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn garbage_input_is_skipped_not_fatal() {
+        for (_, parse, sample) in slice_parsers() {
+            for seed in 0..200u64 {
+                let mut meta = ExtendedMetadataDto::default();
+                parse(&noise(seed, 512), &mut meta);
+                // Keep the magic and the first structure, scramble the rest.
+                let keep = (seed as usize * 7) % sample.len();
+                let mut mangled = sample[..keep].to_vec();
+                mangled.extend(noise(seed, sample.len() - keep));
+                parse(&mangled, &mut meta);
+                // Single-byte corruption anywhere in an otherwise valid file.
+                let mut flipped = sample.clone();
+                let at = (seed as usize * 31) % sample.len();
+                flipped[at] ^= noise(seed, 1)[0] | 1;
+                parse(&flipped, &mut meta);
+            }
+            let mut meta = ExtendedMetadataDto::default();
+            parse(&[], &mut meta);
+            parse(&[0xFF; 64], &mut meta);
+            parse(&[0; 64], &mut meta);
+        }
+    }
+
+    #[test]
+    fn truncated_and_garbage_files_still_open() {
+        let dir = crate::temp_case_dir("extended_metadata_garbage");
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut huge_box = mp4_box(b"ftyp", b"isom\0\0\0\0");
+        huge_box.extend_from_slice(&1u32.to_be_bytes());
+        huge_box.extend_from_slice(b"mdat");
+        huge_box.extend_from_slice(&u64::MAX.to_be_bytes());
+        let files = [
+            ("photo.jpg", jpeg_sample()),
+            ("photo.tif", exif_tiff()),
+            ("song.mp3", mp3_sample(4_000)),
+            ("song.flac", flac_sample()),
+            ("song.wav", wav_sample()),
+            ("clip.mp4", mp4_sample()),
+            ("huge_box.mp4", huge_box),
+            ("report.pdf", pdf_sample()),
+            ("image.png", {
+                let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+                png.extend_from_slice(&640u32.to_be_bytes());
+                png.extend_from_slice(&480u32.to_be_bytes());
+                png
+            }),
+            ("anim.gif", b"GIF89a\x80\x02\xe0\x01".to_vec()),
+        ];
+        for (name, sample) in files {
+            let path = dir.join(name);
+            let step = (sample.len() / 64).max(1);
+            for cut in (0..sample.len()).step_by(step) {
+                std::fs::write(&path, &sample[..cut]).unwrap();
+                assert!(extended_metadata(&path).is_ok(), "{name} cut at {cut}");
+            }
+            for seed in 0..16u64 {
+                let keep = sample.len().min(12);
+                let mut mangled = sample[..keep].to_vec();
+                mangled.extend(noise(seed, sample.len()));
+                std::fs::write(&path, &mangled).unwrap();
+                assert!(extended_metadata(&path).is_ok(), "{name} seed {seed}");
+            }
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reads_mp4_tracks_and_caches_by_mtime() {
+        let dir = crate::temp_case_dir("extended_metadata_mp4");
+        std::fs::create_dir_all(&dir).unwrap();
+        let clip = dir.join("clip.mp4");
+        std::fs::write(&clip, mp4_sample()).unwrap();
+        let meta = extended_metadata(&clip).unwrap();
+        assert_eq!(meta.kind, "video");
+        assert_eq!(meta.video_codec.as_deref(), Some("H.264"));
+        assert_eq!((meta.width, meta.height), (Some(1920), Some(1080)));
+        assert_eq!(meta.duration_secs, Some(90.0));
+
+        let mut cache = MetadataCache::default();
+        let key = MetadataKey::for_path(&clip).unwrap();
+        assert!(cache.get(&key).is_none());
+        cache.insert(key.clone(), meta.clone());
+        assert_eq!(cache.get(&key), Some(meta));
+        std::fs::write(&clip, b"edited").unwrap();
+        let edited = MetadataKey {
+            len: 6,
+            ..MetadataKey::for_path(&clip).unwrap()
+        };
+        assert!(cache.get(&edited).is_none(), "size change misses");
+        assert!(MetadataKey::for_path(&dir).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
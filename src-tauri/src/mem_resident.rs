@@ -0,0 +1,261 @@
+//! Keeps the in-memory index (`MemIndex`, built by the Windows full scans)
+//! resident once the DB is written, so name and extension queries never fall
+//! back to SQLite. Watcher batches are applied to it as deltas; writes that
+//! rewrite rows in place (moves, app renames, subtree rescans) resync the
+//! touched subtrees from the DB; bulk changes (ignore rules, indexed roots)
+//! reload it from the DB in the background. SQLite stays the durable copy,
+//! and everything here is a no-op while no index is resident. Every change
+//! to the resident index clears the search result cache.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use parking_lot::{Mutex, MutexGuard};
+use rusqlite::{params, Connection};
+
+use crate::mem_search::{CompactEntry, MemIndex};
+use crate::{db_connection, subtree_range_bounds, AppResult, AppState, IndexRow};
+
+/// One change applied to the resident index.
+#[derive(Clone)]
+enum Delta {
+    Upsert(Vec<CompactEntry>),
+    Remove(Vec<String>),
+}
+
+#[derive(Clone, Copy)]
+enum Source {
+    /// The index's own live entries: folds the overlay into the base.
+    Live,
+    /// Every row in the DB, after a change the watchers don't report.
+    Db,
+}
+
+#[derive(Default)]
+pub(crate) struct Resident {
+    /// Deltas applied while a rebuild runs, replayed onto the rebuilt index
+    /// before it replaces the current one. `None` while no rebuild runs.
+    /// Held across every apply so a rebuild can't miss one.
+    journal: Mutex<Option<Vec<Delta>>>,
+    /// A DB reload was asked for while a rebuild ran from an older snapshot.
+    reload_requested: AtomicBool,
+}
+
+impl fmt::Debug for Resident {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Resident")
+            .field("reload_requested", &self.reload_requested)
+            .finish()
+    }
+}
+
+/// Apply a watcher batch exactly as it was written to the DB: upserts, then
+/// deletes.
+pub(crate) fn apply_batch(state: &AppState, upserted: &[IndexRow], deleted: &[String]) {
+    if state.mem_index.read().is_none() || (upserted.is_empty() && deleted.is_empty()) {
+        return;
+    }
+    let mut deltas = Vec::with_capacity(2);
+    if !upserted.is_empty() {
+        deltas.push(Delta::Upsert(upserted.iter().map(compact_entry).collect()));
+    }
+    if !deleted.is_empty() {
+        deltas.push(Delta::Remove(deleted.to_vec()));
+    }
+    apply(state, deltas);
+}
+
+/// Reload `roots` and everything under them from the DB, after a write that
+/// doesn't hand over its rows.
+pub(crate) fn resync(state: &AppState, roots: &[String]) {
+    if state.mem_index.read().is_none() || roots.is_empty() {
+        return;
+    }
+    let rows = db_connection(&state.db_path).and_then(|conn| {
+        let mut rows = Vec::new();
+        for root in roots {
+            rows.extend(load_entries(&conn, Some(root.as_str()))?);
+        }
+        Ok(rows)
+    });
+    match rows {
+        Ok(rows) => apply(state, vec![Delta::Remove(roots.to_vec()), Delta::Upsert(rows)]),
+        Err(e) => {
+            eprintln!("[mem_resident] resync failed, reloading from DB: {e}");
+            reload(state);
+        }
+    }
+}
+
+/// Rebuild the resident index from the DB in the background, after a bulk
+/// change. The current index keeps serving until the rebuilt one is ready.
+pub(crate) fn reload(state: &AppState) {
+    if state.mem_index.read().is_none() || state.indexing_active.load(AtomicOrdering::Acquire) {
+        return;
+    }
+    let mut journal = state.mem_resident.journal.lock();
+    if journal.is_some() {
+        state
+            .mem_resident
+            .reload_requested
+            .store(true, AtomicOrdering::Release);
+        return;
+    }
+    begin_rebuild(state, &mut journal, Source::Db);
+}
+
+fn apply(state: &AppState, deltas: Vec<Delta>) {
+    let mut journal = state.mem_resident.journal.lock();
+    let Some(index) = state.mem_index.read().clone() else {
+        return;
+    };
+    if let Some(pending) = journal.as_mut() {
+        for delta in deltas {
+            apply_to(&index, delta.clone(), false);
+            pending.push(delta);
+        }
+        state.search_result_cache.lock().clear();
+        return;
+    }
+    for delta in deltas {
+        apply_to(&index, delta, false);
+    }
+    // `mem_*` results are cached too; drop them once the index moved on.
+    state.search_result_cache.lock().clear();
+    if index.needs_compaction() {
+        begin_rebuild(state, &mut journal, Source::Live);
+    }
+}
+
+/// `replay` removes subtrees even where the snapshot already lacks their
+/// root (the delta may predate it).
+fn apply_to(index: &MemIndex, delta: Delta, replay: bool) {
+    match delta {
+        Delta::Upsert(entries) => index.upsert(entries),
+        Delta::Remove(paths) if replay => index.remove_subtrees(&paths),
+        Delta::Remove(paths) => index.remove(&paths),
+    }
+}
+
+/// Start journaling and build a replacement index on a worker thread.
+fn begin_rebuild(
+    state: &AppState,
+    journal: &mut MutexGuard<'_, Option<Vec<Delta>>>,
+    source: Source,
+) {
+    **journal = Some(Vec::new());
+    let state = state.clone();
+    std::thread::spawn(move || rebuild(&state, source));
+}
+
+fn rebuild(state: &AppState, mut source: Source) {
+    loop {
+        let started = Instant::now();
+        let Some(current) = state.mem_index.read().clone() else {
+            *state.mem_resident.journal.lock() = None;
+            return;
+        };
+        let entries = match source {
+            Source::Live => Ok(current.live_entries()),
+            Source::Db => db_connection(&state.db_path).and_then(|conn| load_entries(&conn, None)),
+        };
+        let entries = match entries {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("[mem_resident] rebuild failed: {e}");
+                *state.mem_resident.journal.lock() = None;
+                return;
+            }
+        };
+        let rebuilt = Arc::new(MemIndex::build(entries));
+
+        let mut journal = state.mem_resident.journal.lock();
+        let replayed = journal.as_ref().map_or(0, Vec::len);
+        for delta in journal.take().unwrap_or_default() {
+            apply_to(&rebuilt, delta, true);
+        }
+        {
+            // A full index (or reset) replaced the index meanwhile: it wins.
+            let mut slot = state.mem_index.write();
+            if !slot.as_ref().is_some_and(|live| Arc::ptr_eq(live, &current)) {
+                return;
+            }
+            *slot = Some(rebuilt);
+        }
+        state.search_result_cache.lock().clear();
+        eprintln!(
+            "[mem_resident] rebuilt from {}: replayed={replayed} in {}ms",
+            match source {
+                Source::Live => "overlay",
+                Source::Db => "db",
+            },
+            started.elapsed().as_millis()
+        );
+        if !state
+            .mem_resident
+            .reload_requested
+            .swap(false, AtomicOrdering::AcqRel)
+        {
+            return;
+        }
+        *journal = Some(Vec::new());
+        source = Source::Db;
+    }
+}
+
+fn compact_entry(row: &IndexRow) -> CompactEntry {
+    CompactEntry {
+        name: row.name.clone(),
+        dir: row.dir.clone(),
+        is_dir: row.is_dir != 0,
+        ext: row.ext.clone(),
+        mtime: row.mtime,
+        mtime_nsec: row.mtime_nsec.and_then(|n| u32::try_from(n).ok()),
+        size: row.size,
+        cloud_only: row.cloud_only,
+    }
+}
+
+/// Rows at `root` or under it, or every row when `root` is `None`.
+fn load_entries(conn: &Connection, root: Option<&str>) -> AppResult<Vec<CompactEntry>> {
+    const COLUMNS: &str =
+        "SELECT name, dir, is_dir, ext, mtime, size, cloud_only, mtime_nsec FROM entries";
+    let map = |row: &rusqlite::Row<'_>| -> rusqlite::Result<CompactEntry> {
+        Ok(CompactEntry {
+            name: row.get(0)?,
+            dir: row.get(1)?,
+            is_dir: row.get::<_, i64>(2)? != 0,
+            ext: row.get(3)?,
+            mtime: row.get(4)?,
+            size: row.get(5)?,
+            cloud_only: row.get(6)?,
+            mtime_nsec: row.get(7)?,
+        })
+    };
+    let rows = match root {
+        Some(root) => {
+            let (lo, hi) = subtree_range_bounds(root);
+            let mut stmt = conn
+                .prepare(&format!(
+                    "{COLUMNS} WHERE path = ?1 OR (path >= ?2 AND path < ?3)"
+                ))
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![root, lo, hi], map)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>();
+            rows
+        }
+        None => {
+            let mut stmt = conn.prepare(COLUMNS).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], map)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>();
+            rows
+        }
+    };
+    rows.map_err(|e| e.to_string())
+}
@@ -1,11 +1,15 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::time::Instant;
 
-#[cfg(target_os = "windows")]
-use rayon::prelude::*;
+use memchr::memmem;
+use jwalk::rayon::prelude::*;
+use parking_lot::{RwLock, RwLockReadGuard};
 
+use crate::glob_class::CharClass;
+use crate::name_norm;
 use crate::query::SearchMode;
+use crate::search_cancel::{self, SearchCancelToken};
 use crate::{perf_log, EntryDto};
 
 /// Compact entry without redundant `path` field (path = dir + sep + name).
@@ -17,7 +21,10 @@ pub struct CompactEntry {
     pub is_dir: bool,
     pub ext: Option<String>,
     pub mtime: Option<i64>,
+    /// Sub-second part of `mtime`, where the source has one.
+    pub mtime_nsec: Option<u32>,
     pub size: Option<i64>,
+    pub cloud_only: bool,
 }
 
 impl CompactEntry {
@@ -34,22 +41,70 @@ impl CompactEntry {
             ext: self.ext.clone(),
             mtime: self.mtime,
             size: self.size,
+            spoken_label: None,
+            mtime_display: None,
+            cloud_only: self.cloud_only.then_some(true),
+            linked_from: None,
         }
     }
 }
 
+/// Ends every name in `MemIndex::names_blob`. File names can't contain it,
+/// so a substring match never spans two entries.
+const NAME_SEP: char = '\0';
+
+/// Overlay size that never triggers compaction, however small the base.
+const COMPACT_MIN_CHANGES: usize = 50_000;
+
 /// Pre-indexed in-memory search structure.
-/// Built once after MFT scan; all queries use pre-computed data.
+/// Built once after the MFT/WalkDir scan and kept resident: watcher changes
+/// land in `overlay`, and `mem_resident` rebuilds the index from its live
+/// entries once the overlay grows past `needs_compaction`.
 pub struct MemIndex {
     entries: Vec<CompactEntry>,
-    /// Pre-lowercased name for each entry (same index)
-    names_lower: Vec<String>,
-    /// Entry indices sorted by names_lower for binary search prefix lookups
+    /// Folded (`name_norm::fold`) names of all entries back to back, each
+    /// followed by `NAME_SEP`: one contiguous buffer that a memmem finder
+    /// scans for contains matches.
+    names_blob: String,
+    /// Byte offset of each entry's name in `names_blob`, plus the blob's end.
+    name_starts: Vec<u32>,
+    /// Entry indices sorted by folded name for binary search prefix lookups
     sorted_idx: Vec<u32>,
     /// ext → sorted [entry_idx, ...] (sorted by name_lower)
     ext_map: HashMap<String, Vec<u32>>,
-    /// dir_lower → [entry_idx, ...]
-    dir_map: HashMap<String, Vec<u32>>,
+    /// folded dir → [entry_idx, ...], ordered so a subtree is one key range
+    dir_map: BTreeMap<String, Vec<u32>>,
+    /// Changes since the build. The built structures above never change.
+    overlay: RwLock<Overlay>,
+}
+
+/// Entries removed from or added to a built `MemIndex`. Queries see one id
+/// space: ids below the built length are built entries (unless `removed`),
+/// the rest index `added`.
+#[derive(Default)]
+struct Overlay {
+    removed: HashSet<u32>,
+    added: Vec<CompactEntry>,
+    /// Folded name of each `added` entry (same index).
+    added_lower: Vec<String>,
+    /// Full path → position in `added`.
+    added_pos: HashMap<String, usize>,
+}
+
+impl Overlay {
+    fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
+
+    fn remove_added(&mut self, path: &str) -> Option<CompactEntry> {
+        let pos = self.added_pos.remove(path)?;
+        self.added_lower.swap_remove(pos);
+        let entry = self.added.swap_remove(pos);
+        if let Some(moved) = self.added.get(pos) {
+            self.added_pos.insert(moved.path(), pos);
+        }
+        Some(entry)
+    }
 }
 
 impl fmt::Debug for MemIndex {
@@ -61,41 +116,48 @@ impl fmt::Debug for MemIndex {
 }
 
 impl MemIndex {
-    #[cfg(target_os = "windows")]
     pub fn build(entries: Vec<CompactEntry>) -> Self {
         let t0 = Instant::now();
         let n = entries.len();
 
-        // Phase 1: names_lower in parallel
-        let names_lower: Vec<String> = entries
+        // Phase 1: folded names in parallel, then packed into one buffer
+        let folded: Vec<String> = entries
             .par_iter()
-            .map(|e| e.name.to_lowercase())
+            .map(|e| name_norm::fold(&e.name))
             .collect();
+        let mut names_blob = String::with_capacity(folded.iter().map(|f| f.len() + 1).sum());
+        let mut name_starts: Vec<u32> = Vec::with_capacity(n + 1);
+        for name in &folded {
+            name_starts.push(names_blob.len() as u32);
+            names_blob.push_str(name);
+            names_blob.push(NAME_SEP);
+        }
+        name_starts.push(names_blob.len() as u32);
+        drop(folded);
+        let name = |idx: u32| name_at(&names_blob, &name_starts, idx);
         let t1 = t0.elapsed().as_millis();
 
         // Phase 2: sorted_idx -- parallel sort
         let mut sorted_idx: Vec<u32> = (0..n as u32).collect();
-        sorted_idx.par_sort_unstable_by(|&a, &b| {
-            names_lower[a as usize].cmp(&names_lower[b as usize])
-        });
+        sorted_idx.par_sort_unstable_by(|&a, &b| name(a).cmp(name(b)));
         let t2 = t0.elapsed().as_millis();
 
-        // Phase 3: ext_map + dir_map (sequential -- HashMap building is inherently serial)
+        // Phase 3: ext_map + dir_map (sequential -- map building is inherently serial)
         let mut ext_map: HashMap<String, Vec<u32>> = HashMap::new();
-        let mut dir_map: HashMap<String, Vec<u32>> = HashMap::new();
+        let mut dir_map: BTreeMap<String, Vec<u32>> = BTreeMap::new();
         for (i, e) in entries.iter().enumerate() {
             let idx = i as u32;
             if let Some(ref ext) = e.ext {
                 ext_map.entry(ext.clone()).or_default().push(idx);
             }
-            let dir_lower = e.dir.to_lowercase();
+            let dir_lower = name_norm::fold(&e.dir);
             dir_map.entry(dir_lower).or_default().push(idx);
         }
         let t3 = t0.elapsed().as_millis();
 
         // Phase 4: Sort ext_map values in parallel
         ext_map.par_iter_mut().for_each(|(_, idxs)| {
-            idxs.sort_unstable_by(|&a, &b| names_lower[a as usize].cmp(&names_lower[b as usize]));
+            idxs.sort_unstable_by(|&a, &b| name(a).cmp(name(b)));
         });
 
         eprintln!(
@@ -109,16 +171,138 @@ impl MemIndex {
 
         MemIndex {
             entries,
-            names_lower,
+            names_blob,
+            name_starts,
             sorted_idx,
             ext_map,
             dir_map,
+            overlay: RwLock::new(Overlay::default()),
+        }
+    }
+
+    /// Add or replace entries, matched by full path.
+    pub fn upsert(&self, entries: Vec<CompactEntry>) {
+        let mut overlay = self.overlay.write();
+        for entry in entries {
+            let path = entry.path();
+            self.remove_one(&mut overlay, &path);
+            let pos = overlay.added.len();
+            overlay.added_pos.insert(path, pos);
+            overlay.added_lower.push(name_norm::fold(&entry.name));
+            overlay.added.push(entry);
         }
     }
 
-    #[allow(dead_code)]
+    /// Remove entries by full path, directories with everything under them
+    /// (like `delete_paths` does for rows).
+    pub fn remove(&self, paths: &[String]) {
+        self.remove_paths(paths, false);
+    }
+
+    /// Like `remove`, but also clears everything under paths that aren't in
+    /// the index (already removed along with their subtree, say).
+    pub fn remove_subtrees(&self, paths: &[String]) {
+        self.remove_paths(paths, true);
+    }
+
+    fn remove_paths(&self, paths: &[String], always_subtree: bool) {
+        let sep = std::path::MAIN_SEPARATOR;
+        let mut overlay = self.overlay.write();
+        for path in paths {
+            let path = path.trim_end_matches(['/', '\\']);
+            if path.is_empty() {
+                continue;
+            }
+            match self.remove_one(&mut overlay, path) {
+                Some(true) => {}
+                None if always_subtree => {}
+                _ => continue,
+            }
+            // A removed directory takes its subtree along.
+            let under = format!("{path}{sep}");
+            let folded = name_norm::fold(path);
+            let folded_under = format!("{folded}{sep}");
+            let subtree = self
+                .dir_map
+                .get(&folded)
+                .into_iter()
+                .chain(
+                    self.dir_map
+                        .range(folded_under.clone()..)
+                        .take_while(|(dir_lower, _)| dir_lower.starts_with(&folded_under))
+                        .map(|(_, idxs)| idxs),
+                );
+            for idxs in subtree {
+                for &idx in idxs {
+                    let dir = &self.entries[idx as usize].dir;
+                    if dir == path || dir.starts_with(&under) {
+                        overlay.removed.insert(idx);
+                    }
+                }
+            }
+            let added_under: Vec<String> = overlay
+                .added
+                .iter()
+                .filter(|e| e.dir == path || e.dir.starts_with(&under))
+                .map(CompactEntry::path)
+                .collect();
+            for path in added_under {
+                overlay.remove_added(&path);
+            }
+        }
+    }
+
+    /// Drop the entry at exactly `path`; returns whether it was a directory
+    /// (so its subtree needs dropping too), `None` if there was none.
+    fn remove_one(&self, overlay: &mut Overlay, path: &str) -> Option<bool> {
+        if let Some(entry) = overlay.remove_added(path) {
+            return Some(entry.is_dir);
+        }
+        let (dir, name) = path.rsplit_once(std::path::MAIN_SEPARATOR)?;
+        let idxs = self.dir_map.get(&name_norm::fold(dir))?;
+        for &idx in idxs {
+            let entry = &self.entries[idx as usize];
+            if entry.name == name && entry.dir == dir && overlay.removed.insert(idx) {
+                return Some(entry.is_dir);
+            }
+        }
+        None
+    }
+
+    /// The overlay has grown enough that queries pay noticeably for it.
+    pub fn needs_compaction(&self) -> bool {
+        let overlay = self.overlay.read();
+        overlay.removed.len() + overlay.added.len()
+            > COMPACT_MIN_CHANGES.max(self.entries.len() / 8)
+    }
+
+    /// Every entry currently in the index, for rebuilding it.
+    pub fn live_entries(&self) -> Vec<CompactEntry> {
+        let overlay = self.overlay.read();
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !overlay.removed.contains(&(*idx as u32)))
+            .map(|(_, entry)| entry.clone())
+            .chain(overlay.added.iter().cloned())
+            .collect()
+    }
+
+    /// Folded name of entry `idx`.
+    fn name_lower(&self, idx: u32) -> &str {
+        name_at(&self.names_blob, &self.name_starts, idx)
+    }
+
+    /// The entry whose name (or trailing separator) holds byte `pos` of
+    /// `names_blob`.
+    fn entry_at(&self, pos: usize) -> u32 {
+        (self.name_starts.partition_point(|&start| start as usize <= pos) - 1) as u32
+    }
+
+    /// Live entries: built ones not removed since, plus added ones.
     pub fn len(&self) -> usize {
-        self.entries.len()
+        let overlay = self.overlay.read();
+        self.entries.len() - overlay.removed.len() + overlay.added.len()
     }
 
     #[cfg(target_os = "windows")]
@@ -127,7 +311,55 @@ impl MemIndex {
     }
 }
 
-/// Search the in-memory index.
+/// A `MemIndex` with its overlay read-locked for one query.
+struct View<'a> {
+    index: &'a MemIndex,
+    overlay: RwLockReadGuard<'a, Overlay>,
+}
+
+impl View<'_> {
+    fn entry(&self, id: u32) -> &CompactEntry {
+        match (id as usize).checked_sub(self.index.entries.len()) {
+            Some(added) => &self.overlay.added[added],
+            None => &self.index.entries[id as usize],
+        }
+    }
+
+    fn name_lower(&self, id: u32) -> &str {
+        match (id as usize).checked_sub(self.index.entries.len()) {
+            Some(added) => &self.overlay.added_lower[added],
+            None => self.index.name_lower(id),
+        }
+    }
+
+    /// Built entry `idx` hasn't been removed since the build.
+    fn live(&self, idx: u32) -> bool {
+        self.overlay.removed.is_empty() || !self.overlay.removed.contains(&idx)
+    }
+
+    /// Ids of the entries added since the build.
+    fn added_ids(&self) -> std::ops::Range<u32> {
+        let base = self.index.entries.len() as u32;
+        base..base + self.overlay.added.len() as u32
+    }
+
+    fn all_ids(&self) -> Vec<u32> {
+        (0..self.index.entries.len() as u32)
+            .filter(|&idx| self.live(idx))
+            .chain(self.added_ids())
+            .collect()
+    }
+}
+
+fn name_at<'a>(names_blob: &'a str, name_starts: &[u32], idx: u32) -> &'a str {
+    let start = name_starts[idx as usize] as usize;
+    let end = name_starts[idx as usize + 1] as usize - NAME_SEP.len_utf8();
+    &names_blob[start..end]
+}
+
+/// Search the in-memory index. The linear scans stop early once `cancel`
+/// fires; the caller discards whatever was collected.
+#[allow(clippy::too_many_arguments)]
 pub fn search_mem_index(
     mem_index: &MemIndex,
     query: &str,
@@ -136,8 +368,13 @@ pub fn search_mem_index(
     offset: u32,
     sort_by: &str,
     sort_dir: &str,
+    cancel: Option<&SearchCancelToken>,
 ) -> Vec<EntryDto> {
     let t0 = Instant::now();
+    let view = View {
+        index: mem_index,
+        overlay: mem_index.overlay.read(),
+    };
     let total_entries = mem_index.entries.len();
 
     let mode_label = match mode {
@@ -146,52 +383,54 @@ pub fn search_mem_index(
         SearchMode::GlobName { .. } => "glob",
         SearchMode::ExtSearch { .. } => "ext",
         SearchMode::PathSearch { .. } => "path",
+        SearchMode::PathContains { .. } => "path_contains",
     };
 
     let t_filter = Instant::now();
 
-    // For Empty mode, use sorted_idx directly (no filter/sort needed)
-    if matches!(mode, SearchMode::Empty) {
+    // For Empty mode, use sorted_idx directly (no filter/sort needed) while
+    // nothing changed since the build
+    if matches!(mode, SearchMode::Empty) && view.overlay.is_empty() {
         let start = offset as usize;
-        let end = (start + effective_limit as usize).min(mem_index.sorted_idx.len());
-        let page: Vec<EntryDto> = if start < mem_index.sorted_idx.len() {
+        let end = (start + effective_limit as usize).min(view.index.sorted_idx.len());
+        let page: Vec<EntryDto> = if start < view.index.sorted_idx.len() {
             if sort_by == "mtime" || sort_by == "size" {
                 let desc = sort_dir == "desc";
                 let is_size = sort_by == "size";
                 let mut all_idx: Vec<u32> = (0..total_entries as u32).collect();
                 all_idx.sort_unstable_by(|&a, &b| {
                     let oa = if is_size {
-                        mem_index.entries[a as usize].size
+                        view.entry(a).size
                     } else {
-                        mem_index.entries[a as usize].mtime
+                        view.entry(a).mtime
                     };
                     let ob = if is_size {
-                        mem_index.entries[b as usize].size
+                        view.entry(b).size
                     } else {
-                        mem_index.entries[b as usize].mtime
+                        view.entry(b).mtime
                     };
                     cmp_opt_none_last(oa, ob, desc)
                 });
                 let end2 = (start + effective_limit as usize).min(all_idx.len());
                 all_idx[start..end2]
                     .iter()
-                    .map(|&i| mem_index.entries[i as usize].to_entry_dto())
+                    .map(|&i| view.entry(i).to_entry_dto())
                     .collect()
             } else {
                 // Default: name sorted -- use sorted_idx directly
                 let iter = if sort_dir == "desc" {
                     // Reverse iteration
-                    let rstart = mem_index.sorted_idx.len().saturating_sub(start + effective_limit as usize);
-                    let rend = mem_index.sorted_idx.len().saturating_sub(start);
-                    mem_index.sorted_idx[rstart..rend]
+                    let rstart = view.index.sorted_idx.len().saturating_sub(start + effective_limit as usize);
+                    let rend = view.index.sorted_idx.len().saturating_sub(start);
+                    view.index.sorted_idx[rstart..rend]
                         .iter()
                         .rev()
-                        .map(|&idx| mem_index.entries[idx as usize].to_entry_dto())
+                        .map(|&idx| view.entry(idx).to_entry_dto())
                         .collect()
                 } else {
-                    mem_index.sorted_idx[start..end]
+                    view.index.sorted_idx[start..end]
                         .iter()
-                        .map(|&idx| mem_index.entries[idx as usize].to_entry_dto())
+                        .map(|&idx| view.entry(idx).to_entry_dto())
                         .collect()
                 };
                 iter
@@ -208,10 +447,11 @@ pub fn search_mem_index(
         return page;
     }
 
-    // ExtSearch: values are pre-sorted by name_lower -- skip sort, paginate directly
-    if let SearchMode::ExtSearch { ext, .. } = &mode {
+    // ExtSearch: values are pre-sorted by name_lower -- skip sort, paginate
+    // directly while nothing changed since the build
+    if let (SearchMode::ExtSearch { ext, .. }, true) = (mode, view.overlay.is_empty()) {
         let ext_lower = ext.to_lowercase();
-        let page = match mem_index.ext_map.get(&ext_lower) {
+        let page = match view.index.ext_map.get(&ext_lower) {
             Some(idxs) => {
                 let start = offset as usize;
                 let lim = effective_limit as usize;
@@ -221,9 +461,9 @@ pub fn search_mem_index(
                     let is_size = sort_by == "size";
                     let opt_val = |idx: u32| -> Option<i64> {
                         if is_size {
-                            mem_index.entries[idx as usize].size
+                            view.entry(idx).size
                         } else {
-                            mem_index.entries[idx as usize].mtime
+                            view.entry(idx).mtime
                         }
                     };
                     let need = start.saturating_add(lim).min(sorted.len());
@@ -243,7 +483,7 @@ pub fn search_mem_index(
                         });
                         sorted[start..need]
                             .iter()
-                            .map(|&i| mem_index.entries[i as usize].to_entry_dto())
+                            .map(|&i| view.entry(i).to_entry_dto())
                             .collect()
                     }
                 } else if sort_dir == "desc" {
@@ -251,13 +491,13 @@ pub fn search_mem_index(
                     let rstart = idxs.len().saturating_sub(start + lim);
                     let rend = idxs.len().saturating_sub(start);
                     idxs[rstart..rend].iter().rev()
-                        .map(|&i| mem_index.entries[i as usize].to_entry_dto()).collect()
+                        .map(|&i| view.entry(i).to_entry_dto()).collect()
                 } else {
                     // Pre-sorted by name asc -- direct slice
                     let end = (start + lim).min(idxs.len());
                     if start < idxs.len() {
                         idxs[start..end].iter()
-                            .map(|&i| mem_index.entries[i as usize].to_entry_dto()).collect()
+                            .map(|&i| view.entry(i).to_entry_dto()).collect()
                     } else {
                         Vec::new()
                     }
@@ -275,33 +515,49 @@ pub fn search_mem_index(
     }
 
     let mut indices: Vec<u32> = match mode {
-        SearchMode::Empty => unreachable!(),
-        SearchMode::ExtSearch { .. } => unreachable!(),
+        SearchMode::Empty => view.all_ids(),
+        SearchMode::ExtSearch { ext, .. } => search_by_ext(&view, ext),
         SearchMode::NameSearch { .. } => {
-            let q_lower = query.trim().to_lowercase();
-            search_by_name_indexed(mem_index, &q_lower, effective_limit)
+            let q_lower = name_norm::fold(query.trim());
+            search_by_name_indexed(&view, &q_lower, offset + effective_limit, cancel)
         }
-        SearchMode::GlobName { name_like } => {
-            search_by_glob_indexed(mem_index, name_like, effective_limit)
+        SearchMode::GlobName {
+            name_like,
+            name_glob,
+        } => {
+            let pattern = LikePattern::for_name(name_like, name_glob.as_deref());
+            search_by_glob_indexed(&view, pattern, effective_limit, cancel)
         }
         SearchMode::PathSearch {
             name_like,
+            name_glob,
             dir_hint,
             ..
         } => {
-            search_by_path_indexed(mem_index, dir_hint, name_like, effective_limit)
+            let pattern = (name_like != "%")
+                .then(|| LikePattern::for_name(name_like, name_glob.as_deref()));
+            search_by_path_indexed(&view, dir_hint, pattern, effective_limit, cancel)
         }
+        SearchMode::PathContains { term } => search_by_path_contains(&view, term, cancel),
     };
     let filter_ms = t_filter.elapsed().as_secs_f64() * 1000.0;
     let matched = indices.len();
+    if search_cancel::is_cancelled(cancel) {
+        return Vec::new();
+    }
 
     let t_sort = Instant::now();
+    // Listings and extension lookups sort by plain name, like their fast paths
+    let rank_query = match mode {
+        SearchMode::Empty | SearchMode::ExtSearch { .. } => "",
+        _ => query,
+    };
     // Use partial sort when result set is much larger than limit
     let lim = (offset as usize + effective_limit as usize).min(indices.len());
     if indices.len() > lim * 3 {
-        partial_sort_indices(mem_index, &mut indices, query, sort_by, sort_dir, lim);
+        partial_sort_indices(&view, &mut indices, rank_query, sort_by, sort_dir, lim);
     } else {
-        sort_indices(mem_index, &mut indices, query, sort_by, sort_dir);
+        sort_indices(&view, &mut indices, rank_query, sort_by, sort_dir);
     }
     let sort_ms = t_sort.elapsed().as_secs_f64() * 1000.0;
 
@@ -310,7 +566,7 @@ pub fn search_mem_index(
     let page: Vec<EntryDto> = if start < indices.len() {
         indices[start..end]
             .iter()
-            .map(|&i| mem_index.entries[i as usize].to_entry_dto())
+            .map(|&i| view.entry(i).to_entry_dto())
             .collect()
     } else {
         Vec::new()
@@ -328,76 +584,115 @@ pub fn search_mem_index(
 
 /// Time budget for linear scan phases (contains, glob full scan, path)
 const SCAN_BUDGET_MS: u128 = 30;
-
-fn search_by_name_indexed(mem_index: &MemIndex, q_lower: &str, limit: u32) -> Vec<u32> {
+/// Exact and prefix matches gathered for ranking before the page is cut,
+/// like `run_db_search` ordering them by depth ahead of its LIMIT. Only a
+/// very short prefix ("a") has more; past this many the rest are dropped
+/// in name order.
+const RANKED_CANDIDATE_CAP: usize = 50_000;
+
+/// Exact and prefix matches (all of them, up to `RANKED_CANDIDATE_CAP`),
+/// then contains matches until `limit` rows are found. The caller's
+/// relevance sort puts the shallowest exact matches first, so a `limit`
+/// taken in name order can't crowd out `~/name` with deep copies.
+fn search_by_name_indexed(
+    view: &View,
+    q_lower: &str,
+    limit: u32,
+    cancel: Option<&SearchCancelToken>,
+) -> Vec<u32> {
     let cap = limit as usize;
+    let ranked_cap = cap.max(RANKED_CANDIDATE_CAP);
+
+    // Entries added since the build are few: bucket them up front.
+    let mut exact: Vec<u32> = Vec::new();
+    let mut prefix: Vec<u32> = Vec::new();
+    let mut contains: Vec<u32> = Vec::new();
+    for id in view.added_ids() {
+        let nl = view.name_lower(id);
+        if nl == q_lower {
+            exact.push(id);
+        } else if nl.starts_with(q_lower) {
+            prefix.push(id);
+        } else if nl.contains(q_lower) {
+            contains.push(id);
+        }
+    }
 
     // Phase 1: exact + prefix via binary search on sorted_idx
-    let lo = mem_index
+    let lo = view
+        .index
         .sorted_idx
-        .partition_point(|&idx| mem_index.names_lower[idx as usize].as_str() < q_lower);
+        .partition_point(|&idx| view.name_lower(idx) < q_lower);
 
-    let mut exact: Vec<u32> = Vec::new();
     let mut i = lo;
-    while i < mem_index.sorted_idx.len()
-        && mem_index.names_lower[mem_index.sorted_idx[i] as usize] == q_lower
+    while i < view.index.sorted_idx.len()
+        && view.name_lower(view.index.sorted_idx[i]) == q_lower
     {
-        exact.push(mem_index.sorted_idx[i]);
+        let idx = view.index.sorted_idx[i];
+        if view.live(idx) && exact.len() < ranked_cap {
+            exact.push(idx);
+        }
         i += 1;
     }
 
-    if exact.len() >= cap {
-        exact.truncate(cap);
-        return exact;
-    }
-
     // Prefix matches: sorted entries from i to upper bound
     let prefix_end_str = increment_string(q_lower);
     let prefix_hi = match &prefix_end_str {
-        Some(end_str) => mem_index
+        Some(end_str) => view
+            .index
             .sorted_idx
-            .partition_point(|&idx| {
-                mem_index.names_lower[idx as usize].as_str() < end_str.as_str()
-            }),
-        None => mem_index.sorted_idx.len(),
+            .partition_point(|&idx| view.name_lower(idx) < end_str.as_str()),
+        None => view.index.sorted_idx.len(),
     };
 
-    let mut prefix: Vec<u32> = Vec::new();
     for j in i..prefix_hi {
-        prefix.push(mem_index.sorted_idx[j]);
-        if exact.len() + prefix.len() >= cap {
+        let idx = view.index.sorted_idx[j];
+        if !view.live(idx) {
+            continue;
+        }
+        prefix.push(idx);
+        if exact.len() + prefix.len() >= ranked_cap {
             break;
         }
     }
 
     if exact.len() + prefix.len() >= cap {
         exact.extend(prefix);
-        exact.truncate(cap);
         return exact;
     }
 
-    // Phase 2: contains matches (linear scan with time budget + early exit)
+    // Phase 2: contains matches — a memmem pass over the packed names with
+    // time budget + early exit. After a hit the search resumes at the next
+    // entry, so each entry is reported once.
     let remaining = cap - exact.len() - prefix.len();
     let exact_prefix_set: std::collections::HashSet<u32> =
         exact.iter().chain(prefix.iter()).copied().collect();
 
     let scan_start = Instant::now();
-    let mut contains: Vec<u32> = Vec::new();
-    for (idx, nl) in mem_index.names_lower.iter().enumerate() {
-        let idx = idx as u32;
-        if exact_prefix_set.contains(&idx) {
-            continue;
-        }
-        if nl.contains(q_lower) {
-            contains.push(idx);
-            if contains.len() >= remaining {
+    contains.truncate(remaining);
+    if contains.len() < remaining && !q_lower.is_empty() && !q_lower.contains(NAME_SEP) {
+        let finder = memmem::Finder::new(q_lower.as_bytes());
+        let haystack = view.index.names_blob.as_bytes();
+        let mut pos = 0;
+        let mut hits: u32 = 0;
+        while let Some(hit) = finder.find(&haystack[pos..]) {
+            let idx = view.index.entry_at(pos + hit);
+            pos = view.index.name_starts[idx as usize + 1] as usize;
+            hits += 1;
+            if !exact_prefix_set.contains(&idx) && view.live(idx) {
+                contains.push(idx);
+                if contains.len() >= remaining {
+                    break;
+                }
+            }
+            // Check time budget every 1K hits
+            if hits & 0x3FF == 0
+                && (scan_start.elapsed().as_millis() > SCAN_BUDGET_MS
+                    || search_cancel::is_cancelled(cancel))
+            {
                 break;
             }
         }
-        // Check time budget every 64K entries
-        if idx & 0x3FFF == 0 && scan_start.elapsed().as_millis() > SCAN_BUDGET_MS {
-            break;
-        }
     }
 
     exact.extend(prefix);
@@ -405,33 +700,37 @@ fn search_by_name_indexed(mem_index: &MemIndex, q_lower: &str, limit: u32) -> Ve
     exact
 }
 
-fn search_by_glob_indexed(mem_index: &MemIndex, name_like: &str, limit: u32) -> Vec<u32> {
-    let pattern = LikePattern::new(name_like);
+fn search_by_glob_indexed(
+    view: &View,
+    pattern: LikePattern,
+    limit: u32,
+    cancel: Option<&SearchCancelToken>,
+) -> Vec<u32> {
+    let mut results: Vec<u32> = view
+        .added_ids()
+        .filter(|&id| pattern.matches_pre_lowered(view.name_lower(id)))
+        .collect();
 
     // Optimization: if pattern starts with a literal prefix (before first wildcard),
     // use binary search to narrow the range
     if let Some(prefix) = pattern.literal_prefix() {
         if !prefix.is_empty() {
-            let lo = mem_index
+            let lo = view
+                .index
                 .sorted_idx
-                .partition_point(|&idx| {
-                    mem_index.names_lower[idx as usize].as_str() < prefix.as_str()
-                });
+                .partition_point(|&idx| view.name_lower(idx) < prefix.as_str());
             let prefix_end = increment_string(&prefix);
             let hi = match &prefix_end {
-                Some(end_str) => mem_index
+                Some(end_str) => view
+                    .index
                     .sorted_idx
-                    .partition_point(|&idx| {
-                        mem_index.names_lower[idx as usize].as_str() < end_str.as_str()
-                    }),
-                None => mem_index.sorted_idx.len(),
+                    .partition_point(|&idx| view.name_lower(idx) < end_str.as_str()),
+                None => view.index.sorted_idx.len(),
             };
 
-            let mut results: Vec<u32> = Vec::new();
             for j in lo..hi {
-                let idx = mem_index.sorted_idx[j];
-                let nl = &mem_index.names_lower[idx as usize];
-                if pattern.matches_pre_lowered(nl) {
+                let idx = view.index.sorted_idx[j];
+                if view.live(idx) && pattern.matches_pre_lowered(view.name_lower(idx)) {
                     results.push(idx);
                     if results.len() >= limit as usize {
                         break;
@@ -444,13 +743,16 @@ fn search_by_glob_indexed(mem_index: &MemIndex, name_like: &str, limit: u32) ->
 
     // Fallback: full scan with time budget
     let scan_start = Instant::now();
-    let mut results: Vec<u32> = Vec::new();
-    for (i, nl) in mem_index.names_lower.iter().enumerate() {
-        if pattern.matches_pre_lowered(nl) {
+    for i in 0..view.index.entries.len() {
+        if view.live(i as u32) && pattern.matches_pre_lowered(view.name_lower(i as u32)) {
             results.push(i as u32);
         }
         // Check time budget every 64K entries
-        if (i as u32) & 0x3FFF == 0 && i > 0 && scan_start.elapsed().as_millis() > SCAN_BUDGET_MS {
+        if (i as u32) & 0x3FFF == 0
+            && i > 0
+            && (scan_start.elapsed().as_millis() > SCAN_BUDGET_MS
+                || search_cancel::is_cancelled(cancel))
+        {
             break;
         }
     }
@@ -458,43 +760,105 @@ fn search_by_glob_indexed(mem_index: &MemIndex, name_like: &str, limit: u32) ->
 }
 
 fn search_by_path_indexed(
-    mem_index: &MemIndex,
+    view: &View,
     dir_hint: &str,
-    name_like: &str,
+    name_pattern: Option<LikePattern>,
     limit: u32,
+    cancel: Option<&SearchCancelToken>,
 ) -> Vec<u32> {
     let sep = std::path::MAIN_SEPARATOR;
-    let dir_hint_normalized = dir_hint.replace('/', &sep.to_string()).to_lowercase();
+    let dir_hint_normalized = name_norm::fold(&dir_hint.replace('/', &sep.to_string()));
     let dir_suffix = format!("{sep}{dir_hint_normalized}").to_lowercase();
     let dir_infix = format!("{sep}{dir_hint_normalized}{sep}").to_lowercase();
 
     let scan_start = Instant::now();
     let collect_cap = (limit as usize) * 30; // Cap collection at 30x limit
     let mut matching_indices: Vec<u32> = Vec::new();
-    for (dir_lower, idxs) in &mem_index.dir_map {
-        if dir_lower.ends_with(&dir_suffix) || dir_lower.contains(&dir_infix) {
-            matching_indices.extend_from_slice(idxs);
+    let dir_matches =
+        |dir_lower: &str| dir_lower.ends_with(&dir_suffix) || dir_lower.contains(&dir_infix);
+    for id in view.added_ids() {
+        if dir_matches(name_norm::fold(&view.entry(id).dir).as_str()) {
+            matching_indices.push(id);
+        }
+    }
+    for (dir_lower, idxs) in &view.index.dir_map {
+        if dir_matches(dir_lower.as_str()) {
+            matching_indices.extend(idxs.iter().copied().filter(|&idx| view.live(idx)));
             if matching_indices.len() >= collect_cap {
                 break;
             }
         }
         // Time budget for dir_map scan
-        if scan_start.elapsed().as_millis() > SCAN_BUDGET_MS {
+        if scan_start.elapsed().as_millis() > SCAN_BUDGET_MS || search_cancel::is_cancelled(cancel) {
             break;
         }
     }
 
-    if name_like == "%" {
+    let Some(pattern) = name_pattern else {
         return matching_indices;
-    }
-
-    let pattern = LikePattern::new(name_like);
+    };
     matching_indices.retain(|&idx| {
-        pattern.matches_pre_lowered(&mem_index.names_lower[idx as usize])
+        pattern.matches_pre_lowered(view.name_lower(idx))
     });
     matching_indices
 }
 
+/// `*.ext` once the index changed since its build: the built entries from
+/// `ext_map` plus matching added ones, left for the caller to sort.
+fn search_by_ext(view: &View, ext: &str) -> Vec<u32> {
+    let ext_lower = ext.to_lowercase();
+    let built = view.index.ext_map.get(&ext_lower).map_or(&[][..], Vec::as_slice);
+    built
+        .iter()
+        .copied()
+        .filter(|&idx| view.live(idx))
+        .chain(view.added_ids().filter(|&id| view.entry(id).ext.as_deref() == Some(ext_lower.as_str())))
+        .collect()
+}
+
+/// `path:term`: every entry whose full path contains `term`. A directory that
+/// contains it matches all of its entries; otherwise only names (or, for terms
+/// spanning a separator, the joined dir + name) are checked.
+fn search_by_path_contains(
+    view: &View,
+    term: &str,
+    cancel: Option<&SearchCancelToken>,
+) -> Vec<u32> {
+    let sep = std::path::MAIN_SEPARATOR;
+    let term_lower = name_norm::fold(&term.replace(['/', '\\'], &sep.to_string()));
+    let spans_sep = term_lower.contains(sep);
+    let finder = memmem::Finder::new(term_lower.as_bytes());
+    let found = |haystack: &str| finder.find(haystack.as_bytes()).is_some();
+
+    let hit = |dir_lower: &str, name_lower: &str| {
+        found(name_lower) || (spans_sep && found(&format!("{dir_lower}{sep}{name_lower}")))
+    };
+
+    let mut matching_indices: Vec<u32> = Vec::new();
+    for id in view.added_ids() {
+        let dir_lower = name_norm::fold(&view.entry(id).dir);
+        if found(dir_lower.as_str()) || hit(dir_lower.as_str(), view.name_lower(id)) {
+            matching_indices.push(id);
+        }
+    }
+    for (dir_lower, idxs) in &view.index.dir_map {
+        // Unbudgeted (every match is wanted), so cancellation is its only exit.
+        if search_cancel::is_cancelled(cancel) {
+            break;
+        }
+        if found(dir_lower.as_str()) {
+            matching_indices.extend(idxs.iter().copied().filter(|&idx| view.live(idx)));
+            continue;
+        }
+        for &idx in idxs {
+            if view.live(idx) && hit(dir_lower.as_str(), view.name_lower(idx)) {
+                matching_indices.push(idx);
+            }
+        }
+    }
+    matching_indices
+}
+
 /// Increment the last character of a string to get the exclusive upper bound.
 fn increment_string(s: &str) -> Option<String> {
     let mut chars: Vec<char> = s.chars().collect();
@@ -508,29 +872,15 @@ fn increment_string(s: &str) -> Option<String> {
 }
 
 fn sort_indices(
-    mem_index: &MemIndex,
+    view: &View,
     indices: &mut [u32],
     query: &str,
     sort_by: &str,
     sort_dir: &str,
 ) {
     if sort_by == "name" && !query.is_empty() {
-        let q_lower = query.trim().to_lowercase();
-        indices.sort_unstable_by(|&a, &b| {
-            let ra = relevance_rank_idx(mem_index, a, &q_lower);
-            let rb = relevance_rank_idx(mem_index, b, &q_lower);
-            if ra != rb {
-                return ra.cmp(&rb);
-            }
-            if ra <= 3 {
-                let da = path_depth(&mem_index.entries[a as usize].dir);
-                let db = path_depth(&mem_index.entries[b as usize].dir);
-                if da != db {
-                    return da.cmp(&db);
-                }
-            }
-            name_cmp_idx(mem_index, a, b, sort_dir)
-        });
+        let k = indices.len();
+        sort_by_relevance(view, indices, query, sort_dir, k);
     } else {
         match sort_by {
             "mtime" | "size" => {
@@ -538,27 +888,27 @@ fn sort_indices(
                 let is_size = sort_by == "size";
                 indices.sort_unstable_by(|&a, &b| {
                     let oa = if is_size {
-                        mem_index.entries[a as usize].size
+                        view.entry(a).size
                     } else {
-                        mem_index.entries[a as usize].mtime
+                        view.entry(a).mtime
                     };
                     let ob = if is_size {
-                        mem_index.entries[b as usize].size
+                        view.entry(b).size
                     } else {
-                        mem_index.entries[b as usize].mtime
+                        view.entry(b).mtime
                     };
                     cmp_opt_none_last(oa, ob, desc)
                 });
             }
             _ => {
-                indices.sort_unstable_by(|&a, &b| name_cmp_idx(mem_index, a, b, sort_dir));
+                indices.sort_unstable_by(|&a, &b| name_cmp_idx(view, a, b, sort_dir));
             }
         }
     }
 }
 
 fn partial_sort_indices(
-    mem_index: &MemIndex,
+    view: &View,
     indices: &mut Vec<u32>,
     query: &str,
     sort_by: &str,
@@ -571,25 +921,8 @@ fn partial_sort_indices(
     let k = k.min(indices.len());
 
     if sort_by == "name" && !query.is_empty() {
-        let q_lower = query.trim().to_lowercase();
-        let cmp = |a: &u32, b: &u32| {
-            let ra = relevance_rank_idx(mem_index, *a, &q_lower);
-            let rb = relevance_rank_idx(mem_index, *b, &q_lower);
-            if ra != rb {
-                return ra.cmp(&rb);
-            }
-            if ra <= 3 {
-                let da = path_depth(&mem_index.entries[*a as usize].dir);
-                let db = path_depth(&mem_index.entries[*b as usize].dir);
-                if da != db {
-                    return da.cmp(&db);
-                }
-            }
-            name_cmp_idx(mem_index, *a, *b, sort_dir)
-        };
-        indices.select_nth_unstable_by(k - 1, cmp);
+        sort_by_relevance(view, indices, query, sort_dir, k);
         indices.truncate(k);
-        indices.sort_unstable_by(cmp);
     } else {
         match sort_by {
             "mtime" | "size" => {
@@ -597,14 +930,14 @@ fn partial_sort_indices(
                 let is_size = sort_by == "size";
                 let cmp = |a: &u32, b: &u32| {
                     let oa = if is_size {
-                        mem_index.entries[*a as usize].size
+                        view.entry(*a).size
                     } else {
-                        mem_index.entries[*a as usize].mtime
+                        view.entry(*a).mtime
                     };
                     let ob = if is_size {
-                        mem_index.entries[*b as usize].size
+                        view.entry(*b).size
                     } else {
-                        mem_index.entries[*b as usize].mtime
+                        view.entry(*b).mtime
                     };
                     cmp_opt_none_last(oa, ob, desc)
                 };
@@ -613,7 +946,7 @@ fn partial_sort_indices(
                 indices.sort_unstable_by(cmp);
             }
             _ => {
-                let cmp = |a: &u32, b: &u32| name_cmp_idx(mem_index, *a, *b, sort_dir);
+                let cmp = |a: &u32, b: &u32| name_cmp_idx(view, *a, *b, sort_dir);
                 indices.select_nth_unstable_by(k - 1, cmp);
                 indices.truncate(k);
                 indices.sort_unstable_by(cmp);
@@ -622,6 +955,37 @@ fn partial_sort_indices(
     }
 }
 
+/// (relevance rank, is a backup copy, path depth): ascending is better.
+type RelevanceKey = (u8, bool, usize);
+
+/// Put the `k` most relevant of `indices` first, in order (then by name).
+/// Each entry's rank key is computed once up front: ranking folds names and
+/// backup detection allocates, far too much to redo per comparison.
+fn sort_by_relevance(view: &View, indices: &mut [u32], query: &str, sort_dir: &str, k: usize) {
+    let q_lower = name_norm::fold(query.trim());
+    let mut keyed: Vec<(RelevanceKey, u32)> = indices
+        .iter()
+        .map(|&idx| {
+            let rank = relevance_rank_idx(view, idx, &q_lower);
+            // Originals before their backup copies ("report (1).pdf").
+            let backup = crate::backups::is_backup_name(&view.entry(idx).name);
+            let depth = if rank <= 3 { path_depth(&view.entry(idx).dir) } else { 0 };
+            ((rank, backup, depth), idx)
+        })
+        .collect();
+    let cmp = |a: &(RelevanceKey, u32), b: &(RelevanceKey, u32)| {
+        a.0.cmp(&b.0).then_with(|| name_cmp_idx(view, a.1, b.1, sort_dir))
+    };
+    if k < keyed.len() {
+        keyed.select_nth_unstable_by(k - 1, cmp);
+        keyed.truncate(k);
+    }
+    keyed.sort_unstable_by(cmp);
+    for (slot, (_, idx)) in indices.iter_mut().zip(keyed) {
+        *slot = idx;
+    }
+}
+
 /// Compare two Option<i64> values, pushing None to the end regardless of sort direction.
 fn cmp_opt_none_last(a: Option<i64>, b: Option<i64>, desc: bool) -> std::cmp::Ordering {
     match (a, b) {
@@ -632,20 +996,20 @@ fn cmp_opt_none_last(a: Option<i64>, b: Option<i64>, desc: bool) -> std::cmp::Or
     }
 }
 
-fn name_cmp_idx(mem_index: &MemIndex, a: u32, b: u32, sort_dir: &str) -> std::cmp::Ordering {
-    let na = &mem_index.names_lower[a as usize];
-    let nb = &mem_index.names_lower[b as usize];
+fn name_cmp_idx(view: &View, a: u32, b: u32, sort_dir: &str) -> std::cmp::Ordering {
+    let na = view.name_lower(a);
+    let nb = view.name_lower(b);
     if sort_dir == "desc" { nb.cmp(na) } else { na.cmp(nb) }
 }
 
-fn relevance_rank_idx(mem_index: &MemIndex, idx: u32, q_lower: &str) -> u8 {
-    let name_lower = &mem_index.names_lower[idx as usize];
+fn relevance_rank_idx(view: &View, idx: u32, q_lower: &str) -> u8 {
+    let name_lower = view.name_lower(idx);
     if name_lower == q_lower {
         return 1;
     }
-    let e = &mem_index.entries[idx as usize];
+    let e = view.entry(idx);
     if let Some(dot_pos) = e.name.rfind('.') {
-        if e.name[..dot_pos].to_lowercase() == *q_lower {
+        if name_norm::fold(&e.name[..dot_pos]) == *q_lower {
             return 2;
         }
     }
@@ -662,15 +1026,93 @@ fn path_depth(path: &str) -> usize {
     path.chars().filter(|&c| c == '/' || c == '\\').count()
 }
 
+/// One entry checked against a parsed query, by the same rules the
+/// `search_by_*` scans apply — for rows that change after a search ran
+/// (live query watches). Limits and time budgets don't apply.
+#[derive(Debug)]
+pub(crate) enum EntryMatcher {
+    All,
+    Name(LikePattern),
+    Ext(String),
+    Path {
+        dir_suffix: String,
+        dir_infix: String,
+        name: LikePattern,
+    },
+    PathContains {
+        term_lower: String,
+    },
+}
+
+impl EntryMatcher {
+    pub(crate) fn new(mode: &SearchMode) -> Self {
+        let sep = std::path::MAIN_SEPARATOR;
+        match mode {
+            SearchMode::Empty => EntryMatcher::All,
+            SearchMode::NameSearch { name_like } => EntryMatcher::Name(LikePattern::new(name_like)),
+            SearchMode::GlobName {
+                name_like,
+                name_glob,
+            } => EntryMatcher::Name(LikePattern::for_name(name_like, name_glob.as_deref())),
+            SearchMode::ExtSearch { ext, .. } => EntryMatcher::Ext(ext.to_lowercase()),
+            SearchMode::PathSearch {
+                name_like,
+                name_glob,
+                dir_hint,
+                ..
+            } => {
+                let hint = name_norm::fold(&dir_hint.replace('/', &sep.to_string()));
+                EntryMatcher::Path {
+                    dir_suffix: format!("{sep}{hint}"),
+                    dir_infix: format!("{sep}{hint}{sep}"),
+                    name: LikePattern::for_name(name_like, name_glob.as_deref()),
+                }
+            }
+            SearchMode::PathContains { term } => EntryMatcher::PathContains {
+                term_lower: name_norm::fold(&term.replace(['/', '\\'], &sep.to_string())),
+            },
+        }
+    }
+
+    pub(crate) fn matches(&self, name: &str, dir: &str, ext: Option<&str>) -> bool {
+        match self {
+            EntryMatcher::All => true,
+            EntryMatcher::Name(pattern) => pattern.matches_pre_lowered(&name_norm::fold(name)),
+            EntryMatcher::Ext(want) => ext.is_some_and(|ext| ext.eq_ignore_ascii_case(want)),
+            EntryMatcher::Path {
+                dir_suffix,
+                dir_infix,
+                name: pattern,
+            } => {
+                let dir_lower = name_norm::fold(dir);
+                (dir_lower.ends_with(dir_suffix.as_str()) || dir_lower.contains(dir_infix.as_str()))
+                    && pattern.matches_pre_lowered(&name_norm::fold(name))
+            }
+            EntryMatcher::PathContains { term_lower } => {
+                let sep = std::path::MAIN_SEPARATOR;
+                let dir_lower = name_norm::fold(dir);
+                let name_lower = name_norm::fold(name);
+                dir_lower.contains(term_lower.as_str())
+                    || name_lower.contains(term_lower.as_str())
+                    || (term_lower.contains(sep)
+                        && format!("{dir_lower}{sep}{name_lower}").contains(term_lower.as_str()))
+            }
+        }
+    }
+}
+
 /// Simple SQL LIKE pattern matcher with backslash escape.
-struct LikePattern {
+#[derive(Debug)]
+pub(crate) struct LikePattern {
     segments: Vec<LikeSegment>,
 }
 
+#[derive(Debug)]
 enum LikeSegment {
     Literal(String), // already lowercased
     SingleChar,
     AnyChars,
+    Class(CharClass),
 }
 
 impl LikePattern {
@@ -689,7 +1131,7 @@ impl LikePattern {
                 '%' => {
                     if !literal.is_empty() {
                         segments.push(LikeSegment::Literal(
-                            std::mem::take(&mut literal).to_lowercase(),
+                            name_norm::fold(&std::mem::take(&mut literal)),
                         ));
                     }
                     while chars.peek() == Some(&'%') {
@@ -700,7 +1142,7 @@ impl LikePattern {
                 '_' => {
                     if !literal.is_empty() {
                         segments.push(LikeSegment::Literal(
-                            std::mem::take(&mut literal).to_lowercase(),
+                            name_norm::fold(&std::mem::take(&mut literal)),
                         ));
                     }
                     segments.push(LikeSegment::SingleChar);
@@ -709,12 +1151,59 @@ impl LikePattern {
             }
         }
         if !literal.is_empty() {
-            segments.push(LikeSegment::Literal(literal.to_lowercase()));
+            segments.push(LikeSegment::Literal(name_norm::fold(&literal)));
         }
 
         LikePattern { segments }
     }
 
+    /// The pattern for a parsed query's name part: `name_glob` (SQLite `GLOB`
+    /// syntax, already folded) when the query has a `[...]` class, since
+    /// `name_like` reads those as any one character.
+    fn for_name(name_like: &str, name_glob: Option<&str>) -> Self {
+        let Some(glob) = name_glob else {
+            return LikePattern::new(name_like);
+        };
+        let chars: Vec<char> = glob.chars().collect();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let class = (chars[i] == '[')
+                .then(|| CharClass::parse(&chars, i))
+                .flatten();
+            let segment = if let Some((class, end)) = class {
+                i = end;
+                LikeSegment::Class(class)
+            } else {
+                let ch = chars[i];
+                i += 1;
+                match ch {
+                    '*' => LikeSegment::AnyChars,
+                    '?' => LikeSegment::SingleChar,
+                    _ => {
+                        literal.push(ch);
+                        continue;
+                    }
+                }
+            };
+            if !literal.is_empty() {
+                segments.push(LikeSegment::Literal(std::mem::take(&mut literal)));
+            }
+            let repeated_star = matches!(
+                (&segment, segments.last()),
+                (LikeSegment::AnyChars, Some(LikeSegment::AnyChars))
+            );
+            if !repeated_star {
+                segments.push(segment);
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(LikeSegment::Literal(literal));
+        }
+        LikePattern { segments }
+    }
+
     /// Extract leading literal prefix before first wildcard (for binary search optimization)
     fn literal_prefix(&self) -> Option<String> {
         match self.segments.first() {
@@ -730,7 +1219,7 @@ impl LikePattern {
 
     #[cfg(test)]
     fn matches(&self, value: &str) -> bool {
-        like_match(&self.segments, &value.to_lowercase(), 0, 0)
+        like_match(&self.segments, &name_norm::fold(value), 0, 0)
     }
 }
 
@@ -755,6 +1244,12 @@ fn like_match(segments: &[LikeSegment], value: &str, seg_idx: usize, val_pos: us
             let char_len = remaining.chars().next().unwrap().len_utf8();
             like_match(segments, value, seg_idx + 1, val_pos + char_len)
         }
+        LikeSegment::Class(class) => match remaining.chars().next() {
+            Some(ch) if class.matches(ch) => {
+                like_match(segments, value, seg_idx + 1, val_pos + ch.len_utf8())
+            }
+            _ => false,
+        },
         LikeSegment::AnyChars => {
             let next_seg = seg_idx + 1;
             if next_seg >= segments.len() {
@@ -836,10 +1331,124 @@ mod tests {
         assert!(matches("%영등포%", "251021 영등포1의2 임시총회공고"));
     }
 
+    #[test]
+    fn class_glob_pattern() {
+        let pattern = LikePattern::for_name("img\\__%", Some("img_[0-9]*"));
+        assert!(pattern.matches("IMG_2024.jpg"));
+        assert!(!pattern.matches("img_new.jpg"));
+        assert_eq!(pattern.literal_prefix(), Some("img_".to_string()));
+        let not_hidden = LikePattern::for_name("_%", Some("[^.]*"));
+        assert!(not_hidden.matches("notes") && !not_hidden.matches(".env"));
+        assert!(LikePattern::for_name("", Some("a[[]b")).matches("a[b"));
+    }
+
     #[test]
     fn literal_prefix_extraction() {
         assert_eq!(LikePattern::new("test%").literal_prefix(), Some("test".to_string()));
         assert_eq!(LikePattern::new("%test").literal_prefix(), None);
         assert_eq!(LikePattern::new("hello").literal_prefix(), Some("hello".to_string()));
     }
+
+    fn entry(dir: &str, name: &str, is_dir: bool) -> CompactEntry {
+        let sep = std::path::MAIN_SEPARATOR;
+        CompactEntry {
+            name: name.to_string(),
+            dir: dir.replace('/', &sep.to_string()),
+            is_dir,
+            ext: name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()),
+            mtime: None,
+            mtime_nsec: None,
+            size: None,
+            cloud_only: false,
+        }
+    }
+
+    fn names(index: &MemIndex, query: &str) -> Vec<String> {
+        let mode = crate::query::parse_query(query);
+        let mut names: Vec<String> =
+            search_mem_index(index, query, &mode, 100, 0, "name", "asc", None)
+                .into_iter()
+                .map(|e| e.name)
+                .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn overlay_upserts_and_removes_subtrees() {
+        let index = MemIndex::build(vec![
+            entry("/r", "docs", true),
+            entry("/r/docs", "report.pdf", false),
+            entry("/r/docs/old", "report-2020.pdf", false),
+            entry("/r", "notes.txt", false),
+        ]);
+        index.upsert(vec![entry("/r", "report-new.pdf", false)]);
+        assert_eq!(names(&index, "report"), ["report-2020.pdf", "report-new.pdf", "report.pdf"]);
+        assert_eq!(names(&index, "*.pdf").len(), 3);
+        assert_eq!(names(&index, "rep*").len(), 3);
+
+        let docs = entry("/r", "docs", true).path();
+        index.remove(&[docs]);
+        assert_eq!(names(&index, "report"), ["report-new.pdf"]);
+        assert_eq!(names(&index, "*.pdf"), ["report-new.pdf"]);
+        assert_eq!(index.len(), 2);
+
+        // Upserting a path again replaces its entry instead of adding one.
+        index.upsert(vec![entry("/r", "report-new.pdf", false)]);
+        assert_eq!(index.len(), 2);
+        let rebuilt = MemIndex::build(index.live_entries());
+        assert_eq!(names(&rebuilt, ""), ["notes.txt", "report-new.pdf"]);
+    }
+
+    #[test]
+    fn name_search_ranks_all_exact_matches_before_cutting_the_page() {
+        let mut entries: Vec<CompactEntry> = (0..5)
+            .map(|i| entry(&format!("/r/deep{i}/a/b"), "report", false))
+            .collect();
+        entries.push(entry("/r/deep/a/b", "report-draft", false));
+        entries.push(entry("/r", "report", false));
+        let index = MemIndex::build(entries);
+        let mode = crate::query::parse_query("report");
+
+        let first = search_mem_index(&index, "report", &mode, 1, 0, "name", "asc", None);
+        assert_eq!(first[0].dir, entry("/r", "report", false).dir);
+
+        // The second page continues the same ranking instead of coming back
+        // empty because only one candidate was gathered.
+        let second = search_mem_index(&index, "report", &mode, 1, 1, "name", "asc", None);
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].name, "report");
+        assert_ne!(second[0].dir, first[0].dir);
+    }
+
+    #[test]
+    fn remove_subtrees_clears_children_of_missing_roots() {
+        let index = MemIndex::build(vec![entry("/r/gone", "a.txt", false)]);
+        let gone = entry("/r", "gone", true).path();
+        index.remove(std::slice::from_ref(&gone));
+        assert_eq!(index.len(), 1);
+        index.remove_subtrees(&[gone]);
+        assert_eq!(index.len(), 0);
+    }
+
+    #[test]
+    fn remove_clears_a_nested_tree_but_not_its_siblings() {
+        let index = MemIndex::build(vec![
+            entry("/r", "a", true),
+            entry("/r/a", "b", true),
+            entry("/r/a/b", "c", true),
+            entry("/r/a/b/c", "deep.txt", false),
+            entry("/r/a/b", "mid.txt", false),
+            entry("/r/a", "top.txt", false),
+            // Sort next to `/r/a` but lie outside it.
+            entry("/r", "a-b", true),
+            entry("/r/a-b", "sibling.txt", false),
+            entry("/r/ab", "other.txt", false),
+        ]);
+        index.upsert(vec![entry("/r/a/b/c", "added.txt", false)]);
+
+        index.remove(&[entry("/r", "a", true).path()]);
+        assert_eq!(names(&index, ""), ["a-b", "other.txt", "sibling.txt"]);
+        assert_eq!(index.len(), 3);
+    }
 }
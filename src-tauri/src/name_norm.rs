@@ -0,0 +1,53 @@
+//! Matching form of names and directories. macOS hands out file names in
+//! NFD (`é` as `e` + U+0301) while typed queries are NFC, so a plain
+//! lowercase compare misses them. Rows carry `name_norm`/`dir_norm` — NFC,
+//! lowercased — and name search compares those against a query folded the
+//! same way. `name` and `dir` stay byte-exact for display and file ops.
+
+use std::borrow::Cow;
+
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+
+/// NFC + lowercase. ASCII (most names) skips the normalizer.
+pub(crate) fn fold(s: &str) -> String {
+    if s.is_ascii() {
+        return s.to_ascii_lowercase();
+    }
+    nfc(s).to_lowercase()
+}
+
+pub(crate) fn nfc(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+    ComposingNormalizerBorrowed::new_nfc().normalize(s)
+}
+
+/// `s` in NFD. With `nfc`, for matching against indexes built over raw names
+/// (the FTS trigram table), which hold whatever form the filesystem returned.
+pub(crate) fn nfd(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+    DecomposingNormalizerBorrowed::new_nfd().normalize(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nfd_and_nfc_names_fold_to_the_same_key() {
+        let nfd_name = "Cafe\u{301}.TXT";
+        let nfc_name = "Caf\u{e9}.txt";
+        assert_eq!(fold(nfd_name), fold(nfc_name));
+        assert_eq!(fold(nfd_name), "caf\u{e9}.txt");
+        assert_eq!(fold("README.md"), "readme.md");
+        // Hangul typed on macOS arrives as conjoining jamo.
+        assert_eq!(fold("\u{1112}\u{1161}\u{11ab}"), "\u{d55c}");
+
+        assert_eq!(nfd(nfc_name), "Cafe\u{301}.txt");
+        assert_eq!(nfc(nfd_name), "Caf\u{e9}.TXT");
+        assert!(matches!(nfd("plain"), Cow::Borrowed("plain")));
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
 use std::process::Command;
 
+use crate::{long_path, mem_resident};
 use crate::{
     db_connection, delete_paths, index_row_from_path_and_metadata,
     invalidate_search_caches, should_skip_path, touch_status_updated,
@@ -73,15 +75,51 @@ pub(crate) fn pathindexing_active_entries(content: &str) -> Vec<String> {
         .collect()
 }
 
+/// Validate roots passed to `set_scan_roots`: absolute existing directories,
+/// duplicates dropped.
+pub(crate) fn normalize_scan_roots(roots: &[String]) -> AppResult<Vec<PathBuf>> {
+    let mut normalized: Vec<PathBuf> = Vec::with_capacity(roots.len());
+    for root in roots.iter().map(|r| r.trim()).filter(|r| !r.is_empty()) {
+        // Collecting the components drops trailing separators but keeps
+        // drive roots like `C:\` intact.
+        let path: PathBuf = Path::new(root).components().collect();
+        if !path.is_absolute() {
+            return Err(format!("Scan root must be an absolute path: {root}"));
+        }
+        if !path.is_dir() {
+            return Err(format!("Scan root is not a directory: {root}"));
+        }
+        if !normalized.contains(&path) {
+            normalized.push(path);
+        }
+    }
+    Ok(normalized)
+}
+
+/// Rewrite `.pathindexing` with `roots`, keeping its comment lines.
+pub(crate) fn write_pathindexing_roots(path: &Path, roots: &[PathBuf]) -> AppResult<()> {
+    ensure_pathindexing_exists(path)?;
+    let existing = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut contents: String = existing
+        .lines()
+        .filter(|l| l.trim().is_empty() || l.trim().starts_with('#'))
+        .map(|l| format!("{l}\n"))
+        .collect();
+    contents.truncate(contents.trim_end().len());
+    contents.push('\n');
+    for root in roots {
+        contents.push_str(&root.to_string_lossy());
+        contents.push('\n');
+    }
+    fs::write(path, contents).map_err(|e| e.to_string())
+}
+
 pub(crate) fn open_pathindexing_file(path: &Path) -> AppResult<()> {
     ensure_pathindexing_exists(path)?;
     #[cfg(target_os = "macos")]
     Command::new("open").arg(path).spawn().map_err(|e| e.to_string())?;
     #[cfg(target_os = "windows")]
-    Command::new("cmd")
-        .args(["/C", "start", "", &path.to_string_lossy().to_string()])
-        .spawn()
-        .map_err(|e| e.to_string())?;
+    crate::win::shell_open::open_path(&path.to_string_lossy())?;
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     let _ = path;
     Ok(())
@@ -109,7 +147,7 @@ pub(crate) fn scan_extra_roots(
 
         // Index the root directory itself so resolve_dirs_from_db can find it
         // (enables fast range-based queries instead of LIKE full-table scan)
-        if let Ok(meta) = fs::symlink_metadata(root) {
+        if let Ok(meta) = long_path::symlink_metadata(root) {
             if let Some(row) = index_row_from_path_and_metadata(root, &meta) {
                 batch.push(row);
             }
@@ -127,7 +165,7 @@ pub(crate) fn scan_extra_roots(
                         if should_skip_path(&full_path, &skip_roots, &skip_patterns) {
                             return false;
                         }
-                        entry.client_state = fs::symlink_metadata(&full_path).ok();
+                        entry.client_state = long_path::symlink_metadata(&full_path).ok();
                         true
                     }
                     Err(_) => false,
@@ -190,7 +228,13 @@ pub(crate) fn handle_pathindexing_change(
     let new_set: HashSet<&PathBuf> = new_roots.iter().collect();
 
     let added: Vec<PathBuf> = new_set.difference(&old_set).map(|p| (*p).clone()).collect();
-    let removed: Vec<PathBuf> = old_set.difference(&new_set).map(|p| (*p).clone()).collect();
+    // A dropped root inside the scan root or a remaining root stays indexed
+    // through that root, so its rows are kept.
+    let removed: Vec<PathBuf> = old_set
+        .difference(&new_set)
+        .filter(|p| !p.starts_with(&state.scan_root) && !new_roots.iter().any(|r| p.starts_with(r)))
+        .map(|p| (*p).clone())
+        .collect();
 
     if !removed.is_empty() {
         eprintln!("[pathindexing] removing {} roots from index", removed.len());
@@ -204,6 +248,12 @@ pub(crate) fn handle_pathindexing_change(
 
     if !added.is_empty() || !removed.is_empty() {
         invalidate_search_caches(state);
+        let changed: Vec<String> = added
+            .iter()
+            .chain(&removed)
+            .map(|root| root.to_string_lossy().to_string())
+            .collect();
+        mem_resident::resync(state, &changed);
         touch_status_updated(state);
     }
 
@@ -221,4 +271,27 @@ mod tests {
         assert_eq!(entries, vec!["/tmp/foo", "/tmp/bar"]);
     }
 
+    #[test]
+    fn write_roots_keeps_comments_and_replaces_entries() {
+        let dir = crate::temp_case_dir("pathindexing_write");
+        let extra = dir.join("extra");
+        fs::create_dir_all(&extra).unwrap();
+        let file = dir.join(".pathindexing");
+        fs::write(&file, "# mine\n/old/root\n\n").unwrap();
+
+        let sep = std::path::MAIN_SEPARATOR;
+        let given = format!("{}{sep}", extra.to_string_lossy());
+        let roots = normalize_scan_roots(&[given, extra.to_string_lossy().to_string()]).unwrap();
+        assert_eq!(roots, vec![extra.clone()]);
+        assert!(normalize_scan_roots(&["relative".to_string()]).is_err());
+        assert!(normalize_scan_roots(&[dir.join("missing").to_string_lossy().to_string()]).is_err());
+
+        write_pathindexing_roots(&file, &roots).unwrap();
+        let content = fs::read_to_string(&file).unwrap();
+        assert!(content.starts_with("# mine\n"));
+        assert_eq!(load_pathindexing_roots(&file), vec![extra]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
 }
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+use crate::AppResult;
+
+const DEFAULT_PATHPRIORITY_CONTENTS: &str = "\
+# Everything - hot directories
+# Directories listed here are scanned first during indexing and their
+# file changes are applied with a shorter watcher debounce.
+# One path per line; absolute or starting with ~/. Lines starting with # are comments.
+# Example:
+# ~/Projects
+# /Volumes/ExternalDrive/Work
+";
+
+pub(crate) fn ensure_pathpriority_exists(path: &Path) -> AppResult<()> {
+    if !path.exists() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(path, DEFAULT_PATHPRIORITY_CONTENTS).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn load_hot_dirs(path: &Path, home_dir: &Path) -> Vec<PathBuf> {
+    let _ = ensure_pathpriority_exists(path);
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    parse_hot_dirs(&contents, home_dir)
+}
+
+/// Hot directories in file order. Entries need not exist yet so a project
+/// folder created later is prioritized without editing the file again.
+pub(crate) fn parse_hot_dirs(content: &str, home_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for line in content.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let p = if line == "~" {
+            home_dir.to_path_buf()
+        } else if let Some(rest) = line.strip_prefix("~/") {
+            home_dir.join(rest)
+        } else {
+            PathBuf::from(line)
+        };
+        if p.is_absolute() && !dirs.contains(&p) {
+            dirs.push(p);
+        }
+    }
+    dirs
+}
+
+pub(crate) fn is_hot_path(path: &Path, hot_dirs: &[PathBuf]) -> bool {
+    hot_dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Position of the first hot directory related to `root` — either inside
+/// it or containing it — or `None` when the root is unrelated.
+fn hot_rank(root: &Path, hot_dirs: &[PathBuf]) -> Option<usize> {
+    hot_dirs
+        .iter()
+        .position(|dir| root.starts_with(dir) || dir.starts_with(root))
+}
+
+/// Stable reorder of scan roots: roots touching a hot directory move to the
+/// front in hot-directory order; the rest keep their priority/deferred order.
+pub(crate) fn order_roots_by_priority(roots: Vec<PathBuf>, hot_dirs: &[PathBuf]) -> Vec<PathBuf> {
+    if hot_dirs.is_empty() {
+        return roots;
+    }
+    let mut ranked: Vec<(usize, PathBuf)> = roots
+        .into_iter()
+        .map(|root| (hot_rank(&root, hot_dirs).unwrap_or(usize::MAX), root))
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+    ranked.into_iter().map(|(_, root)| root).collect()
+}
+
+pub(crate) fn open_pathpriority_file(path: &Path) -> AppResult<()> {
+    ensure_pathpriority_exists(path)?;
+    #[cfg(target_os = "macos")]
+    Command::new("open").arg(path).spawn().map_err(|e| e.to_string())?;
+    #[cfg(target_os = "windows")]
+    crate::win::shell_open::open_path(&path.to_string_lossy())?;
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hot_dirs_expand_home_and_move_related_roots_first() {
+        let home = std::env::temp_dir().join("me");
+        let content = format!(
+            "# comment\n~/Projects\n\nrelative/skip\n{}\n~/Projects\n",
+            home.join("work").join("api").display()
+        );
+        let hot = parse_hot_dirs(&content, &home);
+        assert_eq!(hot, vec![home.join("Projects"), home.join("work").join("api")]);
+        assert!(is_hot_path(&home.join("Projects").join("app").join("main.rs"), &hot));
+        assert!(!is_hot_path(&home.join("Documents").join("a.txt"), &hot));
+
+        let roots = vec![
+            home.join("Documents"),
+            home.join("work"),
+            home.join("Library"),
+            home.join("Projects"),
+        ];
+        assert_eq!(
+            order_roots_by_priority(roots, &hot),
+            vec![
+                home.join("Projects"),
+                home.join("work"),
+                home.join("Documents"),
+                home.join("Library"),
+            ]
+        );
+    }
+}
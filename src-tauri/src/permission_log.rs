@@ -0,0 +1,106 @@
+//! Which paths the last index run could not read, and why. The status only
+//! carries a count (`permission_errors`); this keeps the paths themselves so
+//! the user can tell which folders need Full Disk Access or an ACL fix.
+//! Cleared when a run starts; details past `MAX_RECORDED` are counted only.
+
+use std::io;
+use std::path::Path;
+
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Failures kept with their path; the rest only raise `total`.
+const MAX_RECORDED: usize = 500;
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionErrorDto {
+    pub path: String,
+    /// `io::ErrorKind` name, e.g. `PermissionDenied`; `Walk` for walker
+    /// errors that carry no OS error.
+    pub kind: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionErrorReport {
+    /// Every failure of the run, recorded or not.
+    pub total: u64,
+    /// True when `errors` stops short of `total`.
+    pub truncated: bool,
+    pub errors: Vec<PermissionErrorDto>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct PermissionErrorLog {
+    inner: Mutex<PermissionErrorReport>,
+}
+
+impl PermissionErrorLog {
+    pub(crate) fn clear(&self) {
+        *self.inner.lock() = PermissionErrorReport::default();
+    }
+
+    pub(crate) fn record(&self, path: &Path, error: &io::Error) {
+        self.push(path, format!("{:?}", error.kind()), error.to_string());
+    }
+
+    /// A jwalk error, at its own path when it has one, else at `root`.
+    pub(crate) fn record_walk(&self, root: &Path, error: &jwalk::Error) {
+        let path = error.path().unwrap_or(root);
+        match error.io_error() {
+            Some(io_error) => self.record(path, io_error),
+            None => self.push(path, "Walk".to_string(), error.to_string()),
+        }
+    }
+
+    pub(crate) fn report(&self) -> PermissionErrorReport {
+        self.inner.lock().clone()
+    }
+
+    fn push(&self, path: &Path, kind: String, message: String) {
+        let mut inner = self.inner.lock();
+        inner.total += 1;
+        if inner.errors.len() < MAX_RECORDED {
+            inner.errors.push(PermissionErrorDto {
+                path: path.to_string_lossy().to_string(),
+                kind,
+                message,
+            });
+        } else {
+            inner.truncated = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_up_to_the_cap_and_counts_the_rest() {
+        let log = PermissionErrorLog::default();
+        let denied = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+        for i in 0..MAX_RECORDED + 3 {
+            log.record(Path::new(&format!("/private/dir{i}")), &denied);
+        }
+        let report = log.report();
+        assert_eq!(report.total, (MAX_RECORDED + 3) as u64);
+        assert!(report.truncated);
+        assert_eq!(report.errors.len(), MAX_RECORDED);
+        assert_eq!(
+            report.errors[0],
+            PermissionErrorDto {
+                path: "/private/dir0".to_string(),
+                kind: "PermissionDenied".to_string(),
+                message: "denied".to_string(),
+            }
+        );
+
+        log.clear();
+        let report = log.report();
+        assert_eq!((report.total, report.truncated), (0, false));
+        assert!(report.errors.is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+//! Pinned (favorite) entries. Like saved searches they live in their own
+//! table, so `reset_index` and schema rebuilds leave them alone. The set of
+//! pinned paths is mirrored in `AppState::pinned_paths` so relevance ranking
+//! can float them to the top without a query per search.
+
+use std::collections::HashSet;
+
+use rusqlite::{params, Connection, Row};
+use serde::Serialize;
+
+use crate::{now_epoch, row_to_entry, AppResult, EntryDto};
+
+pub(crate) const CREATE_PINNED_ENTRIES_SQL: &str = "\
+CREATE TABLE IF NOT EXISTS pinned_entries (
+    path TEXT PRIMARY KEY,
+    pinned_at INTEGER NOT NULL
+);";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedEntryDto {
+    pub path: String,
+    pub pinned_at: i64,
+    /// The indexed entry; `None` while the path is not (or no longer) in
+    /// the index.
+    pub entry: Option<EntryDto>,
+}
+
+const SELECT_PINS: &str = "\
+SELECT e.path, e.name, e.dir, e.is_dir, e.ext, e.size, e.mtime, e.cloud_only, p.path, p.pinned_at
+FROM pinned_entries p LEFT JOIN entries e ON e.path = p.path";
+
+fn row_to_pin(row: &Row<'_>) -> rusqlite::Result<PinnedEntryDto> {
+    let indexed: Option<String> = row.get(0)?;
+    Ok(PinnedEntryDto {
+        entry: match indexed {
+            Some(_) => Some(row_to_entry(row)?),
+            None => None,
+        },
+        path: row.get(8)?,
+        pinned_at: row.get(9)?,
+    })
+}
+
+/// Pin `path`. Pinning an already pinned path keeps its original time.
+pub(crate) fn pin_entry(conn: &Connection, path: &str) -> AppResult<PinnedEntryDto> {
+    conn.execute(
+        "INSERT INTO pinned_entries(path, pinned_at) VALUES(?1, ?2)
+         ON CONFLICT(path) DO NOTHING",
+        params![path, now_epoch()],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.query_row(
+        &format!("{SELECT_PINS} WHERE p.path = ?1"),
+        params![path],
+        row_to_pin,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Returns false when `path` was not pinned.
+pub(crate) fn unpin_entry(conn: &Connection, path: &str) -> AppResult<bool> {
+    conn.execute("DELETE FROM pinned_entries WHERE path = ?1", params![path])
+        .map(|n| n > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Newest pin first.
+pub(crate) fn list_pins(conn: &Connection) -> AppResult<Vec<PinnedEntryDto>> {
+    let mut stmt = conn
+        .prepare(&format!("{SELECT_PINS} ORDER BY p.pinned_at DESC, p.path"))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], row_to_pin).map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn load_pinned_paths(conn: &Connection) -> AppResult<HashSet<String>> {
+    let mut stmt = conn
+        .prepare("SELECT path FROM pinned_entries")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<HashSet<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Move pinned entries ahead of the rest, keeping the relevance order on
+/// both sides. Only entries that already matched the query are in `entries`.
+pub(crate) fn float_pinned(entries: &mut Vec<EntryDto>, pinned: &HashSet<String>) {
+    if pinned.is_empty() || !entries.iter().any(|e| pinned.contains(&e.path)) {
+        return;
+    }
+    let (mut front, rest): (Vec<EntryDto>, Vec<EntryDto>) =
+        entries.drain(..).partition(|e| pinned.contains(&e.path));
+    front.extend(rest);
+    *entries = front;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_survive_repeat_and_float_matches_to_top() {
+        let root = crate::temp_case_dir("pins");
+        std::fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+        let notes = std::path::Path::new("/home/notes.md");
+        crate::insert_test_entry(&conn, notes, false, 1);
+        crate::set_test_entry_stats(&conn, notes, Some("md"), Some(5), Some(10));
+
+        let pin = pin_entry(&conn, "/home/notes.md").unwrap();
+        assert_eq!(
+            pin.entry.as_ref().map(|e| e.name.as_str()),
+            Some("notes.md")
+        );
+        conn.execute("UPDATE pinned_entries SET pinned_at = 1", [])
+            .unwrap();
+        assert_eq!(pin_entry(&conn, "/home/notes.md").unwrap().pinned_at, 1);
+        assert!(pin_entry(&conn, "/gone").unwrap().entry.is_none());
+
+        let listed: Vec<String> = list_pins(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.path)
+            .collect();
+        assert_eq!(listed, vec!["/gone", "/home/notes.md"]);
+        assert!(unpin_entry(&conn, "/gone").unwrap());
+        assert!(!unpin_entry(&conn, "/gone").unwrap());
+
+        let pinned = load_pinned_paths(&conn).unwrap();
+        let mut results = vec![
+            crate::test_entry("/home/notes"),
+            crate::test_entry("/home/notes.txt"),
+            crate::test_entry("/home/notes.md"),
+        ];
+        float_pinned(&mut results, &pinned);
+        let order: Vec<&str> = results.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            order,
+            vec!["/home/notes.md", "/home/notes", "/home/notes.txt"]
+        );
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,301 @@
+//! Polling fallback for roots no change notification reaches: network shares
+//! and FUSE mounts, whose remote side never raises a local event, and any
+//! root the platform watcher (FSEvents, USN/RDCW, fanotify/inotify) failed to
+//! open. Every `POLL_INTERVAL` the roots are walked and each entry's mtime
+//! and size compared with the previous walk; whatever was added, removed or
+//! changed goes through `process_watcher_paths` like a watcher batch.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime};
+
+use tauri::AppHandle;
+use walkdir::WalkDir;
+
+use crate::{
+    cached_effective_ignore_rules, emit_and_persist_cached_counts, process_watcher_paths,
+    should_skip_path, AppState, WatcherHints, STATUS_EMIT_MIN_INTERVAL,
+};
+
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often polling threads check for a stop between passes.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    mtime: Option<SystemTime>,
+    size: u64,
+    is_dir: bool,
+}
+
+type Snapshot = HashMap<PathBuf, Stamp>;
+
+/// Periodic walk-and-compare over a fixed set of roots. The first pass only
+/// records a baseline: the full index already covered what was there.
+struct PollScanner {
+    roots: Vec<PathBuf>,
+    snapshot: Option<Snapshot>,
+    next_scan: Instant,
+}
+
+impl PollScanner {
+    /// Walk the roots if a pass is due and return the paths that differ from
+    /// the last pass. A pass cut short by `stop`, or with a root missing,
+    /// keeps the old baseline.
+    fn scan_if_due(&mut self, state: &AppState, stop: &AtomicBool) -> Vec<PathBuf> {
+        if Instant::now() < self.next_scan {
+            return Vec::new();
+        }
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+        let current = walk(&self.roots, stop, |path| {
+            should_skip_path(path, &ignored_roots, &ignored_patterns)
+        });
+        self.next_scan = Instant::now() + POLL_INTERVAL;
+        let Some(current) = current else {
+            return Vec::new();
+        };
+        match self.snapshot.replace(current) {
+            Some(previous) => diff(&previous, self.snapshot.as_ref().unwrap()),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Keeps a polling thread running; dropping it stops the thread, so a
+/// watcher loop that owns one takes it down on exit.
+pub(crate) struct PollHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for PollHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, AtomicOrdering::Release);
+    }
+}
+
+/// Poll `roots` on a background thread, handing each pass's changes to
+/// `send` until it returns false. A slow share can take a while to walk, so
+/// this runs beside a watcher's event loop rather than inside it.
+pub(crate) fn spawn(
+    state: &AppState,
+    roots: Vec<PathBuf>,
+    mut send: impl FnMut(Vec<PathBuf>) -> bool + Send + 'static,
+) -> PollHandle {
+    eprintln!(
+        "[poll_watcher] polling {} root(s) every {}s",
+        roots.len(),
+        POLL_INTERVAL.as_secs()
+    );
+    let stop = Arc::new(AtomicBool::new(false));
+    let (state, thread_stop) = (state.clone(), stop.clone());
+    std::thread::spawn(move || {
+        let mut scanner = PollScanner {
+            roots,
+            snapshot: None,
+            next_scan: Instant::now(),
+        };
+        while !thread_stop.load(AtomicOrdering::Acquire) {
+            let changed = scanner.scan_if_due(&state, &thread_stop);
+            if !changed.is_empty() && !send(changed) {
+                break;
+            }
+            std::thread::sleep(STOP_CHECK_INTERVAL);
+        }
+    });
+    PollHandle { stop }
+}
+
+/// The scan root plus `.pathindexing` roots outside it, as stored.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn indexed_roots(state: &AppState) -> Vec<PathBuf> {
+    let mut roots = vec![state.scan_root.clone()];
+    for extra in state.extra_roots.lock().iter() {
+        if !roots.iter().any(|root| extra.starts_with(root)) {
+            roots.push(extra.clone());
+        }
+    }
+    roots
+}
+
+fn walk(roots: &[PathBuf], stop: &AtomicBool, skip: impl Fn(&Path) -> bool) -> Option<Snapshot> {
+    let mut snapshot = Snapshot::new();
+    for root in roots {
+        // An unmounted or unreachable share would otherwise read as every
+        // file under it deleted; skip the pass and keep the last baseline.
+        if std::fs::metadata(root).is_err() {
+            return None;
+        }
+        let entries = WalkDir::new(root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|entry| !skip(entry.path()));
+        for entry in entries.flatten() {
+            if stop.load(AtomicOrdering::Acquire) {
+                return None;
+            }
+            // Unreadable entries simply drop out and read as deletions until
+            // they're readable again; better than keeping stale rows forever.
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            snapshot.insert(
+                entry.into_path(),
+                Stamp {
+                    mtime: metadata.modified().ok(),
+                    size: if metadata.is_dir() { 0 } else { metadata.len() },
+                    is_dir: metadata.is_dir(),
+                },
+            );
+        }
+    }
+    Some(snapshot)
+}
+
+/// Paths added, removed or restamped between two walks, sorted.
+fn diff(previous: &Snapshot, current: &Snapshot) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = current
+        .iter()
+        .filter(|(path, stamp)| previous.get(*path) != Some(stamp))
+        .map(|(path, _)| path.clone())
+        .chain(
+            previous
+                .keys()
+                .filter(|path| !current.contains_key(*path))
+                .cloned(),
+        )
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// Whether `path` lives on a filesystem whose changes can't be observed
+/// locally: NFS/SMB/AFS and friends, or FUSE.
+#[cfg(target_os = "linux")]
+pub(crate) fn needs_polling(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    const REMOTE_MAGICS: [u32; 9] = [
+        0x0000_6969, // NFS
+        0x0000_517b, // SMB
+        0xff53_4d42, // CIFS
+        0xfe53_4d42, // SMB2
+        0x6573_5546, // FUSE
+        0x5346_414f, // AFS
+        0x0102_1997, // 9P
+        0x00c3_6400, // Ceph
+        0x7375_7245, // Coda
+    ];
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    REMOTE_MAGICS.contains(&(stat.f_type as u32))
+}
+
+/// Whether `path` lives on a filesystem whose changes can't be observed
+/// locally: anything not `MNT_LOCAL`, or a FUSE mount.
+#[cfg(target_os = "macos")]
+pub(crate) fn needs_polling(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    let fs_type = unsafe { std::ffi::CStr::from_ptr(stat.f_fstypename.as_ptr()) };
+    stat.f_flags & libc::MNT_LOCAL as u32 == 0
+        || matches!(fs_type.to_bytes(), b"macfuse" | b"osxfuse" | b"fusefs")
+}
+
+/// Poll `roots` as the live watcher, until `watcher_stop`. For when the
+/// platform watcher couldn't start at all.
+pub(crate) fn start(app: Option<AppHandle>, state: AppState, roots: Vec<PathBuf>) {
+    let (tx, rx) = mpsc::channel::<Vec<PathBuf>>();
+    let poller = spawn(&state, roots, move |changed| tx.send(changed).is_ok());
+    state.watcher_active.store(true, AtomicOrdering::Release);
+    std::thread::spawn(move || {
+        let app = app.as_ref();
+        let mut pending: HashSet<PathBuf> = HashSet::new();
+        let mut deadline: Option<Instant> = None;
+        let mut last_status_emit = Instant::now();
+        let mut pending_status_emit = false;
+        while !state.watcher_stop.load(AtomicOrdering::Acquire) {
+            if let Ok(changed) = rx.recv_timeout(STOP_CHECK_INTERVAL) {
+                pending.extend(changed);
+                pending.extend(rx.try_iter().flatten());
+                deadline = Some(Instant::now());
+            }
+            // Also retries batches process_watcher_paths deferred.
+            if deadline.is_some_and(|due| Instant::now() >= due) {
+                process_watcher_paths(
+                    app,
+                    &state,
+                    &mut pending,
+                    &mut WatcherHints::default(),
+                    &mut deadline,
+                    &mut last_status_emit,
+                    &mut pending_status_emit,
+                );
+            }
+            if pending_status_emit && last_status_emit.elapsed() >= STATUS_EMIT_MIN_INTERVAL {
+                emit_and_persist_cached_counts(app, &state);
+                last_status_emit = Instant::now();
+                pending_status_emit = false;
+            }
+            state
+                .watcher_backlog
+                .store(pending.len(), AtomicOrdering::Relaxed);
+        }
+        drop(poller);
+        state.watcher_backlog.store(0, AtomicOrdering::Relaxed);
+        state.watcher_active.store(false, AtomicOrdering::Release);
+        eprintln!("[poll_watcher] stopped");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_entries() {
+        let root = crate::temp_case_dir("poll_watcher");
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+        std::fs::write(root.join("keep/same.txt"), b"same").unwrap();
+        std::fs::write(root.join("grow.txt"), b"a").unwrap();
+        std::fs::write(root.join("gone.txt"), b"x").unwrap();
+        std::fs::create_dir_all(root.join("skipped")).unwrap();
+        let stop = AtomicBool::new(false);
+        let skip = |path: &Path| path.ends_with("skipped");
+
+        let before = walk(std::slice::from_ref(&root), &stop, skip).unwrap();
+        assert!(!before.contains_key(&root.join("skipped")));
+
+        std::fs::write(root.join("grow.txt"), b"abc").unwrap();
+        std::fs::remove_file(root.join("gone.txt")).unwrap();
+        std::fs::write(root.join("skipped/new.txt"), b"n").unwrap();
+        std::fs::write(root.join("keep/new.txt"), b"n").unwrap();
+        let after = walk(std::slice::from_ref(&root), &stop, skip).unwrap();
+
+        let changed = diff(&before, &after);
+        assert!(changed.contains(&root.join("grow.txt")));
+        assert!(changed.contains(&root.join("gone.txt")));
+        assert!(changed.contains(&root.join("keep/new.txt")));
+        assert!(!changed.contains(&root.join("keep/same.txt")));
+        assert!(!changed.iter().any(|p| p.starts_with(root.join("skipped"))));
+
+        stop.store(true, AtomicOrdering::Release);
+        assert!(walk(std::slice::from_ref(&root), &stop, skip).is_none());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
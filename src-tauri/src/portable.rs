@@ -0,0 +1,128 @@
+//! Portable mode: the index, settings and caches live in one directory of
+//! the user's choosing instead of the per-user app data dir, so the app can
+//! run from a USB stick or in CI benchmarks without touching the profile.
+//! Enabled by `--data-dir <path>`, the `EVERYTHING_DATA_DIR` env var, or a
+//! `portable` file next to the executable (data then goes to `data/` beside
+//! it). Portable runs also skip setup that writes elsewhere: MCP client
+//! registration, URL scheme registration and the window-state plugin.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tauri::{AppHandle, Manager};
+
+use crate::AppResult;
+
+const DATA_DIR_FLAG: &str = "--data-dir";
+const DATA_DIR_ENV: &str = "EVERYTHING_DATA_DIR";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DATA_DIR_NAME: &str = "data";
+
+static PORTABLE_DATA_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// The portable data dir, when running portable.
+pub(crate) fn data_dir() -> Option<&'static Path> {
+    PORTABLE_DATA_DIR
+        .get_or_init(|| {
+            let args: Vec<String> = std::env::args().skip(1).collect();
+            let exe_dir = std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf));
+            resolve(&args, std::env::var_os(DATA_DIR_ENV), exe_dir.as_deref())
+        })
+        .as_deref()
+}
+
+pub(crate) fn is_enabled() -> bool {
+    data_dir().is_some()
+}
+
+/// Where the app keeps its data: the portable dir, or Tauri's app data dir.
+pub(crate) fn app_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    match data_dir() {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {e}")),
+    }
+}
+
+/// The flag wins over the env var, which wins over the marker file. A
+/// relative path is taken from the working directory.
+fn resolve(args: &[String], env: Option<OsString>, exe_dir: Option<&Path>) -> Option<PathBuf> {
+    let explicit = flag_value(args)
+        .map(PathBuf::from)
+        .or_else(|| env.filter(|value| !value.is_empty()).map(PathBuf::from));
+    if let Some(dir) = explicit {
+        if dir.is_absolute() {
+            return Some(dir);
+        }
+        return Some(std::env::current_dir().ok()?.join(dir));
+    }
+    let exe_dir = exe_dir?;
+    exe_dir
+        .join(PORTABLE_MARKER)
+        .is_file()
+        .then(|| exe_dir.join(PORTABLE_DATA_DIR_NAME))
+}
+
+fn flag_value(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == DATA_DIR_FLAG {
+            iter.next().cloned()
+        } else {
+            arg.strip_prefix(DATA_DIR_FLAG)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        };
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_dir_comes_from_flag_env_or_marker_file() {
+        let exe_dir = crate::temp_case_dir("portable");
+        std::fs::create_dir_all(&exe_dir).unwrap();
+        let abs = exe_dir.join("stick");
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(resolve(&args(&[]), None, Some(&exe_dir)), None);
+        assert_eq!(
+            resolve(&args(&[]), Some(OsString::new()), Some(&exe_dir)),
+            None
+        );
+
+        std::fs::write(exe_dir.join(PORTABLE_MARKER), b"").unwrap();
+        assert_eq!(
+            resolve(&args(&[]), None, Some(&exe_dir)),
+            Some(exe_dir.join("data"))
+        );
+
+        let env = Some(abs.clone().into_os_string());
+        assert_eq!(
+            resolve(&args(&[]), env.clone(), Some(&exe_dir)),
+            Some(abs.clone())
+        );
+        let flagged = format!("--data-dir={}", exe_dir.join("other").display());
+        assert_eq!(
+            resolve(&args(&[&flagged]), env, Some(&exe_dir)),
+            Some(exe_dir.join("other"))
+        );
+        assert_eq!(
+            resolve(&args(&["--data-dir", "rel"]), None, None),
+            Some(std::env::current_dir().unwrap().join("rel"))
+        );
+
+        std::fs::remove_dir_all(&exe_dir).unwrap();
+    }
+}
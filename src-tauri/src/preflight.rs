@@ -0,0 +1,513 @@
+//! Preflight checks for destructive file operations (rename, trash, move,
+//! copy). Everything here is read-only: it inspects the filesystem and returns
+//! structured warnings the UI can show in a confirmation step, and the
+//! command implementations call the same checks so a blocking problem is
+//! rejected identically whether or not the UI asked first.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{case_rename, long_path};
+use crate::{validate_new_name, AppResult};
+
+/// Longest single path component most filesystems accept (NAME_MAX on
+/// APFS/ext4, 255 UTF-16 units on NTFS).
+const MAX_NAME_LEN: usize = 255;
+
+/// Full-path limit. Windows warns past MAX_PATH instead of blocking because
+/// long-path support may be enabled system-wide.
+#[cfg(target_os = "windows")]
+const MAX_PATH_LEN: usize = 260;
+#[cfg(target_os = "macos")]
+const MAX_PATH_LEN: usize = 1024;
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const MAX_PATH_LEN: usize = 4096;
+
+/// `/etc` and `/var` are symlinks into `/private`, whose `tmp` and
+/// `var/folders` are ordinary temp space, so only its system subtrees count.
+#[cfg(target_os = "macos")]
+const SYSTEM_PREFIXES: &[&str] = &[
+    "/System",
+    "/Library",
+    "/usr",
+    "/bin",
+    "/sbin",
+    "/etc",
+    "/private/etc",
+    "/var/db",
+    "/private/var/db",
+    "/cores",
+];
+/// Environment variables naming Windows system folders, with the folder
+/// under `%SystemDrive%` to fall back on when one is unset.
+#[cfg(target_os = "windows")]
+const SYSTEM_FOLDER_VARS: &[(&str, &str)] = &[
+    ("SystemRoot", "Windows"),
+    ("ProgramFiles", "Program Files"),
+    ("ProgramFiles(x86)", "Program Files (x86)"),
+    ("ProgramData", "ProgramData"),
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const SYSTEM_PREFIXES: &[&str] = &[
+    "/usr", "/bin", "/sbin", "/lib", "/lib64", "/etc", "/boot", "/proc", "/sys", "/dev",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OperationKind {
+    Rename,
+    Trash,
+    Move,
+    Copy,
+}
+
+impl OperationKind {
+    pub(crate) fn parse(kind: &str) -> AppResult<Self> {
+        match kind {
+            "rename" => Ok(Self::Rename),
+            "trash" => Ok(Self::Trash),
+            "move" => Ok(Self::Move),
+            "copy" => Ok(Self::Copy),
+            other => Err(format!("Unknown operation kind: {other}")),
+        }
+    }
+
+    fn removes_source(self) -> bool {
+        !matches!(self, Self::Copy)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationWarning {
+    pub(crate) path: String,
+    /// Stable machine-readable code: `not_found`, `invalid_name`,
+    /// `name_collision`, `permission_denied`, `cross_volume`, `path_too_long`,
+    /// `invalid_destination`, `protected_location`, `system_location`.
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+    /// Blocking warnings mean the operation will be refused; the rest only
+    /// need user confirmation.
+    pub(crate) blocking: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OperationCheckDto {
+    pub(crate) ok: bool,
+    pub(crate) warnings: Vec<OperationWarning>,
+}
+
+/// Locations that must never be renamed, trashed, or moved: the home
+/// directory, the app data directory holding the index, and any ancestor of
+/// either (trashing `~/Library` would take the DB with it).
+pub(crate) struct PreflightContext {
+    pub(crate) protected: Vec<PathBuf>,
+}
+
+impl PreflightContext {
+    pub(crate) fn new(home_dir: &Path, db_path: &Path) -> Self {
+        let mut protected = vec![home_dir.to_path_buf()];
+        if let Some(app_data) = db_path.parent() {
+            protected.push(app_data.to_path_buf());
+        }
+        Self { protected }
+    }
+
+    fn is_protected(&self, path: &Path) -> bool {
+        is_volume_root(path) || self.protected.iter().any(|p| p.starts_with(path))
+    }
+}
+
+fn is_volume_root(path: &Path) -> bool {
+    path.components()
+        .all(|c| matches!(c, Component::RootDir | Component::Prefix(_)))
+}
+
+/// System folders, wherever Windows is installed (not necessarily `C:`).
+#[cfg(target_os = "windows")]
+fn system_prefixes() -> Vec<PathBuf> {
+    let drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+    SYSTEM_FOLDER_VARS
+        .iter()
+        .map(|(var, folder)| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(format!("{drive}\\{folder}")))
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn system_prefixes() -> Vec<PathBuf> {
+    SYSTEM_PREFIXES.iter().map(PathBuf::from).collect()
+}
+
+fn is_system_location(path: &Path) -> bool {
+    // Windows paths compare case-insensitively ("c:\windows" is system too).
+    let fold = |p: &Path| {
+        if cfg!(target_os = "windows") {
+            PathBuf::from(p.to_string_lossy().to_lowercase())
+        } else {
+            p.to_path_buf()
+        }
+    };
+    let path = fold(path);
+    system_prefixes()
+        .iter()
+        .any(|prefix| path.starts_with(fold(prefix)))
+}
+
+fn warning(path: &Path, code: &'static str, message: impl Into<String>, blocking: bool) -> OperationWarning {
+    OperationWarning {
+        path: path.to_string_lossy().to_string(),
+        code,
+        message: message.into(),
+        blocking,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn dir_is_read_only(dir: &Path) -> bool {
+    fs::metadata(dir)
+        .map(|m| m.permissions().readonly())
+        .unwrap_or(false)
+}
+
+/// Always false: a folder's READONLY attribute only marks it as customized
+/// for Explorer and doesn't stop writes, and whether the ACL allows them
+/// takes an actual write to find out, which a preflight must not do. A
+/// denied write still fails the operation itself.
+#[cfg(target_os = "windows")]
+fn dir_is_read_only(_dir: &Path) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn same_volume(a: &Path, b: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let a = fs::metadata(a).ok()?.dev();
+    let b = fs::metadata(b).ok()?.dev();
+    Some(a == b)
+}
+
+#[cfg(not(unix))]
+fn same_volume(a: &Path, b: &Path) -> Option<bool> {
+    let prefix = |p: &Path| match p.components().next() {
+        Some(Component::Prefix(prefix)) => {
+            Some(prefix.as_os_str().to_string_lossy().to_ascii_uppercase())
+        }
+        _ => None,
+    };
+    Some(prefix(a)? == prefix(b)?)
+}
+
+fn check_destination(source: &Path, dest: &Path, warnings: &mut Vec<OperationWarning>) {
+    let name_len = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().len())
+        .unwrap_or(0);
+    if name_len > MAX_NAME_LEN {
+        warnings.push(warning(
+            dest,
+            "path_too_long",
+            format!("Name exceeds {MAX_NAME_LEN} characters."),
+            true,
+        ));
+    }
+    if dest.as_os_str().len() > MAX_PATH_LEN {
+        warnings.push(warning(
+            dest,
+            "path_too_long",
+            format!("Path exceeds {MAX_PATH_LEN} characters."),
+            !cfg!(target_os = "windows"),
+        ));
+    }
+    // On a case-insensitive volume a case-only rename's destination "exists"
+    // as the source itself; only a folder listing that spelling is a clash.
+    let respelling =
+        case_rename::is_case_only_change(source, dest) && !case_rename::listed_as_is(dest);
+    if dest.exists() && !respelling {
+        warnings.push(warning(
+            dest,
+            "name_collision",
+            "A file/folder with the same name already exists.",
+            true,
+        ));
+    }
+}
+
+/// Run every check for `kind` over `paths`. `target` is the new name for
+/// `rename` and the destination directory for `move`/`copy`; `trash` ignores it.
+pub(crate) fn check_operation(
+    kind: OperationKind,
+    paths: &[String],
+    target: Option<&str>,
+    ctx: &PreflightContext,
+) -> AppResult<Vec<OperationWarning>> {
+    let mut warnings = Vec::new();
+
+    let target_dir = match kind {
+        OperationKind::Move | OperationKind::Copy => {
+            let dir = PathBuf::from(
+                target.ok_or_else(|| "Destination folder is required.".to_string())?,
+            );
+            if !dir.is_dir() {
+                warnings.push(warning(
+                    &dir,
+                    "not_found",
+                    "Destination folder does not exist.",
+                    true,
+                ));
+            } else if dir_is_read_only(&dir) {
+                warnings.push(warning(
+                    &dir,
+                    "permission_denied",
+                    "Destination folder is read-only.",
+                    true,
+                ));
+            } else if is_system_location(&dir) {
+                warnings.push(warning(
+                    &dir,
+                    "system_location",
+                    "The destination is inside a system folder.",
+                    false,
+                ));
+            }
+            Some(dir)
+        }
+        _ => None,
+    };
+    let new_name = match kind {
+        OperationKind::Rename => {
+            let raw = target.ok_or_else(|| "New name is required.".to_string())?;
+            match validate_new_name(raw) {
+                Ok(name) => Some(name),
+                Err(message) => {
+                    warnings.push(warning(Path::new(raw), "invalid_name", message, true));
+                    return Ok(warnings);
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let mut seen_dest: HashSet<PathBuf> = HashSet::new();
+    for raw in paths {
+        let source = PathBuf::from(raw);
+        if long_path::symlink_metadata(&source).is_err() {
+            warnings.push(warning(&source, "not_found", "Source file does not exist.", true));
+            continue;
+        }
+
+        if kind.removes_source() {
+            if ctx.is_protected(&source) {
+                warnings.push(warning(
+                    &source,
+                    "protected_location",
+                    "This location is protected and cannot be changed.",
+                    true,
+                ));
+                continue;
+            }
+            if is_system_location(&source) {
+                warnings.push(warning(
+                    &source,
+                    "system_location",
+                    "This item is inside a system folder.",
+                    false,
+                ));
+            }
+            if source.parent().is_some_and(dir_is_read_only) {
+                warnings.push(warning(
+                    &source,
+                    "permission_denied",
+                    "The containing folder is read-only.",
+                    false,
+                ));
+            }
+        }
+
+        let dest = match (kind, &new_name, &target_dir) {
+            (OperationKind::Rename, Some(name), _) => source.parent().map(|p| p.join(name)),
+            (OperationKind::Move | OperationKind::Copy, _, Some(dir)) => {
+                source.file_name().map(|n| dir.join(n))
+            }
+            _ => None,
+        };
+        let Some(dest) = dest else {
+            continue;
+        };
+        if dest == source {
+            continue;
+        }
+        if let Some(dir) = &target_dir {
+            if dir.starts_with(&source) {
+                warnings.push(warning(
+                    &source,
+                    "invalid_destination",
+                    "A folder cannot be moved or copied into itself.",
+                    true,
+                ));
+                continue;
+            }
+            if kind == OperationKind::Move && same_volume(&source, dir) == Some(false) {
+                warnings.push(warning(
+                    &source,
+                    "cross_volume",
+                    "Moving to another volume copies the data and deletes the original.",
+                    false,
+                ));
+            }
+        }
+        if !seen_dest.insert(dest.clone()) {
+            warnings.push(warning(
+                &dest,
+                "name_collision",
+                "Several selected items share this name.",
+                true,
+            ));
+            continue;
+        }
+        check_destination(&source, &dest, &mut warnings);
+    }
+
+    Ok(warnings)
+}
+
+/// Turn the first blocking warning into an error, for commands that run the
+/// preflight themselves before acting.
+pub(crate) fn ensure_allowed(warnings: &[OperationWarning]) -> AppResult<()> {
+    match warnings.iter().find(|w| w.blocking) {
+        Some(w) => Err(w.message.clone()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(case: &str) -> PathBuf {
+        let dir = crate::temp_case_dir(&format!("preflight_{case}"));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn codes(warnings: &[OperationWarning]) -> Vec<&'static str> {
+        warnings.iter().map(|w| w.code).collect()
+    }
+
+    #[test]
+    fn rename_reports_collision_and_missing_source() {
+        let root = temp_dir("rename");
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        let ctx = PreflightContext::new(Path::new("/nonexistent-home"), &root.join("app/index.db"));
+        let a = root.join("a.txt").to_string_lossy().to_string();
+
+        let warnings = check_operation(OperationKind::Rename, std::slice::from_ref(&a), Some("b.txt"), &ctx).unwrap();
+        assert_eq!(codes(&warnings), vec!["name_collision"]);
+        assert!(ensure_allowed(&warnings).is_err());
+
+        let warnings = check_operation(OperationKind::Rename, std::slice::from_ref(&a), Some("a.txt"), &ctx).unwrap();
+        assert!(warnings.is_empty(), "renaming to the same name is a no-op");
+
+        let warnings = check_operation(OperationKind::Rename, std::slice::from_ref(&a), Some("A.txt"), &ctx).unwrap();
+        assert!(warnings.is_empty(), "a case-only rename does not collide with itself");
+
+        let long = "x".repeat(MAX_NAME_LEN + 1);
+        let warnings = check_operation(OperationKind::Rename, std::slice::from_ref(&a), Some(&long), &ctx).unwrap();
+        assert_eq!(codes(&warnings), vec!["path_too_long"]);
+
+        let warnings = check_operation(OperationKind::Rename, &[a], Some(".."), &ctx).unwrap();
+        assert_eq!(codes(&warnings), vec!["invalid_name"]);
+
+        let missing = root.join("gone.txt").to_string_lossy().to_string();
+        let warnings = check_operation(OperationKind::Rename, &[missing], Some("c.txt"), &ctx).unwrap();
+        assert_eq!(codes(&warnings), vec!["not_found"]);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn trash_blocks_home_app_data_and_their_ancestors() {
+        let root = temp_dir("protected");
+        let home = root.join("home");
+        let app_data = home.join("Library/App");
+        fs::create_dir_all(&app_data).unwrap();
+        fs::write(home.join("note.txt"), "n").unwrap();
+        let ctx = PreflightContext::new(&home, &app_data.join("index.db"));
+
+        for path in [&home, &app_data, &home.join("Library"), &root] {
+            let warnings = check_operation(
+                OperationKind::Trash,
+                &[path.to_string_lossy().to_string()],
+                None,
+                &ctx,
+            )
+            .unwrap();
+            assert_eq!(codes(&warnings), vec!["protected_location"], "{}", path.display());
+        }
+
+        let note = home.join("note.txt").to_string_lossy().to_string();
+        let warnings = check_operation(OperationKind::Trash, &[note], None, &ctx).unwrap();
+        assert!(ensure_allowed(&warnings).is_ok());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn move_detects_batch_collisions_and_moving_into_self() {
+        let root = temp_dir("move");
+        let dest = root.join("dest");
+        let one = root.join("one");
+        let two = root.join("two");
+        for dir in [&dest, &one, &two] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(one.join("same.txt"), "1").unwrap();
+        fs::write(two.join("same.txt"), "2").unwrap();
+        let ctx = PreflightContext::new(Path::new("/nonexistent-home"), &root.join("app/index.db"));
+
+        let paths = vec![
+            one.join("same.txt").to_string_lossy().to_string(),
+            two.join("same.txt").to_string_lossy().to_string(),
+        ];
+        let warnings =
+            check_operation(OperationKind::Move, &paths, Some(&dest.to_string_lossy()), &ctx).unwrap();
+        assert_eq!(codes(&warnings), vec!["name_collision"]);
+
+        let warnings = check_operation(
+            OperationKind::Copy,
+            &[one.to_string_lossy().to_string()],
+            Some(&one.join("inner").to_string_lossy()),
+            &ctx,
+        )
+        .unwrap();
+        assert!(codes(&warnings).contains(&"invalid_destination"));
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let warnings = check_operation(OperationKind::Copy, &paths[..1], Some("/usr"), &ctx)
+                .unwrap();
+            assert_eq!(codes(&warnings), vec!["system_location"]);
+        }
+
+        assert!(check_operation(OperationKind::Move, &paths, None, &ctx).is_err());
+        assert!(OperationKind::parse("shred").is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn private_temp_space_is_not_a_system_location() {
+        assert!(!is_system_location(Path::new("/private/tmp/a.txt")));
+        assert!(!is_system_location(Path::new("/private/var/folders/xy/T/a")));
+        assert!(is_system_location(Path::new("/private/etc/hosts")));
+        assert!(is_system_location(Path::new("/private/var/db/receipts")));
+    }
+}
@@ -0,0 +1,276 @@
+//! Inline preview for `get_file_preview`: the head of a text file decoded
+//! with a best-guess encoding, a hex dump of the head of a binary file, or
+//! a small image as a `data:` URL the frontend can use as-is. Only the first
+//! few KB are read, so previewing a huge file stays instant.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+use base64::Engine;
+use serde::Serialize;
+
+use crate::AppResult;
+
+pub(crate) const DEFAULT_PREVIEW_KB: u32 = 64;
+pub(crate) const MAX_PREVIEW_KB: u32 = 1024;
+/// Images up to this size are returned whole; larger ones get a hex dump.
+const IMAGE_PREVIEW_MAX_BYTES: u64 = 4 * 1024 * 1024;
+/// Hex dumps stop here regardless of the requested size: 256 lines.
+const HEX_PREVIEW_MAX_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilePreviewDto {
+    pub path: String,
+    /// "text", "hex" or "image".
+    pub kind: String,
+    pub size: u64,
+    /// True when only the head of the file is included.
+    pub truncated: bool,
+    /// For text: "utf-8", "utf-16le", "utf-16be" or "latin-1".
+    pub encoding: Option<String>,
+    pub text: Option<String>,
+    pub hex: Option<String>,
+    pub mime: Option<String>,
+    /// For images: `data:<mime>;base64,...`.
+    pub data_url: Option<String>,
+}
+
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+pub(crate) fn file_preview(path: &Path, max_kb: u32) -> AppResult<FilePreviewDto> {
+    // Checked before opening: opening a FIFO or device blocks or never ends.
+    let meta = fs::metadata(path).map_err(|e| e.to_string())?;
+    if !meta.file_type().is_file() {
+        return Err(format!("Not a file: {}", path.display()));
+    }
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let size = meta.len();
+    let mut preview = FilePreviewDto {
+        path: path.to_string_lossy().to_string(),
+        kind: String::new(),
+        size,
+        truncated: false,
+        encoding: None,
+        text: None,
+        hex: None,
+        mime: None,
+        data_url: None,
+    };
+
+    if let Some(mime) = image_mime(path).filter(|_| size <= IMAGE_PREVIEW_MAX_BYTES) {
+        let mut bytes = Vec::with_capacity(size as usize);
+        file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        preview.kind = "image".to_string();
+        preview.mime = Some(mime.to_string());
+        preview.data_url = Some(format!(
+            "data:{mime};base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(&bytes)
+        ));
+        return Ok(preview);
+    }
+
+    let limit = max_kb.clamp(1, MAX_PREVIEW_KB) as u64 * 1024;
+    let mut head = Vec::with_capacity(limit.min(size) as usize);
+    file.by_ref()
+        .take(limit)
+        .read_to_end(&mut head)
+        .map_err(|e| e.to_string())?;
+    preview.truncated = size > head.len() as u64;
+
+    match decode_text(&head, preview.truncated) {
+        Some((encoding, text)) => {
+            preview.kind = "text".to_string();
+            preview.encoding = Some(encoding.to_string());
+            preview.text = Some(text);
+        }
+        None => {
+            let shown = head.len().min(HEX_PREVIEW_MAX_BYTES);
+            preview.kind = "hex".to_string();
+            preview.truncated = size > shown as u64;
+            preview.hex = Some(hex_dump(&head[..shown]));
+        }
+    }
+    Ok(preview)
+}
+
+/// Decode `head` as text, or `None` when it looks binary. When the file was
+/// `truncated`, a multi-byte character cut off at the end of the head is
+/// dropped rather than failing the whole decode.
+pub(crate) fn decode_text(head: &[u8], truncated: bool) -> Option<(&'static str, String)> {
+    if let Some(rest) = head.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return Some(("utf-8", String::from_utf8_lossy(rest).into_owned()));
+    }
+    if let Some(rest) = head.strip_prefix(&[0xFF, 0xFE]) {
+        return Some(("utf-16le", decode_utf16(rest, u16::from_le_bytes)));
+    }
+    if let Some(rest) = head.strip_prefix(&[0xFE, 0xFF]) {
+        return Some(("utf-16be", decode_utf16(rest, u16::from_be_bytes)));
+    }
+    // BOM-less UTF-16 (common for Windows logs): mostly-NUL odd or even bytes.
+    if head.len() >= 16 {
+        let zeros_at = |parity: usize| {
+            head.iter()
+                .skip(parity)
+                .step_by(2)
+                .filter(|&&b| b == 0)
+                .count()
+        };
+        let half = head.len() / 2;
+        let (even, odd) = (zeros_at(0), zeros_at(1));
+        if odd * 10 >= half * 9 && even == 0 {
+            return Some(("utf-16le", decode_utf16(head, u16::from_le_bytes)));
+        }
+        if even * 10 >= half * 9 && odd == 0 {
+            return Some(("utf-16be", decode_utf16(head, u16::from_be_bytes)));
+        }
+    }
+    if looks_binary(head) {
+        return None;
+    }
+    match std::str::from_utf8(head) {
+        Ok(text) => Some(("utf-8", text.to_string())),
+        Err(e) if truncated && e.error_len().is_none() => Some((
+            "utf-8",
+            String::from_utf8_lossy(&head[..e.valid_up_to()]).into_owned(),
+        )),
+        // Not UTF-8 but no control bytes: treat as a single-byte code page.
+        Err(_) => Some(("latin-1", head.iter().map(|&b| b as char).collect())),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| to_unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+/// Any NUL, or more than 10% control characters other than whitespace.
+fn looks_binary(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return true;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C))
+        .count();
+    control * 10 > head.len()
+}
+
+/// `xxd`-style dump: offset, 16 hex bytes, printable ASCII.
+pub(crate) fn hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() / 16 * 78 + 78);
+    for (line, chunk) in bytes.chunks(16).enumerate() {
+        out.push_str(&format!("{:08x} ", line * 16));
+        for i in 0..16 {
+            if i == 8 {
+                out.push(' ');
+            }
+            match chunk.get(i) {
+                Some(b) => out.push_str(&format!(" {b:02x}")),
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str("  |");
+        out.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_encodings_and_binary_content() {
+        assert_eq!(
+            decode_text("héllo\n".as_bytes(), false),
+            Some(("utf-8", "héllo\n".to_string()))
+        );
+        // "é" cut in half at the end of the head.
+        assert_eq!(
+            decode_text(&"abé".as_bytes()[..3], true),
+            Some(("utf-8", "ab".to_string()))
+        );
+        assert_eq!(
+            decode_text(&[0xFF, 0xFE, b'h', 0, b'i', 0], false),
+            Some(("utf-16le", "hi".to_string()))
+        );
+        let bomless: Vec<u8> = "plain utf16 log"
+            .encode_utf16()
+            .flat_map(|u| u.to_le_bytes())
+            .collect();
+        assert_eq!(
+            decode_text(&bomless, false),
+            Some(("utf-16le", "plain utf16 log".to_string()))
+        );
+        assert_eq!(
+            decode_text(&[b'c', b'a', b'f', 0xE9], false),
+            Some(("latin-1", "café".to_string()))
+        );
+        assert_eq!(
+            decode_text(&[0x7F, b'E', b'L', b'F', 2, 1, 1, 0], false),
+            None
+        );
+
+        assert_eq!(
+            hex_dump(b"Hello, world!\x00\x01"),
+            "00000000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 00 01     |Hello, world!..|\n"
+        );
+    }
+
+    #[test]
+    fn previews_text_head_binary_and_small_images() {
+        let dir = crate::temp_case_dir("file_preview");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let text_path = dir.join("notes.txt");
+        std::fs::write(&text_path, "line\n".repeat(1000)).unwrap();
+        let text = file_preview(&text_path, 1).unwrap();
+        assert_eq!(text.kind, "text");
+        assert!(text.truncated);
+        assert_eq!(text.text.unwrap().len(), 1024);
+
+        let bin_path = dir.join("blob.bin");
+        std::fs::write(&bin_path, [0u8, 1, 2, 3]).unwrap();
+        let bin = file_preview(&bin_path, DEFAULT_PREVIEW_KB).unwrap();
+        assert_eq!((bin.kind.as_str(), bin.truncated), ("hex", false));
+        assert!(bin.hex.unwrap().starts_with("00000000  00 01 02 03"));
+
+        let png_path = dir.join("pixel.png");
+        std::fs::write(&png_path, b"\x89PNG\r\n\x1a\n").unwrap();
+        let png = file_preview(&png_path, DEFAULT_PREVIEW_KB).unwrap();
+        assert_eq!(png.kind, "image");
+        assert_eq!(png.mime.as_deref(), Some("image/png"));
+        assert_eq!(
+            png.data_url.as_deref(),
+            Some("data:image/png;base64,iVBORw0KGgo=")
+        );
+
+        assert!(file_preview(&dir, DEFAULT_PREVIEW_KB).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
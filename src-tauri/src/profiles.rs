@@ -0,0 +1,243 @@
+//! Named index profiles, e.g. "Work" scanning only `~/Projects` beside the
+//! default whole-home index. Each profile has its own directory under
+//! `profiles/` in the app data dir holding its DB and sidecars (`.pathignore`,
+//! `.pathindexing`, `.pathpriority`, `index_tuning.json`, …) plus a
+//! `profile.json` naming it; the default profile is the app data dir itself,
+//! so existing installs keep their index. `profiles.json` records which one
+//! the app opens.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{AppResult, AppState};
+
+pub(crate) const DEFAULT_PROFILE_ID: &str = "default";
+const DEFAULT_PROFILE_NAME: &str = "Default";
+const PROFILES_DIR_NAME: &str = "profiles";
+const ACTIVE_FILE_NAME: &str = "profiles.json";
+const PROFILE_FILE_NAME: &str = "profile.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ActiveProfile {
+    active: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileConfig {
+    name: String,
+    /// Replaces the default scan root (home, or `C:\` on Windows).
+    #[serde(default)]
+    root: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDto {
+    pub id: String,
+    pub name: String,
+    pub root: Option<String>,
+    pub active: bool,
+}
+
+fn profile_dir(app_data_dir: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE_ID {
+        app_data_dir.to_path_buf()
+    } else {
+        app_data_dir.join(PROFILES_DIR_NAME).join(id)
+    }
+}
+
+fn read_profile(dir: &Path) -> Option<ProfileConfig> {
+    let text = fs::read_to_string(dir.join(PROFILE_FILE_NAME)).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// Directory-safe id for a profile name: lowercase ASCII alphanumerics with
+/// runs of anything else collapsed to `-`.
+fn profile_id(name: &str) -> String {
+    let mut id = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            id.push(c.to_ascii_lowercase());
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// Id of the profile the app opens; an unknown or deleted one falls back to
+/// the default.
+pub(crate) fn active_profile_id(app_data_dir: &Path) -> String {
+    fs::read_to_string(app_data_dir.join(ACTIVE_FILE_NAME))
+        .ok()
+        .and_then(|text| serde_json::from_str::<ActiveProfile>(&text).ok())
+        .and_then(|active| active.active)
+        .filter(|id| id != DEFAULT_PROFILE_ID)
+        .filter(|id| read_profile(&profile_dir(app_data_dir, id)).is_some())
+        .unwrap_or_else(|| DEFAULT_PROFILE_ID.to_string())
+}
+
+/// Where the active profile keeps its DB and sidecars.
+pub(crate) fn active_data_dir(app_data_dir: &Path) -> PathBuf {
+    profile_dir(app_data_dir, &active_profile_id(app_data_dir))
+}
+
+/// Adjust a state built from a profile's directory: point the scan at the
+/// profile's root and keep the whole app data dir, other profiles' DBs
+/// included, out of the index. A no-op for the default profile.
+pub(crate) fn apply_to_state(state: &mut AppState) {
+    let data_dir = state.data_dir.as_path();
+    let Some(profile) = read_profile(data_dir) else {
+        return;
+    };
+    if let Some(app_data_dir) = data_dir.parent().and_then(Path::parent) {
+        let mut ignores = state.path_ignores.as_ref().clone();
+        if !ignores.iter().any(|r| r == app_data_dir) {
+            ignores.push(app_data_dir.to_path_buf());
+        }
+        state.path_ignores = Arc::new(ignores);
+    }
+    if let Some(root) = profile.root {
+        state.scan_root = root;
+    }
+}
+
+pub(crate) fn list(app_data_dir: &Path) -> Vec<ProfileDto> {
+    let active = active_profile_id(app_data_dir);
+    let mut profiles = vec![ProfileDto {
+        id: DEFAULT_PROFILE_ID.to_string(),
+        name: DEFAULT_PROFILE_NAME.to_string(),
+        root: None,
+        active: active == DEFAULT_PROFILE_ID,
+    }];
+    let mut named: Vec<ProfileDto> = fs::read_dir(app_data_dir.join(PROFILES_DIR_NAME))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let id = entry.file_name().to_string_lossy().to_string();
+            let profile = read_profile(&entry.path())?;
+            Some(ProfileDto {
+                active: active == id,
+                id,
+                name: profile.name,
+                root: profile.root.map(|r| r.to_string_lossy().to_string()),
+            })
+        })
+        .collect();
+    named.sort_by_key(|p| p.name.to_lowercase());
+    profiles.extend(named);
+    profiles
+}
+
+/// Create an empty profile; it is indexed from scratch when first opened.
+/// `root` must be an existing directory.
+pub(crate) fn create(app_data_dir: &Path, name: &str, root: Option<&str>) -> AppResult<ProfileDto> {
+    let name = name.trim();
+    let id = profile_id(name);
+    if id.is_empty() || id == DEFAULT_PROFILE_ID {
+        return Err(format!("Invalid profile name: {name:?}"));
+    }
+    let dir = profile_dir(app_data_dir, &id);
+    if dir.exists() {
+        return Err(format!("A profile named {name:?} already exists"));
+    }
+    let root = match root.map(str::trim).filter(|r| !r.is_empty()) {
+        Some(root) => {
+            let path = PathBuf::from(root);
+            if !path.is_absolute() || !path.is_dir() {
+                return Err(format!("Profile root is not a directory: {root}"));
+            }
+            Some(path)
+        }
+        None => None,
+    };
+    let profile = ProfileConfig {
+        name: name.to_string(),
+        root,
+    };
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let text = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    fs::write(dir.join(PROFILE_FILE_NAME), text).map_err(|e| e.to_string())?;
+    Ok(ProfileDto {
+        id,
+        name: profile.name,
+        root: profile.root.map(|r| r.to_string_lossy().to_string()),
+        active: false,
+    })
+}
+
+/// Delete a profile along with its index. The default and the active
+/// profile can't be deleted.
+pub(crate) fn delete(app_data_dir: &Path, id: &str) -> AppResult<()> {
+    if id == DEFAULT_PROFILE_ID {
+        return Err("The default profile can't be deleted".to_string());
+    }
+    if active_profile_id(app_data_dir) == id {
+        return Err("Switch to another profile before deleting this one".to_string());
+    }
+    let dir = profile_dir(app_data_dir, id);
+    if profile_id(id) != id || read_profile(&dir).is_none() {
+        return Err(format!("No such profile: {id}"));
+    }
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())
+}
+
+/// Make `id` the profile the app opens from now on.
+pub(crate) fn set_active(app_data_dir: &Path, id: &str) -> AppResult<()> {
+    if id != DEFAULT_PROFILE_ID
+        && (profile_id(id) != id || read_profile(&profile_dir(app_data_dir, id)).is_none())
+    {
+        return Err(format!("No such profile: {id}"));
+    }
+    let active = ActiveProfile {
+        active: Some(id.to_string()),
+    };
+    let text = serde_json::to_string_pretty(&active).map_err(|e| e.to_string())?;
+    fs::write(app_data_dir.join(ACTIVE_FILE_NAME), text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profiles_get_their_own_directory_and_switch_by_id() {
+        let dir = crate::temp_case_dir("profiles");
+        fs::create_dir_all(dir.join("projects")).unwrap();
+
+        assert_eq!(active_data_dir(&dir), dir);
+        let root = dir.join("projects").to_string_lossy().to_string();
+        let work = create(&dir, " Work stuff! ", Some(&root)).unwrap();
+        assert_eq!(work.id, "work-stuff");
+        assert!(create(&dir, "work STUFF", None).is_err());
+        assert!(create(&dir, "Default", None).is_err());
+        assert!(create(&dir, "Other", Some("relative/dir")).is_err());
+
+        set_active(&dir, &work.id).unwrap();
+        assert_eq!(
+            active_data_dir(&dir),
+            dir.join("profiles").join("work-stuff")
+        );
+        let listed = list(&dir);
+        assert_eq!(listed.len(), 2);
+        assert!(!listed[0].active && listed[1].active);
+        assert_eq!(listed[1].root.as_deref(), Some(root.as_str()));
+        assert!(delete(&dir, &work.id).is_err());
+        assert!(set_active(&dir, "missing").is_err());
+
+        set_active(&dir, DEFAULT_PROFILE_ID).unwrap();
+        delete(&dir, &work.id).unwrap();
+        assert_eq!(list(&dir).len(), 1);
+        assert!(delete(&dir, "../projects").is_err());
+        assert!(dir.join("projects").is_dir());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+//! `get_properties`: the "Get Info" bundle for one path — what a symlink
+//! points to, every hard link to the same file, permissions and an ACL
+//! summary, allocated blocks, and the platform extras (quarantine flag and
+//! extended attributes on macOS, alternate data streams on Windows). Hard
+//! links are found through the index on Unix (same size and mtime, then
+//! same device and inode), and through NTFS directly on Windows.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::long_path;
+use crate::AppResult;
+
+/// Hard link candidates checked from the index; a size+mtime bucket larger
+/// than this is almost certainly unrelated files.
+#[cfg(unix)]
+const MAX_HARDLINK_CANDIDATES: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamDto {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PropertiesDto {
+    pub path: String,
+    /// "file", "dir", "symlink" or "other".
+    pub kind: String,
+    pub size: u64,
+    /// Bytes actually allocated on disk (Unix `st_blocks` × 512); smaller
+    /// than `size` for sparse or compressed files.
+    pub allocated_bytes: Option<u64>,
+    pub readonly: bool,
+    /// `ls -l` style mode string, e.g. "-rw-r--r--" (Unix only).
+    pub mode: Option<String>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub created: Option<i64>,
+    pub modified: Option<i64>,
+    pub accessed: Option<i64>,
+    /// The link text as stored, for symlinks.
+    pub symlink_target: Option<String>,
+    /// The fully resolved target, when it exists.
+    pub resolved_target: Option<String>,
+    pub broken_link: bool,
+    pub hardlink_count: Option<u64>,
+    /// Every path known to name the same file, including `path`.
+    pub hardlinks: Vec<String>,
+    /// One line per ACL entry; empty when only plain permissions apply.
+    pub acl: Vec<String>,
+    /// macOS: Gatekeeper quarantine flag and the app that downloaded it.
+    pub quarantined: Option<bool>,
+    pub quarantine_agent: Option<String>,
+    /// macOS extended attribute names.
+    pub xattrs: Vec<String>,
+    /// Windows alternate data streams.
+    pub alternate_streams: Vec<StreamDto>,
+}
+
+fn epoch_secs(time: std::io::Result<SystemTime>) -> Option<i64> {
+    time.ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+#[cfg(unix)]
+pub(crate) fn mode_string(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o140000 => 's',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        _ => '-',
+    };
+    let mut out = String::with_capacity(10);
+    out.push(kind);
+    let bits = [
+        (0o400, 'r'),
+        (0o200, 'w'),
+        (0o100, 'x'),
+        (0o040, 'r'),
+        (0o020, 'w'),
+        (0o010, 'x'),
+        (0o004, 'r'),
+        (0o002, 'w'),
+        (0o001, 'x'),
+    ];
+    for (index, (bit, ch)) in bits.iter().enumerate() {
+        let special = match index {
+            2 if mode & 0o4000 != 0 => Some('s'),
+            5 if mode & 0o2000 != 0 => Some('s'),
+            8 if mode & 0o1000 != 0 => Some('t'),
+            _ => None,
+        };
+        let set = mode & bit != 0;
+        out.push(match (special, set) {
+            (Some(s), true) => s,
+            (Some(s), false) => s.to_ascii_uppercase(),
+            (None, true) => *ch,
+            (None, false) => '-',
+        });
+    }
+    out
+}
+
+/// Indexed paths naming the same inode as `path`.
+#[cfg(unix)]
+fn indexed_hardlinks(
+    conn: &Connection,
+    path: &Path,
+    meta: &fs::Metadata,
+) -> AppResult<Vec<String>> {
+    use rusqlite::params;
+    use std::os::unix::fs::MetadataExt;
+
+    let mtime = epoch_secs(meta.modified());
+    let mut stmt = conn
+        .prepare("SELECT path FROM entries WHERE mtime = ?1 AND size = ?2 AND is_dir = 0 LIMIT ?3")
+        .map_err(|e| e.to_string())?;
+    let candidates = stmt
+        .query_map(
+            params![mtime, meta.len() as i64, MAX_HARDLINK_CANDIDATES as i64],
+            |row| row.get::<_, String>(0),
+        )
+        .map_err(|e| e.to_string())?
+        .filter_map(Result::ok)
+        .collect::<Vec<_>>();
+    let mut links: Vec<String> = candidates
+        .into_iter()
+        .filter(|candidate| {
+            long_path::symlink_metadata(candidate)
+                .is_ok_and(|m| m.dev() == meta.dev() && m.ino() == meta.ino())
+        })
+        .collect();
+    let own = path.to_string_lossy().to_string();
+    if !links.contains(&own) {
+        links.push(own);
+    }
+    links.sort();
+    Ok(links)
+}
+
+/// ACL entries beyond the plain permission bits, from the platform tool
+/// that prints them (`ls -le`, `getfacl`, `icacls`).
+fn acl_summary(path: &Path) -> Vec<String> {
+    #[cfg(target_os = "macos")]
+    let output = Command::new("ls").arg("-led").arg(path).output();
+    #[cfg(target_os = "windows")]
+    let output = {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("icacls")
+            .arg(path)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let output = Command::new("getfacl")
+        .args(["--omit-header", "--absolute-names", "--skip-base"])
+        .arg(path)
+        .output();
+
+    let Ok(output) = output else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    parse_acl_output(&String::from_utf8_lossy(&output.stdout), path)
+}
+
+fn parse_acl_output(text: &str, path: &Path) -> Vec<String> {
+    if cfg!(target_os = "windows") {
+        // "<path> ENTRY\n         ENTRY\n\nSuccessfully processed ..."
+        let path_str = path.to_string_lossy();
+        return text
+            .lines()
+            .take_while(|line| !line.trim().is_empty())
+            .map(|line| line.strip_prefix(path_str.as_ref()).unwrap_or(line).trim())
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    // `ls -led` prints the entry line first, then " 0: user:x allow read".
+    let skip = usize::from(cfg!(target_os = "macos"));
+    text.lines()
+        .skip(skip)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) fn properties(path: &Path, conn: Option<&Connection>) -> AppResult<PropertiesDto> {
+    let meta = long_path::symlink_metadata(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let file_type = meta.file_type();
+    let mut dto = PropertiesDto {
+        path: path.to_string_lossy().to_string(),
+        kind: if file_type.is_symlink() {
+            "symlink"
+        } else if file_type.is_dir() {
+            "dir"
+        } else if file_type.is_file() {
+            "file"
+        } else {
+            "other"
+        }
+        .to_string(),
+        size: meta.len(),
+        readonly: meta.permissions().readonly(),
+        created: epoch_secs(meta.created()),
+        modified: epoch_secs(meta.modified()),
+        accessed: epoch_secs(meta.accessed()),
+        ..PropertiesDto::default()
+    };
+
+    if file_type.is_symlink() {
+        dto.symlink_target = fs::read_link(path)
+            .ok()
+            .map(|t| t.to_string_lossy().to_string());
+        match fs::canonicalize(path) {
+            Ok(resolved) => {
+                // Windows canonical paths are verbatim (`\\?\C:\...`).
+                let resolved = long_path::strip(&resolved).to_string_lossy().to_string();
+                dto.resolved_target = Some(resolved);
+            }
+            Err(_) => dto.broken_link = true,
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        dto.allocated_bytes = Some(meta.blocks() * 512);
+        dto.mode = Some(mode_string(meta.mode()));
+        dto.uid = Some(meta.uid());
+        dto.gid = Some(meta.gid());
+        dto.hardlink_count = Some(meta.nlink());
+        if meta.is_file() && meta.nlink() > 1 {
+            if let Some(conn) = conn {
+                dto.hardlinks = indexed_hardlinks(conn, path, &meta)?;
+            }
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = conn;
+        if meta.is_file() {
+            dto.hardlinks = crate::win::file_props::hardlink_paths(path);
+            dto.hardlink_count = Some(dto.hardlinks.len().max(1) as u64);
+        }
+        if !file_type.is_symlink() {
+            dto.alternate_streams = crate::win::file_props::alternate_streams(path)
+                .into_iter()
+                .map(|(name, size)| StreamDto { name, size })
+                .collect();
+        }
+    }
+    #[cfg(target_os = "macos")]
+    {
+        use crate::mac::xattr;
+        dto.xattrs = xattr::list(path);
+        let quarantine = xattr::read(path, xattr::QUARANTINE_ATTR);
+        dto.quarantined = Some(quarantine.is_some());
+        // "flags;timestamp;agent;uuid"
+        dto.quarantine_agent = quarantine.and_then(|value| {
+            String::from_utf8_lossy(&value)
+                .split(';')
+                .nth(2)
+                .filter(|agent| !agent.is_empty())
+                .map(str::to_string)
+        });
+    }
+
+    dto.acl = acl_summary(path);
+    Ok(dto)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_symlinks_hardlinks_and_mode() {
+        let root = crate::temp_case_dir("properties");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("a.txt"), b"hello").unwrap();
+        fs::hard_link(root.join("a.txt"), root.join("sub").join("b.txt")).unwrap();
+        std::os::unix::fs::symlink(root.join("a.txt"), root.join("link")).unwrap();
+        std::os::unix::fs::symlink(root.join("gone"), root.join("dangling")).unwrap();
+
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let mut conn = crate::db_connection(&db_path).unwrap();
+        crate::rescan::rescan_subtree(&mut conn, &root, &[], &[]).unwrap();
+
+        let file = properties(&root.join("a.txt"), Some(&conn)).unwrap();
+        assert_eq!((file.kind.as_str(), file.size), ("file", 5));
+        assert_eq!(file.hardlink_count, Some(2));
+        let mut expected = vec![
+            root.join("a.txt").to_string_lossy().to_string(),
+            root.join("sub").join("b.txt").to_string_lossy().to_string(),
+        ];
+        expected.sort();
+        assert_eq!(file.hardlinks, expected);
+        assert!(file.mode.as_deref().is_some_and(|m| m.starts_with('-')));
+
+        let link = properties(&root.join("link"), Some(&conn)).unwrap();
+        assert_eq!(link.kind, "symlink");
+        assert_eq!(
+            link.symlink_target.as_deref(),
+            Some(root.join("a.txt").to_string_lossy().as_ref())
+        );
+        assert!(link.resolved_target.is_some() && !link.broken_link);
+        assert!(
+            properties(&root.join("dangling"), None)
+                .unwrap()
+                .broken_link
+        );
+
+        assert_eq!(mode_string(0o104755), "-rwsr-xr-x");
+        assert_eq!(mode_string(0o041777), "drwxrwxrwt");
+        assert_eq!(mode_string(0o100640), "-rw-r-----");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,105 @@
+//! Put-back records for `restore_from_trash`. The Windows and Linux trashes
+//! remember each item's original path themselves; the macOS Trash keeps that
+//! in Finder's private metadata, so `move_to_trash` records where each item
+//! landed here instead. Like saved searches, the table survives index resets.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::undo::TrashedItem;
+use crate::{now_epoch, AppResult};
+
+pub(crate) const CREATE_TRASH_PUTBACK_SQL: &str = "\
+CREATE TABLE IF NOT EXISTS trash_putback (
+    original TEXT PRIMARY KEY,
+    trashed_as TEXT NOT NULL,
+    trashed_at INTEGER NOT NULL
+);";
+
+/// Remember the Trash location of every item that has one. Trashing the
+/// same path again replaces the older record.
+pub(crate) fn record(conn: &Connection, items: &[TrashedItem]) -> AppResult<()> {
+    let now = now_epoch();
+    for item in items {
+        let Some(trashed_as) = &item.trashed_as else {
+            continue;
+        };
+        conn.execute(
+            "INSERT INTO trash_putback(original, trashed_as, trashed_at) VALUES(?1, ?2, ?3)
+             ON CONFLICT(original) DO UPDATE SET
+               trashed_as = excluded.trashed_as,
+               trashed_at = excluded.trashed_at",
+            params![item.original, trashed_as.to_string_lossy(), now],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Trash locations recorded for `originals`; paths without a record are
+/// left out.
+pub(crate) fn lookup(
+    conn: &Connection,
+    originals: &[String],
+) -> AppResult<HashMap<String, PathBuf>> {
+    let mut stmt = conn
+        .prepare("SELECT trashed_as FROM trash_putback WHERE original = ?1")
+        .map_err(|e| e.to_string())?;
+    let mut found = HashMap::new();
+    for original in originals {
+        let trashed_as: Option<String> = stmt
+            .query_row(params![original], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if let Some(trashed_as) = trashed_as {
+            found.insert(original.clone(), PathBuf::from(trashed_as));
+        }
+    }
+    Ok(found)
+}
+
+/// Drop the records of items that are back in place.
+pub(crate) fn forget(conn: &Connection, originals: &[String]) -> AppResult<()> {
+    for original in originals {
+        conn.execute(
+            "DELETE FROM trash_putback WHERE original = ?1",
+            params![original],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_latest_location_and_forgets_restored() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CREATE_TRASH_PUTBACK_SQL).unwrap();
+        let item = |original: &str, trashed_as: Option<&str>| TrashedItem {
+            original: original.to_string(),
+            trashed_as: trashed_as.map(PathBuf::from),
+        };
+        record(
+            &conn,
+            &[
+                item("/u/a.txt", Some("/u/.Trash/a.txt")),
+                item("/u/b.txt", None),
+            ],
+        )
+        .unwrap();
+        record(&conn, &[item("/u/a.txt", Some("/u/.Trash/a 10.12.01.txt"))]).unwrap();
+
+        let originals = vec!["/u/a.txt".to_string(), "/u/b.txt".to_string()];
+        let found = lookup(&conn, &originals).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found["/u/a.txt"], PathBuf::from("/u/.Trash/a 10.12.01.txt"));
+
+        forget(&conn, &originals[..1]).unwrap();
+        assert!(lookup(&conn, &originals).unwrap().is_empty());
+    }
+}
@@ -1,11 +1,17 @@
+use crate::glob_class::{self, CharClass};
+use crate::name_norm;
+
 #[derive(Debug, PartialEq)]
 pub enum SearchMode {
     Empty,
     NameSearch {
         name_like: String,
     },
+    /// `name_like` reads a `[...]` class as any one character; `name_glob`,
+    /// set only when the pattern has one, is the exact test over `name_norm`.
     GlobName {
         name_like: String,
+        name_glob: Option<String>,
     },
     ExtSearch {
         ext: String,
@@ -14,8 +20,14 @@ pub enum SearchMode {
     PathSearch {
         path_like: String,
         name_like: String,
+        name_glob: Option<String>,
         dir_hint: String,
     },
+    /// `path:term` — `term` anywhere in the full path text, across directory
+    /// components as well as the name.
+    PathContains {
+        term: String,
+    },
 }
 
 impl SearchMode {
@@ -28,6 +40,7 @@ impl SearchMode {
             SearchMode::GlobName { .. } => "glob",
             SearchMode::ExtSearch { .. } => "ext",
             SearchMode::PathSearch { .. } => "path",
+            SearchMode::PathContains { .. } => "path_contains",
         }
     }
 }
@@ -40,24 +53,42 @@ pub fn escape_like(value: &str) -> String {
 }
 
 fn has_glob_chars(s: &str) -> bool {
-    s.contains('*') || s.contains('?')
+    s.contains('*') || s.contains('?') || glob_class::has_class(s)
 }
 
+/// `pattern` as LIKE. LIKE has no character classes, so a `[...]` becomes
+/// `_`; callers pair it with `class_glob` for the exact test.
 pub fn glob_to_like(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
     let mut out = String::with_capacity(pattern.len() + 8);
-    for ch in pattern.chars() {
-        match ch {
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '[' {
+            if let Some((_, end)) = CharClass::parse(&chars, i) {
+                out.push('_');
+                i = end;
+                continue;
+            }
+        }
+        match chars[i] {
             '*' => out.push('%'),
             '?' => out.push('_'),
             '%' => out.push_str("\\%"),
             '_' => out.push_str("\\_"),
             '\\' => out.push_str("\\\\"),
-            _ => out.push(ch),
+            ch => out.push(ch),
         }
+        i += 1;
     }
     out
 }
 
+/// The SQLite `GLOB` over `name_norm` a name pattern needs beyond its LIKE
+/// form: `Some` only when it has a `[...]` class.
+fn class_glob(pattern: &str) -> Option<String> {
+    glob_class::has_class(pattern).then(|| glob_class::to_sqlite_glob(pattern))
+}
+
 pub fn last_path_separator(s: &str) -> Option<usize> {
     match (s.rfind('/'), s.rfind('\\')) {
         (Some(a), Some(b)) => Some(a.max(b)),
@@ -67,6 +98,11 @@ pub fn last_path_separator(s: &str) -> Option<usize> {
     }
 }
 
+fn strip_prefix_ci<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &s[prefix.len()..])
+}
+
 pub fn parse_query(query: &str) -> SearchMode {
     let trimmed = query.trim();
 
@@ -74,6 +110,15 @@ pub fn parse_query(query: &str) -> SearchMode {
         return SearchMode::Empty;
     }
 
+    if let Some(term) = strip_prefix_ci(trimmed, "path:") {
+        let term = term.trim();
+        if !term.is_empty() {
+            return SearchMode::PathContains {
+                term: term.to_string(),
+            };
+        }
+    }
+
     if let Some(last_sep) = last_path_separator(trimmed) {
         let dir_part_raw = trimmed[..last_sep].trim();
         let name_part = trimmed[last_sep + 1..].trim();
@@ -89,12 +134,14 @@ pub fn parse_query(query: &str) -> SearchMode {
             format!("%{}/%", escape_like(&dir_part))
         };
 
+        // Folded so it can be matched against `name_norm`.
+        let name_folded = name_norm::fold(name_part);
         let name_like = if name_part.is_empty() {
             "%".to_string()
         } else if has_glob_chars(name_part) {
-            glob_to_like(name_part)
+            glob_to_like(&name_folded)
         } else {
-            format!("%{}%", escape_like(name_part))
+            format!("%{}%", escape_like(&name_folded))
         };
 
         let dir_hint = {
@@ -114,6 +161,7 @@ pub fn parse_query(query: &str) -> SearchMode {
         return SearchMode::PathSearch {
             path_like,
             name_like,
+            name_glob: class_glob(name_part),
             dir_hint,
         };
     }
@@ -133,12 +181,14 @@ pub fn parse_query(query: &str) -> SearchMode {
 
     if has_glob_chars(trimmed) {
         return SearchMode::GlobName {
-            name_like: glob_to_like(trimmed),
+            name_like: glob_to_like(&name_norm::fold(trimmed)),
+            name_glob: class_glob(trimmed),
         };
     }
 
+    // Folded so it can be matched against `name_norm`.
     SearchMode::NameSearch {
-        name_like: format!("%{}%", escape_like(trimmed)),
+        name_like: format!("%{}%", escape_like(&name_norm::fold(trimmed))),
     }
 }
 
@@ -176,7 +226,37 @@ mod tests {
         assert_eq!(
             parse_query("spec?.md"),
             SearchMode::GlobName {
-                name_like: "spec_.md".to_string()
+                name_like: "spec_.md".to_string(),
+                name_glob: None,
+            }
+        );
+    }
+
+    #[test]
+    fn glob_class() {
+        assert_eq!(
+            parse_query("IMG_[0-9]*.JPG"),
+            SearchMode::GlobName {
+                name_like: "img\\__%.jpg".to_string(),
+                name_glob: Some("img_[0-9]*.jpg".to_string()),
+            }
+        );
+        match parse_query("src/*.[ch]") {
+            SearchMode::PathSearch {
+                name_like,
+                name_glob,
+                ..
+            } => {
+                assert_eq!(name_like, "%._");
+                assert_eq!(name_glob.as_deref(), Some("*.[ch]"));
+            }
+            other => panic!("expected PathSearch, got {:?}", other),
+        }
+        // An unclosed `[` is still a plain name.
+        assert_eq!(
+            parse_query("notes [draft"),
+            SearchMode::NameSearch {
+                name_like: "%notes [draft%".to_string()
             }
         );
     }
@@ -406,6 +486,7 @@ mod tests {
                 path_like,
                 name_like,
                 dir_hint,
+                ..
             } => {
                 assert_eq!(path_like, "%desktop/%");
                 assert_eq!(name_like, "%");
@@ -422,6 +503,7 @@ mod tests {
                 path_like,
                 name_like,
                 dir_hint,
+                ..
             } => {
                 assert_eq!(path_like, "%desktop/%");
                 assert_eq!(name_like, "%.png");
@@ -431,6 +513,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn path_contains_filter() {
+        assert_eq!(
+            parse_query("path:2023-taxes"),
+            SearchMode::PathContains {
+                term: "2023-taxes".to_string(),
+            }
+        );
+        assert_eq!(
+            parse_query("PATH: Documents/2023 "),
+            SearchMode::PathContains {
+                term: "Documents/2023".to_string(),
+            }
+        );
+        // Bare prefix falls through to a name search for the literal text
+        assert_eq!(
+            parse_query("path:"),
+            SearchMode::NameSearch {
+                name_like: "%path:%".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn ext_search_not_for_backslash_path() {
         assert!(matches!(
@@ -438,4 +543,29 @@ mod tests {
             SearchMode::PathSearch { .. }
         ));
     }
+
+    #[test]
+    fn glob_and_path_names_fold_to_match_name_norm() {
+        let nfc = "Caf\u{e9}*";
+        let nfd = "Cafe\u{301}*";
+        for query in [nfc, nfd] {
+            assert_eq!(
+                parse_query(query),
+                SearchMode::GlobName {
+                    name_like: "caf\u{e9}%".to_string(),
+                    name_glob: None,
+                }
+            );
+        }
+        match parse_query(&format!("docs/{nfd}")) {
+            SearchMode::PathSearch { name_like, .. } => assert_eq!(name_like, "caf\u{e9}%"),
+            other => panic!("expected PathSearch, got {:?}", other),
+        }
+        match parse_query("docs/R\u{c9}SUM\u{c9}") {
+            SearchMode::PathSearch { name_like, .. } => {
+                assert_eq!(name_like, "%r\u{e9}sum\u{e9}%")
+            }
+            other => panic!("expected PathSearch, got {:?}", other),
+        }
+    }
 }
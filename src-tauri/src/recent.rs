@@ -0,0 +1,164 @@
+//! `recent_files`: the most recently modified files, newest first, walked
+//! off `idx_entries_mtime` rather than run through the search pipeline.
+//! Unlike a search it drops churn nobody means by "recent" — caches, logs,
+//! temp and lock files — which the indexer keeps because they can still be
+//! searched for by name. The index has no creation time; a new file's mtime
+//! is its creation time, so new files show up here too.
+
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+
+use crate::{row_to_entry, subtree_range_bounds, AppResult, EntryDto};
+
+pub(crate) const DEFAULT_RECENT_LIMIT: usize = 200;
+pub(crate) const MAX_RECENT_LIMIT: usize = 5_000;
+pub(crate) const DEFAULT_RECENT_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+/// Rows examined per call at most, so a window full of noise can't turn
+/// into a scan of the whole index.
+const MAX_SCANNED_ROWS: usize = 200_000;
+
+/// Folder names (case-insensitive) whose contents are never "recent".
+const NOISE_DIR_NAMES: &[&str] = &[
+    "cache",
+    "caches",
+    "code cache",
+    "gpucache",
+    "cacheddata",
+    "shadercache",
+    "logs",
+    "log",
+    "temp",
+    "tmp",
+    "crashpad",
+];
+
+/// Extensions (as indexed, lowercase) of files that change constantly.
+const NOISE_EXTS: &[&str] = &[
+    "log",
+    "tmp",
+    "temp",
+    "swp",
+    "swo",
+    "lock",
+    "lck",
+    "pid",
+    "part",
+    "crdownload",
+    "db-wal",
+    "db-shm",
+    "db-journal",
+    "sqlite-wal",
+    "sqlite-shm",
+    "sqlite-journal",
+    "etl",
+];
+
+const NOISE_NAMES: &[&str] = &[".DS_Store", "Thumbs.db", "desktop.ini", ".localized"];
+
+/// Whether `entry` is churn rather than a file someone worked on.
+pub(crate) fn is_noise(entry: &EntryDto) -> bool {
+    if NOISE_NAMES.contains(&entry.name.as_str()) || entry.name.starts_with("~$") {
+        return true;
+    }
+    if entry
+        .ext
+        .as_deref()
+        .is_some_and(|ext| NOISE_EXTS.contains(&ext))
+    {
+        return true;
+    }
+    entry.dir.split(['/', '\\']).any(|segment| {
+        NOISE_DIR_NAMES
+            .iter()
+            .any(|noise| segment.eq_ignore_ascii_case(noise))
+    })
+}
+
+/// Files under `root` (everywhere when `None`) modified in `[since, until]`,
+/// newest first, at most `limit` of them. Noise is skipped unless
+/// `include_noise` is set.
+pub(crate) fn recent_files(
+    conn: &Connection,
+    root: Option<&str>,
+    since: i64,
+    until: i64,
+    limit: usize,
+    include_noise: bool,
+) -> AppResult<Vec<EntryDto>> {
+    let mut args = vec![Value::Integer(since), Value::Integer(until)];
+    let scope = match root.filter(|r| *r != "/" && *r != "\\") {
+        Some(root) => {
+            let (lo, hi) = subtree_range_bounds(root);
+            args.push(Value::Text(lo));
+            args.push(Value::Text(hi));
+            "AND path >= ?3 AND path < ?4"
+        }
+        None => "",
+    };
+    let sql = format!(
+        "SELECT path, name, dir, is_dir, ext, size, mtime, cloud_only FROM entries \
+         WHERE mtime >= ?1 AND mtime <= ?2 AND is_dir = 0 {scope} \
+         ORDER BY mtime DESC, mtime_nsec DESC"
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params_from_iter(args.iter()), row_to_entry)
+        .map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for row in rows.take(MAX_SCANNED_ROWS) {
+        let entry = row.map_err(|e| e.to_string())?;
+        if include_noise || !is_noise(&entry) {
+            entries.push(entry);
+            if entries.len() >= limit {
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn lists_newest_files_in_window_without_noise() {
+        let root = crate::temp_case_dir("recent_files");
+        std::fs::create_dir_all(&root).unwrap();
+        let db_path = root.join("index.db");
+        crate::init_db_tables(&db_path).unwrap();
+        let conn = crate::db_connection(&db_path).unwrap();
+        for (dir, name, ext, mtime) in [
+            ("/home/docs", "old.txt", Some("txt"), 100),
+            ("/home/docs", "plan.md", Some("md"), 1_000),
+            ("/home/docs", "draft.md", Some("md"), 900),
+            ("/home/app", "server.log", Some("log"), 1_100),
+            ("/home/app/Cache", "blob", None, 1_050),
+            ("/home/docs", "~$plan.docx", Some("docx"), 1_020),
+            ("/other", "elsewhere.txt", Some("txt"), 950),
+            ("/home/docs", "future.txt", Some("txt"), 99_999),
+        ] {
+            let path = PathBuf::from(format!("{dir}/{name}"));
+            crate::insert_test_entry(&conn, &path, false, 1);
+            crate::set_test_entry_stats(&conn, &path, ext, Some(mtime), Some(1));
+        }
+        let names = |entries: Vec<EntryDto>| -> Vec<String> {
+            entries.into_iter().map(|e| e.name).collect()
+        };
+
+        let recent = recent_files(&conn, None, 500, 2_000, 10, false).unwrap();
+        assert_eq!(names(recent), vec!["plan.md", "elsewhere.txt", "draft.md"]);
+
+        let with_noise = recent_files(&conn, None, 500, 2_000, 2, true).unwrap();
+        assert_eq!(names(with_noise), vec!["server.log", "blob"]);
+
+        if std::path::MAIN_SEPARATOR == '/' {
+            let scoped = recent_files(&conn, Some("/home"), 0, 2_000, 10, false).unwrap();
+            assert_eq!(names(scoped), vec!["plan.md", "draft.md", "old.txt"]);
+        }
+        drop(conn);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -38,12 +38,13 @@ fn path_hash(path: &str) -> u64 {
     hasher.finish()
 }
 
-/// Compact snapshot of the DB rows under a subtree: path hash → (mtime, size).
+/// Compact snapshot of the DB rows under a subtree: path hash → (mtime,
+/// mtime_nsec, size, cloud_only).
 ///
 /// A 64-bit hash collision (odds ≈ n²/2⁶⁵ per subtree) can at worst leave one
 /// vanished row undeleted or one modified row unwritten until the next rescan.
 pub(crate) struct SubtreeDiff {
-    existing: HashMap<u64, (i64, i64)>,
+    existing: HashMap<u64, (i64, i64, i64, bool)>,
     /// Prefixes whose enumeration failed (permission/I-O errors): their rows
     /// are excluded from vanished-row deletion — absence from the walk is not
     /// evidence of deletion there.
@@ -63,7 +64,7 @@ impl SubtreeDiff {
         let (lo, hi) = subtree_range_bounds(dir_prefix);
         let mut diff = Self::empty();
         let Ok(mut stmt) = conn.prepare(&format!(
-            "SELECT path, mtime, size FROM entries WHERE {SUBTREE_WHERE}"
+            "SELECT path, mtime, mtime_nsec, size, cloud_only FROM entries WHERE {SUBTREE_WHERE}"
         )) else {
             return diff;
         };
@@ -72,12 +73,16 @@ impl SubtreeDiff {
                 row.get::<_, String>(0)?,
                 row.get::<_, Option<i64>>(1)?,
                 row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<i64>>(3)?,
+                row.get::<_, bool>(4)?,
             ))
         });
         if let Ok(rows) = rows {
-            for (path, mtime, size) in rows.flatten() {
-                diff.existing
-                    .insert(path_hash(&path), (encode(mtime), encode(size)));
+            for (path, mtime, mtime_nsec, size, cloud_only) in rows.flatten() {
+                diff.existing.insert(
+                    path_hash(&path),
+                    (encode(mtime), encode(mtime_nsec), encode(size), cloud_only),
+                );
             }
         }
         diff
@@ -89,13 +94,20 @@ impl SubtreeDiff {
         self.existing.remove(&path_hash(path));
     }
 
-    /// True when `row` already exists with the same mtime + size. The row is
-    /// removed from the snapshot either way, so after the walk the remainder
-    /// is exactly the set of vanished rows.
+    /// True when `row` already exists with the same mtime + size + cloud
+    /// state (and sub-second mtime, where both sides have one); evicting or
+    /// downloading a cloud file keeps its mtime. The row is removed from the
+    /// snapshot either way, so after the walk the remainder is exactly the
+    /// set of vanished rows.
     pub(crate) fn check_unchanged(&mut self, row: &IndexRow) -> bool {
+        let nsec = encode(row.mtime_nsec);
         matches!(
             self.existing.remove(&path_hash(&row.path)),
-            Some((mtime, size)) if mtime == encode(row.mtime) && size == encode(row.size)
+            Some((mtime, mtime_nsec, size, cloud_only))
+                if mtime == encode(row.mtime)
+                    && size == encode(row.size)
+                    && cloud_only == row.cloud_only
+                    && (mtime_nsec == NONE_SENTINEL || nsec == NONE_SENTINEL || mtime_nsec == nsec)
         )
     }
 
@@ -228,12 +240,16 @@ mod tests {
                 path       TEXT NOT NULL UNIQUE,
                 name       TEXT NOT NULL,
                 dir        TEXT NOT NULL,
+                name_norm  TEXT,
+                dir_norm   TEXT,
                 is_dir     INTEGER NOT NULL,
                 ext        TEXT,
                 mtime      INTEGER,
+                mtime_nsec INTEGER,
                 size       INTEGER,
                 indexed_at INTEGER NOT NULL,
-                run_id     INTEGER NOT NULL DEFAULT 0
+                run_id     INTEGER NOT NULL DEFAULT 0,
+                cloud_only INTEGER NOT NULL DEFAULT 0
             );",
         )
         .expect("create entries");
@@ -0,0 +1,176 @@
+//! Named saved searches (query + sort + UI filter state). They live in their
+//! own table so `reset_index` and schema rebuilds, which only swap `entries`
+//! and clear `meta`, leave them alone.
+
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+
+use crate::{now_epoch, AppResult, SORT_DIRS, SORT_KEYS};
+
+pub(crate) const CREATE_SAVED_SEARCHES_SQL: &str = "\
+CREATE TABLE IF NOT EXISTS saved_searches (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    query TEXT NOT NULL,
+    sort_by TEXT NOT NULL,
+    sort_dir TEXT NOT NULL,
+    filters TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedSearchDto {
+    pub id: i64,
+    pub name: String,
+    pub query: String,
+    pub sort_by: String,
+    pub sort_dir: String,
+    /// Opaque frontend filter state, stored as JSON.
+    pub filters: Option<serde_json::Value>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+fn row_to_saved_search(row: &Row<'_>) -> rusqlite::Result<SavedSearchDto> {
+    let filters: Option<String> = row.get(5)?;
+    Ok(SavedSearchDto {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        query: row.get(2)?,
+        sort_by: row.get(3)?,
+        sort_dir: row.get(4)?,
+        filters: filters.and_then(|f| serde_json::from_str(&f).ok()),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+const SELECT_COLUMNS: &str =
+    "SELECT id, name, query, sort_by, sort_dir, filters, created_at, updated_at FROM saved_searches";
+
+/// Create or overwrite the saved search called `name` (case-insensitive).
+pub(crate) fn save_search(
+    conn: &Connection,
+    name: &str,
+    query: &str,
+    sort_by: Option<&str>,
+    sort_dir: Option<&str>,
+    filters: Option<&serde_json::Value>,
+) -> AppResult<SavedSearchDto> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Saved search name is empty.".to_string());
+    }
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Saved search query is empty.".to_string());
+    }
+    let sort_by = sort_by.unwrap_or("name");
+    if !SORT_KEYS.contains(&sort_by) {
+        return Err(format!("Unknown sort key: {sort_by}"));
+    }
+    let sort_dir = sort_dir.unwrap_or("asc");
+    if !SORT_DIRS.contains(&sort_dir) {
+        return Err(format!("Unknown sort direction: {sort_dir}"));
+    }
+    let filters = filters.map(|f| f.to_string());
+    let now = now_epoch();
+    conn.execute(
+        "INSERT INTO saved_searches(name, query, sort_by, sort_dir, filters, created_at, updated_at)
+         VALUES(?1, ?2, ?3, ?4, ?5, ?6, ?6)
+         ON CONFLICT(name) DO UPDATE SET
+           name = excluded.name,
+           query = excluded.query,
+           sort_by = excluded.sort_by,
+           sort_dir = excluded.sort_dir,
+           filters = excluded.filters,
+           updated_at = excluded.updated_at",
+        params![name, query, sort_by, sort_dir, filters, now],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.query_row(
+        &format!("{SELECT_COLUMNS} WHERE name = ?1"),
+        params![name],
+        row_to_saved_search,
+    )
+    .map_err(|e| e.to_string())
+}
+
+pub(crate) fn list_saved_searches(conn: &Connection) -> AppResult<Vec<SavedSearchDto>> {
+    let mut stmt = conn
+        .prepare(&format!("{SELECT_COLUMNS} ORDER BY name COLLATE NOCASE"))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], row_to_saved_search)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Returns the deleted search, or `None` when `id` did not exist.
+pub(crate) fn delete_saved_search(conn: &Connection, id: i64) -> AppResult<Option<SavedSearchDto>> {
+    let existing = conn
+        .query_row(
+            &format!("{SELECT_COLUMNS} WHERE id = ?1"),
+            params![id],
+            row_to_saved_search,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    if existing.is_some() {
+        conn.execute("DELETE FROM saved_searches WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(existing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_overwrites_by_name_and_delete_reports_missing() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CREATE_SAVED_SEARCHES_SQL).unwrap();
+
+        let filters = serde_json::json!({ "kind": "documents" });
+        let first = save_search(
+            &conn,
+            " Big PDFs ",
+            "ext:pdf",
+            Some("size"),
+            Some("desc"),
+            Some(&filters),
+        )
+        .unwrap();
+        assert_eq!(first.name, "Big PDFs");
+        assert_eq!(first.filters, Some(filters));
+        save_search(&conn, "drafts", "*draft*", None, None, None).unwrap();
+
+        // Same name, different case: updated in place, not duplicated.
+        let updated =
+            save_search(&conn, "big pdfs", "ext:pdf path:Work", None, None, None).unwrap();
+        assert_eq!(updated.id, first.id);
+        assert_eq!(updated.query, "ext:pdf path:Work");
+        assert_eq!(updated.sort_by, "name");
+
+        let names: Vec<String> = list_saved_searches(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec!["big pdfs", "drafts"]);
+
+        assert!(save_search(&conn, "x", "q", Some("owner"), None, None).is_err());
+        assert!(save_search(&conn, "  ", "q", None, None, None).is_err());
+
+        assert_eq!(
+            delete_saved_search(&conn, first.id).unwrap().map(|s| s.id),
+            Some(first.id)
+        );
+        assert!(delete_saved_search(&conn, first.id).unwrap().is_none());
+        assert_eq!(list_saved_searches(&conn).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+//! How hard a filesystem walk pushes. The walk pool is sized once per run
+//! from the storage class under the scan root (SSD, spinning disk, network
+//! volume), and while it runs, workers pause briefly per directory whenever
+//! recent searches are slower than their latency budget, so indexing never
+//! starves interactive queries. `index_tuning` can override the pool size.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{Duration, Instant};
+
+/// Search latency (smoothed) above which an active scan backs off. Typing
+/// feels laggy well before this, but brief spikes from cold pages are normal.
+const SEARCH_LATENCY_BUDGET: Duration = Duration::from_millis(80);
+/// Only searches this recent count as contention; an idle UI never throttles.
+const SEARCH_PRESSURE_WINDOW: Duration = Duration::from_secs(3);
+/// Pause per directory read while searches are degraded. The rayon pool size
+/// is fixed once built, so pausing inside read_dir callbacks is how the walk
+/// sheds effective parallelism at runtime.
+const SCAN_BACKOFF: Duration = Duration::from_millis(4);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StorageKind {
+    Ssd,
+    Hdd,
+    Network,
+    Unknown,
+}
+
+impl StorageKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            StorageKind::Ssd => "ssd",
+            StorageKind::Hdd => "hdd",
+            StorageKind::Network => "network",
+            StorageKind::Unknown => "unknown",
+        }
+    }
+}
+
+pub(crate) fn detect_storage(path: &Path) -> StorageKind {
+    #[cfg(target_os = "macos")]
+    {
+        crate::mac::storage::storage_kind(path)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        crate::win::volume::storage_kind(path)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = path;
+        StorageKind::Unknown
+    }
+}
+
+/// Walk pool size for a storage class. SSDs (and unknowns, which are almost
+/// always SSDs today) are I/O-bound on stat() and benefit from
+/// oversubscription; spinning disks thrash on parallel seeks; network
+/// volumes are latency-bound and want many requests in flight.
+pub(crate) fn scan_threads(n_cpus: usize, storage: StorageKind) -> usize {
+    match storage {
+        StorageKind::Ssd | StorageKind::Unknown => (n_cpus * 2).max(8),
+        StorageKind::Hdd => n_cpus.clamp(2, 4),
+        StorageKind::Network => (n_cpus * 4).clamp(8, 32),
+    }
+}
+
+pub(crate) fn available_cpus() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+}
+
+/// Recent search latency, fed by the `search` command and read by scan
+/// workers to back off while indexing competes with interactive queries.
+#[derive(Debug)]
+pub(crate) struct SearchPressure {
+    origin: Instant,
+    ewma_us: AtomicU64,
+    last_search_ms: AtomicU64,
+}
+
+impl Default for SearchPressure {
+    fn default() -> Self {
+        SearchPressure {
+            origin: Instant::now(),
+            ewma_us: AtomicU64::new(0),
+            last_search_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl SearchPressure {
+    pub(crate) fn record(&self, elapsed: Duration) {
+        let sample = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let prev = self.ewma_us.load(AtomicOrdering::Relaxed);
+        let next = if prev == 0 { sample } else { (prev * 3 + sample) / 4 };
+        self.ewma_us.store(next, AtomicOrdering::Relaxed);
+        // +1 keeps 0 free as the "no search yet" marker.
+        self.last_search_ms
+            .store(self.origin.elapsed().as_millis() as u64 + 1, AtomicOrdering::Relaxed);
+    }
+
+    pub(crate) fn degraded(&self) -> bool {
+        let last = self.last_search_ms.load(AtomicOrdering::Relaxed);
+        if last == 0 {
+            return false;
+        }
+        let since = (self.origin.elapsed().as_millis() as u64 + 1).saturating_sub(last);
+        since <= SEARCH_PRESSURE_WINDOW.as_millis() as u64
+            && self.ewma_us.load(AtomicOrdering::Relaxed)
+                > SEARCH_LATENCY_BUDGET.as_micros() as u64
+    }
+
+    /// Called by scan workers once per directory read.
+    pub(crate) fn yield_if_degraded(&self) {
+        if self.degraded() {
+            std::thread::sleep(SCAN_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thread_counts_follow_storage_and_pressure_tracks_slow_searches() {
+        assert_eq!(scan_threads(8, StorageKind::Ssd), 16);
+        assert_eq!(scan_threads(2, StorageKind::Unknown), 8);
+        assert_eq!(scan_threads(8, StorageKind::Hdd), 4);
+        assert_eq!(scan_threads(1, StorageKind::Hdd), 2);
+        assert_eq!(scan_threads(16, StorageKind::Network), 32);
+
+        let pressure = SearchPressure::default();
+        assert!(!pressure.degraded());
+        pressure.record(Duration::from_millis(5));
+        assert!(!pressure.degraded());
+        for _ in 0..8 {
+            pressure.record(Duration::from_millis(400));
+        }
+        assert!(pressure.degraded());
+    }
+}
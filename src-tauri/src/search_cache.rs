@@ -0,0 +1,132 @@
+//! Small LRU of recent `search` results so flipping between two queries, or
+//! re-running one after a focus change, skips SQLite and the `MemIndex`
+//! entirely. Every index write goes through `invalidate_search_caches`, and
+//! every `MemIndex` swap or delta clears it as well.
+
+use std::collections::VecDeque;
+
+use crate::EntryDto;
+
+const SEARCH_RESULT_CACHE_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SearchCacheKey {
+    pub(crate) query: String,
+    pub(crate) sort_by: String,
+    pub(crate) sort_dir: String,
+    pub(crate) limit: u32,
+    pub(crate) offset: u32,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CachedSearch {
+    pub(crate) mode_label: String,
+    pub(crate) results: Vec<EntryDto>,
+    /// Filled in once a caller asked for the total.
+    pub(crate) total_count: Option<u32>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SearchResultCache {
+    /// Most recently used first.
+    entries: VecDeque<(SearchCacheKey, CachedSearch)>,
+    /// Bumped by `clear`; a search that started under an older generation
+    /// may have read rows that are already stale, so its insert is dropped.
+    generation: u64,
+}
+
+impl SearchResultCache {
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(crate) fn get(&mut self, key: &SearchCacheKey) -> Option<CachedSearch> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos)?;
+        let hit = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(hit)
+    }
+
+    pub(crate) fn insert(&mut self, generation: u64, key: SearchCacheKey, value: CachedSearch) {
+        if generation != self.generation {
+            return;
+        }
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push_front((key, value));
+        self.entries.truncate(SEARCH_RESULT_CACHE_CAPACITY);
+    }
+
+    /// Backfill the exact total once a background count finishes.
+    pub(crate) fn set_total(&mut self, generation: u64, key: &SearchCacheKey, total: u32) {
+        if generation != self.generation {
+            return;
+        }
+        if let Some((_, cached)) = self.entries.iter_mut().find(|(k, _)| k == key) {
+            cached.total_count = Some(total);
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+/// Modes whose results come from outside the index (live filesystem
+/// fallbacks) and so are not covered by any invalidation. `mem_*` modes are
+/// cacheable: the resident index clears the cache whenever it changes.
+pub(crate) fn is_cacheable_mode(mode_label: &str) -> bool {
+    !(mode_label.contains("spotlight")
+        || mode_label == "find_fallback"
+        || mode_label == "name_neg_cache"
+        || mode_label == "db_not_ready")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(query: &str) -> SearchCacheKey {
+        SearchCacheKey {
+            query: query.to_string(),
+            sort_by: "name".to_string(),
+            sort_dir: "asc".to_string(),
+            limit: 300,
+            offset: 0,
+        }
+    }
+
+    fn cached(mode_label: &str) -> CachedSearch {
+        CachedSearch {
+            mode_label: mode_label.to_string(),
+            results: Vec::new(),
+            total_count: None,
+        }
+    }
+
+    #[test]
+    fn evicts_least_recent_and_drops_inserts_from_before_a_clear() {
+        let mut cache = SearchResultCache::default();
+        let generation = cache.generation();
+        for i in 0..SEARCH_RESULT_CACHE_CAPACITY {
+            cache.insert(generation, key(&format!("q{i}")), cached("name"));
+        }
+        // Touch the oldest so the next insert evicts q1 instead.
+        assert!(cache.get(&key("q0")).is_some());
+        cache.insert(generation, key("new"), cached("name"));
+        assert!(cache.get(&key("q0")).is_some());
+        assert!(cache.get(&key("q1")).is_none());
+        cache.set_total(generation, &key("new"), 42);
+        assert_eq!(cache.get(&key("new")).unwrap().total_count, Some(42));
+
+        cache.clear();
+        assert!(cache.get(&key("new")).is_none());
+        cache.insert(generation, key("stale"), cached("name"));
+        assert!(cache.get(&key("stale")).is_none());
+
+        assert!(is_cacheable_mode("name"));
+        assert!(is_cacheable_mode("mem_name"));
+        assert!(!is_cacheable_mode("name_+spotlight"));
+    }
+}
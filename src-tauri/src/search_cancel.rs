@@ -0,0 +1,145 @@
+//! Cancellation for in-flight `search` calls. Each query carries a request id;
+//! once the user has typed past it, `cancel_search` flips its token so
+//! SQLite's progress handler and the in-memory scan loops stop early instead
+//! of finishing work nobody will see.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+pub(crate) const SEARCH_CANCELLED: &str = "Search cancelled.";
+
+/// Server-assigned ids (for callers that did not pass one) have the top bit
+/// set so they never collide with the frontend's own counter.
+const SERVER_ID_BIT: u64 = 1 << 63;
+/// Cancels for ids that are not (or no longer) running are kept so a cancel
+/// that races ahead of its search still lands; past this many they are pruned.
+const MAX_PENDING_CANCELS: usize = 256;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchCancelToken(Arc<AtomicBool>);
+
+impl SearchCancelToken {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+
+    fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+}
+
+/// `Option<&SearchCancelToken>` is how the token is threaded through search
+/// helpers; `None` (MCP server, benchmarks) never cancels.
+pub(crate) fn is_cancelled(cancel: Option<&SearchCancelToken>) -> bool {
+    cancel.is_some_and(SearchCancelToken::is_cancelled)
+}
+
+/// A registered id: its token and how many `ActiveSearch` guards hold it.
+/// A search and the background count it spawns share one id, so the entry
+/// lives until both are done.
+#[derive(Debug, Default)]
+struct Registration {
+    token: SearchCancelToken,
+    holders: usize,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SearchCancelRegistry {
+    next_id: AtomicU64,
+    tokens: Mutex<HashMap<u64, Registration>>,
+}
+
+impl SearchCancelRegistry {
+    /// Register a search; the token is released when the last guard for its
+    /// id drops. Work continuing a search (its exact count) begins the same
+    /// id again and shares the token.
+    pub(crate) fn begin(&self, request_id: Option<u64>) -> ActiveSearch<'_> {
+        let id = request_id
+            .unwrap_or_else(|| self.next_id.fetch_add(1, AtomicOrdering::Relaxed) | SERVER_ID_BIT);
+        let token = {
+            let mut tokens = self.tokens.lock();
+            let registration = tokens.entry(id).or_default();
+            registration.holders += 1;
+            registration.token.clone()
+        };
+        ActiveSearch {
+            registry: self,
+            id,
+            token,
+        }
+    }
+
+    /// Returns true when the search was still running.
+    pub(crate) fn cancel(&self, id: u64) -> bool {
+        let mut tokens = self.tokens.lock();
+        if let Some(registration) = tokens.get(&id) {
+            let running = registration.holders > 0 && !registration.token.is_cancelled();
+            registration.token.cancel();
+            return running;
+        }
+        if tokens.len() >= MAX_PENDING_CANCELS {
+            tokens.retain(|_, registration| registration.holders > 0);
+        }
+        let registration = Registration::default();
+        registration.token.cancel();
+        tokens.insert(id, registration);
+        false
+    }
+}
+
+pub(crate) struct ActiveSearch<'a> {
+    registry: &'a SearchCancelRegistry,
+    pub(crate) id: u64,
+    pub(crate) token: SearchCancelToken,
+}
+
+impl Drop for ActiveSearch<'_> {
+    fn drop(&mut self) {
+        let mut tokens = self.registry.tokens.lock();
+        if let Some(registration) = tokens.get_mut(&self.id) {
+            registration.holders = registration.holders.saturating_sub(1);
+            if registration.holders == 0 {
+                tokens.remove(&self.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_reaches_running_and_not_yet_started_searches() {
+        let registry = SearchCancelRegistry::default();
+
+        let active = registry.begin(Some(7));
+        assert!(!active.token.is_cancelled());
+        assert!(registry.cancel(7));
+        assert!(active.token.is_cancelled());
+        assert!(!registry.cancel(7));
+        drop(active);
+
+        // A cancel that arrives before its search starts still applies.
+        assert!(!registry.cancel(8));
+        assert!(registry.begin(Some(8)).token.is_cancelled());
+
+        // The background count shares its search's id: the search finishing
+        // first must leave the count cancellable.
+        let search = registry.begin(Some(9));
+        let count = registry.begin(Some(9));
+        drop(search);
+        assert!(registry.cancel(9));
+        assert!(count.token.is_cancelled());
+        drop(count);
+        assert!(registry.tokens.lock().get(&9).is_none());
+
+        let assigned = registry.begin(None);
+        assert_ne!(assigned.id & SERVER_ID_BIT, 0);
+        assert!(!is_cancelled(Some(&assigned.token)));
+        assert!(!is_cancelled(None));
+    }
+}
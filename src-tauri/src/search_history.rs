@@ -0,0 +1,140 @@
+//! Recently used search queries, most recent first, kept in their own table
+//! like saved searches so index resets leave them alone. They feed the
+//! Windows taskbar jump list, whose entries relaunch the app with
+//! `--search <query>`.
+
+use rusqlite::{params, Connection};
+use serde::Serialize;
+
+use crate::{now_epoch, AppResult};
+
+pub(crate) const CREATE_SEARCH_HISTORY_SQL: &str = "\
+CREATE TABLE IF NOT EXISTS search_history (
+    query TEXT PRIMARY KEY,
+    use_count INTEGER NOT NULL,
+    last_used INTEGER NOT NULL
+);";
+
+/// Queries kept; older ones are dropped as new ones are recorded.
+pub(crate) const MAX_SEARCH_HISTORY: usize = 100;
+pub(crate) const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+/// Command-line flag the jump list passes a query with.
+pub(crate) const LAUNCH_QUERY_FLAG: &str = "--search";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHistoryDto {
+    pub query: String,
+    pub use_count: i64,
+    pub last_used: i64,
+}
+
+/// Bump `query` to the top of the history. Blank queries are ignored.
+pub(crate) fn record_search(conn: &Connection, query: &str) -> AppResult<()> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(());
+    }
+    // REPLACE gives the row a fresh rowid, which orders records made
+    // within the same second.
+    conn.execute(
+        "INSERT OR REPLACE INTO search_history (query, use_count, last_used)
+         VALUES (?1, COALESCE((SELECT use_count FROM search_history WHERE query = ?1), 0) + 1, ?2)",
+        params![query, now_epoch()],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM search_history WHERE rowid NOT IN (
+             SELECT rowid FROM search_history ORDER BY last_used DESC, rowid DESC LIMIT ?1
+         )",
+        params![MAX_SEARCH_HISTORY as i64],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub(crate) fn recent_searches(conn: &Connection, limit: usize) -> AppResult<Vec<SearchHistoryDto>> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT query, use_count, last_used FROM search_history
+             ORDER BY last_used DESC, rowid DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![limit as i64], |row| {
+            Ok(SearchHistoryDto {
+                query: row.get(0)?,
+                use_count: row.get(1)?,
+                last_used: row.get(2)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<rusqlite::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn clear_search_history(conn: &Connection) -> AppResult<usize> {
+    conn.execute("DELETE FROM search_history", [])
+        .map_err(|e| e.to_string())
+}
+
+/// The query after `--search` (or in `--search=<query>`), if any.
+pub(crate) fn launch_query_from_args(args: &[String]) -> Option<String> {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == LAUNCH_QUERY_FLAG {
+            iter.next().cloned()
+        } else {
+            arg.strip_prefix(LAUNCH_QUERY_FLAG)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(str::to_string)
+        };
+        if let Some(query) = value.filter(|q| !q.trim().is_empty()) {
+            return Some(query);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_most_recent_first_and_caps_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(CREATE_SEARCH_HISTORY_SQL).unwrap();
+
+        record_search(&conn, "ext:pdf").unwrap();
+        record_search(&conn, "  report  ").unwrap();
+        record_search(&conn, "").unwrap();
+        record_search(&conn, "ext:pdf").unwrap();
+        let recent = recent_searches(&conn, 10).unwrap();
+        let summary: Vec<(&str, i64)> = recent
+            .iter()
+            .map(|h| (h.query.as_str(), h.use_count))
+            .collect();
+        assert_eq!(summary, vec![("ext:pdf", 2), ("report", 1)]);
+
+        for i in 0..MAX_SEARCH_HISTORY + 5 {
+            record_search(&conn, &format!("q{i}")).unwrap();
+        }
+        let all = recent_searches(&conn, MAX_SEARCH_HISTORY * 2).unwrap();
+        assert_eq!(all.len(), MAX_SEARCH_HISTORY);
+        assert_eq!(all[0].query, format!("q{}", MAX_SEARCH_HISTORY + 4));
+        assert_eq!(clear_search_history(&conn).unwrap(), MAX_SEARCH_HISTORY);
+
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            launch_query_from_args(&args(&["--search", "ext:md notes"])).as_deref(),
+            Some("ext:md notes")
+        );
+        assert_eq!(
+            launch_query_from_args(&args(&["--search=*.rs"])).as_deref(),
+            Some("*.rs")
+        );
+        assert_eq!(launch_query_from_args(&args(&["--search"])), None);
+        assert_eq!(launch_query_from_args(&args(&["--mcp"])), None);
+    }
+}
@@ -0,0 +1,157 @@
+//! `open_terminal_here`: start the user's terminal in a result's directory.
+//! The terminal is chosen by the caller (the frontend keeps the preference);
+//! without one the platform default is used, falling back to the system
+//! shell window when the preferred terminal can't be launched.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::AppResult;
+
+/// One way to launch a terminal: program, arguments, and whether it needs
+/// its working directory set (for terminals without a directory flag).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TerminalLaunch {
+    pub(crate) program: String,
+    pub(crate) args: Vec<String>,
+    pub(crate) current_dir: Option<PathBuf>,
+}
+
+impl TerminalLaunch {
+    fn new(program: &str, args: &[&str], current_dir: Option<&Path>) -> Self {
+        TerminalLaunch {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            current_dir: current_dir.map(Path::to_path_buf),
+        }
+    }
+}
+
+/// The directory a terminal should open in: `path` itself for folders, its
+/// parent for files.
+pub(crate) fn terminal_dir(path: &Path) -> AppResult<PathBuf> {
+    if path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    path.parent()
+        .filter(|p| p.is_dir())
+        .map(Path::to_path_buf)
+        .ok_or_else(|| format!("Folder not found for {}", path.display()))
+}
+
+/// Launch candidates in order of preference.
+#[cfg(target_os = "macos")]
+pub(crate) fn terminal_launches(terminal: Option<&str>, dir: &Path) -> Vec<TerminalLaunch> {
+    let dir_str = dir.to_string_lossy();
+    let mut launches = Vec::new();
+    if let Some(app) = terminal.map(str::trim).filter(|t| !t.is_empty()) {
+        launches.push(TerminalLaunch::new("open", &["-a", app, &dir_str], None));
+    }
+    launches.push(TerminalLaunch::new(
+        "open",
+        &["-a", "Terminal", &dir_str],
+        None,
+    ));
+    launches
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn terminal_launches(terminal: Option<&str>, dir: &Path) -> Vec<TerminalLaunch> {
+    let dir_str = dir.to_string_lossy();
+    let mut launches = Vec::new();
+    let terminal = terminal.map(str::trim).filter(|t| !t.is_empty());
+    match terminal.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("wt") | Some("windows terminal") => {
+            launches.push(TerminalLaunch::new("wt", &["-d", &dir_str], None))
+        }
+        Some(shell @ ("powershell" | "pwsh")) => launches.push(TerminalLaunch::new(
+            "cmd",
+            &["/C", "start", "", shell, "-NoExit"],
+            Some(dir),
+        )),
+        // The fallback below is already cmd.
+        Some("cmd") => {}
+        Some(_) => launches.push(TerminalLaunch::new(
+            terminal.unwrap_or_default(),
+            &[],
+            Some(dir),
+        )),
+    }
+    launches.push(TerminalLaunch::new(
+        "cmd",
+        &["/C", "start", "", "cmd", "/K"],
+        Some(dir),
+    ));
+    launches
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn terminal_launches(terminal: Option<&str>, dir: &Path) -> Vec<TerminalLaunch> {
+    let mut launches = Vec::new();
+    if let Some(program) = terminal.map(str::trim).filter(|t| !t.is_empty()) {
+        launches.push(TerminalLaunch::new(program, &[], Some(dir)));
+    }
+    launches.push(TerminalLaunch::new("x-terminal-emulator", &[], Some(dir)));
+    launches
+}
+
+/// Try each launch in turn; the first that spawns wins. Terminals are left
+/// running detached.
+pub(crate) fn open_terminal(terminal: Option<&str>, path: &Path) -> AppResult<()> {
+    let dir = terminal_dir(path)?;
+    let mut last_error = String::new();
+    for launch in terminal_launches(terminal, &dir) {
+        let mut cmd = Command::new(&launch.program);
+        cmd.args(&launch.args);
+        if let Some(current_dir) = &launch.current_dir {
+            cmd.current_dir(current_dir);
+        }
+        // `open` and `cmd /C start` exit once the terminal is up; a terminal
+        // spawned directly keeps running, so only wait for the launchers.
+        let waits = matches!(launch.program.as_str(), "open" | "cmd");
+        let result = if waits {
+            cmd.status().map_err(|e| e.to_string()).and_then(|s| {
+                s.success()
+                    .then_some(())
+                    .ok_or_else(|| format!("{} exited with {s}", launch.program))
+            })
+        } else {
+            cmd.spawn().map(|_| ()).map_err(|e| e.to_string())
+        };
+        match result {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = format!("Failed to open {}: {e}", launch.program),
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_configured_terminal_and_falls_back_to_default() {
+        let dir = crate::temp_case_dir("terminal_dir");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("notes.txt");
+        std::fs::write(&file, "n").unwrap();
+        assert_eq!(terminal_dir(&file).unwrap(), dir);
+        assert_eq!(terminal_dir(&dir).unwrap(), dir);
+        assert!(terminal_dir(&dir.join("missing").join("x.txt")).is_err());
+
+        let configured = terminal_launches(Some("kitty"), &dir);
+        let default = terminal_launches(None, &dir);
+        assert_eq!(configured.len(), 2);
+        assert_eq!(configured.last(), default.last());
+        assert!(configured[0]
+            .args
+            .iter()
+            .chain(std::iter::once(&configured[0].program))
+            .any(|a| a == "kitty"));
+        // Blank preference is the same as none.
+        assert_eq!(terminal_launches(Some("  "), &dir), default);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,229 @@
+//! Content thumbnails for `get_thumbnail` (images, video frames, PDF first
+//! pages), rendered by the OS — Quick Look on macOS, the shell thumbnail
+//! cache on Windows — and kept as PNGs in a size-capped disk cache next to
+//! the index. Entries are keyed by a SHA-256 of path, mtime and size plus the
+//! edge length, so an edited file simply misses and keys stay valid across
+//! restarts and toolchain updates; the least recently used files are evicted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+
+pub(crate) const DEFAULT_THUMBNAIL_SIZE: u32 = 256;
+pub(crate) const MAX_THUMBNAIL_SIZE: u32 = 1024;
+const THUMBNAIL_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+/// Eviction frees down to this share of the cap, so the directory is listed
+/// once per batch of writes rather than on every write past the cap.
+const EVICT_TO_PERCENT: u64 = 90;
+
+#[derive(Debug)]
+pub(crate) struct ThumbnailCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    /// Bytes on disk: counted by listing the dir on the first write, then
+    /// kept up to date. Also serializes eviction.
+    total_bytes: Mutex<Option<u64>>,
+}
+
+impl ThumbnailCache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self::with_capacity(dir, THUMBNAIL_CACHE_MAX_BYTES)
+    }
+
+    fn with_capacity(dir: PathBuf, max_bytes: u64) -> Self {
+        ThumbnailCache {
+            dir,
+            max_bytes,
+            total_bytes: Mutex::new(None),
+        }
+    }
+
+    fn entry_path(&self, path: &Path, meta: &fs::Metadata, size: u32) -> PathBuf {
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let mut hasher = Sha256::new();
+        hasher.update(path.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update(mtime.to_le_bytes());
+        hasher.update(meta.len().to_le_bytes());
+        let key: String = hasher.finalize()[..16]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        self.dir.join(format!("{key}_{size}.png"))
+    }
+
+    /// Cached PNG for `path` at `size`, rendering it with `render` on a miss.
+    /// Returns `None` when the file is gone or the OS has no thumbnail for it.
+    pub(crate) fn get_or_render(
+        &self,
+        path: &Path,
+        size: u32,
+        render: impl FnOnce(&Path, u32) -> Option<Vec<u8>>,
+    ) -> Option<Vec<u8>> {
+        let meta = fs::metadata(path).ok()?;
+        let entry = self.entry_path(path, &meta, size);
+        if let Ok(png) = fs::read(&entry) {
+            // Touch so eviction sees it as recently used.
+            if let Ok(file) = fs::File::options().write(true).open(&entry) {
+                let _ = file.set_modified(SystemTime::now());
+            }
+            return Some(png);
+        }
+
+        let png = render(path, size).filter(|png| !png.is_empty())?;
+        if fs::create_dir_all(&self.dir).is_ok() && fs::write(&entry, &png).is_ok() {
+            self.note_written(png.len() as u64);
+        }
+        Some(png)
+    }
+
+    /// Account for a `len`-byte write, evicting once the cache passes its cap.
+    fn note_written(&self, len: u64) {
+        let mut total_bytes = self.total_bytes.lock();
+        let total = match *total_bytes {
+            Some(total) => total + len,
+            // The first count lists the dir, which already has this write.
+            None => self.list_files().iter().map(|(_, len, _)| len).sum(),
+        };
+        *total_bytes = Some(if total > self.max_bytes {
+            self.evict()
+        } else {
+            total
+        });
+    }
+
+    fn list_files(&self) -> Vec<(SystemTime, u64, PathBuf)> {
+        let Ok(read_dir) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .filter_map(|e| {
+                let meta = e.metadata().ok().filter(|m| m.is_file())?;
+                Some((meta.modified().ok()?, meta.len(), e.path()))
+            })
+            .collect()
+    }
+
+    /// Delete least recently used thumbnails until the cache is back under
+    /// `EVICT_TO_PERCENT` of its cap. Returns the bytes left on disk.
+    fn evict(&self) -> u64 {
+        let mut files = self.list_files();
+        let mut total: u64 = files.iter().map(|(_, len, _)| len).sum();
+        let target = self.max_bytes * EVICT_TO_PERCENT / 100;
+        files.sort_by_key(|(modified, _, _)| *modified);
+        for (_, len, path) in files {
+            if total <= target {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                total = total.saturating_sub(len);
+            }
+        }
+        total
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn render_thumbnail_png(path: &Path, size: u32) -> Option<Vec<u8>> {
+    // Quick Look reads the content, which would download an iCloud file.
+    if crate::mac::cloud::is_dataless_path(path) {
+        return None;
+    }
+    // Quick Look renders images, video frames, PDFs and anything with a
+    // third-party generator; it writes "<file name>.png" into the out dir.
+    static NEXT_OUT_DIR: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+    let out_dir = std::env::temp_dir().join(format!(
+        "everything-thumb-{}-{}",
+        std::process::id(),
+        NEXT_OUT_DIR.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&out_dir).ok()?;
+    let status = std::process::Command::new("qlmanage")
+        .arg("-t")
+        .arg("-s")
+        .arg(size.to_string())
+        .arg("-o")
+        .arg(&out_dir)
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+    let png = status.ok().filter(|s| s.success()).and_then(|_| {
+        let name = path.file_name()?;
+        let mut out_name = name.to_os_string();
+        out_name.push(".png");
+        fs::read(out_dir.join(out_name)).ok()
+    });
+    let _ = fs::remove_dir_all(&out_dir);
+    png
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn render_thumbnail_png(path: &Path, size: u32) -> Option<Vec<u8>> {
+    // Rendering a thumbnail reads file content, which would hydrate a cloud
+    // placeholder.
+    if crate::win::cloud::is_placeholder_path(path) {
+        return None;
+    }
+    crate::win::icon::load_thumbnail_png(&path.to_string_lossy(), size)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub(crate) fn render_thumbnail_png(_path: &Path, _size: u32) -> Option<Vec<u8>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn caches_by_mtime_and_evicts_least_recent() {
+        let root = crate::temp_case_dir("thumbnail_cache");
+        fs::create_dir_all(&root).unwrap();
+        let cache = ThumbnailCache::with_capacity(root.join("thumbs"), 25);
+        let source = root.join("photo.jpg");
+        fs::write(&source, b"jpeg").unwrap();
+
+        let renders = Cell::new(0);
+        let render = |_: &Path, size: u32| {
+            renders.set(renders.get() + 1);
+            Some(vec![size as u8; 10])
+        };
+        assert_eq!(cache.get_or_render(&source, 64, render), Some(vec![64; 10]));
+        assert_eq!(cache.get_or_render(&source, 64, render), Some(vec![64; 10]));
+        assert_eq!(renders.get(), 1, "second request served from disk");
+
+        // A different size is a separate entry; a third pushes past the
+        // 25-byte cap and evicts the least recently used one.
+        cache.get_or_render(&source, 32, render);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.get_or_render(&source, 64, render);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cache.get_or_render(&source, 16, render);
+        assert_eq!(renders.get(), 3);
+        cache.get_or_render(&source, 64, render);
+        assert_eq!(renders.get(), 3, "recently used entry survived eviction");
+        cache.get_or_render(&source, 32, render);
+        assert_eq!(renders.get(), 4, "least recent entry was evicted");
+
+        // No thumbnail from the OS: nothing cached, nothing returned.
+        assert_eq!(cache.get_or_render(&source, 128, |_, _| None), None);
+        assert_eq!(
+            cache.get_or_render(&root.join("missing.jpg"), 64, render),
+            None
+        );
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,165 @@
+//! Tray / menu-bar icon: shows the index state and offers Show, Pause,
+//! Rebuild and Quit, so the app stays useful with its window closed (closing
+//! the main window only hides it while the tray is up).
+
+use std::sync::atomic::Ordering as AtomicOrdering;
+use std::time::Duration;
+
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Manager};
+
+use crate::{index_status_snapshot, AppState};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// First line of the tray menu, also used as the tooltip.
+fn status_label(state: &str, entries_count: u64, scanned: u64, paused: bool) -> String {
+    match state {
+        "Error" => "Index error".to_string(),
+        "Indexing" if scanned > 0 => format!("Indexing… {} scanned", group_digits(scanned)),
+        "Indexing" => "Indexing…".to_string(),
+        _ if paused => format!("Paused — {} items", group_digits(entries_count)),
+        _ => format!("Ready — {} items", group_digits(entries_count)),
+    }
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+pub(crate) fn install(app: &AppHandle, state: AppState) -> tauri::Result<()> {
+    let status = MenuItem::with_id(app, "tray_status", "Starting…", false, None::<&str>)?;
+    let show = MenuItem::with_id(app, "tray_show", "Show Everything", true, None::<&str>)?;
+    let pause = CheckMenuItem::with_id(
+        app,
+        "tray_pause",
+        "Pause Indexing",
+        true,
+        false,
+        None::<&str>,
+    )?;
+    let rebuild = MenuItem::with_id(app, "tray_rebuild", "Rebuild Index", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, "tray_quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &status,
+            &PredefinedMenuItem::separator(app)?,
+            &show,
+            &pause,
+            &rebuild,
+            &PredefinedMenuItem::separator(app)?,
+            &quit,
+        ],
+    )?;
+
+    let menu_state = state.clone();
+    let mut builder = TrayIconBuilder::with_id("main")
+        .menu(&menu)
+        .tooltip("Everything")
+        // Windows convention: left click opens the window, right click the menu.
+        .show_menu_on_left_click(!cfg!(target_os = "windows"))
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "tray_show" => show_main_window(app),
+            "tray_pause" => {
+                let (app, state) = (app.clone(), menu_state.clone());
+                std::thread::spawn(move || {
+                    let paused = !state.indexing_paused.load(AtomicOrdering::Acquire);
+                    if let Err(e) = crate::set_indexing_paused_blocking(&app, &state, paused) {
+                        eprintln!("[tray] pause failed: {e}");
+                    }
+                });
+            }
+            "tray_rebuild" => {
+                let (app, state) = (app.clone(), menu_state.clone());
+                std::thread::spawn(move || {
+                    if let Err(e) = crate::reset_index_blocking(app, state) {
+                        eprintln!("[tray] rebuild failed: {e}");
+                    }
+                });
+            }
+            "tray_quit" => app.exit(0),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                if cfg!(target_os = "windows") {
+                    show_main_window(tray.app_handle());
+                }
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let tray = builder.build(app)?;
+
+    // Menu items can't observe the index; poll the status snapshot and only
+    // touch the menu when something visible changed.
+    std::thread::spawn(move || {
+        let mut shown: Option<(String, bool, bool)> = None;
+        loop {
+            let snapshot = index_status_snapshot(&state);
+            let label = status_label(
+                &snapshot.state,
+                snapshot.entries_count,
+                snapshot.scanned,
+                snapshot.paused,
+            );
+            let busy = snapshot.background_active;
+            let current = (label, snapshot.paused, busy);
+            if shown.as_ref() != Some(&current) {
+                let _ = status.set_text(&current.0);
+                let _ = tray.set_tooltip(Some(format!("Everything — {}", current.0)));
+                let _ = pause.set_checked(current.1);
+                // Pausing and rebuilding are refused mid-index anyway.
+                let _ = pause.set_enabled(!busy);
+                let _ = rebuild.set_enabled(!busy);
+                shown = Some(current);
+            }
+            std::thread::sleep(REFRESH_INTERVAL);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_follow_index_state() {
+        assert_eq!(
+            status_label("Ready", 1_234_567, 0, false),
+            "Ready — 1,234,567 items"
+        );
+        assert_eq!(status_label("Ready", 999, 0, true), "Paused — 999 items");
+        assert_eq!(
+            status_label("Indexing", 0, 45_000, false),
+            "Indexing… 45,000 scanned"
+        );
+        assert_eq!(status_label("Indexing", 0, 0, false), "Indexing…");
+        assert_eq!(status_label("Error", 10, 0, false), "Index error");
+    }
+}
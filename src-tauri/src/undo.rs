@@ -0,0 +1,236 @@
+//! Undo for destructive file operations. `rename` and `move_to_trash` record
+//! what they did in a short in-memory log, and `undo_last_operation` reverses
+//! the newest record: renaming back, or putting the trashed batch back where
+//! it came from. Windows and Linux trashes can be queried for an item's
+//! original path; the macOS Trash can't, so there the trashed location is
+//! captured at delete time.
+
+#[cfg(target_os = "macos")]
+use std::collections::HashSet;
+use std::collections::VecDeque;
+#[cfg(target_os = "macos")]
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::long_path;
+use crate::AppResult;
+
+const UNDO_LOG_CAPACITY: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TrashedItem {
+    pub(crate) original: String,
+    /// Where the item landed in the Trash; only known (and needed) on macOS.
+    pub(crate) trashed_as: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum UndoOp {
+    Rename { from: String, to: String },
+    Trash { items: Vec<TrashedItem> },
+}
+
+/// Newest operation first.
+#[derive(Debug, Default)]
+pub(crate) struct UndoLog {
+    ops: VecDeque<UndoOp>,
+}
+
+impl UndoLog {
+    pub(crate) fn push(&mut self, op: UndoOp) {
+        self.ops.push_front(op);
+        self.ops.truncate(UNDO_LOG_CAPACITY);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<UndoOp> {
+        self.ops.pop_front()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResultDto {
+    /// "rename" or "trash".
+    pub kind: String,
+    /// Paths that exist again.
+    pub restored: Vec<String>,
+    /// Paths that went away (the name a rename had given).
+    pub removed: Vec<String>,
+    /// One message per item that could not be put back.
+    pub failed: Vec<String>,
+}
+
+/// Rename `to` back to `from`, refusing to overwrite anything.
+pub(crate) fn undo_rename(from: &str, to: &str) -> AppResult<()> {
+    if long_path::symlink_metadata(to).is_err() {
+        return Err(format!("Cannot undo rename: {to} no longer exists."));
+    }
+    if long_path::symlink_metadata(from).is_ok() {
+        return Err(format!("Cannot undo rename: {from} already exists."));
+    }
+    fs::rename(to, from).map_err(|e| e.to_string())
+}
+
+/// Put trashed items back at their original paths. Returns the restored
+/// paths and a message for each item that could not be restored.
+pub(crate) fn restore_trashed(items: &[TrashedItem]) -> (Vec<String>, Vec<String>) {
+    let mut failed = Vec::new();
+    let pending: Vec<&TrashedItem> = items
+        .iter()
+        .filter(|item| {
+            let taken = long_path::symlink_metadata(&item.original).is_ok();
+            if taken {
+                failed.push(format!("{} already exists.", item.original));
+            }
+            !taken
+        })
+        .collect();
+    let restored = restore_from_platform_trash(&pending, &mut failed);
+    (restored, failed)
+}
+
+#[cfg(not(target_os = "macos"))]
+fn restore_from_platform_trash(items: &[&TrashedItem], failed: &mut Vec<String>) -> Vec<String> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let listed = match trash::os_limited::list() {
+        Ok(listed) => listed,
+        Err(e) => {
+            failed.push(e.to_string());
+            return Vec::new();
+        }
+    };
+    let mut selected = Vec::with_capacity(items.len());
+    let mut originals = Vec::with_capacity(items.len());
+    for item in items {
+        // The newest deletion of that path is the one we made.
+        let found = listed
+            .iter()
+            .filter(|t| t.original_path() == Path::new(&item.original))
+            .max_by_key(|t| t.time_deleted);
+        match found {
+            Some(found) => {
+                selected.push(found.clone());
+                originals.push(item.original.clone());
+            }
+            None => failed.push(format!("{} is no longer in the trash.", item.original)),
+        }
+    }
+    if selected.is_empty() {
+        return Vec::new();
+    }
+    match trash::os_limited::restore_all(selected) {
+        Ok(()) => originals,
+        Err(e) => {
+            failed.push(e.to_string());
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn restore_from_platform_trash(items: &[&TrashedItem], failed: &mut Vec<String>) -> Vec<String> {
+    let mut restored = Vec::with_capacity(items.len());
+    for item in items {
+        let trashed = item
+            .trashed_as
+            .as_ref()
+            .filter(|p| long_path::symlink_metadata(p).is_ok());
+        let Some(trashed) = trashed else {
+            failed.push(format!("{} is no longer in the Trash.", item.original));
+            continue;
+        };
+        match fs::rename(trashed, &item.original) {
+            Ok(()) => restored.push(item.original.clone()),
+            Err(e) => failed.push(format!("{}: {e}", item.original)),
+        }
+    }
+    restored
+}
+
+/// Names in the user's Trash, taken before a delete so `locate_trashed` can
+/// spot the new arrival.
+#[cfg(target_os = "macos")]
+pub(crate) fn trash_snapshot(home: &Path) -> HashSet<OsString> {
+    fs::read_dir(home.join(".Trash"))
+        .map(|rd| rd.flatten().map(|e| e.file_name()).collect())
+        .unwrap_or_default()
+}
+
+/// The Trash entry that appeared since `before` for `original`. Finder keeps
+/// the name, or appends a time stamp when the name is already taken.
+/// Items trashed from other volumes go to that volume's `.Trashes` and are
+/// not found.
+#[cfg(target_os = "macos")]
+pub(crate) fn locate_trashed(
+    home: &Path,
+    before: &HashSet<OsString>,
+    original: &Path,
+) -> Option<PathBuf> {
+    let name = original.file_name()?.to_string_lossy().to_string();
+    let stem = Path::new(&name)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| name.clone());
+    let trash = home.join(".Trash");
+    let fresh: Vec<String> = fs::read_dir(&trash)
+        .ok()?
+        .flatten()
+        .map(|e| e.file_name())
+        .filter(|n| !before.contains(n))
+        .map(|n| n.to_string_lossy().to_string())
+        .collect();
+    let found = fresh
+        .iter()
+        .find(|n| **n == name)
+        .or_else(|| fresh.iter().find(|n| n.starts_with(&stem)))?;
+    Some(trash.join(found))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_is_newest_first_and_rename_undo_refuses_to_overwrite() {
+        let mut log = UndoLog::default();
+        for i in 0..UNDO_LOG_CAPACITY + 5 {
+            log.push(UndoOp::Rename {
+                from: format!("a{i}"),
+                to: format!("b{i}"),
+            });
+        }
+        let newest = format!("a{}", UNDO_LOG_CAPACITY + 4);
+        assert!(matches!(log.pop(), Some(UndoOp::Rename { from, .. }) if from == newest));
+        assert_eq!(log.ops.len(), UNDO_LOG_CAPACITY - 1);
+
+        let dir = crate::temp_case_dir("undo_rename");
+        fs::create_dir_all(&dir).unwrap();
+        let from = dir.join("draft.txt").to_string_lossy().to_string();
+        let to = dir.join("final.txt").to_string_lossy().to_string();
+        fs::write(&to, b"x").unwrap();
+        fs::write(&from, b"new").unwrap();
+        assert!(undo_rename(&from, &to)
+            .unwrap_err()
+            .contains("already exists"));
+        fs::remove_file(&from).unwrap();
+        undo_rename(&from, &to).unwrap();
+        assert_eq!(fs::read(&from).unwrap(), b"x");
+        assert!(undo_rename(&from, &to)
+            .unwrap_err()
+            .contains("no longer exists"));
+
+        let (restored, failed) = restore_trashed(&[TrashedItem {
+            original: from.clone(),
+            trashed_as: None,
+        }]);
+        assert!(restored.is_empty());
+        assert_eq!(failed, vec![format!("{from} already exists.")]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,43 @@
+//! Cloud-sync placeholders (OneDrive / Dropbox files-on-demand). Their
+//! content is fetched on first read, so anything that opens the file — icon
+//! extraction, thumbnailing, launching it — can start a download. Attributes
+//! are available without hydrating: from MFT/USN records during indexing and
+//! from `GetFileAttributesEx` (std metadata) otherwise. The indexers store
+//! the state in the `cloud_only` column, which the USN watcher compares
+//! against attribute-change records to notice when a placeholder is
+//! hydrated or dehydrated in place.
+
+use std::os::windows::fs::MetadataExt;
+use std::path::Path;
+
+use rusqlite::Connection;
+
+use crate::long_path;
+
+/// Content is not local; any data access recalls it from the provider.
+pub const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+/// Opening the handle itself recalls (used by some providers for directories).
+pub const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+
+pub fn is_placeholder_attrs(attributes: u32) -> bool {
+    attributes & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS | FILE_ATTRIBUTE_RECALL_ON_OPEN) != 0
+}
+
+/// Attribute-only check; never opens the file's data.
+pub fn is_placeholder_path(path: &Path) -> bool {
+    long_path::symlink_metadata(path)
+        .map(|m| is_placeholder_attrs(m.file_attributes()))
+        .unwrap_or(false)
+}
+
+/// Whether the placeholder state in an MFT/USN record's attributes differs
+/// from the row's stored `cloud_only` column. Paths without a row are left
+/// to the create/rename handling.
+pub fn stored_state_differs(conn: &Connection, path: &Path, attributes: u32) -> bool {
+    conn.query_row(
+        "SELECT cloud_only FROM entries WHERE path = ?1",
+        [path.to_string_lossy()],
+        |row| row.get::<_, bool>(0),
+    )
+    .is_ok_and(|stored| stored != is_placeholder_attrs(attributes))
+}
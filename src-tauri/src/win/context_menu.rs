@@ -16,6 +16,7 @@ use windows::Win32::UI::WindowsAndMessaging::{
 };
 
 use super::com_guard::{ComGuard, to_wide};
+use crate::long_path;
 
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 const ID_OPEN: u32 = 1;
@@ -214,6 +215,7 @@ fn invoke_shell_command(info: &ShellContextInfo, cmd_offset: u32) {
 fn open_paths(paths: &[String]) {
     for path in paths {
         let mut cmd = Command::new("cmd");
+        let path = long_path::shell_path(path);
         cmd.raw_arg(format!("/C start \"\" \"{}\"", path.replace('"', "")));
         cmd.creation_flags(CREATE_NO_WINDOW);
         let _ = cmd.spawn();
@@ -223,7 +225,7 @@ fn open_paths(paths: &[String]) {
 fn reveal_paths(paths: &[String]) {
     for path in paths {
         let _ = Command::new("explorer")
-            .arg(format!("/select,{}", path))
+            .arg(format!("/select,{}", long_path::shell_path(path)))
             .spawn();
     }
 }
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::MAX_PATH;
+use windows::Win32::Storage::FileSystem::{
+    FindClose, FindFirstFileNameW, FindFirstStreamW, FindNextFileNameW, FindNextStreamW,
+    FindStreamInfoStandard, WIN32_FIND_STREAM_DATA,
+};
+
+use super::com_guard::to_wide;
+
+/// Every name of the file across its volume (NTFS hard links), as full
+/// paths on the same drive as `path`.
+pub fn hardlink_paths(path: &Path) -> Vec<String> {
+    let path_str = path.to_string_lossy();
+    let drive: String = path_str.chars().take(2).collect();
+    if !drive.ends_with(':') {
+        return Vec::new();
+    }
+    let wide = to_wide(&path_str);
+    let mut names = Vec::new();
+    let mut buf = vec![0u16; 32 * MAX_PATH as usize];
+    unsafe {
+        let mut len = buf.len() as u32;
+        let Ok(handle) =
+            FindFirstFileNameW(PCWSTR(wide.as_ptr()), 0, &mut len, PWSTR(buf.as_mut_ptr()))
+        else {
+            return names;
+        };
+        loop {
+            let name = String::from_utf16_lossy(&buf[..len.saturating_sub(1) as usize]);
+            names.push(format!("{drive}{name}"));
+            len = buf.len() as u32;
+            if FindNextFileNameW(handle, &mut len, PWSTR(buf.as_mut_ptr())).is_err() {
+                break;
+            }
+        }
+        let _ = FindClose(handle);
+    }
+    names
+}
+
+/// Named data streams other than the default `::$DATA`, e.g.
+/// `Zone.Identifier` on downloads, with their sizes.
+pub fn alternate_streams(path: &Path) -> Vec<(String, u64)> {
+    let wide = to_wide(&path.to_string_lossy());
+    let mut streams = Vec::new();
+    unsafe {
+        let mut data = WIN32_FIND_STREAM_DATA::default();
+        let Ok(handle) = FindFirstStreamW(
+            PCWSTR(wide.as_ptr()),
+            FindStreamInfoStandard,
+            &mut data as *mut _ as *mut std::ffi::c_void,
+            0,
+        ) else {
+            return streams;
+        };
+        loop {
+            let end = data
+                .cStreamName
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(data.cStreamName.len());
+            let raw = String::from_utf16_lossy(&data.cStreamName[..end]);
+            // ":Zone.Identifier:$DATA" -> "Zone.Identifier"
+            let name = raw
+                .trim_start_matches(':')
+                .trim_end_matches(":$DATA")
+                .to_string();
+            if !name.is_empty() {
+                streams.push((name, data.StreamSize.max(0) as u64));
+            }
+            if FindNextStreamW(handle, &mut data as *mut _ as *mut std::ffi::c_void).is_err() {
+                break;
+            }
+        }
+        let _ = FindClose(handle);
+    }
+    streams
+}
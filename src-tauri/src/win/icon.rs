@@ -3,15 +3,15 @@ use std::mem;
 use windows::core::{Interface, PCWSTR};
 use windows::Win32::Foundation::SIZE;
 use windows::Win32::Graphics::Gdi::{
-    CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDIBits, SelectObject,
-    BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDIBits, GetObjectW,
+    SelectObject, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
 };
 use windows::Win32::Storage::FileSystem::{
     FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_NORMAL, FILE_FLAGS_AND_ATTRIBUTES,
 };
 use windows::Win32::UI::Shell::{
     IShellItemImageFactory, SHCreateItemFromParsingName, SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON,
-    SHGFI_SMALLICON, SHGFI_USEFILEATTRIBUTES, SIIGBF_ICONONLY,
+    SHGFI_SMALLICON, SHGFI_USEFILEATTRIBUTES, SIIGBF_ICONONLY, SIIGBF_THUMBNAILONLY,
 };
 use windows::Win32::Graphics::Gdi::HBITMAP;
 use windows::Win32::UI::WindowsAndMessaging::{DestroyIcon, DrawIconEx, HICON, DI_NORMAL};
@@ -37,7 +37,7 @@ pub fn load_icon_png(path: &str) -> Option<Vec<u8>> {
             .ok()?
     };
 
-    let rgba = hbitmap_to_rgba(hbitmap, ICON_SIZE);
+    let rgba = hbitmap_to_rgba(hbitmap, ICON_SIZE, ICON_SIZE);
     unsafe {
         let _ = DeleteObject(hbitmap);
     }
@@ -45,6 +45,45 @@ pub fn load_icon_png(path: &str) -> Option<Vec<u8>> {
     encode_png(ICON_SIZE as u32, ICON_SIZE as u32, &rgba?)
 }
 
+/// Content thumbnail (image, video frame, document page) from the shell's
+/// thumbnail cache, fitted within `size`×`size`. None for types without a
+/// thumbnail handler instead of falling back to the icon.
+pub fn load_thumbnail_png(path: &str, size: u32) -> Option<Vec<u8>> {
+    let _com = ComGuard::init().ok()?;
+
+    let wide = to_wide(path);
+    let item: windows::Win32::UI::Shell::IShellItem = unsafe {
+        SHCreateItemFromParsingName(PCWSTR(wide.as_ptr()), None).ok()?
+    };
+
+    let factory: IShellItemImageFactory = item.cast().ok()?;
+    let side = size as i32;
+    let hbitmap = unsafe {
+        factory
+            .GetImage(SIZE { cx: side, cy: side }, SIIGBF_THUMBNAILONLY)
+            .ok()?
+    };
+
+    // The bitmap keeps the source aspect ratio, so read its real dimensions.
+    let mut info = BITMAP::default();
+    let read = unsafe {
+        GetObjectW(
+            hbitmap,
+            mem::size_of::<BITMAP>() as i32,
+            Some(&mut info as *mut BITMAP as *mut _),
+        )
+    };
+    let (width, height) = (info.bmWidth, info.bmHeight.abs());
+    let rgba = (read != 0 && width > 0 && height > 0)
+        .then(|| hbitmap_to_rgba(hbitmap, width, height))
+        .flatten();
+    unsafe {
+        let _ = DeleteObject(hbitmap);
+    }
+
+    encode_png(width as u32, height as u32, &rgba?)
+}
+
 /// Fallback icon via SHGetFileInfo (extension only, no real file needed).
 pub fn load_icon_png_by_ext(ext: &str) -> Option<Vec<u8>> {
     let _com = ComGuard::init().ok()?;
@@ -103,7 +142,8 @@ fn get_shell_icon(
 /// Read pixels from HBITMAP returned by IShellItemImageFactory::GetImage.
 fn hbitmap_to_rgba(
     hbitmap: HBITMAP,
-    size: i32,
+    width: i32,
+    height: i32,
 ) -> Option<Vec<u8>> {
     unsafe {
         let hdc = CreateCompatibleDC(None);
@@ -114,8 +154,8 @@ fn hbitmap_to_rgba(
         let mut bmi = BITMAPINFO {
             bmiHeader: BITMAPINFOHEADER {
                 biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
-                biWidth: size,
-                biHeight: -size, // top-down
+                biWidth: width,
+                biHeight: -height, // top-down
                 biPlanes: 1,
                 biBitCount: 32,
                 biCompression: BI_RGB.0,
@@ -124,14 +164,14 @@ fn hbitmap_to_rgba(
             ..Default::default()
         };
 
-        let byte_count = (size * size * 4) as usize;
+        let byte_count = (width * height * 4) as usize;
         let mut buffer = vec![0u8; byte_count];
 
         let lines = GetDIBits(
             hdc,
             hbitmap,
             0,
-            size as u32,
+            height as u32,
             Some(buffer.as_mut_ptr() as *mut _),
             &mut bmi,
             DIB_RGB_COLORS,
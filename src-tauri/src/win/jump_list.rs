@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+use windows::core::{Interface, HSTRING, PCWSTR, PROPVARIANT};
+use windows::Win32::Storage::EnhancedStorage::PKEY_Title;
+use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+use windows::Win32::UI::Shell::Common::{IObjectArray, IObjectCollection};
+use windows::Win32::UI::Shell::PropertiesSystem::IPropertyStore;
+use windows::Win32::UI::Shell::{
+    DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW, ShellLink,
+};
+
+use super::com_guard::{ComGuard, to_wide};
+
+/// Quote one argument so `CommandLineToArgvW` / the CRT parse it back
+/// unchanged: backslashes before a quote are doubled, quotes escaped.
+pub fn quote_arg(arg: &str) -> String {
+    let mut out = String::from("\"");
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            '"' => {
+                out.extend(std::iter::repeat('\\').take(backslashes * 2 + 1));
+                out.push('"');
+                backslashes = 0;
+            }
+            _ => {
+                out.extend(std::iter::repeat('\\').take(backslashes));
+                out.push(ch);
+                backslashes = 0;
+            }
+        }
+    }
+    out.extend(std::iter::repeat('\\').take(backslashes * 2));
+    out.push('"');
+    out
+}
+
+fn search_link(
+    exe: &str,
+    flag: &str,
+    title: &str,
+    query: &str,
+) -> windows::core::Result<IShellLinkW> {
+    let exe_w = to_wide(exe);
+    let args_w = to_wide(&format!("{flag} {}", quote_arg(query)));
+    let desc_w = to_wide(query);
+    unsafe {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+        link.SetPath(PCWSTR(exe_w.as_ptr()))?;
+        link.SetArguments(PCWSTR(args_w.as_ptr()))?;
+        link.SetDescription(PCWSTR(desc_w.as_ptr()))?;
+        link.SetIconLocation(PCWSTR(exe_w.as_ptr()), 0)?;
+        // Jump list items show PKEY_Title, not the link's file name.
+        let store: IPropertyStore = link.cast()?;
+        store.SetValue(&PKEY_Title, &PROPVARIANT::from(title))?;
+        store.Commit()?;
+        Ok(link)
+    }
+}
+
+/// Arguments of items the user removed from the jump list; the shell
+/// rejects a list that adds them back.
+fn removed_arguments(removed: &IObjectArray) -> HashSet<String> {
+    let mut args = HashSet::new();
+    unsafe {
+        let count = removed.GetCount().unwrap_or(0);
+        for i in 0..count {
+            let Ok(link) = removed.GetAt::<IShellLinkW>(i) else {
+                continue;
+            };
+            let mut buf = [0u16; 1024];
+            if link.GetArguments(&mut buf).is_ok() {
+                let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+                args.insert(String::from_utf16_lossy(&buf[..end]));
+            }
+        }
+    }
+    args
+}
+
+/// Replace the taskbar jump list with `saved` (title, query) and `recent`
+/// queries, each item relaunching the app with `flag <query>`.
+pub fn update(flag: &str, saved: &[(String, String)], recent: &[String]) -> Result<(), String> {
+    let _com = ComGuard::init()?;
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let exe = exe.to_string_lossy().to_string();
+    unsafe {
+        let list: ICustomDestinationList =
+            CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)
+                .map_err(|e| format!("CoCreateInstance(DestinationList): {e}"))?;
+        let mut slots = 0u32;
+        let removed: IObjectArray = list
+            .BeginList(&mut slots)
+            .map_err(|e| format!("BeginList: {e}"))?;
+        let removed = removed_arguments(&removed);
+        let mut remaining = slots as usize;
+
+        let recent: Vec<(String, String)> = recent.iter().map(|q| (q.clone(), q.clone())).collect();
+        for (category, items) in [("Saved Searches", saved), ("Recent Searches", &recent[..])] {
+            let collection: IObjectCollection =
+                CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)
+                    .map_err(|e| format!("CoCreateInstance(EnumerableObjectCollection): {e}"))?;
+            let mut added = 0;
+            for (title, query) in items {
+                if remaining == 0 {
+                    break;
+                }
+                if removed.contains(&format!("{flag} {}", quote_arg(query))) {
+                    continue;
+                }
+                let Ok(link) = search_link(&exe, flag, title, query) else {
+                    continue;
+                };
+                if collection.AddObject(&link).is_ok() {
+                    added += 1;
+                    remaining -= 1;
+                }
+            }
+            if added == 0 {
+                continue;
+            }
+            let array: IObjectArray = collection.cast().map_err(|e| e.to_string())?;
+            list.AppendCategory(&HSTRING::from(category), &array)
+                .map_err(|e| format!("AppendCategory({category}): {e}"))?;
+        }
+        list.CommitList().map_err(|e| format!("CommitList: {e}"))
+    }
+}
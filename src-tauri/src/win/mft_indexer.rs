@@ -1,21 +1,23 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::mem;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use rayon::prelude::*;
 
-use rusqlite::params;
 use tauri::AppHandle;
 
-use super::path_resolver::PathResolver;
+use super::path_resolver::{mount_table, PathResolver};
 use super::volume;
 use crate::{
     cached_effective_ignore_rules,
-    cleanup_entries_gc_tables, db_connection, emit_index_progress, emit_index_state, emit_index_updated,
+    cleanup_entries_gc_tables, db_connection, delete_stale_run_rows, emit_index_progress, emit_index_state, emit_index_updated,
     get_meta, invalidate_search_caches, matches_ignore_pattern, now_epoch,
+    record_full_index_duration,
     mem_search::CompactEntry,
+    name_norm,
     refresh_and_emit_status_counts,
     restore_normal_pragmas, set_indexing_pragmas, set_meta, set_progress, set_state,
     update_status_counts, upsert_rows,
@@ -26,7 +28,6 @@ use windows::Win32::Foundation::HANDLE;
 use windows::Win32::System::IO::DeviceIoControl;
 use windows::Win32::System::Ioctl::FSCTL_ENUM_USN_DATA;
 
-const MFT_BATCH_SIZE: usize = 50_000;
 const EMIT_INTERVAL: Duration = Duration::from_millis(200);
 const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
 
@@ -48,55 +49,100 @@ struct MftRecord {
     parent_frn: u64,
     name: String,
     attributes: u32,
-    timestamp: Option<i64>,
+    /// Raw FILETIME (100 ns intervals since 1601) of the record.
+    filetime: Option<i64>,
 }
 
 /// Lightweight file entry — directories go into PathResolver instead.
 struct MftFileEntry {
     parent_frn: u64,
     name: String,
-    timestamp: Option<i64>,
+    filetime: Option<i64>,
 }
 
-pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, String> {
-    use std::sync::atomic::Ordering as AtomicOrdering;
-
-    let started = Instant::now();
-    let ts = || format!("{:.1}s", started.elapsed().as_secs_f32());
-    eprintln!("[win/mft +{}] starting MFT scan", ts());
-
-    // Open volume FIRST — requires admin privileges.
-    // Do NOT modify state/DB before this succeeds, so a failed open_volume
-    // leaves index_complete and status untouched.
-    let vol = volume::open_volume('C')?;
-    eprintln!("[win/mft +{}] volume opened", ts());
-
-    state
-        .indexing_active
-        .store(true, AtomicOrdering::Release);
+/// Ignore-rule views shared by every volume scanned in one MFT pass.
+struct ScanFilters<'a> {
+    ignored_roots: &'a [std::path::PathBuf],
+    all_skip_names: Vec<&'a str>,
+    glob_patterns: Vec<&'a IgnorePattern>,
+    skip_path_infixes: Vec<String>,
+    skip_path_suffixes: Vec<String>,
+}
 
-    // Mark index as incomplete — cleared when background DB finalize succeeds
-    if let Ok(c) = db_connection(&state.db_path) {
-        let _ = set_meta(&c, "index_complete", "0");
+impl<'a> ScanFilters<'a> {
+    fn new(
+        ignored_roots: &'a [std::path::PathBuf],
+        ignored_patterns: &'a [IgnorePattern],
+    ) -> Self {
+        // Enhanced skip names: BUILTIN_SKIP_NAMES + AnySegment patterns from pathignore
+        let mut all_skip_names: Vec<&str> = BUILTIN_SKIP_NAMES.to_vec();
+        for p in ignored_patterns {
+            if let IgnorePattern::AnySegment { segment, .. } = p {
+                all_skip_names.push(segment.as_str());
+            }
+        }
+        // Glob patterns for pruned-subtree skip checks
+        let glob_patterns = ignored_patterns
+            .iter()
+            .filter(|p| matches!(p, IgnorePattern::Glob(_)))
+            .collect();
+        // Backslash versions of BUILTIN_SKIP_PATHS for Windows path matching
+        let skip_path_infixes = crate::BUILTIN_SKIP_PATHS.iter()
+            .map(|p| format!("\\{}\\", p.replace('/', "\\")))
+            .collect();
+        let skip_path_suffixes = crate::BUILTIN_SKIP_PATHS.iter()
+            .map(|p| format!("\\{}", p.replace('/', "\\")))
+            .collect();
+        Self {
+            ignored_roots,
+            all_skip_names,
+            glob_patterns,
+            skip_path_infixes,
+            skip_path_suffixes,
+        }
     }
+}
 
-    {
-        let mut status = state.status.lock();
-        status.state = IndexState::Indexing;
-        status.message = None;
-        status.scanned = 0;
-        status.indexed = 0;
-        status.current_path.clear();
-    }
-    emit_index_state(app, "Indexing", None);
+/// Everything one volume contributes to an MFT pass.
+struct VolumeScan {
+    letter: char,
+    vol: volume::VolumeHandle,
+    /// Directories first, then files.
+    entries: Vec<CompactEntry>,
+    /// FRN → directory path for the USN watcher.
+    frn_cache: HashMap<u64, String>,
+    /// Directory FRNs outside the scanned root (USN watcher skip set).
+    outside_scan_frns: HashSet<u64>,
+    scanned: u64,
+    filtered_skip: u64,
+}
+
+/// Enumerate one volume's MFT and resolve the entries under `root`.
+/// `on_dirs_ready` runs after directories are resolved but before files, so
+/// the primary volume can publish a progressive Ready.
+#[allow(clippy::too_many_arguments)]
+fn scan_volume(
+    state: &AppState,
+    app: &AppHandle,
+    letter: char,
+    vol: volume::VolumeHandle,
+    root: &str,
+    filters: &ScanFilters<'_>,
+    started: Instant,
+    on_dirs_ready: impl FnOnce(&[CompactEntry], u64),
+) -> Result<VolumeScan, String> {
+    let ts = || format!("{:.1}s", started.elapsed().as_secs_f32());
 
     // ── Pass 1: Enumerate MFT — dirs into resolver, files into Vec ──
     let pass1_started = Instant::now();
-    let mut resolver = PathResolver::with_capacity("C:", 300_000);
+    let mut resolver = PathResolver::with_capacity(&format!("{letter}:"), 300_000);
     let mut total_records: u64 = 0;
     let mut total_dirs: u64 = 0;
     let mut dir_entries: Vec<(u64, Option<i64>)> = Vec::with_capacity(300_000);
     let mut file_entries: Vec<MftFileEntry> = Vec::with_capacity(2_500_000);
+    // (parent_frn, name) of files-on-demand placeholders — rare, so kept apart
+    // instead of widening every MftFileEntry.
+    let mut placeholder_files: Vec<(u64, String)> = Vec::new();
     let mut pass1_last_emit = Instant::now();
 
     enumerate_mft(vol.raw(), |record| {
@@ -105,18 +151,21 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
 
         if is_dir {
             total_dirs += 1;
-            dir_entries.push((record.frn, record.timestamp));
+            dir_entries.push((record.frn, record.filetime));
             resolver.add_record(record.frn, record.parent_frn, record.name);
         } else {
+            if super::cloud::is_placeholder_attrs(record.attributes) {
+                placeholder_files.push((record.parent_frn, record.name.clone()));
+            }
             file_entries.push(MftFileEntry {
                 parent_frn: record.parent_frn,
                 name: record.name,
-                timestamp: record.timestamp,
+                filetime: record.filetime,
             });
         }
 
         if pass1_last_emit.elapsed() >= EMIT_INTERVAL {
-            let msg = format!("Reading MFT... ({total_records} records)");
+            let msg = format!("Reading MFT ({letter}:)... ({total_records} records)");
             set_progress(state, 0, 0, &msg);
             emit_index_progress(app, 0, 0, msg);
             pass1_last_emit = Instant::now();
@@ -124,53 +173,46 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
     })?;
 
     eprintln!(
-        "[win/mft +{}] pass1 done: {} records ({} dirs + {} files) in {}ms",
+        "[win/mft +{}] {letter}: pass1 done: {} records ({} dirs + {} files) in {}ms",
         ts(), total_records, total_dirs, file_entries.len(),
         pass1_started.elapsed().as_millis()
     );
 
-    // ── Pass 1.5: Compute effective ignore rules + collect home subtree dirs ──
+    // ── Pass 1.5: Collect the root's subtree dirs ──
     let subtree_started = Instant::now();
-    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
-
-    // Build enhanced skip names: BUILTIN_SKIP_NAMES + AnySegment patterns from pathignore
-    let extra_segment_names: Vec<String> = ignored_patterns.iter().filter_map(|p| {
-        if let IgnorePattern::AnySegment { segment, .. } = p { Some(segment.clone()) } else { None }
-    }).collect();
-    let mut all_skip_names: Vec<&str> = BUILTIN_SKIP_NAMES.to_vec();
-    for name in &extra_segment_names {
-        all_skip_names.push(name.as_str());
-    }
 
     // Find FRNs for pathignore root directories to prune entire subtrees
     let mut skip_frns: HashSet<u64> = HashSet::new();
-    for root in &ignored_roots {
-        let root_win = root.to_string_lossy().replace('/', "\\");
+    for ignored in filters.ignored_roots {
+        let root_win = mount_table()
+            .canonical(&ignored.to_string_lossy().replace('/', "\\"))
+            .into_owned();
+        if volume::drive_letter_of(Path::new(&root_win)) != Some(letter) {
+            continue;
+        }
         if let Some(frn) = resolver.find_frn_by_path(&root_win) {
             skip_frns.insert(frn);
         }
     }
 
-    let scan_str = state.scan_root.to_string_lossy().to_string();
-    let scan_path_win = scan_str.replace('/', "\\");
-
-    let scan_frn = resolver.find_frn_by_path(&scan_path_win);
-    let dir_subtree = match scan_frn {
+    let root_win = root.replace('/', "\\");
+    let root_frn = resolver.find_frn_by_path(&root_win);
+    let dir_subtree = match root_frn {
         Some(frn) => {
-            let subtree = resolver.collect_subtree_pruned(frn, &all_skip_names, &skip_frns);
+            let subtree = resolver.collect_subtree_pruned(frn, &filters.all_skip_names, &skip_frns);
             eprintln!(
-                "[win/mft +{}] pass1.5: scan_root FRN={} dir_subtree={} dirs \
+                "[win/mft +{}] {letter}: pass1.5: root FRN={} dir_subtree={} dirs \
                  (pruned from {} total dirs) skip_names={} skip_frns={} in {}ms",
                 ts(), frn, subtree.len(), total_dirs,
-                all_skip_names.len(), skip_frns.len(),
+                filters.all_skip_names.len(), skip_frns.len(),
                 subtree_started.elapsed().as_millis()
             );
             subtree
         }
         None => {
             eprintln!(
-                "[win/mft +{}] pass1.5: scan_root not found in MFT ({}), using all dirs",
-                ts(), scan_path_win
+                "[win/mft +{}] {letter}: pass1.5: root not found in MFT ({}), using all dirs",
+                ts(), root_win
             );
             dir_entries.iter().map(|(frn, _)| *frn).collect()
         }
@@ -184,42 +226,28 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         let _ = resolver.resolve(dir_frn);
     }
     eprintln!(
-        "[win/mft +{}] pre-resolved {} dir paths in {}ms",
+        "[win/mft +{}] {letter}: pre-resolved {} dir paths in {}ms",
         ts(), dir_subtree.len(), preresolve_started.elapsed().as_millis()
     );
 
     // All subtree dirs are now in path_cache — frn_map no longer needed
     resolver.drop_frn_map();
 
-    // ── Pass 2: Resolve paths, filter, stat → collect into memory ──
+    // ── Pass 2: Resolve paths, filter → collect into memory ──
     let pass2_started = Instant::now();
 
     // Get path_cache early — all subtree dirs pre-resolved in pass 1.5
     let path_cache = resolver.path_cache();
 
-    // Pre-extract Glob patterns for pruned-subtree skip checks
-    let glob_patterns: Vec<&IgnorePattern> = ignored_patterns
-        .iter()
-        .filter(|p| matches!(p, IgnorePattern::Glob(_)))
-        .collect();
-
-    // Pre-compute backslash versions of BUILTIN_SKIP_PATHS for Windows path matching
-    let skip_path_infixes: Vec<String> = crate::BUILTIN_SKIP_PATHS.iter()
-        .map(|p| format!("\\{}\\", p.replace('/', "\\")))
-        .collect();
-    let skip_path_suffixes: Vec<String> = crate::BUILTIN_SKIP_PATHS.iter()
-        .map(|p| format!("\\{}", p.replace('/', "\\")))
-        .collect();
-
     // --- Process directories (parallel, mtime from USN timestamp) ---
     let dir_results: Vec<CompactEntry> = dir_entries
         .par_iter()
         .filter(|(frn, _)| dir_subtree.contains(frn))
-        .filter_map(|(frn, timestamp)| {
+        .filter_map(|(frn, filetime)| {
             let full_path = path_cache.get(frn)?;
             if should_skip_dir_in_pruned_subtree(
-                full_path, &glob_patterns,
-                &skip_path_infixes, &skip_path_suffixes,
+                full_path, &filters.glob_patterns,
+                &filters.skip_path_infixes, &filters.skip_path_suffixes,
             ) {
                 return None;
             }
@@ -231,7 +259,10 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
             if name.is_empty() { return None; }
             Some(CompactEntry {
                 name: name.to_string(), dir: dir.to_string(),
-                is_dir: true, ext: None, mtime: *timestamp, size: None,
+                is_dir: true, ext: None,
+                mtime: filetime.map(filetime_to_unix),
+                mtime_nsec: filetime.map(filetime_subsec_nanos),
+                size: None, cloud_only: false,
             })
         })
         .collect();
@@ -239,50 +270,25 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
     let dirs_in_subtree = dir_entries.iter().filter(|(frn, _)| dir_subtree.contains(frn)).count() as u64;
     let dir_indexed = dir_results.len() as u64;
     let mut scanned: u64 = dirs_in_subtree;
-    let mut indexed: u64 = dir_indexed;
     let mut filtered_skip: u64 = dirs_in_subtree - dir_indexed;
 
     eprintln!(
-        "[win/mft +{}] dirs done: scanned={scanned} indexed={indexed} \
+        "[win/mft +{}] {letter}: dirs done: scanned={scanned} indexed={dir_indexed} \
          skip={filtered_skip} resolve_fail=0 in {}ms",
         ts(), pass2_started.elapsed().as_millis()
     );
 
-    // ── Progressive Ready: build partial MemIndex from dirs only ──
-    let early_cap = super::EARLY_MEM_INDEX_LIMIT.min(dir_results.len());
-    eprintln!(
-        "[win/mft +{}] building partial MemIndex ({} of {} dirs)...",
-        ts(),
-        early_cap,
-        dir_results.len()
-    );
-    let early_entries: Vec<CompactEntry> = dir_results.iter().take(early_cap).cloned().collect();
-    let early_idx = Arc::new(crate::mem_search::MemIndex::build(early_entries));
-    eprintln!("[win/mft +{}] partial MemIndex built", ts());
-    *state.mem_index.write() = Some(Arc::clone(&early_idx));
-    {
-        let mut status = state.status.lock();
-        status.state = IndexState::Ready;
-        status.permission_errors = 0;
-        status.scanned = scanned;
-        status.indexed = indexed;
-        status.message = None;
-        status.entries_count = indexed;
-        status.last_updated = Some(now_epoch());
-    }
-    emit_index_progress(app, scanned, indexed, String::new());
-    emit_index_updated(app, indexed, now_epoch(), 0);
-    emit_index_state(app, "Ready", None);
-    eprintln!(
-        "[win/mft +{}] progressive Ready emitted ({indexed} dirs)",
-        ts()
-    );
+    on_dirs_ready(&dir_results, scanned);
 
-    let mut mem_entries: Vec<CompactEntry> = Vec::with_capacity(dir_subtree.len() + file_entries.len());
-    mem_entries.extend(dir_results);
+    let mut entries: Vec<CompactEntry> = Vec::with_capacity(dir_results.len() + file_entries.len());
+    entries.extend(dir_results);
 
-    // --- Process files (parallel + stat) ---
+    // --- Process files (parallel) ---
     let pass2_files_started = Instant::now();
+    let placeholder_keys: HashSet<(u64, &str)> = placeholder_files
+        .iter()
+        .map(|(parent_frn, name)| (*parent_frn, name.as_str()))
+        .collect();
 
     let file_results: Vec<CompactEntry> = file_entries
         .par_iter()
@@ -290,7 +296,7 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         .filter_map(|entry| {
             let parent_path = path_cache.get(&entry.parent_frn)?;
             if should_skip_file_in_pruned_subtree(
-                parent_path, &entry.name, &glob_patterns,
+                parent_path, &entry.name, &filters.glob_patterns,
             ) {
                 return None;
             }
@@ -308,8 +314,11 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
                 dir: parent_path.clone(),
                 is_dir: false,
                 ext,
-                mtime: entry.timestamp,
+                mtime: entry.filetime.map(filetime_to_unix),
+                mtime_nsec: entry.filetime.map(filetime_subsec_nanos),
                 size: None,
+                cloud_only: !placeholder_keys.is_empty()
+                    && placeholder_keys.contains(&(entry.parent_frn, entry.name.as_str())),
             })
         })
         .collect();
@@ -320,18 +329,17 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         .count() as u64;
     let file_indexed = file_results.len() as u64;
     scanned += files_in_subtree;
-    indexed += file_indexed;
     filtered_skip += files_in_subtree - file_indexed;
-    mem_entries.extend(file_results);
+    entries.extend(file_results);
 
     eprintln!(
-        "[win/mft +{}] files done: {} files in subtree, indexed={file_indexed} skip={} in {}ms",
+        "[win/mft +{}] {letter}: files done: {} files in subtree, indexed={file_indexed} skip={} in {}ms",
         ts(), files_in_subtree,
         files_in_subtree - file_indexed,
         pass2_files_started.elapsed().as_millis()
     );
 
-    // Collect FRNs of directories known to be outside scan_root.
+    // Collect FRNs of directories known to be outside the root.
     // These pre-populate USN watcher's skip set to avoid syscalls.
     let outside_scan_frns: HashSet<u64> = dir_entries
         .iter()
@@ -339,18 +347,159 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         .map(|(frn, _)| *frn)
         .collect();
 
-    // Free large temporaries before building MemIndex
+    // Free large temporaries before the caller builds MemIndex
     drop(file_entries);   // ~100MB+
     drop(dir_entries);    // ~2MB
     drop(dir_subtree);    // ~2MB
 
-    let frn_cache = resolver.into_path_cache(); // also drops resolver.frn_map
+    Ok(VolumeScan {
+        letter,
+        vol,
+        entries,
+        frn_cache: resolver.into_path_cache(), // also drops resolver.frn_map
+        outside_scan_frns,
+        scanned,
+        filtered_skip,
+    })
+}
+
+/// Index scan_root's volume from its MFT. When scan_root is a whole drive
+/// (the default `C:\`), every other fixed NTFS volume (D:, E:, ...) is
+/// indexed whole too; a folder root (e.g. a profile's `~\Projects`) keeps
+/// the scan to that folder, and extra roots on other volumes are left to
+/// `.pathindexing`. Only the primary volume is required: secondary volumes
+/// that can't be opened are logged and skipped.
+pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, String> {
+    use std::sync::atomic::Ordering as AtomicOrdering;
+
+    let started = Instant::now();
+    let ts = || format!("{:.1}s", started.elapsed().as_secs_f32());
+    eprintln!("[win/mft +{}] starting MFT scan", ts());
+
+    // A scan root given through a mounted folder or volume GUID path is
+    // scanned, and its rows spelled, on the volume's drive letter.
+    let scan_str = mount_table()
+        .canonical(&state.scan_root.to_string_lossy().replace('/', "\\"))
+        .into_owned();
+    let primary = volume::drive_letter_of(Path::new(&scan_str)).unwrap_or('C');
+
+    // Open volume FIRST — requires admin privileges.
+    // Do NOT modify state/DB before this succeeds, so a failed open_volume
+    // leaves index_complete and status untouched.
+    let vol = volume::open_volume(primary)?;
+    eprintln!("[win/mft +{}] volume {primary}: opened", ts());
+
+    state
+        .indexing_active
+        .store(true, AtomicOrdering::Release);
+
+    // Mark index as incomplete — cleared when background DB finalize succeeds
+    if let Ok(c) = db_connection(&state.db_path) {
+        let _ = set_meta(&c, "index_complete", "0");
+    }
+
+    {
+        let mut status = state.status.lock();
+        status.state = IndexState::Indexing;
+        status.message = None;
+        status.scanned = 0;
+        status.indexed = 0;
+        status.current_path.clear();
+    }
+    emit_index_state(app, "Indexing", None);
+
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+    let filters = ScanFilters::new(&ignored_roots, &ignored_patterns);
+
+    // ── Primary volume, with progressive Ready once its directories resolve ──
+    let mut early_idx: Option<Arc<crate::mem_search::MemIndex>> = None;
+    let primary_scan = scan_volume(state, app, primary, vol, &scan_str, &filters, started, |dir_results, scanned| {
+        let indexed = dir_results.len() as u64;
+        let early_cap = super::EARLY_MEM_INDEX_LIMIT.min(dir_results.len());
+        eprintln!(
+            "[win/mft +{}] building partial MemIndex ({} of {} dirs)...",
+            ts(),
+            early_cap,
+            dir_results.len()
+        );
+        let early_entries: Vec<CompactEntry> = dir_results.iter().take(early_cap).cloned().collect();
+        let idx = Arc::new(crate::mem_search::MemIndex::build(early_entries));
+        eprintln!("[win/mft +{}] partial MemIndex built", ts());
+        *state.mem_index.write() = Some(Arc::clone(&idx));
+        state.search_result_cache.lock().clear();
+        early_idx = Some(idx);
+        {
+            let mut status = state.status.lock();
+            status.state = IndexState::Ready;
+            status.permission_errors = 0;
+            status.scanned = scanned;
+            status.indexed = indexed;
+            status.message = None;
+            status.entries_count = indexed;
+            status.last_updated = Some(now_epoch());
+        }
+        emit_index_progress(app, scanned, indexed, String::new());
+        emit_index_updated(app, indexed, now_epoch(), 0);
+        emit_index_state(app, "Ready", None);
+        eprintln!(
+            "[win/mft +{}] progressive Ready emitted ({indexed} dirs)",
+            ts()
+        );
+    })?;
+
+    let mut scanned = primary_scan.scanned;
+    let mut indexed = primary_scan.entries.len() as u64;
+    let mut filtered_skip = primary_scan.filtered_skip;
+    let mut volume_scans = vec![primary_scan];
+
+    // ── Secondary volumes: whole-volume roots, same filters ──
+    let secondary_volumes = if volume::is_whole_volume_root(&scan_str) {
+        volume::fixed_ntfs_volumes()
+    } else {
+        Vec::new()
+    };
+    for letter in secondary_volumes {
+        if letter == primary {
+            continue;
+        }
+        let sec_vol = match volume::open_volume(letter) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("[win/mft +{}] skipping volume {letter}: {e}", ts());
+                continue;
+            }
+        };
+        match scan_volume(state, app, letter, sec_vol, &format!("{letter}:\\"), &filters, started, |_, _| {}) {
+            Ok(scan) => {
+                scanned += scan.scanned;
+                indexed += scan.entries.len() as u64;
+                filtered_skip += scan.filtered_skip;
+                {
+                    let mut status = state.status.lock();
+                    status.scanned = scanned;
+                    status.indexed = indexed;
+                }
+                emit_index_progress(app, scanned, indexed, String::new());
+                volume_scans.push(scan);
+            }
+            Err(e) => eprintln!("[win/mft +{}] volume {letter} scan failed: {e}", ts()),
+        }
+    }
+
+    let mut mem_entries: Vec<CompactEntry> = Vec::with_capacity(indexed as usize);
+    let mut frn_caches: HashMap<char, (HashMap<u64, String>, HashSet<u64>)> = HashMap::new();
+    let mut volumes: Vec<(char, volume::VolumeHandle)> = Vec::with_capacity(volume_scans.len());
+    for scan in volume_scans {
+        mem_entries.extend(scan.entries);
+        frn_caches.insert(scan.letter, (scan.frn_cache, scan.outside_scan_frns));
+        volumes.push((scan.letter, scan.vol));
+    }
 
     let entry_count = mem_entries.len();
     eprintln!(
-        "[win/mft +{}] full index ready: indexed={indexed} skip={filtered_skip} \
+        "[win/mft +{}] full index ready: volumes={} indexed={indexed} skip={filtered_skip} \
          entries={entry_count}",
-        ts(),
+        ts(), volumes.len(),
     );
 
     // Update status counts immediately (files done)
@@ -367,11 +516,10 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
     // ── Background: MemIndex build + DB upsert + USN watcher start ──
     let bg_state = state.clone();
     let bg_app = app.clone();
-    let bg_vol = vol;
 
     eprintln!(
-        "[win/mft +{}] passing {} FRN path entries + {} outside-scan FRNs to USN watcher",
-        ts(), frn_cache.len(), outside_scan_frns.len()
+        "[win/mft +{}] passing FRN caches for {} volume(s) to USN watcher",
+        ts(), frn_caches.len()
     );
 
     let bg_started = started;
@@ -384,6 +532,8 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         let mem_idx = Arc::new(crate::mem_search::MemIndex::build(mem_entries));
         eprintln!("[win/mft/bg +{}] full MemIndex built", ts());
         *bg_state.mem_index.write() = Some(Arc::clone(&mem_idx));
+        // Results cached from the partial index would hide the rest.
+        bg_state.search_result_cache.lock().clear();
 
         // DB upsert
         eprintln!("[win/mft/bg +{}] starting DB upsert ({entry_count} entries)", ts());
@@ -392,12 +542,7 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         let bg_ok = match bulk_result {
             Ok((conn, current_run_id)) => {
                 if let Err(e) = background_db_finalize(
-                    conn, &bg_state, &bg_app, &bg_vol, current_run_id, entry_count > 0, bg_started,
-                    || {
-                        drop(mem_idx);
-                        *bg_state.mem_index.write() = None;
-                        eprintln!("[win/mft/bg +{}] MemIndex freed (name index ready, building remaining)", format!("{:.1}s", bg_started.elapsed().as_secs_f32()));
-                    },
+                    conn, &bg_state, &bg_app, &volumes, current_run_id, entry_count > 0, bg_started,
                 ) {
                     eprintln!("[win/mft/bg +{}] DB finalize FAILED: {e}", ts());
                     false
@@ -409,6 +554,7 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
                 eprintln!("[win/mft/bg +{}] DB bulk insert FAILED: {e}", ts());
                 drop(mem_idx);
                 *bg_state.mem_index.write() = None;
+                bg_state.search_result_cache.lock().clear();
                 false
             }
         };
@@ -430,10 +576,12 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
         }
         eprintln!("[win/mft/bg +{}] background work done (ok={})", ts(), bg_ok);
 
-        if let Err(e) = super::usn_watcher::start(bg_app.clone(), bg_state.clone(), frn_cache, outside_scan_frns) {
+        if let Err(e) = super::usn_watcher::start(bg_app.clone(), bg_state.clone(), frn_caches) {
             eprintln!("[win/mft/bg +{}] USN watcher failed ({e}), trying RDCW fallback", format!("{:.1}s", bg_started.elapsed().as_secs_f32()));
-            if let Err(e2) = super::rdcw_watcher::start(bg_app, bg_state) {
-                eprintln!("[win/mft/bg] RDCW watcher also failed ({e2}), no live updates");
+            if let Err(e2) = super::rdcw_watcher::start(bg_app.clone(), bg_state.clone()) {
+                eprintln!("[win/mft/bg] RDCW watcher also failed ({e2}), polling instead");
+                let poll_roots = super::nonadmin_indexer::compute_watch_roots(&bg_state);
+                crate::poll_watcher::start(Some(bg_app), bg_state, poll_roots);
             }
         }
     });
@@ -450,7 +598,7 @@ pub fn scan_mft(state: &AppState, app: &AppHandle) -> Result<MftScanResult, Stri
 /// Much faster than per-file fs::metadata() because it reads one directory listing at a time.
 fn build_dir_stat_cache(
     entries: &[CompactEntry],
-) -> HashMap<String, HashMap<String, (i64, i64)>> {
+) -> HashMap<String, HashMap<String, (i64, i64, u32)>> {
     use windows::Win32::Storage::FileSystem::{
         FindClose, FindFirstFileW, FindNextFileW, WIN32_FIND_DATAW,
     };
@@ -466,7 +614,7 @@ fn build_dir_stat_cache(
     let dir_list: Vec<&str> = unique_dirs.into_iter().collect();
 
     // Parallel enumeration via rayon
-    let results: Vec<(String, HashMap<String, (i64, i64)>)> = dir_list
+    let results: Vec<(String, HashMap<String, (i64, i64, u32)>)> = dir_list
         .par_iter()
         .filter_map(|&dir_path| {
             let pattern = format!("{}\\*", dir_path);
@@ -480,7 +628,7 @@ fn build_dir_stat_cache(
                 Err(_) => return None,
             };
 
-            let mut dir_map: HashMap<String, (i64, i64)> = HashMap::new();
+            let mut dir_map: HashMap<String, (i64, i64, u32)> = HashMap::new();
 
             loop {
                 let name = wide_name_to_string(&find_data.cFileName);
@@ -491,8 +639,10 @@ fn build_dir_stat_cache(
                             | (find_data.nFileSizeLow as i64);
                         let ft = ((find_data.ftLastWriteTime.dwHighDateTime as i64) << 32)
                             | (find_data.ftLastWriteTime.dwLowDateTime as i64);
-                        let mtime = filetime_to_unix(ft);
-                        dir_map.insert(name.to_lowercase(), (size, mtime));
+                        dir_map.insert(
+                            name.to_lowercase(),
+                            (size, filetime_to_unix(ft), filetime_subsec_nanos(ft)),
+                        );
                     }
                 }
 
@@ -533,13 +683,18 @@ fn background_db_bulk_insert(
     let current_run_id = last_run_id + 1;
     let indexed_at = now_epoch();
 
-    // Drop indexes for faster bulk insert
+    // Drop indexes for faster bulk insert. idx_entries_run_id stays: the
+    // stale-row sweep in Phase 2 reads it.
     let _ = conn.execute_batch(
         r#"
         DROP INDEX IF EXISTS idx_entries_dir_ext_name_nocase;
         DROP INDEX IF EXISTS idx_entries_mtime;
         DROP INDEX IF EXISTS idx_entries_name_nocase;
+        DROP INDEX IF EXISTS idx_entries_name_norm;
         DROP INDEX IF EXISTS idx_entries_ext_name;
+        DROP INDEX IF EXISTS idx_entries_ext_size;
+        DROP INDEX IF EXISTS idx_entries_ext_mtime;
+        DROP INDEX IF EXISTS idx_entries_name_mtime;
         "#,
     );
     eprintln!("[win/mft/bg +{}] indexes dropped", ts());
@@ -553,36 +708,41 @@ fn background_db_bulk_insert(
     );
 
     let upsert_started = Instant::now();
+    let batch_size = state.index_tuning.read().batch_size;
 
-    for chunk in entries.chunks(MFT_BATCH_SIZE) {
+    for chunk in entries.chunks(batch_size) {
         let chunk_rows: Vec<IndexRow> = chunk
             .par_iter()
             .map(|entry| {
-                let (size, mtime) = if entry.is_dir {
+                let (size, mtime, mtime_nsec) = if entry.is_dir {
                     // Dirs: use USN timestamp (already set in mtime)
-                    (None, entry.mtime)
+                    (None, entry.mtime, entry.mtime_nsec)
                 } else if let Some(dir_cache) = dir_stat_cache.get(&entry.dir) {
                     // Files: lookup from FindFirstFileW cache
-                    if let Some(&(sz, mt)) = dir_cache.get(&entry.name.to_lowercase()) {
-                        (Some(sz), Some(mt))
+                    if let Some(&(sz, mt, ns)) = dir_cache.get(&entry.name.to_lowercase()) {
+                        (Some(sz), Some(mt), Some(ns))
                     } else {
                         // Not found in cache — use USN timestamp, no size
-                        (None, entry.mtime)
+                        (None, entry.mtime, entry.mtime_nsec)
                     }
                 } else {
                     // Dir enumeration failed — use USN timestamp fallback
-                    (None, entry.mtime)
+                    (None, entry.mtime, entry.mtime_nsec)
                 };
                 IndexRow {
                     path: entry.path(),
                     name: entry.name.clone(),
                     dir: entry.dir.clone(),
+                    name_norm: name_norm::fold(&entry.name),
+                    dir_norm: name_norm::fold(&entry.dir),
                     is_dir: if entry.is_dir { 1 } else { 0 },
                     ext: entry.ext.clone(),
                     mtime,
+                    mtime_nsec: mtime_nsec.map(i64::from),
                     size,
                     indexed_at,
                     run_id: current_run_id,
+                    cloud_only: entry.cloud_only,
                 }
             })
             .collect();
@@ -594,34 +754,21 @@ fn background_db_bulk_insert(
 }
 
 /// Phase 2: Cleanup stale rows, recreate indexes, save USN position.
-/// `free_mem_index` is called after the primary name index is built, allowing
-/// MemIndex to be freed while remaining indexes are created (reduces peak memory).
+/// The MemIndex stays resident afterwards; the watcher keeps it current.
 fn background_db_finalize(
     conn: rusqlite::Connection,
     state: &AppState,
     app: &AppHandle,
-    vol: &volume::VolumeHandle,
+    volumes: &[(char, volume::VolumeHandle)],
     current_run_id: i64,
     has_entries: bool,
     scan_started: Instant,
-    free_mem_index: impl FnOnce(),
 ) -> Result<(), String> {
     let ts = || format!("{:.1}s", scan_started.elapsed().as_secs_f32());
 
     // Cleanup stale entries
     let cleanup_started = Instant::now();
-    let deleted_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM entries WHERE run_id < ?1",
-            params![current_run_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-    conn.execute(
-        "DELETE FROM entries WHERE run_id < ?1",
-        params![current_run_id],
-    )
-    .map_err(|e| e.to_string())?;
+    let deleted_count = delete_stale_run_rows(&conn, current_run_id)? as i64;
     eprintln!("[win/mft/bg +{}] cleanup: deleted={deleted_count} in {}ms", ts(), cleanup_started.elapsed().as_millis());
 
     set_meta(&conn, "last_run_id", &current_run_id.to_string())?;
@@ -634,15 +781,12 @@ fn background_db_finalize(
     .map_err(|e| e.to_string())?;
     eprintln!("[win/mft/bg +{}] name index created in {}ms", ts(), idx_started.elapsed().as_millis());
 
-    // Free MemIndex now — DB search can use the name index
-    free_mem_index();
-
-    // Create remaining indexes (MemIndex freed, lower peak memory)
+    // Create remaining indexes
     let idx2_started = Instant::now();
     conn.execute_batch(
         r#"
         CREATE INDEX IF NOT EXISTS idx_entries_dir_ext_name_nocase ON entries(dir, ext, name COLLATE NOCASE);
-        CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime);
+        CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime, mtime_nsec);
         CREATE INDEX IF NOT EXISTS idx_entries_ext_name ON entries(ext, name COLLATE NOCASE);
         "#,
     )
@@ -654,14 +798,23 @@ fn background_db_finalize(
         eprintln!("[win/mft/bg] gc cleanup error: {e}");
     }
 
-    // Save USN journal position for future resume
-    if let Ok(journal) = volume::query_usn_journal(vol) {
-        let _ = set_meta(&conn, "win_last_usn", &journal.next_usn.to_string());
-        let _ = set_meta(&conn, "win_journal_id", &journal.journal_id.to_string());
+    // Save each volume's USN journal position for future resume, and record
+    // which volumes the index covers so the watcher tails all of them.
+    // `volumes` starts with the primary (scan_root's) volume.
+    let primary = volumes.first().map(|(letter, _)| *letter).unwrap_or('C');
+    for (letter, vol) in volumes {
+        if let Ok(journal) = volume::query_usn_journal(vol) {
+            let (usn_key, jid_key) = volume::usn_meta_keys(*letter, primary);
+            let _ = set_meta(&conn, &usn_key, &journal.next_usn.to_string());
+            let _ = set_meta(&conn, &jid_key, &journal.journal_id.to_string());
+        }
     }
+    let letters: String = volumes.iter().map(|(letter, _)| *letter).collect();
+    let _ = set_meta(&conn, volume::MFT_VOLUMES_META_KEY, &letters);
 
     // Mark index as complete — startup will check this to decide catchup vs re-index
     let _ = set_meta(&conn, "index_complete", "1");
+    record_full_index_duration(&conn, scan_started.elapsed());
 
     if deleted_count > 0 || has_entries {
         invalidate_search_caches(state);
@@ -835,11 +988,7 @@ fn parse_usn_record_v2(data: &[u8]) -> Option<MftRecord> {
 
     // Bytes 32-40: TimeStamp (FILETIME) — last modification time from USN record
     let filetime_raw = i64::from_le_bytes(data[32..40].try_into().ok()?);
-    let timestamp = if filetime_raw > 0 {
-        Some(filetime_to_unix(filetime_raw))
-    } else {
-        None
-    };
+    let filetime = (filetime_raw > 0).then_some(filetime_raw);
 
     let attributes = u32::from_le_bytes(data[52..56].try_into().ok()?);
 
@@ -866,14 +1015,24 @@ fn parse_usn_record_v2(data: &[u8]) -> Option<MftRecord> {
         parent_frn,
         name,
         attributes,
-        timestamp,
+        filetime,
     })
 }
 
+/// 100 ns intervals between 1601-01-01 (FILETIME) and 1970-01-01.
+const FILETIME_UNIX_DIFF: i64 = 116_444_736_000_000_000;
+
 pub fn filetime_to_unix(filetime: i64) -> i64 {
-    const FILETIME_UNIX_DIFF: i64 = 116_444_736_000_000_000;
     if filetime <= FILETIME_UNIX_DIFF {
         return 0;
     }
     (filetime - FILETIME_UNIX_DIFF) / 10_000_000
 }
+
+/// Sub-second part of a FILETIME in nanoseconds; FILETIMEs count 100 ns.
+pub fn filetime_subsec_nanos(filetime: i64) -> u32 {
+    if filetime <= FILETIME_UNIX_DIFF {
+        return 0;
+    }
+    ((filetime - FILETIME_UNIX_DIFF) % 10_000_000 * 100) as u32
+}
@@ -8,6 +8,13 @@ pub mod rdcw_watcher;
 pub mod context_menu;
 pub mod search_catchup;
 pub mod icon;
+pub mod cloud;
+pub mod shortcut;
+pub mod shell_open;
+pub mod file_props;
+pub mod jump_list;
+pub mod wsearch_search;
+pub mod reparse;
 
 pub const EARLY_MEM_INDEX_LIMIT: usize = 200_000;
 
@@ -21,7 +28,7 @@ use crate::{
     start_full_index_worker_silent,
     AppState,
 };
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 pub fn start_windows_indexing(app: AppHandle, state: AppState) {
     let win_started = std::time::Instant::now();
@@ -32,11 +39,13 @@ pub fn start_windows_indexing(app: AppHandle, state: AppState) {
     // immediately when index_complete=true. This eliminates the race condition
     // where the frontend's refreshStatus() runs before the spawned thread
     // gets scheduled by the OS.
+    let primary = usn_watcher::primary_volume(&state);
     let (stored_usn, stored_journal_id, index_complete) = match db_connection(&state.db_path) {
         Ok(conn) => {
-            let usn = get_meta(&conn, "win_last_usn")
+            let (usn_key, jid_key) = volume::usn_meta_keys(primary, primary);
+            let usn = get_meta(&conn, &usn_key)
                 .and_then(|v| v.parse::<i64>().ok());
-            let jid = get_meta(&conn, "win_journal_id")
+            let jid = get_meta(&conn, &jid_key)
                 .and_then(|v| v.parse::<u64>().ok());
             let ic = get_meta(&conn, "index_complete")
                 .map(|v| v == "1")
@@ -50,7 +59,7 @@ pub fn start_windows_indexing(app: AppHandle, state: AppState) {
     };
 
     eprintln!(
-        "[startup/win] +{}ms startup check: stored_usn={:?} stored_journal_id={:?} index_complete={}",
+        "[startup/win] +{}ms startup check: primary={primary} stored_usn={:?} stored_journal_id={:?} index_complete={}",
         win_started.elapsed().as_millis(), stored_usn, stored_journal_id, index_complete
     );
 
@@ -68,6 +77,7 @@ pub fn start_windows_indexing(app: AppHandle, state: AppState) {
             match usn_watcher::start_with_resume(
                 app.clone(),
                 state.clone(),
+                primary,
                 stored_usn.unwrap(),
                 stored_journal_id.unwrap(),
             ) {
@@ -162,19 +172,42 @@ pub fn start_windows_indexing(app: AppHandle, state: AppState) {
                         // Non-admin fast index: home-dir first → MemIndex → Ready in <30s
                         // Handles watcher startup internally.
                         eprintln!("[win] MFT failed ({e}), DB empty — starting non-admin fast index");
-                        nonadmin_indexer::run_nonadmin_index(app, state);
+                        // Until the fast index is Ready, searches also ask
+                        // the Windows Search indexer.
+                        state.wsearch_fallback.store(true, AtomicOrdering::Release);
+                        nonadmin_indexer::run_nonadmin_index(app, state.clone());
+                        state.wsearch_fallback.store(false, AtomicOrdering::Release);
                         return;
                     }
                 }
 
-                if let Err(e2) = usn_watcher::start(app.clone(), state.clone(), HashMap::new(), HashSet::new()) {
+                if let Err(e2) = usn_watcher::start(app.clone(), state.clone(), HashMap::new()) {
                     eprintln!("[win] USN watcher also failed ({e2}), trying RDCW fallback");
                     let watch_roots = nonadmin_indexer::compute_watch_roots(&state);
-                    if let Err(e3) = rdcw_watcher::start_with_roots(app, state, watch_roots) {
-                        eprintln!("[win] RDCW watcher also failed ({e3}), no live updates");
-                    }
+                    start_rdcw_or_poll(app, state, watch_roots);
                 }
             }
         }
     });
 }
+
+/// Start a live watcher over the current roots after `set_scan_roots`
+/// stopped the previous one: USN from each journal's current position, RDCW
+/// when the journals can't be opened.
+pub fn restart_watcher(app: AppHandle, state: AppState) {
+    if let Err(e) = usn_watcher::start(app.clone(), state.clone(), HashMap::new()) {
+        eprintln!("[win] USN watcher restart failed ({e}), trying RDCW fallback");
+        let watch_roots = nonadmin_indexer::compute_watch_roots(&state);
+        start_rdcw_or_poll(app, state, watch_roots);
+    }
+}
+
+/// RDCW over `roots`, or — when not even that can watch them — the polling
+/// watcher over the indexed roots.
+pub fn start_rdcw_or_poll(app: AppHandle, state: AppState, roots: Vec<std::path::PathBuf>) {
+    if let Err(e) = rdcw_watcher::start_with_roots(app.clone(), state.clone(), roots) {
+        eprintln!("[win] RDCW watcher also failed ({e}), polling instead");
+        let poll_roots = nonadmin_indexer::compute_watch_roots(&state);
+        crate::poll_watcher::start(Some(app), state, poll_roots);
+    }
+}
@@ -3,23 +3,21 @@ use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, UNIX_EPOCH};
 
-use rusqlite::params;
 use tauri::AppHandle;
 
+use super::reparse::VisitedDirs;
+use crate::long_path;
 use crate::mem_search::CompactEntry;
+use crate::name_norm;
 use crate::{
-    cleanup_entries_gc_tables, db_connection, effective_ignore_rules, emit_index_progress, emit_index_state,
-    emit_index_updated, get_meta, invalidate_search_caches, now_epoch,
-    refresh_and_emit_status_counts, restore_normal_pragmas, set_indexing_pragmas,
+    cleanup_entries_gc_tables, db_connection, delete_stale_run_rows, effective_ignore_rules, emit_index_progress, emit_index_state,
+    emit_index_updated, fixed_ignore_roots, get_meta, index_tuning, invalidate_search_caches, now_epoch,
+    scan_tuning, record_full_index_duration, refresh_and_emit_status_counts, restore_normal_pragmas, set_indexing_pragmas,
     set_meta, set_progress, should_skip_path, update_status_counts, upsert_rows,
     AppState, IgnorePattern, IndexRow, IndexState,
 };
 
-const JWALK_THREADS: usize = 8;
 const EMIT_INTERVAL: Duration = Duration::from_millis(200);
-const DB_BATCH_SIZE: usize = 50_000;
-/// Shallow scan depth for Phase 1 — captures most user-visible files quickly.
-const SHALLOW_DEPTH: usize = 6;
 
 /// Counters returned alongside entries from a scan.
 struct ScanResult {
@@ -50,6 +48,8 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
     if let Ok(c) = db_connection(&state.db_path) {
         let _ = set_meta(&c, "index_complete", "0");
     }
+    // Shallow scan depth for Phase 1 — captures most user-visible files quickly.
+    let shallow_depth = index_tuning::apply(&state).shallow_scan_depth;
 
     {
         let mut status = state.status.lock();
@@ -66,8 +66,8 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
         &state.config_file_path,
         &state.home_dir,
         &state.cwd,
-        state.path_ignores.as_ref(),
-        state.path_ignore_patterns.as_ref(),
+        &fixed_ignore_roots(&state),
+        &[],
     );
 
     let arc_roots = Arc::new(ignored_roots);
@@ -77,12 +77,12 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
     let mut indexed: u64 = 0;
     let mut permission_errors: u64 = 0;
 
-    // ── Phase 1: Shallow scan of home directory (depth ≤ SHALLOW_DEPTH) ──
+    // ── Phase 1: Shallow scan of home directory (depth ≤ shallow_depth) ──
     eprintln!(
         "[nonadmin +{}] Phase 1: shallow scan {} (depth≤{})",
         ts(),
         state.home_dir.display(),
-        SHALLOW_DEPTH
+        shallow_depth
     );
 
     let shallow = scan_dir_jwalk(
@@ -91,7 +91,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
         &app,
         &arc_roots,
         &arc_patterns,
-        Some(SHALLOW_DEPTH),
+        Some(shallow_depth),
         0,
         None,
     );
@@ -116,6 +116,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
             shallow.entries.iter().take(early_cap).cloned().collect();
         let early_idx = Arc::new(crate::mem_search::MemIndex::build(early_entries));
         *state.mem_index.write() = Some(early_idx);
+        state.search_result_cache.lock().clear();
 
         let shallow_total = shallow.entries.len();
         all_entries.extend(shallow.entries);
@@ -239,7 +240,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
             &deep_roots,
             &deep_patterns,
             None,          // no max_depth
-            SHALLOW_DEPTH, // skip shallow entries already in Phase 1
+            shallow_depth, // skip shallow entries already in Phase 1
             Some(&sp),
         )
     });
@@ -319,6 +320,8 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
     // Build full MemIndex
     let full_idx = Arc::new(crate::mem_search::MemIndex::build(all_entries));
     *state.mem_index.write() = Some(Arc::clone(&full_idx));
+    // Results cached from the shallow index would hide the rest.
+    state.search_result_cache.lock().clear();
 
     {
         let mut status = state.status.lock();
@@ -367,14 +370,6 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
                     run_id,
                     total_entries > 0,
                     started,
-                    || {
-                        drop(full_idx);
-                        *bg_state.mem_index.write() = None;
-                        eprintln!(
-                            "[nonadmin/bg +{}] MemIndex freed",
-                            format!("{:.1}s", started.elapsed().as_secs_f32())
-                        );
-                    },
                 ) {
                     eprintln!("[nonadmin/bg +{}] DB finalize error: {e}", ts());
                 }
@@ -384,6 +379,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
                 eprintln!("[nonadmin/bg +{}] DB bulk insert error: {e}", ts());
                 drop(full_idx);
                 *bg_state.mem_index.write() = None;
+                bg_state.search_result_cache.lock().clear();
                 bg_state
                     .indexing_active
                     .store(false, AtomicOrdering::Release);
@@ -391,9 +387,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
         }
 
         // Start RDCW file watcher on indexed roots only (not all of C:\)
-        if let Err(e) = super::rdcw_watcher::start_with_roots(bg_app, bg_state, watch_roots) {
-            eprintln!("[nonadmin/bg] RDCW watcher failed: {e}");
-        }
+        super::start_rdcw_or_poll(bg_app, bg_state, watch_roots);
     });
 }
 
@@ -403,7 +397,7 @@ pub fn run_nonadmin_index(app: AppHandle, state: AppState) {
 fn is_reparse_point(path: &Path) -> bool {
     use std::os::windows::fs::MetadataExt;
     const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
-    std::fs::symlink_metadata(path)
+    long_path::symlink_metadata(path)
         .map(|m| m.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0)
         .unwrap_or(false)
 }
@@ -416,6 +410,8 @@ fn expand_children_excluding(
     exclude: &Path,
     ignored_roots: &Arc<Vec<PathBuf>>,
     ignored_patterns: &Arc<Vec<IgnorePattern>>,
+    follow_reparse: bool,
+    visited: &VisitedDirs,
     out: &mut Vec<PathBuf>,
 ) {
     let Ok(entries) = std::fs::read_dir(parent) else {
@@ -426,7 +422,7 @@ fn expand_children_excluding(
         if child == exclude || exclude.starts_with(&child) {
             continue;
         }
-        if !child.is_dir() || is_reparse_point(&child) {
+        if !child.is_dir() || (!follow_reparse && is_reparse_point(&child)) {
             continue;
         }
         if should_skip_path(
@@ -436,29 +432,41 @@ fn expand_children_excluding(
         ) {
             continue;
         }
+        if !visited.first_visit(&child) {
+            continue;
+        }
         out.push(child);
     }
 }
 
 /// Compute the list of directories to watch for filesystem changes.
 /// Returns home_dir + top-level dirs under scan_root (expanding ancestors of home_dir).
+/// Roots are deduplicated by volume + file ID, so with reparse points
+/// followed a junction onto an already watched directory adds no second
+/// recursive watch.
 pub fn compute_watch_roots(state: &AppState) -> Vec<PathBuf> {
     let (ignored_roots, ignored_patterns) = effective_ignore_rules(
         &state.config_file_path,
         &state.home_dir,
         &state.cwd,
-        state.path_ignores.as_ref(),
-        state.path_ignore_patterns.as_ref(),
+        &fixed_ignore_roots(state),
+        &[],
     );
     let arc_roots = Arc::new(ignored_roots);
     let arc_patterns = Arc::new(ignored_patterns);
+    let follow_reparse = state.index_tuning.read().follow_reparse_points;
+    let visited = VisitedDirs::default();
+    visited.first_visit(&state.home_dir);
 
     let mut roots: Vec<PathBuf> = vec![state.home_dir.clone()];
 
     if let Ok(entries) = std::fs::read_dir(&state.scan_root) {
         for e in entries.flatten() {
             let path = e.path();
-            if path == state.home_dir || !path.is_dir() || is_reparse_point(&path) {
+            if path == state.home_dir
+                || !path.is_dir()
+                || (!follow_reparse && is_reparse_point(&path))
+            {
                 continue;
             }
             if should_skip_path(&path, &arc_roots, &arc_patterns) {
@@ -471,9 +479,11 @@ pub fn compute_watch_roots(state: &AppState) -> Vec<PathBuf> {
                     &state.home_dir,
                     &arc_roots,
                     &arc_patterns,
+                    follow_reparse,
+                    &visited,
                     &mut roots,
                 );
-            } else {
+            } else if visited.first_visit(&path) {
                 roots.push(path);
             }
         }
@@ -510,29 +520,57 @@ fn scan_dir_jwalk(
 
     let skip_roots = Arc::clone(ignored_roots);
     let skip_patterns = Arc::clone(ignored_patterns);
+    let pressure = Arc::clone(&state.search_pressure);
+    let threads = state.index_tuning.read().scan_threads(
+        scan_tuning::available_cpus(),
+        scan_tuning::detect_storage(root),
+    );
 
-    let mut builder = jwalk::WalkDir::new(root)
-        .follow_links(false)
+    // Junctions and directory symlinks are only descended when configured;
+    // then every directory's volume + file ID is recorded so a link back
+    // into an ancestor, or a second link onto the same target, is indexed
+    // as an entry but not walked again.
+    let follow_reparse = state.index_tuning.read().follow_reparse_points;
+    let visited = follow_reparse.then(|| {
+        let visited = Arc::new(VisitedDirs::default());
+        visited.first_visit(root);
+        visited
+    });
+
+    // Walk extended-length paths so trees past MAX_PATH are read instead of
+    // erroring; rows and ignore rules see them stripped.
+    let mut builder = jwalk::WalkDir::new(long_path::extended(root))
+        .follow_links(follow_reparse)
         .skip_hidden(false);
     if let Some(md) = max_depth {
         builder = builder.max_depth(md);
     }
     let walker = builder
-        .parallelism(jwalk::Parallelism::RayonNewPool(JWALK_THREADS))
+        .parallelism(jwalk::Parallelism::RayonNewPool(threads))
         .process_read_dir(move |_depth, path, _state, children| {
+            pressure.yield_if_degraded();
             children.retain(|entry_result| {
                 entry_result
                     .as_ref()
                     .map(|entry| {
                         let full_path = path.join(&entry.file_name);
                         !should_skip_path(
-                            &full_path,
+                            &long_path::strip(&full_path),
                             &skip_roots,
                             &skip_patterns,
                         )
                     })
                     .unwrap_or(false)
             });
+            if let Some(visited) = &visited {
+                for entry in children.iter_mut().flatten() {
+                    if entry.read_children_path.is_some()
+                        && !visited.first_visit(&path.join(&entry.file_name))
+                    {
+                        entry.read_children_path = None;
+                    }
+                }
+            }
         });
 
     for result in walker {
@@ -552,7 +590,8 @@ fn scan_dir_jwalk(
                     }
                 };
 
-                let path = entry.path();
+                let walked = entry.path();
+                let path = long_path::strip(&walked);
                 let is_dir = metadata.is_dir();
                 let name = match path.file_name().and_then(|n| n.to_str()) {
                     Some(n) => n.to_string(),
@@ -571,11 +610,12 @@ fn scan_dir_jwalk(
                     None
                 };
 
-                let mtime = metadata
+                let modified = metadata
                     .modified()
                     .ok()
-                    .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs() as i64);
+                    .and_then(|m| m.duration_since(UNIX_EPOCH).ok());
+                let mtime = modified.map(|d| d.as_secs() as i64);
+                let mtime_nsec = modified.map(|d| d.subsec_nanos());
 
                 let size = if metadata.is_file() {
                     Some(metadata.len() as i64)
@@ -589,7 +629,9 @@ fn scan_dir_jwalk(
                     is_dir,
                     ext,
                     mtime,
+                    mtime_nsec,
                     size,
+                    cloud_only: crate::is_cloud_only_metadata(&metadata),
                 });
                 indexed += 1;
 
@@ -632,7 +674,7 @@ fn scan_dir_jwalk(
 }
 
 fn compact_entry_from_path(path: &Path) -> Option<CompactEntry> {
-    let metadata = std::fs::symlink_metadata(path).ok()?;
+    let metadata = long_path::symlink_metadata(path).ok()?;
     let is_dir = metadata.is_dir();
     let name = path.file_name()?.to_str()?.to_string();
     let dir = path
@@ -648,11 +690,12 @@ fn compact_entry_from_path(path: &Path) -> Option<CompactEntry> {
         None
     };
 
-    let mtime = metadata
+    let modified = metadata
         .modified()
         .ok()
-        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64);
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok());
+    let mtime = modified.map(|d| d.as_secs() as i64);
+    let mtime_nsec = modified.map(|d| d.subsec_nanos());
 
     let size = if metadata.is_file() {
         Some(metadata.len() as i64)
@@ -666,7 +709,9 @@ fn compact_entry_from_path(path: &Path) -> Option<CompactEntry> {
         is_dir,
         ext,
         mtime,
+        mtime_nsec,
         size,
+        cloud_only: crate::is_cloud_only_metadata(&metadata),
     })
 }
 
@@ -691,26 +736,35 @@ fn background_db_insert(
         DROP INDEX IF EXISTS idx_entries_dir_ext_name_nocase;
         DROP INDEX IF EXISTS idx_entries_mtime;
         DROP INDEX IF EXISTS idx_entries_name_nocase;
+        DROP INDEX IF EXISTS idx_entries_name_norm;
         DROP INDEX IF EXISTS idx_entries_ext_name;
+        DROP INDEX IF EXISTS idx_entries_ext_size;
+        DROP INDEX IF EXISTS idx_entries_ext_mtime;
+        DROP INDEX IF EXISTS idx_entries_name_mtime;
         "#,
     );
     eprintln!("[nonadmin/bg +{}] indexes dropped", ts());
 
     let upsert_started = Instant::now();
+    let batch_size = state.index_tuning.read().batch_size;
 
-    for chunk in entries.chunks(DB_BATCH_SIZE) {
+    for chunk in entries.chunks(batch_size) {
         let chunk_rows: Vec<IndexRow> = chunk
             .iter()
             .map(|entry| IndexRow {
                 path: entry.path(),
                 name: entry.name.clone(),
                 dir: entry.dir.clone(),
+                name_norm: name_norm::fold(&entry.name),
+                dir_norm: name_norm::fold(&entry.dir),
                 is_dir: if entry.is_dir { 1 } else { 0 },
                 ext: entry.ext.clone(),
                 mtime: entry.mtime,
+                mtime_nsec: entry.mtime_nsec.map(i64::from),
                 size: entry.size,
                 indexed_at,
                 run_id: current_run_id,
+                cloud_only: entry.cloud_only,
             })
             .collect();
         upsert_rows(&mut conn, &chunk_rows)?;
@@ -733,23 +787,11 @@ fn background_db_finalize(
     current_run_id: i64,
     has_entries: bool,
     scan_started: Instant,
-    free_mem_index: impl FnOnce(),
 ) -> Result<(), String> {
     let ts = || format!("{:.1}s", scan_started.elapsed().as_secs_f32());
 
     let cleanup_started = Instant::now();
-    let deleted_count: i64 = conn
-        .query_row(
-            "SELECT COUNT(*) FROM entries WHERE run_id < ?1",
-            params![current_run_id],
-            |row| row.get(0),
-        )
-        .unwrap_or(0);
-    conn.execute(
-        "DELETE FROM entries WHERE run_id < ?1",
-        params![current_run_id],
-    )
-    .map_err(|e| e.to_string())?;
+    let deleted_count = delete_stale_run_rows(&conn, current_run_id)? as i64;
     eprintln!(
         "[nonadmin/bg +{}] cleanup: deleted={} in {}ms",
         ts(),
@@ -770,13 +812,11 @@ fn background_db_finalize(
         idx_started.elapsed().as_millis()
     );
 
-    free_mem_index();
-
     let idx2_started = Instant::now();
     conn.execute_batch(
         r#"
         CREATE INDEX IF NOT EXISTS idx_entries_dir_ext_name_nocase ON entries(dir, ext, name COLLATE NOCASE);
-        CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime);
+        CREATE INDEX IF NOT EXISTS idx_entries_mtime ON entries(mtime, mtime_nsec);
         CREATE INDEX IF NOT EXISTS idx_entries_ext_name ON entries(ext, name COLLATE NOCASE);
         "#,
     )
@@ -794,6 +834,7 @@ fn background_db_finalize(
     }
 
     let _ = set_meta(&conn, "index_complete", "1");
+    record_full_index_duration(&conn, scan_started.elapsed());
     let _ = set_meta(&conn, "win_last_active_ts", &now_epoch().to_string());
 
     if deleted_count > 0 || has_entries {
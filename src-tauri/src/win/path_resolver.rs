@@ -1,4 +1,13 @@
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::OnceLock;
+
+use windows::core::PCWSTR;
+use windows::Win32::Storage::FileSystem::{
+    FindFirstVolumeW, FindNextVolumeW, FindVolumeClose, GetVolumePathNamesForVolumeNameW,
+};
+
+use crate::long_path;
 
 /// Resolves MFT File Reference Numbers (FRN) to full file system paths.
 ///
@@ -220,6 +229,122 @@ impl PathResolver {
     }
 }
 
+/// Maps the other spellings of a volume's paths — `\\?\Volume{GUID}\…` and
+/// NTFS mounted folders (`C:\mnt\data\…`) — onto the one the index uses:
+/// the volume's drive letter, or its first mount folder if it has none.
+/// Without it a file reached both ways was indexed twice.
+#[derive(Debug, Default)]
+pub struct MountTable {
+    // (alias prefix, canonical root), both ending in '\'; longest alias first
+    aliases: Vec<(String, String)>,
+}
+
+impl MountTable {
+    /// Build from (volume GUID name, mount paths) pairs, as
+    /// `GetVolumePathNamesForVolumeNameW` lists them.
+    pub fn from_volumes(volumes: impl IntoIterator<Item = (String, Vec<String>)>) -> Self {
+        let with_slash = |s: &str| format!("{}\\", s.trim_end_matches('\\'));
+        let mut aliases = Vec::new();
+        for (volume, mut paths) in volumes {
+            paths.sort_by_key(|p| p.len());
+            let Some(canonical) = paths
+                .iter()
+                .find(|p| is_drive_root(p))
+                .or_else(|| paths.first())
+                .map(|p| with_slash(p))
+            else {
+                continue;
+            };
+            for alias in std::iter::once(&volume).chain(&paths) {
+                let alias = with_slash(alias);
+                if !alias.eq_ignore_ascii_case(&canonical) {
+                    aliases.push((alias, canonical.clone()));
+                }
+            }
+        }
+        aliases.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        Self { aliases }
+    }
+
+    /// Ask the volume manager for every volume and where it is mounted.
+    pub fn query() -> Self {
+        let mut volumes = Vec::new();
+        let mut name = [0u16; 260];
+        let Ok(find) = (unsafe { FindFirstVolumeW(&mut name) }) else {
+            return Self::default();
+        };
+        loop {
+            if let Some(len) = name.iter().position(|&c| c == 0) {
+                let volume = String::from_utf16_lossy(&name[..len]);
+                volumes.push((volume, volume_path_names(&name[..=len])));
+            }
+            if unsafe { FindNextVolumeW(find, &mut name) }.is_err() {
+                break;
+            }
+        }
+        let _ = unsafe { FindVolumeClose(find) };
+        Self::from_volumes(volumes)
+    }
+
+    /// `path` in the index's spelling. Paths on no known alias come back
+    /// as they are (minus any `\\?\` drive prefix).
+    pub fn canonical<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let mut current = long_path::strip_str(path);
+        // Bounded: a mount folder can sit on a volume that is itself mounted
+        // in a folder, but never in a cycle.
+        for _ in 0..=self.aliases.len() {
+            let Some((alias, root)) = self.aliases.iter().find(|(alias, _)| {
+                current
+                    .get(..alias.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(alias))
+                    || current.eq_ignore_ascii_case(alias.trim_end_matches('\\'))
+            }) else {
+                break;
+            };
+            let rest = current.get(alias.len()..).unwrap_or("");
+            current = Cow::Owned(if rest.is_empty() && !is_drive_root(root) {
+                root.trim_end_matches('\\').to_string()
+            } else {
+                format!("{root}{rest}")
+            });
+        }
+        current
+    }
+}
+
+/// Mount table of this machine, read on first use.
+pub fn mount_table() -> &'static MountTable {
+    static TABLE: OnceLock<MountTable> = OnceLock::new();
+    TABLE.get_or_init(MountTable::query)
+}
+
+fn is_drive_root(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() == 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && bytes[2] == b'\\'
+}
+
+/// Mount paths of a volume; `volume` is its NUL-terminated GUID name.
+fn volume_path_names(volume: &[u16]) -> Vec<String> {
+    let mut buf = vec![0u16; 512];
+    let mut needed = 0u32;
+    let mut listed = unsafe {
+        GetVolumePathNamesForVolumeNameW(PCWSTR(volume.as_ptr()), Some(&mut buf), &mut needed)
+    };
+    if listed.is_err() && needed as usize > buf.len() {
+        buf = vec![0u16; needed as usize];
+        listed = unsafe {
+            GetVolumePathNamesForVolumeNameW(PCWSTR(volume.as_ptr()), Some(&mut buf), &mut needed)
+        };
+    }
+    if listed.is_err() {
+        return Vec::new();
+    }
+    buf.split(|&c| c == 0)
+        .take_while(|s| !s.is_empty())
+        .map(String::from_utf16_lossy)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,4 +438,28 @@ mod tests {
         assert!(!subtree.contains(&700)); // .git — pruned
         assert!(!subtree.contains(&800)); // objects — pruned (child of .git)
     }
+    #[test]
+    fn mount_table_maps_guid_and_mount_folder_paths_to_drive_letters() {
+        let table = MountTable::from_volumes([
+            ("\\\\?\\Volume{c0}\\".to_string(), vec!["C:\\".to_string()]),
+            (
+                "\\\\?\\Volume{d1}\\".to_string(),
+                vec!["C:\\mnt\\data\\".to_string(), "D:\\".to_string()],
+            ),
+            // No drive letter: the mount folder is the canonical spelling.
+            ("\\\\?\\Volume{e2}\\".to_string(), vec!["D:\\mnt\\e\\".to_string()]),
+            ("\\\\?\\Volume{f3}\\".to_string(), Vec::new()),
+        ]);
+
+        assert_eq!(table.canonical("\\\\?\\Volume{d1}\\docs\\a.txt"), "D:\\docs\\a.txt");
+        assert_eq!(table.canonical("\\\\?\\volume{D1}\\docs"), "D:\\docs");
+        assert_eq!(table.canonical("C:\\mnt\\data\\docs\\a.txt"), "D:\\docs\\a.txt");
+        assert_eq!(table.canonical("c:\\MNT\\data"), "D:\\");
+        assert_eq!(table.canonical("C:\\mnt\\database"), "C:\\mnt\\database");
+        assert_eq!(table.canonical("\\\\?\\C:\\Users"), "C:\\Users");
+        // Nested: E sits in a folder on D, which sits in a folder on C.
+        assert_eq!(table.canonical("C:\\mnt\\data\\mnt\\e\\x"), "D:\\mnt\\e\\x");
+        assert_eq!(table.canonical("\\\\?\\Volume{e2}"), "D:\\mnt\\e");
+        assert_eq!(table.canonical("\\\\?\\Volume{f3}\\x"), "\\\\?\\Volume{f3}\\x");
+    }
 }
@@ -1,25 +1,62 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::os::windows::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
-use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
+use windows::core::{HRESULT, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, ERROR_NOTIFY_ENUM_DIR, HANDLE, WAIT_TIMEOUT};
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, ReadDirectoryChangesW, FILE_ACTION, FILE_ACTION_ADDED, FILE_ACTION_MODIFIED,
+    FILE_ACTION_REMOVED, FILE_ACTION_RENAMED_NEW_NAME, FILE_ACTION_RENAMED_OLD_NAME,
+    FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OVERLAPPED, FILE_LIST_DIRECTORY,
+    FILE_NOTIFY_CHANGE, FILE_NOTIFY_CHANGE_CREATION, FILE_NOTIFY_CHANGE_DIR_NAME,
+    FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE, FILE_NOTIFY_CHANGE_SIZE,
+    FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+use windows::Win32::System::IO::{
+    CancelIoEx, GetOverlappedResult, GetOverlappedResultEx, OVERLAPPED,
+};
 
 use std::sync::atomic::Ordering as AtomicOrdering;
 
+use super::reparse::VisitedDirs;
+use crate::{case_rename, long_path, mem_resident};
 use crate::{
-    db_connection, delete_paths, invalidate_search_caches,
+    cached_effective_ignore_rules, db_connection, delete_paths, invalidate_search_caches,
     index_row_from_path_and_metadata, is_recently_touched,
-    now_epoch, pathignore_active_entries, refresh_and_emit_status_counts,
-    set_meta, should_skip_path, update_status_counts, upsert_rows,
-    AppState, WATCH_DEBOUNCE,
+    notify_live_queries, now_epoch, pathpriority, refresh_and_emit_status_counts,
+    reindex_subtree_blocking, reload_pathignore, rename_case_only, set_meta, should_skip_path,
+    update_status_counts, upsert_rows, AppState, HOT_WATCH_DEBOUNCE,
 };
 
 const STATUS_EMIT_MIN_INTERVAL: Duration = Duration::from_secs(5);
 const RENAME_PAIR_TIMEOUT: Duration = Duration::from_millis(500);
 const TS_PERSIST_INTERVAL: Duration = Duration::from_secs(30);
 
+/// ReadDirectoryChangesW fails outright with a larger buffer on network
+/// drives, so every root uses the SMB limit.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+const NOTIFY_FILTER: FILE_NOTIFY_CHANGE = FILE_NOTIFY_CHANGE(
+    FILE_NOTIFY_CHANGE_FILE_NAME.0
+        | FILE_NOTIFY_CHANGE_DIR_NAME.0
+        | FILE_NOTIFY_CHANGE_SIZE.0
+        | FILE_NOTIFY_CHANGE_LAST_WRITE.0
+        | FILE_NOTIFY_CHANGE_CREATION.0,
+);
+/// How long a reader blocks on its directory before checking for a stop.
+const READER_STOP_CHECK_MS: u32 = 250;
+/// How often a reader retries a root that went away (stick unplugged,
+/// share disconnected).
+const REOPEN_INTERVAL: Duration = Duration::from_secs(30);
+/// Minimum spacing between two overflow rescans of the same root; a root
+/// overflowing again inside the window is rescanned when it ends.
+const OVERFLOW_RESCAN_COOLDOWN: Duration = Duration::from_secs(60);
+
 #[derive(Debug)]
 enum FileChange {
     Create(PathBuf),
@@ -33,59 +70,305 @@ struct RenamePending {
     created_at: Instant,
 }
 
+/// What a root's reader thread hands the event loop.
+enum RootEvent {
+    Changes(Vec<(FILE_ACTION, PathBuf)>),
+    /// The root's notification buffer overflowed or the root was reopened
+    /// after going away: events were lost and it needs a rescan.
+    Overflow(PathBuf),
+}
+
+struct DirHandle(HANDLE);
+
+// SAFETY: directory handles can be used from any thread.
+unsafe impl Send for DirHandle {}
+
+impl Drop for DirHandle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = CloseHandle(self.0);
+        }
+    }
+}
+
+fn open_dir(root: &Path) -> Result<DirHandle, String> {
+    let wide: Vec<u16> = root
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let handle = unsafe {
+        CreateFileW(
+            PCWSTR(wide.as_ptr()),
+            FILE_LIST_DIRECTORY.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
+            None,
+        )
+    }
+    .map_err(|e| format!("CreateFileW for {}: {e}", root.display()))?;
+    Ok(DirHandle(handle))
+}
+
+/// An RDCW watcher started by `start_alongside`. Dropping it stops the
+/// watcher and waits for its threads, so the owner's shutdown covers it.
+pub struct RdcwHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for RdcwHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, AtomicOrdering::Release);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
 pub fn start(app: AppHandle, state: AppState) -> Result<(), String> {
     start_with_roots(app, state, vec![PathBuf::from("C:\\")])
 }
 
+/// RDCW as the live watcher over `roots`, for when no USN journal can be
+/// read.
 pub fn start_with_roots(
     app: AppHandle,
     state: AppState,
     roots: Vec<PathBuf>,
 ) -> Result<(), String> {
-    let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+    let readers = open_readers(&roots)?;
+    state.watcher_active.store(true, AtomicOrdering::Release);
+
+    std::thread::spawn(move || {
+        run(&app, &state, readers, Arc::new(AtomicBool::new(false)), true);
+        state.watcher_active.store(false, AtomicOrdering::Release);
+        eprintln!("[win/rdcw] watcher stopped");
+    });
 
-    let mut watcher = RecommendedWatcher::new(tx, Config::default())
-        .map_err(|e| format!("notify watcher creation failed: {e}"))?;
+    Ok(())
+}
 
-    let mut watched = 0;
-    for root in &roots {
-        match watcher.watch(root, RecursiveMode::Recursive) {
-            Ok(()) => watched += 1,
+/// RDCW over `roots` no journal reader covers — FAT/exFAT sticks, network
+/// drives — running beside the USN watcher, which owns the watcher status.
+pub fn start_alongside(
+    app: AppHandle,
+    state: AppState,
+    roots: Vec<PathBuf>,
+) -> Result<RdcwHandle, String> {
+    let readers = open_readers(&roots)?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let loop_stop = stop.clone();
+    let thread = std::thread::spawn(move || {
+        run(&app, &state, readers, loop_stop, false);
+        eprintln!("[win/rdcw] companion watcher stopped");
+    });
+    Ok(RdcwHandle {
+        stop,
+        thread: Some(thread),
+    })
+}
+
+/// Open every root, skipping (and logging) the ones that can't be watched.
+/// A root that resolves to a directory already being watched — a volume
+/// mounted both as a drive letter and into a folder, or a junction root —
+/// is skipped too, since its recursive watch would report every change twice.
+fn open_readers(roots: &[PathBuf]) -> Result<Vec<(PathBuf, DirHandle)>, String> {
+    let visited = VisitedDirs::default();
+    let mut readers = Vec::new();
+    for root in roots {
+        if !visited.first_visit(root) {
+            eprintln!("[win/rdcw] skipping {}: same directory as another root", root.display());
+            continue;
+        }
+        match open_dir(root) {
+            Ok(dir) => readers.push((root.clone(), dir)),
             Err(e) => eprintln!("[win/rdcw] skipping {}: {e}", root.display()),
         }
     }
-    if watched == 0 {
+    if readers.is_empty() {
         return Err("no directories could be watched".to_string());
     }
-    eprintln!("[win/rdcw] watcher started on {}/{} root(s)", watched, roots.len());
-    state.watcher_active.store(true, AtomicOrdering::Release);
+    eprintln!("[win/rdcw] watcher started on {}/{} root(s)", readers.len(), roots.len());
+    Ok(readers)
+}
 
-    std::thread::spawn(move || {
-        event_loop(&app, &state, rx);
-        state.watcher_active.store(false, AtomicOrdering::Release);
-        // Keep watcher alive until event_loop exits — drop ends the watch
-        drop(watcher);
-        eprintln!("[win/rdcw] watcher stopped");
-    });
+/// Spawn a reader per root and run the event loop until a stop, then take
+/// the readers down with it.
+fn run(
+    app: &AppHandle,
+    state: &AppState,
+    readers: Vec<(PathBuf, DirHandle)>,
+    stop: Arc<AtomicBool>,
+    owns_status: bool,
+) {
+    let roots: Vec<PathBuf> = readers.iter().map(|(root, _)| root.clone()).collect();
+    let (tx, rx) = mpsc::channel::<RootEvent>();
+    let threads: Vec<JoinHandle<()>> = readers
+        .into_iter()
+        .map(|(root, dir)| {
+            let (tx, stop) = (tx.clone(), stop.clone());
+            std::thread::spawn(move || read_root(root, dir, tx, stop))
+        })
+        .collect();
+    drop(tx);
+    event_loop(app, state, rx, &roots, &stop, owns_status);
+    stop.store(true, AtomicOrdering::Release);
+    for thread in threads {
+        let _ = thread.join();
+    }
+}
 
-    Ok(())
+/// Result of waiting on one read: bytes written, or `None` when stopped.
+fn wait_for_read(
+    dir: &DirHandle,
+    overlapped: &OVERLAPPED,
+    stop: &AtomicBool,
+) -> Option<windows::core::Result<u32>> {
+    let timeout = HRESULT::from_win32(WAIT_TIMEOUT.0);
+    let mut bytes = 0u32;
+    loop {
+        let result = unsafe {
+            GetOverlappedResultEx(dir.0, overlapped, &mut bytes, READER_STOP_CHECK_MS, false)
+        };
+        match result {
+            Err(e) if e.code() == timeout => {}
+            result => return Some(result.map(|()| bytes)),
+        }
+        if stop.load(AtomicOrdering::Acquire) {
+            // The buffer must outlive the read, so wait for the cancel.
+            unsafe {
+                let _ = CancelIoEx(dir.0, Some(overlapped));
+                let _ = GetOverlappedResult(dir.0, overlapped, &mut bytes, true);
+            }
+            return None;
+        }
+    }
+}
+
+/// Read one root's change notifications until `stop` or the event loop is
+/// gone. A root that errors (unplugged, share dropped) is reopened every
+/// `REOPEN_INTERVAL` and reported as overflowed once it's back.
+fn read_root(
+    root: PathBuf,
+    mut dir: DirHandle,
+    tx: mpsc::Sender<RootEvent>,
+    stop: Arc<AtomicBool>,
+) {
+    // u64s keep the buffer DWORD-aligned, as ReadDirectoryChangesW requires.
+    let mut buffer = vec![0u64; READ_BUFFER_SIZE / 8];
+    let enum_dir = ERROR_NOTIFY_ENUM_DIR.to_hresult();
+    while !stop.load(AtomicOrdering::Acquire) {
+        let mut overlapped = OVERLAPPED::default();
+        let started = unsafe {
+            ReadDirectoryChangesW(
+                dir.0,
+                buffer.as_mut_ptr().cast(),
+                READ_BUFFER_SIZE as u32,
+                true,
+                NOTIFY_FILTER,
+                None,
+                Some(&mut overlapped),
+                None,
+            )
+        };
+        let result = match started {
+            Ok(()) => match wait_for_read(&dir, &overlapped, &stop) {
+                Some(result) => result,
+                None => return,
+            },
+            Err(e) => Err(e),
+        };
+        let event = match result {
+            // Zero bytes: more changes than the buffer held.
+            Ok(0) => RootEvent::Overflow(root.clone()),
+            Err(e) if e.code() == enum_dir => RootEvent::Overflow(root.clone()),
+            Ok(len) => {
+                let bytes = unsafe {
+                    std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len as usize)
+                };
+                RootEvent::Changes(parse_notify_buffer(&root, bytes))
+            }
+            Err(e) => {
+                eprintln!("[win/rdcw] {} stopped reporting ({e}), will reopen", root.display());
+                let Some(reopened) = reopen(&root, &stop) else {
+                    return;
+                };
+                dir = reopened;
+                RootEvent::Overflow(root.clone())
+            }
+        };
+        if tx.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+fn reopen(root: &Path, stop: &AtomicBool) -> Option<DirHandle> {
+    loop {
+        let retry_at = Instant::now() + REOPEN_INTERVAL;
+        while Instant::now() < retry_at {
+            if stop.load(AtomicOrdering::Acquire) {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(READER_STOP_CHECK_MS as u64));
+        }
+        if let Ok(dir) = open_dir(root) {
+            eprintln!("[win/rdcw] {} reopened", root.display());
+            return Some(dir);
+        }
+    }
+}
+
+/// Decode a buffer of `FILE_NOTIFY_INFORMATION` records into actions on
+/// absolute paths under `root`.
+fn parse_notify_buffer(root: &Path, buf: &[u8]) -> Vec<(FILE_ACTION, PathBuf)> {
+    const HEADER_LEN: usize = 12;
+    let u32_at = |at: usize| u32::from_le_bytes(buf[at..at + 4].try_into().unwrap());
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while offset + HEADER_LEN <= buf.len() {
+        let next = u32_at(offset) as usize;
+        let action = FILE_ACTION(u32_at(offset + 4));
+        let name_len = u32_at(offset + 8) as usize;
+        let Some(name) = buf.get(offset + HEADER_LEN..offset + HEADER_LEN + name_len) else {
+            break;
+        };
+        let wide: Vec<u16> = name
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        records.push((action, root.join(String::from_utf16_lossy(&wide))));
+        if next == 0 {
+            break;
+        }
+        offset += next;
+    }
+    records
 }
 
 fn event_loop(
     app: &AppHandle,
     state: &AppState,
-    rx: mpsc::Receiver<notify::Result<Event>>,
+    rx: mpsc::Receiver<RootEvent>,
+    roots: &[PathBuf],
+    stop: &AtomicBool,
+    owns_status: bool,
 ) {
     let mut pending_changes: Vec<FileChange> = Vec::new();
     let mut pending_renames: VecDeque<RenamePending> = VecDeque::new();
+    // Overflowed roots → earliest time their rescan may run.
+    let mut pending_rescans: HashMap<PathBuf, Instant> = HashMap::new();
+    let mut last_rescan: HashMap<PathBuf, Instant> = HashMap::new();
     let mut last_flush = Instant::now();
     let mut last_status_emit = Instant::now();
     let mut last_ts_persist = Instant::now();
-
-    // Track config file changes to emit pathignore_changed when rules change
-    let mut last_config_entries = pathignore_active_entries(
-        &std::fs::read_to_string(&state.config_file_path).unwrap_or_default(),
-    );
+    // Set when a pending change falls under a .pathpriority hot directory;
+    // such batches flush after HOT_WATCH_DEBOUNCE instead of the configured
+    // watcher debounce.
+    let mut hot_pending = false;
 
     // Fixed poll: sleep, then drain all accumulated events at once.
     // ~1 wake/sec keeps CPU near 0% even with hundreds of events/sec.
@@ -95,43 +378,66 @@ fn event_loop(
         // Sleep — events accumulate in the channel buffer
         std::thread::sleep(POLL_INTERVAL);
 
-        if state.watcher_stop.load(AtomicOrdering::Acquire) {
+        if state.watcher_stop.load(AtomicOrdering::Acquire) || stop.load(AtomicOrdering::Acquire) {
             eprintln!("[win/rdcw] stop signal received, exiting");
             break;
         }
 
         // Drain all buffered events
+        let hot_dirs = state.hot_dirs.lock().clone();
         let mut drained = false;
-        while let Ok(result) = rx.try_recv() {
+        while let Ok(event) = rx.try_recv() {
             drained = true;
-            if let Ok(ev) = result {
-                // Check for config file change before classifying
-                if ev.paths.iter().any(|p| *p == state.config_file_path) {
-                    let new_entries = pathignore_active_entries(
-                        &std::fs::read_to_string(&state.config_file_path).unwrap_or_default(),
-                    );
-                    if new_entries != last_config_entries {
-                        last_config_entries = new_entries;
-                        app.emit("pathignore_changed", ()).ok();
+            match event {
+                RootEvent::Changes(records) => {
+                    // Check for config file change before classifying
+                    if records.iter().any(|(_, p)| *p == state.config_file_path) {
+                        reload_pathignore(Some(app), state);
+                    }
+                    for (action, path) in records {
+                        hot_pending |= pathpriority::is_hot_path(&path, &hot_dirs);
+                        classify_action(action, path, &mut pending_changes, &mut pending_renames);
                     }
                 }
-                classify_event(ev, &mut pending_changes, &mut pending_renames);
+                RootEvent::Overflow(root) => {
+                    let not_before = last_rescan
+                        .get(&root)
+                        .map_or_else(Instant::now, |at| *at + OVERFLOW_RESCAN_COOLDOWN);
+                    eprintln!("[win/rdcw] {} overflowed, queueing rescan", root.display());
+                    pending_rescans.entry(root).or_insert(not_before);
+                }
             }
         }
 
-        if !drained && pending_changes.is_empty() && pending_renames.is_empty() {
+        if !drained
+            && pending_changes.is_empty()
+            && pending_renames.is_empty()
+            && pending_rescans.is_empty()
+        {
             continue;
         }
 
         cleanup_expired_renames(&mut pending_renames, &mut pending_changes);
 
-        if !pending_changes.is_empty() && last_flush.elapsed() >= WATCH_DEBOUNCE {
-            apply_changes(app, state, &mut pending_changes, &mut last_status_emit);
+        let debounce = if hot_pending {
+            HOT_WATCH_DEBOUNCE
+        } else {
+            state.index_tuning.read().watch_debounce()
+        };
+        if !pending_changes.is_empty() && last_flush.elapsed() >= debounce {
+            apply_changes(app, state, roots, &mut pending_changes, &mut last_status_emit);
             last_flush = Instant::now();
+            hot_pending = false;
+        }
+        run_due_rescans(app, state, &mut pending_rescans, &mut last_rescan);
+        if owns_status {
+            state
+                .watcher_backlog
+                .store(pending_changes.len() + pending_renames.len(), AtomicOrdering::Relaxed);
         }
 
         // Periodically persist last active timestamp for startup catchup
-        if last_ts_persist.elapsed() >= TS_PERSIST_INTERVAL {
+        if owns_status && last_ts_persist.elapsed() >= TS_PERSIST_INTERVAL {
             if let Ok(conn) = db_connection(&state.db_path) {
                 let _ = set_meta(&conn, "win_last_active_ts", &now_epoch().to_string());
             }
@@ -140,71 +446,72 @@ fn event_loop(
     }
 }
 
-fn classify_event(
-    event: Event,
-    pending_changes: &mut Vec<FileChange>,
-    pending_renames: &mut VecDeque<RenamePending>,
+/// Rescan each overflowed root whose cooldown has passed. Waits out a full
+/// index, which would otherwise race the rescan's writes.
+fn run_due_rescans(
+    app: &AppHandle,
+    state: &AppState,
+    pending: &mut HashMap<PathBuf, Instant>,
+    last_rescan: &mut HashMap<PathBuf, Instant>,
 ) {
-    match event.kind {
-        EventKind::Create(_) => {
-            for path in event.paths {
-                pending_changes.push(FileChange::Create(path));
+    if state.indexing_active.load(AtomicOrdering::Acquire) {
+        return;
+    }
+    let now = Instant::now();
+    let due: Vec<PathBuf> = pending
+        .iter()
+        .filter(|(_, not_before)| **not_before <= now)
+        .map(|(root, _)| root.clone())
+        .collect();
+    for root in due {
+        match reindex_subtree_blocking(state, &root.to_string_lossy()) {
+            Ok(result) => {
+                eprintln!(
+                    "[win/rdcw] rescanned {}: upserted={} deleted={} {}ms",
+                    root.display(),
+                    result.upserted,
+                    result.deleted,
+                    result.elapsed_ms
+                );
+                if result.upserted + result.deleted > 0 {
+                    let _ = refresh_and_emit_status_counts(Some(app), state);
+                }
             }
+            // A full index started in the meantime; try again next round.
+            Err(_) if state.indexing_active.load(AtomicOrdering::Acquire) => continue,
+            Err(e) => eprintln!("[win/rdcw] rescan of {} failed: {e}", root.display()),
         }
-        EventKind::Remove(_) => {
-            for path in event.paths {
-                pending_changes.push(FileChange::Delete(path));
-            }
+        pending.remove(&root);
+        last_rescan.insert(root, Instant::now());
+    }
+}
+
+fn classify_action(
+    action: FILE_ACTION,
+    path: PathBuf,
+    pending_changes: &mut Vec<FileChange>,
+    pending_renames: &mut VecDeque<RenamePending>,
+) {
+    match action {
+        FILE_ACTION_ADDED => pending_changes.push(FileChange::Create(path)),
+        FILE_ACTION_REMOVED => pending_changes.push(FileChange::Delete(path)),
+        FILE_ACTION_MODIFIED => pending_changes.push(FileChange::Modify(path)),
+        FILE_ACTION_RENAMED_OLD_NAME => {
+            cleanup_expired_renames(pending_renames, pending_changes);
+            pending_renames.push_back(RenamePending {
+                old_path: path,
+                created_at: Instant::now(),
+            });
         }
-        EventKind::Modify(modify_kind) => {
-            use notify::event::ModifyKind;
-            match modify_kind {
-                ModifyKind::Name(rename_mode) => {
-                    use notify::event::RenameMode;
-                    match rename_mode {
-                        RenameMode::From => {
-                            if let Some(path) = event.paths.into_iter().next() {
-                                cleanup_expired_renames(pending_renames, pending_changes);
-                                pending_renames.push_back(RenamePending {
-                                    old_path: path,
-                                    created_at: Instant::now(),
-                                });
-                            }
-                        }
-                        RenameMode::To => {
-                            if let Some(new_path) = event.paths.into_iter().next() {
-                                // Pair with the oldest pending From (FIFO order)
-                                if let Some(old) = pending_renames.pop_front() {
-                                    pending_changes.push(FileChange::Rename {
-                                        old: old.old_path,
-                                        new: new_path,
-                                    });
-                                } else {
-                                    pending_changes.push(FileChange::Create(new_path));
-                                }
-                            }
-                        }
-                        RenameMode::Both => {
-                            let mut paths = event.paths.into_iter();
-                            if let (Some(old), Some(new)) = (paths.next(), paths.next()) {
-                                pending_changes.push(FileChange::Rename { old, new });
-                            }
-                        }
-                        _ => {
-                            // RenameMode::Any or other — treat as modify
-                            for path in event.paths {
-                                pending_changes.push(FileChange::Modify(path));
-                            }
-                        }
-                    }
-                }
-                _ => {
-                    // Data, Metadata, Any, Other — all treated as modify.
-                    // Windows RDCW typically sends ModifyKind::Any.
-                    for path in event.paths {
-                        pending_changes.push(FileChange::Modify(path));
-                    }
-                }
+        FILE_ACTION_RENAMED_NEW_NAME => {
+            // Pair with the oldest pending old name (FIFO order)
+            if let Some(old) = pending_renames.pop_front() {
+                pending_changes.push(FileChange::Rename {
+                    old: old.old_path,
+                    new: path,
+                });
+            } else {
+                pending_changes.push(FileChange::Create(path));
             }
         }
         _ => {}
@@ -226,13 +533,14 @@ fn cleanup_expired_renames(
     }
 }
 
-fn is_under_scan_root(path: &Path, scan_root: &Path) -> bool {
-    path.starts_with(scan_root)
+fn is_under_root(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
 }
 
 fn apply_changes(
     app: &AppHandle,
     state: &AppState,
+    roots: &[PathBuf],
     changes: &mut Vec<FileChange>,
     last_status_emit: &mut Instant,
 ) {
@@ -240,30 +548,27 @@ fn apply_changes(
         return;
     }
 
-    let scan_root = &state.scan_root;
     let batch: Vec<FileChange> = changes.drain(..).collect();
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
 
     let mut to_upsert = Vec::new();
     let mut to_delete = Vec::new();
+    let mut case_moves = Vec::new();
 
     for change in batch {
         match change {
             FileChange::Create(path) | FileChange::Modify(path) => {
-                if !is_under_scan_root(&path, scan_root) {
+                if !is_under_root(&path, roots) {
                     continue;
                 }
                 let path_str = path.to_string_lossy().to_string();
                 if is_recently_touched(state, &path_str) {
                     continue;
                 }
-                if should_skip_path(
-                    &path,
-                    &state.path_ignores,
-                    &state.path_ignore_patterns,
-                ) {
+                if should_skip_path(&path, &ignored_roots, &ignored_patterns) {
                     continue;
                 }
-                match std::fs::symlink_metadata(&path) {
+                match long_path::symlink_metadata(&path) {
                     Ok(metadata) => {
                         if let Some(row) = index_row_from_path_and_metadata(&path, &metadata) {
                             to_upsert.push(row);
@@ -275,7 +580,7 @@ fn apply_changes(
                 }
             }
             FileChange::Delete(path) => {
-                if !is_under_scan_root(&path, scan_root) {
+                if !is_under_root(&path, roots) {
                     continue;
                 }
                 let path_str = path.to_string_lossy().to_string();
@@ -285,8 +590,8 @@ fn apply_changes(
                 to_delete.push(path_str);
             }
             FileChange::Rename { old, new } => {
-                let old_under = is_under_scan_root(&old, scan_root);
-                let new_under = is_under_scan_root(&new, scan_root);
+                let old_under = is_under_root(&old, roots);
+                let new_under = is_under_root(&new, roots);
                 if !old_under && !new_under {
                     continue;
                 }
@@ -294,19 +599,25 @@ fn apply_changes(
                 let old_str = old.to_string_lossy().to_string();
                 let new_str = new.to_string_lossy().to_string();
 
-                if old_under && !is_recently_touched(state, &old_str) {
+                // A case-only rename keeps the row (and those under it) and
+                // only respells it; the upsert below refreshes its metadata.
+                if old_under
+                    && new_under
+                    && case_rename::is_case_only_change(&old, &new)
+                    && !is_recently_touched(state, &old_str)
+                    && !is_recently_touched(state, &new_str)
+                    && !should_skip_path(&new, &ignored_roots, &ignored_patterns)
+                {
+                    case_moves.push((old_str, new_str.clone()));
+                } else if old_under && !is_recently_touched(state, &old_str) {
                     to_delete.push(old_str);
                 }
 
                 if new_under
                     && !is_recently_touched(state, &new_str)
-                    && !should_skip_path(
-                        &new,
-                        &state.path_ignores,
-                        &state.path_ignore_patterns,
-                    )
+                    && !should_skip_path(&new, &ignored_roots, &ignored_patterns)
                 {
-                    if let Ok(metadata) = std::fs::symlink_metadata(&new) {
+                    if let Ok(metadata) = long_path::symlink_metadata(&new) {
                         if let Some(row) = index_row_from_path_and_metadata(&new, &metadata) {
                             to_upsert.push(row);
                         }
@@ -316,13 +627,19 @@ fn apply_changes(
         }
     }
 
-    if to_upsert.is_empty() && to_delete.is_empty() {
+    if to_upsert.is_empty() && to_delete.is_empty() && case_moves.is_empty() {
         return;
     }
 
     let changed = match db_connection(&state.db_path) {
         Ok(mut conn) => {
             let mut total = 0;
+            for (old, new) in &case_moves {
+                match rename_case_only(&mut conn, old, new) {
+                    Ok((moved, removed)) => total += moved + removed,
+                    Err(e) => eprintln!("[win/rdcw] respelling {old} -> {new} failed: {e}"),
+                }
+            }
             if let Ok(n) = upsert_rows(&mut conn, &to_upsert) {
                 total += n;
             }
@@ -339,6 +656,13 @@ fn apply_changes(
 
     if changed > 0 {
         invalidate_search_caches(state);
+        notify_live_queries(Some(app), state, &to_upsert, &to_delete);
+        let respelled: Vec<String> = case_moves
+            .into_iter()
+            .flat_map(|(old, new)| [old, new])
+            .collect();
+        mem_resident::resync(state, &respelled);
+        mem_resident::apply_batch(state, &to_upsert, &to_delete);
         let _ = update_status_counts(state);
 
         if last_status_emit.elapsed() >= STATUS_EMIT_MIN_INTERVAL {
@@ -347,3 +671,51 @@ fn apply_changes(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(next: u32, action: FILE_ACTION, name: &str) -> Vec<u8> {
+        let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut buf = Vec::new();
+        buf.extend(next.to_le_bytes());
+        buf.extend(action.0.to_le_bytes());
+        buf.extend((name.len() as u32).to_le_bytes());
+        buf.extend(name);
+        buf
+    }
+
+    #[test]
+    fn parses_chained_notify_records() {
+        let root = Path::new(r"E:\photos");
+        let mut first = record(0, FILE_ACTION_RENAMED_OLD_NAME, r"2024\a.jpg");
+        first.resize(first.len().next_multiple_of(4), 0);
+        let next = first.len() as u32;
+        first[..4].copy_from_slice(&next.to_le_bytes());
+        let mut buf = first;
+        buf.extend(record(0, FILE_ACTION_RENAMED_NEW_NAME, r"2024\b.jpg"));
+
+        let records = parse_notify_buffer(root, &buf);
+        assert_eq!(
+            records,
+            vec![
+                (FILE_ACTION_RENAMED_OLD_NAME, root.join(r"2024\a.jpg")),
+                (FILE_ACTION_RENAMED_NEW_NAME, root.join(r"2024\b.jpg")),
+            ]
+        );
+
+        let mut changes = Vec::new();
+        let mut renames = VecDeque::new();
+        for (action, path) in records {
+            classify_action(action, path, &mut changes, &mut renames);
+        }
+        assert!(matches!(
+            changes.as_slice(),
+            [FileChange::Rename { old, new }] if old.ends_with("a.jpg") && new.ends_with("b.jpg")
+        ));
+
+        // A truncated record is dropped rather than read past the end.
+        assert!(parse_notify_buffer(root, &buf[..14]).is_empty());
+    }
+}
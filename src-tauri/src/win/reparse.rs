@@ -0,0 +1,69 @@
+//! Cycle protection for walks that follow junctions, directory symlinks and
+//! volume mount points. A junction back into an ancestor makes the walk
+//! infinite, and two links onto one target index it twice; recording the
+//! volume serial + file index of every directory entered catches both,
+//! whichever path reached it first.
+
+use std::collections::HashSet;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+
+use parking_lot::Mutex;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::Storage::FileSystem::{
+    CreateFileW, GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION,
+    FILE_FLAG_BACKUP_SEMANTICS, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE, FILE_SHARE_READ,
+    FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+
+/// (volume serial number, file index): unique per directory on a machine.
+type FileId = (u32, u64);
+
+/// Identity of the directory `path` resolves to, following any link.
+/// `None` when it can't be opened (gone, access denied).
+pub fn file_id(path: &Path) -> Option<FileId> {
+    let wide: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let handle = unsafe {
+        CreateFileW(
+            PCWSTR(wide.as_ptr()),
+            FILE_READ_ATTRIBUTES.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            None,
+            OPEN_EXISTING,
+            FILE_FLAG_BACKUP_SEMANTICS,
+            None,
+        )
+    }
+    .ok()?;
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    let result = unsafe { GetFileInformationByHandle(handle, &mut info) };
+    unsafe {
+        let _ = CloseHandle(handle);
+    }
+    result.ok()?;
+    let index = (u64::from(info.nFileIndexHigh) << 32) | u64::from(info.nFileIndexLow);
+    Some((info.dwVolumeSerialNumber, index))
+}
+
+/// Directories entered so far by one walk; shared by its worker threads.
+#[derive(Default)]
+pub struct VisitedDirs {
+    ids: Mutex<HashSet<FileId>>,
+}
+
+impl VisitedDirs {
+    /// True the first time the directory behind `path` is seen. A directory
+    /// whose identity can't be read counts as new: the walk then errors on
+    /// it like on any other unreadable directory.
+    pub fn first_visit(&self, path: &Path) -> bool {
+        match file_id(path) {
+            Some(id) => self.ids.lock().insert(id),
+            None => true,
+        }
+    }
+}
@@ -9,8 +9,9 @@ use walkdir::WalkDir;
 
 use std::time::Duration;
 
+use crate::long_path;
 use crate::{
-    db_connection, delete_paths, emit_index_state, index_row_from_path_and_metadata,
+    cached_effective_ignore_rules, db_connection, delete_paths, emit_index_state, index_row_from_path_and_metadata,
     invalidate_search_caches, perf_log, refresh_and_emit_status_counts,
     should_skip_path, upsert_rows, AppResult, AppState, BATCH_SIZE,
 };
@@ -145,17 +146,18 @@ fn apply_wsearch_results(
     let mut to_upsert = Vec::new();
     let mut to_delete = Vec::new();
 
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
     for path_str in &paths {
         let path = PathBuf::from(path_str);
 
         if !path.starts_with(&state.scan_root) {
             continue;
         }
-        if should_skip_path(&path, &state.path_ignores, &state.path_ignore_patterns) {
+        if should_skip_path(&path, &ignored_roots, &ignored_patterns) {
             continue;
         }
 
-        match std::fs::symlink_metadata(&path) {
+        match long_path::symlink_metadata(&path) {
             Ok(metadata) => {
                 if let Some(row) = index_row_from_path_and_metadata(&path, &metadata) {
                     to_upsert.push(row);
@@ -201,8 +203,7 @@ fn mtime_scan_catchup(
 ) -> AppResult<CatchupResult> {
     let t0 = Instant::now();
     let scan_root = &state.scan_root;
-    let ignores = state.path_ignores.clone();
-    let patterns = state.path_ignore_patterns.clone();
+    let (ignores, patterns) = cached_effective_ignore_rules(state);
     let mut conn = db_connection(&state.db_path)?;
 
     let mut total_upserted = 0;
@@ -212,15 +213,16 @@ fn mtime_scan_catchup(
     let mut last_emit = Instant::now();
     const EMIT_INTERVAL: Duration = Duration::from_millis(200);
 
-    // Walk directories only, pruning ignored subtrees via filter_entry
-    let walker = WalkDir::new(scan_root)
+    // Walk directories only, pruning ignored subtrees via filter_entry. The
+    // walk runs on extended-length paths so deep trees aren't cut off.
+    let walker = WalkDir::new(long_path::extended(scan_root))
         .follow_links(false)
         .into_iter()
         .filter_entry(|e| {
             if !e.file_type().is_dir() {
                 return false;
             }
-            !should_skip_path(e.path(), &ignores, &patterns)
+            !should_skip_path(&long_path::strip(e.path()), &ignores, &patterns)
         });
 
     for entry in walker {
@@ -229,7 +231,7 @@ fn mtime_scan_catchup(
             Err(_) => continue,
         };
 
-        let path = entry.path();
+        let path = long_path::strip(entry.path());
         dirs_scanned += 1;
 
         // Emit progress periodically so UI doesn't appear frozen.
@@ -264,13 +266,13 @@ fn mtime_scan_catchup(
 
         // Read current directory contents
         let mut disk_entries: HashMap<String, std::fs::Metadata> = HashMap::new();
-        if let Ok(read_dir) = std::fs::read_dir(path) {
+        if let Ok(read_dir) = std::fs::read_dir(entry.path()) {
             for child in read_dir.flatten() {
-                let child_path = child.path();
+                let child_path = long_path::strip(&child.path()).into_owned();
                 if should_skip_path(&child_path, &ignores, &patterns) {
                     continue;
                 }
-                if let Ok(meta) = std::fs::symlink_metadata(&child_path) {
+                if let Ok(meta) = long_path::symlink_metadata(&child_path) {
                     let p = child_path.to_string_lossy().to_string();
                     disk_entries.insert(p, meta);
                 }
@@ -0,0 +1,48 @@
+//! Opening and revealing through the shell API instead of `cmd /C start` and
+//! `explorer /select,`. Those take the path on a command line, where `&`, `%`,
+//! `^` and commas in a name are syntax, so such files failed to open or
+//! opened the wrong thing.
+
+use windows::core::PCWSTR;
+use windows::Win32::UI::Shell::{
+    ILCreateFromPathW, ILFree, SHOpenFolderAndSelectItems, ShellExecuteExW, SEE_MASK_NOASYNC,
+    SHELLEXECUTEINFOW,
+};
+use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+use super::com_guard::{to_wide, ComGuard};
+use crate::long_path;
+
+/// Open `path` with its default handler, like a double-click in Explorer.
+/// A file type with no handler gets the "Open with" chooser.
+pub fn open_path(path: &str) -> Result<(), String> {
+    let _com = ComGuard::init()?;
+    let file = to_wide(&long_path::shell_path(path));
+    let mut info = SHELLEXECUTEINFOW {
+        cbSize: std::mem::size_of::<SHELLEXECUTEINFOW>() as u32,
+        // The call returns before the handler has started otherwise, and
+        // the COM apartment above would be gone by the time it does.
+        fMask: SEE_MASK_NOASYNC,
+        lpFile: PCWSTR(file.as_ptr()),
+        nShow: SW_SHOWNORMAL.0,
+        ..Default::default()
+    };
+    unsafe { ShellExecuteExW(&mut info) }.map_err(|e| format!("Failed to open: {path} ({e})"))
+}
+
+/// Open the folder holding `path` in Explorer, with `path` selected.
+pub fn reveal_path(path: &str) -> Result<(), String> {
+    let _com = ComGuard::init()?;
+    let wide = to_wide(&long_path::shell_path(path));
+    unsafe {
+        let pidl = ILCreateFromPathW(PCWSTR(wide.as_ptr()));
+        if pidl.is_null() {
+            return Err(format!("Failed to reveal: {path}"));
+        }
+        // With no child items, the folder is the item's parent and the item
+        // itself is what gets selected.
+        let result = SHOpenFolderAndSelectItems(pidl, None, 0);
+        ILFree(Some(pidl));
+        result.map_err(|e| format!("Failed to reveal: {path} ({e})"))
+    }
+}
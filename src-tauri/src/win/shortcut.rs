@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use windows::core::{Interface, PCWSTR};
+use windows::Win32::System::Com::{CoCreateInstance, IPersistFile, CLSCTX_INPROC_SERVER};
+use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+
+use super::com_guard::{ComGuard, to_wide};
+
+/// Writes an Explorer shortcut (`.lnk`) at `lnk_path` pointing to `target`.
+/// Used instead of a symlink, which needs Developer Mode or admin rights.
+pub fn create_shortcut(target: &Path, lnk_path: &Path) -> Result<(), String> {
+    let _com = ComGuard::init()?;
+    let target_w = to_wide(&target.to_string_lossy());
+    let lnk_w = to_wide(&lnk_path.to_string_lossy());
+    let workdir_w = target.parent().map(|p| to_wide(&p.to_string_lossy()));
+
+    unsafe {
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)
+            .map_err(|e| format!("CoCreateInstance(ShellLink): {e}"))?;
+        link.SetPath(PCWSTR(target_w.as_ptr()))
+            .map_err(|e| format!("IShellLinkW::SetPath: {e}"))?;
+        if let Some(workdir_w) = &workdir_w {
+            let _ = link.SetWorkingDirectory(PCWSTR(workdir_w.as_ptr()));
+        }
+        let file: IPersistFile = link.cast().map_err(|e| format!("IPersistFile: {e}"))?;
+        file.Save(PCWSTR(lnk_w.as_ptr()), true)
+            .map_err(|e| format!("IPersistFile::Save: {e}"))?;
+    }
+    Ok(())
+}
@@ -1,19 +1,21 @@
 use std::collections::{HashMap, HashSet};
 use std::mem;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering as AtomicOrdering;
 use std::time::{Duration, Instant};
 
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 
-use super::volume;
+use super::path_resolver::mount_table;
+use super::{rdcw_watcher, volume};
+use crate::{case_rename, long_path, mem_resident};
 use crate::{
-    db_connection, delete_paths, invalidate_search_caches,
+    cached_effective_ignore_rules, db_connection, delete_paths, get_meta, invalidate_search_caches,
     index_row_from_path_and_metadata, is_recently_touched,
-    now_epoch, pathignore_active_entries, perf_log,
-    refresh_and_emit_status_counts, set_meta,
+    notify_live_queries, now_epoch, pathpriority, perf_log, refresh_and_emit_status_counts,
+    reload_pathignore, rename_case_only, rename_subtree, set_meta,
     should_skip_path, update_status_counts, upsert_rows,
-    AppState,
+    AppState, HOT_WATCH_DEBOUNCE,
 };
 
 use windows::Win32::Foundation::HANDLE;
@@ -27,18 +29,15 @@ const STATUS_EMIT_MIN_INTERVAL: Duration = Duration::from_secs(2);
 const POLL_IDLE: Duration = Duration::from_secs(2);
 const POLL_BUSY: Duration = Duration::from_millis(100);
 
-// Debounce for flushing accumulated USN changes to DB.
-// Higher than macOS FSEvent debounce (300ms) because USN records accumulate
-// many noisy system changes, and we want fewer DB round-trips.
-const USN_CHANGE_DEBOUNCE: Duration = Duration::from_secs(5);
-
 const FALLBACK_CACHE_CLEAR_INTERVAL: Duration = Duration::from_secs(120);
 
-// USN_REASON flags (only existence-related reasons; metadata changes are skipped)
+// USN_REASON flags (existence-related reasons, plus attribute changes so
+// files-on-demand hydration reaches the index; other metadata is skipped)
 const USN_REASON_FILE_CREATE: u32 = 0x00000100;
 const USN_REASON_FILE_DELETE: u32 = 0x00000200;
 const USN_REASON_RENAME_OLD_NAME: u32 = 0x00001000;
 const USN_REASON_RENAME_NEW_NAME: u32 = 0x00002000;
+const USN_REASON_BASIC_INFO_CHANGE: u32 = 0x00008000;
 
 /// READ_USN_JOURNAL_DATA_V0 structure
 #[repr(C)]
@@ -56,6 +55,7 @@ struct UsnChangeRecord {
     parent_frn: u64,
     usn: i64,
     reason: u32,
+    attributes: u32,
     name: String,
 }
 
@@ -76,109 +76,252 @@ const RENAME_PAIR_TIMEOUT: Duration = Duration::from_millis(500);
 /// Enables zero-syscall path resolution for USN records.
 type FrnPathCache = HashMap<u64, String>;
 
-/// Start the USN watcher, reading from the current journal position.
-/// `frn_cache`: pre-built FRN→path map from MFT scan (empty if unavailable).
-/// `outside_scan_frns`: directory FRNs known to be outside scan_root (pre-populated skip set).
-pub fn start(
-    app: AppHandle,
-    state: AppState,
+/// One watched NTFS volume: its journal handle, read position, and the
+/// per-volume FRN caches (FRNs are only unique within a volume).
+struct VolumeWatch {
+    letter: char,
+    vol: volume::VolumeHandle,
+    last_usn: i64,
+    journal_id: u64,
+    /// FRN → directory path cache from MFT scan (empty if unavailable).
     frn_cache: FrnPathCache,
-    outside_scan_frns: HashSet<u64>,
-) -> Result<(), String> {
-    let vol = volume::open_volume('C')?;
-    let journal = volume::query_usn_journal(&vol)?;
+    /// Positive fallback cache: FRN → resolved PathBuf (new dirs under a root).
+    /// Cleared periodically to handle moved/renamed directories.
+    dir_cache: HashMap<u64, PathBuf>,
+    /// Negative cache: FRNs confirmed outside every indexed root or
+    /// unresolvable. Never cleared — these won't move into an indexed root.
+    skip_frns: HashSet<u64>,
+    /// Indexed roots on this volume as (exact, prefix-with-separator) strings.
+    roots: Vec<(String, String)>,
+}
 
-    perf_log(format!(
-        "[win/usn] starting watcher, journal_id={} next_usn={} frn_cache={} skip_frns={}",
-        journal.journal_id, journal.next_usn, frn_cache.len(), outside_scan_frns.len()
-    ));
+impl VolumeWatch {
+    fn covers(&self, path: &std::path::Path) -> bool {
+        let s = path.to_string_lossy();
+        self.roots
+            .iter()
+            .any(|(exact, prefix)| *s == **exact || s.starts_with(prefix.as_str()))
+    }
+}
 
-    let last_usn = journal.next_usn;
-    let journal_id = journal.journal_id;
+/// `path` as the MFT scan spells it: on its volume's drive letter rather than
+/// through a mounted folder or `\\?\Volume{GUID}` path.
+fn canonical_path(path: &Path) -> PathBuf {
+    PathBuf::from(
+        mount_table()
+            .canonical(&path.to_string_lossy().replace('/', "\\"))
+            .into_owned(),
+    )
+}
 
-    spawn_poll_loop(app, state, vol, last_usn, journal_id, frn_cache, outside_scan_frns);
-    Ok(())
+/// Indexed roots (scan_root + .pathindexing extra roots) grouped by drive
+/// letter. Only volumes that hold at least one root get a journal reader.
+fn indexed_roots_by_volume(state: &AppState) -> HashMap<char, Vec<(String, String)>> {
+    let mut roots = vec![canonical_path(&state.scan_root)];
+    roots.extend(state.extra_roots.lock().iter().map(|root| canonical_path(root)));
+    let primary = volume::drive_letter_of(&roots[0]);
+    // Whole volumes indexed by the last MFT scan (e.g. "CD").
+    let mft_volumes = db_connection(&state.db_path)
+        .ok()
+        .and_then(|c| get_meta(&c, volume::MFT_VOLUMES_META_KEY))
+        .unwrap_or_default();
+    roots.extend(
+        mft_volumes
+            .chars()
+            .filter(|l| primary != Some(*l))
+            .map(|l| std::path::PathBuf::from(format!("{l}:\\"))),
+    );
+    let mut by_volume: HashMap<char, Vec<(String, String)>> = HashMap::new();
+    for root in roots {
+        let Some(letter) = volume::drive_letter_of(&root) else {
+            continue;
+        };
+        let exact = root.to_string_lossy().replace('/', "\\");
+        let exact = exact.trim_end_matches('\\').to_string();
+        let prefix = format!("{exact}\\");
+        by_volume.entry(letter).or_default().push((exact, prefix));
+    }
+    by_volume
 }
 
-/// Start USN watcher with replay from a previously saved position.
-/// Returns Err if the journal has been reset (different journal_id).
-pub fn start_with_resume(
+/// Drive letter of the volume holding scan_root, as the MFT scan spells it.
+/// Its USN position lives under the unsuffixed meta keys.
+pub fn primary_volume(state: &AppState) -> char {
+    volume::drive_letter_of(&canonical_path(&state.scan_root)).unwrap_or('C')
+}
+
+/// Open every fixed NTFS volume that holds an indexed root. `position` picks
+/// the starting (usn, journal_id) for a volume from its journal metadata, or
+/// returns Err to reject it. The system drive (scan_root's volume) is
+/// required; failures on secondary volumes are logged and skipped.
+fn open_watched_volumes(
+    state: &AppState,
+    mut position: impl FnMut(char, &volume::UsnJournalData) -> Result<i64, String>,
+) -> Result<Vec<VolumeWatch>, String> {
+    let primary = primary_volume(state);
+    let mut roots_by_volume = indexed_roots_by_volume(state);
+    let mut letters = volume::fixed_ntfs_volumes();
+    if !letters.contains(&primary) {
+        letters.insert(0, primary);
+    }
+
+    let mut watches = Vec::new();
+    for letter in letters {
+        let Some(roots) = roots_by_volume.remove(&letter) else {
+            continue;
+        };
+        let opened = volume::open_volume(letter).and_then(|vol| {
+            let journal = volume::query_usn_journal(&vol)?;
+            let last_usn = position(letter, &journal)?;
+            Ok(VolumeWatch {
+                letter,
+                vol,
+                last_usn,
+                journal_id: journal.journal_id,
+                frn_cache: HashMap::new(),
+                dir_cache: HashMap::new(),
+                skip_frns: HashSet::new(),
+                roots,
+            })
+        });
+        match opened {
+            Ok(watch) => watches.push(watch),
+            Err(e) if letter == primary => return Err(e),
+            Err(e) => eprintln!("[win/usn] skipping volume {letter}: {e}"),
+        }
+    }
+    if watches.is_empty() {
+        return Err("no NTFS volume holds an indexed root".to_string());
+    }
+    Ok(watches)
+}
+
+/// Start the USN watcher on every fixed NTFS volume holding an indexed root,
+/// reading from each journal's current position.
+/// `frn_cache`: pre-built FRN→path map from the system-drive MFT scan (empty if unavailable).
+/// `outside_scan_frns`: system-drive directory FRNs known to be outside scan_root.
+pub fn start(
     app: AppHandle,
     state: AppState,
-    stored_usn: i64,
-    stored_journal_id: u64,
+    mut caches: HashMap<char, (FrnPathCache, HashSet<u64>)>,
 ) -> Result<(), String> {
-    let vol = volume::open_volume('C')?;
-    let journal = volume::query_usn_journal(&vol)?;
-
-    if journal.journal_id != stored_journal_id {
-        return Err(format!(
-            "journal_id changed: stored={} current={}",
-            stored_journal_id, journal.journal_id
-        ));
+    let mut watches = open_watched_volumes(&state, |_, journal| Ok(journal.next_usn))?;
+    for watch in &mut watches {
+        if let Some((frn_cache, outside_scan_frns)) = caches.remove(&watch.letter) {
+            watch.frn_cache = frn_cache;
+            watch.skip_frns = outside_scan_frns;
+        }
     }
 
-    if stored_usn < journal.first_usn {
-        return Err(format!(
-            "stored USN {} < first_usn {}, journal wrapped",
-            stored_usn, journal.first_usn
+    for watch in &watches {
+        perf_log(format!(
+            "[win/usn] starting watcher on {}:, journal_id={} next_usn={} frn_cache={} skip_frns={}",
+            watch.letter, watch.journal_id, watch.last_usn, watch.frn_cache.len(), watch.skip_frns.len()
         ));
     }
 
-    perf_log(format!(
-        "[win/usn] resuming from stored_usn={} (current next_usn={})",
-        stored_usn, journal.next_usn
-    ));
-
-    spawn_poll_loop(app, state, vol, stored_usn, journal.journal_id, HashMap::new(), HashSet::new());
+    spawn_poll_loop(app, state, watches);
     Ok(())
 }
 
-fn spawn_poll_loop(
+/// Start USN watcher with replay from previously saved positions.
+/// `primary` is the volume `stored_usn`/`stored_journal_id` were read for.
+/// Returns Err if the primary volume's journal has been reset (different
+/// journal_id) or wrapped. Secondary volumes without a usable saved position
+/// start from their current journal position instead.
+pub fn start_with_resume(
     app: AppHandle,
     state: AppState,
-    vol: volume::VolumeHandle,
-    initial_usn: i64,
-    journal_id: u64,
-    frn_cache: FrnPathCache,
-    outside_scan_frns: HashSet<u64>,
-) {
-    std::thread::spawn(move || {
-        poll_loop(&app, &state, &vol, initial_usn, journal_id, frn_cache, outside_scan_frns);
+    primary: char,
+    stored_usn: i64,
+    stored_journal_id: u64,
+) -> Result<(), String> {
+    let conn = db_connection(&state.db_path).ok();
+    let watches = open_watched_volumes(&state, |letter, journal| {
+        let (usn, jid) = if letter == primary {
+            (Some(stored_usn), Some(stored_journal_id))
+        } else {
+            let (usn_key, jid_key) = volume::usn_meta_keys(letter, primary);
+            (
+                conn.as_ref().and_then(|c| get_meta(c, &usn_key)).and_then(|v| v.parse::<i64>().ok()),
+                conn.as_ref().and_then(|c| get_meta(c, &jid_key)).and_then(|v| v.parse::<u64>().ok()),
+            )
+        };
+        let resumable = match (usn, jid) {
+            (Some(_), Some(jid)) if jid != journal.journal_id => Err(format!(
+                "journal_id changed: stored={} current={}",
+                jid, journal.journal_id
+            )),
+            (Some(usn), Some(_)) if usn < journal.first_usn => Err(format!(
+                "stored USN {} < first_usn {}, journal wrapped",
+                usn, journal.first_usn
+            )),
+            (Some(usn), Some(_)) => Ok(usn),
+            _ => Err("no saved position".to_string()),
+        };
+        match resumable {
+            Ok(usn) => {
+                perf_log(format!(
+                    "[win/usn] {letter}: resuming from stored_usn={} (current next_usn={})",
+                    usn, journal.next_usn
+                ));
+                Ok(usn)
+            }
+            Err(e) if letter == primary => Err(e),
+            Err(e) => {
+                eprintln!("[win/usn] {letter}: cannot resume ({e}), starting at current position");
+                Ok(journal.next_usn)
+            }
+        }
+    })?;
+    drop(conn);
+
+    spawn_poll_loop(app, state, watches);
+    Ok(())
+}
+
+/// Indexed roots no journal reader covers: roots on FAT/exFAT sticks,
+/// network drives and UNC shares, or on an NTFS volume that failed to open.
+fn unwatched_roots(state: &AppState, watches: &[VolumeWatch]) -> Vec<PathBuf> {
+    let mut roots = vec![state.scan_root.clone()];
+    roots.extend(state.extra_roots.lock().iter().cloned());
+    roots.retain(|root| {
+        let root = canonical_path(root);
+        !watches.iter().any(|watch| watch.covers(&root))
     });
+    roots
 }
 
-fn poll_loop(
-    app: &AppHandle,
-    state: &AppState,
-    vol: &volume::VolumeHandle,
-    initial_usn: i64,
-    journal_id: u64,
-    mut frn_cache: FrnPathCache,
-    outside_scan_frns: HashSet<u64>,
-) {
-    let scan_root = state.scan_root.clone();
-    let scan_str = scan_root.to_string_lossy().to_string().replace('/', "\\");
-    let scan_prefix = format!("{}\\", scan_str);
+/// Run the journal readers, plus RDCW over whatever roots they don't cover;
+/// the companion watcher stops when the poll loop does.
+fn spawn_poll_loop(app: AppHandle, state: AppState, watches: Vec<VolumeWatch>) {
+    let unwatched = unwatched_roots(&state, &watches);
+    let companion = if unwatched.is_empty() {
+        None
+    } else {
+        rdcw_watcher::start_alongside(app.clone(), state.clone(), unwatched)
+            .map_err(|e| eprintln!("[win/usn] no RDCW for roots outside the journals: {e}"))
+            .ok()
+    };
+    std::thread::spawn(move || {
+        poll_loop(&app, &state, watches);
+        drop(companion);
+    });
+}
 
-    let mut last_usn = initial_usn;
+fn poll_loop(app: &AppHandle, state: &AppState, mut watches: Vec<VolumeWatch>) {
     let mut pending_changes: Vec<FileChange> = Vec::new();
-    let mut pending_renames: HashMap<u64, RenamePending> = HashMap::new();
+    // Keyed by (drive letter, FRN): FRNs repeat across volumes.
+    let mut pending_renames: HashMap<(char, u64), RenamePending> = HashMap::new();
     let mut last_flush = Instant::now();
     let mut last_usn_persist = Instant::now();
     let mut last_status_emit = Instant::now();
 
     // Persistent DB connection — avoids expensive per-flush Connection::open()
     let mut db_conn = db_connection(&state.db_path).ok();
+    let primary = primary_volume(state);
 
-    // Positive fallback cache: FRN → resolved PathBuf (new dirs under scan_root).
-    // Cleared periodically to handle moved/renamed directories.
-    let mut dir_cache: HashMap<u64, PathBuf> = HashMap::new();
     let mut last_cache_clear = Instant::now();
-    // Negative cache: FRNs confirmed outside scan_root or unresolvable.
-    // Pre-populated from MFT scan with known outside-scan directory FRNs.
-    // Never cleared — these are system dirs that won't move into scan_root.
-    let mut skip_frns = outside_scan_frns;
     let mut reusable_buffer: Vec<u8> = vec![0u8; 64 * 1024];
 
     // Diagnostic counters (logged periodically)
@@ -194,159 +337,191 @@ fn poll_loop(
     let mut diag_process_us: u64 = 0;
     let mut diag_apply_us: u64 = 0;
 
-    // Track config file changes to emit pathignore_changed when rules change
-    let mut last_config_entries = pathignore_active_entries(
-        &std::fs::read_to_string(&state.config_file_path).unwrap_or_default(),
-    );
+    // Set when a pending change falls under a .pathpriority hot directory;
+    // such batches flush after HOT_WATCH_DEBOUNCE instead of the configured
+    // watcher debounce.
+    let mut hot_pending = false;
 
     loop {
         if state.watcher_stop.load(AtomicOrdering::Acquire) {
             eprintln!("[win/usn] stop signal received, exiting");
             break;
         }
-
-        let t0 = Instant::now();
-        let records = match read_usn_journal(vol.raw(), last_usn, journal_id, &mut reusable_buffer) {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("[win/usn] read error: {e}");
-                std::thread::sleep(Duration::from_secs(1));
-                continue;
-            }
-        };
-        diag_read_us += t0.elapsed().as_micros() as u64;
+        let hot_dirs = state.hot_dirs.lock().clone();
+        // Re-read each poll so rules added at runtime (exclude_folder,
+        // add_ignore_rule) prune the watcher without a restart.
+        let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
 
         diag_polls += 1;
-        let t1 = Instant::now();
-
-        for record in &records {
-            last_usn = record.usn;
-            diag_total_records += 1;
-
-            // Resolve parent path: FRN cache (MFT) → skip_frns → dir_cache → syscall
-            let parent_path: Option<PathBuf> =
-                if let Some(path) = frn_cache.get(&record.parent_frn) {
-                    diag_frn_cache_hits += 1;
-                    Some(PathBuf::from(path))
-                } else if skip_frns.contains(&record.parent_frn) {
-                    // Known outside scan_root or unresolvable — skip without syscall
-                    diag_skip_hits += 1;
-                    None
-                } else if let Some(cached) = dir_cache.get(&record.parent_frn) {
-                    diag_frn_cache_hits += 1;
-                    Some(cached.clone())
-                } else {
-                    diag_syscalls += 1;
-                    let resolved =
-                        frn_to_path(vol.raw(), record.parent_frn).filter(|p| {
-                            let s = p.to_string_lossy();
-                            *s == *scan_str || s.starts_with(&scan_prefix)
-                        });
-                    match &resolved {
-                        Some(p) => { dir_cache.insert(record.parent_frn, p.clone()); }
-                        None => { skip_frns.insert(record.parent_frn); }
-                    }
-                    resolved
-                };
-            let full_path = match parent_path {
-                Some(ref _p) => { diag_home_matches += 1; _p.join(&record.name) }
-                None => continue,
+        for watch in watches.iter_mut() {
+            let t0 = Instant::now();
+            let records = match read_usn_journal(
+                watch.vol.raw(),
+                watch.last_usn,
+                watch.journal_id,
+                &mut reusable_buffer,
+            ) {
+                Ok(r) => r,
+                Err(e) => {
+                    eprintln!("[win/usn] {}: read error: {e}", watch.letter);
+                    continue;
+                }
             };
+            diag_read_us += t0.elapsed().as_micros() as u64;
+
+            let t1 = Instant::now();
+
+            for record in &records {
+                watch.last_usn = record.usn;
+                diag_total_records += 1;
+
+                // Resolve parent path: FRN cache (MFT) → skip_frns → dir_cache → syscall
+                let parent_path: Option<PathBuf> =
+                    if let Some(path) = watch.frn_cache.get(&record.parent_frn) {
+                        diag_frn_cache_hits += 1;
+                        Some(PathBuf::from(path))
+                    } else if watch.skip_frns.contains(&record.parent_frn) {
+                        // Known outside every indexed root or unresolvable — skip without syscall
+                        diag_skip_hits += 1;
+                        None
+                    } else if let Some(cached) = watch.dir_cache.get(&record.parent_frn) {
+                        diag_frn_cache_hits += 1;
+                        Some(cached.clone())
+                    } else {
+                        diag_syscalls += 1;
+                        let resolved = frn_to_path(watch.vol.raw(), record.parent_frn)
+                            .filter(|p| watch.covers(p));
+                        match &resolved {
+                            Some(p) => { watch.dir_cache.insert(record.parent_frn, p.clone()); }
+                            None => { watch.skip_frns.insert(record.parent_frn); }
+                        }
+                        resolved
+                    };
+                let full_path = match parent_path {
+                    Some(ref _p) => { diag_home_matches += 1; _p.join(&record.name) }
+                    None => continue,
+                };
 
-            // Detect config file changes before skip check (config is under ignored app_data_dir)
-            if full_path == state.config_file_path {
-                let new_entries = pathignore_active_entries(
-                    &std::fs::read_to_string(&state.config_file_path).unwrap_or_default(),
-                );
-                if new_entries != last_config_entries {
-                    last_config_entries = new_entries;
-                    app.emit("pathignore_changed", ()).ok();
+                // Detect config file changes before skip check (config is under ignored app_data_dir)
+                if full_path == state.config_file_path {
+                    reload_pathignore(Some(app), state);
+                    continue;
+                }
+                if full_path == state.pathpriority_file_path {
+                    *state.hot_dirs.lock() =
+                        pathpriority::load_hot_dirs(&state.pathpriority_file_path, &state.home_dir);
+                    continue;
                 }
-                continue;
-            }
 
-            // Early path filter: skip paths in ignored directories BEFORE
-            // creating FileChange events (avoids expensive stat + DB ops)
-            if should_skip_path(
-                &full_path,
-                &state.path_ignores,
-                &state.path_ignore_patterns,
-            ) {
-                continue;
-            }
+                // Early path filter: skip paths in ignored directories BEFORE
+                // creating FileChange events (avoids expensive stat + DB ops)
+                if should_skip_path(&full_path, &ignored_roots, &ignored_patterns) {
+                    continue;
+                }
+                hot_pending |= pathpriority::is_hot_path(&full_path, &hot_dirs);
 
-            let reason = record.reason;
+                let reason = record.reason;
+                let rename_key = (watch.letter, record.frn);
 
-            // Invalidate caches when a directory may have been renamed
-            if (reason & (USN_REASON_RENAME_OLD_NAME | USN_REASON_RENAME_NEW_NAME)) != 0 {
-                frn_cache.remove(&record.frn);
-                dir_cache.remove(&record.frn);
-                skip_frns.remove(&record.frn);
-            }
+                // Invalidate caches when a directory may have been renamed
+                if (reason & (USN_REASON_RENAME_OLD_NAME | USN_REASON_RENAME_NEW_NAME)) != 0 {
+                    watch.frn_cache.remove(&record.frn);
+                    watch.dir_cache.remove(&record.frn);
+                    watch.skip_frns.remove(&record.frn);
+                }
 
-            if (reason & USN_REASON_RENAME_OLD_NAME) != 0 {
-                // First half of rename pair
-                cleanup_expired_renames(&mut pending_renames, &mut pending_changes);
-                pending_renames.insert(
-                    record.frn,
-                    RenamePending {
-                        old_path: full_path,
-                        created_at: Instant::now(),
-                    },
-                );
-                continue;
-            }
 
-            if (reason & USN_REASON_RENAME_NEW_NAME) != 0 {
-                // Second half of rename pair
-                if let Some(old_rename) = pending_renames.remove(&record.frn) {
-                    pending_changes.push(FileChange::Rename {
-                        old: old_rename.old_path,
-                        new: full_path,
-                    });
-                } else {
-                    // No matching OLD_NAME — treat as create
+                if (reason & USN_REASON_RENAME_OLD_NAME) != 0 {
+                    // First half of rename pair
+                    cleanup_expired_renames(&mut pending_renames, &mut pending_changes);
+                    pending_renames.insert(
+                        rename_key,
+                        RenamePending {
+                            old_path: full_path,
+                            created_at: Instant::now(),
+                        },
+                    );
+                    continue;
+                }
+
+                if (reason & USN_REASON_RENAME_NEW_NAME) != 0 {
+                    // Second half of rename pair
+                    if let Some(old_rename) = pending_renames.remove(&rename_key) {
+                        pending_changes.push(FileChange::Rename {
+                            old: old_rename.old_path,
+                            new: full_path,
+                        });
+                    } else {
+                        // No matching OLD_NAME — treat as create
+                        pending_changes.push(FileChange::Create(full_path));
+                    }
+                    continue;
+                }
+
+                if (reason & USN_REASON_FILE_DELETE) != 0 {
+                    pending_changes.push(FileChange::Delete(full_path));
+                    continue;
+                }
+
+                if (reason & USN_REASON_FILE_CREATE) != 0 {
                     pending_changes.push(FileChange::Create(full_path));
+                    continue;
                 }
-                continue;
-            }
 
-            if (reason & USN_REASON_FILE_DELETE) != 0 {
-                pending_changes.push(FileChange::Delete(full_path));
-                continue;
+                // Hydrated or dehydrated in place shows up as an attribute
+                // change: re-stat the row when its cloud_only column no
+                // longer matches the record.
+                if (reason & USN_REASON_BASIC_INFO_CHANGE) != 0
+                    && db_conn.as_ref().is_some_and(|conn| {
+                        super::cloud::stored_state_differs(conn, &full_path, record.attributes)
+                    })
+                {
+                    pending_changes.push(FileChange::Create(full_path));
+                }
             }
 
-            if (reason & USN_REASON_FILE_CREATE) != 0 {
-                pending_changes.push(FileChange::Create(full_path));
-                continue;
-            }
+            diag_process_us += t1.elapsed().as_micros() as u64;
         }
 
-        diag_process_us += t1.elapsed().as_micros() as u64;
-
         // Expire any pending rename OLD_NAMEs that didn't get matched
         cleanup_expired_renames(&mut pending_renames, &mut pending_changes);
 
-        // Debounce: flush pending changes (5s debounce reduces DB round-trips)
-        if !pending_changes.is_empty() && last_flush.elapsed() >= USN_CHANGE_DEBOUNCE {
+        // Debounce: flush pending changes from all volumes together. Read on
+        // every pass so a saved `watchDebounceMs` applies to the next batch;
+        // hot directories flush sooner.
+        let debounce = if hot_pending {
+            HOT_WATCH_DEBOUNCE
+        } else {
+            state.index_tuning.read().watch_debounce()
+        };
+        if !pending_changes.is_empty() && last_flush.elapsed() >= debounce {
             let ta = Instant::now();
             apply_changes(app, state, &mut pending_changes, &mut last_status_emit, &mut db_conn);
             diag_apply_us += ta.elapsed().as_micros() as u64;
             last_flush = Instant::now();
+            hot_pending = false;
         }
+        state
+            .watcher_backlog
+            .store(pending_changes.len() + pending_renames.len(), AtomicOrdering::Relaxed);
 
         // Periodically clear positive dir_cache to handle moved/deleted directories.
         // skip_frns (negative cache) is never cleared — system dirs won't move into scan_root.
         if last_cache_clear.elapsed() >= FALLBACK_CACHE_CLEAR_INTERVAL {
-            dir_cache.clear();
+            for watch in watches.iter_mut() {
+                watch.dir_cache.clear();
+            }
             last_cache_clear = Instant::now();
         }
 
-        // Periodically persist USN position + last active timestamp
+        // Periodically persist per-volume USN positions + last active timestamp
         if last_usn_persist.elapsed() >= USN_FLUSH_INTERVAL {
             if let Some(ref conn) = db_conn {
-                let _ = set_meta(conn, "win_last_usn", &last_usn.to_string());
-                let _ = set_meta(conn, "win_journal_id", &journal_id.to_string());
+                for watch in &watches {
+                    let (usn_key, jid_key) = volume::usn_meta_keys(watch.letter, primary);
+                    let _ = set_meta(conn, &usn_key, &watch.last_usn.to_string());
+                    let _ = set_meta(conn, &jid_key, &watch.journal_id.to_string());
+                }
                 let _ = set_meta(conn, "win_last_active_ts", &now_epoch().to_string());
             }
             last_usn_persist = Instant::now();
@@ -355,8 +530,8 @@ fn poll_loop(
         // Diagnostic log every 30s
         if last_diag.elapsed() >= Duration::from_secs(30) {
             eprintln!(
-                "[win/usn/diag] polls={} records={} frn_hits={} skip_hits={} syscalls={} home={} pending={} read_ms={} proc_ms={} apply_ms={}",
-                diag_polls, diag_total_records, diag_frn_cache_hits,
+                "[win/usn/diag] volumes={} polls={} records={} frn_hits={} skip_hits={} syscalls={} home={} pending={} read_ms={} proc_ms={} apply_ms={}",
+                watches.len(), diag_polls, diag_total_records, diag_frn_cache_hits,
                 diag_skip_hits, diag_syscalls,
                 diag_home_matches, pending_changes.len(),
                 diag_read_us / 1000, diag_process_us / 1000, diag_apply_us / 1000
@@ -375,24 +550,24 @@ fn poll_loop(
 
         // Sleep-based polling: read accumulated records, process, then sleep.
         // Only use POLL_BUSY for outstanding rename pairs (which have a 500ms timeout).
-        // pending_changes don't need fast polling — they accumulate until the 5s debounce.
+        // pending_changes don't need fast polling — they accumulate until the debounce.
         let has_rename_pending = !pending_renames.is_empty();
         std::thread::sleep(if has_rename_pending { POLL_BUSY } else { POLL_IDLE });
     }
 }
 
 fn cleanup_expired_renames(
-    pending_renames: &mut HashMap<u64, RenamePending>,
+    pending_renames: &mut HashMap<(char, u64), RenamePending>,
     pending_changes: &mut Vec<FileChange>,
 ) {
-    let expired: Vec<u64> = pending_renames
+    let expired: Vec<(char, u64)> = pending_renames
         .iter()
         .filter(|(_, v)| v.created_at.elapsed() >= RENAME_PAIR_TIMEOUT)
         .map(|(k, _)| *k)
         .collect();
 
-    for frn in expired {
-        if let Some(old) = pending_renames.remove(&frn) {
+    for key in expired {
+        if let Some(old) = pending_renames.remove(&key) {
             // Treat as delete (old path disappeared, new path never appeared)
             pending_changes.push(FileChange::Delete(old.old_path));
         }
@@ -410,15 +585,38 @@ fn apply_changes(
         return;
     }
 
+    let (ignored_roots, ignored_patterns) = cached_effective_ignore_rules(state);
+
     // Deduplicate: keep only the last change per path.
     // This avoids redundant stat + DB ops for files changed multiple times.
+    // Renames also become Delete + Create, but the rows under a renamed
+    // folder are moved first (rename_subtree) rather than deleted with it.
+    // A case-only rename respells the existing rows and keeps just the Create.
     let mut deduped: HashMap<PathBuf, FileChange> = HashMap::new();
+    let mut moves: Vec<(String, String)> = Vec::new();
+    let mut case_moves: Vec<(String, String)> = Vec::new();
     for change in changes.drain(..) {
         match &change {
             FileChange::Create(p) | FileChange::Delete(p) => {
                 deduped.insert(p.clone(), change);
             }
             FileChange::Rename { old, new } => {
+                let (old_str, new_str) = (
+                    old.to_string_lossy().to_string(),
+                    new.to_string_lossy().to_string(),
+                );
+                let plain = should_skip_path(old, &ignored_roots, &ignored_patterns)
+                    || should_skip_path(new, &ignored_roots, &ignored_patterns)
+                    || is_recently_touched(state, &old_str)
+                    || is_recently_touched(state, &new_str);
+                if !plain && case_rename::is_case_only_change(old, new) {
+                    case_moves.push((old_str, new_str));
+                    deduped.insert(new.clone(), FileChange::Create(new.clone()));
+                    continue;
+                }
+                if !plain && new.is_dir() {
+                    moves.push((old_str, new_str));
+                }
                 deduped.insert(old.clone(), FileChange::Delete(old.clone()));
                 deduped.insert(new.clone(), FileChange::Create(new.clone()));
             }
@@ -435,14 +633,10 @@ fn apply_changes(
                 if is_recently_touched(state, &path_str) {
                     continue;
                 }
-                if should_skip_path(
-                    &path,
-                    &state.path_ignores,
-                    &state.path_ignore_patterns,
-                ) {
+                if should_skip_path(&path, &ignored_roots, &ignored_patterns) {
                     continue;
                 }
-                match std::fs::symlink_metadata(&path) {
+                match long_path::symlink_metadata(&path) {
                     Ok(metadata) => {
                         if let Some(row) = index_row_from_path_and_metadata(&path, &metadata) {
                             to_upsert.push(row);
@@ -466,7 +660,7 @@ fn apply_changes(
         }
     }
 
-    if to_upsert.is_empty() && to_delete.is_empty() {
+    if to_upsert.is_empty() && to_delete.is_empty() && moves.is_empty() && case_moves.is_empty() {
         return;
     }
 
@@ -478,6 +672,18 @@ fn apply_changes(
     let changed = match db_conn.as_mut() {
         Some(conn) => {
             let mut total = 0;
+            for (old, new) in &moves {
+                match rename_subtree(conn, old, new) {
+                    Ok((moved, removed)) => total += moved + removed,
+                    Err(e) => eprintln!("[win/usn] moving rows {old} -> {new} failed: {e}"),
+                }
+            }
+            for (old, new) in &case_moves {
+                match rename_case_only(conn, old, new) {
+                    Ok((moved, removed)) => total += moved + removed,
+                    Err(e) => eprintln!("[win/usn] respelling {old} -> {new} failed: {e}"),
+                }
+            }
             if let Ok(n) = upsert_rows(conn, &to_upsert) {
                 total += n;
             }
@@ -494,6 +700,14 @@ fn apply_changes(
 
     if changed > 0 {
         invalidate_search_caches(state);
+        notify_live_queries(Some(app), state, &to_upsert, &to_delete);
+        let moved: Vec<String> = moves
+            .into_iter()
+            .chain(case_moves)
+            .flat_map(|(old, new)| [old, new])
+            .collect();
+        mem_resident::resync(state, &moved);
+        mem_resident::apply_batch(state, &to_upsert, &to_delete);
         let _ = update_status_counts(state);
 
         if last_status_emit.elapsed() >= STATUS_EMIT_MIN_INTERVAL {
@@ -517,7 +731,8 @@ fn read_usn_journal(
         reason_mask: USN_REASON_FILE_CREATE
             | USN_REASON_FILE_DELETE
             | USN_REASON_RENAME_OLD_NAME
-            | USN_REASON_RENAME_NEW_NAME,
+            | USN_REASON_RENAME_NEW_NAME
+            | USN_REASON_BASIC_INFO_CHANGE,
         return_only_on_close: 0,
         timeout: 0,
         bytes_to_wait_for: 0,
@@ -584,6 +799,7 @@ fn parse_usn_change_record(data: &[u8]) -> Option<UsnChangeRecord> {
         u64::from_le_bytes(data[16..24].try_into().ok()?) & 0x0000_FFFF_FFFF_FFFF;
     let usn = i64::from_le_bytes(data[24..32].try_into().ok()?);
     let reason = u32::from_le_bytes(data[40..44].try_into().ok()?);
+    let attributes = u32::from_le_bytes(data[52..56].try_into().ok()?);
 
     let name_len = u16::from_le_bytes(data[56..58].try_into().ok()?) as usize;
     let name_offset = u16::from_le_bytes(data[58..60].try_into().ok()?) as usize;
@@ -608,6 +824,7 @@ fn parse_usn_change_record(data: &[u8]) -> Option<UsnChangeRecord> {
         parent_frn,
         usn,
         reason,
+        attributes,
         name,
     })
 }
@@ -655,11 +872,8 @@ fn frn_to_path(volume_handle: HANDLE, frn: u64) -> Option<PathBuf> {
         return None;
     }
 
-    let path_str = String::from_utf16_lossy(&buf[..len as usize]);
-    // GetFinalPathNameByHandleW returns "\\?\C:\..." prefix; strip it
-    let cleaned = path_str
-        .strip_prefix("\\\\?\\")
-        .unwrap_or(&path_str);
-
-    Some(PathBuf::from(cleaned))
+    let path = PathBuf::from(String::from_utf16_lossy(&buf[..len as usize]));
+    // GetFinalPathNameByHandleW returns "\\?\C:\..." prefix; strip it, and
+    // respell a path it gave through a mounted folder on the drive letter
+    Some(canonical_path(&long_path::strip(&path)))
 }
@@ -7,6 +7,8 @@ use windows::Win32::System::IO::DeviceIoControl;
 use windows::Win32::System::Ioctl::FSCTL_QUERY_USN_JOURNAL;
 use windows::core::PCWSTR;
 
+use crate::scan_tuning::StorageKind;
+
 #[derive(Debug)]
 pub struct VolumeHandle {
     handle: HANDLE,
@@ -112,3 +114,163 @@ pub fn query_usn_journal(vol: &VolumeHandle) -> Result<UsnJournalData, String> {
         max_usn,
     })
 }
+
+/// `GetDriveTypeW` result for fixed disks (WindowsProgramming::DRIVE_FIXED,
+/// whose feature this crate does not enable).
+const DRIVE_FIXED: u32 = 3;
+
+/// Drive letters of all fixed (non-removable, non-network) NTFS volumes.
+/// Only NTFS exposes an MFT and a USN journal, so FAT/exFAT/ReFS data drives
+/// are left to the directory-walk and RDCW fallbacks.
+pub fn fixed_ntfs_volumes() -> Vec<char> {
+    use windows::Win32::Storage::FileSystem::{
+        GetDriveTypeW, GetLogicalDrives, GetVolumeInformationW,
+    };
+
+    let mask = unsafe { GetLogicalDrives() };
+    let mut letters = Vec::new();
+    for bit in 0..26u32 {
+        if mask & (1 << bit) == 0 {
+            continue;
+        }
+        let letter = (b'A' + bit as u8) as char;
+        let root: Vec<u16> = format!("{letter}:\\")
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        if unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) } != DRIVE_FIXED {
+            continue;
+        }
+        let mut fs_name = [0u16; 32];
+        let ok = unsafe {
+            GetVolumeInformationW(
+                PCWSTR(root.as_ptr()),
+                None,
+                None,
+                None,
+                None,
+                Some(&mut fs_name),
+            )
+        };
+        if ok.is_err() {
+            continue;
+        }
+        let len = fs_name.iter().position(|&c| c == 0).unwrap_or(fs_name.len());
+        if String::from_utf16_lossy(&fs_name[..len]).eq_ignore_ascii_case("NTFS") {
+            letters.push(letter);
+        }
+    }
+    letters
+}
+
+/// Upper-case drive letter of an absolute `X:\...` path.
+pub fn drive_letter_of(path: &std::path::Path) -> Option<char> {
+    let s = path.to_string_lossy();
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), Some(':')) if letter.is_ascii_alphabetic() => {
+            Some(letter.to_ascii_uppercase())
+        }
+        _ => None,
+    }
+}
+
+/// Whether `root` is a whole drive (`X:\` or `X:`) rather than a folder on it.
+pub fn is_whole_volume_root(root: &str) -> bool {
+    let trimmed = root.trim_end_matches(['\\', '/']);
+    trimmed.len() == 2 && drive_letter_of(std::path::Path::new(trimmed)).is_some()
+}
+
+/// Meta key listing the drive letters whose whole MFT was indexed by the
+/// last full scan, e.g. "CDE".
+pub const MFT_VOLUMES_META_KEY: &str = "win_mft_volumes";
+
+/// Meta keys holding the saved USN position and journal id for a volume.
+/// The primary volume (the one holding scan_root) keeps the original
+/// unsuffixed keys so existing DBs resume without a rescan.
+pub fn usn_meta_keys(letter: char, primary: char) -> (String, String) {
+    if letter.eq_ignore_ascii_case(&primary) {
+        ("win_last_usn".to_string(), "win_journal_id".to_string())
+    } else {
+        let letter = letter.to_ascii_uppercase();
+        (format!("win_last_usn_{letter}"), format!("win_journal_id_{letter}"))
+    }
+}
+
+/// `GetDriveTypeW` result for network shares (WindowsProgramming::DRIVE_REMOTE).
+const DRIVE_REMOTE: u32 = 4;
+
+/// Storage class of the drive holding `path`. Rotational disks report a seek
+/// penalty through `IOCTL_STORAGE_QUERY_PROPERTY`; the query needs no access
+/// rights on the volume handle, so it works without admin.
+pub fn storage_kind(path: &std::path::Path) -> StorageKind {
+    use windows::Win32::Storage::FileSystem::GetDriveTypeW;
+    use windows::Win32::System::Ioctl::{
+        PropertyStandardQuery, StorageDeviceSeekPenaltyProperty,
+        DEVICE_SEEK_PENALTY_DESCRIPTOR, IOCTL_STORAGE_QUERY_PROPERTY,
+        STORAGE_PROPERTY_QUERY,
+    };
+
+    let Some(letter) = drive_letter_of(path) else {
+        // UNC paths (\\server\share) are always remote.
+        return if path.to_string_lossy().starts_with("\\\\") {
+            StorageKind::Network
+        } else {
+            StorageKind::Unknown
+        };
+    };
+    let root: Vec<u16> = format!("{letter}:\\")
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    if unsafe { GetDriveTypeW(PCWSTR(root.as_ptr())) } == DRIVE_REMOTE {
+        return StorageKind::Network;
+    }
+
+    let device: Vec<u16> = format!("\\\\.\\{letter}:")
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+    let Ok(handle) = (unsafe {
+        CreateFileW(
+            PCWSTR(device.as_ptr()),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE,
+            None,
+            OPEN_EXISTING,
+            Default::default(),
+            None,
+        )
+    }) else {
+        return StorageKind::Unknown;
+    };
+    let vol = VolumeHandle { handle };
+
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: StorageDeviceSeekPenaltyProperty,
+        QueryType: PropertyStandardQuery,
+        AdditionalParameters: [0],
+    };
+    let mut desc = DEVICE_SEEK_PENALTY_DESCRIPTOR::default();
+    let mut bytes_returned: u32 = 0;
+    let ok = unsafe {
+        DeviceIoControl(
+            vol.raw(),
+            IOCTL_STORAGE_QUERY_PROPERTY,
+            Some(&query as *const _ as *const std::ffi::c_void),
+            std::mem::size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+            Some(&mut desc as *mut _ as *mut std::ffi::c_void),
+            std::mem::size_of::<DEVICE_SEEK_PENALTY_DESCRIPTOR>() as u32,
+            Some(&mut bytes_returned),
+            None,
+        )
+    };
+    if ok.is_err() {
+        return StorageKind::Unknown;
+    }
+    if desc.IncursSeekPenalty.as_bool() {
+        StorageKind::Hdd
+    } else {
+        StorageKind::Ssd
+    }
+}
@@ -0,0 +1,208 @@
+//! Name search against the Windows Search indexer (`SystemIndex`, through the
+//! Search.CollatorDSO OLE DB provider), used while a first full index is
+//! still being built so results show up immediately, much like the macOS
+//! Spotlight fallback. The SQL is built here and handed to PowerShell's
+//! ADODB through an environment variable, so no query text is ever spliced
+//! into the script.
+
+use std::io::BufRead;
+use std::os::windows::process::CommandExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::long_path;
+use crate::EntryDto;
+
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+const WSEARCH_TIMEOUT: Duration = Duration::from_secs(3);
+const WSEARCH_MAX_RESULTS: usize = 300;
+const SQL_ENV_VAR: &str = "EVERYTHING_WSEARCH_SQL";
+
+const QUERY_SCRIPT: &str = r#"[Console]::OutputEncoding = [Text.Encoding]::UTF8; $conn = New-Object -ComObject ADODB.Connection; $conn.Open('Provider=Search.CollatorDSO;Extended Properties="Application=Windows"'); $rs = $conn.Execute($env:EVERYTHING_WSEARCH_SQL); while (-not $rs.EOF) { $rs.Fields.Item('System.ItemPathDisplay').Value; $rs.MoveNext() }; $rs.Close(); $conn.Close()"#;
+
+pub struct WSearchResult {
+    pub entries: Vec<EntryDto>,
+    pub timed_out: bool,
+}
+
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// A LIKE pattern for one search term: `*`/`?` keep their glob meaning,
+/// LIKE's own wildcards match literally.
+fn like_pattern(term: &str) -> String {
+    let mut out = String::new();
+    for ch in term.chars() {
+        match ch {
+            '*' => out.push('%'),
+            '?' => out.push('_'),
+            '%' | '_' | '[' => {
+                out.push('[');
+                out.push(ch);
+                out.push(']');
+            }
+            _ => out.push(ch),
+        }
+    }
+    if term.contains(['*', '?']) {
+        out
+    } else {
+        format!("%{out}%")
+    }
+}
+
+/// SystemIndex SQL for `query` under `scope`, or `None` for queries the
+/// indexer can't answer by name (paths, operators other than `ext:`).
+pub fn build_sql(scope: &Path, query: &str) -> Option<String> {
+    let mut conditions = Vec::new();
+    for term in query.split_whitespace() {
+        if let Some(exts) = term.strip_prefix("ext:") {
+            let exts: Vec<String> = exts
+                .split([',', ';'])
+                .filter(|e| !e.is_empty())
+                .map(|e| sql_string(&format!(".{}", e.trim_start_matches('.'))))
+                .collect();
+            if exts.is_empty() {
+                return None;
+            }
+            conditions.push(format!("System.FileExtension IN ({})", exts.join(", ")));
+        } else if term.contains([':', '/', '\\']) {
+            return None;
+        } else {
+            conditions.push(format!(
+                "System.FileName LIKE {}",
+                sql_string(&like_pattern(term))
+            ));
+        }
+    }
+    if conditions.is_empty() {
+        return None;
+    }
+    let scope = format!("file:{}", scope.to_string_lossy().replace('\\', "/"));
+    Some(format!(
+        "SELECT TOP {WSEARCH_MAX_RESULTS} System.ItemPathDisplay FROM SystemIndex \
+         WHERE SCOPE = {} AND {}",
+        sql_string(&scope),
+        conditions.join(" AND ")
+    ))
+}
+
+pub fn search_windows_index(scope: &Path, query: &str) -> WSearchResult {
+    let empty = WSearchResult {
+        entries: Vec::new(),
+        timed_out: false,
+    };
+    let trimmed = query.trim();
+    if trimmed.chars().count() < 2 {
+        return empty;
+    }
+    let Some(sql) = build_sql(scope, trimmed) else {
+        return empty;
+    };
+
+    let mut child = match Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", QUERY_SCRIPT])
+        .env(SQL_ENV_VAR, &sql)
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(_) => return empty,
+    };
+    let Some(stdout) = child.stdout.take() else {
+        return empty;
+    };
+
+    let reader = std::io::BufReader::new(stdout);
+    let mut entries = Vec::with_capacity(WSEARCH_MAX_RESULTS);
+    let started = Instant::now();
+    let mut timed_out = false;
+
+    for line in reader.lines() {
+        if started.elapsed() >= WSEARCH_TIMEOUT {
+            timed_out = true;
+            break;
+        }
+        let Ok(line) = line else { continue };
+        let path_str = line.trim().to_string();
+        let path = Path::new(&path_str);
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy().to_string(),
+            None => continue,
+        };
+        let dir = path
+            .parent()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let meta = long_path::symlink_metadata(path).ok();
+        let is_dir = meta.as_ref().is_some_and(|m| m.is_dir());
+        let ext = if is_dir {
+            None
+        } else {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+        };
+        let size = meta
+            .as_ref()
+            .filter(|m| m.is_file())
+            .map(|m| m.len() as i64);
+        let cloud_only = meta
+            .as_ref()
+            .is_some_and(crate::is_cloud_only_metadata)
+            .then_some(true);
+        let mtime = meta
+            .and_then(|m| m.modified().ok())
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64);
+
+        entries.push(EntryDto {
+            path: path_str,
+            name,
+            dir,
+            is_dir,
+            ext,
+            size,
+            mtime,
+            spoken_label: None,
+            mtime_display: None,
+            cloud_only,
+            linked_from: None,
+        });
+        if entries.len() >= WSEARCH_MAX_RESULTS {
+            break;
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+
+    WSearchResult { entries, timed_out }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_name_and_extension_conditions() {
+        let scope = Path::new("C:\\Users\\o'neil");
+        assert_eq!(
+            build_sql(scope, "report ext:pdf,docx").as_deref(),
+            Some(
+                "SELECT TOP 300 System.ItemPathDisplay FROM SystemIndex \
+                 WHERE SCOPE = 'file:C:/Users/o''neil' AND System.FileName LIKE '%report%' \
+                 AND System.FileExtension IN ('.pdf', '.docx')"
+            )
+        );
+        assert_eq!(like_pattern("*.rs"), "%.rs");
+        assert_eq!(like_pattern("100%_done"), "%100[%][_]done%");
+        assert_eq!(build_sql(scope, "path:src"), None);
+        assert_eq!(build_sql(scope, "src\\main"), None);
+    }
+}